@@ -7,8 +7,21 @@
 ---
 --- Supported kinds: note, tip, important, warning, error, question, answer,
 --- example, exercise, and solution.
-
-local is_latex = FORMAT:match('latex') or FORMAT:match('beamer')
+---
+--- Beamer slides use the theme's native `block`, `alertblock`, and
+--- `exampleblock` environments instead of the tcolorbox-based omni-blocks.
+--- GitHub Markdown uses GitHub alerts where a matching alert exists; plain
+--- text and man pages degrade to an indented block with a bold title.
+--- DocBook uses its native admonition elements (`error` becomes `caution`,
+--- kinds without a counterpart become titled notes) and JATS a
+--- `<boxed-text>` whose `content-type` names the kind.
+
+local is_beamer = FORMAT:match('beamer')
+local is_latex = FORMAT:match('latex') or is_beamer
+local is_gfm = FORMAT == 'gfm' or FORMAT == 'commonmark_x'
+local is_text = FORMAT == 'plain' or FORMAT == 'man'
+local is_docbook = FORMAT:match('^docbook') ~= nil
+local is_jats = FORMAT:match('^jats') ~= nil
 local utils = pandoc.utils
 local language = 'en'
 local title_format = 'markdown+tex_math_dollars-raw_attribute-raw_html-raw_tex'
@@ -36,6 +49,21 @@ local marks = {
   question = '?', answer = 'A', example = 'E', exercise = 'X', solution = 'S',
 }
 
+local beamer_environments = {
+  important = 'alertblock', warning = 'alertblock', error = 'alertblock',
+  example = 'exampleblock', exercise = 'exampleblock', solution = 'exampleblock',
+}
+
+local gfm_alerts = {
+  note = 'NOTE', tip = 'TIP', important = 'IMPORTANT', warning = 'WARNING',
+  error = 'CAUTION',
+}
+
+local docbook_admonitions = {
+  note = 'note', tip = 'tip', important = 'important', warning = 'warning',
+  error = 'caution',
+}
+
 local supported_kind = {}
 for _, kind in ipairs(kinds) do
   supported_kind[kind] = true
@@ -85,11 +113,19 @@ local function parse_title(value)
   return block.content
 end
 
+local function latex_environment(kind)
+  if is_beamer then
+    return beamer_environments[kind] or 'block'
+  end
+  return 'OmniAdmonition'
+end
+
 local function latex_title_block(kind, title)
+  local opening = is_beamer
+    and string.format('\\begin{%s}{', latex_environment(kind))
+    or string.format('\\begin{OmniAdmonition}{%s}{', kind)
   local inlines = {
-    pandoc.RawInline('latex', string.format(
-      '\\begin{OmniAdmonition}{%s}{', kind
-    )),
+    pandoc.RawInline('latex', opening),
   }
   for _, inline in ipairs(title) do
     table.insert(inlines, inline)
@@ -115,7 +151,7 @@ end
 function Meta(meta)
   local lang = meta.lang and utils.stringify(meta.lang):lower() or ''
   language = lang:match('^zh') and 'zh' or 'en'
-  if is_latex then
+  if is_latex and not is_beamer then
     append_latex_package(meta)
   end
   return meta
@@ -127,10 +163,50 @@ function Div(el)
     return nil
   end
 
-  local title_source = el.attributes.title or titles[language][kind]
+  local explicit_title = el.attributes.title
+  local title_source = explicit_title or titles[language][kind]
   local title = parse_title(title_source)
   el.attributes.title = nil
 
+  if is_gfm or is_text then
+    local blocks = {}
+    local alert = is_gfm and gfm_alerts[kind]
+    if alert then
+      table.insert(blocks, pandoc.Para({
+        pandoc.RawInline('markdown', '[!' .. alert .. ']')
+      }))
+    end
+    -- An alert already names its kind; repeat only a custom title.
+    if not alert or explicit_title then
+      table.insert(blocks, pandoc.Para({pandoc.Strong(title)}))
+    end
+    for _, block in ipairs(el.content) do
+      table.insert(blocks, block)
+    end
+    return pandoc.BlockQuote(blocks)
+  end
+
+  if is_docbook then
+    -- The writer turns a leading `.title` div into the admonition <title>.
+    local blocks = {pandoc.Div({pandoc.Plain(title)}, pandoc.Attr('', {'title'}))}
+    for _, block in ipairs(el.content) do
+      table.insert(blocks, block)
+    end
+    return pandoc.Div(blocks, pandoc.Attr(
+      el.identifier, {docbook_admonitions[kind] or 'note'}
+    ))
+  end
+
+  if is_jats then
+    local blocks = {pandoc.Para({pandoc.Strong(title)})}
+    for _, block in ipairs(el.content) do
+      table.insert(blocks, block)
+    end
+    return pandoc.Div(blocks, pandoc.Attr(
+      el.identifier, {}, {['content-type'] = kind}
+    ))
+  end
+
   if is_latex then
     local blocks = {
       latex_title_block(kind, title),
@@ -138,7 +214,9 @@ function Div(el)
     for _, block in ipairs(el.content) do
       table.insert(blocks, block)
     end
-    table.insert(blocks, pandoc.RawBlock('latex', '\\end{OmniAdmonition}'))
+    table.insert(blocks, pandoc.RawBlock(
+      'latex', string.format('\\end{%s}', latex_environment(kind))
+    ))
     return blocks
   end
 
@@ -0,0 +1,99 @@
+--- Resolve audience-conditional content for `omnidoc build --variant`.
+---
+--- OmniDoc passes the selected variant as `omnidoc-variant` and its audiences
+--- as a comma-separated `omnidoc-audiences` metadata value. Divs and Spans
+--- marked with `only-<audience>` classes or a `when="a, !b"` attribute are
+--- kept only when the condition matches the active audiences; kept elements
+--- lose the markers and unwrap when nothing else is left on them. Without the
+--- variant key the filter leaves the document untouched.
+
+local utils = pandoc.utils
+local variant_key = 'omnidoc-variant'
+local audiences_key = 'omnidoc-audiences'
+local only_prefix = '^only%-(.+)$'
+
+local active = nil
+
+local function Meta(meta)
+  if meta[variant_key] == nil then
+    return nil
+  end
+
+  active = {}
+  local audiences = meta[audiences_key]
+  if audiences ~= nil then
+    for audience in utils.stringify(audiences):gmatch('[^,%s]+') do
+      active[audience] = true
+    end
+  end
+
+  meta[variant_key] = nil
+  meta[audiences_key] = nil
+  return meta
+end
+
+local function matches_only(classes)
+  local conditional = false
+  for _, class in ipairs(classes) do
+    local audience = class:match(only_prefix)
+    if audience then
+      conditional = true
+      if active[audience] then
+        return true, true
+      end
+    end
+  end
+  return conditional, not conditional
+end
+
+local function matches_when(when)
+  local wanted = false
+  local matched = false
+  for token in when:gmatch('[^,%s]+') do
+    local negated, audience = token:match('^(!?)(.+)$')
+    if negated == '!' then
+      if active[audience] then
+        return false
+      end
+    else
+      wanted = true
+      matched = matched or active[audience] == true
+    end
+  end
+  return matched or not wanted
+end
+
+local function resolve(el)
+  local when = el.attributes.when
+  local conditional, visible = matches_only(el.classes)
+  if not conditional and when == nil then
+    return nil
+  end
+  if when ~= nil then
+    visible = visible and matches_when(when)
+  end
+  if not visible then
+    return {}
+  end
+
+  el.classes = el.classes:filter(function(class)
+    return not class:match(only_prefix)
+  end)
+  el.attributes.when = nil
+  if el.identifier == '' and #el.classes == 0 and #el.attributes == 0 then
+    return el.content
+  end
+  return el
+end
+
+local function filter_block(el)
+  if active == nil then
+    return nil
+  end
+  return resolve(el)
+end
+
+return {
+  {Meta = Meta},
+  {Div = filter_block, Span = filter_block},
+}
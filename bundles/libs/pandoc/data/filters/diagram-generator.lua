@@ -50,6 +50,9 @@ Metadata options:
   - pdflatex_path / pdflatexPath: Path to pdflatex executable
   - asymptote_path / asymptotePath: Path to Asymptote executable
   - omnidoc_path / omnidocPath: Path to the OmniDoc executable
+  - omnidoc-render-cache: Directory of rendered PlantUML, GraphViz and
+    bitfield images keyed by their source, format and omnidoc-render-salt
+  - omnidoc-render-stats: File listing which diagrams hit that cache
 
 Copyright: © 2018-2021 John MacFarlane <jgm@berkeley.edu>,
            2018 Florian Schätzig <florian@schaetzig.de>,
@@ -68,6 +71,18 @@ local path = require 'pandoc.path'
 local depfile_path = nil
 local diagram_dependencies = {}
 
+-- Renderers whose image depends on nothing but the block text. Python, TikZ
+-- and Asymptote sources can read arbitrary files, so they always rerun.
+local cacheable_renderers = {
+  plantuml = true,
+  graphviz = true,
+  bitfield = true,
+}
+local render_cache_dir = nil
+local render_salt = ''
+local render_stats_path = nil
+local render_events = {}
+
 local function record_dependency(file_path)
   if not file_path or file_path == '' or file_path:find('[\r\n]') then
     return
@@ -79,7 +94,23 @@ local function record_dependency(file_path)
   diagram_dependencies[path.normalize(resolved)] = true
 end
 
+local function write_render_stats()
+  if not render_stats_path or render_stats_path == '' then
+    return
+  end
+  local file = io.open(render_stats_path, 'w')
+  if not file then
+    return
+  end
+  file:write('# omnidoc-renders-v1\n')
+  for _, event in ipairs(render_events) do
+    file:write(event, '\n')
+  end
+  file:close()
+end
+
 local function write_depfile(doc)
+  write_render_stats()
   if not depfile_path or depfile_path == '' then
     return doc
   end
@@ -159,12 +190,12 @@ local bitfield_mimetype = "image/svg+xml"
 if FORMAT == "docx" or FORMAT == "pptx" or FORMAT == "rtf" then
   filetype = "png"
   mimetype = "image/png"
-elseif FORMAT == "pdf" or FORMAT == "latex" then
+elseif FORMAT == "pdf" or FORMAT == "latex" or FORMAT == "beamer" then
   filetype = "pdf"
   mimetype = "application/pdf"
 end
 
-if FORMAT == "pdf" or FORMAT == "latex" then
+if FORMAT == "pdf" or FORMAT == "latex" or FORMAT == "beamer" then
   bitfield_filetype = "pdf"
   bitfield_mimetype = "application/pdf"
 end
@@ -184,6 +215,13 @@ function Meta(meta)
   local generic_depfile = meta['omnidoc-depfile-diagram-generator']
   depfile_path = generic_depfile and stringify(generic_depfile) or nil
   diagram_dependencies = {}
+  local render_cache = meta['omnidoc-render-cache']
+  local salt = meta['omnidoc-render-salt']
+  local stats = meta['omnidoc-render-stats']
+  render_cache_dir = render_cache and stringify(render_cache) or nil
+  render_salt = salt and stringify(salt) or ''
+  render_stats_path = stats and stringify(stats) or nil
+  render_events = {}
   -- Update tool paths from metadata if provided
   plantuml_path = stringify(
     meta.plantuml_path or meta.plantumlPath or plantuml_path
@@ -669,6 +707,48 @@ local function generate_image(block, converter, output_type)
   return img
 end
 
+--- Path of the cached rendering of a block, or nil when it is not cacheable
+---
+--- @param block table The CodeBlock element
+--- @param output_type string Image file extension
+--- @return string|nil Cache file path
+local function render_cache_path(block, output_type)
+  local renderer = block.classes[1]
+  if not render_cache_dir or render_cache_dir == '' or
+     not cacheable_renderers[renderer] then
+    return nil
+  end
+  local key = pandoc.sha1(table.concat({
+    tostring(PANDOC_VERSION), render_salt, renderer, output_type, block.text
+  }, '\0'))
+  return path.join({render_cache_dir, key .. '.' .. output_type})
+end
+
+local function read_cached_render(cache_path)
+  local file = io.open(cache_path, 'rb')
+  if not file then
+    return nil
+  end
+  local data = file:read('*all')
+  file:close()
+  return data ~= '' and data or nil
+end
+
+local function write_cached_render(cache_path, img_data)
+  -- Rename into place so concurrent builds never read a partial image.
+  local staging = cache_path .. '.' .. tostring(os.time()) ..
+    tostring(math.random(1, 1000000)) .. '.tmp'
+  local file = io.open(staging, 'wb')
+  if not file then
+    return
+  end
+  file:write(img_data)
+  file:close()
+  if not os.rename(staging, cache_path) then
+    os.remove(staging)
+  end
+end
+
 --- Generate filename for the image
 ---
 --- @param block table The CodeBlock element
@@ -759,9 +839,19 @@ function CodeBlock(block)
   local is_bitfield = block.classes[1] == "bitfield"
   local output_type = is_bitfield and bitfield_filetype or filetype
   local media_type = is_bitfield and bitfield_mimetype or mimetype
-  local img_data = generate_image(block, converter, output_type)
-  if not img_data then
-    return nil
+  local cache_path = render_cache_path(block, output_type)
+  local img_data = cache_path and read_cached_render(cache_path)
+  if img_data then
+    table.insert(render_events, 'hit ' .. path.filename(cache_path))
+  else
+    img_data = generate_image(block, converter, output_type)
+    if not img_data then
+      return nil
+    end
+    if cache_path then
+      write_cached_render(cache_path, img_data)
+      table.insert(render_events, 'miss ' .. path.filename(cache_path))
+    end
   end
 
   -- Generate filename and save image
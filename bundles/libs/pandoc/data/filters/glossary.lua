@@ -0,0 +1,237 @@
+--- Expand glossary and acronym references for OmniDoc builds.
+---
+--- OmniDoc reads the project's glossary files and passes the entries as the
+--- `omnidoc-glossary` metadata map (key -> short, long, description), plus an
+--- optional `omnidoc-glossary-title`. `[+KEY]` in running text and
+--- `[KEY]{.gls}` spans (or `[text]{.gls key=KEY}`) expand to "long (short)"
+--- on first use and to the short form afterwards.
+---
+--- LaTeX and Beamer map every reference to the `glossaries` package and
+--- print the used entries with `\printnoidxglossary`, which needs no external
+--- indexing tool. HTML and EPUB link each use to a generated glossary
+--- chapter. Every other writer keeps the plain expansions. Unknown keys are
+--- left as written; `omnidoc check lint` reports them.
+
+local utils = pandoc.utils
+local glossary_key = 'omnidoc-glossary'
+local title_key = 'omnidoc-glossary-title'
+local reference_pattern = '^(.-)%[%+([^%]%s]+)%](.*)$'
+
+local entries = nil
+local title = nil
+local mode = 'plain'
+local used = {}
+local used_keys = {}
+
+local function output_mode()
+  if FORMAT:match('latex') or FORMAT:match('beamer') then
+    return 'latex'
+  end
+  if FORMAT:match('html') or FORMAT:match('epub') then
+    return 'link'
+  end
+  return 'plain'
+end
+
+local function to_inlines(value)
+  if value == nil then
+    return nil
+  end
+  local kind = utils.type(value)
+  if kind == 'Inlines' then
+    return value
+  end
+  if kind == 'Blocks' then
+    return utils.blocks_to_inlines(value)
+  end
+  return pandoc.Inlines(utils.stringify(value))
+end
+
+local function identifier(key)
+  return 'gls-' .. (key:lower():gsub('[^%w%-]', '-'))
+end
+
+local function latex(inlines)
+  local text = pandoc.write(pandoc.Pandoc({pandoc.Plain(inlines)}), 'latex')
+  return (text:gsub('%s+$', ''))
+end
+
+local function Meta(meta)
+  local glossary = meta[glossary_key]
+  if glossary == nil then
+    return nil
+  end
+
+  entries = {}
+  for key, entry in pairs(glossary) do
+    entries[key] = {
+      short = to_inlines(entry.short) or pandoc.Inlines(key),
+      long = to_inlines(entry.long),
+      description = to_inlines(entry.description),
+    }
+  end
+  if meta[title_key] ~= nil then
+    title = utils.stringify(meta[title_key])
+  end
+  if title == nil or title == '' then
+    local lang = meta.lang and utils.stringify(meta.lang) or ''
+    title = lang:match('^zh') and '术语表' or 'Glossary'
+  end
+  mode = output_mode()
+
+  meta[glossary_key] = nil
+  meta[title_key] = nil
+  return meta
+end
+
+--- The inlines a reference to `key` becomes, or nil for unknown keys.
+local function expand(key)
+  local entry = entries[key]
+  if entry == nil then
+    return nil
+  end
+  local first = not used[key]
+  if first then
+    used[key] = true
+    table.insert(used_keys, key)
+  end
+
+  if mode == 'latex' then
+    return pandoc.Inlines({pandoc.RawInline('latex', '\\gls{' .. identifier(key) .. '}')})
+  end
+
+  local text = pandoc.Inlines({})
+  if first and entry.long ~= nil then
+    text:extend(entry.long:clone())
+    text:extend({pandoc.Space(), pandoc.Str('(')})
+    text:extend(entry.short:clone())
+    text:insert(pandoc.Str(')'))
+  else
+    text:extend(entry.short:clone())
+  end
+  if mode == 'link' then
+    local tooltip = utils.stringify(entry.long or entry.description)
+    return pandoc.Inlines({pandoc.Link(text, '#' .. identifier(key), tooltip)})
+  end
+  return text
+end
+
+local function Str(el)
+  if entries == nil or not el.text:find('[+', 1, true) then
+    return nil
+  end
+  local result = pandoc.Inlines({})
+  local rest = el.text
+  local changed = false
+  while true do
+    local before, key, after = rest:match(reference_pattern)
+    if key == nil then
+      break
+    end
+    if before ~= '' then
+      result:insert(pandoc.Str(before))
+    end
+    local expansion = expand(key)
+    if expansion ~= nil then
+      result:extend(expansion)
+      changed = true
+    else
+      result:insert(pandoc.Str('[+' .. key .. ']'))
+    end
+    rest = after
+  end
+  if not changed then
+    return nil
+  end
+  if rest ~= '' then
+    result:insert(pandoc.Str(rest))
+  end
+  return result
+end
+
+local function Span(el)
+  if entries == nil or not el.classes:includes('gls') then
+    return nil
+  end
+  local key = el.attributes.key or utils.stringify(el.content)
+  return expand(key)
+end
+
+local function sorted_used_keys()
+  local keys = {table.unpack(used_keys)}
+  table.sort(keys, function(left, right)
+    return utils.stringify(entries[left].short):lower() < utils.stringify(entries[right].short):lower()
+  end)
+  return keys
+end
+
+local function latex_glossary(doc)
+  local definitions = {'\\usepackage{glossaries}', '\\setacronymstyle{long-short}', '\\makenoidxglossaries'}
+  for _, key in ipairs(sorted_used_keys()) do
+    local entry = entries[key]
+    local description = entry.description and latex(entry.description) or ''
+    if entry.long ~= nil then
+      -- Acronyms list their long form when no description is given.
+      local options = description ~= '' and string.format('[description={%s}]', description) or ''
+      table.insert(definitions, string.format(
+        '\\newacronym%s{%s}{%s}{%s}',
+        options, identifier(key), latex(entry.short), latex(entry.long)))
+    else
+      table.insert(definitions, string.format(
+        '\\newglossaryentry{%s}{name={%s},description={%s}}',
+        identifier(key), latex(entry.short), description))
+    end
+  end
+
+  local header = pandoc.MetaBlocks({pandoc.RawBlock('latex', table.concat(definitions, '\n'))})
+  local includes = doc.meta['header-includes']
+  if includes == nil then
+    doc.meta['header-includes'] = pandoc.MetaList({header})
+  elseif utils.type(includes) == 'List' then
+    table.insert(includes, header)
+  else
+    doc.meta['header-includes'] = pandoc.MetaList({includes, header})
+  end
+  doc.blocks:insert(pandoc.RawBlock('latex', string.format(
+    '\\printnoidxglossary[title={%s},sort=word]', latex(pandoc.Inlines(title)))))
+  return doc
+end
+
+local function linked_glossary(doc)
+  local items = {}
+  for _, key in ipairs(sorted_used_keys()) do
+    local entry = entries[key]
+    local term = pandoc.Inlines({pandoc.Span(entry.short:clone(), pandoc.Attr(identifier(key)))})
+    local definition = {}
+    if entry.long ~= nil then
+      table.insert(definition, pandoc.Plain(entry.long:clone()))
+    end
+    if entry.description ~= nil then
+      table.insert(definition, pandoc.Para(entry.description:clone()))
+    end
+    table.insert(items, {term, {definition}})
+  end
+  doc.blocks:insert(pandoc.Header(1, pandoc.Inlines(title),
+    pandoc.Attr('glossary', {'unnumbered'})))
+  doc.blocks:insert(pandoc.DefinitionList(items))
+  return doc
+end
+
+local function Pandoc(doc)
+  if entries == nil or #used_keys == 0 then
+    return nil
+  end
+  if mode == 'latex' then
+    return latex_glossary(doc)
+  end
+  if mode == 'link' then
+    return linked_glossary(doc)
+  end
+  return nil
+end
+
+return {
+  {Meta = Meta},
+  {Str = Str, Span = Span},
+  {Pandoc = Pandoc},
+}
@@ -25,6 +25,9 @@
 --- Metadata options:
 ---   - include-auto: Automatically shift heading levels based on current heading level
 ---   - update-contents: Update relative paths in included content (images, code includes)
+---   - omnidoc-fragment-cache: Directory holding the parsed JSON AST of every
+---     included file, keyed by its content, format and omnidoc-fragment-salt
+---   - omnidoc-fragment-stats: File listing which includes hit that cache
 ---
 --- Copyright: © 2019–2021 Albert Krewinkel
 --- License:   MIT – see LICENSE file for details
@@ -64,6 +67,12 @@ local function absolute_dependency(file_path)
   return path.normalize(resolved)
 end
 
+--- Optional per-include AST cache shared by every build of the project.
+local fragment_dir = nil
+local fragment_salt = ''
+local fragment_stats_path = nil
+local fragment_events = {}
+
 local function record_dependency(file_path)
   local resolved = absolute_dependency(file_path)
   if not resolved:find('[\r\n]') then
@@ -96,6 +105,74 @@ local function write_depfile()
   file:close()
 end
 
+local function fragment_cache_path(file_content, format)
+  if not fragment_dir or fragment_dir == '' or not pandoc.write then
+    return nil
+  end
+  local key = pandoc.utils.sha1(table.concat({
+    tostring(PANDOC_VERSION), fragment_salt, format, file_content
+  }, '\0'))
+  return path.join({fragment_dir, key .. '.json'})
+end
+
+--- Parsed blocks of a cached fragment, or nil when the cache has none.
+local function read_fragment(fragment_path)
+  local fh = io.open(fragment_path)
+  if not fh then
+    return nil
+  end
+  local json = fh:read('*a')
+  fh:close()
+  local success, result = pcall(function()
+    return pandoc.read(json, 'json').blocks
+  end)
+  return success and result or nil
+end
+
+local function write_fragment(fragment_path, blocks)
+  local success, json = pcall(function()
+    return pandoc.write(pandoc.Pandoc(blocks), 'json')
+  end)
+  if not success then
+    return
+  end
+  -- Concurrent builds may store the same fragment; rename keeps readers
+  -- from ever seeing a partial file.
+  local staging = fragment_path .. '.' .. tostring(os.time()) ..
+    tostring(math.random(1, 1000000)) .. '.tmp'
+  local fh = io.open(staging, 'w')
+  if not fh then
+    return
+  end
+  fh:write(json)
+  fh:close()
+  if not os.rename(staging, fragment_path) then
+    os.remove(staging)
+  end
+end
+
+local function record_fragment_event(event, file_path)
+  local resolved = absolute_dependency(file_path)
+  if not resolved:find('[\r\n]') then
+    table.insert(fragment_events, event .. ' ' .. resolved)
+  end
+end
+
+local function write_fragment_stats()
+  if not fragment_stats_path or fragment_stats_path == '' then
+    return
+  end
+  local file = io.open(fragment_stats_path, 'w')
+  if not file then
+    return
+  end
+  file:write('# omnidoc-fragments-v1\n')
+  for _, event in ipairs(fragment_events) do
+    file:write(event, '\n')
+  end
+  file:close()
+end
+
 -- ============================================================================
 -- Meta Processing
 -- ============================================================================
@@ -111,6 +188,13 @@ function get_vars(meta)
   depfile_path = generic_depfile and pandoc.utils.stringify(generic_depfile) or
     (legacy_depfile and pandoc.utils.stringify(legacy_depfile) or nil)
   included_dependencies = {}
+  local fragment_cache = meta['omnidoc-fragment-cache']
+  local salt = meta['omnidoc-fragment-salt']
+  local stats = meta['omnidoc-fragment-stats']
+  fragment_dir = fragment_cache and pandoc.utils.stringify(fragment_cache) or nil
+  fragment_salt = salt and pandoc.utils.stringify(salt) or ''
+  fragment_stats_path = stats and pandoc.utils.stringify(stats) or nil
+  fragment_events = {}
   return nil
 end
 
@@ -164,6 +248,7 @@ function Pandoc(doc)
     document_title_inlines = nil
   end
   write_depfile()
+  write_fragment_stats()
   return doc
 end
 
@@ -283,6 +368,16 @@ local function read_included_file(file_path, format)
   fh:close()
   record_dependency(file_path)
 
+  -- Reuse the parsed AST of an unchanged file
+  local fragment_path = fragment_cache_path(file_content, format)
+  if fragment_path then
+    local cached = read_fragment(fragment_path)
+    if cached then
+      record_fragment_event('hit', file_path)
+      return cached
+    end
+  end
+
   -- Parse file content
   local success, result = pcall(function()
     return pandoc.read(file_content, format, PANDOC_READER_OPTIONS).blocks
@@ -296,6 +391,10 @@ local function read_included_file(file_path, format)
     return nil
   end
 
+  if fragment_path then
+    record_fragment_event('miss', file_path)
+    write_fragment(fragment_path, result)
+  end
   return result
 end
 
@@ -0,0 +1,205 @@
+--- Build a back-of-book index for OmniDoc builds.
+---
+--- `[term]{.idx}` (or `.index`) marks running text as an index entry.
+--- `term="Parent!Child"` files the entry under another or a nested heading,
+--- `sort="…"` sets its sort key (for example the pinyin of a Chinese term),
+--- and `[]{.idx term="…"}` adds an entry without visible text. OmniDoc may
+--- pass the chapter title as `omnidoc-index-title`.
+---
+--- LaTeX gets `\index{sort@term}` commands, `imakeidx` and a final
+--- `\printindex`; OmniDoc's LaTeX recorder runs the configured index
+--- processor (makeindex, xindy or zhmakeindex) between engine passes. HTML
+--- and EPUB anchor every occurrence and append an index chapter linking
+--- back to them. Every other writer keeps the marked text only.
+
+local utils = pandoc.utils
+local title_key = 'omnidoc-index-title'
+
+local title = nil
+local mode = 'plain'
+local root = {children = {}}
+local count = 0
+
+local function output_mode()
+  if FORMAT:match('latex') then
+    return 'latex'
+  end
+  if FORMAT:match('html') or FORMAT:match('epub') then
+    return 'link'
+  end
+  return 'plain'
+end
+
+local function latex(inlines)
+  local text = pandoc.write(pandoc.Pandoc({pandoc.Plain(inlines)}), 'latex')
+  return (text:gsub('%s+$', ''))
+end
+
+--- makeindex, xindy and zhmakeindex all read `"` as the quote character.
+local function quote(text)
+  return (text:gsub('(["@!|])', '"%1'))
+end
+
+local function split_levels(text)
+  local levels = {}
+  for level in text:gmatch('[^!]+') do
+    level = level:match('^%s*(.-)%s*$')
+    if level ~= '' then
+      table.insert(levels, level)
+    end
+  end
+  return levels
+end
+
+local function Meta(meta)
+  if meta[title_key] ~= nil then
+    title = utils.stringify(meta[title_key])
+  end
+  if title == nil or title == '' then
+    local lang = meta.lang and utils.stringify(meta.lang) or ''
+    title = lang:match('^zh') and '索引' or 'Index'
+  end
+  mode = output_mode()
+  meta[title_key] = nil
+  return meta
+end
+
+--- Index levels of a marked span: names, display inlines and sort keys.
+local function levels_of(el)
+  local names = split_levels(el.attributes.term or utils.stringify(el.content))
+  if #names == 0 then
+    return nil
+  end
+  local sorts = split_levels(el.attributes.sort or '')
+  local levels = {}
+  for position, name in ipairs(names) do
+    local display = pandoc.Inlines(name)
+    -- Without a `term` attribute the marked text itself is the heading,
+    -- formatting included.
+    if el.attributes.term == nil and #names == 1 then
+      display = el.content:clone()
+    end
+    table.insert(levels, {name = name, display = display, sort = sorts[position] or name})
+  end
+  return levels
+end
+
+local function index_command(levels)
+  local parts = {}
+  for _, level in ipairs(levels) do
+    local display = latex(level.display)
+    if level.sort == display then
+      table.insert(parts, quote(display))
+    else
+      table.insert(parts, quote(level.sort) .. '@' .. quote(display))
+    end
+  end
+  return '\\index{' .. table.concat(parts, '!') .. '}'
+end
+
+local function record(levels, target)
+  local node = root
+  for _, level in ipairs(levels) do
+    local child = node.children[level.name]
+    if child == nil then
+      child = {display = level.display, sort = level.sort, children = {}, targets = {}}
+      node.children[level.name] = child
+    end
+    node = child
+  end
+  table.insert(node.targets, target)
+end
+
+local function Span(el)
+  if not (el.classes:includes('idx') or el.classes:includes('index')) then
+    return nil
+  end
+  local levels = levels_of(el)
+  if levels == nil then
+    return el.content
+  end
+  count = count + 1
+
+  if mode == 'latex' then
+    local result = el.content:clone()
+    result:insert(pandoc.RawInline('latex', index_command(levels)))
+    return result
+  end
+  if mode == 'link' then
+    local target = 'idx-' .. count
+    record(levels, target)
+    return pandoc.Span(el.content, pandoc.Attr(target))
+  end
+  return el.content
+end
+
+local function sorted_children(node)
+  local children = {}
+  for _, child in pairs(node.children) do
+    table.insert(children, child)
+  end
+  table.sort(children, function(left, right)
+    return left.sort:lower() < right.sort:lower()
+  end)
+  return children
+end
+
+local function index_list(node)
+  local items = {}
+  for _, child in ipairs(sorted_children(node)) do
+    local line = child.display:clone()
+    for position, target in ipairs(child.targets) do
+      line:insert(pandoc.Str(position == 1 and ', ' or ' '))
+      line:insert(pandoc.Link(pandoc.Inlines(tostring(position)), '#' .. target))
+    end
+    local item = {pandoc.Plain(line)}
+    if next(child.children) ~= nil then
+      table.insert(item, index_list(child))
+    end
+    table.insert(items, item)
+  end
+  return pandoc.BulletList(items)
+end
+
+local function latex_index(doc)
+  local header = pandoc.MetaBlocks({pandoc.RawBlock('latex', table.concat({
+    '\\usepackage{imakeidx}',
+    string.format('\\makeindex[intoc,noautomatic,title={%s}]', latex(pandoc.Inlines(title))),
+  }, '\n'))})
+  local includes = doc.meta['header-includes']
+  if includes == nil then
+    doc.meta['header-includes'] = pandoc.MetaList({header})
+  elseif utils.type(includes) == 'List' then
+    table.insert(includes, header)
+  else
+    doc.meta['header-includes'] = pandoc.MetaList({includes, header})
+  end
+  doc.blocks:insert(pandoc.RawBlock('latex', '\\printindex'))
+  return doc
+end
+
+local function linked_index(doc)
+  doc.blocks:insert(pandoc.Header(1, pandoc.Inlines(title),
+    pandoc.Attr('index', {'unnumbered'})))
+  doc.blocks:insert(index_list(root))
+  return doc
+end
+
+local function Pandoc(doc)
+  if count == 0 then
+    return nil
+  end
+  if mode == 'latex' then
+    return latex_index(doc)
+  end
+  if mode == 'link' then
+    return linked_index(doc)
+  end
+  return nil
+end
+
+return {
+  {Meta = Meta},
+  {Span = Span},
+  {Pandoc = Pandoc},
+}
@@ -189,7 +189,7 @@ local function proc_image(image)
     -- checked-in PDF sibling into Pandoc's media bag instead. The hashed name
     -- is deterministic, keeps source trees clean, and lets the LaTeX writer
     -- consume a normal PDF image from its temporary media directory.
-    if FORMAT:match('latex') then
+    if FORMAT:match('latex') or FORMAT:match('beamer') then
       local fetched, mime, svg = pcall(pandoc.mediabag.fetch, image.src)
       if not fetched or not svg then
         error('Cannot read SVG image for PDF conversion: ' .. image.src ..
@@ -0,0 +1,170 @@
+--- Map front matter to the metadata shapes of the JATS and DocBook writers.
+---
+--- Authors may be plain names or maps with `name` (or `surname` and
+--- `given-names`), `email`, `orcid`, and `affiliation`. Affiliations are
+--- referenced by id or by name from a top-level `affiliation` list, or named
+--- inline; `authors`/`affiliations` are accepted as aliases and a
+--- comma-separated `keywords` string becomes a list.
+---
+--- JATS: `author[].affiliation` holds affiliation ids and `affiliation[]`
+--- carries `id` and `organization`, which Pandoc's template links with
+--- `<xref ref-type="aff">`.
+--- DocBook: Pandoc's writer only understands author names, so `author`
+--- becomes a list of names and the full records go to `omnidoc-authors`
+--- for the bundled `omnidoc-docbook5.xml` template.
+
+local stringify = pandoc.utils.stringify
+
+local function as_list(value)
+  if value == nil then
+    return {}
+  end
+  if pandoc.utils.type(value) == 'List' then
+    return value
+  end
+  return {value}
+end
+
+local function is_map(value)
+  return pandoc.utils.type(value) == 'table'
+end
+
+local function text(value)
+  if value == nil then
+    return nil
+  end
+  local result = stringify(value)
+  if result == '' then
+    return nil
+  end
+  return result
+end
+
+--- Collect the declared affiliations, keyed by id and by name.
+local function affiliation_registry(meta)
+  local registry = {list = {}, by_key = {}}
+  function registry.add(id, name)
+    if id == nil then
+      id = 'aff' .. tostring(#registry.list + 1)
+    end
+    local entry = registry.by_key[id] or (name and registry.by_key[name])
+    if entry == nil then
+      entry = {id = id, name = name or id}
+      table.insert(registry.list, entry)
+      registry.by_key[id] = entry
+      registry.by_key[entry.name] = entry
+    end
+    return entry
+  end
+  for _, item in ipairs(as_list(meta.affiliation or meta.affiliations)) do
+    if is_map(item) then
+      registry.add(text(item.id), text(item.organization or item.name))
+    else
+      registry.add(nil, text(item))
+    end
+  end
+  return registry
+end
+
+local function author_records(meta, registry)
+  local records = {}
+  for _, item in ipairs(as_list(meta.author or meta.authors)) do
+    local record = {affiliations = {}}
+    if is_map(item) then
+      record.surname = text(item.surname)
+      record.given = text(item['given-names'])
+      record.name = text(item.name)
+        or table.concat({record.given or '', record.surname or ''}, ' '):match('^%s*(.-)%s*$')
+      record.email = text(item.email)
+      record.orcid = text(item.orcid)
+      for _, affiliation in ipairs(as_list(item.affiliation or item.affiliations)) do
+        local key = text(affiliation)
+        if key then
+          local entry = registry.by_key[key] or registry.add(nil, key)
+          table.insert(record.affiliations, entry)
+        end
+      end
+    else
+      record.name = text(item)
+    end
+    if record.name and record.name ~= '' then
+      table.insert(records, record)
+    end
+  end
+  return records
+end
+
+local function jats_metadata(meta, records, registry)
+  local authors = pandoc.List()
+  for _, record in ipairs(records) do
+    local author = {name = record.name}
+    if record.surname then
+      author.surname = record.surname
+      author['given-names'] = record.given
+    end
+    author.email = record.email
+    author.orcid = record.orcid
+    local ids = pandoc.List()
+    for _, entry in ipairs(record.affiliations) do
+      ids:insert(entry.id)
+    end
+    if #ids > 0 then
+      author.affiliation = ids
+    end
+    authors:insert(author)
+  end
+  local affiliations = pandoc.List()
+  for _, entry in ipairs(registry.list) do
+    affiliations:insert({id = entry.id, organization = entry.name})
+  end
+  meta.author = #authors > 0 and authors or nil
+  meta.affiliation = #affiliations > 0 and affiliations or nil
+end
+
+local function docbook_metadata(meta, records)
+  local names = pandoc.List()
+  local full = pandoc.List()
+  for _, record in ipairs(records) do
+    names:insert(record.name)
+    local affiliations = pandoc.List()
+    for _, entry in ipairs(record.affiliations) do
+      affiliations:insert(entry.name)
+    end
+    full:insert({
+      name = record.name,
+      email = record.email,
+      orcid = record.orcid,
+      affiliation = #affiliations > 0 and affiliations or nil,
+    })
+  end
+  meta.author = #names > 0 and names or nil
+  meta['omnidoc-authors'] = #full > 0 and full or nil
+  meta.affiliation = nil
+end
+
+local function Meta(meta)
+  local registry = affiliation_registry(meta)
+  local records = author_records(meta, registry)
+  meta.authors = nil
+  meta.affiliations = nil
+
+  if meta.keywords ~= nil and pandoc.utils.type(meta.keywords) ~= 'List' then
+    local keywords = pandoc.List()
+    for keyword in stringify(meta.keywords):gmatch('[^,;]+') do
+      keyword = keyword:match('^%s*(.-)%s*$')
+      if keyword ~= '' then
+        keywords:insert(keyword)
+      end
+    end
+    meta.keywords = keywords
+  end
+
+  if FORMAT:match('^jats') then
+    jats_metadata(meta, records, registry)
+  elseif FORMAT:match('^docbook') then
+    docbook_metadata(meta, records)
+  end
+  return meta
+end
+
+return {{Meta = Meta}}
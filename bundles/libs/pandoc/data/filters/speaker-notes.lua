@@ -0,0 +1,16 @@
+--- Drop `::: notes` speaker notes from non-presentation outputs.
+---
+--- Pandoc's PPTX, Beamer, and reveal.js writers turn a Div with the `notes`
+--- class into speaker notes. Every other writer would print the notes as
+--- ordinary body text, so OmniDoc runs this filter for those outputs only.
+
+local function Div(el)
+  for _, class in ipairs(el.classes) do
+    if class == 'notes' then
+      return {}
+    end
+  end
+  return nil
+end
+
+return {{Div = Div}}
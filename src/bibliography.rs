@@ -0,0 +1,423 @@
+//! Bibliography databases as citeproc sees them.
+//!
+//! BibTeX/BibLaTeX, CSL-JSON, and CSL-YAML files are read into one entry
+//! shape: the citation key, the CSL item type, and the CSL variables the
+//! entry provides. BibTeX types and fields are mapped the way Pandoc's
+//! citeproc converts them, so `journal` counts as `container-title` and
+//! `year` as `issued`. Only what the lint rules need is kept; values are not.
+
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// One bibliography entry, with its 1-based line in the database file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BibEntry {
+    pub key: String,
+    pub csl_type: String,
+    pub variables: BTreeSet<String>,
+    pub line: usize,
+}
+
+/// 按扩展名解析参考文献数据库；不支持的格式返回 `None`
+pub fn parse(path: &Path, content: &str) -> Option<Vec<BibEntry>> {
+    let extension = path
+        .extension()
+        .and_then(|value| value.to_str())?
+        .to_ascii_lowercase();
+    match extension.as_str() {
+        "bib" | "bibtex" => Some(parse_bibtex(content)),
+        "json" => parse_csl_json(content),
+        "yaml" | "yml" => Some(parse_csl_yaml(content)),
+        _ => None,
+    }
+}
+
+/// Whether `path` has a bibliography extension Pandoc understands.
+pub fn is_bibliography(path: &Path) -> bool {
+    path.extension()
+        .and_then(|value| value.to_str())
+        .is_some_and(|value| {
+            matches!(
+                value.to_ascii_lowercase().as_str(),
+                "bib" | "bibtex" | "json" | "yaml" | "yml"
+            )
+        })
+}
+
+pub fn parse_bibtex(content: &str) -> Vec<BibEntry> {
+    let mut entries = Vec::new();
+    let bytes = content.as_bytes();
+    let mut index = 0;
+    while let Some(offset) = content[index..].find('@') {
+        let start = index + offset;
+        index = start + 1;
+        let kind_len = content[index..]
+            .find(|character: char| !character.is_ascii_alphanumeric())
+            .unwrap_or(content.len() - index);
+        let kind = content[index..index + kind_len].to_ascii_lowercase();
+        let mut cursor = index + kind_len;
+        while bytes.get(cursor).is_some_and(u8::is_ascii_whitespace) {
+            cursor += 1;
+        }
+        if !matches!(bytes.get(cursor), Some(b'{' | b'(')) {
+            continue;
+        }
+        let Some(end) = entry_end(bytes, cursor) else {
+            break;
+        };
+        index = end + 1;
+        if kind.is_empty() || matches!(kind.as_str(), "string" | "comment" | "preamble") {
+            continue;
+        }
+        let body = &content[cursor + 1..end];
+        let Some((key, fields)) = body.split_once(',') else {
+            continue;
+        };
+        let key = key.trim();
+        if key.is_empty() {
+            continue;
+        }
+        let variables = bibtex_field_names(fields)
+            .into_iter()
+            .filter_map(|field| bibtex_variable(&kind, &field))
+            .map(str::to_string)
+            .collect();
+        entries.push(BibEntry {
+            key: key.to_string(),
+            csl_type: bibtex_type(&kind).to_string(),
+            variables,
+            line: line_of(content, start),
+        });
+    }
+    entries
+}
+
+/// Byte offset of the `}` or `)` closing the entry opened at `open`.
+fn entry_end(bytes: &[u8], open: usize) -> Option<usize> {
+    let close = if bytes[open] == b'{' { b'}' } else { b')' };
+    let mut depth = 0usize;
+    for (offset, byte) in bytes[open + 1..].iter().enumerate() {
+        match *byte {
+            b'{' => depth += 1,
+            b'}' if depth > 0 => depth -= 1,
+            byte if byte == close && depth == 0 => return Some(open + 1 + offset),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Names of the `name = value` fields at the top level of an entry body.
+fn bibtex_field_names(fields: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut name = String::new();
+    let mut in_value = false;
+    let mut depth = 0usize;
+    let mut quoted = false;
+    for character in fields.chars() {
+        let top_level = depth == 0 && !quoted;
+        match character {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            '"' if depth == 0 => quoted = !quoted,
+            '=' if top_level && !in_value => {
+                names.push(name.trim().to_ascii_lowercase());
+                in_value = true;
+            }
+            ',' if top_level => {
+                name.clear();
+                in_value = false;
+            }
+            character if !in_value => name.push(character),
+            _ => {}
+        }
+    }
+    names
+}
+
+/// CSL item type for a BibTeX/BibLaTeX entry type.
+fn bibtex_type(kind: &str) -> &'static str {
+    match kind {
+        "article" => "article-journal",
+        "book" | "mvbook" | "collection" | "mvcollection" | "proceedings" | "mvproceedings" => {
+            "book"
+        }
+        "inbook" | "incollection" | "bookinbook" | "suppbook" => "chapter",
+        "inproceedings" | "conference" => "paper-conference",
+        "phdthesis" | "mastersthesis" | "thesis" => "thesis",
+        "techreport" | "report" => "report",
+        "online" | "electronic" | "www" => "webpage",
+        "manual" => "book",
+        "patent" => "patent",
+        "unpublished" => "manuscript",
+        _ => "document",
+    }
+}
+
+/// CSL variable a BibTeX field provides.
+fn bibtex_variable(kind: &str, field: &str) -> Option<&'static str> {
+    Some(match field {
+        "author" => "author",
+        "editor" => "editor",
+        "title" => "title",
+        "year" | "date" => "issued",
+        "journal" | "journaltitle" => "container-title",
+        "booktitle" if kind != "book" => "container-title",
+        "publisher" | "school" | "institution" | "organization" => "publisher",
+        "address" | "location" => "publisher-place",
+        "volume" => "volume",
+        "number" | "issue" => "issue",
+        "pages" => "page",
+        "doi" => "DOI",
+        "url" => "URL",
+        "isbn" => "ISBN",
+        "edition" => "edition",
+        _ => return None,
+    })
+}
+
+pub fn parse_csl_json(content: &str) -> Option<Vec<BibEntry>> {
+    let value: Value = serde_json::from_str(content).ok()?;
+    let items = match &value {
+        Value::Array(items) => items,
+        Value::Object(object) => object.get("references")?.as_array()?,
+        _ => return None,
+    };
+    let mut search_from = 0;
+    let entries = items
+        .iter()
+        .filter_map(|item| {
+            let object = item.as_object()?;
+            let key = match object.get("id")? {
+                Value::String(key) => key.clone(),
+                Value::Number(key) => key.to_string(),
+                _ => return None,
+            };
+            // serde_json keeps no positions; find the id in source order.
+            let line = content[search_from..]
+                .find(&format!("\"{key}\""))
+                .map(|offset| {
+                    search_from += offset + 1;
+                    line_of(content, search_from - 1)
+                })
+                .unwrap_or(1);
+            Some(BibEntry {
+                key,
+                csl_type: object
+                    .get("type")
+                    .and_then(Value::as_str)
+                    .unwrap_or("document")
+                    .to_string(),
+                variables: object
+                    .iter()
+                    .filter(|(name, value)| {
+                        !matches!(name.as_str(), "id" | "type") && !value.is_null()
+                    })
+                    .map(|(name, _)| name.clone())
+                    .collect(),
+                line,
+            })
+        })
+        .collect();
+    Some(entries)
+}
+
+/// CSL-YAML as Pandoc writes it: a `references:` list (or a bare list) of
+/// mappings. Only each item's own keys are read, so nested name lists and
+/// dates do not need a YAML parser.
+pub fn parse_csl_yaml(content: &str) -> Vec<BibEntry> {
+    struct Item {
+        /// Indentation of the item's own keys.
+        indent: usize,
+        entry: BibEntry,
+    }
+    fn finish(item: Option<Item>, entries: &mut Vec<BibEntry>) {
+        if let Some(item) = item.filter(|item| !item.entry.key.is_empty()) {
+            entries.push(item.entry);
+        }
+    }
+
+    let mut entries = Vec::new();
+    let mut item: Option<Item> = None;
+    for (line_index, line) in content.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+        let mut pair = trimmed;
+        if let Some(rest) = trimmed.strip_prefix("- ") {
+            // A dash deeper than the item's keys belongs to a nested list.
+            if item.as_ref().is_some_and(|item| indent >= item.indent) {
+                continue;
+            }
+            finish(item.take(), &mut entries);
+            item = Some(Item {
+                indent: indent + 2,
+                entry: BibEntry {
+                    key: String::new(),
+                    csl_type: "document".to_string(),
+                    variables: BTreeSet::new(),
+                    line: line_index + 1,
+                },
+            });
+            pair = rest;
+        } else if item.as_ref().is_some_and(|item| indent < item.indent) {
+            finish(item.take(), &mut entries);
+            continue;
+        } else if item.as_ref().is_some_and(|item| indent > item.indent) {
+            continue;
+        }
+        let (Some(item), Some((name, value))) = (item.as_mut(), pair.split_once(':')) else {
+            continue;
+        };
+        let value = value.trim().trim_matches(['"', '\'']);
+        match name.trim() {
+            "id" => item.entry.key = value.to_string(),
+            "type" => item.entry.csl_type = value.to_string(),
+            name => {
+                item.entry.variables.insert(name.to_string());
+            }
+        }
+    }
+    finish(item, &mut entries);
+    entries
+}
+
+/// Variables a CSL style reads anywhere in its macros, citation, or
+/// bibliography layout.
+pub fn csl_style_variables(style: &str) -> BTreeSet<String> {
+    let pattern = regex::Regex::new(r#"variable="([^"]+)""#).expect("CSL variable regex");
+    pattern
+        .captures_iter(style)
+        .flat_map(|capture| {
+            capture[1]
+                .split_whitespace()
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Variables citeproc needs to render a complete reference of `csl_type`.
+/// Alternatives are grouped: `author` or `editor` satisfies a book.
+pub fn required_variables(csl_type: &str) -> &'static [&'static [&'static str]] {
+    match csl_type {
+        "article-journal" | "article-magazine" | "article-newspaper" => {
+            &[&["author"], &["title"], &["container-title"], &["issued"]]
+        }
+        "book" => &[
+            &["author", "editor"],
+            &["title"],
+            &["publisher"],
+            &["issued"],
+        ],
+        "chapter" | "paper-conference" => {
+            &[&["author"], &["title"], &["container-title"], &["issued"]]
+        }
+        "thesis" => &[&["author"], &["title"], &["publisher"], &["issued"]],
+        "report" => &[&["author", "editor"], &["title"], &["issued"]],
+        _ => &[&["title"]],
+    }
+}
+
+fn line_of(content: &str, offset: usize) -> usize {
+    content[..offset].matches('\n').count() + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{csl_style_variables, parse_bibtex, parse_csl_json, parse_csl_yaml};
+
+    #[test]
+    fn bibtex_entries_map_to_csl_types_and_variables() {
+        let entries = parse_bibtex(concat!(
+            "@string{acm = \"ACM\"}\n",
+            "@Article{knuth1984,\n",
+            "  author = {Donald E. Knuth},\n",
+            "  title = {Literate {P}rogramming, a = b},\n",
+            "  journal = acm,\n",
+            "  year = 1984\n",
+            "}\n\n",
+            "@inproceedings( lamport94 , title = \"LaTeX, again\", booktitle = {Proc})\n",
+        ));
+
+        let keys = entries
+            .iter()
+            .map(|entry| (entry.key.as_str(), entry.csl_type.as_str(), entry.line))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            keys,
+            [
+                ("knuth1984", "article-journal", 2),
+                ("lamport94", "paper-conference", 9),
+            ]
+        );
+        assert_eq!(
+            entries[0].variables.iter().collect::<Vec<_>>(),
+            ["author", "container-title", "issued", "title"]
+        );
+        assert_eq!(
+            entries[1].variables.iter().collect::<Vec<_>>(),
+            ["container-title", "title"]
+        );
+    }
+
+    #[test]
+    fn csl_json_and_yaml_entries_keep_their_keys_and_lines() {
+        let json = parse_csl_json(
+            "[\n  {\"id\": \"doe2020\", \"type\": \"book\", \"title\": \"T\",\n   \"author\": [{\"family\": \"Doe\"}]},\n  {\"id\": \"roe\", \"title\": \"R\"}\n]\n",
+        )
+        .expect("CSL-JSON");
+        assert_eq!(json[0].key, "doe2020");
+        assert_eq!(json[0].csl_type, "book");
+        assert_eq!(
+            json[0].variables.iter().collect::<Vec<_>>(),
+            ["author", "title"]
+        );
+        assert_eq!((json[1].key.as_str(), json[1].line), ("roe", 4));
+        assert!(parse_csl_json("{\"not\": \"a bibliography\"}").is_none());
+
+        let yaml = parse_csl_yaml(concat!(
+            "---\n",
+            "references:\n",
+            "- id: doe2020\n",
+            "  type: article-journal\n",
+            "  author:\n",
+            "    - family: Doe\n",
+            "      given: Jane\n",
+            "  title: 'A: title'\n",
+            "  issued:\n",
+            "    - year: 2020\n",
+            "- type: book\n",
+            "  id: \"roe\"\n",
+            "...\n",
+        ));
+        assert_eq!(yaml.len(), 2);
+        assert_eq!((yaml[0].key.as_str(), yaml[0].line), ("doe2020", 3));
+        assert_eq!(
+            yaml[0].variables.iter().collect::<Vec<_>>(),
+            ["author", "issued", "title"]
+        );
+        assert_eq!(
+            (
+                yaml[1].key.as_str(),
+                yaml[1].csl_type.as_str(),
+                yaml[1].line
+            ),
+            ("roe", "book", 11)
+        );
+    }
+
+    #[test]
+    fn reads_variables_from_csl_styles() {
+        let variables = csl_style_variables(
+            r#"<macro name="a"><names variable="author editor"/></macro><text variable="title"/>"#,
+        );
+        assert_eq!(
+            variables.iter().collect::<Vec<_>>(),
+            ["author", "editor", "title"]
+        );
+    }
+}
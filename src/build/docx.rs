@@ -0,0 +1,414 @@
+//! Watermark header for DOCX outputs.
+//!
+//! Pandoc's DOCX writer takes its headers and footers from the reference
+//! document, so the watermark is added after the build: the stamp paragraph
+//! is prepended to the section's existing header of the stamped type, or a
+//! new header part is added and referenced from the body's `w:sectPr`.
+
+use crate::error::{OmniDocError, Result};
+use crate::extensions::ResolvedWatermark;
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+const DOCUMENT_PART: &str = "word/document.xml";
+const DOCUMENT_RELS_PART: &str = "word/_rels/document.xml.rels";
+const CONTENT_TYPES_PART: &str = "[Content_Types].xml";
+const HEADER_PART: &str = "word/omnidoc-watermark-header.xml";
+const HEADER_RELATIONSHIP_ID: &str = "rIdOmniDocWatermark";
+const HEADER_RELATIONSHIP_TYPE: &str =
+    "http://schemas.openxmlformats.org/officeDocument/2006/relationships/header";
+const HEADER_CONTENT_TYPE: &str =
+    "application/vnd.openxmlformats-officedocument.wordprocessingml.header+xml";
+const RELATIONSHIPS_NAMESPACE: &str =
+    "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+const WORDPROCESSING_NAMESPACE: &str =
+    "http://schemas.openxmlformats.org/wordprocessingml/2006/main";
+/// `w:sectPr` children that follow `w:titlePg` in the schema order.
+const AFTER_TITLE_PAGE: [&str; 6] = [
+    "<w:textDirection",
+    "<w:bidi",
+    "<w:rtlGutter",
+    "<w:docGrid",
+    "<w:printerSettings",
+    "<w:sectPrChange",
+];
+
+/// 在 DOCX 页眉中加入水印文本
+pub(crate) fn stamp_watermark(path: &Path, watermark: &ResolvedWatermark) -> Result<()> {
+    let mut parts = read_parts(path)?;
+    let document = part_text(&parts, DOCUMENT_PART)?;
+    let (section_start, section_end) = body_section(&document)?;
+    let mut section = document[section_start..section_end].to_string();
+    if section.ends_with("/>") {
+        section = format!("{}></w:sectPr>", section.trim_end_matches("/>"));
+    }
+
+    let references = header_references(&section);
+    let mut stamped_types = vec![if watermark.first_page_only {
+        "first"
+    } else {
+        "default"
+    }];
+    // A title page uses its own header, which an all-pages stamp must cover.
+    if !watermark.first_page_only && section.contains("<w:titlePg") {
+        stamped_types.push("first");
+    }
+
+    let relationships = relationship_targets(&part_text(&parts, DOCUMENT_RELS_PART)?);
+    let paragraph = stamp_paragraph(watermark);
+    let mut new_references = Vec::new();
+    for header_type in stamped_types {
+        let existing = references
+            .get(header_type)
+            .and_then(|id| relationships.get(id))
+            .map(|target| format!("word/{}", target.trim_start_matches('/')))
+            .filter(|part| parts.contains_key(part));
+        match existing {
+            Some(part) => {
+                let header = part_text(&parts, &part)?;
+                parts.insert(part, prepend_to_header(&header, &paragraph)?.into_bytes());
+            }
+            None => new_references.push(header_type),
+        }
+    }
+
+    if !new_references.is_empty() {
+        parts.insert(
+            HEADER_PART.to_string(),
+            header_part(&paragraph).into_bytes(),
+        );
+        let rels = part_text(&parts, DOCUMENT_RELS_PART)?;
+        parts.insert(
+            DOCUMENT_RELS_PART.to_string(),
+            insert_before_closing(
+                &rels,
+                "</Relationships>",
+                &format!(
+                    "<Relationship Id=\"{HEADER_RELATIONSHIP_ID}\" Type=\"{HEADER_RELATIONSHIP_TYPE}\" Target=\"{}\"/>",
+                    HEADER_PART.trim_start_matches("word/")
+                ),
+            )?
+            .into_bytes(),
+        );
+        let content_types = part_text(&parts, CONTENT_TYPES_PART)?;
+        parts.insert(
+            CONTENT_TYPES_PART.to_string(),
+            insert_before_closing(
+                &content_types,
+                "</Types>",
+                &format!(
+                    "<Override PartName=\"/{HEADER_PART}\" ContentType=\"{HEADER_CONTENT_TYPE}\"/>"
+                ),
+            )?
+            .into_bytes(),
+        );
+
+        let open_end = section.find('>').map(|index| index + 1).unwrap_or(0);
+        let mut section_children = section[open_end..].to_string();
+        let reference_pattern = Regex::new(r"<w:headerReference\b[^>]*/>").expect("valid regex");
+        section_children = reference_pattern
+            .replace_all(&section_children, |captures: &regex::Captures<'_>| {
+                let reference = &captures[0];
+                if new_references
+                    .iter()
+                    .any(|header_type| attribute(reference, "w:type") == Some(header_type))
+                {
+                    String::new()
+                } else {
+                    reference.to_string()
+                }
+            })
+            .to_string();
+        let inserted = new_references
+            .iter()
+            .map(|header_type| {
+                format!(
+                    "<w:headerReference xmlns:r=\"{RELATIONSHIPS_NAMESPACE}\" w:type=\"{header_type}\" r:id=\"{HEADER_RELATIONSHIP_ID}\"/>"
+                )
+            })
+            .collect::<String>();
+        section = format!("{}{inserted}{section_children}", &section[..open_end]);
+        if watermark.first_page_only && !section.contains("<w:titlePg") {
+            let position = AFTER_TITLE_PAGE
+                .iter()
+                .filter_map(|element| section.find(element))
+                .min()
+                .or_else(|| section.rfind("</w:sectPr>"))
+                .unwrap_or(section.len());
+            section.insert_str(position, "<w:titlePg/>");
+        }
+    }
+
+    let document = format!(
+        "{}{section}{}",
+        &document[..section_start],
+        &document[section_end..]
+    );
+    parts.insert(DOCUMENT_PART.to_string(), document.into_bytes());
+    write_parts(path, &parts)
+}
+
+fn stamp_paragraph(watermark: &ResolvedWatermark) -> String {
+    format!(
+        "<w:p><w:pPr><w:jc w:val=\"center\"/></w:pPr><w:r><w:rPr><w:b/><w:color w:val=\"{}\"/><w:sz w:val=\"28\"/></w:rPr><w:t xml:space=\"preserve\">{}</w:t></w:r></w:p>",
+        watermark.faded_color(),
+        xml_escape(&watermark.text)
+    )
+}
+
+fn header_part(paragraph: &str) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<w:hdr xmlns:w=\"{WORDPROCESSING_NAMESPACE}\">{paragraph}</w:hdr>"
+    )
+}
+
+fn prepend_to_header(header: &str, paragraph: &str) -> Result<String> {
+    let start = header
+        .find("<w:hdr")
+        .and_then(|start| header[start..].find('>').map(|end| start + end + 1))
+        .ok_or_else(|| OmniDocError::Other("DOCX header part has no <w:hdr>".to_string()))?;
+    Ok(format!(
+        "{}{paragraph}{}",
+        &header[..start],
+        &header[start..]
+    ))
+}
+
+/// Byte range of the body-level `w:sectPr`, the last one in the document.
+fn body_section(document: &str) -> Result<(usize, usize)> {
+    let start = document
+        .match_indices("<w:sectPr")
+        .map(|(index, _)| index)
+        .filter(|index| {
+            matches!(
+                document.as_bytes().get(index + "<w:sectPr".len()),
+                Some(b' ' | b'>' | b'/')
+            )
+        })
+        .last()
+        .ok_or_else(|| {
+            OmniDocError::Other("DOCX document has no section properties".to_string())
+        })?;
+    let open_end = start
+        + document[start..].find('>').ok_or_else(|| {
+            OmniDocError::Other("DOCX section properties are truncated".to_string())
+        })?
+        + 1;
+    if document[..open_end].ends_with("/>") {
+        return Ok((start, open_end));
+    }
+    let end = document[open_end..]
+        .find("</w:sectPr>")
+        .map(|index| open_end + index + "</w:sectPr>".len())
+        .ok_or_else(|| OmniDocError::Other("DOCX section properties are truncated".to_string()))?;
+    Ok((start, end))
+}
+
+/// Header type (`default`, `first`, `even`) → relationship id.
+fn header_references(section: &str) -> BTreeMap<String, String> {
+    let pattern = Regex::new(r"<w:headerReference\b[^>]*/>").expect("valid regex");
+    pattern
+        .find_iter(section)
+        .filter_map(|reference| {
+            let reference = reference.as_str();
+            Some((
+                attribute(reference, "w:type")?.to_string(),
+                attribute(reference, "r:id")?.to_string(),
+            ))
+        })
+        .collect()
+}
+
+/// Relationship id → target, relative to `word/`.
+fn relationship_targets(rels: &str) -> BTreeMap<String, String> {
+    let pattern = Regex::new(r"<Relationship\b[^>]*>").expect("valid regex");
+    pattern
+        .find_iter(rels)
+        .filter_map(|relationship| {
+            let relationship = relationship.as_str();
+            Some((
+                attribute(relationship, "Id")?.to_string(),
+                attribute(relationship, "Target")?.to_string(),
+            ))
+        })
+        .collect()
+}
+
+fn attribute<'a>(element: &'a str, name: &str) -> Option<&'a str> {
+    let marker = format!(" {name}=\"");
+    let start = element.find(&marker)? + marker.len();
+    let end = start + element[start..].find('"')?;
+    Some(&element[start..end])
+}
+
+fn insert_before_closing(xml: &str, closing: &str, content: &str) -> Result<String> {
+    let position = xml
+        .rfind(closing)
+        .ok_or_else(|| OmniDocError::Other(format!("DOCX part is missing {closing}")))?;
+    Ok(format!("{}{content}{}", &xml[..position], &xml[position..]))
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn part_text(parts: &BTreeMap<String, Vec<u8>>, name: &str) -> Result<String> {
+    let bytes = parts
+        .get(name)
+        .ok_or_else(|| OmniDocError::Other(format!("DOCX package is missing {name}")))?;
+    String::from_utf8(bytes.clone())
+        .map_err(|_| OmniDocError::Other(format!("DOCX part {name} is not UTF-8")))
+}
+
+fn read_parts(path: &Path) -> Result<BTreeMap<String, Vec<u8>>> {
+    let mut archive = ZipArchive::new(File::open(path)?)
+        .map_err(|error| OmniDocError::Other(format!("cannot read DOCX: {error}")))?;
+    let mut parts = BTreeMap::new();
+    for index in 0..archive.len() {
+        let mut entry = archive
+            .by_index(index)
+            .map_err(|error| OmniDocError::Other(format!("cannot read DOCX: {error}")))?;
+        if entry.is_dir() {
+            continue;
+        }
+        let mut content = Vec::new();
+        entry.read_to_end(&mut content)?;
+        parts.insert(entry.name().to_string(), content);
+    }
+    Ok(parts)
+}
+
+fn write_parts(path: &Path, parts: &BTreeMap<String, Vec<u8>>) -> Result<()> {
+    let staging = path.with_extension("docx.omnidoc-tmp");
+    let mut writer = ZipWriter::new(File::create(&staging)?);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    // Word expects the content types part first.
+    let ordered = parts
+        .get_key_value(CONTENT_TYPES_PART)
+        .into_iter()
+        .chain(parts.iter().filter(|(name, _)| *name != CONTENT_TYPES_PART));
+    for (name, content) in ordered {
+        writer
+            .start_file(name.as_str(), options)
+            .map_err(|error| OmniDocError::Other(format!("cannot write DOCX: {error}")))?;
+        writer.write_all(content)?;
+    }
+    writer
+        .finish()
+        .map_err(|error| OmniDocError::Other(format!("cannot write DOCX: {error}")))?;
+    std::fs::rename(&staging, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_parts, stamp_watermark, HEADER_PART};
+    use crate::extensions::ResolvedWatermark;
+    use std::fs::File;
+    use std::io::Write;
+    use std::path::Path;
+    use zip::write::SimpleFileOptions;
+    use zip::ZipWriter;
+
+    fn write_docx(path: &Path, section: &str, extra: &[(&str, &str)], rels: &str) {
+        let mut writer = ZipWriter::new(File::create(path).expect("DOCX file"));
+        let document = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?><w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><w:body><w:p/>{section}</w:body></w:document>"#
+        );
+        let rels = format!(
+            r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">{rels}</Relationships>"#
+        );
+        let mut parts = vec![
+            (
+                "[Content_Types].xml",
+                r#"<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"></Types>"#.to_string(),
+            ),
+            ("word/document.xml", document),
+            ("word/_rels/document.xml.rels", rels),
+        ];
+        parts.extend(
+            extra
+                .iter()
+                .map(|(name, content)| (*name, content.to_string())),
+        );
+        for (name, content) in parts {
+            writer
+                .start_file(name, SimpleFileOptions::default())
+                .expect("DOCX entry");
+            writer.write_all(content.as_bytes()).expect("DOCX content");
+        }
+        writer.finish().expect("DOCX archive");
+    }
+
+    fn text(path: &Path, part: &str) -> String {
+        String::from_utf8(read_parts(path).expect("parts")[part].clone()).expect("UTF-8")
+    }
+
+    fn watermark(first_page_only: bool) -> ResolvedWatermark {
+        ResolvedWatermark {
+            text: "CONFIDENTIAL – R&D".to_string(),
+            color: "CC0000".to_string(),
+            opacity: 0.5,
+            first_page_only,
+        }
+    }
+
+    #[test]
+    fn first_page_stamp_adds_a_header_part_and_title_page() {
+        let directory = tempfile::tempdir().expect("temporary DOCX");
+        let docx = directory.path().join("guide.docx");
+        write_docx(
+            &docx,
+            r#"<w:sectPr><w:pgSz w:w="11906"/><w:docGrid w:linePitch="360"/></w:sectPr>"#,
+            &[],
+            "",
+        );
+
+        stamp_watermark(&docx, &watermark(true)).expect("stamp DOCX");
+
+        let document = text(&docx, "word/document.xml");
+        assert!(document.contains(r#"w:type="first" r:id="rIdOmniDocWatermark"/><w:pgSz"#));
+        assert!(document.contains(r#"<w:titlePg/><w:docGrid"#));
+        let header = text(&docx, HEADER_PART);
+        assert!(header.contains("CONFIDENTIAL – R&amp;D"));
+        assert!(header.contains(r#"<w:color w:val="E68080"/>"#));
+        assert!(text(&docx, "[Content_Types].xml").contains("/word/omnidoc-watermark-header.xml"));
+        assert!(text(&docx, "word/_rels/document.xml.rels")
+            .contains(r#"Target="omnidoc-watermark-header.xml""#));
+    }
+
+    #[test]
+    fn reference_document_headers_keep_their_content() {
+        let directory = tempfile::tempdir().expect("temporary DOCX");
+        let docx = directory.path().join("guide.docx");
+        write_docx(
+            &docx,
+            r#"<w:sectPr><w:headerReference w:type="default" r:id="rId9"/></w:sectPr>"#,
+            &[(
+                "word/header1.xml",
+                r#"<w:hdr xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:p><w:r><w:t>Acme</w:t></w:r></w:p></w:hdr>"#,
+            )],
+            r#"<Relationship Id="rId9" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/header" Target="header1.xml"/>"#,
+        );
+
+        stamp_watermark(&docx, &watermark(false)).expect("stamp DOCX");
+
+        let header = text(&docx, "word/header1.xml");
+        let stamp = header.find("CONFIDENTIAL").expect("stamp paragraph");
+        assert!(stamp < header.find("Acme").expect("existing header"));
+        assert!(!read_parts(&docx).expect("parts").contains_key(HEADER_PART));
+        assert_eq!(
+            text(&docx, "word/document.xml")
+                .matches("<w:headerReference")
+                .count(),
+            1
+        );
+    }
+}
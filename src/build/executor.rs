@@ -1,6 +1,8 @@
 use crate::constants::pandoc;
 use crate::diagnostics::summarize_command_output;
 use crate::error::{OmniDocError, Result};
+use crate::terminal;
+use crate::warm;
 use std::ffi::OsString;
 use std::io::Write;
 use std::path::{Path, PathBuf};
@@ -91,6 +93,20 @@ impl BuildExecutor {
             return self.resolve_tectonic().map(|engine| engine.executable);
         }
 
+        let configured = self.tool_paths.get(tool).cloned().flatten();
+        let key = format!("{tool}\0{}", configured.as_deref().unwrap_or_default());
+        let fingerprint =
+            || warm::Fingerprint::new().with_value(std::env::var("PATH").unwrap_or_default());
+        let resolved = warm::TOOLS.get_or_load(&key, fingerprint, || self.find_tool(tool))?;
+        // A memoized tool may have been uninstalled since it was resolved.
+        if !Path::new(&resolved).exists() {
+            warm::TOOLS.invalidate(&key);
+            return warm::TOOLS.get_or_load(&key, fingerprint, || self.find_tool(tool));
+        }
+        Ok(resolved)
+    }
+
+    fn find_tool(&self, tool: &str) -> Result<String> {
         // 首先检查配置的路径
         if let Some(Some(path)) = self.tool_paths.get(tool) {
             if PathBuf::from(path).exists() {
@@ -171,6 +187,36 @@ impl BuildExecutor {
         }
     }
 
+    /// Markdown PDF 是否改走 Typst 引擎
+    pub fn uses_typst_pdf_engine(&self) -> bool {
+        uses_typst_pdf_engine(&self.tool_paths)
+    }
+
+    /// 解析 Typst 可执行文件。`latex_engine` 指向 typst 程序时优先使用，
+    /// 否则按 `tools.typst` 与 PATH 查找。
+    pub fn resolve_typst(&self) -> Result<String> {
+        if let Some(Some(configured)) = self.tool_paths.get("latex_engine") {
+            let configured = configured.trim();
+            if !configured.eq_ignore_ascii_case(pandoc::DEFAULT_ENGINE_TYPST)
+                && is_typst_program(configured)
+            {
+                let path = PathBuf::from(configured);
+                if path.is_file() {
+                    return Ok(configured.to_string());
+                }
+                return which::which(configured)
+                    .map(|resolved| resolved.to_string_lossy().to_string())
+                    .map_err(|_| {
+                        OmniDocError::Other(format!(
+                            "Configured Typst engine '{}' not found. Install it or update the latex_engine setting.",
+                            configured
+                        ))
+                    });
+            }
+        }
+        self.check_tool(pandoc::DEFAULT_ENGINE_TYPST)
+    }
+
     fn resolve_tectonic(&self) -> Result<ResolvedLatexEngine> {
         if let Some(Some(configured)) = self.tool_paths.get("tectonic") {
             let mut engine = self
@@ -309,7 +355,7 @@ impl BuildExecutor {
         }
 
         if verbose {
-            println!("Executing: {} {}", program, args.join(" "));
+            terminal::out(format!("Executing: {} {}\n", program, args.join(" ")));
         }
 
         let output = command.output().map_err(|e| {
@@ -331,11 +377,11 @@ impl BuildExecutor {
         if verbose {
             let stdout = String::from_utf8_lossy(&output.stdout);
             if !stdout.is_empty() {
-                print!("{}", stdout);
+                terminal::out(stdout);
             }
             let stderr = String::from_utf8_lossy(&output.stderr);
             if !stderr.is_empty() {
-                eprint!("{}", stderr);
+                terminal::err(stderr);
             }
         }
 
@@ -424,6 +470,24 @@ impl BuildExecutor {
     }
 }
 
+/// `--pdf-engine typst` or `tools.latex_engine = "typst"` (or a path to a
+/// typst executable) routes Markdown PDF builds through Pandoc's Typst writer.
+pub(crate) fn uses_typst_pdf_engine(
+    tool_paths: &std::collections::HashMap<String, Option<String>>,
+) -> bool {
+    tool_paths
+        .get("latex_engine")
+        .and_then(|value| value.as_deref())
+        .is_some_and(|value| is_typst_program(value.trim()))
+}
+
+fn is_typst_program(value: &str) -> bool {
+    Path::new(value)
+        .file_stem()
+        .and_then(|name| name.to_str())
+        .is_some_and(|stem| stem.eq_ignore_ascii_case(pandoc::DEFAULT_ENGINE_TYPST))
+}
+
 fn bundled_tectonic_candidates() -> Vec<PathBuf> {
     let Ok(executable) = std::env::current_exe() else {
         return Vec::new();
@@ -507,7 +571,9 @@ fn verify_tectonic_program(path: &Path) -> std::result::Result<(), String> {
 
 #[cfg(test)]
 mod tests {
-    use super::{verify_tectonic_program, BuildExecutor, LatexEnginePreference};
+    use super::{
+        uses_typst_pdf_engine, verify_tectonic_program, BuildExecutor, LatexEnginePreference,
+    };
     use std::collections::HashMap;
 
     #[test]
@@ -558,6 +624,35 @@ mod tests {
         assert!(err.to_string().contains("__omnidoc_missing_tectonic__"));
     }
 
+    #[test]
+    fn typst_pdf_engine_is_selected_by_keyword_or_executable_name() {
+        let selected = |value: &str| {
+            uses_typst_pdf_engine(&HashMap::from([(
+                "latex_engine".to_string(),
+                Some(value.to_string()),
+            )]))
+        };
+
+        assert!(selected("typst"));
+        assert!(selected("Typst"));
+        assert!(selected("/opt/typst/bin/typst"));
+        assert!(!selected("xelatex"));
+        assert!(!selected("tectonic"));
+        assert!(!uses_typst_pdf_engine(&HashMap::new()));
+
+        let executor = BuildExecutor::new(HashMap::from([
+            ("latex_engine".to_string(), Some("typst".to_string())),
+            (
+                "typst".to_string(),
+                Some("__omnidoc_missing_typst__".to_string()),
+            ),
+        ]));
+        let err = executor
+            .resolve_typst()
+            .expect_err("missing configured Typst should fail");
+        assert!(err.to_string().contains("__omnidoc_missing_typst__"));
+    }
+
     #[cfg(unix)]
     #[test]
     fn rejects_a_non_tectonic_program_with_a_tectonic_role() {
@@ -1,4 +1,5 @@
 use crate::build::executor::{BuildExecutor, LatexEnginePreference, ResolvedLatexEngine};
+use crate::build::pandoc_policy::PandocOutputKind;
 use crate::build::pipeline::{BuildPipeline, ProjectType};
 use crate::build::tectonic;
 use crate::config::MergedConfig;
@@ -6,7 +7,8 @@ use crate::diagnostics::summarize_latex_log;
 use crate::doc::services::FigureService;
 use crate::error::{OmniDocError, Result};
 use crate::latex_recorder;
-use crate::project_tools::LATEX_INPUT_DEPFILE;
+use crate::project_tools::{output_scratch_dir, LATEX_INPUT_DEPFILE};
+use crate::terminal;
 use crate::utils::directories::data_local_dir;
 use crate::utils::fs;
 use regex::Regex;
@@ -145,17 +147,17 @@ impl LatexBuilder {
 
         if sources.is_empty() {
             if verbose {
-                println!("No referenced generated figures found.");
+                terminal::out("No referenced generated figures found.\n");
             }
             return Ok(());
         }
 
         if verbose {
-            println!(
-                "Generating {} referenced figure source(s) into {}",
+            terminal::out(format!(
+                "Generating {} referenced figure source(s) into {}\n",
                 sources.len(),
                 figures_dir.display()
-            );
+            ));
         }
 
         let figure_service = FigureService::new(self.config.clone())?;
@@ -452,7 +454,7 @@ impl LatexBuilder {
 
         for pass in 1..=max_passes {
             if verbose {
-                println!("LaTeX engine pass {}/{}", pass, max_passes);
+                terminal::out(format!("LaTeX engine pass {}/{}\n", pass, max_passes));
             }
 
             self.executor
@@ -461,7 +463,9 @@ impl LatexBuilder {
 
             if current_hashes.is_empty() {
                 if verbose {
-                    println!("No tracked LaTeX auxiliary files found; stopping after one pass.");
+                    terminal::out(
+                        "No tracked LaTeX auxiliary files found; stopping after one pass.\n",
+                    );
                 }
                 return Ok(());
             }
@@ -472,7 +476,10 @@ impl LatexBuilder {
                 .unwrap_or(false)
             {
                 if verbose {
-                    println!("LaTeX auxiliary files stabilized after {} passes.", pass);
+                    terminal::out(format!(
+                        "LaTeX auxiliary files stabilized after {} passes.\n",
+                        pass
+                    ));
                 }
                 return Ok(());
             }
@@ -481,10 +488,10 @@ impl LatexBuilder {
         }
 
         if verbose {
-            println!(
-                "Reached max LaTeX passes ({}); continuing with latest output.",
+            terminal::out(format!(
+                "Reached max LaTeX passes ({}); continuing with latest output.\n",
                 max_passes
-            );
+            ));
         }
         Ok(())
     }
@@ -668,16 +675,16 @@ impl BuildPipeline for LatexBuilder {
         self.ensure_tectonic_project_compatible(project_path, &latex_engine)?;
         self.ensure_bibliography_tools(project_path, &latex_engine, &latex_backend)?;
 
-        let cache_dir = project_path.join(".omnidoc-cache");
-        fs::create_dir_all(&cache_dir)?;
-        let depfile = cache_dir.join(LATEX_INPUT_DEPFILE);
+        let scratch_dir = output_scratch_dir(project_path, &self.config, PandocOutputKind::Pdf);
+        fs::create_dir_all(&scratch_dir)?;
+        let depfile = scratch_dir.join(LATEX_INPUT_DEPFILE);
         if depfile.exists() {
             fs::remove_file(&depfile)?;
         }
         let mut tectonic_recording = None;
 
         let result = if use_tectonic {
-            let rules = cache_dir.join("tectonic-inputs.make");
+            let rules = scratch_dir.join("tectonic-inputs.make");
             if rules.exists() {
                 fs::remove_file(&rules)?;
             }
@@ -715,7 +722,7 @@ impl BuildPipeline for LatexBuilder {
                 self.find_latex_log_summary(project_path, &outdir, &entry_file, &target_name);
 
             if verbose && !use_tectonic && latex_backend == "latexmk" {
-                println!("⚠ Build failed, attempting to clean...");
+                terminal::out("⚠ Build failed, attempting to clean...\n");
             }
             if !use_tectonic && latex_backend == "latexmk" {
                 let jobname_arg = format!("-jobname={}", target_name);
@@ -773,7 +780,7 @@ impl BuildPipeline for LatexBuilder {
             let alt_output = project_path.join(format!("{}.pdf", target_name));
             if fs::exists(&alt_output) {
                 if verbose {
-                    println!("✓ Built PDF: {}", alt_output.display());
+                    terminal::out(format!("✓ Built PDF: {}\n", alt_output.display()));
                 }
             } else {
                 return Err(OmniDocError::Project(format!(
@@ -784,7 +791,7 @@ impl BuildPipeline for LatexBuilder {
             }
         } else {
             if verbose {
-                println!("✓ Built PDF: {}", output_file.display());
+                terminal::out(format!("✓ Built PDF: {}\n", output_file.display()));
             }
         }
 
@@ -0,0 +1,114 @@
+//! Figure handling for text and XML outputs that cannot embed images.
+//!
+//! GitHub Markdown, JATS and DocBook reference figures by path, so Pandoc
+//! extracts every figure (rendered diagrams from the media bag as well as
+//! linked local images) into `<artifact>-media/` beside the output. Pandoc
+//! writes the extraction directory into each link verbatim; OmniDoc passes
+//! the same absolute directory it passes for `-o` and afterwards rewrites it
+//! to the relative directory name, so the artifact renders or ingests from
+//! any checkout or release archive.
+
+use crate::constants::pandoc;
+use crate::error::Result;
+use crate::utils::fs;
+use std::path::{Path, PathBuf};
+
+/// Media directory written next to an artifact: `cli.md` → `cli-media/`.
+pub(crate) fn media_dir(artifact: &Path) -> PathBuf {
+    let stem = artifact
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    artifact.with_file_name(format!("{stem}-media"))
+}
+
+/// 清理旧的媒体目录并让 Pandoc 把图片提取到其中
+pub(crate) fn prepare_media_dir(options: &mut Vec<String>, media_dir: &Path) -> Result<()> {
+    // Figures from a previous build would otherwise be published with
+    // the artifact even after the document stopped referencing them.
+    if media_dir.exists() {
+        fs::remove_dir_all(media_dir)?;
+    }
+    options.push(format!(
+        "{}={}",
+        pandoc::FLAG_EXTRACT_MEDIA,
+        media_dir.to_string_lossy()
+    ));
+    Ok(())
+}
+
+/// 将文档中的绝对媒体路径改写为相对 `<artifact>-media/` 的链接
+///
+/// Returns the number of rewritten references.
+pub(crate) fn relativize_media_links(document: &Path, media_dir: &Path) -> Result<usize> {
+    let Some(name) = media_dir.file_name() else {
+        return Ok(0);
+    };
+    let content = fs::read_to_string(document)?;
+    let absolute = format!("{}/", media_dir.to_string_lossy());
+    let count = content.matches(&absolute).count();
+    if count > 0 {
+        let relative = format!("{}/", name.to_string_lossy());
+        fs::write(document, content.replace(&absolute, &relative))?;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{media_dir, prepare_media_dir, relativize_media_links};
+    use std::path::Path;
+
+    #[test]
+    fn media_directories_are_named_after_the_artifact() {
+        assert_eq!(
+            media_dir(Path::new("build/readme.md")),
+            Path::new("build/readme-media")
+        );
+        assert_eq!(
+            media_dir(Path::new("build/paper-jats.xml")),
+            Path::new("build/paper-jats-media")
+        );
+    }
+
+    #[test]
+    fn stale_media_is_removed_before_extraction() {
+        let root = tempfile::tempdir().expect("root");
+        let media = media_dir(&root.path().join("readme.md"));
+        std::fs::create_dir_all(&media).expect("media");
+        std::fs::write(media.join("old.svg"), "<svg/>").expect("stale figure");
+        let mut options = Vec::new();
+
+        prepare_media_dir(&mut options, &media).expect("prepare");
+
+        assert!(!media.exists());
+        assert_eq!(
+            options,
+            [format!("--extract-media={}", media.to_string_lossy())]
+        );
+    }
+
+    #[test]
+    fn extracted_figure_links_become_relative_to_the_document() {
+        let root = tempfile::tempdir().expect("root");
+        let media = root.path().join("readme-media");
+        let document = root.path().join("readme.md");
+        std::fs::write(
+            &document,
+            format!(
+                "![Flow]({dir}/flow.svg)\n\n<img src=\"{dir}/images/board.png\" width=\"50%\" />\n\n[docs](docs/readme-media/x)\n",
+                dir = media.display()
+            ),
+        )
+        .expect("document");
+
+        assert_eq!(
+            relativize_media_links(&document, &media).expect("rewrite"),
+            2
+        );
+        assert_eq!(
+            std::fs::read_to_string(&document).expect("document"),
+            "![Flow](readme-media/flow.svg)\n\n<img src=\"readme-media/images/board.png\" width=\"50%\" />\n\n[docs](docs/readme-media/x)\n"
+        );
+    }
+}
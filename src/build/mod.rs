@@ -1,9 +1,14 @@
+pub(crate) mod docx;
 pub mod executor;
 pub mod latex;
+pub(crate) mod media;
 pub mod pandoc;
 pub(crate) mod pandoc_policy;
 pub mod pipeline;
+pub(crate) mod search;
+pub(crate) mod site;
 pub mod source_map;
+pub(crate) mod synctex;
 pub mod tectonic;
 
 pub use executor::BuildExecutor;
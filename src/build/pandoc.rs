@@ -1,20 +1,29 @@
-use crate::build::executor::{BuildExecutor, LatexEnginePreference};
+use crate::build::docx;
+use crate::build::executor::{BuildExecutor, LatexEnginePreference, ResolvedLatexEngine};
+use crate::build::media;
 use crate::build::pandoc_policy::PandocOutputKind;
 use crate::build::pipeline::{BuildPipeline, ProjectType};
+use crate::build::search;
+use crate::build::site;
 use crate::build::source_map::locate_markdown_error;
 use crate::build::tectonic;
 use crate::config::MergedConfig;
 use crate::constants::pandoc;
 use crate::error::{OmniDocError, Result};
 use crate::extensions::{
-    acquire_extension_store_read_locks, materialize_theme_tokens, plugin_filters_for_output,
-    resolve_selected_theme, ExtensionStoreReadLocks, GeneratedThemeAssets, ResolvedTheme,
+    acquire_extension_store_read_locks, materialize_theme_tokens, materialize_watermark,
+    plugin_filters_for_output, resolve_selected_theme, resolve_watermark, ExtensionStoreReadLocks,
+    GeneratedThemeAssets, GeneratedWatermarkAssets, ResolvedTheme,
 };
+use crate::glossary;
+use crate::index;
 use crate::latex_recorder;
 use crate::project_tools::{
-    filter_depfile_metadata_key, filter_depfile_name, INCLUDE_CODE_DEPFILE, INCLUDE_DEPFILE,
-    LATEX_INPUT_DEPFILE,
+    document_lang, filter_depfile_metadata_key, filter_depfile_name, fragment_cache_dir,
+    output_scratch_dir, render_cache_dir, DIAGRAM_RENDER_LOG, INCLUDE_CODE_DEPFILE,
+    INCLUDE_DEPFILE, INCLUDE_FRAGMENT_LOG, LATEX_INPUT_DEPFILE, SYNCTEX_DIR,
 };
+use crate::terminal;
 use crate::utils::directories::data_local_dir;
 use crate::utils::fs;
 use std::collections::{BTreeMap, BTreeSet};
@@ -28,6 +37,11 @@ use std::path::{Path, PathBuf};
 const LATEX_HEADER_METADATA_PREFIX: &str = "omnidoc-theme-latex-header";
 const THEME_LATEX_HEADER_OFFSET: usize = 1000;
 const USER_LATEX_HEADER_OFFSET: usize = 2000;
+// The watermark follows every theme header so its colors win, and stays
+// below user headers, which may restyle it.
+const WATERMARK_LATEX_HEADER_ORDINAL: usize = 1999;
+// Glossary entries handed to glossary.lua, written to the output scratch dir.
+const GLOSSARY_METADATA_FILE: &str = "glossary.json";
 const STANDALONE_CJK_FONT_DEFAULTS: [(&str, &str); 3] = [
     ("CJKmainfont", "Noto Serif CJK SC"),
     ("CJKsansfont", "Noto Sans CJK SC"),
@@ -82,7 +96,7 @@ impl PandocBuilder {
     fn theme_for_output(&self, output_kind: PandocOutputKind) -> Option<&ResolvedTheme> {
         self.theme
             .as_ref()
-            .filter(|theme| theme.supports_output(output_kind.config_key()))
+            .filter(|theme| theme.supports_output(output_kind.family_key()))
     }
 
     fn theme_latex_search_paths(&self, output_kind: PandocOutputKind) -> Vec<PathBuf> {
@@ -185,15 +199,17 @@ impl PandocBuilder {
             options.push(format!("omnidocPath:{}", omnidoc_path.display()));
         }
 
-        options.push(pandoc::FLAG_FILTER.to_string());
-        let crossref = self
-            .executor
-            .check_tool(pandoc::DEFAULT_PLUGIN_CROSSREF)
-            .unwrap_or_else(|_| pandoc::DEFAULT_PLUGIN_CROSSREF.to_string());
-        options.push(crossref);
+        if output_kind.uses_pandoc_crossref() {
+            options.push(pandoc::FLAG_FILTER.to_string());
+            let crossref = self
+                .executor
+                .check_tool(pandoc::DEFAULT_PLUGIN_CROSSREF)
+                .unwrap_or_else(|_| pandoc::DEFAULT_PLUGIN_CROSSREF.to_string());
+            options.push(crossref);
+        }
         options.push("--citeproc".to_string());
 
-        if output_kind == PandocOutputKind::Pdf {
+        if output_kind.uses_latex_pdf_engine() {
             options.push(pandoc::FLAG_PDF_ENGINE.to_string());
             let latex_engine = self
                 .executor
@@ -219,6 +235,10 @@ impl PandocBuilder {
                 }
             }
         }
+        if output_kind == PandocOutputKind::TypstPdf {
+            options.push(pandoc::FLAG_PDF_ENGINE.to_string());
+            options.push(self.executor.resolve_typst()?);
+        }
 
         options.push(pandoc::FLAG_SYNTAX_HIGHLIGHTING.to_string());
         let syntax_highlighting = self
@@ -256,9 +276,16 @@ impl PandocBuilder {
             options.push(pandoc::FLAG_STANDALONE.to_string());
         }
 
-        if self.config.pandoc_toc {
+        // The site navigation sidebar is rendered from `table-of-contents`,
+        // so chunked HTML always asks Pandoc for one.
+        if self.config.pandoc_toc || output_kind == PandocOutputKind::HtmlSite {
             options.push("--toc".to_string());
         }
+        if output_kind == PandocOutputKind::HtmlSite {
+            if let Some(level) = self.config.pandoc_site_split_level {
+                options.push(format!("--split-level={level}"));
+            }
+        }
 
         if self.config.pandoc_embed_resources && output_kind.supports_embed_resources() {
             options.push(pandoc::FLAG_EMBED_RESOURCES.to_string());
@@ -282,10 +309,21 @@ impl PandocBuilder {
             .map(|theme| materialize_theme_tokens(theme, project_path))
             .transpose()?
             .unwrap_or_default();
+        let watermark_assets = self
+            .config
+            .watermark
+            .as_ref()
+            .map(|watermark| {
+                resolve_watermark(watermark)
+                    .and_then(|watermark| materialize_watermark(&watermark, project_path))
+            })
+            .transpose()?
+            .unwrap_or_default();
 
         self.push_template(&mut options, output_kind);
         self.push_default_latex_headers(&mut options, output_kind, &omnidoc_lib);
         self.push_theme_latex_headers(&mut options, output_kind, &generated_theme_assets);
+        self.push_theme_typst_header(&mut options, output_kind, &generated_theme_assets);
         self.push_css(
             &mut options,
             output_kind,
@@ -293,6 +331,7 @@ impl PandocBuilder {
             profile,
             &generated_theme_assets,
         );
+        self.push_watermark(&mut options, output_kind, &watermark_assets);
         self.push_format_assets(&mut options, output_kind, &omnidoc_lib);
         self.push_math_output(&mut options, output_kind);
         self.push_metadata(&mut options, output_kind, &omnidoc_lib, profile);
@@ -321,7 +360,7 @@ impl PandocBuilder {
             options.push(path.to_string_lossy().to_string());
         }
         for filter in
-            plugin_filters_for_output(project_path, &self.config, output_kind.config_key())?
+            plugin_filters_for_output(project_path, &self.config, output_kind.family_key())?
         {
             if added.insert(filter.script.clone()) {
                 options.push("--lua-filter".to_string());
@@ -386,6 +425,14 @@ impl PandocBuilder {
             return;
         }
         let mut ordinal = THEME_LATEX_HEADER_OFFSET;
+        // Theme LaTeX headers style article pages and clash with Beamer's
+        // frames; decks only take the generated color theme.
+        if output_kind == PandocOutputKind::Beamer {
+            if let Some(header) = generated.beamer_header.as_deref() {
+                Self::push_latex_header_metadata(options, ordinal + 1, &header.to_string_lossy());
+            }
+            return;
+        }
         if let Some(header) = generated.latex_header.as_deref() {
             ordinal += 1;
             Self::push_latex_header_metadata(options, ordinal, &header.to_string_lossy());
@@ -398,13 +445,62 @@ impl PandocBuilder {
         }
     }
 
+    /// DOCX outputs are stamped after the build; see [`docx::stamp_watermark`].
+    fn push_watermark(
+        &self,
+        options: &mut Vec<String>,
+        output_kind: PandocOutputKind,
+        generated: &GeneratedWatermarkAssets,
+    ) {
+        if output_kind.uses_latex_defaults() {
+            if let Some(header) = generated.latex_header.as_deref() {
+                Self::push_latex_header_metadata(
+                    options,
+                    WATERMARK_LATEX_HEADER_ORDINAL,
+                    &header.to_string_lossy(),
+                );
+            }
+        } else if output_kind.uses_typst_defaults() {
+            if let Some(header) = generated.typst_header.as_deref() {
+                options.push(pandoc::FLAG_INCLUDE_IN_HEADER.to_string());
+                options.push(header.to_string_lossy().to_string());
+            }
+        } else if output_kind.is_html()
+            || matches!(
+                output_kind,
+                PandocOutputKind::Epub | PandocOutputKind::RevealJs
+            )
+        {
+            if let Some(css) = generated.css.as_ref() {
+                push_existing_css(options, css.clone());
+            }
+        }
+    }
+
+    fn push_theme_typst_header(
+        &self,
+        options: &mut Vec<String>,
+        output_kind: PandocOutputKind,
+        generated: &GeneratedThemeAssets,
+    ) {
+        if !output_kind.uses_typst_defaults() {
+            return;
+        }
+        if let Some(header) = generated.typst_header.as_deref() {
+            options.push(pandoc::FLAG_INCLUDE_IN_HEADER.to_string());
+            options.push(header.to_string_lossy().to_string());
+        }
+    }
+
     fn push_default_latex_headers(
         &self,
         options: &mut Vec<String>,
         output_kind: PandocOutputKind,
         omnidoc_lib: &str,
     ) {
-        if !output_kind.uses_latex_defaults() {
+        // Beamer renders semantic blocks as native beamer blocks and the emoji
+        // filter only rewrites for the LaTeX writer.
+        if !output_kind.uses_latex_defaults() || output_kind == PandocOutputKind::Beamer {
             return;
         }
         let filters = output_kind.filters(&self.config);
@@ -431,7 +527,18 @@ impl PandocBuilder {
                     }
                     PandocOutputKind::Html => theme.resources.html_template.as_ref(),
                     PandocOutputKind::Epub => theme.resources.epub_template.as_ref(),
-                    PandocOutputKind::Docx | PandocOutputKind::Pptx => None,
+                    PandocOutputKind::Docx
+                    | PandocOutputKind::Pptx
+                    | PandocOutputKind::Beamer
+                    | PandocOutputKind::RevealJs
+                    | PandocOutputKind::Gfm
+                    | PandocOutputKind::Plain
+                    | PandocOutputKind::Man
+                    | PandocOutputKind::Jats
+                    | PandocOutputKind::DocBook
+                    | PandocOutputKind::Typst
+                    | PandocOutputKind::TypstPdf
+                    | PandocOutputKind::HtmlSite => None,
                 });
         let theme_template = theme_template.map(|path| path.to_string_lossy().to_string());
         let template = match output_kind {
@@ -453,7 +560,41 @@ impl PandocBuilder {
                 .clone()
                 .or_else(|| self.config.pandoc_template.clone())
                 .or(theme_template),
-            PandocOutputKind::Docx | PandocOutputKind::Pptx => None,
+            // Chunked pages need the navigation variables that only the
+            // bundled site template renders; a theme's single-page HTML
+            // template would silently drop them.
+            PandocOutputKind::HtmlSite => self
+                .config
+                .pandoc_site_template
+                .clone()
+                .or_else(|| self.library_file(pandoc::LIB_PANDOC_SITE_TEMPLATE)),
+            // Typst has no theme template slot; the shared omnidoc-libs
+            // template carries the house style when nothing is configured.
+            PandocOutputKind::Typst | PandocOutputKind::TypstPdf => self
+                .config
+                .pandoc_typst_template
+                .clone()
+                .or_else(|| self.config.pandoc_template.clone())
+                .or_else(|| self.library_file(pandoc::LIB_PANDOC_TYPST_TEMPLATE)),
+            // Pandoc's DocBook writer reduces authors to names; the library
+            // template renders the affiliations and keywords publishers need.
+            PandocOutputKind::DocBook => self
+                .config
+                .pandoc_template
+                .clone()
+                .or_else(|| self.library_file(pandoc::LIB_PANDOC_DOCBOOK_TEMPLATE)),
+            // Slide decks, text outputs and JATS use Pandoc's own templates;
+            // a document template would not know about frames, man sections
+            // or JATS front matter.
+            // Projects override them through `[pandoc.format_options]`.
+            PandocOutputKind::Docx
+            | PandocOutputKind::Pptx
+            | PandocOutputKind::Beamer
+            | PandocOutputKind::RevealJs
+            | PandocOutputKind::Gfm
+            | PandocOutputKind::Plain
+            | PandocOutputKind::Man
+            | PandocOutputKind::Jats => None,
         };
 
         if let Some(template) = template {
@@ -462,6 +603,46 @@ impl PandocBuilder {
         }
     }
 
+    /// 为启用搜索的 HTML 输出注入搜索组件，返回索引文件名
+    fn prepare_search_widget(
+        &self,
+        options: &mut Vec<String>,
+        output_kind: PandocOutputKind,
+        scratch_dir: &Path,
+        target_name: &str,
+    ) -> Result<Option<String>> {
+        if !search::search_enabled(&self.config, self.theme.as_ref(), output_kind) {
+            return Ok(None);
+        }
+        let widget = join_portable_relative(
+            &self.get_omnidoc_lib_path(),
+            pandoc::LIB_PANDOC_SEARCH_WIDGET,
+        );
+        let widget = fs::read_to_string(&widget).map_err(|_| {
+            OmniDocError::Config(format!(
+                "search is enabled but the library search widget is missing: {}; run `omnidoc lib update`",
+                widget.display()
+            ))
+        })?;
+        let index_name = if output_kind == PandocOutputKind::HtmlSite {
+            search::SITE_SEARCH_INDEX.to_string()
+        } else {
+            search::page_index_name(target_name)
+        };
+        let include = scratch_dir.join("search-widget.html");
+        fs::write(&include, search::widget_include(&widget, &index_name))?;
+        options.push(pandoc::FLAG_INCLUDE_AFTER_BODY.to_string());
+        options.push(include.to_string_lossy().to_string());
+        Ok(Some(index_name))
+    }
+
+    fn library_file(&self, relative: &str) -> Option<String> {
+        let library = join_portable_relative(&self.get_omnidoc_lib_path(), relative);
+        library
+            .exists()
+            .then(|| library.to_string_lossy().to_string())
+    }
+
     fn push_css(
         &self,
         options: &mut Vec<String>,
@@ -470,7 +651,11 @@ impl PandocBuilder {
         profile: &PandocCommandProfile,
         generated: &GeneratedThemeAssets,
     ) {
-        if !matches!(output_kind, PandocOutputKind::Html | PandocOutputKind::Epub) {
+        if output_kind == PandocOutputKind::RevealJs {
+            self.push_revealjs_css(options, omnidoc_lib, generated);
+            return;
+        }
+        if !output_kind.is_html() && output_kind != PandocOutputKind::Epub {
             return;
         }
 
@@ -479,6 +664,12 @@ impl PandocBuilder {
             options.push(pandoc::FLAG_CSS.to_string());
             options.push(base_css.to_string_lossy().to_string());
         }
+        if output_kind == PandocOutputKind::HtmlSite {
+            push_existing_css(
+                options,
+                join_portable_relative(omnidoc_lib, pandoc::LIB_PANDOC_CSS_SITE),
+            );
+        }
 
         if let Some(css) = generated.css.as_ref() {
             push_existing_css(options, css.clone());
@@ -513,6 +704,24 @@ impl PandocBuilder {
         }
     }
 
+    /// reveal.js ships its own slide theme, so document stylesheets (the
+    /// base CSS, `pandoc.css`, and theme HTML CSS) are not applied to decks.
+    /// Extra stylesheets belong in `[pandoc.format_options] revealjs`.
+    fn push_revealjs_css(
+        &self,
+        options: &mut Vec<String>,
+        omnidoc_lib: &str,
+        generated: &GeneratedThemeAssets,
+    ) {
+        push_existing_css(
+            options,
+            join_portable_relative(omnidoc_lib, pandoc::LIB_PANDOC_CSS_REVEALJS),
+        );
+        if let Some(css) = generated.revealjs_css.as_ref() {
+            push_existing_css(options, css.clone());
+        }
+    }
+
     fn push_format_assets(
         &self,
         options: &mut Vec<String>,
@@ -574,7 +783,12 @@ impl PandocBuilder {
     }
 
     fn push_math_output(&self, options: &mut Vec<String>, output_kind: PandocOutputKind) {
-        if !matches!(output_kind, PandocOutputKind::Html | PandocOutputKind::Epub) {
+        if !output_kind.is_html()
+            && !matches!(
+                output_kind,
+                PandocOutputKind::Epub | PandocOutputKind::RevealJs
+            )
+        {
             return;
         }
         if output_kind.has_explicit_html_math(&self.config) {
@@ -659,6 +873,18 @@ impl PandocBuilder {
             options.push(format!("lang={lang}"));
         }
 
+        // audience.lua only acts when the variant key is present, so a
+        // variant with an empty audience list still hides conditional content.
+        if let Some(variant) = self.config.variant.as_deref() {
+            options.push(pandoc::FLAG_META_SHORT.to_string());
+            options.push(format!("omnidoc-variant={variant}"));
+            options.push(pandoc::FLAG_META_SHORT.to_string());
+            options.push(format!(
+                "omnidoc-audiences={}",
+                self.config.variant_audiences.join(",")
+            ));
+        }
+
         // Publication title and author belong to the document metadata (or an
         // explicit metadata file). The global author is a project-template
         // default, while `target` is an artifact name; passing either with
@@ -669,6 +895,38 @@ impl PandocBuilder {
         }
     }
 
+    /// Let the LaTeX recorder sort `\index` entries between engine passes;
+    /// Pandoc itself never runs an index processor.
+    fn push_index_environment(
+        &self,
+        project_path: &Path,
+        engine: &ResolvedLatexEngine,
+        environment: &mut Vec<(OsString, OsString)>,
+    ) -> Result<()> {
+        let lang = document_lang(project_path, &self.config);
+        let program =
+            index::IndexProgram::resolve(self.config.index_program.as_deref(), lang.as_deref())
+                .ok_or_else(|| {
+                    OmniDocError::Config(format!(
+                        "Unsupported index.program '{}'; choose makeindex, xindy, or zhmakeindex",
+                        self.config.index_program.as_deref().unwrap_or_default()
+                    ))
+                })?;
+        if engine.is_tectonic() {
+            terminal::warning(format!(
+                "Tectonic cannot run {}; the PDF index stays empty. Use xelatex, lualatex or pdflatex for indexed documents",
+                program.tool()
+            ));
+            return Ok(());
+        }
+        let executable = self.executor.check_tool(program.tool())?;
+        environment.extend(latex_recorder::index_environment(
+            program,
+            Path::new(&executable),
+        ));
+        Ok(())
+    }
+
     fn push_theme_metadata_defaults(
         &self,
         options: &mut Vec<String>,
@@ -741,7 +999,7 @@ fn push_existing_css(options: &mut Vec<String>, css_path: PathBuf) {
 
 fn push_depfile_metadata(
     options: &mut Vec<String>,
-    cache_dir: &Path,
+    scratch_dir: &Path,
     keys: &mut BTreeMap<String, String>,
     names: &mut BTreeMap<String, String>,
     key: String,
@@ -763,10 +1021,75 @@ fn push_depfile_metadata(
     keys.insert(key.clone(), origin.clone());
     names.insert(file.clone(), origin);
     options.push(pandoc::FLAG_METADATA.to_string());
-    options.push(format!("{}={}", key, cache_dir.join(file).display()));
+    options.push(format!("{}={}", key, scratch_dir.join(file).display()));
     Ok(())
 }
 
+/// Digest of everything besides a chapter's own text that shapes its cached
+/// AST: the reader format and reader options. Fragments hold reader output
+/// only, before any Lua filter runs, so filter edits keep them valid.
+fn fragment_salt(options: &[String]) -> String {
+    const READER_FLAGS: &[&str] = &[
+        "--abbreviations",
+        "--default-image-extension",
+        "--file-scope",
+        "--indented-code-classes",
+        "--preserve-tabs",
+        "--strip-comments",
+        "--tab-stop",
+        "--track-changes",
+    ];
+    let mut hasher = blake3::Hasher::new();
+    let mut field = |value: &[u8]| {
+        hasher.update(&(value.len() as u64).to_le_bytes());
+        hasher.update(value);
+    };
+    for (index, option) in options.iter().enumerate() {
+        let next = options.get(index + 1);
+        if option == pandoc::FLAG_FROM {
+            field(option.as_bytes());
+            field(next.map(String::as_bytes).unwrap_or_default());
+        } else if READER_FLAGS.iter().any(|flag| {
+            option
+                .split_once('=')
+                .map_or(option.as_str(), |(flag, _)| flag)
+                == *flag
+        }) {
+            // Over-hashing a boolean flag's neighbour only costs a re-parse.
+            field(option.as_bytes());
+            if !option.contains('=') {
+                field(next.map(String::as_bytes).unwrap_or_default());
+            }
+        }
+    }
+    hasher.finalize().to_hex().to_string()
+}
+
+/// Digest of the renderer setup behind cached diagram images: the Lua filter
+/// chain and the configured tool paths. Tool versions are not probed per
+/// build, so upgrading PlantUML or Graphviz in place needs
+/// `omnidoc cache prune --all`.
+fn render_salt(options: &[String], config: &MergedConfig) -> String {
+    let mut hasher = blake3::Hasher::new();
+    let mut field = |value: &[u8]| {
+        hasher.update(&(value.len() as u64).to_le_bytes());
+        hasher.update(value);
+    };
+    for (index, option) in options.iter().enumerate() {
+        if option == "--lua-filter" {
+            let script = options
+                .get(index + 1)
+                .map(String::as_str)
+                .unwrap_or_default();
+            field(&std::fs::read(script).unwrap_or_default());
+        }
+    }
+    let tool_paths = config.tool_paths.iter().collect::<BTreeMap<_, _>>();
+    field(format!("{tool_paths:?}").as_bytes());
+    field(format!("{:?}", config.pandoc_python_path).as_bytes());
+    hasher.finalize().to_hex().to_string()
+}
+
 fn join_portable_relative(root: &str, relative: &str) -> PathBuf {
     relative
         .split(['/', '\\'])
@@ -817,22 +1140,62 @@ impl BuildPipeline for PandocBuilder {
         });
 
         let output_kind = PandocOutputKind::from_config(&self.config)?;
-        let output_file = outdir.join(format!("{}.{}", target_name, output_kind.extension()));
+        let output_file = outdir.join(output_kind.artifact_name(&target_name));
+        let scratch_dir = output_scratch_dir(project_path, &self.config, output_kind);
+        fs::create_dir_all(&scratch_dir)?;
+        // chunkedhtml writes a whole directory and refuses to reuse one, so
+        // the site is staged in scratch space and swapped in after success.
+        let site_staging =
+            (output_kind == PandocOutputKind::HtmlSite).then(|| scratch_dir.join("site"));
+        if let Some(staging) = site_staging.as_deref().filter(|staging| staging.exists()) {
+            fs::remove_dir_all(staging)?;
+        }
         let mut options = self.build_command_options(
             project_path,
             &entry_file,
-            &output_file,
+            site_staging.as_deref().unwrap_or(&output_file),
             output_kind,
             &PandocCommandProfile::Project,
         )?;
-        let cache_dir = project_path.join(".omnidoc-cache");
-        fs::create_dir_all(&cache_dir)?;
+        let site_stylesheets = site_staging
+            .as_ref()
+            .map(|_| site::localize_stylesheets(&mut options, project_path))
+            .unwrap_or_default();
+        let search_index =
+            self.prepare_search_widget(&mut options, output_kind, &scratch_dir, &target_name)?;
+        let media_dir = output_kind
+            .extracts_media()
+            .then(|| media::media_dir(&output_file));
+        if let Some(media_dir) = media_dir.as_deref() {
+            media::prepare_media_dir(&mut options, media_dir)?;
+        }
         for (key, file) in [
             ("omnidoc-include-depfile", INCLUDE_DEPFILE),
             ("omnidoc-include-code-depfile", INCLUDE_CODE_DEPFILE),
         ] {
             options.push(pandoc::FLAG_METADATA.to_string());
-            options.push(format!("{}={}", key, cache_dir.join(file).display()));
+            options.push(format!("{}={}", key, scratch_dir.join(file).display()));
+        }
+        if !self.config.glossary_files.is_empty() {
+            let entries = glossary::load(project_path, &self.config)?;
+            let metadata = glossary::metadata(&entries, self.config.glossary_title.as_deref());
+            let metadata_file = scratch_dir.join(GLOSSARY_METADATA_FILE);
+            fs::write(
+                &metadata_file,
+                serde_json::to_string_pretty(&metadata)
+                    .map_err(|error| OmniDocError::Other(error.to_string()))?,
+            )?;
+            options.push("--metadata-file".to_string());
+            options.push(metadata_file.display().to_string());
+        }
+        if let Some(title) = self
+            .config
+            .index_title
+            .as_deref()
+            .filter(|_| self.config.index_enabled)
+        {
+            options.push(pandoc::FLAG_METADATA.to_string());
+            options.push(format!("{}={title}", index::TITLE_METADATA_KEY));
         }
         let mut depfile_keys = BTreeMap::new();
         let mut depfile_names = BTreeMap::new();
@@ -845,7 +1208,7 @@ impl BuildPipeline for PandocBuilder {
             };
             push_depfile_metadata(
                 &mut options,
-                &cache_dir,
+                &scratch_dir,
                 &mut depfile_keys,
                 &mut depfile_names,
                 key,
@@ -854,7 +1217,7 @@ impl BuildPipeline for PandocBuilder {
             )?;
         }
         for filter in
-            plugin_filters_for_output(project_path, &self.config, output_kind.config_key())?
+            plugin_filters_for_output(project_path, &self.config, output_kind.family_key())?
         {
             let (Some(key), Some(file)) = (filter.depfile_metadata_key(), filter.depfile_name())
             else {
@@ -862,7 +1225,7 @@ impl BuildPipeline for PandocBuilder {
             };
             push_depfile_metadata(
                 &mut options,
-                &cache_dir,
+                &scratch_dir,
                 &mut depfile_keys,
                 &mut depfile_names,
                 key,
@@ -876,21 +1239,50 @@ impl BuildPipeline for PandocBuilder {
             )?;
         }
         for file in depfile_names.keys() {
-            let path = cache_dir.join(file);
+            let path = scratch_dir.join(file);
             if path.exists() {
                 fs::remove_file(path)?;
             }
         }
+        let fragment_dir = fragment_cache_dir(project_path);
+        fs::create_dir_all(&fragment_dir)?;
+        let fragment_log = scratch_dir.join(INCLUDE_FRAGMENT_LOG);
+        if fragment_log.exists() {
+            fs::remove_file(&fragment_log)?;
+        }
+        let render_dir = render_cache_dir(project_path);
+        fs::create_dir_all(&render_dir)?;
+        let render_log = scratch_dir.join(DIAGRAM_RENDER_LOG);
+        if render_log.exists() {
+            fs::remove_file(&render_log)?;
+        }
+        let fragment_salt = fragment_salt(&options);
+        let render_salt = render_salt(&options, &self.config);
+        for (key, value) in [
+            ("omnidoc-fragment-cache", fragment_dir.display().to_string()),
+            ("omnidoc-fragment-salt", fragment_salt),
+            ("omnidoc-fragment-stats", fragment_log.display().to_string()),
+            ("omnidoc-render-cache", render_dir.display().to_string()),
+            ("omnidoc-render-salt", render_salt),
+            ("omnidoc-render-stats", render_log.display().to_string()),
+        ] {
+            options.push(pandoc::FLAG_METADATA.to_string());
+            options.push(format!("{key}={value}"));
+        }
 
         let mut recorder_environment = Vec::new();
-        if output_kind == PandocOutputKind::Pdf {
+        if output_kind.uses_latex_pdf_engine() {
             let theme_latex_search_paths = self.theme_latex_search_paths(output_kind);
-            let depfile = cache_dir.join(LATEX_INPUT_DEPFILE);
+            let depfile = scratch_dir.join(LATEX_INPUT_DEPFILE);
+            let synctex_dir = scratch_dir.join(SYNCTEX_DIR);
+            if synctex_dir.exists() {
+                fs::remove_dir_all(&synctex_dir)?;
+            }
             let real_engine = self
                 .executor
                 .resolve_latex_engine(LatexEnginePreference::Markdown)?;
             if real_engine.is_tectonic() {
-                let rules = cache_dir.join("tectonic-inputs.make");
+                let rules = scratch_dir.join("tectonic-inputs.make");
                 if rules.exists() {
                     fs::remove_file(&rules)?;
                 }
@@ -917,10 +1309,23 @@ impl BuildPipeline for PandocBuilder {
                             *engine = recorder.wrapper.to_string_lossy().to_string();
                         }
                     }
-                    if !real_engine.is_tectonic() {
+                    if real_engine.is_tectonic() {
+                        options.push("--pdf-engine-opt=--synctex".to_string());
+                    } else {
                         options.push("--pdf-engine-opt=-recorder".to_string());
+                        options.push("--pdf-engine-opt=-synctex=1".to_string());
                     }
                     recorder_environment = recorder.environment;
+                    // `omnidoc locate` maps PDF positions back to Markdown
+                    // through the preserved SyncTeX data.
+                    recorder_environment.push(latex_recorder::synctex_environment(&synctex_dir));
+                    if self.config.index_enabled {
+                        self.push_index_environment(
+                            project_path,
+                            &real_engine,
+                            &mut recorder_environment,
+                        )?;
+                    }
                 }
                 None => {
                     if depfile.exists() {
@@ -951,8 +1356,40 @@ impl BuildPipeline for PandocBuilder {
             return Err(OmniDocError::Project(message));
         }
 
+        if let Some(media_dir) = media_dir.as_deref() {
+            media::relativize_media_links(&output_file, media_dir)?;
+        }
+        if output_kind == PandocOutputKind::Docx {
+            if let Some(watermark) = self.config.watermark.as_ref() {
+                docx::stamp_watermark(&output_file, &resolve_watermark(watermark)?)?;
+            }
+        }
+        if let (Some(staging), Some(site_dir)) = (site_staging.as_deref(), output_file.parent()) {
+            site::finish_site(staging, site_dir, &site_stylesheets)?;
+        }
+        if let (Some(index_name), Some(pages_dir)) = (search_index.as_deref(), output_file.parent())
+        {
+            let pages = if output_kind == PandocOutputKind::HtmlSite {
+                search::site_pages(pages_dir)?
+            } else {
+                vec![output_kind.artifact_name(&target_name)]
+            };
+            search::write_index(pages_dir, &pages, index_name)?;
+        } else if output_kind == PandocOutputKind::Html {
+            // Publishing picks the index up by name; drop one left over from a
+            // build that still had search enabled.
+            let stale = outdir.join(search::page_index_name(&target_name));
+            if stale.exists() {
+                fs::remove_file(stale)?;
+            }
+        }
+
         if verbose {
-            println!("✓ Built {}: {}", output_kind.label(), output_file.display());
+            terminal::out(format!(
+                "✓ Built {}: {}\n",
+                output_kind.label(),
+                output_file.display()
+            ));
         }
 
         Ok(())
@@ -1003,7 +1440,8 @@ fn prepend_path_list(variable: &str, prefixes: &[PathBuf]) -> Result<Option<(OsS
 #[cfg(test)]
 mod tests {
     use super::{
-        join_portable_relative, prepend_path_list, push_depfile_metadata, resolve_css_path,
+        fragment_salt, join_portable_relative, prepend_path_list, push_depfile_metadata,
+        resolve_css_path,
     };
     use crate::build::pandoc::{PandocBuilder, PandocCommandProfile, StandalonePdfLanguage};
     use crate::build::pandoc_policy::PandocOutputKind;
@@ -1015,6 +1453,36 @@ mod tests {
     use std::path::PathBuf;
     use std::time::{SystemTime, UNIX_EPOCH};
 
+    #[test]
+    fn fragment_salt_covers_the_reader_but_not_lua_filters() {
+        let directory = tempfile::tempdir().expect("temporary filters");
+        let filter = directory.path().join("callouts.lua");
+        fs::write(&filter, "return {}\n").expect("filter");
+        let options = |from: &str, extra: &[&str]| {
+            let mut options = vec![
+                "-f".to_string(),
+                from.to_string(),
+                "--lua-filter".to_string(),
+                filter.display().to_string(),
+            ];
+            options.extend(extra.iter().map(|option| option.to_string()));
+            options
+        };
+        let salt = fragment_salt(&options("markdown", &[]));
+
+        fs::write(&filter, "return { Str = function(s) return s end }\n").expect("edit");
+        assert_eq!(fragment_salt(&options("markdown", &[])), salt);
+        assert_eq!(
+            fragment_salt(&options("markdown", &["--toc", "--number-sections"])),
+            salt
+        );
+        assert_ne!(fragment_salt(&options("commonmark", &[])), salt);
+        assert_ne!(
+            fragment_salt(&options("markdown", &["--tab-stop", "8"])),
+            salt
+        );
+    }
+
     #[test]
     fn portable_relative_paths_use_native_components() {
         let root = PathBuf::from("bundle-root");
@@ -1868,6 +2336,184 @@ documentclass = "scrbook"
             .any(|option| option.contains("pandoc/data/crossref.yaml")));
     }
 
+    #[test]
+    fn typst_outputs_use_the_typst_writer_template_and_native_crossrefs() {
+        let root = tempfile::tempdir().expect("tempdir");
+        let template = root.path().join("pandoc/data/templates/pantypst.typ");
+        fs::create_dir_all(template.parent().expect("templates dir")).expect("templates dir");
+        fs::write(&template, "$body$\n").expect("typst template");
+        let typst = root.path().join("typst");
+        fs::write(&typst, "").expect("fake typst");
+        let builder = PandocBuilder::new(MergedConfig {
+            lib_path: Some(root.path().to_string_lossy().to_string()),
+            tool_paths: [
+                ("latex_engine".to_string(), Some("typst".to_string())),
+                (
+                    "typst".to_string(),
+                    Some(typst.to_string_lossy().to_string()),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        })
+        .expect("builder");
+
+        for (kind, output) in [
+            (PandocOutputKind::Typst, "output.typ"),
+            (PandocOutputKind::TypstPdf, "output.pdf"),
+        ] {
+            let options = builder
+                .build_command_options(
+                    std::path::Path::new("."),
+                    std::path::Path::new("input.md"),
+                    std::path::Path::new(output),
+                    kind,
+                    &PandocCommandProfile::Project,
+                )
+                .expect("options");
+
+            assert!(options.windows(2).any(|pair| pair == ["-t", "typst"]));
+            assert!(options
+                .windows(2)
+                .any(|pair| pair == ["--template", template.to_string_lossy().as_ref()]));
+            assert!(!options
+                .iter()
+                .any(|option| option.contains("pandoc-crossref")));
+            assert!(options
+                .iter()
+                .any(|option| option.ends_with("typst-crossref.lua")));
+            assert!(!options
+                .iter()
+                .any(|option| option.starts_with("omnidoc-theme-latex-header")));
+            assert_eq!(
+                options
+                    .windows(2)
+                    .any(|pair| pair == ["--pdf-engine", typst.to_string_lossy().as_ref()]),
+                kind == PandocOutputKind::TypstPdf
+            );
+        }
+    }
+
+    #[test]
+    fn html_site_uses_the_site_template_navigation_css_and_split_level() {
+        let root = tempfile::tempdir().expect("tempdir");
+        for relative in [
+            "pandoc/data/templates/omnidoc-site.html",
+            "pandoc/css/omnidoc-base.css",
+            "pandoc/css/modules/site.css",
+        ] {
+            let path = root.path().join(relative);
+            fs::create_dir_all(path.parent().expect("parent")).expect("resource dir");
+            fs::write(&path, "").expect("resource");
+        }
+        let builder = PandocBuilder::new(MergedConfig {
+            lib_path: Some(root.path().to_string_lossy().to_string()),
+            pandoc_site_split_level: Some(2),
+            ..Default::default()
+        })
+        .expect("builder");
+
+        let options = builder
+            .build_command_options(
+                std::path::Path::new("."),
+                std::path::Path::new("input.md"),
+                std::path::Path::new("site"),
+                PandocOutputKind::HtmlSite,
+                &PandocCommandProfile::Project,
+            )
+            .expect("options");
+
+        assert!(options.windows(2).any(|pair| pair == ["-t", "chunkedhtml"]));
+        assert!(options.iter().any(|option| option == "--toc"));
+        assert!(options.iter().any(|option| option == "--split-level=2"));
+        assert!(options.iter().any(|option| option == "--mathml"));
+        assert!(options
+            .iter()
+            .any(|option| option.ends_with("omnidoc-site.html")));
+        let css = options
+            .windows(2)
+            .filter(|pair| pair[0] == "--css")
+            .map(|pair| pair[1].as_str())
+            .collect::<Vec<_>>();
+        assert!(css[0].ends_with("omnidoc-base.css"));
+        assert!(css[1].ends_with("site.css"));
+    }
+
+    #[test]
+    fn slide_decks_take_generated_token_styles_instead_of_document_resources() {
+        let root = tempfile::tempdir().expect("tempdir");
+        for relative in [
+            "pandoc/css/omnidoc-base.css",
+            "pandoc/css/modules/revealjs.css",
+            "pandoc/headers/semantic-blocks.tex",
+            "tokens-beamer.tex",
+            "tokens-revealjs.css",
+        ] {
+            let path = root.path().join(relative);
+            fs::create_dir_all(path.parent().expect("parent")).expect("resource dir");
+            fs::write(&path, "").expect("resource");
+        }
+        let library = root.path().to_str().expect("library path");
+        let generated = GeneratedThemeAssets {
+            latex_header: Some(root.path().join("tokens.tex")),
+            beamer_header: Some(root.path().join("tokens-beamer.tex")),
+            revealjs_css: Some(root.path().join("tokens-revealjs.css")),
+            ..Default::default()
+        };
+        let builder = PandocBuilder::new(MergedConfig {
+            lib_path: Some(library.to_string()),
+            pandoc_css: Some("advance-editor.css".to_string()),
+            ..Default::default()
+        })
+        .expect("builder");
+
+        let mut beamer = Vec::new();
+        builder.push_default_latex_headers(&mut beamer, PandocOutputKind::Beamer, library);
+        builder.push_theme_latex_headers(&mut beamer, PandocOutputKind::Beamer, &generated);
+        assert_eq!(
+            beamer,
+            [
+                "--metadata".to_string(),
+                format!(
+                    "omnidoc-theme-latex-header-1001={}",
+                    root.path().join("tokens-beamer.tex").display()
+                )
+            ]
+        );
+
+        let mut reveal = Vec::new();
+        builder.push_css(
+            &mut reveal,
+            PandocOutputKind::RevealJs,
+            library,
+            &PandocCommandProfile::Project,
+            &generated,
+        );
+        builder.push_math_output(&mut reveal, PandocOutputKind::RevealJs);
+        assert_eq!(
+            reveal,
+            [
+                "--css".to_string(),
+                root.path()
+                    .join("pandoc/css/modules/revealjs.css")
+                    .to_string_lossy()
+                    .to_string(),
+                "--css".to_string(),
+                root.path()
+                    .join("tokens-revealjs.css")
+                    .to_string_lossy()
+                    .to_string(),
+                "--mathml".to_string(),
+            ]
+        );
+
+        let mut templates = Vec::new();
+        builder.push_template(&mut templates, PandocOutputKind::Beamer);
+        builder.push_template(&mut templates, PandocOutputKind::RevealJs);
+        assert!(templates.is_empty());
+    }
+
     #[test]
     fn standalone_pdf_profile_controls_language_fonts_and_crossref_metadata() {
         let builder = PandocBuilder::new(MergedConfig::default()).expect("builder");
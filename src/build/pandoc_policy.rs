@@ -1,3 +1,4 @@
+use crate::build::executor::uses_typst_pdf_engine;
 use crate::config::MergedConfig;
 use crate::error::{OmniDocError, Result};
 
@@ -5,19 +6,52 @@ use crate::error::{OmniDocError, Result};
 pub(crate) enum PandocOutputKind {
     Pdf,
     Html,
+    /// Multi-page HTML site from Pandoc's `chunkedhtml` writer.
+    HtmlSite,
     Epub,
     Docx,
     Pptx,
+    /// PDF slides from Pandoc's LaTeX Beamer writer.
+    Beamer,
+    /// HTML slides from Pandoc's reveal.js writer.
+    RevealJs,
+    /// GitHub-flavored Markdown, for READMEs; figures go to `<target>-media/`.
+    Gfm,
+    Plain,
+    Man,
+    /// JATS archiving XML for journal publishers; figures go to `<target>-jats-media/`.
+    Jats,
+    /// DocBook 5 XML; figures go to `<target>-docbook-media/`.
+    DocBook,
     Latex,
+    Typst,
+    /// PDF rendered by Pandoc's Typst writer and the `typst` engine.
+    TypstPdf,
 }
 
 impl PandocOutputKind {
     const METADATA_DEFAULTS_FILTER: &'static str = "metadata-defaults.lua";
     const LATEX_HEADERS_FILTER: &'static str = "latex-headers.lua";
+    const TYPST_CROSSREF_FILTER: &'static str = "typst-crossref.lua";
+    const AUDIENCE_FILTER: &'static str = "audience.lua";
+    const SPEAKER_NOTES_FILTER: &'static str = "speaker-notes.lua";
+    const GLOSSARY_FILTER: &'static str = "glossary.lua";
+    const INDEX_FILTER: &'static str = "index.lua";
+    const PUBLISHER_METADATA_FILTER: &'static str = "publisher-metadata.lua";
+    const INCLUDE_FILTERS: &'static [&'static str] =
+        &["include-files.lua", "include-code-files.lua"];
 
     pub(crate) fn from_config(config: &MergedConfig) -> Result<Self> {
         let requested = config.to.as_deref().or(config.pandoc_to_format.as_deref());
-        Self::from_requested(requested)
+        match Self::from_requested(requested)? {
+            Self::Pdf if uses_typst_pdf_engine(&config.tool_paths) => Ok(Self::TypstPdf),
+            Self::Beamer if uses_typst_pdf_engine(&config.tool_paths) => {
+                Err(OmniDocError::UnsupportedDocumentType(
+                    "Beamer slides need a LaTeX engine; `latex_engine = \"typst\"` only renders pdf output".to_string(),
+                ))
+            }
+            kind => Ok(kind),
+        }
     }
 
     pub(crate) fn from_requested(requested: Option<&str>) -> Result<Self> {
@@ -25,12 +59,21 @@ impl PandocOutputKind {
         match requested.as_str() {
             "" | "pdf" => Ok(Self::Pdf),
             "html" | "html4" | "html5" => Ok(Self::Html),
+            "html-site" | "chunkedhtml" => Ok(Self::HtmlSite),
             "epub" | "epub2" | "epub3" => Ok(Self::Epub),
             "docx" => Ok(Self::Docx),
             "pptx" | "powerpoint" => Ok(Self::Pptx),
+            "beamer" => Ok(Self::Beamer),
+            "revealjs" | "reveal" => Ok(Self::RevealJs),
+            "gfm" | "github" => Ok(Self::Gfm),
+            "plain" | "text" | "txt" => Ok(Self::Plain),
+            "man" | "manpage" => Ok(Self::Man),
+            "jats" | "jats_archiving" => Ok(Self::Jats),
+            "docbook" | "docbook5" => Ok(Self::DocBook),
             "latex" | "tex" => Ok(Self::Latex),
+            "typst" | "typ" => Ok(Self::Typst),
             _ => Err(OmniDocError::UnsupportedDocumentType(format!(
-                "Unsupported build output format '{}'. Supported formats: pdf, html, epub, docx, pptx, latex",
+                "Unsupported build output format '{}'. Supported formats: pdf, html, html-site, epub, docx, pptx, beamer, revealjs, gfm, plain, man, jats, docbook, latex, typst",
                 requested
             ))),
         }
@@ -38,23 +81,38 @@ impl PandocOutputKind {
 
     pub(crate) fn extension(self) -> &'static str {
         match self {
-            Self::Pdf => "pdf",
-            Self::Html => "html",
+            Self::Pdf | Self::TypstPdf | Self::Beamer => "pdf",
+            Self::Html | Self::HtmlSite | Self::RevealJs => "html",
             Self::Epub => "epub",
             Self::Docx => "docx",
             Self::Pptx => "pptx",
+            Self::Gfm => "md",
+            Self::Plain => "txt",
+            // Section 1: user commands, the usual home of CLI documentation.
+            Self::Man => "1",
+            Self::Jats | Self::DocBook => "xml",
             Self::Latex => "tex",
+            Self::Typst => "typ",
         }
     }
 
     pub(crate) fn label(self) -> &'static str {
         match self {
-            Self::Pdf => "PDF",
+            Self::Pdf | Self::TypstPdf => "PDF",
             Self::Html => "HTML",
+            Self::HtmlSite => "HTML site",
             Self::Epub => "EPUB",
             Self::Docx => "DOCX",
             Self::Pptx => "PPTX",
+            Self::Beamer => "Beamer",
+            Self::RevealJs => "reveal.js",
+            Self::Gfm => "GitHub Markdown",
+            Self::Plain => "plain text",
+            Self::Man => "man page",
+            Self::Jats => "JATS XML",
+            Self::DocBook => "DocBook XML",
             Self::Latex => "LaTeX",
+            Self::Typst => "Typst",
         }
     }
 
@@ -62,15 +120,38 @@ impl PandocOutputKind {
         match self {
             Self::Pdf => None,
             Self::Html => Some("html"),
+            Self::HtmlSite => Some("chunkedhtml"),
             Self::Epub => Some("epub3"),
             Self::Docx => Some("docx"),
             Self::Pptx => Some("pptx"),
+            Self::Beamer => Some("beamer"),
+            Self::RevealJs => Some("revealjs"),
+            Self::Gfm => Some("gfm"),
+            Self::Plain => Some("plain"),
+            Self::Man => Some("man"),
+            Self::Jats => Some("jats_archiving"),
+            Self::DocBook => Some("docbook5"),
             Self::Latex => Some("latex"),
+            Self::Typst | Self::TypstPdf => Some("typst"),
         }
     }
 
     pub(crate) fn uses_latex_defaults(self) -> bool {
-        matches!(self, Self::Pdf | Self::Latex)
+        matches!(self, Self::Pdf | Self::Latex | Self::Beamer)
+    }
+
+    /// Outputs that Pandoc hands to a LaTeX PDF engine.
+    pub(crate) fn uses_latex_pdf_engine(self) -> bool {
+        matches!(self, Self::Pdf | Self::Beamer)
+    }
+
+    /// Presentation outputs; their writers render `::: notes` as speaker notes.
+    pub(crate) fn is_slides(self) -> bool {
+        matches!(self, Self::Pptx | Self::Beamer | Self::RevealJs)
+    }
+
+    pub(crate) fn uses_typst_defaults(self) -> bool {
+        matches!(self, Self::Typst | Self::TypstPdf)
     }
 
     pub(crate) fn supports_embed_resources(self) -> bool {
@@ -83,12 +164,58 @@ impl PandocOutputKind {
 
     pub(crate) fn config_key(self) -> &'static str {
         match self {
-            Self::Pdf => "pdf",
+            Self::Pdf | Self::TypstPdf => "pdf",
             Self::Html => "html",
+            Self::HtmlSite => "html-site",
             Self::Epub => "epub",
             Self::Docx => "docx",
             Self::Pptx => "pptx",
+            Self::Beamer => "beamer",
+            Self::RevealJs => "revealjs",
+            Self::Gfm => "gfm",
+            Self::Plain => "plain",
+            Self::Man => "man",
+            Self::Jats => "jats",
+            Self::DocBook => "docbook",
             Self::Latex => "latex",
+            Self::Typst => "typst",
+        }
+    }
+
+    /// Output family used to select theme resources and plugin filters: an
+    /// HTML site is styled and filtered exactly like single-page HTML.
+    pub(crate) fn family_key(self) -> &'static str {
+        match self {
+            Self::HtmlSite => "html",
+            kind => kind.config_key(),
+        }
+    }
+
+    pub(crate) fn is_html(self) -> bool {
+        matches!(self, Self::Html | Self::HtmlSite)
+    }
+
+    /// XML outputs checked structurally after every build.
+    pub(crate) fn is_publisher_xml(self) -> bool {
+        matches!(self, Self::Jats | Self::DocBook)
+    }
+
+    /// Outputs that reference figures by path; Pandoc extracts them to
+    /// `<artifact>-media/` beside the artifact.
+    pub(crate) fn extracts_media(self) -> bool {
+        matches!(self, Self::Gfm | Self::Jats | Self::DocBook)
+    }
+
+    /// Artifact path relative to the output directory. A site is a directory
+    /// of pages; its `index.html` stands for the whole artifact.
+    pub(crate) fn artifact_name(self, target: &str) -> String {
+        match self {
+            Self::HtmlSite => format!("{target}-site/index.html"),
+            // Slides share their extension with the document outputs, so
+            // the deck gets its own name beside `<target>.pdf`/`.html`.
+            Self::Beamer | Self::RevealJs => format!("{target}-slides.{}", self.extension()),
+            Self::Jats | Self::DocBook => format!("{target}-{}.xml", self.config_key()),
+            kind => format!("{target}.{}", kind.extension()),
         }
     }
 
@@ -111,8 +238,17 @@ impl PandocOutputKind {
             "admonition.lua",
             "fonts-and-alignment.lua",
         ];
+        const TYPST_FILTERS: &[&str] = &[
+            "include-files.lua",
+            "include-code-files.lua",
+            "diagram-generator.lua",
+            "admonition.lua",
+            "fonts-and-alignment.lua",
+        ];
         if self.uses_latex_defaults() {
             LATEX_FILTERS
+        } else if self.uses_typst_defaults() {
+            TYPST_FILTERS
         } else {
             PORTABLE_FILTERS
         }
@@ -132,14 +268,76 @@ impl PandocOutputKind {
         if self.uses_latex_defaults() {
             filters.push(Self::LATEX_HEADERS_FILTER);
         }
+        if self.is_publisher_xml() {
+            filters.push(Self::PUBLISHER_METADATA_FILTER);
+        }
+        let builtin = filters.len();
         for filter in configured {
             if !filters.contains(&filter) {
                 filters.push(filter);
             }
         }
+        // Variant builds resolve audience conditions right after the include
+        // filters, so included chapters are filtered too and every later
+        // filter only sees the content the audience keeps.
+        if config.variant.is_some() && !filters.contains(&Self::AUDIENCE_FILTER) {
+            let position = filters
+                .iter()
+                .rposition(|filter| Self::INCLUDE_FILTERS.contains(filter))
+                .map_or(builtin, |index| index + 1);
+            filters.insert(position, Self::AUDIENCE_FILTER);
+        }
+        // Only slide writers know what to do with `::: notes`; every other
+        // output would print the speaker notes as body text.
+        if !self.is_slides() && !filters.contains(&Self::SPEAKER_NOTES_FILTER) {
+            let position = filters
+                .iter()
+                .rposition(|filter| {
+                    Self::INCLUDE_FILTERS.contains(filter) || *filter == Self::AUDIENCE_FILTER
+                })
+                .map_or(builtin, |index| index + 1);
+            filters.insert(position, Self::SPEAKER_NOTES_FILTER);
+        }
+        // Glossary references expand once the document is assembled and
+        // filtered, so "first use" follows the text the reader actually sees.
+        if !config.glossary_files.is_empty() && !filters.contains(&Self::GLOSSARY_FILTER) {
+            let position = filters
+                .iter()
+                .rposition(|filter| {
+                    Self::INCLUDE_FILTERS.contains(filter)
+                        || *filter == Self::AUDIENCE_FILTER
+                        || *filter == Self::SPEAKER_NOTES_FILTER
+                })
+                .map_or(builtin, |index| index + 1);
+            filters.insert(position, Self::GLOSSARY_FILTER);
+        }
+        // Index entries are collected from the same final text, after glossary
+        // expansion; slide decks have no back matter to hold an index.
+        if config.index_enabled && !self.is_slides() && !filters.contains(&Self::INDEX_FILTER) {
+            let position = filters
+                .iter()
+                .rposition(|filter| {
+                    Self::INCLUDE_FILTERS.contains(filter)
+                        || *filter == Self::AUDIENCE_FILTER
+                        || *filter == Self::SPEAKER_NOTES_FILTER
+                        || *filter == Self::GLOSSARY_FILTER
+                })
+                .map_or(builtin, |index| index + 1);
+            filters.insert(position, Self::INDEX_FILTER);
+        }
+        // Typst numbers figures, tables and equations itself, so the Typst
+        // writers replace pandoc-crossref with native references. The filter
+        // runs last so it also sees content spliced in by include filters.
+        if self.uses_typst_defaults() && !filters.contains(&Self::TYPST_CROSSREF_FILTER) {
+            filters.push(Self::TYPST_CROSSREF_FILTER);
+        }
         filters
     }
 
+    pub(crate) fn uses_pandoc_crossref(self) -> bool {
+        !self.uses_typst_defaults()
+    }
+
     pub(crate) fn append_configured_options(
         self,
         config: &MergedConfig,
@@ -167,7 +365,24 @@ impl PandocOutputKind {
 }
 
 pub(crate) fn is_supported_format_key(key: &str) -> bool {
-    matches!(key, "pdf" | "html" | "epub" | "docx" | "pptx" | "latex")
+    matches!(
+        key,
+        "pdf"
+            | "html"
+            | "html-site"
+            | "epub"
+            | "docx"
+            | "pptx"
+            | "beamer"
+            | "revealjs"
+            | "gfm"
+            | "plain"
+            | "man"
+            | "jats"
+            | "docbook"
+            | "latex"
+            | "typst"
+    )
 }
 
 fn is_html_math_option(option: &str) -> bool {
@@ -180,7 +395,7 @@ fn is_html_math_option(option: &str) -> bool {
 mod tests {
     use super::{is_supported_format_key, PandocOutputKind};
     use crate::config::MergedConfig;
-    use std::collections::BTreeMap;
+    use std::collections::{BTreeMap, HashMap};
 
     #[test]
     fn normalizes_writer_aliases_to_policy_kinds() {
@@ -200,6 +415,56 @@ mod tests {
             PandocOutputKind::from_requested(Some("powerpoint")).expect("pptx"),
             PandocOutputKind::Pptx
         );
+        assert_eq!(
+            PandocOutputKind::from_requested(Some("typ")).expect("typst"),
+            PandocOutputKind::Typst
+        );
+    }
+
+    #[test]
+    fn html_site_is_a_directory_artifact_in_the_html_family() {
+        let kind = PandocOutputKind::from_requested(Some("chunkedhtml")).expect("site");
+
+        assert_eq!(kind, PandocOutputKind::HtmlSite);
+        assert_eq!(kind.config_key(), "html-site");
+        assert_eq!(kind.family_key(), "html");
+        assert_eq!(kind.default_to_format(), Some("chunkedhtml"));
+        assert_eq!(kind.artifact_name("guide"), "guide-site/index.html");
+        assert_eq!(PandocOutputKind::Html.artifact_name("guide"), "guide.html");
+        assert!(!kind.supports_embed_resources());
+        assert!(is_supported_format_key("html-site"));
+    }
+
+    #[test]
+    fn typst_pdf_engine_switches_pdf_to_the_typst_writer() {
+        let config = MergedConfig {
+            tool_paths: HashMap::from([("latex_engine".to_string(), Some("typst".to_string()))]),
+            ..Default::default()
+        };
+        let kind = PandocOutputKind::from_config(&config).expect("pdf");
+
+        assert_eq!(kind, PandocOutputKind::TypstPdf);
+        assert_eq!(kind.extension(), "pdf");
+        assert_eq!(kind.config_key(), "pdf");
+        assert_eq!(kind.default_to_format(), Some("typst"));
+        assert!(!kind.uses_latex_defaults());
+        assert!(!kind.uses_pandoc_crossref());
+
+        let filters = kind.filters(&config);
+        assert_eq!(filters.first().copied(), Some("metadata-defaults.lua"));
+        assert_eq!(filters.last().copied(), Some("typst-crossref.lua"));
+        assert!(!filters.contains(&"latex-headers.lua"));
+        assert!(!filters.contains(&"display-math.lua"));
+
+        let html = MergedConfig {
+            to: Some("html".to_string()),
+            ..config
+        };
+        assert_eq!(
+            PandocOutputKind::from_config(&html).expect("html"),
+            PandocOutputKind::Html
+        );
+        assert!(is_supported_format_key("typst"));
     }
 
     #[test]
@@ -247,4 +512,202 @@ mod tests {
         assert!(is_supported_format_key("pptx"));
         assert!(!is_supported_format_key("html5"));
     }
+
+    #[test]
+    fn variant_builds_resolve_audiences_after_include_filters() {
+        let base = MergedConfig::default();
+        assert!(!PandocOutputKind::Html
+            .filters(&base)
+            .contains(&"audience.lua"));
+
+        let variant = MergedConfig {
+            variant: Some("customer".to_string()),
+            ..Default::default()
+        };
+        let filters = PandocOutputKind::Html.filters(&variant);
+        assert_eq!(
+            filters[..4],
+            [
+                "metadata-defaults.lua",
+                "include-files.lua",
+                "include-code-files.lua",
+                "audience.lua"
+            ]
+        );
+
+        let custom = MergedConfig {
+            pandoc_lua_filters: vec!["custom.lua".to_string()],
+            ..variant
+        };
+        assert_eq!(
+            PandocOutputKind::Pdf.filters(&custom),
+            [
+                "metadata-defaults.lua",
+                "latex-headers.lua",
+                "audience.lua",
+                "speaker-notes.lua",
+                "custom.lua"
+            ]
+        );
+    }
+
+    #[test]
+    fn glossary_projects_expand_references_after_content_filters() {
+        assert!(!PandocOutputKind::Html
+            .filters(&MergedConfig::default())
+            .contains(&"glossary.lua"));
+
+        let config = MergedConfig {
+            glossary_files: vec!["glossary.yaml".to_string()],
+            variant: Some("customer".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            PandocOutputKind::Html.filters(&config)[..6],
+            [
+                "metadata-defaults.lua",
+                "include-files.lua",
+                "include-code-files.lua",
+                "audience.lua",
+                "speaker-notes.lua",
+                "glossary.lua"
+            ]
+        );
+        assert_eq!(
+            PandocOutputKind::Pptx.filters(&config)[..5],
+            [
+                "metadata-defaults.lua",
+                "include-files.lua",
+                "include-code-files.lua",
+                "audience.lua",
+                "glossary.lua"
+            ]
+        );
+    }
+
+    #[test]
+    fn indexed_projects_collect_terms_after_glossary_expansion() {
+        assert!(!PandocOutputKind::Pdf
+            .filters(&MergedConfig::default())
+            .contains(&"index.lua"));
+
+        let config = MergedConfig {
+            glossary_files: vec!["glossary.yaml".to_string()],
+            index_enabled: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            PandocOutputKind::Pdf.filters(&config)[..7],
+            [
+                "metadata-defaults.lua",
+                "latex-headers.lua",
+                "include-files.lua",
+                "include-code-files.lua",
+                "speaker-notes.lua",
+                "glossary.lua",
+                "index.lua"
+            ]
+        );
+        assert!(!PandocOutputKind::Beamer
+            .filters(&config)
+            .contains(&"index.lua"));
+    }
+
+    #[test]
+    fn text_outputs_use_pandoc_writers_and_portable_filters() {
+        let config = MergedConfig::default();
+        for (requested, kind, artifact) in [
+            ("github", PandocOutputKind::Gfm, "cli.md"),
+            ("txt", PandocOutputKind::Plain, "cli.txt"),
+            ("manpage", PandocOutputKind::Man, "cli.1"),
+        ] {
+            assert_eq!(
+                PandocOutputKind::from_requested(Some(requested)).expect("text output"),
+                kind
+            );
+            assert_eq!(kind.default_to_format(), Some(kind.config_key()));
+            assert_eq!(kind.artifact_name("cli"), artifact);
+            assert!(kind.supports_standalone());
+            assert!(is_supported_format_key(kind.config_key()));
+            let filters = kind.filters(&config);
+            assert!(filters.contains(&"include-files.lua"));
+            assert!(filters.contains(&"admonition.lua"));
+            assert!(filters.contains(&"speaker-notes.lua"));
+            assert!(!filters.contains(&"latex-headers.lua"));
+        }
+    }
+
+    #[test]
+    fn publisher_xml_outputs_map_metadata_and_extract_figures() {
+        let config = MergedConfig::default();
+        for (requested, kind, to, artifact) in [
+            (
+                "jats",
+                PandocOutputKind::Jats,
+                "jats_archiving",
+                "paper-jats.xml",
+            ),
+            (
+                "docbook5",
+                PandocOutputKind::DocBook,
+                "docbook5",
+                "paper-docbook.xml",
+            ),
+        ] {
+            let parsed = PandocOutputKind::from_requested(Some(requested)).expect("XML output");
+            assert_eq!(parsed, kind);
+            assert_eq!(kind.default_to_format(), Some(to));
+            assert_eq!(kind.artifact_name("paper"), artifact);
+            assert!(kind.is_publisher_xml() && kind.extracts_media());
+            let filters = kind.filters(&config);
+            assert_eq!(
+                &filters[..2],
+                ["metadata-defaults.lua", "publisher-metadata.lua"]
+            );
+            assert!(filters.contains(&"include-files.lua"));
+        }
+        assert!(PandocOutputKind::Gfm.extracts_media());
+        assert!(!PandocOutputKind::Gfm.is_publisher_xml());
+        assert!(!PandocOutputKind::Html
+            .filters(&config)
+            .contains(&"publisher-metadata.lua"));
+    }
+
+    #[test]
+    fn slide_outputs_keep_speaker_notes_and_get_their_own_artifacts() {
+        let config = MergedConfig::default();
+        let beamer = PandocOutputKind::from_requested(Some("beamer")).expect("beamer");
+        let reveal = PandocOutputKind::from_requested(Some("reveal")).expect("revealjs");
+
+        assert_eq!(beamer.default_to_format(), Some("beamer"));
+        assert_eq!(reveal.default_to_format(), Some("revealjs"));
+        assert_eq!(beamer.artifact_name("talk"), "talk-slides.pdf");
+        assert_eq!(reveal.artifact_name("talk"), "talk-slides.html");
+        assert!(beamer.uses_latex_defaults() && beamer.uses_latex_pdf_engine());
+        assert!(!reveal.is_html() && !reveal.uses_latex_defaults());
+        assert_eq!(
+            beamer.filters(&config).get(1).copied(),
+            Some("latex-headers.lua")
+        );
+        for kind in [beamer, reveal, PandocOutputKind::Pptx] {
+            assert!(!kind.filters(&config).contains(&"speaker-notes.lua"));
+        }
+        assert_eq!(
+            PandocOutputKind::Html.filters(&config)[..4],
+            [
+                "metadata-defaults.lua",
+                "include-files.lua",
+                "include-code-files.lua",
+                "speaker-notes.lua"
+            ]
+        );
+        assert!(is_supported_format_key("beamer") && is_supported_format_key("revealjs"));
+
+        let typst_engine = MergedConfig {
+            to: Some("beamer".to_string()),
+            tool_paths: HashMap::from([("latex_engine".to_string(), Some("typst".to_string()))]),
+            ..Default::default()
+        };
+        assert!(PandocOutputKind::from_config(&typst_engine).is_err());
+    }
 }
@@ -0,0 +1,413 @@
+//! Offline full-text search for HTML outputs.
+//!
+//! After Pandoc writes the HTML page(s), OmniDoc splits each page into
+//! heading sections, tokenizes their text and writes a JSON inverted index
+//! next to the pages. The bundled widget (`pandoc/data/search/`) is included
+//! after the body and performs the same tokenization in the browser, so no
+//! search server is needed. Han text has no word delimiters, so it is indexed
+//! as overlapping character bigrams; other scripts use lowercase words.
+
+use crate::build::pandoc_policy::PandocOutputKind;
+use crate::config::MergedConfig;
+use crate::error::{OmniDocError, Result};
+use crate::extensions::ResolvedTheme;
+use crate::utils::fs;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Index file written at the root of an `html-site` directory.
+pub(crate) const SITE_SEARCH_INDEX: &str = "search-index.json";
+/// Placeholder in the bundled widget replaced by the index URL.
+pub(crate) const WIDGET_INDEX_PLACEHOLDER: &str = "__OMNIDOC_SEARCH_INDEX__";
+pub(crate) const SEARCH_INDEX_VERSION: u32 = 1;
+const EXCERPT_CHARS: usize = 160;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct SearchDocument {
+    pub url: String,
+    pub title: String,
+    pub excerpt: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct SearchIndex {
+    pub version: u32,
+    pub documents: Vec<SearchDocument>,
+    /// Token → indexes into `documents`, ascending.
+    pub terms: BTreeMap<String, Vec<usize>>,
+}
+
+/// 判断 HTML 输出是否生成搜索索引：项目配置优先，否则由主题决定
+pub(crate) fn search_enabled(
+    config: &MergedConfig,
+    theme: Option<&ResolvedTheme>,
+    output_kind: PandocOutputKind,
+) -> bool {
+    output_kind.is_html()
+        && config
+            .pandoc_search
+            .or_else(|| {
+                theme
+                    .filter(|theme| theme.supports_output(output_kind.family_key()))
+                    .and_then(|theme| theme.search)
+            })
+            .unwrap_or(false)
+}
+
+/// Index file name for a single-page HTML output, next to `<target>.html`.
+pub(crate) fn page_index_name(target: &str) -> String {
+    format!("{target}-search.json")
+}
+
+/// 判断字符是否按汉字二元组切分
+fn is_han(ch: char) -> bool {
+    matches!(ch,
+        '\u{3400}'..='\u{4DBF}'
+        | '\u{4E00}'..='\u{9FFF}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{20000}'..='\u{2FA1F}')
+}
+
+/// 分词：汉字按二元组切分，其他文字按小写单词切分
+///
+/// A Han run of one character is kept as a unigram so single-character
+/// queries still match. The widget mirrors this function exactly.
+pub(crate) fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    let mut han = Vec::new();
+    for ch in text.chars().chain(std::iter::once(' ')) {
+        if is_han(ch) {
+            flush_word(&mut word, &mut tokens);
+            han.push(ch);
+        } else if ch.is_alphanumeric() {
+            flush_han(&mut han, &mut tokens);
+            word.extend(ch.to_lowercase());
+        } else {
+            flush_word(&mut word, &mut tokens);
+            flush_han(&mut han, &mut tokens);
+        }
+    }
+    tokens
+}
+
+fn flush_word(word: &mut String, tokens: &mut Vec<String>) {
+    if !word.is_empty() {
+        tokens.push(std::mem::take(word));
+    }
+}
+
+fn flush_han(han: &mut Vec<char>, tokens: &mut Vec<String>) {
+    match han.len() {
+        0 => {}
+        1 => tokens.push(han[0].to_string()),
+        _ => tokens.extend(han.windows(2).map(|pair| pair.iter().collect::<String>())),
+    }
+    han.clear();
+}
+
+struct HtmlPatterns {
+    noise: Regex,
+    marker: Regex,
+    id_attribute: Regex,
+    title: Regex,
+    tag: Regex,
+}
+
+static HTML_PATTERNS: OnceLock<HtmlPatterns> = OnceLock::new();
+
+fn html_patterns() -> &'static HtmlPatterns {
+    HTML_PATTERNS.get_or_init(|| HtmlPatterns {
+        // Navigation, scripts, and styles (including the widget itself) are
+        // not document content.
+        noise: Regex::new(r"(?is)<(nav|script|style)\b.*?</(nav|script|style)>")
+            .expect("search noise regex"),
+        marker: Regex::new(
+            r#"(?is)<section\b[^>]*?\sid="([^"]+)"[^>]*>|<h[1-6]\b([^>]*)>(.*?)</h[1-6]>"#,
+        )
+        .expect("search section marker regex"),
+        id_attribute: Regex::new(r#"\sid="([^"]+)""#).expect("search id attribute regex"),
+        title: Regex::new(r"(?is)<title>(.*?)</title>").expect("search title regex"),
+        tag: Regex::new(r"(?s)<[^>]*>").expect("search tag regex"),
+    })
+}
+
+struct Section {
+    anchor: Option<String>,
+    title: String,
+    text: String,
+}
+
+/// 按标题把 HTML 页面切分为可检索的小节
+fn page_sections(html: &str) -> Vec<Section> {
+    let body = html
+        .split_once("<body")
+        .and_then(|(_, rest)| rest.split_once('>'))
+        .map(|(_, rest)| rest)
+        .unwrap_or(html);
+    let body = body.split("</body>").next().unwrap_or(body);
+    let patterns = html_patterns();
+    let body = patterns.noise.replace_all(body, " ");
+
+    let mut sections = vec![Section {
+        anchor: None,
+        title: String::new(),
+        text: String::new(),
+    }];
+    let mut pending_section = None;
+    let mut cursor = 0usize;
+    for capture in patterns.marker.captures_iter(&body) {
+        let whole = capture.get(0).expect("match");
+        append_text(
+            &mut sections.last_mut().expect("section").text,
+            &body[cursor..whole.start()],
+        );
+        cursor = whole.end();
+        if let Some(section_id) = capture.get(1) {
+            pending_section = Some(section_id.as_str().to_string());
+            continue;
+        }
+        let attributes = capture.get(2).map(|m| m.as_str()).unwrap_or_default();
+        let anchor = patterns
+            .id_attribute
+            .captures(attributes)
+            .map(|id| id[1].to_string())
+            .or(pending_section.take());
+        sections.push(Section {
+            anchor,
+            title: plain_text(capture.get(3).map(|m| m.as_str()).unwrap_or_default()),
+            text: String::new(),
+        });
+    }
+    append_text(
+        &mut sections.last_mut().expect("section").text,
+        &body[cursor..],
+    );
+    sections
+        .into_iter()
+        .filter(|section| !section.title.is_empty() || !section.text.is_empty())
+        .collect()
+}
+
+fn append_text(target: &mut String, html: &str) {
+    let text = plain_text(html);
+    if text.is_empty() {
+        return;
+    }
+    if !target.is_empty() {
+        target.push(' ');
+    }
+    target.push_str(&text);
+}
+
+fn plain_text(html: &str) -> String {
+    let text = html_patterns().tag.replace_all(html, " ");
+    let text = text
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn excerpt(text: &str) -> String {
+    let mut excerpt = text.chars().take(EXCERPT_CHARS).collect::<String>();
+    if text.chars().count() > EXCERPT_CHARS {
+        excerpt.push('…');
+    }
+    excerpt
+}
+
+/// 从 `(url, html)` 页面列表构建倒排索引
+pub(crate) fn build_index(pages: &[(String, String)]) -> SearchIndex {
+    let mut documents = Vec::new();
+    let mut terms: BTreeMap<String, BTreeSet<usize>> = BTreeMap::new();
+    for (url, html) in pages {
+        let page_title = html_patterns()
+            .title
+            .captures(html)
+            .map(|title| plain_text(&title[1]))
+            .unwrap_or_default();
+        for section in page_sections(html) {
+            let index = documents.len();
+            for token in tokenize(&section.title)
+                .into_iter()
+                .chain(tokenize(&section.text))
+            {
+                terms.entry(token).or_default().insert(index);
+            }
+            documents.push(SearchDocument {
+                url: match &section.anchor {
+                    Some(anchor) => format!("{url}#{anchor}"),
+                    None => url.clone(),
+                },
+                title: if section.title.is_empty() {
+                    page_title.clone()
+                } else {
+                    section.title
+                },
+                excerpt: excerpt(&section.text),
+            });
+        }
+    }
+    SearchIndex {
+        version: SEARCH_INDEX_VERSION,
+        documents,
+        terms: terms
+            .into_iter()
+            .map(|(term, documents)| (term, documents.into_iter().collect()))
+            .collect(),
+    }
+}
+
+/// 为 `dir` 下的 HTML 页面写入搜索索引
+///
+/// `pages` are file names relative to `dir`; URLs in the index are relative
+/// to the index file, which is written into the same directory.
+pub(crate) fn write_index(dir: &Path, pages: &[String], index_name: &str) -> Result<()> {
+    let mut contents = Vec::new();
+    for page in pages {
+        contents.push((page.clone(), fs::read_to_string(dir.join(page))?));
+    }
+    let index = build_index(&contents);
+    let json = serde_json::to_string(&index)
+        .map_err(|error| OmniDocError::Other(format!("cannot serialize search index: {error}")))?;
+    fs::write(dir.join(index_name), json)
+}
+
+/// HTML pages directly inside a site directory, sorted by name.
+pub(crate) fn site_pages(site_dir: &Path) -> Result<Vec<String>> {
+    let mut pages = Vec::new();
+    for entry in fs::read_dir(site_dir)? {
+        let path = entry.map_err(OmniDocError::Io)?.path();
+        if path.extension().and_then(|ext| ext.to_str()) == Some("html") {
+            if let Some(name) = path.file_name() {
+                pages.push(name.to_string_lossy().to_string());
+            }
+        }
+    }
+    pages.sort();
+    Ok(pages)
+}
+
+/// Bundled widget markup with its index URL filled in.
+pub(crate) fn widget_include(widget: &str, index_url: &str) -> String {
+    widget.replace(WIDGET_INDEX_PLACEHOLDER, index_url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{build_index, tokenize, widget_include};
+
+    #[test]
+    fn han_text_is_segmented_into_bigrams_and_words_are_lowercased() {
+        assert_eq!(
+            tokenize("配置 OmniDoc 构建系统, v2"),
+            vec!["配置", "omnidoc", "构建", "建系", "系统", "v2"]
+        );
+        assert_eq!(tokenize("图"), vec!["图"]);
+    }
+
+    #[test]
+    fn sections_become_documents_with_anchors_and_postings() {
+        let pages = vec![(
+            "guide.html".to_string(),
+            r##"<html><head><title>指南</title></head><body>
+<nav id="TOC"><a href="#intro">简介</a></nav>
+<p>前言内容</p>
+<section id="intro" class="level1"><h1>简介</h1><p>构建 &amp; 发布</p></section>
+<h2 id="api">API</h2><p>Search index</p>
+<script>var ignored = "构建";</script>
+</body></html>"##
+                .to_string(),
+        )];
+
+        let index = build_index(&pages);
+
+        let urls = index
+            .documents
+            .iter()
+            .map(|document| document.url.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(urls, ["guide.html", "guide.html#intro", "guide.html#api"]);
+        assert_eq!(index.documents[0].title, "指南");
+        assert_eq!(index.documents[1].excerpt, "构建 & 发布");
+        assert_eq!(index.terms["构建"], vec![1]);
+        assert_eq!(index.terms["search"], vec![2]);
+        assert!(!index.terms.contains_key("ignored"));
+    }
+
+    #[test]
+    fn widget_placeholder_is_replaced_by_the_index_url() {
+        assert_eq!(
+            widget_include(
+                r#"<div data-index="__OMNIDOC_SEARCH_INDEX__">"#,
+                "a-search.json"
+            ),
+            r#"<div data-index="a-search.json">"#
+        );
+    }
+
+    /// Drive the bundled widget script under Node with a stub DOM. Skipped
+    /// when Node is not installed.
+    #[test]
+    fn widget_ignores_object_prototype_names_in_queries() {
+        let widget = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("bundles/libs")
+            .join(crate::constants::pandoc::LIB_PANDOC_SEARCH_WIDGET);
+        let harness = r#"
+const html = require('fs').readFileSync(process.argv[1], 'utf8');
+const script = html.slice(html.indexOf('<script>') + 8, html.indexOf('</script>'));
+function element() {
+  return {
+    hidden: false, value: '', children: [], handlers: {},
+    set textContent(value) { this.children = []; },
+    addEventListener(type, handler) { this.handlers[type] = handler; },
+    appendChild(child) { this.children.push(child); },
+  };
+}
+const input = element(), list = element(), root = element();
+root.querySelector = (selector) => selector === '.omni-search-input' ? input : list;
+root.getAttribute = () => 'search.json';
+const index = { documents: [{ url: 'a.html#search', title: 'Search', excerpt: '' }],
+  terms: { search: [0] } };
+globalThis.window = globalThis;
+globalThis.fetch = () => Promise.resolve({ json: () => index });
+globalThis.document = { getElementById: () => root, createElement: element };
+eval(script);
+(async () => {
+  const counts = [];
+  for (const query of ['constructor', 'toString', '__proto__', 'search', 'sea']) {
+    input.value = query;
+    input.handlers.input();
+    await new Promise((resolve) => setTimeout(resolve, 0));
+    counts.push(`${query}:${list.children.length}`);
+  }
+  console.log(`${counts.join(' ')} hidden:${root.hidden}`);
+})();
+"#;
+        let Ok(output) = std::process::Command::new("node")
+            .arg("-e")
+            .arg(harness)
+            .arg(&widget)
+            .output()
+        else {
+            return;
+        };
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert_eq!(
+            stdout.trim(),
+            "constructor:0 toString:0 __proto__:0 search:1 sea:1 hidden:false"
+        );
+    }
+}
@@ -0,0 +1,246 @@
+//! Post-processing for the chunked `html-site` output.
+//!
+//! Pandoc's `chunkedhtml` writer produces a flat directory of pages. OmniDoc
+//! stages that directory in the output scratch area, copies the stylesheets
+//! next to the pages so the site can be published as-is, resolves fragment
+//! links whose anchors landed on another page, and only then swaps the
+//! finished site into the build directory.
+
+use crate::constants::pandoc;
+use crate::error::{OmniDocError, Result};
+use crate::utils::fs;
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+/// Stylesheets are copied into this directory of the site.
+pub(crate) const SITE_ASSETS_DIR: &str = "assets";
+
+/// A `--css` argument redirected to a site-local copy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SiteStylesheet {
+    pub source: PathBuf,
+    pub name: String,
+}
+
+/// 将 `--css` 参数改写为站点内的 `assets/` 相对路径
+///
+/// Pandoc runs in the project directory, so relative stylesheet paths are
+/// resolved against `project_path` before they are copied. Remote URLs are
+/// left as they are.
+pub(crate) fn localize_stylesheets(
+    options: &mut [String],
+    project_path: &Path,
+) -> Vec<SiteStylesheet> {
+    let mut stylesheets = Vec::new();
+    let mut index = 0usize;
+    while index + 1 < options.len() {
+        if options[index] != pandoc::FLAG_CSS {
+            index += 1;
+            continue;
+        }
+        if options[index + 1].contains("://") {
+            index += 2;
+            continue;
+        }
+        let source = PathBuf::from(&options[index + 1]);
+        let source = if source.is_absolute() {
+            source
+        } else {
+            project_path.join(source)
+        };
+        let file_name = source
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "style.css".to_string());
+        // Numbering keeps the cascade order visible and avoids collisions
+        // between a theme's and the library's `default.css`.
+        let name = format!("{:02}-{}", stylesheets.len() + 1, file_name);
+        options[index + 1] = format!("{SITE_ASSETS_DIR}/{name}");
+        stylesheets.push(SiteStylesheet { source, name });
+        index += 2;
+    }
+    stylesheets
+}
+
+/// 完成站点：复制样式表、改写跨页链接并替换旧站点目录
+pub(crate) fn finish_site(
+    staging: &Path,
+    site_dir: &Path,
+    stylesheets: &[SiteStylesheet],
+) -> Result<()> {
+    if !stylesheets.is_empty() {
+        let assets = staging.join(SITE_ASSETS_DIR);
+        fs::create_dir_all(&assets)?;
+        for stylesheet in stylesheets {
+            fs::copy(&stylesheet.source, assets.join(&stylesheet.name))?;
+        }
+    }
+    rewrite_cross_page_links(staging)?;
+
+    if site_dir.exists() {
+        fs::remove_dir_all(site_dir)?;
+    }
+    if let Some(parent) = site_dir.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    // The scratch area may live on another filesystem than the build
+    // directory (for example a shared cache), so fall back to copying.
+    if fs::rename(staging, site_dir).is_err() {
+        crate::fs::copy_dir(staging, site_dir).map_err(OmniDocError::Io)?;
+        fs::remove_dir_all(staging)?;
+    }
+    Ok(())
+}
+
+/// 将指向其他页面锚点的 `href="#id"` 改写为 `page.html#id`
+///
+/// A fragment is rewritten only when its id is missing from the current page
+/// and defined on exactly one other page; ambiguous ids are left untouched.
+/// Returns the number of rewritten links.
+pub(crate) fn rewrite_cross_page_links(site_dir: &Path) -> Result<usize> {
+    let id_pattern = Regex::new(r#"\sid="([^"]+)""#).expect("id regex");
+    let link_pattern = Regex::new(r##"href="#([^"]+)""##).expect("fragment link regex");
+
+    let mut pages = BTreeMap::new();
+    for entry in fs::read_dir(site_dir)? {
+        let path = entry.map_err(OmniDocError::Io)?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("html") {
+            continue;
+        }
+        let Some(name) = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+        else {
+            continue;
+        };
+        pages.insert(name, fs::read_to_string(&path)?);
+    }
+
+    let mut owners: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    let mut page_ids: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for (name, content) in &pages {
+        let ids = page_ids.entry(name.as_str()).or_default();
+        for capture in id_pattern.captures_iter(content) {
+            let id = capture.get(1).map(|id| id.as_str()).unwrap_or_default();
+            ids.insert(id);
+            owners
+                .entry(id.to_string())
+                .or_default()
+                .insert(name.clone());
+        }
+    }
+
+    let mut rewritten = 0usize;
+    for (name, content) in &pages {
+        let local = page_ids.get(name.as_str());
+        let mut changed = 0usize;
+        let updated = link_pattern.replace_all(content, |capture: &regex::Captures<'_>| {
+            let id = &capture[1];
+            if local.is_some_and(|ids| ids.contains(id)) {
+                return capture[0].to_string();
+            }
+            match owners.get(id) {
+                Some(pages) if pages.len() == 1 => {
+                    changed += 1;
+                    let page = pages.iter().next().expect("single owner");
+                    format!(r##"href="{page}#{id}""##)
+                }
+                _ => capture[0].to_string(),
+            }
+        });
+        if changed > 0 {
+            fs::write(site_dir.join(name), updated.as_bytes())?;
+            rewritten += changed;
+        }
+    }
+    Ok(rewritten)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{finish_site, localize_stylesheets, rewrite_cross_page_links, SiteStylesheet};
+    use std::path::PathBuf;
+
+    #[test]
+    fn stylesheets_are_numbered_into_the_assets_directory() {
+        let project = PathBuf::from("/project");
+        let mut options = vec![
+            "--css".to_string(),
+            "/lib/pandoc/css/base.css".to_string(),
+            "--toc".to_string(),
+            "--css".to_string(),
+            "theme/default.css".to_string(),
+        ];
+
+        let stylesheets = localize_stylesheets(&mut options, &project);
+
+        assert_eq!(options[1], "assets/01-base.css");
+        assert_eq!(options[4], "assets/02-default.css");
+        assert_eq!(
+            stylesheets,
+            vec![
+                SiteStylesheet {
+                    source: PathBuf::from("/lib/pandoc/css/base.css"),
+                    name: "01-base.css".to_string(),
+                },
+                SiteStylesheet {
+                    source: project.join("theme/default.css"),
+                    name: "02-default.css".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn fragment_links_point_at_the_page_that_owns_the_anchor() {
+        let site = tempfile::tempdir().expect("site");
+        std::fs::write(
+            site.path().join("index.html"),
+            r##"<a href="#fig:arch">Figure 1</a> <a href="#intro">Intro</a> <a href="#dup">Dup</a>
+<h1 id="intro">Intro</h1>"##,
+        )
+        .expect("index");
+        std::fs::write(
+            site.path().join("1-design.html"),
+            r#"<figure id="fig:arch"></figure><p id="dup"></p>"#,
+        )
+        .expect("design");
+        std::fs::write(site.path().join("2-appendix.html"), r#"<p id="dup"></p>"#)
+            .expect("appendix");
+
+        assert_eq!(rewrite_cross_page_links(site.path()).expect("rewrite"), 1);
+        let index = std::fs::read_to_string(site.path().join("index.html")).expect("index");
+        assert!(index.contains(r##"href="1-design.html#fig:arch""##));
+        assert!(index.contains(r##"href="#intro""##));
+        assert!(index.contains(r##"href="#dup""##));
+    }
+
+    #[test]
+    fn finished_site_replaces_the_previous_build() {
+        let root = tempfile::tempdir().expect("root");
+        let staging = root.path().join("staging");
+        let site = root.path().join("build/book-site");
+        let css = root.path().join("site.css");
+        std::fs::create_dir_all(&staging).expect("staging");
+        std::fs::create_dir_all(&site).expect("old site");
+        std::fs::write(site.join("stale.html"), "old").expect("stale page");
+        std::fs::write(staging.join("index.html"), "new").expect("index");
+        std::fs::write(&css, "body {}").expect("css");
+
+        finish_site(
+            &staging,
+            &site,
+            &[SiteStylesheet {
+                source: css,
+                name: "01-site.css".to_string(),
+            }],
+        )
+        .expect("finish site");
+
+        assert!(site.join("index.html").is_file());
+        assert!(site.join("assets/01-site.css").is_file());
+        assert!(!site.join("stale.html").exists());
+        assert!(!staging.exists());
+    }
+}
@@ -9,12 +9,14 @@ const MAX_NEEDLES: usize = 8;
 const CONTEXT_RADIUS: usize = 1;
 const MAX_CONTEXT_CHARS: usize = 140;
 
+/// One Markdown element carrying a `data-pos` source position.
 #[derive(Debug, Clone)]
-struct SourceSpan {
-    file: PathBuf,
-    line: usize,
-    column: usize,
-    text: String,
+pub(crate) struct SourceSpan {
+    pub(crate) file: PathBuf,
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+    pub(crate) end_line: usize,
+    pub(crate) text: String,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -149,14 +151,28 @@ fn load_source_spans(executor: &BuildExecutor, entry_file: &Path) -> Result<Vec<
     Ok(spans)
 }
 
+/// Source spans of every Markdown file in the project, entry file first.
+pub(crate) fn load_project_source_spans(
+    executor: &BuildExecutor,
+    project_root: &Path,
+    entry_file: &Path,
+) -> Result<Vec<SourceSpan>> {
+    let mut spans = Vec::new();
+    for file in markdown_candidates(project_root, entry_file) {
+        spans.extend(load_source_spans(executor, &file)?);
+    }
+    Ok(spans)
+}
+
 fn collect_spans(source_file: &Path, value: &Value, spans: &mut Vec<SourceSpan>) {
-    if let Some((line, column)) = data_pos_start(value) {
+    if let Some((line, column, end_line)) = data_pos(value) {
         let text = collect_text(value);
         if !text.trim().is_empty() {
             spans.push(SourceSpan {
                 file: source_file.to_path_buf(),
                 line,
                 column,
+                end_line,
                 text,
             });
         }
@@ -177,14 +193,16 @@ fn collect_spans(source_file: &Path, value: &Value, spans: &mut Vec<SourceSpan>)
     }
 }
 
-fn data_pos_start(value: &Value) -> Option<(usize, usize)> {
+fn data_pos(value: &Value) -> Option<(usize, usize, usize)> {
     let attrs = find_attr_keyvals(value)?;
     for keyval in attrs {
         let pair = keyval.as_array()?;
         let key = pair.first()?.as_str()?;
         let data_pos = pair.get(1)?.as_str()?;
         if key == "data-pos" {
-            return parse_data_pos_start(data_pos);
+            let (line, column) = parse_data_pos_start(data_pos)?;
+            let end_line = parse_data_pos_end_line(data_pos).unwrap_or(line).max(line);
+            return Some((line, column, end_line));
         }
     }
     None
@@ -201,6 +219,11 @@ fn parse_data_pos_start(data_pos: &str) -> Option<(usize, usize)> {
     Some((line, column))
 }
 
+fn parse_data_pos_end_line(data_pos: &str) -> Option<usize> {
+    let (_, end) = data_pos.split_once('-')?;
+    end.split(':').next()?.parse::<usize>().ok()
+}
+
 fn find_attr_keyvals(value: &Value) -> Option<&Vec<Value>> {
     match value {
         Value::Array(items) if items.len() == 3 => items.get(2)?.as_array(),
@@ -786,7 +809,7 @@ mod tests {
     use super::{
         build_markdown_diagnostic, classify_diagnostic, configured_project_root, extract_needles,
         find_column, locate_direct_markdown_location, locate_in_raw_markdown, markdown_candidates,
-        parse_data_pos_start, path_suffix_matches, source_context_line,
+        parse_data_pos_end_line, parse_data_pos_start, path_suffix_matches, source_context_line,
     };
     use std::fs;
     use std::path::Path;
@@ -826,6 +849,8 @@ mod tests {
     fn parses_sourcepos_line_and_column() {
         assert_eq!(parse_data_pos_start("12:5-12:20"), Some((12, 5)));
         assert_eq!(parse_data_pos_start("9"), Some((9, 1)));
+        assert_eq!(parse_data_pos_end_line("12:5-14:2"), Some(14));
+        assert_eq!(parse_data_pos_end_line("9"), None);
     }
 
     #[test]
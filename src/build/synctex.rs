@@ -0,0 +1,511 @@
+//! SyncTeX 与 Markdown 源码位置之间的桥接
+//!
+//! Markdown PDF builds run the LaTeX engine with SyncTeX enabled and keep the
+//! engine's `.synctex.gz` together with the intermediate `.tex` file. SyncTeX
+//! relates PDF positions to lines of that `.tex` file; the Markdown side is
+//! recovered by matching the words on a `.tex` line against the `data-pos`
+//! spans Pandoc reports for the project's Markdown sources.
+
+use crate::build::source_map::SourceSpan;
+use crate::error::{OmniDocError, Result};
+use flate2::read::GzDecoder;
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+/// Scaled points per PostScript point.
+const SP_PER_BP: f64 = 65781.76;
+/// SyncTeX positions are relative to TeX's reference point, one inch from the
+/// top-left corner of the page.
+const ONE_INCH_SP: f64 = 72.27 * 65536.0;
+/// Lines searched backwards when the `.tex` line under the cursor holds only
+/// markup, for example `\end{itemize}`.
+const CONTEXT_LINES: usize = 5;
+
+static LATEX_COMMAND: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\\[A-Za-z@]+\*?").expect("LaTeX command pattern"));
+
+/// One positioned node of a SyncTeX file, in PDF points from the top-left
+/// corner of its page.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SyncRecord {
+    pub(crate) page: usize,
+    pub(crate) tag: u32,
+    pub(crate) line: usize,
+    pub(crate) x: f64,
+    pub(crate) y: f64,
+    pub(crate) width: f64,
+    pub(crate) height: f64,
+    pub(crate) depth: f64,
+    boxed: bool,
+}
+
+impl SyncRecord {
+    fn distance_to(&self, x: f64, y: f64) -> f64 {
+        let (left, right) = (
+            self.x.min(self.x + self.width),
+            self.x.max(self.x + self.width),
+        );
+        let (top, bottom) = (self.y - self.height, self.y + self.depth);
+        let dx = if x < left {
+            left - x
+        } else if x > right {
+            x - right
+        } else {
+            0.0
+        };
+        let dy = if y < top {
+            top - y
+        } else if y > bottom {
+            y - bottom
+        } else {
+            0.0
+        };
+        dx.hypot(dy)
+    }
+
+    fn area(&self) -> f64 {
+        self.width.abs() * (self.height + self.depth).abs()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SyncTex {
+    inputs: BTreeMap<u32, PathBuf>,
+    records: Vec<SyncRecord>,
+}
+
+impl SyncTex {
+    /// Read a `.synctex.gz` (or uncompressed `.synctex`) file.
+    pub(crate) fn read(path: &Path) -> Result<Self> {
+        let bytes = fs::read(path)?;
+        let text = if path.extension().is_some_and(|extension| extension == "gz") {
+            let mut text = String::new();
+            GzDecoder::new(bytes.as_slice())
+                .read_to_string(&mut text)
+                .map_err(|error| {
+                    OmniDocError::Other(format!(
+                        "cannot decompress SyncTeX file {}: {error}",
+                        path.display()
+                    ))
+                })?;
+            text
+        } else {
+            String::from_utf8_lossy(&bytes).to_string()
+        };
+        Ok(Self::parse(&text))
+    }
+
+    pub(crate) fn parse(text: &str) -> Self {
+        let mut synctex = Self::default();
+        let mut unit = 1.0;
+        let mut magnification = 1000.0;
+        let (mut x_offset, mut y_offset) = (0.0, 0.0);
+        let mut page = 0;
+        let mut raw = Vec::new();
+        for line in text.lines() {
+            if let Some(input) = line.strip_prefix("Input:") {
+                if let Some((tag, path)) = input.split_once(':') {
+                    if let Ok(tag) = tag.parse() {
+                        synctex.inputs.insert(tag, PathBuf::from(path));
+                    }
+                }
+            } else if let Some(value) = line.strip_prefix("Unit:") {
+                unit = value.trim().parse().unwrap_or(unit);
+            } else if let Some(value) = line.strip_prefix("Magnification:") {
+                magnification = value.trim().parse().unwrap_or(magnification);
+            } else if let Some(value) = line.strip_prefix("X Offset:") {
+                x_offset = value.trim().parse().unwrap_or(x_offset);
+            } else if let Some(value) = line.strip_prefix("Y Offset:") {
+                y_offset = value.trim().parse().unwrap_or(y_offset);
+            } else if let Some(number) = line.strip_prefix('{') {
+                page = number.trim().parse().unwrap_or(page + 1);
+            } else if page > 0 {
+                if let Some(record) = parse_record(page, line) {
+                    raw.push(record);
+                }
+            }
+        }
+
+        let scale = unit * magnification / 1000.0 / SP_PER_BP;
+        let x_origin = (ONE_INCH_SP + x_offset) / SP_PER_BP;
+        let y_origin = (ONE_INCH_SP + y_offset) / SP_PER_BP;
+        synctex.records = raw
+            .into_iter()
+            .map(|mut record| {
+                record.x = record.x * scale + x_origin;
+                record.y = record.y * scale + y_origin;
+                record.width *= scale;
+                record.height *= scale;
+                record.depth *= scale;
+                record
+            })
+            .collect();
+        synctex
+    }
+
+    /// Tag of the input whose file name is `file_name`.
+    pub(crate) fn input_tag(&self, file_name: &str) -> Option<u32> {
+        self.inputs.iter().find_map(|(tag, path)| {
+            (path.file_name().and_then(|name| name.to_str()) == Some(file_name)).then_some(*tag)
+        })
+    }
+
+    /// The node of input `tag` closest to (`x`, `y`) on `page`; when several
+    /// boxes contain the point the smallest one wins.
+    pub(crate) fn record_at(&self, tag: u32, page: usize, x: f64, y: f64) -> Option<&SyncRecord> {
+        self.records
+            .iter()
+            .filter(|record| record.page == page && record.tag == tag)
+            .min_by(|left, right| {
+                let left_key = (left.distance_to(x, y), left.area());
+                let right_key = (right.distance_to(x, y), right.area());
+                left_key
+                    .partial_cmp(&right_key)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+    }
+
+    /// The first typeset node produced from `line` of input `tag`, or from
+    /// the nearest line that produced one.
+    pub(crate) fn record_for_line(&self, tag: u32, line: usize) -> Option<&SyncRecord> {
+        self.records
+            .iter()
+            .filter(|record| record.tag == tag)
+            .min_by_key(|record| {
+                (
+                    record.line.abs_diff(line),
+                    record.line < line,
+                    record.page,
+                    !record.boxed,
+                    (record.y * 100.0) as i64,
+                )
+            })
+    }
+}
+
+fn parse_record(page: usize, line: &str) -> Option<SyncRecord> {
+    let mut characters = line.chars();
+    let kind = characters.next()?;
+    let rest = characters.as_str().trim_start();
+    let boxed = match kind {
+        '[' | '(' | 'v' | 'h' => true,
+        'x' | 'k' | 'g' | '$' => false,
+        _ => return None,
+    };
+    let mut parts = rest.split(':');
+    let mut link = parts.next()?.split(',');
+    let tag = link.next()?.parse().ok()?;
+    let source_line = link.next()?.parse().ok()?;
+    let mut point = parts.next()?.split(',');
+    let x = point.next()?.parse().ok()?;
+    let y = point.next()?.parse().ok()?;
+    let mut size = parts
+        .next()
+        .map(|size| {
+            size.split(',')
+                .map(|value| value.parse::<f64>().unwrap_or(0.0))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default()
+        .into_iter();
+    Some(SyncRecord {
+        page,
+        tag,
+        line: source_line,
+        x,
+        y,
+        width: size.next().unwrap_or(0.0),
+        height: if boxed {
+            size.next().unwrap_or(0.0)
+        } else {
+            0.0
+        },
+        depth: if boxed {
+            size.next().unwrap_or(0.0)
+        } else {
+            0.0
+        },
+        boxed,
+    })
+}
+
+/// A Markdown source position recovered from a `.tex` line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct MarkdownPosition {
+    pub(crate) file: PathBuf,
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+}
+
+/// Matches lines of the intermediate `.tex` file against Markdown spans.
+pub(crate) struct SourceBridge<'a> {
+    tex_lines: Vec<BTreeSet<String>>,
+    spans: &'a [SourceSpan],
+    words: Vec<BTreeSet<String>>,
+}
+
+impl<'a> SourceBridge<'a> {
+    pub(crate) fn new(tex: &str, spans: &'a [SourceSpan]) -> Self {
+        Self {
+            tex_lines: tex.lines().map(latex_words).collect(),
+            words: spans.iter().map(|span| words(&span.text)).collect(),
+            spans,
+        }
+    }
+
+    /// Markdown position of the text typeset from `.tex` line `tex_line`
+    /// (1-based). Lines holding only markup defer to the closest line above.
+    pub(crate) fn markdown_for_tex_line(&self, tex_line: usize) -> Option<MarkdownPosition> {
+        let index = tex_line.checked_sub(1)?;
+        let last = index.min(self.tex_lines.len().checked_sub(1)?);
+        (last.saturating_sub(CONTEXT_LINES)..=last)
+            .rev()
+            .filter(|index| !self.tex_lines[*index].is_empty())
+            .find_map(|index| self.markdown_for_words(&self.tex_lines[index]))
+    }
+
+    fn markdown_for_words(&self, needle: &BTreeSet<String>) -> Option<MarkdownPosition> {
+        // Single-line spans pin the exact line; blocks spanning several lines
+        // (code blocks, for example) are the fallback.
+        let mut lines: BTreeMap<(&Path, usize), (BTreeSet<&str>, usize)> = BTreeMap::new();
+        for (span, words) in self.spans.iter().zip(&self.words) {
+            if span.end_line != span.line {
+                continue;
+            }
+            let entry = lines
+                .entry((span.file.as_path(), span.line))
+                .or_insert_with(|| (BTreeSet::new(), span.column));
+            entry.0.extend(words.iter().map(String::as_str));
+            if !words.is_disjoint(needle) {
+                entry.1 = entry.1.min(span.column);
+            }
+        }
+        let best_line = lines
+            .iter()
+            .map(|(&(file, line), (words, column))| {
+                let overlap = words.iter().filter(|word| needle.contains(**word)).count();
+                (overlap, std::cmp::Reverse(words.len()), file, line, *column)
+            })
+            .filter(|candidate| candidate.0 > 0)
+            .max_by(|left, right| {
+                (left.0, left.1)
+                    .cmp(&(right.0, right.1))
+                    .then_with(|| (right.2, right.3).cmp(&(left.2, left.3)))
+            });
+        if let Some((_, _, file, line, column)) = best_line {
+            return Some(MarkdownPosition {
+                file: file.to_path_buf(),
+                line,
+                column,
+            });
+        }
+
+        self.spans
+            .iter()
+            .zip(&self.words)
+            .filter(|(span, _)| span.end_line != span.line)
+            .map(|(span, words)| (words.intersection(needle).count(), span))
+            .filter(|(overlap, _)| *overlap > 0)
+            .max_by_key(|(overlap, span)| (*overlap, std::cmp::Reverse(span.end_line - span.line)))
+            .map(|(_, span)| MarkdownPosition {
+                file: span.file.clone(),
+                line: span.line,
+                column: span.column,
+            })
+    }
+
+    /// `.tex` line (1-based) holding the text of Markdown `file` at `line`.
+    pub(crate) fn tex_line_for_markdown(&self, file: &Path, line: usize) -> Option<usize> {
+        let in_file = || {
+            self.spans
+                .iter()
+                .zip(&self.words)
+                .filter(move |(span, words)| span.file == file && !words.is_empty())
+        };
+        // Words written on `line` or the closest line above it, unless a
+        // block spanning `line` (a code block, say) starts after that line.
+        let nearest = in_file()
+            .filter(|(span, _)| span.end_line == span.line && span.line <= line)
+            .map(|(span, _)| span.line)
+            .max();
+        let block = in_file()
+            .filter(|(span, _)| span.line <= line && line <= span.end_line)
+            .filter(|(span, _)| span.end_line != span.line)
+            .min_by_key(|(span, _)| span.end_line - span.line);
+        let needle = match (nearest, block) {
+            (Some(nearest), Some((span, words))) if span.line > nearest => words.clone(),
+            (Some(nearest), _) => in_file()
+                .filter(|(span, _)| span.end_line == span.line && span.line == nearest)
+                .flat_map(|(_, words)| words.iter().cloned())
+                .collect::<BTreeSet<_>>(),
+            (None, Some((_, words))) => words.clone(),
+            (None, None) => return None,
+        };
+
+        self.tex_lines
+            .iter()
+            .enumerate()
+            .map(|(index, words)| (words.intersection(&needle).count(), index))
+            .filter(|(overlap, _)| *overlap > 0)
+            .max_by_key(|(overlap, index)| (*overlap, std::cmp::Reverse(*index)))
+            .map(|(_, index)| index + 1)
+    }
+}
+
+fn latex_words(line: &str) -> BTreeSet<String> {
+    let line = line.split_once('%').map_or(line, |(code, _)| code);
+    words(&LATEX_COMMAND.replace_all(line, " "))
+}
+
+/// Lower-cased words of at least two characters; CJK text has no spaces, so
+/// each of its characters counts as a word.
+fn words(text: &str) -> BTreeSet<String> {
+    let mut words = BTreeSet::new();
+    for chunk in text.split(|character: char| !character.is_alphanumeric()) {
+        let mut latin = String::new();
+        for character in chunk.chars() {
+            if is_cjk(character) {
+                push_word(&mut words, &mut latin);
+                words.insert(character.to_string());
+            } else {
+                latin.extend(character.to_lowercase());
+            }
+        }
+        push_word(&mut words, &mut latin);
+    }
+    words
+}
+
+fn push_word(words: &mut BTreeSet<String>, word: &mut String) {
+    if word.chars().count() >= 2 {
+        words.insert(std::mem::take(word));
+    } else {
+        word.clear();
+    }
+}
+
+fn is_cjk(character: char) -> bool {
+    matches!(
+        character as u32,
+        0x3040..=0x30FF | 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xAC00..=0xD7AF | 0xF900..=0xFAFF
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SourceBridge, SyncTex};
+    use crate::build::source_map::SourceSpan;
+    use std::path::{Path, PathBuf};
+
+    const SYNCTEX: &str = "SyncTeX Version:1\n\
+Input:1:/tmp/render/input.tex\n\
+Input:2:/usr/share/texmf/tex/latex/base/article.cls\n\
+Output:pdf\n\
+Magnification:1000\n\
+Unit:1\n\
+X Offset:0\n\
+Y Offset:0\n\
+Content:\n\
+!220\n\
+{1\n\
+[1,12:0,0:30000000,40000000,0\n\
+(1,12:0,3000000:20000000,600000,200000\n\
+g1,12:1000000,3000000\n\
+)\n\
+(1,14:0,6000000:20000000,600000,200000\n\
+)\n\
+]\n\
+}1\n\
+{2\n\
+[1,20:0,0:30000000,40000000,0\n\
+(1,20:0,3000000:20000000,600000,200000\n\
+)\n\
+]\n\
+}2\n\
+Postamble:\n";
+
+    fn span(file: &str, line: usize, end_line: usize, text: &str) -> SourceSpan {
+        SourceSpan {
+            file: PathBuf::from(file),
+            line,
+            column: 1,
+            end_line,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn parses_records_in_pdf_points_from_the_page_corner() {
+        let synctex = SyncTex::parse(SYNCTEX);
+        assert_eq!(synctex.input_tag("input.tex"), Some(1));
+
+        let line = synctex.record_at(1, 1, 100.0, 160.0).expect("record");
+        assert_eq!(line.line, 14);
+        assert!((line.x - 72.27 * 65536.0 / 65781.76).abs() < 1e-6);
+        let line = synctex.record_at(1, 1, 100.0, 114.0).expect("record");
+        assert_eq!((line.line, line.boxed), (12, true));
+
+        let forward = synctex.record_for_line(1, 19).expect("forward record");
+        assert_eq!((forward.page, forward.line), (2, 20));
+    }
+
+    #[test]
+    fn bridges_tex_lines_and_markdown_spans() {
+        let spans = vec![
+            span("main.md", 3, 4, "Alpha beta gamma delta"),
+            span("main.md", 3, 3, "Alpha beta"),
+            span("main.md", 4, 4, "gamma delta"),
+            span("chapters/intro.md", 7, 7, "电路 分析"),
+            span("main.md", 9, 12, "fn main() { println!() }"),
+        ];
+        let tex = "\\begin{document}\n\
+\\section{Intro}\\label{intro}\n\
+Alpha beta\n\
+gamma \\emph{delta}\n\
+\\end{itemize}\n\
+电路分析\n\
+\\begin{verbatim}\n\
+fn main() {\n";
+        let bridge = SourceBridge::new(tex, &spans);
+
+        let position = bridge.markdown_for_tex_line(4).expect("inverse");
+        assert_eq!(
+            (position.file.as_path(), position.line),
+            (Path::new("main.md"), 4)
+        );
+        let position = bridge.markdown_for_tex_line(5).expect("markup-only line");
+        assert_eq!(position.line, 4);
+        let position = bridge.markdown_for_tex_line(6).expect("CJK line");
+        assert_eq!(
+            (position.file.as_path(), position.line),
+            (Path::new("chapters/intro.md"), 7)
+        );
+        let position = bridge.markdown_for_tex_line(8).expect("code block");
+        assert_eq!(position.line, 9);
+
+        assert_eq!(
+            bridge.tex_line_for_markdown(Path::new("main.md"), 4),
+            Some(4)
+        );
+        assert_eq!(
+            bridge.tex_line_for_markdown(Path::new("main.md"), 5),
+            Some(4)
+        );
+        assert_eq!(
+            bridge.tex_line_for_markdown(Path::new("main.md"), 8),
+            Some(4)
+        );
+        assert_eq!(
+            bridge.tex_line_for_markdown(Path::new("main.md"), 10),
+            Some(8)
+        );
+        assert_eq!(
+            bridge.tex_line_for_markdown(Path::new("chapters/intro.md"), 7),
+            Some(6)
+        );
+    }
+}
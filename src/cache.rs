@@ -0,0 +1,825 @@
+//! Build caches shared between checkouts and CI jobs.
+//!
+//! A shared cache is a directory (a network mount, a CI cache volume) or an
+//! HTTP server that stores objects under the same relative keys:
+//!
+//! - `objects/<aa>/<blake3>`: artifact and rendered diagram bytes;
+//! - `builds/<key>.json`: one [`SharedBuildEntry`] per build target and
+//!   `BuildInputState` digest;
+//! - `targets/<key>.json`: the newest entry of a build target, which seeds the
+//!   diagram cache of a build that cannot be restored as a whole.
+//!
+//! The HTTP protocol is plain `GET`, `HEAD` and `PUT` of those keys below the
+//! base URL, with `404` meaning a miss, so any static file server that accepts
+//! uploads can serve it. Every object is verified against its digest before it
+//! is written into a project.
+
+use crate::build::tectonic::expand_home;
+use crate::config::MergedConfig;
+use crate::error::{OmniDocError, Result};
+use crate::project_tools::{fragment_cache_dir, project_cache_dir, render_cache_dir};
+use crate::utils::fs;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use walkdir::WalkDir;
+
+const ENTRY_SCHEMA: u32 = 1;
+const OBJECTS_DIR: &str = "objects";
+const BUILDS_DIR: &str = "builds";
+const TARGETS_DIR: &str = "targets";
+
+/// Where a shared cache lives.
+#[derive(Debug, Clone)]
+pub enum SharedCacheStore {
+    Directory(PathBuf),
+    Http(String),
+}
+
+/// One file recorded in a shared build entry.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SharedFile {
+    /// Project-relative artifact path, or the diagram's file name.
+    pub path: String,
+    pub digest: String,
+    pub bytes: u64,
+}
+
+/// Artifacts and diagrams produced by one build of one target.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SharedBuildEntry {
+    pub schema: u32,
+    pub target: String,
+    pub input_digest: String,
+    pub created_at: u64,
+    pub artifacts: Vec<SharedFile>,
+    pub renders: Vec<SharedFile>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
+pub struct CacheUsage {
+    pub files: u64,
+    pub bytes: u64,
+}
+
+/// Sizes of a project's local caches and of its shared cache.
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheStats {
+    pub project_root: String,
+    pub local: CacheUsage,
+    pub fragments: CacheUsage,
+    pub renders: CacheUsage,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shared: Option<SharedCacheStats>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SharedCacheStats {
+    pub location: String,
+    pub builds: u64,
+    pub targets: u64,
+    pub objects: CacheUsage,
+}
+
+/// What `omnidoc cache prune` removed, or would remove with `--dry-run`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CachePruneReport {
+    pub dry_run: bool,
+    pub fragments: CacheUsage,
+    pub renders: CacheUsage,
+    pub builds: u64,
+    pub targets: u64,
+    pub objects: CacheUsage,
+}
+
+/// Integrity problems found by `omnidoc cache verify`.
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheVerifyReport {
+    pub location: String,
+    pub valid: bool,
+    pub builds: u64,
+    pub objects: u64,
+    pub problems: Vec<String>,
+}
+
+/// 打开项目配置的共享缓存；未配置时返回 `None`
+pub fn shared_cache_store(
+    project_path: &Path,
+    config: &MergedConfig,
+) -> Result<Option<SharedCacheStore>> {
+    config
+        .shared_cache
+        .as_deref()
+        .map(|location| SharedCacheStore::open(location, project_path))
+        .transpose()
+}
+
+impl SharedCacheStore {
+    /// Resolve `[cache] shared`; relative directories are project-relative.
+    pub fn open(location: &str, project_path: &Path) -> Result<Self> {
+        let location = location.trim();
+        if let Some((scheme, _)) = location.split_once("://") {
+            if !matches!(scheme.to_ascii_lowercase().as_str(), "http" | "https") {
+                return Err(OmniDocError::Config(format!(
+                    "Unsupported shared cache scheme '{scheme}'; use a directory or an http(s) URL"
+                )));
+            }
+            return Ok(Self::Http(location.trim_end_matches('/').to_string()));
+        }
+        let path = PathBuf::from(expand_home(location));
+        Ok(Self::Directory(if path.is_absolute() {
+            path
+        } else {
+            project_path.join(path)
+        }))
+    }
+
+    pub fn location(&self) -> String {
+        match self {
+            Self::Directory(path) => path.display().to_string(),
+            Self::Http(base) => base.clone(),
+        }
+    }
+
+    /// Restore the artifacts of `target` built from `input_digest`. Returns
+    /// the number of files written, or `None` when the cache has no entry.
+    pub fn restore_build(
+        &self,
+        project_path: &Path,
+        target: &str,
+        input_digest: &str,
+    ) -> Result<Option<usize>> {
+        let key = build_key(target, input_digest);
+        let Some(entry) = self.read_entry(&key)? else {
+            return Ok(None);
+        };
+        if entry.schema != ENTRY_SCHEMA
+            || entry.target != target
+            || entry.input_digest != input_digest
+        {
+            return Ok(None);
+        }
+        // Fetch everything before touching the project so a partial cache
+        // never leaves a mix of old and restored artifacts behind.
+        let mut restored = Vec::new();
+        for artifact in &entry.artifacts {
+            let destination = project_path.join(safe_relative_path(&artifact.path)?);
+            let Some(bytes) = self.read_object(&artifact.digest)? else {
+                return Ok(None);
+            };
+            restored.push((destination, bytes));
+        }
+        for (destination, bytes) in &restored {
+            if let Some(parent) = destination.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::atomic_write(destination, bytes)?;
+        }
+        self.touch(&key);
+        Ok(Some(restored.len()))
+    }
+
+    /// Seed the local diagram cache from the newest shared build of
+    /// `target`. Returns the number of diagrams downloaded.
+    pub fn prefetch_renders(&self, project_path: &Path, target: &str) -> Result<usize> {
+        match self.read_entry(&target_key(target))? {
+            Some(entry) if entry.schema == ENTRY_SCHEMA && entry.target == target => {
+                self.fetch_renders(project_path, &entry)
+            }
+            _ => Ok(0),
+        }
+    }
+
+    /// Upload a finished build under `input_digest`, the state written after
+    /// filters and engines reported their dependencies. The state probed
+    /// before the build misses inputs only depfiles name, so it would let a
+    /// checkout that changed one of them restore a stale artifact. Returns
+    /// the number of objects uploaded.
+    pub fn store_build(
+        &self,
+        project_path: &Path,
+        target: &str,
+        input_digest: &str,
+        artifacts: &[PathBuf],
+        renders: &[String],
+    ) -> Result<usize> {
+        let mut uploaded = 0;
+        let mut upload = |path: &Path, name: String| -> Result<SharedFile> {
+            let bytes = std::fs::read(path)?;
+            let digest = blake3::hash(&bytes).to_hex().to_string();
+            let key = object_key(&digest)?;
+            if !self.contains(&key)? {
+                self.put(&key, &bytes)?;
+                uploaded += 1;
+            }
+            Ok(SharedFile {
+                path: name,
+                digest,
+                bytes: bytes.len() as u64,
+            })
+        };
+        let artifacts = artifacts
+            .iter()
+            .map(|artifact| {
+                let relative = artifact.strip_prefix(project_path).map_err(|_| {
+                    OmniDocError::Project(format!(
+                        "Cannot share an artifact outside the project: {}",
+                        artifact.display()
+                    ))
+                })?;
+                upload(artifact, relative.to_string_lossy().replace('\\', "/"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let render_dir = render_cache_dir(project_path);
+        let renders = renders
+            .iter()
+            .filter(|name| is_render_name(name) && render_dir.join(name).is_file())
+            .map(|name| upload(&render_dir.join(name), name.clone()))
+            .collect::<Result<Vec<_>>>()?;
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        let entry = SharedBuildEntry {
+            schema: ENTRY_SCHEMA,
+            target: target.to_string(),
+            input_digest: input_digest.to_string(),
+            created_at,
+            artifacts,
+            renders,
+        };
+        let content = serde_json::to_vec_pretty(&entry)
+            .map_err(|error| OmniDocError::Other(error.to_string()))?;
+        self.put(&build_key(target, input_digest), &content)?;
+        self.put(&target_key(target), &content)?;
+        Ok(uploaded)
+    }
+
+    fn fetch_renders(&self, project_path: &Path, entry: &SharedBuildEntry) -> Result<usize> {
+        let render_dir = render_cache_dir(project_path);
+        let mut fetched = 0;
+        for render in &entry.renders {
+            if !is_render_name(&render.path) || render_dir.join(&render.path).is_file() {
+                continue;
+            }
+            if let Some(bytes) = self.read_object(&render.digest)? {
+                fs::create_dir_all(&render_dir)?;
+                fs::atomic_write(render_dir.join(&render.path), bytes)?;
+                fetched += 1;
+            }
+        }
+        Ok(fetched)
+    }
+
+    fn read_entry(&self, key: &str) -> Result<Option<SharedBuildEntry>> {
+        Ok(self
+            .get(key)?
+            .and_then(|content| serde_json::from_slice(&content).ok()))
+    }
+
+    fn read_object(&self, digest: &str) -> Result<Option<Vec<u8>>> {
+        let Some(bytes) = self.get(&object_key(digest)?)? else {
+            return Ok(None);
+        };
+        if blake3::hash(&bytes).to_hex().as_str() != digest {
+            return Err(OmniDocError::Project(format!(
+                "Shared cache object {digest} is corrupt; run `omnidoc cache verify`"
+            )));
+        }
+        Ok(Some(bytes))
+    }
+
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        match self {
+            Self::Directory(root) => match std::fs::read(root.join(key)) {
+                Ok(bytes) => Ok(Some(bytes)),
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(error) => Err(error.into()),
+            },
+            Self::Http(base) => {
+                let url = format!("{base}/{key}");
+                let response = reqwest::blocking::get(&url).map_err(|_| http_error(0, &url))?;
+                match response.status().as_u16() {
+                    404 => Ok(None),
+                    status if response.status().is_success() => response
+                        .bytes()
+                        .map(|bytes| Some(bytes.to_vec()))
+                        .map_err(|_| http_error(status, &url)),
+                    status => Err(http_error(status, &url)),
+                }
+            }
+        }
+    }
+
+    fn contains(&self, key: &str) -> Result<bool> {
+        match self {
+            Self::Directory(root) => Ok(root.join(key).is_file()),
+            Self::Http(base) => {
+                let url = format!("{base}/{key}");
+                let response = reqwest::blocking::Client::new()
+                    .head(&url)
+                    .send()
+                    .map_err(|_| http_error(0, &url))?;
+                Ok(response.status().is_success())
+            }
+        }
+    }
+
+    fn put(&self, key: &str, bytes: &[u8]) -> Result<()> {
+        match self {
+            Self::Directory(root) => {
+                let path = root.join(key);
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::atomic_write(path, bytes)
+            }
+            Self::Http(base) => {
+                let url = format!("{base}/{key}");
+                let response = reqwest::blocking::Client::new()
+                    .put(&url)
+                    .body(bytes.to_vec())
+                    .send()
+                    .map_err(|_| http_error(0, &url))?;
+                if !response.status().is_success() {
+                    return Err(http_error(response.status().as_u16(), &url));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Mark a directory entry as recently used so `prune` keeps it.
+    fn touch(&self, key: &str) {
+        if let Self::Directory(root) = self {
+            if let Ok(file) = std::fs::File::options().append(true).open(root.join(key)) {
+                let _ = file.set_modified(SystemTime::now());
+            }
+        }
+    }
+
+    fn directory(&self, operation: &str) -> Result<&Path> {
+        match self {
+            Self::Directory(root) => Ok(root),
+            Self::Http(base) => Err(OmniDocError::Project(format!(
+                "`omnidoc cache {operation}` needs a directory cache; the HTTP cache at {base} is managed by its server"
+            ))),
+        }
+    }
+
+    pub fn stats(&self) -> Result<SharedCacheStats> {
+        let root = self.directory("stats")?;
+        Ok(SharedCacheStats {
+            location: self.location(),
+            builds: usage(&root.join(BUILDS_DIR)).files,
+            targets: usage(&root.join(TARGETS_DIR)).files,
+            objects: usage(&root.join(OBJECTS_DIR)),
+        })
+    }
+
+    /// Drop entries unused for `older_than`, then every object that no
+    /// remaining entry references.
+    pub fn prune(
+        &self,
+        older_than: Duration,
+        dry_run: bool,
+        report: &mut CachePruneReport,
+    ) -> Result<()> {
+        let root = self.directory("prune")?;
+        let cutoff = SystemTime::now()
+            .checked_sub(older_than)
+            .unwrap_or(UNIX_EPOCH);
+        let mut referenced = BTreeSet::new();
+        for (dir, removed) in [
+            (BUILDS_DIR, &mut report.builds),
+            (TARGETS_DIR, &mut report.targets),
+        ] {
+            for path in files_in(&root.join(dir)) {
+                let stale = modified(&path).is_none_or(|modified| modified < cutoff);
+                let entry = std::fs::read(&path)
+                    .ok()
+                    .and_then(|content| serde_json::from_slice::<SharedBuildEntry>(&content).ok());
+                match entry {
+                    Some(entry) if !stale => referenced.extend(
+                        entry
+                            .artifacts
+                            .into_iter()
+                            .chain(entry.renders)
+                            .map(|file| file.digest),
+                    ),
+                    _ => {
+                        *removed += 1;
+                        if !dry_run {
+                            fs::remove_file(&path)?;
+                        }
+                    }
+                }
+            }
+        }
+        for path in files_in(&root.join(OBJECTS_DIR)) {
+            let name = path.file_name().and_then(|name| name.to_str());
+            if name.is_some_and(|name| referenced.contains(name)) {
+                continue;
+            }
+            report.objects.files += 1;
+            report.objects.bytes += file_size(&path);
+            if !dry_run {
+                fs::remove_file(&path)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn verify(&self) -> Result<CacheVerifyReport> {
+        let root = self.directory("verify")?;
+        let mut problems = Vec::new();
+        let mut digests = BTreeSet::new();
+        let objects = files_in(&root.join(OBJECTS_DIR));
+        for path in &objects {
+            let relative = display_key(root, path);
+            let name = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or_default();
+            match std::fs::read(path) {
+                Ok(bytes) if blake3::hash(&bytes).to_hex().as_str() == name => {
+                    digests.insert(name.to_string());
+                }
+                Ok(_) => problems.push(format!("corrupt_object:{relative}")),
+                Err(error) => problems.push(format!("unreadable_object:{relative}:{error}")),
+            }
+        }
+        let entries = files_in(&root.join(BUILDS_DIR))
+            .into_iter()
+            .chain(files_in(&root.join(TARGETS_DIR)))
+            .collect::<Vec<_>>();
+        let mut builds = 0;
+        for path in &entries {
+            let relative = display_key(root, path);
+            let Some(entry) = std::fs::read(path)
+                .ok()
+                .and_then(|content| serde_json::from_slice::<SharedBuildEntry>(&content).ok())
+            else {
+                problems.push(format!("invalid_entry:{relative}"));
+                continue;
+            };
+            if path.starts_with(root.join(BUILDS_DIR)) {
+                builds += 1;
+            }
+            for file in entry.artifacts.iter().chain(&entry.renders) {
+                if !digests.contains(&file.digest) {
+                    problems.push(format!("missing_object:{relative}:{}", file.path));
+                }
+            }
+        }
+        Ok(CacheVerifyReport {
+            location: self.location(),
+            valid: problems.is_empty(),
+            builds,
+            objects: objects.len() as u64,
+            problems,
+        })
+    }
+}
+
+/// 统计项目本地缓存与共享缓存的占用
+pub fn cache_stats(project_path: &Path, shared: Option<&SharedCacheStore>) -> Result<CacheStats> {
+    Ok(CacheStats {
+        project_root: project_path.display().to_string(),
+        local: usage(&project_cache_dir(project_path)),
+        fragments: usage(&fragment_cache_dir(project_path)),
+        renders: usage(&render_cache_dir(project_path)),
+        shared: shared.map(SharedCacheStore::stats).transpose()?,
+    })
+}
+
+/// 清理本地片段与图表缓存；`older_than` 为 `None` 时全部清理
+pub fn prune_local(
+    project_path: &Path,
+    older_than: Option<Duration>,
+    dry_run: bool,
+    report: &mut CachePruneReport,
+) -> Result<()> {
+    let cutoff = older_than.map(|age| SystemTime::now().checked_sub(age).unwrap_or(UNIX_EPOCH));
+    for (dir, removed) in [
+        (fragment_cache_dir(project_path), &mut report.fragments),
+        (render_cache_dir(project_path), &mut report.renders),
+    ] {
+        for path in files_in(&dir) {
+            let stale = match cutoff {
+                Some(cutoff) => modified(&path).is_none_or(|modified| modified < cutoff),
+                None => true,
+            };
+            if !stale {
+                continue;
+            }
+            removed.files += 1;
+            removed.bytes += file_size(&path);
+            if !dry_run {
+                fs::remove_file(&path)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn build_key(target: &str, input_digest: &str) -> String {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(target.as_bytes());
+    hasher.update(&[0]);
+    hasher.update(input_digest.as_bytes());
+    format!("{BUILDS_DIR}/{}.json", hasher.finalize().to_hex())
+}
+
+fn target_key(target: &str) -> String {
+    format!(
+        "{TARGETS_DIR}/{}.json",
+        blake3::hash(target.as_bytes()).to_hex()
+    )
+}
+
+fn object_key(digest: &str) -> Result<String> {
+    if digest.len() != 64 || !digest.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return Err(OmniDocError::Project(format!(
+            "Invalid shared cache object digest '{digest}'"
+        )));
+    }
+    Ok(format!("{OBJECTS_DIR}/{}/{digest}", &digest[..2]))
+}
+
+/// Diagram file names are `<sha1>.<extension>`, as written by the filter.
+fn is_render_name(name: &str) -> bool {
+    name.split_once('.').is_some_and(|(key, extension)| {
+        !key.is_empty()
+            && key.bytes().all(|byte| byte.is_ascii_hexdigit())
+            && !extension.is_empty()
+            && extension.bytes().all(|byte| byte.is_ascii_alphanumeric())
+    })
+}
+
+fn safe_relative_path(path: &str) -> Result<PathBuf> {
+    let relative = PathBuf::from(path);
+    if relative.as_os_str().is_empty()
+        || !relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+    {
+        return Err(OmniDocError::Project(format!(
+            "Shared cache entry names an unsafe artifact path '{path}'"
+        )));
+    }
+    Ok(relative)
+}
+
+fn http_error(status: u16, url: &str) -> OmniDocError {
+    OmniDocError::HttpError {
+        status,
+        url: url.to_string(),
+    }
+}
+
+fn files_in(dir: &Path) -> Vec<PathBuf> {
+    WalkDir::new(dir)
+        .sort_by_file_name()
+        .into_iter()
+        .flatten()
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| entry.into_path())
+        .collect()
+}
+
+fn usage(dir: &Path) -> CacheUsage {
+    files_in(dir)
+        .iter()
+        .fold(CacheUsage::default(), |mut usage, path| {
+            usage.files += 1;
+            usage.bytes += file_size(path);
+            usage
+        })
+}
+
+fn file_size(path: &Path) -> u64 {
+    std::fs::metadata(path)
+        .map(|metadata| metadata.len())
+        .unwrap_or_default()
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+fn display_key(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+
+    fn write(path: &Path, content: &str) {
+        std::fs::create_dir_all(path.parent().expect("parent")).expect("parent dir");
+        std::fs::write(path, content).expect("write file");
+    }
+
+    #[test]
+    fn directory_store_round_trips_artifacts_and_diagrams_between_checkouts() {
+        let shared = tempfile::tempdir().expect("shared cache");
+        let producer = tempfile::tempdir().expect("producer checkout");
+        let consumer = tempfile::tempdir().expect("consumer checkout");
+        let store = SharedCacheStore::Directory(shared.path().to_path_buf());
+        let artifact = producer.path().join("build/guide.html");
+        write(&artifact, "<html>guide</html>");
+        let render = "0123abcd.svg".to_string();
+        write(&render_cache_dir(producer.path()).join(&render), "<svg/>");
+
+        let uploaded = store
+            .store_build(
+                producer.path(),
+                "html",
+                "blake3:after",
+                std::slice::from_ref(&artifact),
+                std::slice::from_ref(&render),
+            )
+            .expect("store build");
+        assert_eq!(uploaded, 2);
+        assert_eq!(
+            store
+                .store_build(
+                    producer.path(),
+                    "html",
+                    "blake3:after",
+                    &[artifact],
+                    &[render],
+                )
+                .expect("store again"),
+            0
+        );
+
+        assert_eq!(
+            store
+                .restore_build(consumer.path(), "html", "blake3:unknown")
+                .expect("miss"),
+            None
+        );
+        assert_eq!(
+            store
+                .restore_build(consumer.path(), "html", "blake3:before")
+                .expect("pre-build digest"),
+            None
+        );
+        assert_eq!(
+            store
+                .restore_build(consumer.path(), "html", "blake3:after")
+                .expect("restore"),
+            Some(1)
+        );
+        assert_eq!(
+            std::fs::read_to_string(consumer.path().join("build/guide.html")).expect("artifact"),
+            "<html>guide</html>"
+        );
+        assert_eq!(
+            store
+                .prefetch_renders(consumer.path(), "html")
+                .expect("prefetch"),
+            1
+        );
+        assert!(render_cache_dir(consumer.path())
+            .join("0123abcd.svg")
+            .is_file());
+
+        let stats = store.stats().expect("stats");
+        assert_eq!(
+            (stats.builds, stats.targets, stats.objects.files),
+            (1, 1, 2)
+        );
+        assert!(store.verify().expect("verify").valid);
+
+        let object = files_in(&shared.path().join(OBJECTS_DIR))
+            .into_iter()
+            .next()
+            .expect("object");
+        std::fs::write(&object, "tampered").expect("tamper");
+        let verification = store.verify().expect("verify tampered");
+        assert!(!verification.valid);
+        assert!(verification
+            .problems
+            .iter()
+            .any(|problem| problem.starts_with("corrupt_object:objects/")));
+
+        let mut report = CachePruneReport::default();
+        store
+            .prune(Duration::ZERO, false, &mut report)
+            .expect("prune everything");
+        assert_eq!(
+            (report.builds, report.targets, report.objects.files),
+            (1, 1, 2)
+        );
+        assert_eq!(store.stats().expect("stats").objects.files, 0);
+    }
+
+    #[test]
+    fn rejects_entries_that_escape_the_project() {
+        assert!(safe_relative_path("build/guide.pdf").is_ok());
+        assert!(safe_relative_path("../outside.pdf").is_err());
+        assert!(safe_relative_path("/etc/passwd").is_err());
+        assert!(is_render_name("0a1b.svg"));
+        assert!(!is_render_name("../0a1b.svg"));
+        assert!(SharedCacheStore::open("s3://bucket", Path::new(".")).is_err());
+    }
+
+    #[test]
+    fn http_store_uses_get_head_and_put_below_the_base_url() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let base = format!("http://{}/cache", listener.local_addr().expect("address"));
+        let server = std::thread::spawn(move || {
+            let mut objects = std::collections::HashMap::<String, Vec<u8>>::new();
+            let mut requests = Vec::new();
+            for stream in listener.incoming().take(6) {
+                let mut stream = stream.expect("connection");
+                let mut reader = BufReader::new(stream.try_clone().expect("clone"));
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).expect("request line");
+                let mut content_length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).expect("header");
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().expect("length");
+                        }
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).expect("body");
+                let mut parts = request_line.split_whitespace();
+                let method = parts.next().unwrap_or_default().to_string();
+                let path = parts.next().unwrap_or_default().to_string();
+                requests.push(format!("{method} {path}"));
+                let response = match (method.as_str(), objects.get(&path)) {
+                    ("PUT", _) => {
+                        objects.insert(path, body);
+                        b"HTTP/1.1 201 Created\r\nconnection: close\r\ncontent-length: 0\r\n\r\n"
+                            .to_vec()
+                    }
+                    ("GET", Some(object)) => {
+                        let mut response = format!(
+                            "HTTP/1.1 200 OK\r\nconnection: close\r\ncontent-length: {}\r\n\r\n",
+                            object.len()
+                        )
+                        .into_bytes();
+                        response.extend(object);
+                        response
+                    }
+                    ("HEAD", Some(_)) => {
+                        b"HTTP/1.1 200 OK\r\nconnection: close\r\ncontent-length: 0\r\n\r\n"
+                            .to_vec()
+                    }
+                    _ => {
+                        b"HTTP/1.1 404 Not Found\r\nconnection: close\r\ncontent-length: 0\r\n\r\n"
+                            .to_vec()
+                    }
+                };
+                stream.write_all(&response).expect("response");
+            }
+            requests
+        });
+
+        let producer = tempfile::tempdir().expect("producer checkout");
+        let consumer = tempfile::tempdir().expect("consumer checkout");
+        let artifact = producer.path().join("build/guide.pdf");
+        write(&artifact, "%PDF-1.7");
+        let store = SharedCacheStore::open(&format!("{base}/"), producer.path()).expect("store");
+        store
+            .store_build(producer.path(), "pdf", "blake3:state", &[artifact], &[])
+            .expect("upload");
+        assert_eq!(
+            store
+                .restore_build(consumer.path(), "pdf", "blake3:state")
+                .expect("restore"),
+            Some(1)
+        );
+        assert_eq!(
+            std::fs::read_to_string(consumer.path().join("build/guide.pdf")).expect("artifact"),
+            "%PDF-1.7"
+        );
+
+        let requests = server.join().expect("server");
+        assert!(requests[0].starts_with("HEAD /cache/objects/"));
+        assert!(requests[1].starts_with("PUT /cache/objects/"));
+        assert!(requests[2].starts_with("PUT /cache/builds/"));
+        assert!(requests[3].starts_with("PUT /cache/targets/"));
+        assert!(requests[4].starts_with("GET /cache/builds/"));
+        assert!(requests[5].starts_with("GET /cache/objects/"));
+    }
+}
@@ -128,7 +128,19 @@ pub enum Commands {
         #[arg(value_hint = ValueHint::DirPath)]
         path: Option<String>,
 
-        /// override output format (pdf, html, epub, docx, pptx, latex)
+        /// select one `[[documents]]` entry (default: every declared document)
+        #[arg(long = "doc", value_name = "NAME")]
+        document: Option<String>,
+
+        /// apply the `[profiles.NAME]` overlay from .omnidoc.toml
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
+
+        /// select the `[variants.NAME]` audience variant
+        #[arg(long, value_name = "NAME")]
+        variant: Option<String>,
+
+        /// override output format (pdf, html, html-site, epub, docx, pptx, beamer, revealjs, gfm, plain, man, jats, docbook, latex, typst)
         #[arg(long)]
         to: Option<String>,
 
@@ -140,7 +152,7 @@ pub enum Commands {
         #[arg(long = "output")]
         outputs: Vec<String>,
 
-        /// override PDF engine (xelatex, lualatex, pdflatex, tectonic, or executable path)
+        /// override PDF engine (xelatex, lualatex, pdflatex, tectonic, typst, or executable path)
         #[arg(long = "pdf-engine")]
         pdf_engine: Option<String>,
 
@@ -152,6 +164,10 @@ pub enum Commands {
         #[arg(long = "max-latex-passes")]
         max_latex_passes: Option<usize>,
 
+        /// number of outputs to build concurrently (0 uses one job per CPU)
+        #[arg(short = 'j', long)]
+        jobs: Option<usize>,
+
         /// force rebuild even when input cache is unchanged
         #[arg(short = 'F', long)]
         force: bool,
@@ -179,7 +195,19 @@ pub enum Commands {
         #[arg(value_hint = ValueHint::DirPath)]
         path: Option<String>,
 
-        /// override output format (pdf, html, epub, docx, pptx, latex)
+        /// select one `[[documents]]` entry (default: every declared document)
+        #[arg(long = "doc", value_name = "NAME")]
+        document: Option<String>,
+
+        /// apply the `[profiles.NAME]` overlay from .omnidoc.toml
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
+
+        /// select the `[variants.NAME]` audience variant
+        #[arg(long, value_name = "NAME")]
+        variant: Option<String>,
+
+        /// override output format (pdf, html, html-site, epub, docx, pptx, beamer, revealjs, gfm, plain, man, jats, docbook, latex, typst)
         #[arg(long)]
         to: Option<String>,
 
@@ -191,7 +219,7 @@ pub enum Commands {
         #[arg(long = "output")]
         outputs: Vec<String>,
 
-        /// override PDF engine (xelatex, lualatex, pdflatex, tectonic, or executable path)
+        /// override PDF engine (xelatex, lualatex, pdflatex, tectonic, typst, or executable path)
         #[arg(long = "pdf-engine")]
         pdf_engine: Option<String>,
 
@@ -203,14 +231,26 @@ pub enum Commands {
         #[arg(long = "max-latex-passes")]
         max_latex_passes: Option<usize>,
 
+        /// number of outputs to build concurrently (0 uses one job per CPU)
+        #[arg(short = 'j', long)]
+        jobs: Option<usize>,
+
         /// debounce interval in milliseconds
         #[arg(long = "debounce-ms", default_value_t = 250)]
         debounce_ms: u64,
 
         /// run the initial build and exit after one scan cycle
-        #[arg(long)]
+        #[arg(long, conflicts_with = "serve")]
         once: bool,
 
+        /// serve the build directory on localhost and live-reload pages after each rebuild
+        #[arg(long)]
+        serve: bool,
+
+        /// port for --serve (default: pick a free port)
+        #[arg(long, requires = "serve")]
+        port: Option<u16>,
+
         /// force rebuild even when input cache is unchanged
         #[arg(short = 'F', long)]
         force: bool,
@@ -228,13 +268,57 @@ pub enum Commands {
         verbose: bool,
     },
 
+    /// serve build, status, lint, and deps requests over a local JSON-RPC socket
+    #[command(
+        after_help = "Examples:\n  omnidoc daemon\n  omnidoc daemon docs --socket /tmp/omnidoc-docs.sock\n\nClients send one JSON-RPC 2.0 request per line, for example:\n  {\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"build\",\"params\":{\"outputs\":[\"html\"]}}\nMethods: build, status, lint, deps, stats, reload, shutdown. Builds stream `build/progress` notifications."
+    )]
+    Daemon {
+        /// set the path to a documentation project
+        #[arg(value_hint = ValueHint::DirPath)]
+        path: Option<String>,
+
+        /// Unix socket to listen on (default: .omnidoc-cache/daemon.sock in the project)
+        #[arg(long, value_hint = ValueHint::FilePath)]
+        socket: Option<String>,
+
+        /// show verbose build messages
+        #[arg(short = 'v', long)]
+        verbose: bool,
+    },
+
+    /// run a Language Server Protocol server on stdio for editors
+    #[command(
+        after_help = "Examples:\n  omnidoc lsp\n  omnidoc lsp --path docs\n\nEditors start the server themselves, e.g. Neovim:\n  vim.lsp.start({ name = \"omnidoc\", cmd = { \"omnidoc\", \"lsp\" } })\nFeatures: lint diagnostics, cross-reference/citation/path completion, admonition hover, go to included chapters."
+    )]
+    Lsp {
+        /// set the path to a documentation project (default: the editor's workspace root)
+        #[arg(long, value_hint = ValueHint::DirPath)]
+        path: Option<String>,
+
+        /// communicate over stdin/stdout (the only transport; accepted for editor compatibility)
+        #[arg(long)]
+        stdio: bool,
+    },
+
     /// build and publish generated artifacts into a dist directory
     Publish {
         /// set the path to a documentation project
         #[arg(value_hint = ValueHint::DirPath)]
         path: Option<String>,
 
-        /// override output format (pdf, html, epub, docx, pptx, latex)
+        /// select one `[[documents]]` entry (default: every declared document)
+        #[arg(long = "doc", value_name = "NAME")]
+        document: Option<String>,
+
+        /// apply the `[profiles.NAME]` overlay from .omnidoc.toml
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
+
+        /// select the `[variants.NAME]` audience variant
+        #[arg(long, value_name = "NAME")]
+        variant: Option<String>,
+
+        /// override output format (pdf, html, html-site, epub, docx, pptx, beamer, revealjs, gfm, plain, man, jats, docbook, latex, typst)
         #[arg(long)]
         to: Option<String>,
 
@@ -246,7 +330,7 @@ pub enum Commands {
         #[arg(long = "output")]
         outputs: Vec<String>,
 
-        /// override PDF engine (xelatex, lualatex, pdflatex, tectonic, or executable path)
+        /// override PDF engine (xelatex, lualatex, pdflatex, tectonic, typst, or executable path)
         #[arg(long = "pdf-engine")]
         pdf_engine: Option<String>,
 
@@ -258,6 +342,10 @@ pub enum Commands {
         #[arg(long = "max-latex-passes")]
         max_latex_passes: Option<usize>,
 
+        /// number of outputs to build concurrently (0 uses one job per CPU)
+        #[arg(short = 'j', long)]
+        jobs: Option<usize>,
+
         /// publish directory
         #[arg(long = "dist-dir", default_value = "dist")]
         dist_dir: String,
@@ -355,6 +443,18 @@ pub enum Commands {
         #[arg(value_hint = ValueHint::DirPath)]
         path: Option<String>,
 
+        /// select one `[[documents]]` entry (default: every declared document)
+        #[arg(long = "doc", value_name = "NAME")]
+        document: Option<String>,
+
+        /// apply the `[profiles.NAME]` overlay from .omnidoc.toml
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
+
+        /// select the `[variants.NAME]` audience variant
+        #[arg(long, value_name = "NAME")]
+        variant: Option<String>,
+
         /// output format to build (repeatable)
         #[arg(long = "output")]
         outputs: Vec<String>,
@@ -367,6 +467,18 @@ pub enum Commands {
         #[arg(value_hint = ValueHint::DirPath)]
         path: Option<String>,
 
+        /// select one `[[documents]]` entry (default: every declared document)
+        #[arg(long = "doc", value_name = "NAME")]
+        document: Option<String>,
+
+        /// apply the `[profiles.NAME]` overlay from .omnidoc.toml
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
+
+        /// select the `[variants.NAME]` audience variant
+        #[arg(long, value_name = "NAME")]
+        variant: Option<String>,
+
         /// check whether omnidoc.lock matches current project inputs
         #[arg(long, conflicts_with = "update")]
         check: bool,
@@ -392,6 +504,10 @@ pub enum Commands {
         #[arg(value_hint = ValueHint::DirPath)]
         path: Option<String>,
 
+        /// select one `[[documents]]` entry (default: every declared document)
+        #[arg(long = "doc", value_name = "NAME")]
+        document: Option<String>,
+
         /// emit stable JSON status
         #[arg(long)]
         json: bool,
@@ -406,7 +522,15 @@ pub enum Commands {
         #[arg(value_hint = ValueHint::DirPath)]
         path: Option<String>,
 
-        /// select the output format (pdf, html, epub, docx, pptx, latex)
+        /// select one `[[documents]]` entry (default: every declared document)
+        #[arg(long = "doc", value_name = "NAME")]
+        document: Option<String>,
+
+        /// select the `[variants.NAME]` audience variant
+        #[arg(long, value_name = "NAME")]
+        variant: Option<String>,
+
+        /// select the output format (pdf, html, html-site, epub, docx, pptx, beamer, revealjs, gfm, plain, man, jats, docbook, latex, typst)
         #[arg(long, visible_alias = "format", value_name = "FORMAT")]
         to: Option<String>,
 
@@ -415,6 +539,48 @@ pub enum Commands {
         print_path: bool,
     },
 
+    /// map between Markdown source lines and PDF positions (SyncTeX)
+    #[command(
+        after_help = "Examples:\n  omnidoc locate main.md:120\n  omnidoc locate --pdf-page 3 --x 120 --y 340\n  omnidoc locate chapters/intro.md:12 --json\n\nPDF coordinates are PostScript points from the top-left corner of the page."
+    )]
+    Locate {
+        /// Markdown position to find in the PDF, as FILE:LINE
+        #[arg(
+            value_name = "FILE:LINE",
+            required_unless_present = "pdf_page",
+            conflicts_with = "pdf_page"
+        )]
+        source: Option<String>,
+
+        /// PDF page (1-based) to map back to Markdown
+        #[arg(long, value_name = "N", requires_all = ["x", "y"])]
+        pdf_page: Option<usize>,
+
+        /// horizontal PDF position in points
+        #[arg(long, requires = "pdf_page", allow_negative_numbers = true)]
+        x: Option<f64>,
+
+        /// vertical PDF position in points, measured from the top of the page
+        #[arg(long, requires = "pdf_page", allow_negative_numbers = true)]
+        y: Option<f64>,
+
+        /// set the path to a documentation project (default: the project containing FILE)
+        #[arg(long, value_hint = ValueHint::DirPath)]
+        path: Option<String>,
+
+        /// select one `[[documents]]` entry
+        #[arg(long = "doc", value_name = "NAME")]
+        document: Option<String>,
+
+        /// select the `[variants.NAME]` audience variant
+        #[arg(long, value_name = "NAME")]
+        variant: Option<String>,
+
+        /// emit the location as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
     /// preview or remove generated build artifacts
     #[command(
         after_help = "Examples:\n  omnidoc clean --dry-run\n  omnidoc clean --dry-run --json\n  omnidoc clean\n  omnidoc clean --distclean"
@@ -437,6 +603,15 @@ pub enum Commands {
         path: Option<String>,
     },
 
+    /// inspect, prune, and verify local and shared build caches
+    #[command(
+        after_help = "Examples:\n  omnidoc cache stats\n  omnidoc cache prune --older-than 14 --dry-run\n  omnidoc cache prune --all\n  omnidoc cache verify --json\n\nSet `[cache] shared` to a directory or an http(s) URL to share builds between checkouts and CI."
+    )]
+    Cache {
+        #[command(subcommand)]
+        subcommand: CacheSubcommand,
+    },
+
     /// preview or refresh project scaffolding for the current OmniDoc version
     #[command(
         after_help = "Examples:\n  omnidoc update --dry-run\n  omnidoc update --diff\n  omnidoc update --dry-run --json\n  omnidoc update --no-commit\n  omnidoc update"
@@ -795,6 +970,55 @@ pub enum ConfigSubcommand {
     },
 }
 
+#[derive(Debug, Subcommand)]
+pub enum CacheSubcommand {
+    /// show the size of the local fragment, diagram, and shared caches
+    Stats {
+        /// emit stable JSON cache statistics
+        #[arg(long)]
+        json: bool,
+
+        /// set the path to a documentation project
+        #[arg(value_hint = ValueHint::DirPath)]
+        path: Option<String>,
+    },
+
+    /// remove cache entries that have not been used recently
+    Prune {
+        /// remove entries unused for this many days
+        #[arg(long, value_name = "DAYS", default_value_t = 30)]
+        older_than: u64,
+
+        /// empty the local fragment and diagram caches regardless of age;
+        /// shared entries still follow --older-than
+        #[arg(long)]
+        all: bool,
+
+        /// report what would be removed without deleting anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// emit a stable JSON prune report
+        #[arg(long)]
+        json: bool,
+
+        /// set the path to a documentation project
+        #[arg(value_hint = ValueHint::DirPath)]
+        path: Option<String>,
+    },
+
+    /// check shared cache objects against their digests
+    Verify {
+        /// emit stable JSON verification details
+        #[arg(long)]
+        json: bool,
+
+        /// set the path to a documentation project
+        #[arg(value_hint = ValueHint::DirPath)]
+        path: Option<String>,
+    },
+}
+
 #[derive(Debug, Subcommand)]
 pub enum LibSubcommand {
     /// install the release-bound OmniDoc library
@@ -1051,12 +1275,42 @@ pub enum CheckSubcommand {
         json: bool,
     },
 
+    /// check external HTTP(S) links in sources and bibliographies
+    #[command(
+        after_help = "Examples:\n  omnidoc check links\n  omnidoc check links --json docs\n  omnidoc check links --no-cache\n\nConfigure `[links] ignore`, `allow`, `timeout`, `concurrency`, `rate_limit`, and `cache_ttl` in .omnidoc.toml."
+    )]
+    Links {
+        /// set the path to a documentation project
+        #[arg(value_hint = ValueHint::DirPath)]
+        path: Option<String>,
+
+        /// emit JSON link results
+        #[arg(long)]
+        json: bool,
+
+        /// re-check links that have a cached result
+        #[arg(long)]
+        no_cache: bool,
+    },
+
     /// create, update, or verify omnidoc.lock
     Lock {
         /// set the path to a documentation project
         #[arg(value_hint = ValueHint::DirPath)]
         path: Option<String>,
 
+        /// select one `[[documents]]` entry (default: every declared document)
+        #[arg(long = "doc", value_name = "NAME")]
+        document: Option<String>,
+
+        /// apply the `[profiles.NAME]` overlay from .omnidoc.toml
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
+
+        /// select the `[variants.NAME]` audience variant
+        #[arg(long, value_name = "NAME")]
+        variant: Option<String>,
+
         /// check whether omnidoc.lock matches current project inputs
         #[arg(long, conflicts_with = "update")]
         check: bool,
@@ -1072,6 +1326,18 @@ pub enum CheckSubcommand {
         #[arg(value_hint = ValueHint::DirPath)]
         path: Option<String>,
 
+        /// select one `[[documents]]` entry (default: every declared document)
+        #[arg(long = "doc", value_name = "NAME")]
+        document: Option<String>,
+
+        /// apply the `[profiles.NAME]` overlay from .omnidoc.toml
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
+
+        /// select the `[variants.NAME]` audience variant
+        #[arg(long, value_name = "NAME")]
+        variant: Option<String>,
+
         /// output format to build (repeatable)
         #[arg(long = "output")]
         outputs: Vec<String>,
@@ -1114,6 +1380,10 @@ pub enum ConvertSubcommand {
         #[arg(short, long)]
         output: Option<String>,
 
+        /// apply the `[profiles.NAME]` overlay from .omnidoc.toml
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
+
         /// input Markdown files
         #[arg(required = true, value_hint = ValueHint::FilePath)]
         inputs: Vec<String>,
@@ -1125,6 +1395,10 @@ pub enum ConvertSubcommand {
         #[arg(short, long)]
         output: Option<String>,
 
+        /// apply the `[profiles.NAME]` overlay from .omnidoc.toml
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
+
         /// CSS file path
         #[arg(short, long)]
         css: Option<String>,
@@ -1468,8 +1742,8 @@ pub enum FigureSubcommand {
 #[cfg(test)]
 mod tests {
     use super::{
-        CheckSubcommand, Commands, ConfigScope, ConfigSubcommand, ConfigWriteScope,
-        ConvertSubcommand, LibSubcommand, OmniCli, PluginSubcommand,
+        CacheSubcommand, CheckSubcommand, Commands, ConfigScope, ConfigSubcommand,
+        ConfigWriteScope, ConvertSubcommand, LibSubcommand, OmniCli, PluginSubcommand,
     };
     use crate::doctype::DocumentFormat;
     use clap::Parser;
@@ -1602,14 +1876,16 @@ mod tests {
 
     #[test]
     fn project_status_open_and_clean_options_parse() {
-        let status = OmniCli::try_parse_from(["omnidoc", "status", "docs", "--json"])
-            .expect("status command");
+        let status =
+            OmniCli::try_parse_from(["omnidoc", "status", "docs", "--doc", "manual", "--json"])
+                .expect("status command");
         assert!(matches!(
             status.command,
             Commands::Status {
                 path: Some(path),
+                document: Some(document),
                 json: true
-            } if path == "docs"
+            } if path == "docs" && document == "manual"
         ));
 
         let open =
@@ -1619,11 +1895,48 @@ mod tests {
             open.command,
             Commands::Open {
                 path: Some(path),
+                document: None,
+                variant: None,
                 to: Some(output),
                 print_path: true
             } if path == "docs" && output == "html"
         ));
 
+        let locate = OmniCli::try_parse_from(["omnidoc", "locate", "main.md:120", "--json"])
+            .expect("forward locate command");
+        assert!(matches!(
+            locate.command,
+            Commands::Locate {
+                source: Some(source),
+                pdf_page: None,
+                json: true,
+                ..
+            } if source == "main.md:120"
+        ));
+        let locate = OmniCli::try_parse_from([
+            "omnidoc",
+            "locate",
+            "--pdf-page",
+            "3",
+            "--x",
+            "120.5",
+            "--y",
+            "340",
+        ])
+        .expect("inverse locate command");
+        assert!(matches!(
+            locate.command,
+            Commands::Locate {
+                source: None,
+                pdf_page: Some(3),
+                x: Some(x),
+                y: Some(y),
+                ..
+            } if x == 120.5 && y == 340.0
+        ));
+        assert!(OmniCli::try_parse_from(["omnidoc", "locate", "--pdf-page", "3"]).is_err());
+        assert!(OmniCli::try_parse_from(["omnidoc", "locate"]).is_err());
+
         let clean = OmniCli::try_parse_from([
             "omnidoc",
             "clean",
@@ -1643,6 +1956,66 @@ mod tests {
             } if path == "docs"
         ));
 
+        let prune = OmniCli::try_parse_from([
+            "omnidoc",
+            "cache",
+            "prune",
+            "docs",
+            "--older-than",
+            "7",
+            "--all",
+            "--dry-run",
+        ])
+        .expect("cache prune command");
+        assert!(matches!(
+            prune.command,
+            Commands::Cache {
+                subcommand: CacheSubcommand::Prune {
+                    path: Some(path),
+                    older_than: 7,
+                    all: true,
+                    dry_run: true,
+                    json: false,
+                }
+            } if path == "docs"
+        ));
+
+        let daemon =
+            OmniCli::try_parse_from(["omnidoc", "daemon", "docs", "--socket", "/tmp/docs.sock"])
+                .expect("daemon command");
+        assert!(matches!(
+            daemon.command,
+            Commands::Daemon {
+                path: Some(path),
+                socket: Some(socket),
+                verbose: false,
+            } if path == "docs" && socket == "/tmp/docs.sock"
+        ));
+
+        let lsp = OmniCli::try_parse_from(["omnidoc", "lsp", "--stdio", "--path", "docs"])
+            .expect("lsp command");
+        assert!(matches!(
+            lsp.command,
+            Commands::Lsp {
+                path: Some(path),
+                stdio: true,
+            } if path == "docs"
+        ));
+
+        let watch = OmniCli::try_parse_from(["omnidoc", "watch", "--serve", "--port", "8123"])
+            .expect("watch --serve command");
+        assert!(matches!(
+            watch.command,
+            Commands::Watch {
+                serve: true,
+                port: Some(8123),
+                once: false,
+                ..
+            }
+        ));
+        assert!(OmniCli::try_parse_from(["omnidoc", "watch", "--serve", "--once"]).is_err());
+        assert!(OmniCli::try_parse_from(["omnidoc", "watch", "--port", "8123"]).is_err());
+
         let update = OmniCli::try_parse_from([
             "omnidoc",
             "update",
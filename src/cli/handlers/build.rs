@@ -1,15 +1,29 @@
+use crate::build::media::media_dir;
+use crate::build::pandoc_policy::PandocOutputKind;
+use crate::build::search::page_index_name;
+use crate::cache;
 use crate::cli::handlers::common::{
-    check_omnidoc_project, create_build_service, create_config_manager,
+    check_omnidoc_project, create_build_service, create_config_manager, selected_documents,
 };
 use crate::config::CliOverrides;
 use crate::config::MergedConfig;
-use crate::doc::artifacts::expected_output_file;
+use crate::doc::artifacts::{entry_path, expected_output_file};
 use crate::epub::{validate_epub, EpubCompatibilityReport};
 use crate::error::{OmniDocError, Result};
 use crate::extensions::acquire_extension_store_read_locks;
+use crate::git::git_head_revision;
+use crate::pdf::{front_matter_metadata, inspect_pdf, postprocess_pdf, PdfMetadata, PdfReport};
 use crate::project_tools;
+use crate::terminal;
 use crate::utils::path;
+use crate::xml::{validate_publisher_xml, XmlValidationReport};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::num::NonZeroUsize;
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
 use std::time::Instant;
 
 #[derive(Debug, Clone, Default)]
@@ -18,18 +32,60 @@ pub struct BuildRunOptions {
     pub report: bool,
     pub write_lock: bool,
     pub strict: bool,
+    /// Observer told when each output starts and finishes
+    pub progress: Option<BuildProgress>,
+}
+
+/// Progress of one output (`OUTPUT` or `DOCUMENT/OUTPUT`) within a build.
+pub enum BuildProgressEvent<'a> {
+    Started {
+        output: &'a str,
+    },
+    Finished {
+        output: &'a str,
+        report: &'a project_tools::BuildReport,
+    },
+    Failed {
+        output: &'a str,
+        error: &'a OmniDocError,
+    },
+}
+
+/// Build progress callback, shared by every output job of one build. Used by
+/// `omnidoc daemon` to stream notifications while a request runs.
+#[derive(Clone)]
+pub struct BuildProgress(Arc<dyn Fn(BuildProgressEvent<'_>) + Send + Sync>);
+
+impl BuildProgress {
+    pub fn new(callback: impl Fn(BuildProgressEvent<'_>) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(callback))
+    }
+
+    fn emit(&self, event: BuildProgressEvent<'_>) {
+        (self.0)(event);
+    }
+}
+
+impl fmt::Debug for BuildProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("BuildProgress")
+    }
 }
 
 /// Handle the 'build' command
 #[allow(clippy::too_many_arguments)]
 pub fn handle_build(
     path: Option<String>,
+    document: Option<String>,
+    profile: Option<String>,
+    variant: Option<String>,
     to: Option<String>,
     all: bool,
     outputs: Vec<String>,
     pdf_engine: Option<String>,
     latex_backend: Option<String>,
     max_latex_passes: Option<usize>,
+    jobs: Option<usize>,
     force: bool,
     report: bool,
     write_lock: bool,
@@ -43,8 +99,12 @@ pub fn handle_build(
         pdf_engine,
         latex_backend,
         max_latex_passes,
+        jobs,
         verbose,
-    );
+    )
+    .with_document(document)
+    .with_profile(profile)
+    .with_variant(variant);
 
     build_project_outputs(
         &project_path,
@@ -55,6 +115,7 @@ pub fn handle_build(
             report,
             write_lock,
             strict,
+            ..BuildRunOptions::default()
         },
         verbose,
     )
@@ -83,66 +144,288 @@ pub fn build_project_outputs(
 ) -> Result<()> {
     let _project_lock = project_tools::acquire_project_write_lock(project_path, "build project")?;
     build_project_outputs_unlocked(project_path, cli_overrides, all, run_options, verbose)
+        .map(|_| ())
 }
 
+/// Build every selected output and return their reports in output order.
 pub(crate) fn build_project_outputs_unlocked(
     project_path: &Path,
     cli_overrides: CliOverrides,
     all: bool,
     run_options: BuildRunOptions,
     verbose: bool,
-) -> Result<()> {
+) -> Result<Vec<project_tools::BuildReport>> {
     let config_manager = create_config_manager(Some(project_path), cli_overrides.clone())?;
     let merged = config_manager.get_merged().clone();
-    let _extension_locks = (merged.theme_name.is_some() || !merged.plugins_enabled.is_empty())
-        .then(|| acquire_extension_store_read_locks(Some(project_path), &merged, "build project"))
-        .transpose()?;
-    let outputs = resolve_outputs(&merged, &cli_overrides, all);
-    let mut reports = Vec::new();
+    // Environment variables are process-wide; set them once before any output
+    // job starts instead of racing `set_var` from concurrent builds.
+    config_manager.setup_env()?;
     let mut per_output_options = run_options.clone();
     per_output_options.write_lock = false;
 
-    if outputs.len() == 1 {
-        let report = build_project_once(
+    let mut extension_locks = Vec::new();
+    let mut jobs = Vec::new();
+    let mut artifacts: Vec<(std::path::PathBuf, String)> = Vec::new();
+    let mut lint = DocumentIssues::new();
+    let mut project_issues = None;
+    for document in selected_documents(&merged, cli_overrides.document.clone()) {
+        let document_overrides = cli_overrides.clone().with_document(document.clone());
+        let document_config =
+            create_config_manager(Some(project_path), document_overrides.clone())?
+                .get_merged()
+                .clone();
+        if document_config.theme_name.is_some() || !document_config.plugins_enabled.is_empty() {
+            extension_locks.push(acquire_extension_store_read_locks(
+                Some(project_path),
+                &document_config,
+                "build project",
+            )?);
+        }
+        // Source lint is shared by every output of a document, so it runs
+        // here once instead of inside each output job.
+        let project_issues = project_issues
+            .get_or_insert_with(|| project_tools::lint_project(project_path, &merged));
+        lint.insert(
+            document.clone(),
+            document_lint_issues(
+                project_path,
+                &document_config,
+                project_issues,
+                run_options.strict,
+            ),
+        );
+        let outputs = resolve_outputs(&document_config, &document_overrides, all);
+        if outputs.is_empty() {
+            jobs.push((
+                project_tools::document_output_key(document.as_deref(), "pdf"),
+                document_overrides,
+            ));
+            continue;
+        }
+        for output in outputs {
+            let label = project_tools::document_output_key(document.as_deref(), &output);
+            if document.is_some() {
+                // Two documents writing the same file would silently keep
+                // whichever finished last.
+                let target = document_config.target.clone().unwrap_or_default();
+                let artifact =
+                    expected_output_file(project_path, &document_config, &output, &target);
+                if let Some((_, other)) = artifacts.iter().find(|(path, _)| path == &artifact) {
+                    return Err(OmniDocError::Config(format!(
+                        "Documents '{}' and '{}' both write {}; set a distinct target",
+                        other,
+                        label,
+                        artifact.display()
+                    )));
+                }
+                artifacts.push((artifact, label.clone()));
+            }
+            jobs.push((label, document_overrides.clone().with_to(Some(output))));
+        }
+    }
+
+    let reports = if jobs.len() == 1 {
+        let (label, overrides) = jobs.remove(0);
+        vec![build_labeled_output(
             project_path,
-            cli_overrides.clone().with_to(outputs.into_iter().next()),
-            per_output_options.clone(),
+            &label,
+            overrides,
+            &lint,
+            &per_output_options,
             verbose,
-        )?;
-        reports.push(report);
-    } else if outputs.is_empty() {
-        let report = build_project_once(
+        )?]
+    } else {
+        build_output_jobs(
             project_path,
-            cli_overrides.clone(),
-            per_output_options.clone(),
+            &jobs,
+            effective_jobs(merged.jobs, jobs.len()),
+            &lint,
+            &per_output_options,
             verbose,
-        )?;
-        reports.push(report);
-    } else {
-        for output in outputs {
-            let output_overrides = cli_overrides.clone().with_to(Some(output));
-            reports.push(build_project_once(
-                project_path,
-                output_overrides,
-                per_output_options.clone(),
-                verbose,
-            )?);
-        }
-    }
+        )?
+    };
 
     if run_options.report {
         project_tools::write_reports(project_path, &merged, &reports)?;
     }
     if run_options.write_lock {
-        write_project_lock(project_path)?;
+        write_project_lock(
+            project_path,
+            cli_overrides.profile.as_deref(),
+            cli_overrides.variant.as_deref(),
+        )?;
     }
 
-    Ok(())
+    Ok(reports)
+}
+
+/// Lint issues of each selected document, keyed by document name.
+type DocumentIssues = BTreeMap<Option<String>, Vec<project_tools::ProjectIssue>>;
+
+/// Lint issues reported for every output of one document. Cross-reference,
+/// citation, glossary and index lint rebuild the dependency graph and rescan the
+/// sources, so plain builds leave them to `check lint` and only run them
+/// under `--strict`.
+fn document_lint_issues(
+    project_path: &Path,
+    config: &MergedConfig,
+    project_issues: &[project_tools::ProjectIssue],
+    strict: bool,
+) -> Vec<project_tools::ProjectIssue> {
+    let mut issues = project_issues.to_vec();
+    if strict {
+        issues.extend(project_tools::lint_crossrefs(project_path, config));
+        issues.extend(project_tools::lint_citations(project_path, config));
+        issues.extend(project_tools::lint_glossary(project_path, config));
+        issues.extend(project_tools::lint_index(project_path, config));
+    }
+    if let Some(variant) = config.variant.as_deref() {
+        issues.extend(project_tools::lint_variants(
+            project_path,
+            config,
+            &BTreeMap::from([(variant.to_string(), config.variant_audiences.clone())]),
+        ));
+    }
+    issues
+}
+
+/// Resolve `build.jobs` / `--jobs` to a worker count; 0 means one per CPU.
+fn effective_jobs(configured: usize, outputs: usize) -> usize {
+    let jobs = if configured == 0 {
+        thread::available_parallelism().map_or(1, NonZeroUsize::get)
+    } else {
+        configured
+    };
+    jobs.clamp(1, outputs.max(1))
+}
+
+/// Build independent outputs with up to `jobs` workers. Each output keeps its
+/// own scratch directory and cache entry; terminal output is buffered per
+/// output and replayed as one block when that output finishes. Reports are
+/// returned in the requested output order so the caller writes them once.
+/// `outputs` pairs a label (`OUTPUT` or `DOCUMENT/OUTPUT`) with its overrides.
+fn build_output_jobs(
+    project_path: &Path,
+    outputs: &[(String, CliOverrides)],
+    jobs: usize,
+    lint: &DocumentIssues,
+    run_options: &BuildRunOptions,
+    verbose: bool,
+) -> Result<Vec<project_tools::BuildReport>> {
+    let build_output = |(label, overrides): &(String, CliOverrides)| {
+        build_labeled_output(
+            project_path,
+            label,
+            overrides.clone(),
+            lint,
+            run_options,
+            verbose,
+        )
+    };
+    if jobs <= 1 {
+        return outputs.iter().map(build_output).collect();
+    }
+
+    let next = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+    let results = Mutex::new(
+        std::iter::repeat_with(|| None)
+            .take(outputs.len())
+            .collect::<Vec<_>>(),
+    );
+    thread::scope(|scope| {
+        for _ in 0..jobs {
+            scope.spawn(|| loop {
+                // Like the sequential loop, stop starting new outputs after a
+                // failure; builds already running are allowed to finish.
+                if failed.load(Ordering::SeqCst) {
+                    break;
+                }
+                let index = next.fetch_add(1, Ordering::SeqCst);
+                let Some(output) = outputs.get(index) else {
+                    break;
+                };
+                let (result, captured) = terminal::capture_output(|| build_output(output));
+                captured.replay();
+                if result.is_err() {
+                    failed.store(true, Ordering::SeqCst);
+                }
+                results.lock().unwrap_or_else(PoisonError::into_inner)[index] = Some(result);
+            });
+        }
+    });
+
+    let mut reports = Vec::new();
+    let mut failures = Vec::new();
+    let results = results.into_inner().unwrap_or_else(PoisonError::into_inner);
+    for ((output, _), result) in outputs.iter().zip(results) {
+        match result {
+            Some(Ok(report)) => reports.push(report),
+            Some(Err(error)) => failures.push((output, error)),
+            None => {}
+        }
+    }
+    if failures.len() == 1 {
+        return Err(failures.remove(0).1);
+    }
+    if !failures.is_empty() {
+        let details = failures
+            .iter()
+            .map(|(output, error)| format!("- {}: {}", output, error))
+            .collect::<Vec<_>>()
+            .join("\n");
+        return Err(OmniDocError::Project(format!(
+            "{} outputs failed to build\n{}",
+            failures.len(),
+            details
+        )));
+    }
+    Ok(reports)
+}
+
+/// Build one output, reporting its progress to `run_options.progress`.
+fn build_labeled_output(
+    project_path: &Path,
+    label: &str,
+    cli_overrides: CliOverrides,
+    lint: &DocumentIssues,
+    run_options: &BuildRunOptions,
+    verbose: bool,
+) -> Result<project_tools::BuildReport> {
+    let progress = run_options.progress.as_ref();
+    if let Some(progress) = progress {
+        progress.emit(BuildProgressEvent::Started { output: label });
+    }
+    let lint = lint
+        .get(&cli_overrides.document)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let result = build_project_once(
+        project_path,
+        cli_overrides,
+        lint,
+        run_options.clone(),
+        verbose,
+    );
+    if let Some(progress) = progress {
+        progress.emit(match &result {
+            Ok(report) => BuildProgressEvent::Finished {
+                output: label,
+                report,
+            },
+            Err(error) => BuildProgressEvent::Failed {
+                output: label,
+                error,
+            },
+        });
+    }
+    result
 }
 
 fn build_project_once(
     project_path: &Path,
     cli_overrides: CliOverrides,
+    lint: &[project_tools::ProjectIssue],
     run_options: BuildRunOptions,
     verbose: bool,
 ) -> Result<project_tools::BuildReport> {
@@ -151,10 +434,9 @@ fn build_project_once(
 
     let config_manager = create_config_manager(Some(project_path), cli_overrides.clone())?;
     let config = config_manager.get_merged().clone();
-    config_manager.setup_env()?;
 
     let mut issues = project_tools::validate_config(project_path, &config);
-    issues.extend(project_tools::lint_project(project_path));
+    issues.extend_from_slice(lint);
     if run_options.strict && project_tools::has_warnings_or_errors(&issues) {
         project_tools::print_issues(&issues);
         return Err(OmniDocError::Project(
@@ -179,25 +461,18 @@ fn build_project_once(
     });
     let input_state = project_tools::build_input_state(project_path, &graph, &config, &output)?;
     let input_digest = input_state.input_digest.clone();
-    let cache_probe = project_tools::probe_cache(project_path, &output, &input_state);
+    let cache_key = project_tools::target_output_key(&config, &output);
+    let cache_probe = project_tools::probe_cache(project_path, &cache_key, &input_state);
 
     let output_file = expected_output_file(project_path, &config, &output, &target);
     let cache_candidate = !run_options.force && output_file.exists() && cache_probe.hit;
-    let cached_compatibility = if cache_candidate {
-        validate_output_compatibility(&output, &config, &output_file)
-            .ok()
-            .flatten()
-    } else {
-        None
-    };
-    let compatibility_required = configured_epub_profile(&output, &config).is_some();
-    let cached_compatibility_valid = !compatibility_required
-        || cached_compatibility
-            .as_ref()
-            .is_some_and(|report| report.valid);
-    if cache_candidate && cached_compatibility_valid {
+    let mut cached = cache_candidate.then(|| check_cached_artifact(&output, &config, &output_file));
+    if let Some(cached) = cached.take_if(|cached| cached.valid()) {
         if verbose {
-            println!("Skipping {} build; input cache is unchanged.", output);
+            terminal::out(format!(
+                "Skipping {} build; input cache is unchanged.\n",
+                output
+            ));
         }
         return Ok(project_tools::build_report(
             project_tools::BuildReportContext {
@@ -212,17 +487,82 @@ fn build_project_once(
                 graph: &graph,
                 config: &config,
                 artifact: &output_file,
-                compatibility: cached_compatibility,
+                compatibility: cached.compatibility,
+                xml_validation: cached.xml_validation,
+                pdf: cached.pdf,
                 issues,
             },
         ));
     }
 
-    let (cache_reason, mut cache_details) = if cache_candidate && !cached_compatibility_valid {
+    // A teammate or CI job may already have built this exact input state.
+    let shared_cache = cache::shared_cache_store(project_path, &config)?;
+    let shareable = shared_cache.is_some() && is_shareable_output(&output);
+    let mut shared_details = Vec::new();
+    if let Some(store) = shared_cache
+        .as_ref()
+        .filter(|_| shareable && !run_options.force)
+    {
+        match store.restore_build(project_path, &cache_key, &input_digest) {
+            Ok(Some(files)) => {
+                let restored = check_cached_artifact(&output, &config, &output_file);
+                if restored.valid() {
+                    project_tools::write_cache_state(project_path, &cache_key, &input_state)?;
+                    if verbose {
+                        terminal::out(format!(
+                            "Restored {} build from the shared cache at {}.\n",
+                            output,
+                            store.location()
+                        ));
+                    }
+                    return Ok(project_tools::build_report(
+                        project_tools::BuildReportContext {
+                            project_path,
+                            output,
+                            target,
+                            skipped: true,
+                            cache_reason: "shared_cache_hit".to_string(),
+                            cache_details: vec![format!("shared_artifacts_restored:{files}")],
+                            duration_ms: started_at.elapsed().as_millis() as u64,
+                            input_digest,
+                            graph: &graph,
+                            config: &config,
+                            artifact: &output_file,
+                            compatibility: restored.compatibility,
+                            xml_validation: restored.xml_validation,
+                            pdf: restored.pdf,
+                            issues,
+                        },
+                    ));
+                }
+                shared_details.push("shared_artifact_validation_failed".to_string());
+            }
+            Ok(None) => shared_details.push("shared_cache_miss".to_string()),
+            Err(error) => {
+                terminal::warning(format!("Shared cache unavailable: {error}"));
+                shared_details.push(format!("shared_cache_unavailable:{error}"));
+            }
+        }
+        match store.prefetch_renders(project_path, &cache_key) {
+            Ok(0) => {}
+            Ok(fetched) => shared_details.push(format!("shared_renders_fetched:{fetched}")),
+            Err(error) => terminal::warning(format!("Shared diagram cache unavailable: {error}")),
+        }
+    }
+
+    let (cache_reason, mut cache_details) = if cached
+        .as_ref()
+        .is_some_and(|cached| !cached.compatibility_valid)
+    {
         (
             "artifact_compatibility_failed",
             vec!["artifact_compatibility_failed".to_string()],
         )
+    } else if cached.is_some() {
+        (
+            "artifact_validation_failed",
+            vec!["artifact_validation_failed".to_string()],
+        )
     } else if run_options.force {
         ("forced_rebuild", vec!["forced_by_user".to_string()])
     } else if !output_file.exists() {
@@ -233,11 +573,14 @@ fn build_project_once(
     if !cache_probe.hit {
         cache_details.extend(cache_probe.details);
     }
+    cache_details.extend(shared_details);
 
     let build_service = create_build_service(Some(project_path), cli_overrides)?;
     build_service
         .build(project_path, verbose)
         .map_err(|e| OmniDocError::Project(format!("Failed to build project: {}", e)))?;
+    cache_details.extend(project_tools::fragment_cache_details(project_path, &config));
+    cache_details.extend(project_tools::render_cache_details(project_path, &config));
 
     // Filters may emit authoritative dependency files during the build. Re-read
     // the graph before writing the cache/report so the first successful build
@@ -262,7 +605,75 @@ fn build_project_once(
             )));
         }
     }
-    project_tools::write_cache_state(project_path, &output, &final_input_state)?;
+    let xml_validation = validate_output_xml(&output, &output_file)?;
+    if let Some(report) = xml_validation.as_ref().filter(|report| !report.valid) {
+        let failures = report
+            .checks
+            .iter()
+            .filter(|check| !check.passed)
+            .map(|check| format!("- {}: {}", check.name, check.detail))
+            .collect::<Vec<_>>()
+            .join("\n");
+        return Err(OmniDocError::Project(format!(
+            "XML validation failed for schema '{}'\n{}",
+            report.schema, failures
+        )));
+    }
+    let pdf = postprocess_output_pdf(project_path, &config, &output_file)?;
+    if let Some(report) = &pdf {
+        let failures = report.checks.iter().filter(|check| !check.passed);
+        if report.profile.is_some() && !report.valid {
+            let failures = failures
+                .map(|check| format!("- {}: {}", check.name, check.detail))
+                .collect::<Vec<_>>()
+                .join("\n");
+            return Err(OmniDocError::Project(format!(
+                "PDF validation failed for profile '{}'\n{}",
+                report.profile.as_deref().unwrap_or_default(),
+                failures
+            )));
+        }
+        for check in failures {
+            terminal::warning(format!(
+                "PDF check '{}' failed: {}",
+                check.name, check.detail
+            ));
+            issues.push(project_tools::ProjectIssue {
+                severity: project_tools::IssueSeverity::Warning,
+                message: format!("PDF check '{}' failed: {}", check.name, check.detail),
+                path: Some(
+                    output_file
+                        .strip_prefix(project_path)
+                        .unwrap_or(&output_file)
+                        .to_string_lossy()
+                        .to_string(),
+                ),
+                line: None,
+                column: None,
+                rule: None,
+            });
+        }
+    }
+    project_tools::write_cache_state(project_path, &cache_key, &final_input_state)?;
+    if let Some(store) = shared_cache
+        .as_ref()
+        .filter(|_| shareable && !config.shared_cache_read_only)
+    {
+        let stored = store.store_build(
+            project_path,
+            &cache_key,
+            &final_input_digest,
+            &shared_artifacts(&output, &target, &output_file),
+            &project_tools::rendered_diagrams(project_path, &config),
+        );
+        match stored {
+            Ok(uploaded) => cache_details.push(format!("shared_objects_uploaded:{uploaded}")),
+            Err(error) => {
+                terminal::warning(format!("Could not update the shared cache: {error}"));
+                cache_details.push(format!("shared_cache_store_failed:{error}"));
+            }
+        }
+    }
     Ok(project_tools::build_report(
         project_tools::BuildReportContext {
             project_path,
@@ -277,11 +688,84 @@ fn build_project_once(
             config: &config,
             artifact: &output_file,
             compatibility,
+            xml_validation,
+            pdf,
             issues,
         },
     ))
 }
 
+/// Validation results of an artifact about to be reused instead of rebuilt.
+struct CachedArtifactChecks {
+    compatibility: Option<EpubCompatibilityReport>,
+    compatibility_valid: bool,
+    xml_validation: Option<XmlValidationReport>,
+    pdf: Option<PdfReport>,
+    validation_valid: bool,
+}
+
+impl CachedArtifactChecks {
+    fn valid(&self) -> bool {
+        self.compatibility_valid && self.validation_valid
+    }
+}
+
+fn check_cached_artifact(
+    output: &str,
+    config: &MergedConfig,
+    output_file: &Path,
+) -> CachedArtifactChecks {
+    let compatibility = validate_output_compatibility(output, config, output_file)
+        .ok()
+        .flatten();
+    let compatibility_valid = configured_epub_profile(output, config).is_none()
+        || compatibility.as_ref().is_some_and(|report| report.valid);
+    let xml_validation = validate_output_xml(output, output_file).ok().flatten();
+    let xml_valid = publisher_xml_format(output).is_none()
+        || xml_validation.as_ref().is_some_and(|report| report.valid);
+    let pdf = inspect_output_pdf(config, output_file).ok().flatten();
+    let pdf_valid = config.pdf_profile.is_none()
+        || !is_pdf_artifact(output_file)
+        || pdf.as_ref().is_some_and(|report| report.valid);
+    CachedArtifactChecks {
+        compatibility,
+        compatibility_valid,
+        xml_validation,
+        pdf,
+        validation_valid: xml_valid && pdf_valid,
+    }
+}
+
+/// Chunked HTML writes a whole directory that may share the output
+/// directory with other formats, so it is never uploaded.
+fn is_shareable_output(output: &str) -> bool {
+    PandocOutputKind::from_requested(Some(output)).ok() != Some(PandocOutputKind::HtmlSite)
+}
+
+/// The artifact plus the sidecars that ship with it, as `publish` collects them.
+fn shared_artifacts(output: &str, target: &str, output_file: &Path) -> Vec<std::path::PathBuf> {
+    let mut artifacts = vec![output_file.to_path_buf()];
+    let kind = PandocOutputKind::from_requested(Some(output)).ok();
+    if kind.is_some_and(PandocOutputKind::extracts_media) {
+        let media_dir = media_dir(output_file);
+        artifacts.extend(
+            walkdir::WalkDir::new(media_dir)
+                .sort_by_file_name()
+                .into_iter()
+                .flatten()
+                .filter(|entry| entry.file_type().is_file())
+                .map(|entry| entry.into_path()),
+        );
+    }
+    if kind == Some(PandocOutputKind::Html) {
+        let search_index = output_file.with_file_name(page_index_name(target));
+        if search_index.is_file() {
+            artifacts.push(search_index);
+        }
+    }
+    artifacts
+}
+
 fn configured_epub_profile<'a>(output: &str, config: &'a MergedConfig) -> Option<&'a str> {
     matches!(
         output.to_ascii_lowercase().as_str(),
@@ -302,7 +786,84 @@ fn validate_output_compatibility(
     validate_epub(artifact, profile).map(Some)
 }
 
-fn write_project_lock(project_path: &Path) -> Result<()> {
+/// JATS and DocBook artifacts are always checked structurally.
+fn publisher_xml_format(output: &str) -> Option<&'static str> {
+    PandocOutputKind::from_requested(Some(output))
+        .ok()
+        .filter(|kind| kind.is_publisher_xml())
+        .map(PandocOutputKind::config_key)
+}
+
+fn validate_output_xml(output: &str, artifact: &Path) -> Result<Option<XmlValidationReport>> {
+    let Some(format) = publisher_xml_format(output) else {
+        return Ok(None);
+    };
+    validate_publisher_xml(artifact, format).map(Some)
+}
+
+fn is_pdf_artifact(artifact: &Path) -> bool {
+    artifact
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("pdf"))
+}
+
+/// Re-checks a cached PDF without rewriting it.
+fn inspect_output_pdf(config: &MergedConfig, artifact: &Path) -> Result<Option<PdfReport>> {
+    if !is_pdf_artifact(artifact) {
+        return Ok(None);
+    }
+    inspect_pdf(artifact, config.pdf_profile.as_deref()).map(Some)
+}
+
+/// Stamps front matter metadata and the git revision into a freshly built PDF
+/// and completes the configured conformance profile.
+fn postprocess_output_pdf(
+    project_path: &Path,
+    config: &MergedConfig,
+    artifact: &Path,
+) -> Result<Option<PdfReport>> {
+    if !is_pdf_artifact(artifact) {
+        return Ok(None);
+    }
+    let metadata = if config.pdf_metadata {
+        pdf_document_metadata(project_path, config)
+    } else {
+        PdfMetadata::default()
+    };
+    postprocess_pdf(
+        artifact,
+        &metadata,
+        config.pdf_metadata,
+        config.pdf_profile.as_deref(),
+    )
+    .map(Some)
+}
+
+/// Entry front matter wins over `build.metadata_file`, which wins over the
+/// configured author. LaTeX entries have no front matter, so the values the
+/// engine wrote through hyperref are kept.
+fn pdf_document_metadata(project_path: &Path, config: &MergedConfig) -> PdfMetadata {
+    let entry = std::fs::read_to_string(entry_path(project_path, config)).unwrap_or_default();
+    let mut metadata = front_matter_metadata(&entry);
+    if let Some(file) = &config.metadata_file {
+        let content = std::fs::read_to_string(project_path.join(file)).unwrap_or_default();
+        let defaults = front_matter_metadata(&format!("---\n{content}\n---\n"));
+        metadata.title = metadata.title.or(defaults.title);
+        metadata.author = metadata.author.or(defaults.author);
+        if metadata.keywords.is_empty() {
+            metadata.keywords = defaults.keywords;
+        }
+    }
+    metadata.author = metadata.author.or_else(|| config.author.clone());
+    metadata.revision = git_head_revision(project_path);
+    metadata
+}
+
+fn write_project_lock(
+    project_path: &Path,
+    profile: Option<&str>,
+    variant: Option<&str>,
+) -> Result<()> {
     // omnidoc.lock describes the project's configured output contract, not
     // merely the subset selected for one build invocation. Replacing a
     // multi-target lock after `build --output pdf --write-lock` used to drop
@@ -310,20 +871,13 @@ fn write_project_lock(project_path: &Path) -> Result<()> {
     // missing targets. Keep lock generation aligned with the standalone
     // `omnidoc lock` command by always writing every configured target after a
     // successful build.
-    let base_manager = create_config_manager(Some(project_path), CliOverrides::new())?;
-    let outputs = configured_lock_outputs(base_manager.get_merged());
-    let targets = outputs
+    let targets = configured_lock_targets(project_path, profile, variant)?
         .into_iter()
-        .map(|output| {
-            let config_manager = create_config_manager(
-                Some(project_path),
-                CliOverrides::new().with_to(Some(output.clone())),
-            )?;
-            let config = config_manager.get_merged().clone();
+        .map(|(output, config)| {
             let graph = project_tools::dependency_graph(project_path, &config);
-            Ok((output, config, graph))
+            (output, config, graph)
         })
-        .collect::<Result<Vec<_>>>()?;
+        .collect::<Vec<_>>();
     let inputs = targets
         .iter()
         .map(|(output, config, graph)| project_tools::LockTargetInput {
@@ -335,6 +889,34 @@ fn write_project_lock(project_path: &Path) -> Result<()> {
     project_tools::write_lock_targets(project_path, &inputs)
 }
 
+/// Every configured output of every declared document, each with its merged
+/// config under `profile` and `variant`. This is the target set `omnidoc.lock`
+/// always records.
+pub(crate) fn configured_lock_targets(
+    project_path: &Path,
+    profile: Option<&str>,
+    variant: Option<&str>,
+) -> Result<Vec<(String, MergedConfig)>> {
+    let base_overrides = CliOverrides::new()
+        .with_profile(profile.map(str::to_string))
+        .with_variant(variant.map(str::to_string));
+    let base_manager = create_config_manager(Some(project_path), base_overrides.clone())?;
+    let mut targets = Vec::new();
+    for document in selected_documents(base_manager.get_merged(), None) {
+        let document_overrides = base_overrides.clone().with_document(document);
+        let document_manager =
+            create_config_manager(Some(project_path), document_overrides.clone())?;
+        for output in configured_lock_outputs(document_manager.get_merged()) {
+            let config_manager = create_config_manager(
+                Some(project_path),
+                document_overrides.clone().with_to(Some(output.clone())),
+            )?;
+            targets.push((output, config_manager.get_merged().clone()));
+        }
+    }
+    Ok(targets)
+}
+
 fn configured_lock_outputs(config: &MergedConfig) -> Vec<String> {
     if config.outputs.is_empty() {
         vec![config.to.clone().unwrap_or_else(|| "pdf".to_string())]
@@ -349,6 +931,7 @@ pub fn build_cli_overrides(
     pdf_engine: Option<String>,
     latex_backend: Option<String>,
     max_latex_passes: Option<usize>,
+    jobs: Option<usize>,
     verbose: bool,
 ) -> CliOverrides {
     let mut cli_overrides = CliOverrides::new()
@@ -356,7 +939,8 @@ pub fn build_cli_overrides(
         .with_to(to)
         .with_outputs(outputs)
         .with_latex_backend(latex_backend)
-        .with_max_latex_passes(max_latex_passes);
+        .with_max_latex_passes(max_latex_passes)
+        .with_jobs(jobs);
     if let Some(engine) = pdf_engine {
         cli_overrides = cli_overrides.with_tool_path("latex_engine".to_string(), Some(engine));
     }
@@ -397,7 +981,7 @@ fn normalize_outputs(outputs: Vec<String>) -> Vec<String> {
 
 #[cfg(test)]
 mod tests {
-    use super::{configured_lock_outputs, resolve_outputs, write_project_lock};
+    use super::{configured_lock_outputs, effective_jobs, resolve_outputs, write_project_lock};
     use crate::config::{CliOverrides, MergedConfig};
     use crate::project_tools::LockFile;
     use std::fs;
@@ -459,6 +1043,15 @@ mod tests {
         assert_eq!(defaults, vec!["pdf", "html", "docx", "epub"]);
     }
 
+    #[test]
+    fn jobs_are_capped_by_output_count_and_zero_uses_available_cpus() {
+        assert_eq!(effective_jobs(1, 4), 1);
+        assert_eq!(effective_jobs(8, 3), 3);
+        assert_eq!(effective_jobs(2, 0), 1);
+        let automatic = effective_jobs(0, 64);
+        assert!((1..=64).contains(&automatic));
+    }
+
     #[test]
     fn write_lock_keeps_the_configured_output_contract() {
         let configured = MergedConfig {
@@ -496,7 +1089,7 @@ outputs = ["html", "pdf", "epub"]
         )
         .expect("config");
 
-        write_project_lock(project.path()).expect("write project lock");
+        write_project_lock(project.path(), None, None).expect("write project lock");
 
         let content = fs::read_to_string(project.path().join("omnidoc.lock")).expect("lock");
         let lock: LockFile = toml::from_str(&content).expect("parse lock");
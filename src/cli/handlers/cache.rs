@@ -0,0 +1,170 @@
+use crate::cache::SharedCacheStore;
+use crate::cache::{
+    cache_stats, prune_local, shared_cache_store, CachePruneReport, CacheStats, CacheUsage,
+    CacheVerifyReport,
+};
+use crate::cli::commands::CacheSubcommand;
+use crate::cli::handlers::clean::format_bytes;
+use crate::cli::handlers::common::{
+    check_omnidoc_project, create_config_manager, print_json_error,
+};
+use crate::config::CliOverrides;
+use crate::error::{OmniDocError, Result};
+use crate::utils::path;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Handle the 'cache' command
+pub fn handle_cache(subcommand: CacheSubcommand) -> Result<()> {
+    match subcommand {
+        CacheSubcommand::Stats { json, path } => {
+            let stats = with_json_errors(json, || {
+                let (project_path, shared) = open_project_cache(path)?;
+                cache_stats(&project_path, shared.as_ref())
+            })?;
+            if json {
+                print_json(&stats, "cache statistics")?;
+            } else {
+                print_stats(&stats);
+            }
+        }
+        CacheSubcommand::Prune {
+            older_than,
+            all,
+            dry_run,
+            json,
+            path,
+        } => {
+            let report = with_json_errors(json, || {
+                let (project_path, shared) = open_project_cache(path)?;
+                let _project_lock = (!dry_run)
+                    .then(|| {
+                        crate::project_tools::acquire_project_write_lock(
+                            &project_path,
+                            "prune caches",
+                        )
+                    })
+                    .transpose()?;
+                let age = Duration::from_secs(older_than.saturating_mul(SECONDS_PER_DAY));
+                let mut report = CachePruneReport {
+                    dry_run,
+                    ..Default::default()
+                };
+                prune_local(&project_path, (!all).then_some(age), dry_run, &mut report)?;
+                if let Some(shared) =
+                    shared.filter(|shared| matches!(shared, SharedCacheStore::Directory(_)))
+                {
+                    shared.prune(age, dry_run, &mut report)?;
+                }
+                Ok(report)
+            })?;
+            if json {
+                print_json(&report, "cache prune report")?;
+            } else {
+                print_prune_report(&report);
+            }
+        }
+        CacheSubcommand::Verify { json, path } => {
+            let report = with_json_errors(json, || {
+                let (_, shared) = open_project_cache(path)?;
+                shared
+                    .ok_or_else(|| {
+                        OmniDocError::Config(
+                            "No shared cache configured; set `[cache] shared` first".to_string(),
+                        )
+                    })?
+                    .verify()
+            })?;
+            if json {
+                print_json(&report, "cache verification")?;
+            } else {
+                print_verify_report(&report);
+            }
+            if !report.valid {
+                return Err(OmniDocError::Project(format!(
+                    "Shared cache verification found {} problem(s)",
+                    report.problems.len()
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn open_project_cache(path: Option<String>) -> Result<(PathBuf, Option<SharedCacheStore>)> {
+    let project_path = path::determine_project_root(path)?;
+    check_omnidoc_project(&project_path)?;
+    let config = create_config_manager(Some(&project_path), CliOverrides::new())?
+        .get_merged()
+        .clone();
+    let shared = shared_cache_store(&project_path, &config)?;
+    Ok((project_path, shared))
+}
+
+fn with_json_errors<T>(json: bool, run: impl FnOnce() -> Result<T>) -> Result<T> {
+    run().inspect_err(|error| {
+        if json {
+            print_json_error(error);
+        }
+    })
+}
+
+fn print_json(value: &impl Serialize, label: &str) -> Result<()> {
+    println!(
+        "{}",
+        serde_json::to_string_pretty(value).map_err(|error| {
+            OmniDocError::Other(format!("Failed to serialize {label}: {error}"))
+        })?
+    );
+    Ok(())
+}
+
+fn usage(usage: &CacheUsage) -> String {
+    format!("{} files, {}", usage.files, format_bytes(usage.bytes))
+}
+
+fn print_stats(stats: &CacheStats) {
+    println!("Project cache: {}", usage(&stats.local));
+    println!("  include fragments: {}", usage(&stats.fragments));
+    println!("  rendered diagrams: {}", usage(&stats.renders));
+    match &stats.shared {
+        Some(shared) => {
+            println!("Shared cache: {}", shared.location);
+            println!("  builds: {} ({} targets)", shared.builds, shared.targets);
+            println!("  objects: {}", usage(&shared.objects));
+        }
+        None => println!("Shared cache: not configured"),
+    }
+}
+
+fn print_prune_report(report: &CachePruneReport) {
+    let verb = if report.dry_run {
+        "Would remove"
+    } else {
+        "Removed"
+    };
+    println!("{verb} include fragments: {}", usage(&report.fragments));
+    println!("{verb} rendered diagrams: {}", usage(&report.renders));
+    println!(
+        "{verb} shared entries: {} builds, {} targets, {}",
+        report.builds,
+        report.targets,
+        usage(&report.objects)
+    );
+}
+
+fn print_verify_report(report: &CacheVerifyReport) {
+    println!(
+        "Shared cache {}: {} builds, {} objects",
+        report.location, report.builds, report.objects
+    );
+    if report.valid {
+        println!("✓ Every object matches its digest");
+    }
+    for problem in &report.problems {
+        println!("✗ {problem}");
+    }
+}
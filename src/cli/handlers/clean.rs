@@ -82,7 +82,7 @@ fn print_clean_report(project_path: &Path, report: &CleanReport) {
     }
 }
 
-fn format_bytes(bytes: u64) -> String {
+pub(super) fn format_bytes(bytes: u64) -> String {
     if bytes < 1024 {
         format!("{bytes} B")
     } else if bytes < 1024 * 1024 {
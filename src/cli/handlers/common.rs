@@ -28,6 +28,47 @@ pub fn get_merged_config(config_manager: &ConfigManager) -> MergedConfig {
     config_manager.get_merged().clone()
 }
 
+/// Config for commands that act on a single artifact: the `--doc` selection,
+/// or the only declared document when there is no choice to make.
+pub fn create_single_document_config_manager(
+    project_path: &Path,
+    document: Option<String>,
+    variant: Option<String>,
+) -> Result<ConfigManager> {
+    let config_manager = create_config_manager(
+        Some(project_path),
+        CliOverrides::new()
+            .with_document(document.clone())
+            .with_variant(variant.clone()),
+    )?;
+    if document.is_some() {
+        return Ok(config_manager);
+    }
+    match config_manager.get_merged().documents.as_slice() {
+        [] => Ok(config_manager),
+        [only] => create_config_manager(
+            Some(project_path),
+            CliOverrides::new()
+                .with_document(Some(only.clone()))
+                .with_variant(variant),
+        ),
+        documents => Err(OmniDocError::Config(format!(
+            "This project declares several documents; choose one with --doc ({})",
+            documents.join(", ")
+        ))),
+    }
+}
+
+/// Documents a command covers: the `--doc` selection, otherwise every declared
+/// `[[documents]]` entry, or a single `None` for a single-document project.
+pub fn selected_documents(config: &MergedConfig, document: Option<String>) -> Vec<Option<String>> {
+    if document.is_some() || config.documents.is_empty() {
+        vec![document]
+    } else {
+        config.documents.iter().cloned().map(Some).collect()
+    }
+}
+
 /// Helper to create BuildService
 pub fn create_build_service(
     project_path: Option<&Path>,
@@ -38,9 +79,9 @@ pub fn create_build_service(
     Ok(BuildService::new(merged_config))
 }
 
-/// Helper to create ConverterService
-pub fn create_converter_service() -> Result<ConverterService> {
-    let config_manager = create_config_manager_default(None)?;
+/// Helper to create ConverterService, optionally under a project build profile
+pub fn create_converter_service(profile: Option<String>) -> Result<ConverterService> {
+    let config_manager = create_config_manager(None, CliOverrides::new().with_profile(profile))?;
     // 确保环境变量（如 TEXMFHOME）在转换前已设置
     let _ = config_manager.setup_env();
     let merged_config = get_merged_config(&config_manager);
@@ -758,7 +758,7 @@ fn validate_write_key(
         if !crate::project_tools::supported_outputs().contains(format))
     {
         return Err(OmniDocError::Config(format!(
-            "Unsupported pandoc.format_options key '{}'; choose pdf, html, epub, docx, pptx, or latex",
+            "Unsupported pandoc.format_options key '{}'; choose pdf, html, html-site, epub, docx, pptx, beamer, revealjs, gfm, plain, man, jats, docbook, latex, or typst",
             segments[2]
         )));
     }
@@ -794,6 +794,14 @@ fn is_known_write_root(root: &str) -> bool {
             | "plugins"
             | "tools"
             | "tectonic"
+            | "pdf"
+            | "watermark"
+            | "cache"
+            | "terms"
+            | "glossary"
+            | "index"
+            | "links"
+            | "lint"
             | "paths"
             | "template_dir"
             | "download"
@@ -815,6 +823,14 @@ fn section_child_example(segments: &[&str]) -> Option<&'static str> {
         ["plugins"] => Some("plugins.enabled"),
         ["tools"] => Some("tools.pandoc"),
         ["tectonic"] => Some("tectonic.only_cached"),
+        ["pdf"] => Some("pdf.profile"),
+        ["watermark"] => Some("watermark.text"),
+        ["cache"] => Some("cache.shared"),
+        ["terms"] => Some("terms.dictionary"),
+        ["glossary"] => Some("glossary.files"),
+        ["index"] => Some("index.title"),
+        ["links"] => Some("links.ignore"),
+        ["lint"] | ["lint", "rules"] => Some("lint.rules.missing_image"),
         ["paths"] => Some("paths.build_dir"),
         _ => None,
     }
@@ -823,18 +839,21 @@ fn section_child_example(segments: &[&str]) -> Option<&'static str> {
 fn key_allowed_in_scope(segments: &[&str], scope: ConfigWriteScope) -> bool {
     let root = segments[0];
     match scope {
-        ConfigWriteScope::Global => matches!(
-            root,
-            "author"
-                | "lib"
-                | "env"
-                | "theme"
-                | "extensions"
-                | "tools"
-                | "tectonic"
-                | "paths"
-                | "template_dir"
-        ),
+        ConfigWriteScope::Global => {
+            matches!(
+                root,
+                "author"
+                    | "lib"
+                    | "env"
+                    | "theme"
+                    | "extensions"
+                    | "tools"
+                    | "tectonic"
+                    | "cache"
+                    | "paths"
+                    | "template_dir"
+            ) || (root == "terms" && segments.get(1) == Some(&"spellcheck"))
+        }
         ConfigWriteScope::Project => matches!(
             root,
             "author"
@@ -846,6 +865,14 @@ fn key_allowed_in_scope(segments: &[&str], scope: ConfigWriteScope) -> bool {
                 | "plugins"
                 | "tools"
                 | "tectonic"
+                | "pdf"
+                | "watermark"
+                | "cache"
+                | "terms"
+                | "glossary"
+                | "index"
+                | "links"
+                | "lint"
                 | "paths"
                 | "download"
         ),
@@ -891,6 +918,73 @@ fn validate_semantic_value(key: &str, value: &Value) -> Result<()> {
                 )));
             }
         }
+        "index.program" => {
+            let program = value.as_str().unwrap_or_default();
+            if crate::index::IndexProgram::parse(program).is_none() {
+                return Err(OmniDocError::Config(format!(
+                    "Unsupported index.program '{program}'; choose makeindex, xindy, or zhmakeindex"
+                )));
+            }
+        }
+        "links.concurrency" if value.as_i64().unwrap_or(0) < 1 => {
+            return Err(OmniDocError::Config(
+                "links.concurrency must be at least 1".to_string(),
+            ));
+        }
+        "links.rate_limit" if value.as_f64().unwrap_or(0.0) <= 0.0 => {
+            return Err(OmniDocError::Config(
+                "links.rate_limit must be greater than 0".to_string(),
+            ));
+        }
+        key if key.starts_with("lint.rules.") => {
+            let rule = &key["lint.rules.".len()..];
+            if crate::lint::rule(rule).is_none() {
+                return Err(OmniDocError::Config(format!(
+                    "Unknown lint rule '{rule}'; choose {}",
+                    crate::lint::rule_ids()
+                )));
+            }
+            let level = value.as_str().unwrap_or_default();
+            if crate::lint::RuleLevel::parse(level).is_none() {
+                return Err(OmniDocError::Config(format!(
+                    "Unsupported {key} '{level}'; choose error, warning, info, or off"
+                )));
+            }
+        }
+        "pdf.profile" => {
+            let profile = value.as_str().unwrap_or_default();
+            if crate::pdf::normalize_pdf_profile(profile).is_none() {
+                return Err(OmniDocError::Config(format!(
+                    "Unsupported pdf.profile '{profile}'; choose pdf-a-2b"
+                )));
+            }
+        }
+        "watermark.pages" => {
+            let pages = value.as_str().unwrap_or_default();
+            if !matches!(pages.to_ascii_lowercase().as_str(), "all" | "first") {
+                return Err(OmniDocError::Config(format!(
+                    "Unsupported watermark.pages '{pages}'; choose all or first"
+                )));
+            }
+        }
+        "watermark.opacity" => {
+            let opacity = value.as_f64().unwrap_or(-1.0);
+            if opacity <= 0.0 || opacity > 1.0 {
+                return Err(OmniDocError::Config(
+                    "watermark.opacity must be greater than 0 and at most 1".to_string(),
+                ));
+            }
+        }
+        "cache.shared" => {
+            let shared = value.as_str().unwrap_or_default();
+            if let Some((scheme, _)) = shared.split_once("://") {
+                if !matches!(scheme.to_ascii_lowercase().as_str(), "http" | "https") {
+                    return Err(OmniDocError::Config(format!(
+                        "Unsupported cache.shared scheme '{scheme}'; use a directory or an http(s) URL"
+                    )));
+                }
+            }
+        }
         "build.max_latex_passes" if value.as_u64() == Some(0) => {
             return Err(OmniDocError::Config(
                 "build.max_latex_passes must be greater than 0".to_string(),
@@ -909,7 +1003,7 @@ fn validate_output_name(key: &str, value: &Value) -> Result<()> {
         Ok(())
     } else {
         Err(OmniDocError::Config(format!(
-            "Unsupported {key} value '{output}'; choose pdf, html, epub, docx, pptx, or latex"
+            "Unsupported {key} value '{output}'; choose pdf, html, html-site, epub, docx, pptx, beamer, revealjs, gfm, plain, man, jats, docbook, latex, or typst"
         )))
     }
 }
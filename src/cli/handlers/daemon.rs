@@ -0,0 +1,498 @@
+//! `omnidoc daemon`: a long-running build server for editors and review tools.
+//!
+//! Clients connect to a Unix domain socket and exchange JSON-RPC 2.0 messages,
+//! one JSON document per line. The daemon enables the [`crate::warm`] memo
+//! tables, so merged configuration, the resolved theme and plugins and tool
+//! lookups are reused across requests until their input files change.
+//!
+//! Methods: `build`, `status`, `lint`, `deps`, `stats`, `reload`, `shutdown`.
+//! While a `build` request runs the daemon sends `build/progress`
+//! notifications on the same connection.
+
+use crate::build::executor::BuildExecutor;
+use crate::cli::handlers::build::{
+    build_cli_overrides, build_project_outputs_unlocked, BuildProgress, BuildProgressEvent,
+    BuildRunOptions,
+};
+use crate::cli::handlers::common::{check_omnidoc_project, create_config_manager};
+use crate::cli::handlers::quality::{configured_extension_locks, lint_issues};
+use crate::cli::handlers::status::resolve_status;
+use crate::config::CliOverrides;
+use crate::error::{OmniDocError, Result};
+use crate::extensions::{enabled_plugins, resolve_selected_theme};
+use crate::project_tools;
+use crate::terminal;
+use crate::utils::path;
+use crate::warm;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::Instant;
+
+const SOCKET_FILE: &str = "daemon.sock";
+
+// JSON-RPC 2.0 error codes.
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const SERVER_ERROR: i64 = -32000;
+
+#[derive(Debug, Deserialize)]
+struct RpcRequest {
+    jsonrpc: String,
+    #[serde(default)]
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Debug)]
+struct RpcError {
+    code: i64,
+    message: String,
+    data: Option<Value>,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+
+    fn with_data(mut self, data: Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+}
+
+impl From<OmniDocError> for RpcError {
+    fn from(error: OmniDocError) -> Self {
+        Self::new(SERVER_ERROR, error.message()).with_data(json!({ "category": error.category() }))
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct BuildParams {
+    document: Option<String>,
+    profile: Option<String>,
+    variant: Option<String>,
+    outputs: Vec<String>,
+    all: bool,
+    force: bool,
+    strict: bool,
+    report: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct StatusParams {
+    document: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct LintParams {
+    strict: bool,
+}
+
+/// State shared by every connection of one daemon.
+struct Daemon {
+    project_path: PathBuf,
+    socket: PathBuf,
+    started_at: Instant,
+    requests: AtomicU64,
+    verbose: bool,
+    /// Builds share the project write lock; queue them instead of failing.
+    build_lock: Mutex<()>,
+    shutdown: AtomicBool,
+}
+
+/// Handle the 'daemon' command
+pub fn handle_daemon(path: Option<String>, socket: Option<String>, verbose: bool) -> Result<()> {
+    let project_path = path::determine_project_root(path)?;
+    check_omnidoc_project(&project_path)?;
+    let socket = socket
+        .map(PathBuf::from)
+        .unwrap_or_else(|| default_socket_path(&project_path));
+    warm::enable();
+    let daemon = Daemon::new(project_path, socket, verbose);
+    daemon.warm_up();
+    serve(daemon)
+}
+
+/// `.omnidoc-cache/daemon.sock` inside the project.
+pub fn default_socket_path(project_path: &Path) -> PathBuf {
+    project_tools::project_cache_dir(project_path).join(SOCKET_FILE)
+}
+
+#[cfg(unix)]
+fn serve(daemon: Daemon) -> Result<()> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::sync::Arc;
+    use std::thread;
+
+    fn bind(socket: &Path) -> Result<UnixListener> {
+        if socket.exists() {
+            if UnixStream::connect(socket).is_ok() {
+                return Err(OmniDocError::Project(format!(
+                    "another omnidoc daemon is already listening on {}",
+                    socket.display()
+                )));
+            }
+            // Left behind by a daemon that did not shut down cleanly.
+            std::fs::remove_file(socket)?;
+        }
+        if let Some(parent) = socket.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        UnixListener::bind(socket).map_err(|error| {
+            OmniDocError::Project(format!(
+                "cannot listen on {}: {error}; pick a shorter path with --socket",
+                socket.display()
+            ))
+        })
+    }
+
+    let listener = bind(&daemon.socket)?;
+    let daemon = Arc::new(daemon);
+    terminal::out(format!(
+        "omnidoc daemon for {} listening on {}\n",
+        daemon.project_path.display(),
+        daemon.socket.display()
+    ));
+    for stream in listener.incoming() {
+        if daemon.shutdown.load(Ordering::SeqCst) {
+            break;
+        }
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(error) => {
+                terminal::warning(format!("Failed to accept daemon connection: {error}"));
+                continue;
+            }
+        };
+        let daemon = Arc::clone(&daemon);
+        thread::spawn(move || {
+            let Ok(writer) = stream.try_clone() else {
+                return;
+            };
+            let writer = Mutex::new(writer);
+            let send = |message: Value| {
+                let mut writer = writer.lock().unwrap_or_else(PoisonError::into_inner);
+                let _ = writeln!(writer, "{message}").and_then(|()| writer.flush());
+            };
+            for line in BufReader::new(stream).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if let Some(response) = daemon.handle_line(&line, &send) {
+                    send(response);
+                }
+                if daemon.shutdown.load(Ordering::SeqCst) {
+                    // Wake the accept loop so it notices the shutdown.
+                    let _ = UnixStream::connect(&daemon.socket);
+                    break;
+                }
+            }
+        });
+    }
+    let _ = std::fs::remove_file(&daemon.socket);
+    terminal::out("omnidoc daemon stopped\n");
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn serve(_daemon: Daemon) -> Result<()> {
+    Err(OmniDocError::Other(
+        "omnidoc daemon requires Unix domain sockets, which this platform does not support"
+            .to_string(),
+    ))
+}
+
+impl Daemon {
+    fn new(project_path: PathBuf, socket: PathBuf, verbose: bool) -> Self {
+        Self {
+            project_path,
+            socket,
+            started_at: Instant::now(),
+            requests: AtomicU64::new(0),
+            verbose,
+            build_lock: Mutex::new(()),
+            shutdown: AtomicBool::new(false),
+        }
+    }
+
+    /// Resolve configuration, theme, plugins and Pandoc once up front so the
+    /// first request is as fast as later ones. Failures are reported by the
+    /// requests that need the result.
+    fn warm_up(&self) {
+        let Ok(config_manager) =
+            create_config_manager(Some(&self.project_path), CliOverrides::new())
+        else {
+            return;
+        };
+        let config = config_manager.get_merged();
+        let _ = resolve_selected_theme(Some(&self.project_path), config);
+        let _ = enabled_plugins(&self.project_path, config);
+        let _ = BuildExecutor::new(config.tool_paths.clone()).check_tool("pandoc");
+    }
+
+    /// Answer one line of input. Returns `None` for notifications, which get no
+    /// response; `notify` sends progress messages to the same client.
+    fn handle_line(&self, line: &str, notify: &(dyn Fn(Value) + Sync)) -> Option<Value> {
+        if line.trim().is_empty() {
+            return None;
+        }
+        let message: Value = match serde_json::from_str(line) {
+            Ok(message) => message,
+            Err(error) => {
+                return Some(error_response(
+                    Value::Null,
+                    RpcError::new(PARSE_ERROR, format!("invalid JSON: {error}")),
+                ))
+            }
+        };
+        let id = message.get("id").cloned().unwrap_or(Value::Null);
+        let request = match serde_json::from_value::<RpcRequest>(message) {
+            Ok(request) if request.jsonrpc == "2.0" => request,
+            Ok(_) => {
+                return Some(error_response(
+                    id,
+                    RpcError::new(INVALID_REQUEST, "jsonrpc must be \"2.0\""),
+                ))
+            }
+            Err(error) => {
+                return Some(error_response(
+                    id,
+                    RpcError::new(INVALID_REQUEST, format!("invalid request: {error}")),
+                ))
+            }
+        };
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        let result = self.dispatch(&request, notify);
+        let id = request.id?;
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(error) => error_response(id, error),
+        })
+    }
+
+    fn dispatch(
+        &self,
+        request: &RpcRequest,
+        notify: &(dyn Fn(Value) + Sync),
+    ) -> std::result::Result<Value, RpcError> {
+        match request.method.as_str() {
+            "build" => self.build(params(&request.params)?, request.id.clone(), notify),
+            "status" => self.status(params(&request.params)?),
+            "lint" => self.lint(params(&request.params)?),
+            "deps" => self.deps(),
+            "stats" => Ok(json!({
+                "project": self.project_path,
+                "socket": self.socket,
+                "pid": std::process::id(),
+                "uptime_ms": self.started_at.elapsed().as_millis() as u64,
+                "requests": self.requests.load(Ordering::Relaxed),
+                "warm": warm::stats(),
+            })),
+            "reload" => {
+                warm::clear();
+                self.warm_up();
+                Ok(json!({ "reloaded": true }))
+            }
+            "shutdown" => {
+                self.shutdown.store(true, Ordering::SeqCst);
+                Ok(json!({ "shutdown": true }))
+            }
+            method => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("unknown method '{method}'"),
+            )),
+        }
+    }
+
+    fn build(
+        &self,
+        params: BuildParams,
+        request_id: Option<Value>,
+        notify: &(dyn Fn(Value) + Sync),
+    ) -> std::result::Result<Value, RpcError> {
+        let cli_overrides =
+            build_cli_overrides(None, params.outputs, None, None, None, None, self.verbose)
+                .with_document(params.document)
+                .with_profile(params.profile)
+                .with_variant(params.variant);
+
+        let _queued = self
+            .build_lock
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let _project_lock =
+            project_tools::acquire_project_write_lock(&self.project_path, "build project")?;
+        let reports = std::thread::scope(|scope| {
+            // Progress arrives from output worker threads; forward it to this
+            // connection from one place so messages never interleave.
+            let (sender, receiver) = std::sync::mpsc::channel::<Value>();
+            scope.spawn(move || {
+                for message in receiver {
+                    notify(message);
+                }
+            });
+            let request_id = request_id.unwrap_or(Value::Null);
+            let progress = BuildProgress::new(move |event| {
+                let _ = sender.send(progress_notification(&request_id, event));
+            });
+            build_project_outputs_unlocked(
+                &self.project_path,
+                cli_overrides,
+                params.all,
+                BuildRunOptions {
+                    force: params.force,
+                    report: params.report,
+                    strict: params.strict,
+                    progress: Some(progress),
+                    ..BuildRunOptions::default()
+                },
+                self.verbose,
+            )
+        })?;
+        Ok(json!({ "reports": reports }))
+    }
+
+    fn status(&self, params: StatusParams) -> std::result::Result<Value, RpcError> {
+        let status = resolve_status(
+            Some(self.project_path.to_string_lossy().to_string()),
+            params.document,
+        )?;
+        Ok(serde_json::to_value(status).unwrap_or(Value::Null))
+    }
+
+    fn lint(&self, params: LintParams) -> std::result::Result<Value, RpcError> {
+        let config_manager = create_config_manager(Some(&self.project_path), CliOverrides::new())?;
+        let issues = lint_issues(&self.project_path, config_manager.get_merged())?;
+        let failed = if params.strict {
+            project_tools::has_warnings_or_errors(&issues)
+        } else {
+            project_tools::has_errors(&issues)
+        };
+        Ok(json!({ "passed": !failed, "issues": issues }))
+    }
+
+    fn deps(&self) -> std::result::Result<Value, RpcError> {
+        let config_manager = create_config_manager(Some(&self.project_path), CliOverrides::new())?;
+        let config = config_manager.get_merged();
+        let _extension_locks =
+            configured_extension_locks(&self.project_path, config, "inspect dependencies")?;
+        let issues = project_tools::validate_config(&self.project_path, config);
+        if project_tools::has_errors(&issues) {
+            return Err(
+                RpcError::new(SERVER_ERROR, "configuration validation failed")
+                    .with_data(json!({ "category": "configuration", "issues": issues })),
+            );
+        }
+        Ok(json!(project_tools::dependency_graph(
+            &self.project_path,
+            config
+        )))
+    }
+}
+
+fn params<T: DeserializeOwned + Default>(params: &Value) -> std::result::Result<T, RpcError> {
+    if params.is_null() {
+        return Ok(T::default());
+    }
+    serde_json::from_value(params.clone())
+        .map_err(|error| RpcError::new(INVALID_PARAMS, format!("invalid params: {error}")))
+}
+
+fn error_response(id: Value, error: RpcError) -> Value {
+    let mut body = json!({ "code": error.code, "message": error.message });
+    if let Some(data) = error.data {
+        body["data"] = data;
+    }
+    json!({ "jsonrpc": "2.0", "id": id, "error": body })
+}
+
+fn progress_notification(request_id: &Value, event: BuildProgressEvent<'_>) -> Value {
+    let params = match event {
+        BuildProgressEvent::Started { output } => json!({
+            "request": request_id,
+            "output": output,
+            "state": "started",
+        }),
+        BuildProgressEvent::Finished { output, report } => json!({
+            "request": request_id,
+            "output": output,
+            "state": "finished",
+            "skipped": report.skipped,
+            "cache_reason": report.cache_reason,
+            "duration_ms": report.duration_ms,
+        }),
+        BuildProgressEvent::Failed { output, error } => json!({
+            "request": request_id,
+            "output": output,
+            "state": "failed",
+            "message": error.message(),
+        }),
+    };
+    json!({ "jsonrpc": "2.0", "method": "build/progress", "params": params })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn daemon() -> Daemon {
+        Daemon::new(
+            PathBuf::from("/nonexistent"),
+            PathBuf::from("/nonexistent/daemon.sock"),
+            false,
+        )
+    }
+
+    fn respond(daemon: &Daemon, line: &str) -> Option<Value> {
+        daemon.handle_line(line, &|_| {})
+    }
+
+    #[test]
+    fn malformed_messages_get_json_rpc_errors() {
+        let daemon = daemon();
+        let response = respond(&daemon, "{not json").unwrap();
+        assert_eq!(response["error"]["code"], PARSE_ERROR);
+        assert_eq!(response["id"], Value::Null);
+
+        let response = respond(&daemon, r#"{"jsonrpc":"1.0","id":1,"method":"stats"}"#).unwrap();
+        assert_eq!(response["error"]["code"], INVALID_REQUEST);
+
+        let response = respond(&daemon, r#"{"jsonrpc":"2.0","id":2,"method":"publish"}"#).unwrap();
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+
+        let response = respond(
+            &daemon,
+            r#"{"jsonrpc":"2.0","id":3,"method":"lint","params":{"stritc":true}}"#,
+        )
+        .unwrap();
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+    }
+
+    #[test]
+    fn notifications_run_without_a_response() {
+        let daemon = daemon();
+        assert!(respond(&daemon, r#"{"jsonrpc":"2.0","method":"shutdown"}"#).is_none());
+        assert!(daemon.shutdown.load(Ordering::SeqCst));
+
+        let response = respond(&daemon, r#"{"jsonrpc":"2.0","id":"s","method":"stats"}"#).unwrap();
+        assert_eq!(response["id"], "s");
+        assert_eq!(response["result"]["requests"], 2);
+    }
+}
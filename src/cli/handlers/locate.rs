@@ -0,0 +1,285 @@
+use crate::build::pandoc_policy::PandocOutputKind;
+use crate::build::source_map::load_project_source_spans;
+use crate::build::synctex::{SourceBridge, SyncTex};
+use crate::build::BuildExecutor;
+use crate::cli::handlers::common::{check_omnidoc_project, create_single_document_config_manager};
+use crate::doc::artifacts::{artifact_for_format, entry_path};
+use crate::error::{OmniDocError, Result};
+use crate::project_tools::{output_scratch_dir, SYNCTEX_DIR};
+use crate::utils::path;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// What `omnidoc locate` was asked to find.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LocateTarget {
+    /// Markdown → PDF (forward search).
+    Source { file: PathBuf, line: usize },
+    /// PDF → Markdown (inverse search), in points from the top-left corner.
+    Pdf { page: usize, x: f64, y: f64 },
+}
+
+impl LocateTarget {
+    /// Parse a `FILE:LINE` argument.
+    pub fn parse_source(source: &str) -> Result<Self> {
+        let parsed = source
+            .rsplit_once(':')
+            .and_then(|(file, line)| Some((file, line.parse::<usize>().ok()?)))
+            .filter(|(file, line)| !file.is_empty() && *line > 0);
+        let Some((file, line)) = parsed else {
+            return Err(OmniDocError::Other(format!(
+                "Expected a Markdown position as FILE:LINE, got '{source}'"
+            )));
+        };
+        Ok(Self::Source {
+            file: PathBuf::from(file),
+            line,
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct LocateReport {
+    schema_version: u32,
+    direction: &'static str,
+    source: SourceLocation,
+    pdf: PdfLocation,
+}
+
+#[derive(Debug, Serialize)]
+struct SourceLocation {
+    file: String,
+    path: String,
+    line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    column: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+struct PdfLocation {
+    path: String,
+    page: usize,
+    x: f64,
+    y: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    width: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    height: Option<f64>,
+}
+
+/// Handle the 'locate' command
+pub fn handle_locate(
+    target: LocateTarget,
+    path: Option<String>,
+    document: Option<String>,
+    variant: Option<String>,
+    json: bool,
+) -> Result<()> {
+    let source_file = match &target {
+        LocateTarget::Source { file, .. } => Some(file.canonicalize().map_err(|_| {
+            OmniDocError::Project(format!("Markdown file not found: {}", file.display()))
+        })?),
+        LocateTarget::Pdf { .. } => None,
+    };
+    let start = path.or_else(|| {
+        source_file
+            .as_deref()
+            .and_then(Path::parent)
+            .map(|parent| parent.to_string_lossy().to_string())
+    });
+    let project_path = path::determine_project_root(start)?;
+    check_omnidoc_project(&project_path)?;
+
+    let config_manager = create_single_document_config_manager(&project_path, document, variant)?;
+    let config = config_manager.get_merged();
+    let entry = entry_path(&project_path, config);
+    if entry
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("tex"))
+    {
+        return Err(OmniDocError::Project(
+            "omnidoc locate maps Markdown sources; LaTeX projects can use the engine's SyncTeX file directly"
+                .to_string(),
+        ));
+    }
+    let artifact = artifact_for_format(&project_path, config, "pdf")?;
+    let pdf = artifact.path_buf();
+    let rebuild_hint =
+        "Run `omnidoc build --to pdf` with xelatex, lualatex, pdflatex or tectonic first.";
+    if !artifact.exists {
+        return Err(OmniDocError::Project(format!(
+            "PDF '{}' does not exist. {rebuild_hint}",
+            pdf.display()
+        )));
+    }
+
+    let synctex_dir =
+        output_scratch_dir(&project_path, config, PandocOutputKind::Pdf).join(SYNCTEX_DIR);
+    let (synctex_file, tex_file) = synctex_files(&synctex_dir).ok_or_else(|| {
+        OmniDocError::Project(format!(
+            "No SyncTeX data was recorded for '{}'. {rebuild_hint}",
+            pdf.display()
+        ))
+    })?;
+    let synctex = SyncTex::read(&synctex_file)?;
+    let tex_name = tex_file
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default();
+    let tag = synctex.input_tag(tex_name).ok_or_else(|| {
+        OmniDocError::Project(format!(
+            "SyncTeX data in {} does not describe {tex_name}",
+            synctex_file.display()
+        ))
+    })?;
+    let tex = std::fs::read_to_string(&tex_file)?;
+
+    let executor = BuildExecutor::new(config.tool_paths.clone());
+    let mut spans = load_project_source_spans(&executor, &project_path, &entry)?;
+    for span in &mut spans {
+        if let Ok(canonical) = span.file.canonicalize() {
+            span.file = canonical;
+        }
+    }
+    let bridge = SourceBridge::new(&tex, &spans);
+
+    let report = match target {
+        LocateTarget::Source { line, .. } => {
+            let file = source_file.expect("forward search has a source file");
+            let record = bridge
+                .tex_line_for_markdown(&file, line)
+                .and_then(|tex_line| synctex.record_for_line(tag, tex_line))
+                .ok_or_else(|| {
+                    OmniDocError::Project(format!(
+                        "No typeset text in '{}' was traced back to {}:{line}",
+                        pdf.display(),
+                        display_path(&project_path, &file)
+                    ))
+                })?;
+            LocateReport {
+                schema_version: 1,
+                direction: "forward",
+                source: source_location(&project_path, &file, line, None),
+                pdf: PdfLocation {
+                    path: pdf.display().to_string(),
+                    page: record.page,
+                    x: round(record.x),
+                    y: round(record.y - record.height),
+                    width: Some(round(record.width.abs())),
+                    height: Some(round(record.height + record.depth)),
+                },
+            }
+        }
+        LocateTarget::Pdf { page, x, y } => {
+            let position = synctex
+                .record_at(tag, page, x, y)
+                .and_then(|record| bridge.markdown_for_tex_line(record.line))
+                .ok_or_else(|| {
+                    OmniDocError::Project(format!(
+                        "No Markdown source was found for page {page} at ({x}, {y}) of '{}'",
+                        pdf.display()
+                    ))
+                })?;
+            LocateReport {
+                schema_version: 1,
+                direction: "inverse",
+                source: source_location(
+                    &project_path,
+                    &position.file,
+                    position.line,
+                    Some(position.column),
+                ),
+                pdf: PdfLocation {
+                    path: pdf.display().to_string(),
+                    page,
+                    x,
+                    y,
+                    width: None,
+                    height: None,
+                },
+            }
+        }
+    };
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report)
+                .map_err(|error| OmniDocError::Other(error.to_string()))?
+        );
+    } else if report.direction == "forward" {
+        println!(
+            "{}:{}:{}:{}",
+            report.pdf.path, report.pdf.page, report.pdf.x, report.pdf.y
+        );
+    } else {
+        println!(
+            "{}:{}:{}",
+            report.source.file,
+            report.source.line,
+            report.source.column.unwrap_or(1)
+        );
+    }
+    Ok(())
+}
+
+/// The preserved `.synctex.gz` and the `.tex` file it describes.
+fn synctex_files(directory: &Path) -> Option<(PathBuf, PathBuf)> {
+    let synctex = std::fs::read_dir(directory)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.ends_with(".synctex.gz"))
+        })?;
+    let name = synctex.file_name()?.to_str()?;
+    let tex = directory.join(format!("{}.tex", name.strip_suffix(".synctex.gz")?));
+    tex.is_file().then_some((synctex, tex))
+}
+
+fn source_location(
+    project_path: &Path,
+    file: &Path,
+    line: usize,
+    column: Option<usize>,
+) -> SourceLocation {
+    SourceLocation {
+        file: display_path(project_path, file),
+        path: file.display().to_string(),
+        line,
+        column,
+    }
+}
+
+fn display_path(project_path: &Path, file: &Path) -> String {
+    file.strip_prefix(project_path)
+        .unwrap_or(file)
+        .display()
+        .to_string()
+}
+
+fn round(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LocateTarget;
+    use std::path::PathBuf;
+
+    #[test]
+    fn parses_markdown_positions() {
+        assert_eq!(
+            LocateTarget::parse_source("chapters/intro.md:120").expect("position"),
+            LocateTarget::Source {
+                file: PathBuf::from("chapters/intro.md"),
+                line: 120
+            }
+        );
+        assert!(LocateTarget::parse_source("main.md").is_err());
+        assert!(LocateTarget::parse_source("main.md:0").is_err());
+        assert!(LocateTarget::parse_source(":12").is_err());
+    }
+}
@@ -0,0 +1,878 @@
+//! `omnidoc lsp`: a Language Server Protocol server for OmniDoc Markdown
+//! projects, speaking JSON-RPC 2.0 with `Content-Length` framing on stdio.
+//!
+//! - Diagnostics: `lint_project` and `validate_config` issues, with the lint
+//!   rule as the diagnostic code, refreshed when the client connects and
+//!   whenever a document is saved.
+//! - Completion: cross-reference labels after `@fig:`/`@tbl:`/`@sec:` (or the
+//!   `-` spelling), citation keys from the project's `.bib` files, and paths
+//!   inside `include-code="…"` attributes and `.include` blocks.
+//! - Hover: admonition types, described by the library's `BLOCKS.md`.
+//! - Go to definition: included chapters and source files, resolved through
+//!   the project dependency graph.
+
+use crate::cli::handlers::common::create_config_manager;
+use crate::cli::handlers::quality::lint_issues;
+use crate::config::{CliOverrides, MergedConfig};
+use crate::error::{OmniDocError, Result};
+use crate::project_tools::{self, IssueSeverity, ProjectIssue};
+use crate::utils::path;
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS};
+use regex::Regex;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+/// Bundled copy of the public block syntax, used when the installed library
+/// has no `BLOCKS.md`.
+const BUNDLED_BLOCKS: &str = include_str!("../../../bundles/libs/BLOCKS.md");
+/// Characters escaped when turning a path into a `file://` URI.
+const URI_PATH: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+// JSON-RPC 2.0 and LSP error codes.
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const SERVER_NOT_INITIALIZED: i64 = -32002;
+const REQUEST_FAILED: i64 = -32803;
+
+// LSP enumerations.
+const SYNC_FULL: u8 = 1;
+const KIND_FILE: u8 = 17;
+const KIND_REFERENCE: u8 = 18;
+const KIND_FOLDER: u8 = 19;
+
+static ATTRIBUTES: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{([^}]*)\}").expect("attribute regex"));
+static BIB_ENTRY: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^\s*@(\w+)\s*[{(]\s*([^,\s{}()]+)\s*,").expect("BibTeX entry regex")
+});
+static REFERENCE_PREFIX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:^|[^\w@])@([\w:.\-]*)$").expect("reference regex"));
+static INCLUDE_ATTRIBUTE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"include(?:-code)?=["']([^"']*)$"#).expect("include attribute regex")
+});
+static INCLUDE_TARGET: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"include(?:-code)?=["']([^"']+)["']"#).expect("include target regex")
+});
+
+/// Handle the 'lsp' command
+pub fn handle_lsp(path: Option<String>) -> Result<()> {
+    let stdin = std::io::stdin();
+    let mut reader = stdin.lock();
+    let mut writer = std::io::stdout().lock();
+    let mut server = LanguageServer::new(path);
+    while let Some(message) = read_message(&mut reader)? {
+        for outgoing in server.handle(message) {
+            write_message(&mut writer, &outgoing)?;
+        }
+        if server.exit {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Read one `Content-Length` framed message; `None` at end of input.
+fn read_message(reader: &mut impl BufRead) -> Result<Option<Value>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+    let length = length.ok_or_else(|| {
+        OmniDocError::Other("LSP message without a Content-Length header".to_string())
+    })?;
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    // A malformed body is answered like any unknown message rather than
+    // ending the session.
+    Ok(Some(serde_json::from_slice(&body).unwrap_or(Value::Null)))
+}
+
+fn write_message(writer: &mut impl Write, message: &Value) -> Result<()> {
+    let body = message.to_string();
+    write!(writer, "Content-Length: {}\r\n\r\n{body}", body.len())?;
+    writer.flush()?;
+    Ok(())
+}
+
+struct LanguageServer {
+    path: Option<String>,
+    project_path: Option<PathBuf>,
+    /// Text of open documents by URI; everything else is read from disk.
+    documents: HashMap<String, String>,
+    /// URIs that currently carry diagnostics, so fixed files get cleared.
+    published: BTreeSet<String>,
+    shutdown: bool,
+    exit: bool,
+}
+
+impl LanguageServer {
+    fn new(path: Option<String>) -> Self {
+        Self {
+            path,
+            project_path: None,
+            documents: HashMap::new(),
+            published: BTreeSet::new(),
+            shutdown: false,
+            exit: false,
+        }
+    }
+
+    /// Messages to send in reply to one client message.
+    fn handle(&mut self, message: Value) -> Vec<Value> {
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            // Responses to server requests; this server sends none.
+            return Vec::new();
+        };
+        let id = message.get("id").cloned();
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        if method == "exit" {
+            self.exit = true;
+            return Vec::new();
+        }
+        if method == "initialize" {
+            let id = id.unwrap_or(Value::Null);
+            return vec![match self.initialize(&params) {
+                Ok(result) => response(id, result),
+                Err(error) => error_response(id, REQUEST_FAILED, error.message()),
+            }];
+        }
+        if self.project_path.is_none() {
+            return id
+                .map(|id| error_response(id, SERVER_NOT_INITIALIZED, "server not initialized"))
+                .into_iter()
+                .collect();
+        }
+
+        match (method, id) {
+            ("initialized", None) => self.refresh_diagnostics(),
+            ("textDocument/didOpen", None) => {
+                if let (Some(uri), Some(text)) = (
+                    params["textDocument"]["uri"].as_str(),
+                    params["textDocument"]["text"].as_str(),
+                ) {
+                    self.documents.insert(uri.to_string(), text.to_string());
+                }
+                Vec::new()
+            }
+            ("textDocument/didChange", None) => {
+                let text = params["contentChanges"]
+                    .as_array()
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str());
+                if let (Some(uri), Some(text)) = (params["textDocument"]["uri"].as_str(), text) {
+                    self.documents.insert(uri.to_string(), text.to_string());
+                }
+                Vec::new()
+            }
+            ("textDocument/didSave", None) => self.refresh_diagnostics(),
+            ("textDocument/didClose", None) => {
+                if let Some(uri) = params["textDocument"]["uri"].as_str() {
+                    self.documents.remove(uri);
+                }
+                Vec::new()
+            }
+            ("shutdown", Some(id)) => {
+                self.shutdown = true;
+                vec![response(id, Value::Null)]
+            }
+            (method, Some(id)) => {
+                let result = match method {
+                    "textDocument/completion" => self.completion(&params),
+                    "textDocument/hover" => self.hover(&params),
+                    "textDocument/definition" => self.definition(&params),
+                    method => {
+                        return vec![error_response(
+                            id,
+                            METHOD_NOT_FOUND,
+                            format!("unknown method '{method}'"),
+                        )]
+                    }
+                };
+                vec![match result {
+                    Some(result) => response(id, result),
+                    None if params["textDocument"]["uri"].is_string() => response(id, Value::Null),
+                    None => error_response(id, INVALID_PARAMS, "missing text document position"),
+                }]
+            }
+            // Notifications this server does not act on.
+            (_, None) => Vec::new(),
+        }
+    }
+
+    fn initialize(&mut self, params: &Value) -> Result<Value> {
+        let start = self.path.clone().or_else(|| {
+            params["rootUri"]
+                .as_str()
+                .and_then(uri_to_path)
+                .or_else(|| params["rootPath"].as_str().map(PathBuf::from))
+                .map(|root| root.to_string_lossy().to_string())
+        });
+        self.project_path = Some(path::determine_project_root(start)?);
+        Ok(json!({
+            "capabilities": {
+                "textDocumentSync": {
+                    "openClose": true,
+                    "change": SYNC_FULL,
+                    "save": { "includeText": false },
+                },
+                "completionProvider": { "triggerCharacters": ["@", ":", "-", "/", "\""] },
+                "hoverProvider": true,
+                "definitionProvider": true,
+            },
+            "serverInfo": { "name": "omnidoc", "version": env!("CARGO_PKG_VERSION") },
+        }))
+    }
+
+    fn project_path(&self) -> &Path {
+        self.project_path
+            .as_deref()
+            .expect("requests are only handled after initialize")
+    }
+
+    fn config(&self) -> Result<MergedConfig> {
+        Ok(
+            create_config_manager(Some(self.project_path()), CliOverrides::new())?
+                .get_merged()
+                .clone(),
+        )
+    }
+
+    /// Current text of `uri`: the open buffer, else the file on disk.
+    fn text(&self, uri: &str) -> Option<String> {
+        self.documents
+            .get(uri)
+            .cloned()
+            .or_else(|| std::fs::read_to_string(uri_to_path(uri)?).ok())
+    }
+
+    fn refresh_diagnostics(&mut self) -> Vec<Value> {
+        let project_path = self.project_path().to_path_buf();
+        let issues = self
+            .config()
+            .and_then(|config| lint_issues(&project_path, &config))
+            .unwrap_or_else(|error| {
+                vec![ProjectIssue {
+                    severity: IssueSeverity::Error,
+                    message: error.message().to_string(),
+                    path: None,
+                    line: None,
+                    column: None,
+                    rule: None,
+                }]
+            });
+
+        let mut by_uri: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+        for issue in issues {
+            let file = project_path.join(issue.path.as_deref().unwrap_or(".omnidoc.toml"));
+            let uri = path_to_uri(&file);
+            let line = issue.line.unwrap_or(1).saturating_sub(1);
+            let text = self
+                .text(&uri)
+                .and_then(|text| text.lines().nth(line).map(str::to_string))
+                .unwrap_or_default();
+            // Lint columns count characters; LSP counts UTF-16 code units.
+            let start = issue.column.map_or(0, |column| {
+                text.chars()
+                    .take(column.saturating_sub(1))
+                    .map(char::len_utf16)
+                    .sum()
+            });
+            let mut diagnostic = json!({
+                "range": range(line, start, line, utf16_len(&text).max(start)),
+                "severity": match issue.severity {
+                    IssueSeverity::Error => 1,
+                    IssueSeverity::Warning => 2,
+                    IssueSeverity::Info => 3,
+                },
+                "source": "omnidoc",
+                "message": issue.message,
+            });
+            if let Some(rule) = issue.rule {
+                diagnostic["code"] = Value::String(rule);
+            }
+            by_uri.entry(uri).or_default().push(diagnostic);
+        }
+
+        let stale = std::mem::take(&mut self.published);
+        let mut notifications = Vec::new();
+        for uri in stale.iter().filter(|uri| !by_uri.contains_key(*uri)) {
+            notifications.push(publish_diagnostics(uri, Vec::new()));
+        }
+        for (uri, diagnostics) in by_uri {
+            notifications.push(publish_diagnostics(&uri, diagnostics));
+            self.published.insert(uri);
+        }
+        notifications
+    }
+
+    fn completion(&self, params: &Value) -> Option<Value> {
+        let (uri, line, character) = text_position(params)?;
+        let text = self.text(uri)?;
+        let items = match completion_context(&text, line, character)? {
+            CompletionContext::Reference { prefix, start } => self
+                .reference_targets()
+                .into_iter()
+                .filter(|target| target.key.starts_with(&prefix))
+                .map(|target| {
+                    json!({
+                        "label": target.key,
+                        "kind": KIND_REFERENCE,
+                        "detail": target.detail,
+                        "documentation": target.context,
+                        "textEdit": {
+                            "range": range(line, start, line, character),
+                            "newText": target.key,
+                        },
+                    })
+                })
+                .collect::<Vec<_>>(),
+            CompletionContext::Path { prefix, start } => {
+                let (directory, partial) = match prefix.rsplit_once('/') {
+                    Some((directory, partial)) => (format!("{directory}/"), partial.to_string()),
+                    None => (String::new(), prefix.clone()),
+                };
+                let start = start + utf16_len(&directory);
+                let mut entries = std::fs::read_dir(self.project_path().join(&directory))
+                    .into_iter()
+                    .flatten()
+                    .flatten()
+                    .filter_map(|entry| {
+                        let name = entry.file_name().to_string_lossy().to_string();
+                        let visible = !name.starts_with('.') || partial.starts_with('.');
+                        (visible && name.starts_with(&partial))
+                            .then(|| (name, entry.path().is_dir()))
+                    })
+                    .collect::<Vec<_>>();
+                entries.sort();
+                entries
+                    .into_iter()
+                    .map(|(name, is_dir)| {
+                        let insert = if is_dir { format!("{name}/") } else { name };
+                        json!({
+                            "label": insert,
+                            "kind": if is_dir { KIND_FOLDER } else { KIND_FILE },
+                            "textEdit": {
+                                "range": range(line, start, line, character),
+                                "newText": insert,
+                            },
+                        })
+                    })
+                    .collect()
+            }
+        };
+        Some(json!({ "isIncomplete": false, "items": items }))
+    }
+
+    /// Cross-reference labels declared in the project's Markdown and keys of
+    /// its BibTeX databases. Open buffers win over the files on disk.
+    fn reference_targets(&self) -> Vec<ReferenceTarget> {
+        let project_path = self.project_path();
+        let mut targets = Vec::new();
+        for file in project_tools::source_files(project_path) {
+            let extension = file
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .map(str::to_ascii_lowercase);
+            let Some(text) = self.text(&path_to_uri(&file)) else {
+                continue;
+            };
+            let display = file
+                .strip_prefix(project_path)
+                .unwrap_or(&file)
+                .to_string_lossy()
+                .replace('\\', "/");
+            match extension.as_deref() {
+                Some("md" | "markdown") => targets.extend(crossref_labels(&text, &display)),
+                Some("bib") => targets.extend(citation_keys(&text, &display)),
+                _ => {}
+            }
+        }
+        targets.sort_by(|left, right| left.key.cmp(&right.key));
+        targets.dedup_by(|left, right| left.key == right.key);
+        targets
+    }
+
+    fn hover(&self, params: &Value) -> Option<Value> {
+        let (uri, line, character) = text_position(params)?;
+        let text = self.text(uri)?;
+        let line_text = text.lines().nth(line)?;
+        let trimmed = line_text.trim_start();
+        if !trimmed.starts_with(":::") || !trimmed.contains("admonition") {
+            return None;
+        }
+        let (word, start, end) = word_at(line_text, character)?;
+        let library_blocks = self.config().ok().and_then(|config| {
+            std::fs::read_to_string(project_tools::omnidoc_library_root(&config).join("BLOCKS.md"))
+                .ok()
+        });
+        let reference =
+            AdmonitionReference::parse(library_blocks.as_deref().unwrap_or(BUNDLED_BLOCKS));
+        let contents = reference.hover(word.trim_start_matches('.'))?;
+        Some(json!({
+            "contents": { "kind": "markdown", "value": contents },
+            "range": range(line, start, line, end),
+        }))
+    }
+
+    fn definition(&self, params: &Value) -> Option<Value> {
+        let (uri, line, character) = text_position(params)?;
+        let text = self.text(uri)?;
+        let target = include_target_at(&text, line, character)?;
+        let project_path = self.project_path();
+        let graph = project_tools::dependency_graph(project_path, &self.config().ok()?);
+        let document_dir = uri_to_path(uri)
+            .and_then(|path| path.parent().map(Path::to_path_buf))
+            .unwrap_or_else(|| project_path.to_path_buf());
+        // The include filters run from the project root; chapters that name
+        // siblings relative to themselves resolve through their directory.
+        let resolved = [project_path.join(&target), document_dir.join(&target)]
+            .into_iter()
+            .find(|candidate| {
+                let relative = candidate
+                    .strip_prefix(project_path)
+                    .unwrap_or(candidate)
+                    .to_string_lossy()
+                    .replace('\\', "/");
+                graph.files.iter().any(|file| file == &relative)
+            })?;
+        Some(json!({
+            "uri": path_to_uri(&resolved),
+            "range": range(0, 0, 0, 0),
+        }))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ReferenceTarget {
+    key: String,
+    detail: String,
+    context: String,
+}
+
+fn crossref_labels(text: &str, file: &str) -> Vec<ReferenceTarget> {
+    let mut labels = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        for capture in ATTRIBUTES.captures_iter(line) {
+            for label in project_tools::crossref_labels(&capture[1]) {
+                labels.push(ReferenceTarget {
+                    key: label.to_string(),
+                    detail: format!("{file}:{}", index + 1),
+                    context: line.trim().to_string(),
+                });
+            }
+        }
+    }
+    labels
+}
+
+fn citation_keys(text: &str, file: &str) -> Vec<ReferenceTarget> {
+    BIB_ENTRY
+        .captures_iter(text)
+        .filter(|capture| {
+            !matches!(
+                capture[1].to_ascii_lowercase().as_str(),
+                "string" | "comment" | "preamble"
+            )
+        })
+        .map(|capture| ReferenceTarget {
+            key: capture[2].to_string(),
+            detail: format!("@{} in {file}", capture[1].to_ascii_lowercase()),
+            context: String::new(),
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum CompletionContext {
+    /// After `@`; `start` is the UTF-16 column just past the `@`.
+    Reference { prefix: String, start: usize },
+    /// A project path typed so far, starting at UTF-16 column `start`.
+    Path { prefix: String, start: usize },
+}
+
+fn completion_context(text: &str, line: usize, character: usize) -> Option<CompletionContext> {
+    let lines = text.lines().collect::<Vec<_>>();
+    let current = lines.get(line).copied().unwrap_or("");
+    let before = &current[..utf16_to_byte(current, character)];
+
+    if inside_include_block(&lines, line) {
+        let prefix = before.trim_start();
+        return Some(CompletionContext::Path {
+            prefix: prefix.to_string(),
+            start: utf16_len(&before[..before.len() - prefix.len()]),
+        });
+    }
+    if let Some(capture) = INCLUDE_ATTRIBUTE.captures(before) {
+        let prefix = capture.get(1)?;
+        return Some(CompletionContext::Path {
+            prefix: prefix.as_str().to_string(),
+            start: utf16_len(&before[..prefix.start()]),
+        });
+    }
+    let capture = REFERENCE_PREFIX.captures(before)?;
+    let prefix = capture.get(1)?;
+    Some(CompletionContext::Reference {
+        prefix: prefix.as_str().to_string(),
+        start: utf16_len(&before[..prefix.start()]),
+    })
+}
+
+/// Whether `line` sits between the fences of a ```` ```{.include} ```` block.
+fn inside_include_block(lines: &[&str], line: usize) -> bool {
+    let current = lines.get(line).map_or("", |text| text.trim_start());
+    if current.starts_with("```") || current.starts_with("~~~") {
+        return false;
+    }
+    let mut open: Option<(String, bool)> = None;
+    for text in lines.iter().take(line) {
+        let trimmed = text.trim_start();
+        let fence = trimmed
+            .chars()
+            .take_while(|character| *character == '`' || *character == '~')
+            .collect::<String>();
+        let is_fence = fence.len() >= 3
+            && fence
+                .chars()
+                .all(|c| c == fence.chars().next().unwrap_or('`'));
+        match &open {
+            Some((marker, _))
+                if is_fence
+                    && fence.starts_with(marker.as_str())
+                    && trimmed[fence.len()..].trim().is_empty() =>
+            {
+                open = None;
+            }
+            Some(_) => {}
+            None if is_fence => {
+                let info = &trimmed[fence.len()..];
+                let include = ATTRIBUTES.captures(info).is_some_and(|capture| {
+                    capture[1]
+                        .split_whitespace()
+                        .any(|token| token == ".include")
+                });
+                open = Some((fence, include));
+            }
+            None => {}
+        }
+    }
+    open.is_some_and(|(_, include)| include)
+}
+
+/// Path named by an include attribute under the cursor, or the line of an
+/// `.include` block the cursor is on.
+fn include_target_at(text: &str, line: usize, character: usize) -> Option<String> {
+    let lines = text.lines().collect::<Vec<_>>();
+    let current = *lines.get(line)?;
+    if inside_include_block(&lines, line) {
+        let target = current.trim();
+        return (!target.is_empty()).then(|| target.to_string());
+    }
+    let offset = utf16_to_byte(current, character);
+    INCLUDE_TARGET
+        .captures_iter(current)
+        .find(|capture| capture.get(0).is_some_and(|m| m.range().contains(&offset)))
+        .map(|capture| capture[1].to_string())
+}
+
+/// Admonition types from the `BLOCKS.md` type table.
+struct AdmonitionReference {
+    columns: Vec<String>,
+    types: Vec<Vec<String>>,
+}
+
+impl AdmonitionReference {
+    fn parse(blocks: &str) -> Self {
+        let mut columns = Vec::new();
+        let mut types = Vec::new();
+        for line in blocks.lines().map(str::trim) {
+            if !line.starts_with('|') {
+                if !types.is_empty() {
+                    break;
+                }
+                columns.clear();
+                continue;
+            }
+            let cells = line
+                .trim_matches('|')
+                .split('|')
+                .map(|cell| cell.trim().to_string())
+                .collect::<Vec<_>>();
+            if columns.is_empty() {
+                columns = cells;
+            } else if let Some(name) = cells
+                .first()
+                .and_then(|cell| cell.strip_prefix('`'))
+                .and_then(|cell| cell.strip_suffix('`'))
+            {
+                let mut row = cells.clone();
+                row[0] = name.to_string();
+                types.push(row);
+            }
+        }
+        Self { columns, types }
+    }
+
+    fn hover(&self, word: &str) -> Option<String> {
+        if word == "admonition" {
+            let names = self
+                .types
+                .iter()
+                .map(|row| format!("`{}`", row[0]))
+                .collect::<Vec<_>>();
+            return Some(format!(
+                "**admonition**\n\n```markdown\n::: {{.admonition .TYPE title=\"…\"}}\n\n…\n\n:::\n```\n\n{}",
+                names.join(" · ")
+            ));
+        }
+        let row = self.types.iter().find(|row| row[0] == word)?;
+        let mut hover = format!(
+            "**`{word}`** admonition\n\n```markdown\n::: {{.admonition .{word}}}\n\n…\n\n:::\n```\n"
+        );
+        for (column, value) in self.columns.iter().zip(row).skip(1) {
+            hover.push_str(&format!("\n{column}: {value}  "));
+        }
+        Some(hover.trim_end().to_string())
+    }
+}
+
+/// The `[\w.-]` word under UTF-16 column `character`, with its UTF-16 range.
+fn word_at(line: &str, character: usize) -> Option<(&str, usize, usize)> {
+    let offset = utf16_to_byte(line, character);
+    let is_word =
+        |character: char| character.is_alphanumeric() || matches!(character, '-' | '_' | '.');
+    let start = line[..offset]
+        .char_indices()
+        .rev()
+        .take_while(|(_, character)| is_word(*character))
+        .last()
+        .map_or(offset, |(index, _)| index);
+    let end = line[offset..]
+        .char_indices()
+        .find(|(_, character)| !is_word(*character))
+        .map_or(line.len(), |(index, _)| offset + index);
+    (start < end).then(|| {
+        (
+            &line[start..end],
+            utf16_len(&line[..start]),
+            utf16_len(&line[..end]),
+        )
+    })
+}
+
+fn text_position(params: &Value) -> Option<(&str, usize, usize)> {
+    let uri = params["textDocument"]["uri"].as_str()?;
+    let line = params["position"]["line"].as_u64()? as usize;
+    let character = params["position"]["character"].as_u64()? as usize;
+    Some((uri, line, character))
+}
+
+/// Byte offset of UTF-16 column `character` in `line`, clamped to its end.
+fn utf16_to_byte(line: &str, character: usize) -> usize {
+    let mut units = 0;
+    for (index, ch) in line.char_indices() {
+        if units >= character {
+            return index;
+        }
+        units += ch.len_utf16();
+    }
+    line.len()
+}
+
+fn utf16_len(text: &str) -> usize {
+    text.encode_utf16().count()
+}
+
+fn range(start_line: usize, start: usize, end_line: usize, end: usize) -> Value {
+    json!({
+        "start": { "line": start_line, "character": start },
+        "end": { "line": end_line, "character": end },
+    })
+}
+
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    let path = percent_decode_str(path).decode_utf8().ok()?;
+    // `file:///C:/docs` on Windows.
+    let path = match path.strip_prefix('/') {
+        Some(rest) if rest.as_bytes().get(1) == Some(&b':') => rest.to_string(),
+        _ => path.to_string(),
+    };
+    Some(PathBuf::from(path))
+}
+
+fn path_to_uri(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let path = if path.starts_with('/') {
+        path
+    } else {
+        format!("/{path}")
+    };
+    format!("file://{}", utf8_percent_encode(&path, URI_PATH))
+}
+
+fn publish_diagnostics(uri: &str, diagnostics: Vec<Value>) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": { "uri": uri, "diagnostics": diagnostics },
+    })
+}
+
+fn response(id: Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+fn error_response(id: Value, code: i64, message: impl AsRef<str>) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message.as_ref() },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn completion_contexts_follow_the_cursor() {
+        let text = "See @fig:pl and [@knu\n\
+```{.include shift-heading-level-by=1}\n\
+chapters/in\n\
+```\n\
+```{.python include-code=\"scripts/an\"}\n\
+```\n\
+mail me@example.com";
+        assert_eq!(
+            completion_context(text, 0, 11),
+            Some(CompletionContext::Reference {
+                prefix: "fig:pl".to_string(),
+                start: 5
+            })
+        );
+        assert_eq!(
+            completion_context(text, 0, 21),
+            Some(CompletionContext::Reference {
+                prefix: "knu".to_string(),
+                start: 18
+            })
+        );
+        assert_eq!(
+            completion_context(text, 2, 11),
+            Some(CompletionContext::Path {
+                prefix: "chapters/in".to_string(),
+                start: 0
+            })
+        );
+        assert_eq!(
+            completion_context(text, 4, 36),
+            Some(CompletionContext::Path {
+                prefix: "scripts/an".to_string(),
+                start: 26
+            })
+        );
+        assert_eq!(completion_context(text, 3, 3), None);
+        assert_eq!(completion_context(text, 6, 19), None);
+        assert_eq!(
+            include_target_at(text, 2, 0),
+            Some("chapters/in".to_string())
+        );
+        assert_eq!(
+            include_target_at(text, 4, 28),
+            Some("scripts/an".to_string())
+        );
+    }
+
+    #[test]
+    fn collects_crossref_labels_and_citation_keys() {
+        let labels = crossref_labels(
+            "# Intro {#sec:intro}\n\n![Plot](plot.png){#fig-plot width=50%}\n{#custom}\n",
+            "main.md",
+        );
+        assert_eq!(
+            labels
+                .iter()
+                .map(|label| label.key.as_str())
+                .collect::<Vec<_>>(),
+            ["sec:intro", "fig-plot"]
+        );
+        assert_eq!(labels[1].detail, "main.md:3");
+
+        let keys = citation_keys(
+            "@string{acm = \"ACM\"}\n@Book{knuth1984,\n  title = {TeX}\n}\n@article( lamport94 ,\n",
+            "refs.bib",
+        );
+        assert_eq!(
+            keys.iter().map(|key| key.key.as_str()).collect::<Vec<_>>(),
+            ["knuth1984", "lamport94"]
+        );
+        assert_eq!(keys[0].detail, "@book in refs.bib");
+    }
+
+    #[test]
+    fn admonition_hover_comes_from_the_blocks_table() {
+        let reference = AdmonitionReference::parse(BUNDLED_BLOCKS);
+        assert!(reference.types.len() >= 10);
+        let hover = reference.hover("warning").expect("warning hover");
+        assert!(hover.contains("::: {.admonition .warning}"), "{hover}");
+        assert!(hover.contains("警告"), "{hover}");
+        assert!(reference
+            .hover("admonition")
+            .unwrap()
+            .contains("`solution`"));
+        assert!(reference.hover("unknown").is_none());
+
+        assert_eq!(
+            word_at("::: {.admonition .warning title=\"x\"}", 20),
+            Some((".warning", 17, 25))
+        );
+    }
+
+    #[test]
+    fn file_uris_round_trip() {
+        let path = Path::new("/tmp/my docs/章节.md");
+        let uri = path_to_uri(path);
+        assert_eq!(uri, "file:///tmp/my%20docs/%E7%AB%A0%E8%8A%82.md");
+        assert_eq!(uri_to_path(&uri).as_deref(), Some(path));
+        assert_eq!(utf16_to_byte("章节 ab", 3), "章节 ".len());
+    }
+
+    #[test]
+    fn requests_before_initialize_are_rejected() {
+        let mut server = LanguageServer::new(None);
+        let replies = server.handle(json!({
+            "jsonrpc": "2.0", "id": 1, "method": "textDocument/hover", "params": {}
+        }));
+        assert_eq!(replies[0]["error"]["code"], SERVER_NOT_INITIALIZED);
+        assert!(server
+            .handle(json!({ "jsonrpc": "2.0", "method": "exit" }))
+            .is_empty());
+        assert!(server.exit);
+    }
+}
@@ -9,6 +9,7 @@ pub fn handle_md2html(
     inputs: Vec<String>,
     output: Option<String>,
     css: Option<String>,
+    profile: Option<String>,
 ) -> Result<()> {
     if inputs.is_empty() {
         return Err(OmniDocError::Project(
@@ -16,7 +17,7 @@ pub fn handle_md2html(
         ));
     }
 
-    let converter = create_converter_service()?;
+    let converter = create_converter_service(profile)?;
     let css_path = css.as_ref().map(Path::new);
 
     // 处理每个输入文件
@@ -9,6 +9,7 @@ pub fn handle_md2pdf(
     lang: Option<String>,
     inputs: Vec<String>,
     output: Option<String>,
+    profile: Option<String>,
 ) -> Result<()> {
     if inputs.is_empty() {
         return Err(OmniDocError::Project(
@@ -16,7 +17,7 @@ pub fn handle_md2pdf(
         ));
     }
 
-    let converter = create_converter_service()?;
+    let converter = create_converter_service(profile)?;
 
     // 处理每个输入文件
     for input_str in &inputs {
@@ -1,12 +1,16 @@
 mod common;
 
 pub mod build;
+pub mod cache;
 pub mod clean;
 pub mod config;
+pub mod daemon;
 pub mod figure;
 pub mod fmt;
 pub mod init;
 pub mod lib;
+pub mod locate;
+pub mod lsp;
 pub mod md2html;
 pub mod md2pdf;
 pub mod new;
@@ -21,15 +25,19 @@ pub mod update;
 pub mod watch;
 
 pub use build::handle_build;
+pub use cache::handle_cache;
 pub use clean::handle_clean;
 pub(crate) use common::print_json_error;
 pub use config::{
     handle_config, handle_config_get, handle_config_set, handle_config_show, handle_config_unset,
 };
+pub use daemon::handle_daemon;
 pub use figure::{handle_figure, BitfieldOptions};
 pub use fmt::handle_fmt;
 pub use init::handle_init;
 pub use lib::handle_lib;
+pub use locate::{handle_locate, LocateTarget};
+pub use lsp::handle_lsp;
 pub use md2html::handle_md2html;
 pub use md2pdf::handle_md2pdf;
 pub use new::handle_new;
@@ -37,7 +45,8 @@ pub use open::handle_open;
 pub use plugin::handle_plugin;
 pub use publish::handle_publish;
 pub use quality::{
-    handle_ci, handle_config_validate, handle_deps, handle_doctor, handle_lint, handle_lock,
+    handle_ci, handle_config_validate, handle_deps, handle_doctor, handle_links, handle_lint,
+    handle_lock,
 };
 pub use status::handle_status;
 pub use template::handle_template_validate;
@@ -1,15 +1,22 @@
-use crate::cli::handlers::common::{check_omnidoc_project, create_config_manager_default};
+use crate::cli::handlers::common::{check_omnidoc_project, create_single_document_config_manager};
 use crate::doc::artifacts::{artifact_for_format, primary_output_format};
 use crate::doc::open_path;
 use crate::error::{OmniDocError, Result};
 use crate::utils::path;
 
 /// Handle the 'open' command
-pub fn handle_open(path: Option<String>, to: Option<String>, print_path: bool) -> Result<()> {
+pub fn handle_open(
+    path: Option<String>,
+    document: Option<String>,
+    variant: Option<String>,
+    to: Option<String>,
+    print_path: bool,
+) -> Result<()> {
     let project_path = path::determine_project_root(path)?;
     check_omnidoc_project(&project_path)?;
 
-    let config_manager = create_config_manager_default(Some(project_path.as_path()))?;
+    // A viewer shows one file, so a multi-document project needs --doc.
+    let config_manager = create_single_document_config_manager(&project_path, document, variant)?;
     let merged_config = config_manager.get_merged();
     let output = match to {
         Some(output) => output,
@@ -20,9 +27,19 @@ pub fn handle_open(path: Option<String>, to: Option<String>, print_path: bool) -
 
     if !artifact.exists {
         return Err(OmniDocError::Project(format!(
-            "Build artifact '{}' does not exist. Run `omnidoc build --to {} {}` first.",
+            "Build artifact '{}' does not exist. Run `omnidoc build --to {}{}{} {}` first.",
             artifact_path.display(),
             artifact.format,
+            merged_config
+                .document
+                .as_deref()
+                .map(|document| format!(" --doc {document}"))
+                .unwrap_or_default(),
+            merged_config
+                .variant
+                .as_deref()
+                .map(|variant| format!(" --variant {variant}"))
+                .unwrap_or_default(),
             project_path.display()
         )));
     }
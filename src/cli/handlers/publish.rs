@@ -1,16 +1,20 @@
+use crate::build::media::media_dir;
+use crate::build::pandoc_policy::PandocOutputKind;
+use crate::build::search::page_index_name;
 use crate::cli::handlers::build::{
     build_cli_overrides, build_project_outputs_unlocked, resolve_outputs, BuildRunOptions,
 };
-use crate::cli::handlers::common::create_config_manager;
+use crate::cli::handlers::common::{create_config_manager, selected_documents};
 use crate::doc::artifacts::expected_output_file;
 use crate::error::{OmniDocError, Result};
-use crate::project_tools::content_digest;
+use crate::project_tools::{content_digest, document_output_key};
 use crate::utils::path;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeSet;
 use std::fs;
 use std::path::{Component, Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
+use walkdir::WalkDir;
 
 #[derive(Debug, Deserialize, Serialize)]
 struct PublishArtifact {
@@ -131,12 +135,16 @@ fn remove_path(path: &Path) -> std::io::Result<()> {
 #[allow(clippy::too_many_arguments)]
 pub fn handle_publish(
     path: Option<String>,
+    document: Option<String>,
+    profile: Option<String>,
+    variant: Option<String>,
     to: Option<String>,
     all: bool,
     outputs: Vec<String>,
     pdf_engine: Option<String>,
     latex_backend: Option<String>,
     max_latex_passes: Option<usize>,
+    jobs: Option<usize>,
     dist_dir: String,
     tag: Option<String>,
     no_build: bool,
@@ -159,8 +167,12 @@ pub fn handle_publish(
         pdf_engine,
         latex_backend,
         max_latex_passes,
+        jobs,
         verbose,
-    );
+    )
+    .with_document(document)
+    .with_profile(profile)
+    .with_variant(variant);
 
     if !no_build {
         build_project_outputs_unlocked(
@@ -172,6 +184,7 @@ pub fn handle_publish(
                 report: true,
                 write_lock: true,
                 strict,
+                ..BuildRunOptions::default()
             },
             verbose,
         )?;
@@ -179,7 +192,6 @@ pub fn handle_publish(
 
     let config_manager = create_config_manager(Some(&project_path), cli_overrides.clone())?;
     let config = config_manager.get_merged().clone();
-    let outputs = resolve_outputs(&config, &cli_overrides, all);
     let target = config.target.clone().unwrap_or_else(|| {
         project_path
             .file_name()
@@ -194,9 +206,61 @@ pub fn handle_publish(
     let publish_dir = transaction.path();
 
     let mut artifacts = Vec::new();
-    for output in outputs {
-        let source = expected_output_file(&project_path, &config, &output, &target);
-        artifacts.push(copy_artifact(&project_path, &source, publish_dir, &output)?);
+    for document in selected_documents(&config, cli_overrides.document.clone()) {
+        let document_overrides = cli_overrides.clone().with_document(document.clone());
+        let document_config =
+            create_config_manager(Some(&project_path), document_overrides.clone())?
+                .get_merged()
+                .clone();
+        let document_target = document_config
+            .target
+            .clone()
+            .unwrap_or_else(|| target.clone());
+        for output in resolve_outputs(&document_config, &document_overrides, all) {
+            let source =
+                expected_output_file(&project_path, &document_config, &output, &document_target);
+            let output_key = document_output_key(document.as_deref(), &output);
+            let kind = PandocOutputKind::from_requested(Some(&output)).ok();
+            if kind == Some(PandocOutputKind::HtmlSite) {
+                artifacts.extend(copy_site_artifacts(
+                    &project_path,
+                    &source,
+                    publish_dir,
+                    &output_key,
+                )?);
+                continue;
+            }
+            artifacts.push(copy_artifact(
+                &project_path,
+                &source,
+                publish_dir,
+                &output_key,
+            )?);
+            if kind.is_some_and(PandocOutputKind::extracts_media) {
+                // Extracted figures live beside the artifact and ship with it.
+                let media_dir = media_dir(&source);
+                if media_dir.is_dir() {
+                    artifacts.extend(copy_directory_artifacts(
+                        &project_path,
+                        &media_dir,
+                        publish_dir,
+                        &format!("{output_key}-media"),
+                    )?);
+                }
+                continue;
+            }
+            if kind != Some(PandocOutputKind::Html) {
+                continue;
+            }
+            if let Some(search_index) = copy_optional_sidecar(
+                &project_path,
+                &source.with_file_name(page_index_name(&document_target)),
+                publish_dir,
+                &format!("{output_key}-search"),
+            )? {
+                artifacts.push(search_index);
+            }
+        }
     }
 
     if let Some(lock_artifact) = copy_optional_sidecar(
@@ -332,7 +396,7 @@ fn verify_published_release(
 
     let mut expected_files = BTreeSet::from(["omnidoc-publish.json".to_string()]);
     for artifact in &manifest.artifacts {
-        if !safe_publish_file_name(&artifact.destination) {
+        if !safe_publish_destination(&artifact.destination) {
             verification.errors.push(format!(
                 "unsafe publish artifact destination: {}",
                 artifact.destination
@@ -390,22 +454,36 @@ fn verify_published_release(
         verification.checked_artifacts += 1;
     }
 
-    match fs::read_dir(&publish_dir) {
-        Ok(entries) => {
-            for entry in entries.flatten() {
-                let name = entry.file_name().to_string_lossy().to_string();
-                if !expected_files.contains(&name) {
-                    verification
-                        .errors
-                        .push(format!("unexpected file in published release: {name}"));
-                }
-            }
-        }
-        Err(error) => verification.errors.push(format!(
+    if let Err(error) = fs::read_dir(&publish_dir) {
+        verification.errors.push(format!(
             "cannot inspect publish directory {}: {}",
             publish_dir.display(),
             error
-        )),
+        ));
+    } else {
+        // Site outputs publish nested pages, so walk the whole release and
+        // compare files by their `/`-separated destination.
+        for entry in WalkDir::new(&publish_dir)
+            .min_depth(1)
+            .sort_by_file_name()
+            .into_iter()
+            .flatten()
+        {
+            if entry.file_type().is_dir() {
+                continue;
+            }
+            let name = entry
+                .path()
+                .strip_prefix(&publish_dir)
+                .unwrap_or(entry.path())
+                .to_string_lossy()
+                .replace('\\', "/");
+            if !expected_files.contains(&name) {
+                verification
+                    .errors
+                    .push(format!("unexpected file in published release: {name}"));
+            }
+        }
     }
     verification.valid = verification.errors.is_empty();
     finish_verification(verification, json)
@@ -420,6 +498,10 @@ fn safe_publish_file_name(value: &str) -> bool {
         && Path::new(value).components().count() == 1
 }
 
+fn safe_publish_destination(value: &str) -> bool {
+    !value.is_empty() && value.split('/').all(safe_publish_file_name)
+}
+
 fn safe_publish_source(value: &str) -> bool {
     !value.is_empty()
         && !value.chars().any(|ch| matches!(ch, '\\' | ':'))
@@ -502,6 +584,65 @@ fn copy_artifact(
     })
 }
 
+/// 发布 `html-site` 输出：站点目录中的每个文件都作为独立产物记录
+fn copy_site_artifacts(
+    project_path: &Path,
+    index: &Path,
+    publish_dir: &Path,
+    output: &str,
+) -> Result<Vec<PublishArtifact>> {
+    let site_dir = index.parent().filter(|dir| dir.is_dir()).ok_or_else(|| {
+        OmniDocError::Project(format!("Publish artifact not found: {}", index.display()))
+    })?;
+    copy_directory_artifacts(project_path, site_dir, publish_dir, output)
+}
+
+/// 复制整个目录，保留目录名和内部相对路径
+fn copy_directory_artifacts(
+    project_path: &Path,
+    directory: &Path,
+    publish_dir: &Path,
+    output: &str,
+) -> Result<Vec<PublishArtifact>> {
+    let directory_name = directory
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "output".to_string());
+    let mut artifacts = Vec::new();
+    for entry in WalkDir::new(directory).sort_by_file_name() {
+        let entry = entry.map_err(|error| OmniDocError::Other(error.to_string()))?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let relative = entry
+            .path()
+            .strip_prefix(directory)
+            .unwrap_or(entry.path())
+            .to_string_lossy()
+            .replace('\\', "/");
+        let destination_name = format!("{directory_name}/{relative}");
+        let destination = publish_dir.join(&destination_name);
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(entry.path(), &destination)?;
+        let source_label = entry
+            .path()
+            .strip_prefix(project_path)
+            .unwrap_or(entry.path())
+            .to_string_lossy()
+            .replace('\\', "/");
+        artifacts.push(PublishArtifact {
+            output: output.to_string(),
+            source: source_label,
+            destination: destination_name,
+            bytes: fs::metadata(&destination)?.len(),
+            digest: content_digest(&destination)?,
+        });
+    }
+    Ok(artifacts)
+}
+
 fn write_embedded_artifact(
     content: &str,
     publish_dir: &Path,
@@ -545,11 +686,20 @@ fn sanitize_path_part(value: &str) -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::sanitize_path_part;
+    use super::{safe_publish_destination, sanitize_path_part};
 
     #[test]
     fn sanitizes_publish_tag() {
         assert_eq!(sanitize_path_part("v1/report"), "v1-report");
         assert_eq!(sanitize_path_part(""), "release");
     }
+
+    #[test]
+    fn site_destinations_may_nest_but_not_escape() {
+        assert!(safe_publish_destination("guide.pdf"));
+        assert!(safe_publish_destination("guide-site/assets/01-base.css"));
+        assert!(!safe_publish_destination("guide-site/../secret"));
+        assert!(!safe_publish_destination("/etc/passwd"));
+        assert!(!safe_publish_destination("guide-site//index.html"));
+    }
 }
@@ -1,13 +1,17 @@
 use crate::build::executor::{BuildExecutor, LatexEnginePreference, ResolvedLatexEngine};
 use crate::build::pipeline::{detect_project_type, ProjectType};
-use crate::cli::handlers::build::{build_project_outputs, BuildRunOptions};
+use crate::cli::handlers::build::{
+    build_project_outputs, configured_lock_targets, BuildRunOptions,
+};
 use crate::cli::handlers::common::{create_config_manager, create_config_manager_default};
 use crate::cli::handlers::lib::library_diagnostic;
 use crate::cli::handlers::theme::theme_diagnostic;
 use crate::config::{CliOverrides, MergedConfig};
 use crate::error::{OmniDocError, Result};
 use crate::extensions::{acquire_extension_store_read_locks, ExtensionStoreReadLocks};
-use crate::project_tools;
+use crate::index::IndexProgram;
+use crate::links::{self, LinkState};
+use crate::project_tools::{self, IssueSeverity};
 use crate::utils::path;
 use serde::Serialize;
 use std::path::Path;
@@ -49,6 +53,7 @@ pub fn handle_doctor(
             "epub" | "epub2" | "epub3"
         )
     });
+    let typst_pdf = has_pdf && !entry_is_latex && executor.uses_typst_pdf_engine();
     let engine_preference = if entry_is_latex {
         LatexEnginePreference::Latex
     } else {
@@ -59,7 +64,9 @@ pub fn handle_doctor(
         checks.push(doctor_tool(&executor, "pandoc", "pandoc"));
         checks.push(doctor_tool(&executor, "pandoc-crossref", "pandoc-crossref"));
     }
-    if has_pdf {
+    if typst_pdf {
+        checks.push(doctor_typst_engine(&executor));
+    } else if has_pdf {
         let (check, engine) = doctor_latex_engine(&executor, engine_preference);
         checks.push(check);
         resolved_pdf_engine = engine;
@@ -75,6 +82,17 @@ pub fn handle_doctor(
     if has_epub {
         checks.push(doctor_tool(&executor, "epubcheck", "epubcheck"));
     }
+    if !config.spellcheck_dictionaries.is_empty() {
+        checks.push(doctor_tool(&executor, "hunspell", "hunspell"));
+    }
+    if config.index_enabled && has_pdf && !typst_pdf && !entry_is_latex {
+        let lang = project_tools::document_lang(&project_path, &config);
+        if let Some(program) =
+            IndexProgram::resolve(config.index_program.as_deref(), lang.as_deref())
+        {
+            checks.push(doctor_tool(&executor, program.tool(), program.tool()));
+        }
+    }
 
     if let Some(lib_path) = &config.lib_path {
         let (ok, detail) = library_diagnostic(Path::new(lib_path));
@@ -94,7 +112,12 @@ pub fn handle_doctor(
         let tectonic = resolved_pdf_engine
             .as_ref()
             .is_some_and(ResolvedLatexEngine::is_tectonic);
-        let (ok, detail) = theme_diagnostic(&project_path, &config, has_pdf, has_pdf && !tectonic);
+        let (ok, detail) = theme_diagnostic(
+            &project_path,
+            &config,
+            has_pdf,
+            has_pdf && !tectonic && !typst_pdf,
+        );
         checks.push(DoctorCheck {
             name: format!("theme:{theme}"),
             ok,
@@ -191,6 +214,40 @@ fn doctor_latex_engine(
     }
 }
 
+fn doctor_typst_engine(executor: &BuildExecutor) -> DoctorCheck {
+    match executor.resolve_typst() {
+        Ok(path) => {
+            let version = Command::new(&path)
+                .arg("--version")
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .and_then(|output| {
+                    String::from_utf8_lossy(&output.stdout)
+                        .lines()
+                        .next()
+                        .map(str::trim)
+                        .filter(|line| !line.is_empty())
+                        .map(str::to_string)
+                });
+            DoctorCheck {
+                name: "pdf-engine".to_string(),
+                ok: true,
+                detail: format!(
+                    "{} [typst; {}]",
+                    path,
+                    version.unwrap_or_else(|| "version unavailable".to_string())
+                ),
+            }
+        }
+        Err(error) => DoctorCheck {
+            name: "pdf-engine".to_string(),
+            ok: false,
+            detail: error.to_string(),
+        },
+    }
+}
+
 fn doctor_tool(executor: &BuildExecutor, key: &str, name: &str) -> DoctorCheck {
     match executor.check_tool(key) {
         Ok(path) => {
@@ -242,10 +299,7 @@ pub fn handle_config_validate(path: Option<String>) -> Result<()> {
 pub fn handle_lint(path: Option<String>, strict: bool) -> Result<()> {
     let project_path = path::determine_project_root(path)?;
     let config_manager = create_config_manager_default(Some(&project_path))?;
-    let config = config_manager.get_merged();
-    let _extension_locks = configured_extension_locks(&project_path, config, "lint project")?;
-    let mut issues = project_tools::validate_config(&project_path, config);
-    issues.extend(project_tools::lint_project(&project_path));
+    let issues = lint_issues(&project_path, config_manager.get_merged())?;
     project_tools::print_issues(&issues);
     if (strict && project_tools::has_warnings_or_errors(&issues))
         || project_tools::has_errors(&issues)
@@ -255,6 +309,108 @@ pub fn handle_lint(path: Option<String>, strict: bool) -> Result<()> {
     Ok(())
 }
 
+/// Configuration, source and variant issues reported by `check lint`.
+pub(crate) fn lint_issues(
+    project_path: &Path,
+    config: &MergedConfig,
+) -> Result<Vec<project_tools::ProjectIssue>> {
+    let _extension_locks = configured_extension_locks(project_path, config, "lint project")?;
+    let mut issues = project_tools::validate_config(project_path, config);
+    issues.extend(project_tools::lint_project(project_path, config));
+    issues.extend(project_tools::lint_variants(
+        project_path,
+        config,
+        &config.variants,
+    ));
+    issues.extend(project_tools::lint_terms(project_path, config));
+    let document_issues = |config: &MergedConfig| {
+        let mut issues = project_tools::lint_crossrefs(project_path, config);
+        issues.extend(project_tools::lint_citations(project_path, config));
+        issues.extend(project_tools::lint_glossary(project_path, config));
+        issues.extend(project_tools::lint_index(project_path, config));
+        issues
+    };
+    if config.document.is_some() || config.documents.is_empty() {
+        issues.extend(document_issues(config));
+    } else {
+        // Labels, citations, glossary and index terms resolve per document; chapters
+        // shared between documents would otherwise report the same issue once
+        // per document.
+        for document in &config.documents {
+            let overrides = CliOverrides::new().with_document(Some(document.clone()));
+            let document_config = create_config_manager(Some(project_path), overrides)?;
+            for issue in document_issues(document_config.get_merged()) {
+                if !issues.contains(&issue) {
+                    issues.push(issue);
+                }
+            }
+        }
+    }
+    Ok(issues)
+}
+
+pub fn handle_links(path: Option<String>, json: bool, no_cache: bool) -> Result<()> {
+    let project_path = path::determine_project_root(path)?;
+    let config_manager = create_config_manager_default(Some(&project_path))?;
+    let config = config_manager.get_merged();
+    let issues = project_tools::validate_config(&project_path, config);
+    if project_tools::has_errors(&issues) {
+        project_tools::print_issues(&issues);
+        return Err(OmniDocError::Config(
+            "configuration validation failed".to_string(),
+        ));
+    }
+    let results = links::check_links(&project_path, config, !no_cache)?;
+    let failed = results.iter().filter(|result| result.failed()).count();
+
+    if json {
+        let content = serde_json::to_string_pretty(&results)
+            .map_err(|err| OmniDocError::Other(err.to_string()))?;
+        println!("{}", content);
+    } else {
+        let mut issues = Vec::new();
+        for result in &results {
+            let (severity, label) = match result.state {
+                LinkState::Broken | LinkState::Error => (IssueSeverity::Error, "Broken link"),
+                LinkState::Allowed => (IssueSeverity::Warning, "Allowed broken link"),
+                LinkState::Ok | LinkState::Ignored => continue,
+            };
+            for location in &result.locations {
+                issues.push(project_tools::ProjectIssue {
+                    severity: severity.clone(),
+                    message: format!("{label} {} ({})", result.url, result.detail()),
+                    path: Some(location.path.clone()),
+                    line: Some(location.line),
+                    column: None,
+                    rule: None,
+                });
+            }
+        }
+        project_tools::print_issues(&issues);
+        let count = |state: LinkState| {
+            results
+                .iter()
+                .filter(|result| result.state == state)
+                .count()
+        };
+        println!(
+            "{} link(s): {} ok, {} broken, {} allowed, {} ignored",
+            results.len(),
+            count(LinkState::Ok),
+            failed,
+            count(LinkState::Allowed),
+            count(LinkState::Ignored)
+        );
+    }
+
+    if failed > 0 {
+        return Err(OmniDocError::Project(format!(
+            "link check failed: {failed} broken link(s)"
+        )));
+    }
+    Ok(())
+}
+
 pub fn handle_deps(path: Option<String>, json: bool) -> Result<()> {
     let project_path = path::determine_project_root(path)?;
     let config_manager = create_config_manager_default(Some(&project_path))?;
@@ -289,9 +445,18 @@ pub fn handle_deps(path: Option<String>, json: bool) -> Result<()> {
     Ok(())
 }
 
-pub fn handle_ci(path: Option<String>, outputs: Vec<String>) -> Result<()> {
+pub fn handle_ci(
+    path: Option<String>,
+    document: Option<String>,
+    profile: Option<String>,
+    variant: Option<String>,
+    outputs: Vec<String>,
+) -> Result<()> {
     let project_path = path::determine_project_root(path)?;
     let cli_overrides = CliOverrides::new()
+        .with_document(document)
+        .with_profile(profile)
+        .with_variant(variant)
         .with_outputs(outputs)
         .with_latex_backend(Some("latexmk".to_string()));
     build_project_outputs(
@@ -303,17 +468,32 @@ pub fn handle_ci(path: Option<String>, outputs: Vec<String>) -> Result<()> {
             report: true,
             write_lock: true,
             strict: true,
+            ..BuildRunOptions::default()
         },
         true,
     )
 }
 
-pub fn handle_lock(path: Option<String>, check: bool, update: bool) -> Result<()> {
+pub fn handle_lock(
+    path: Option<String>,
+    document: Option<String>,
+    profile: Option<String>,
+    variant: Option<String>,
+    check: bool,
+    update: bool,
+) -> Result<()> {
     let project_path = path::determine_project_root(path)?;
     let _project_lock = (!check)
         .then(|| project_tools::acquire_project_write_lock(&project_path, "update lock file"))
         .transpose()?;
-    let base_manager = create_config_manager_default(Some(&project_path))?;
+    // Validates `--doc` before anything is read or written.
+    let base_manager = create_config_manager(
+        Some(&project_path),
+        CliOverrides::new()
+            .with_document(document.clone())
+            .with_profile(profile.clone())
+            .with_variant(variant.clone()),
+    )?;
     let base_config = base_manager.get_merged();
     let _extension_locks = configured_extension_locks(
         &project_path,
@@ -324,19 +504,9 @@ pub fn handle_lock(path: Option<String>, check: bool, update: bool) -> Result<()
             "write the project lock"
         },
     )?;
-    let outputs = if base_config.outputs.is_empty() {
-        vec![base_config.to.clone().unwrap_or_else(|| "pdf".to_string())]
-    } else {
-        base_config.outputs.clone()
-    };
-    let targets = outputs
+    let targets = configured_lock_targets(&project_path, profile.as_deref(), variant.as_deref())?
         .into_iter()
-        .map(|output| {
-            let manager = create_config_manager(
-                Some(&project_path),
-                CliOverrides::new().with_to(Some(output.clone())),
-            )?;
-            let config = manager.get_merged().clone();
+        .map(|(output, config)| {
             let issues = project_tools::validate_config(&project_path, &config);
             if project_tools::has_errors(&issues) {
                 project_tools::print_issues(&issues);
@@ -357,7 +527,11 @@ pub fn handle_lock(path: Option<String>, check: bool, update: bool) -> Result<()
         })
         .collect::<Vec<_>>();
     if check {
-        let status = project_tools::check_lock_targets(&project_path, &inputs)?;
+        let status = project_tools::check_document_lock_targets(
+            &project_path,
+            &inputs,
+            document.as_deref(),
+        )?;
         if status.up_to_date {
             println!("omnidoc.lock is up to date");
             return Ok(());
@@ -378,7 +552,7 @@ pub fn handle_lock(path: Option<String>, check: bool, update: bool) -> Result<()
     Ok(())
 }
 
-fn configured_extension_locks(
+pub(crate) fn configured_extension_locks(
     project_path: &Path,
     config: &MergedConfig,
     operation: &str,
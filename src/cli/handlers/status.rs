@@ -1,6 +1,7 @@
 use crate::cli::handlers::common::{
-    check_omnidoc_project, create_config_manager_default, print_json_error,
+    check_omnidoc_project, create_config_manager, print_json_error,
 };
+use crate::config::{CliOverrides, MergedConfig};
 use crate::doc::artifacts::{
     artifact_for_format, configured_output_formats, entry_path, output_directory,
     primary_output_format, target_name, ProjectArtifact,
@@ -26,7 +27,7 @@ impl PathStatus {
 }
 
 #[derive(Debug, Serialize)]
-struct ProjectStatus {
+pub(crate) struct ProjectStatus {
     schema_version: u32,
     project_root: String,
     config_file: PathStatus,
@@ -37,11 +38,26 @@ struct ProjectStatus {
     default_output: String,
     configured_outputs: Vec<String>,
     artifacts: Vec<ProjectArtifact>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    document: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    documents: Vec<DocumentStatus>,
+}
+
+/// One `[[documents]]` entry, listed when `--doc` does not select a document.
+#[derive(Debug, Serialize)]
+struct DocumentStatus {
+    name: String,
+    entry: PathStatus,
+    target: String,
+    default_output: String,
+    configured_outputs: Vec<String>,
+    artifacts: Vec<ProjectArtifact>,
 }
 
 /// Show the resolved project configuration and expected build artifacts.
-pub fn handle_status(path: Option<String>, json: bool) -> Result<()> {
-    let status = match resolve_status(path) {
+pub fn handle_status(path: Option<String>, document: Option<String>, json: bool) -> Result<()> {
+    let status = match resolve_status(path, document) {
         Ok(status) => status,
         Err(error) => {
             if json {
@@ -66,18 +82,30 @@ pub fn handle_status(path: Option<String>, json: bool) -> Result<()> {
     Ok(())
 }
 
-fn resolve_status(path: Option<String>) -> Result<ProjectStatus> {
+pub(crate) fn resolve_status(
+    path: Option<String>,
+    document: Option<String>,
+) -> Result<ProjectStatus> {
     let project_path = path::determine_project_root(path)?;
     check_omnidoc_project(&project_path)?;
 
-    let config_manager = create_config_manager_default(Some(&project_path))?;
+    let config_manager = create_config_manager(
+        Some(&project_path),
+        CliOverrides::new().with_document(document),
+    )?;
     let config = config_manager.get_merged();
     let entry = entry_path(&project_path, config);
     let outputs = configured_output_formats(config)?;
-    let artifacts = outputs
-        .iter()
-        .map(|output| artifact_for_format(&project_path, config, output))
-        .collect::<Result<Vec<_>>>()?;
+    let artifacts = output_artifacts(&project_path, config, &outputs)?;
+    let documents = if config.document.is_some() {
+        Vec::new()
+    } else {
+        config
+            .documents
+            .iter()
+            .map(|name| document_status(&project_path, name))
+            .collect::<Result<Vec<_>>>()?
+    };
     let source_format = config.from.clone().unwrap_or_else(|| {
         match entry.extension().and_then(|extension| extension.to_str()) {
             Some("tex") => "latex".to_string(),
@@ -95,11 +123,44 @@ fn resolve_status(path: Option<String>) -> Result<ProjectStatus> {
         default_output: primary_output_format(config)?,
         configured_outputs: outputs,
         artifacts,
+        document: config.document.clone(),
+        documents,
+    })
+}
+
+fn document_status(project_path: &Path, name: &str) -> Result<DocumentStatus> {
+    let config_manager = create_config_manager(
+        Some(project_path),
+        CliOverrides::new().with_document(Some(name.to_string())),
+    )?;
+    let config = config_manager.get_merged();
+    let outputs = configured_output_formats(config)?;
+    Ok(DocumentStatus {
+        name: name.to_string(),
+        entry: PathStatus::new(entry_path(project_path, config)),
+        target: target_name(project_path, config),
+        default_output: primary_output_format(config)?,
+        artifacts: output_artifacts(project_path, config, &outputs)?,
+        configured_outputs: outputs,
     })
 }
 
+fn output_artifacts(
+    project_path: &Path,
+    config: &MergedConfig,
+    outputs: &[String],
+) -> Result<Vec<ProjectArtifact>> {
+    outputs
+        .iter()
+        .map(|output| artifact_for_format(project_path, config, output))
+        .collect()
+}
+
 fn print_human_status(project_path: &Path, status: &ProjectStatus) {
     println!("Project: {}", status.project_root);
+    if let Some(document) = &status.document {
+        println!("Document: {}", document);
+    }
     println!(
         "Config:  {} ({})",
         display_path(project_path, &status.config_file.path),
@@ -118,13 +179,31 @@ fn print_human_status(project_path: &Path, status: &ProjectStatus) {
         status.default_output
     );
     println!("Artifacts:");
-    for artifact in &status.artifacts {
+    print_artifacts(project_path, &status.artifacts, "  ");
+    if !status.documents.is_empty() {
+        println!("Documents:");
+    }
+    for document in &status.documents {
+        println!(
+            "  {} -> {} ({}, {})",
+            document.name,
+            document.target,
+            display_path(project_path, &document.entry.path),
+            readiness(document.entry.exists)
+        );
+        print_artifacts(project_path, &document.artifacts, "    ");
+    }
+}
+
+fn print_artifacts(project_path: &Path, artifacts: &[ProjectArtifact], indent: &str) {
+    for artifact in artifacts {
         let detail = artifact
             .bytes
             .map(format_bytes)
             .unwrap_or_else(|| "missing".to_string());
         println!(
-            "  {:<6} {:<8} {}",
+            "{}{:<6} {:<8} {}",
+            indent,
             artifact.format,
             detail,
             display_path(project_path, &artifact.path)
@@ -1,13 +1,16 @@
 use crate::cli::handlers::build::{
     build_cli_overrides, build_project_outputs, resolve_outputs, BuildRunOptions,
 };
-use crate::cli::handlers::common::{check_omnidoc_project, create_config_manager};
+use crate::cli::handlers::common::{
+    check_omnidoc_project, create_config_manager, selected_documents,
+};
 use crate::config::MergedConfig;
-use crate::doc::artifacts::{artifact_for_format, output_directory};
+use crate::doc::artifacts::{artifact_for_format, output_directory, ProjectArtifact};
 use crate::error::{OmniDocError, Result};
 use crate::extensions::{
     acquire_extension_store_read_locks, extension_store_roots, plugin_trust_path,
 };
+use crate::preview::PreviewServer;
 use crate::project_tools;
 use crate::terminal;
 use crate::utils::directories::data_local_dir;
@@ -18,18 +21,27 @@ use std::path::{Path, PathBuf};
 use std::sync::mpsc;
 use std::time::{Duration, Instant};
 
+/// Outputs a browser can display through `watch --serve`.
+const PREVIEW_FORMATS: &[&str] = &["html", "html-site", "revealjs", "pdf"];
+
 /// Handle the 'watch' command using the notify backend.
 #[allow(clippy::too_many_arguments)]
 pub fn handle_watch(
     path: Option<String>,
+    document: Option<String>,
+    profile: Option<String>,
+    variant: Option<String>,
     to: Option<String>,
     all: bool,
     outputs: Vec<String>,
     pdf_engine: Option<String>,
     latex_backend: Option<String>,
     max_latex_passes: Option<usize>,
+    jobs: Option<usize>,
     debounce_ms: u64,
     once: bool,
+    serve: bool,
+    port: Option<u16>,
     force: bool,
     report: bool,
     strict: bool,
@@ -44,13 +56,18 @@ pub fn handle_watch(
         pdf_engine,
         latex_backend,
         max_latex_passes,
+        jobs,
         verbose,
-    );
+    )
+    .with_document(document)
+    .with_profile(profile)
+    .with_variant(variant);
     let run_options = BuildRunOptions {
         force,
         report,
         write_lock: false,
         strict,
+        ..BuildRunOptions::default()
     };
     let mut watch_context = WatchContext::load(&project_path, &cli_overrides, all)?;
 
@@ -58,6 +75,16 @@ pub fn handle_watch(
     if once {
         return run_watch_build(&project_path, cli_overrides, all, run_options, verbose);
     }
+    let preview = if serve {
+        Some(start_preview(
+            &project_path,
+            &cli_overrides,
+            port,
+            &watch_context,
+        )?)
+    } else {
+        None
+    };
 
     let (tx, rx) = mpsc::channel();
     let mut watcher = RecommendedWatcher::new(
@@ -82,9 +109,10 @@ pub fn handle_watch(
         run_options.clone(),
         verbose,
     );
-    if let Err(error) = initial_build {
-        terminal::print_error(&error);
+    if let Err(error) = &initial_build {
+        terminal::print_error(error);
     }
+    publish_build_outcome(preview.as_ref(), &initial_build);
     refresh_watch_context(
         &project_path,
         &cli_overrides,
@@ -124,15 +152,17 @@ pub fn handle_watch(
             let recursive_roots_to_rearm =
                 recursive_roots_requiring_rearm(&watch_context.filter, &pending);
             println!("Change detected: {}", changed.join(", "));
-            if let Err(error) = run_watch_build(
+            let rebuild = run_watch_build(
                 &project_path,
                 cli_overrides.clone(),
                 all,
                 run_options.clone(),
                 verbose,
-            ) {
-                terminal::print_error(&error);
+            );
+            if let Err(error) = &rebuild {
+                terminal::print_error(error);
             }
+            publish_build_outcome(preview.as_ref(), &rebuild);
             refresh_watch_context(
                 &project_path,
                 &cli_overrides,
@@ -160,6 +190,38 @@ fn run_watch_build(
     Ok(())
 }
 
+/// Serve the output directory and print where each previewable artifact is.
+fn start_preview(
+    project_path: &Path,
+    cli_overrides: &crate::config::CliOverrides,
+    port: Option<u16>,
+    watch_context: &WatchContext,
+) -> Result<PreviewServer> {
+    let config = create_config_manager(Some(project_path), cli_overrides.clone())?
+        .get_merged()
+        .clone();
+    let server = PreviewServer::start(&output_directory(project_path, &config), port.unwrap_or(0))?;
+    println!("Preview server: http://{}/", server.address());
+    for artifact in &watch_context.artifacts {
+        if !PREVIEW_FORMATS.contains(&artifact.format.as_str()) {
+            continue;
+        }
+        if let Some(url) = server.url_for(Path::new(&artifact.path)) {
+            println!("  {}: {}", artifact.format, url);
+        }
+    }
+    Ok(server)
+}
+
+/// Reload open preview pages, or show the failed build as an overlay.
+fn publish_build_outcome(preview: Option<&PreviewServer>, outcome: &Result<()>) {
+    match (preview, outcome) {
+        (Some(preview), Ok(())) => preview.notify_success(),
+        (Some(preview), Err(error)) => preview.notify_failure(terminal::render_error(error)),
+        (None, _) => {}
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 struct WatchRegistration {
     path: PathBuf,
@@ -169,6 +231,7 @@ struct WatchRegistration {
 struct WatchContext {
     filter: WatchFilter,
     registrations: BTreeSet<WatchRegistration>,
+    artifacts: Vec<ProjectArtifact>,
 }
 
 impl WatchContext {
@@ -180,41 +243,54 @@ impl WatchContext {
         let manager = create_config_manager(Some(project_path), cli_overrides.clone())?;
         let config = manager.get_merged().clone();
         let outputs = resolve_outputs(&config, cli_overrides, all);
-        let extensions_active = config.theme_name.is_some() || !config.plugins_enabled.is_empty();
-        let _extension_locks = extensions_active
-            .then(|| {
-                acquire_extension_store_read_locks(
+        // Every selected document rebuilds on a change, so watch the union of
+        // their dependency graphs.
+        let mut document_targets = Vec::new();
+        for document in selected_documents(&config, cli_overrides.document.clone()) {
+            let document_overrides = cli_overrides.clone().with_document(document);
+            let document_config =
+                create_config_manager(Some(project_path), document_overrides.clone())?
+                    .get_merged()
+                    .clone();
+            let document_outputs = resolve_outputs(&document_config, &document_overrides, all);
+            document_targets.push((document_config, document_outputs));
+        }
+        let mut extension_locks = Vec::new();
+        let mut extension_roots = Vec::new();
+        for (document_config, _) in &document_targets {
+            if document_config.theme_name.is_some() || !document_config.plugins_enabled.is_empty() {
+                extension_locks.push(acquire_extension_store_read_locks(
                     Some(project_path),
-                    &config,
+                    document_config,
                     "refresh watched extension inputs",
-                )
-            })
-            .transpose()?;
+                )?);
+                extension_roots.extend(extension_store_roots(Some(project_path), document_config)?);
+            }
+        }
 
         let mut tracked_files = BTreeSet::new();
         let mut resource_paths = BTreeSet::new();
-        for output in &outputs {
-            let mut output_config = config.clone();
-            output_config.to = Some(output.clone());
-            let graph = project_tools::dependency_graph(project_path, &output_config);
-            tracked_files.extend(
-                graph
-                    .files
-                    .into_iter()
-                    .map(|file| absolute_watch_path(project_path, Path::new(&file))),
-            );
-            resource_paths.extend(
-                graph
-                    .resources
-                    .into_iter()
-                    .map(|resource| absolute_watch_path(project_path, Path::new(&resource.path))),
-            );
+        let mut artifacts = Vec::new();
+        for (document_config, document_outputs) in &document_targets {
+            for output in document_outputs {
+                let mut output_config = document_config.clone();
+                output_config.to = Some(output.clone());
+                let graph = project_tools::dependency_graph(project_path, &output_config);
+                tracked_files.extend(
+                    graph
+                        .files
+                        .into_iter()
+                        .map(|file| absolute_watch_path(project_path, Path::new(&file))),
+                );
+                resource_paths.extend(
+                    graph.resources.into_iter().map(|resource| {
+                        absolute_watch_path(project_path, Path::new(&resource.path))
+                    }),
+                );
+                artifacts.push(artifact_for_format(project_path, document_config, output)?);
+            }
         }
 
-        let mut extension_roots = Vec::new();
-        if extensions_active {
-            extension_roots.extend(extension_store_roots(Some(project_path), &config)?);
-        }
         let mut external_roots = Vec::new();
         let library_root = configured_library_root(&config);
         if library_root.is_dir() {
@@ -236,7 +312,7 @@ impl WatchContext {
             }
         }
 
-        let filter = WatchFilter::with_dependencies(
+        let mut filter = WatchFilter::with_dependencies(
             project_path,
             &config,
             &outputs,
@@ -245,10 +321,20 @@ impl WatchContext {
             external_roots,
             external_files,
         )?;
+        // Document artifacts are named after each document's own target.
+        filter.ignored_files.extend(
+            artifacts
+                .iter()
+                .map(|artifact| normalize_path(artifact.path_buf())),
+        );
+        filter.ignored_files.sort();
+        filter.ignored_files.dedup();
+        drop(extension_locks);
         let registrations = watch_registrations(&filter);
         Ok(Self {
             filter,
             registrations,
+            artifacts,
         })
     }
 }
@@ -624,6 +710,7 @@ impl WatchFilter {
             ext.to_ascii_lowercase().as_str(),
             "md" | "markdown"
                 | "tex"
+                | "typ"
                 | "bib"
                 | "cls"
                 | "sty"
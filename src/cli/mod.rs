@@ -24,6 +24,7 @@ fn command_needs_library(command: &Commands) -> bool {
         command,
         Commands::Build { .. }
             | Commands::Watch { .. }
+            | Commands::Daemon { .. }
             | Commands::Publish { verify: false, .. }
             | Commands::Ci { .. }
             | Commands::Md2pdf { .. }
@@ -119,12 +120,16 @@ pub fn cli() -> Result<()> {
         }
         Commands::Build {
             path,
+            document,
+            profile,
+            variant,
             to,
             all,
             outputs,
             pdf_engine,
             latex_backend,
             max_latex_passes,
+            jobs,
             force,
             report,
             write_lock,
@@ -133,12 +138,16 @@ pub fn cli() -> Result<()> {
         } => {
             handle_build(
                 path,
+                document,
+                profile,
+                variant,
                 to,
                 all,
                 outputs,
                 pdf_engine,
                 latex_backend,
                 max_latex_passes,
+                jobs,
                 force,
                 report,
                 write_lock,
@@ -148,14 +157,20 @@ pub fn cli() -> Result<()> {
         }
         Commands::Watch {
             path,
+            document,
+            profile,
+            variant,
             to,
             all,
             outputs,
             pdf_engine,
             latex_backend,
             max_latex_passes,
+            jobs,
             debounce_ms,
             once,
+            serve,
+            port,
             force,
             report,
             strict,
@@ -163,28 +178,48 @@ pub fn cli() -> Result<()> {
         } => {
             handle_watch(
                 path,
+                document,
+                profile,
+                variant,
                 to,
                 all,
                 outputs,
                 pdf_engine,
                 latex_backend,
                 max_latex_passes,
+                jobs,
                 debounce_ms,
                 once,
+                serve,
+                port,
                 force,
                 report,
                 strict,
                 verbose,
             )?;
         }
+        Commands::Daemon {
+            path,
+            socket,
+            verbose,
+        } => {
+            handle_daemon(path, socket, verbose)?;
+        }
+        Commands::Lsp { path, stdio: _ } => {
+            handle_lsp(path)?;
+        }
         Commands::Publish {
             path,
+            document,
+            profile,
+            variant,
             to,
             all,
             outputs,
             pdf_engine,
             latex_backend,
             max_latex_passes,
+            jobs,
             dist_dir,
             tag,
             no_build,
@@ -196,12 +231,16 @@ pub fn cli() -> Result<()> {
         } => {
             handle_publish(
                 path,
+                document,
+                profile,
+                variant,
                 to,
                 all,
                 outputs,
                 pdf_engine,
                 latex_backend,
                 max_latex_passes,
+                jobs,
                 dist_dir,
                 tag,
                 no_build,
@@ -232,26 +271,65 @@ pub fn cli() -> Result<()> {
         Commands::Deps { path, json } => {
             handle_deps(path, json)?;
         }
-        Commands::Ci { path, outputs } => {
-            handle_ci(path, outputs)?;
+        Commands::Ci {
+            path,
+            document,
+            profile,
+            variant,
+            outputs,
+        } => {
+            handle_ci(path, document, profile, variant, outputs)?;
         }
         Commands::Lock {
             path,
+            document,
+            profile,
+            variant,
             check,
             update,
         } => {
-            handle_lock(path, check, update)?;
+            handle_lock(path, document, profile, variant, check, update)?;
         }
         Commands::Plugin { subcommand } => handle_plugin(subcommand)?,
-        Commands::Status { path, json } => {
-            handle_status(path, json)?;
+        Commands::Status {
+            path,
+            document,
+            json,
+        } => {
+            handle_status(path, document, json)?;
         }
         Commands::Open {
             path,
+            document,
+            variant,
             to,
             print_path,
         } => {
-            handle_open(path, to, print_path)?;
+            handle_open(path, document, variant, to, print_path)?;
+        }
+        Commands::Locate {
+            source,
+            pdf_page,
+            x,
+            y,
+            path,
+            document,
+            variant,
+            json,
+        } => {
+            let target = match (source, pdf_page, x, y) {
+                (Some(source), _, _, _) => LocateTarget::parse_source(&source)?,
+                (None, Some(page), Some(x), Some(y)) => LocateTarget::Pdf { page, x, y },
+                _ => {
+                    return Err(OmniDocError::Other(
+                        "locate needs FILE:LINE or --pdf-page with --x and --y".to_string(),
+                    ))
+                }
+            };
+            handle_locate(target, path, document, variant, json)?;
+        }
+        Commands::Cache { subcommand } => {
+            handle_cache(subcommand)?;
         }
         Commands::Clean {
             path,
@@ -386,14 +464,14 @@ pub fn cli() -> Result<()> {
             inputs,
             output,
         } => {
-            handle_md2pdf(lang, inputs, output)?;
+            handle_md2pdf(lang, inputs, output, None)?;
         }
         Commands::Md2html {
             inputs,
             output,
             css,
         } => {
-            handle_md2html(inputs, output, css)?;
+            handle_md2html(inputs, output, css, None)?;
         }
         Commands::Fmt {
             paths,
@@ -430,12 +508,26 @@ fn handle_check(subcommand: CheckSubcommand) -> Result<()> {
         CheckSubcommand::Config { path } => handle_config_validate(path),
         CheckSubcommand::Lint { path, strict } => handle_lint(path, strict),
         CheckSubcommand::Deps { path, json } => handle_deps(path, json),
+        CheckSubcommand::Links {
+            path,
+            json,
+            no_cache,
+        } => handle_links(path, json, no_cache),
         CheckSubcommand::Lock {
             path,
+            document,
+            profile,
+            variant,
             check,
             update,
-        } => handle_lock(path, check, update),
-        CheckSubcommand::Ci { path, outputs } => handle_ci(path, outputs),
+        } => handle_lock(path, document, profile, variant, check, update),
+        CheckSubcommand::Ci {
+            path,
+            document,
+            profile,
+            variant,
+            outputs,
+        } => handle_ci(path, document, profile, variant, outputs),
     }
 }
 
@@ -445,11 +537,13 @@ fn handle_convert(subcommand: ConvertSubcommand) -> Result<()> {
             lang,
             inputs,
             output,
-        } => handle_md2pdf(lang, inputs, output),
+            profile,
+        } => handle_md2pdf(lang, inputs, output, profile),
         ConvertSubcommand::Html {
             inputs,
             output,
+            profile,
             css,
-        } => handle_md2html(inputs, output, css),
+        } => handle_md2html(inputs, output, css, profile),
     }
 }
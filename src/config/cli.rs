@@ -12,6 +12,10 @@ pub struct CliOverrides {
     pub verbose: Option<bool>,
     pub latex_backend: Option<String>,
     pub max_latex_passes: Option<usize>,
+    pub jobs: Option<usize>,
+    pub document: Option<String>,
+    pub profile: Option<String>,
+    pub variant: Option<String>,
     pub tool_paths: HashMap<String, Option<String>>,
 }
 
@@ -72,6 +76,26 @@ impl CliOverrides {
         self
     }
 
+    pub fn with_jobs(mut self, jobs: Option<usize>) -> Self {
+        self.jobs = jobs;
+        self
+    }
+
+    pub fn with_document(mut self, document: Option<String>) -> Self {
+        self.document = document;
+        self
+    }
+
+    pub fn with_profile(mut self, profile: Option<String>) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    pub fn with_variant(mut self, variant: Option<String>) -> Self {
+        self.variant = variant;
+        self
+    }
+
     pub fn with_tool_path(mut self, tool: String, path: Option<String>) -> Self {
         self.tool_paths.insert(tool, path);
         self
@@ -7,6 +7,7 @@ use std::env::var;
 use std::path::{Path, PathBuf};
 
 /// 全局配置管理器（~/.config/omnidoc.toml）
+#[derive(Clone)]
 pub struct GlobalConfig {
     path: PathBuf,
     config: Option<ConfigSchema>,
@@ -15,20 +16,7 @@ pub struct GlobalConfig {
 impl GlobalConfig {
     /// 加载全局配置
     pub fn load() -> Result<Self> {
-        let config_local_dir = match config_local_dir() {
-            None => {
-                let home_path = var("HOME").map_err(|_| {
-                    OmniDocError::Config("HOME environment variable not found".to_string())
-                })?;
-                let mut conf_dir = PathBuf::from(home_path);
-                conf_dir.push(config_consts::CONFIG_DIR);
-                let _ = fs::create_dir_all(&conf_dir);
-                conf_dir
-            }
-            Some(cld) => cld,
-        };
-
-        let config_file = config_local_dir.join(config_consts::OMNIDOC_CONFIG_FILE);
+        let config_file = Self::config_file()?;
 
         let config = if fs::exists(&config_file) {
             let content = fs::read_to_string(&config_file)?;
@@ -51,6 +39,23 @@ impl GlobalConfig {
         })
     }
 
+    /// 全局配置文件路径（不要求文件存在）
+    pub fn config_file() -> Result<PathBuf> {
+        let config_local_dir = match config_local_dir() {
+            None => {
+                let home_path = var("HOME").map_err(|_| {
+                    OmniDocError::Config("HOME environment variable not found".to_string())
+                })?;
+                let mut conf_dir = PathBuf::from(home_path);
+                conf_dir.push(config_consts::CONFIG_DIR);
+                let _ = fs::create_dir_all(&conf_dir);
+                conf_dir
+            }
+            Some(cld) => cld,
+        };
+        Ok(config_local_dir.join(config_consts::OMNIDOC_CONFIG_FILE))
+    }
+
     /// 创建默认全局配置
     pub fn create_default(path: &Path) -> Result<()> {
         let config = Self::default_schema()?;
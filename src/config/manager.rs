@@ -2,8 +2,9 @@ use crate::config::cli::CliOverrides;
 use crate::config::global::GlobalConfig;
 use crate::config::project::ProjectConfig;
 use crate::config::schema::*;
-use crate::error::Result;
+use crate::error::{OmniDocError, Result};
 use crate::utils::directories::data_local_dir;
+use crate::warm;
 use serde::Serialize;
 use std::collections::{BTreeMap, HashMap};
 use std::env;
@@ -11,6 +12,7 @@ use std::path::{Path, PathBuf};
 
 /// 统一配置管理器
 /// 处理配置合并：命令行 > 项目配置 > 全局配置
+#[derive(Clone)]
 pub struct ConfigManager {
     global: GlobalConfig,
     project: Option<ProjectConfig>,
@@ -33,10 +35,23 @@ pub struct MergedConfig {
     pub to: Option<String>,
     pub outputs: Vec<String>,
     pub target: Option<String>,
+    /// Document selected with `--doc`, if the project declares `[[documents]]`
+    pub document: Option<String>,
+    /// Names of every declared `[[documents]]` entry, in file order
+    pub documents: Vec<String>,
+    /// Build profile applied with `--profile`
+    pub profile: Option<String>,
+    /// Audience variant selected with `--variant`
+    pub variant: Option<String>,
+    /// Audiences shown by the selected variant
+    pub variant_audiences: Vec<String>,
+    /// Every declared `[variants]` entry and its audiences
+    pub variants: BTreeMap<String, Vec<String>>,
     pub metadata_file: Option<String>,
     pub verbose: bool,
     pub latex_backend: String,
     pub max_latex_passes: usize,
+    pub jobs: usize,
     pub figure_paths: Vec<String>,
     pub figure_output: Option<String>,
     pub theme_name: Option<String>,
@@ -58,6 +73,10 @@ pub struct MergedConfig {
     pub pandoc_template: Option<String>,
     pub pandoc_html_template: Option<String>,
     pub pandoc_latex_template: Option<String>,
+    pub pandoc_typst_template: Option<String>,
+    pub pandoc_site_template: Option<String>,
+    pub pandoc_site_split_level: Option<usize>,
+    pub pandoc_search: Option<bool>,
     pub pandoc_epub_template: Option<String>,
     pub pandoc_data_dir: Option<String>,
     pub pandoc_resource_path: Vec<String>,
@@ -71,6 +90,44 @@ pub struct MergedConfig {
     pub tectonic_only_cached: bool,
     pub tectonic_shell_escape: bool,
     pub tectonic_search_paths: Vec<String>,
+    /// Stamp front matter metadata into PDF outputs
+    pub pdf_metadata: bool,
+    /// PDF conformance profile such as `pdf-a-2b`
+    pub pdf_profile: Option<String>,
+    /// Watermark with the selected variant's fields applied; `None` without text
+    pub watermark: Option<WatermarkSection>,
+    /// Shared cache directory or HTTP base URL (`[cache] shared`)
+    pub shared_cache: Option<String>,
+    /// Only restore from the shared cache (`[cache] read_only`)
+    pub shared_cache_read_only: bool,
+    /// Terminology dictionary file (`[terms] dictionary`)
+    pub terms_dictionary: Option<String>,
+    /// Hunspell dictionaries for spell checking (`[terms] spellcheck`)
+    pub spellcheck_dictionaries: Vec<String>,
+    /// Glossary definition files, relative to the project (`[glossary] files`)
+    pub glossary_files: Vec<String>,
+    /// Title of the generated glossary chapter (`[glossary] title`)
+    pub glossary_title: Option<String>,
+    /// Whether Markdown builds generate a back-of-book index (`[index]`)
+    pub index_enabled: bool,
+    /// Title of the generated index chapter (`[index] title`)
+    pub index_title: Option<String>,
+    /// PDF index processor (`[index] program`)
+    pub index_program: Option<String>,
+    /// URLs `check links` skips (`[links] ignore`)
+    pub links_ignore: Vec<String>,
+    /// URLs whose link check failures are tolerated (`[links] allow`)
+    pub links_allow: Vec<String>,
+    /// Link check request timeout in seconds (`[links] timeout`)
+    pub links_timeout: Option<u64>,
+    /// Concurrent link check requests (`[links] concurrency`)
+    pub links_concurrency: Option<usize>,
+    /// Link check requests per second per host (`[links] rate_limit`)
+    pub links_rate_limit: Option<f64>,
+    /// Hours a cached link check result stays valid (`[links] cache_ttl`)
+    pub links_cache_ttl: Option<u64>,
+    /// Severity overrides per lint rule id (`[lint.rules]`)
+    pub lint_rules: BTreeMap<String, String>,
     pub tool_paths: HashMap<String, Option<String>>,
     pub template_dir: Option<String>,
     pub paths: PathConfig,
@@ -79,8 +136,41 @@ pub struct MergedConfig {
 impl ConfigManager {
     /// 创建配置管理器
     pub fn new(project_path: Option<&Path>, cli_overrides: CliOverrides) -> Result<Self> {
+        let start = match project_path {
+            Some(path) => path.to_path_buf(),
+            None => env::current_dir().map_err(OmniDocError::Io)?,
+        };
+        let key = warm_config_key(&start, &cli_overrides);
+        warm::CONFIGS.get_or_load(
+            &key,
+            || {
+                let mut fingerprint = warm::Fingerprint::new();
+                if let Ok(global) = GlobalConfig::config_file() {
+                    fingerprint = fingerprint.with_file(&global);
+                }
+                warm::project_config_candidates(&start)
+                    .iter()
+                    .fold(fingerprint, |fingerprint, candidate| {
+                        fingerprint.with_file(candidate)
+                    })
+            },
+            || Self::load(project_path, cli_overrides),
+        )
+    }
+
+    fn load(project_path: Option<&Path>, cli_overrides: CliOverrides) -> Result<Self> {
         let global = GlobalConfig::load()?;
-        let project = ProjectConfig::load_from_path(project_path)?;
+        let mut project = ProjectConfig::load_from_path(project_path)?;
+        if let Some(profile) = cli_overrides.profile.as_deref() {
+            project
+                .as_mut()
+                .ok_or_else(|| {
+                    OmniDocError::Config(format!(
+                        "Unknown profile '{profile}'; no .omnidoc.toml declares [profiles]"
+                    ))
+                })?
+                .apply_profile(profile)?;
+        }
 
         let merged = Self::merge_configs(&global, project.as_ref(), &cli_overrides)?;
 
@@ -100,6 +190,53 @@ impl ConfigManager {
     ) -> Result<MergedConfig> {
         let global_config = global.get_config();
         let project_config = project.and_then(|p| p.get_config());
+        let declared_documents = project_config
+            .and_then(|c| c.project.as_ref())
+            .and_then(|p| p.documents.as_deref())
+            .unwrap_or_default();
+        validate_documents(declared_documents)?;
+        let documents = declared_documents
+            .iter()
+            .map(|document| document.name.clone())
+            .collect::<Vec<_>>();
+        let selected_document = cli
+            .document
+            .as_deref()
+            .map(|name| {
+                declared_documents
+                    .iter()
+                    .find(|document| document.name == name)
+                    .ok_or_else(|| unknown_document_error(name, &documents))
+            })
+            .transpose()?;
+        let document = selected_document.map(|document| document.name.clone());
+        let declared_variants = project_config
+            .and_then(|c| c.project.as_ref())
+            .and_then(|p| p.variants.as_ref());
+        let variants = declared_variants
+            .map(|variants| {
+                variants
+                    .iter()
+                    .map(|(name, variant)| (name.clone(), variant_audiences(name, variant)))
+                    .collect::<BTreeMap<_, _>>()
+            })
+            .unwrap_or_default();
+        validate_names("[variants]", variants.keys().map(String::as_str))?;
+        for (name, audiences) in &variants {
+            validate_names(
+                &format!("[variants.{name}] audience"),
+                audiences.iter().map(String::as_str),
+            )?;
+        }
+        let selected_variant = cli
+            .variant
+            .as_deref()
+            .map(|name| {
+                declared_variants
+                    .and_then(|variants| variants.get_key_value(name))
+                    .ok_or_else(|| unknown_variant_error(name, &variants))
+            })
+            .transpose()?;
 
         // 合并作者
         let author = cli
@@ -150,12 +287,16 @@ impl ConfigManager {
             .and_then(|e| e.texinputs.clone());
 
         // 合并项目配置
-        let entry = cli.entry.clone().or_else(|| {
-            project_config
-                .and_then(|c| c.project.as_ref())
-                .and_then(|p| p.project.as_ref())
-                .and_then(|p| p.entry.clone())
-        });
+        let entry = cli
+            .entry
+            .clone()
+            .or_else(|| selected_document.and_then(|d| d.entry.clone()))
+            .or_else(|| {
+                project_config
+                    .and_then(|c| c.project.as_ref())
+                    .and_then(|p| p.project.as_ref())
+                    .and_then(|p| p.entry.clone())
+            });
 
         let from = cli.from.clone().or_else(|| {
             project_config
@@ -164,22 +305,51 @@ impl ConfigManager {
                 .and_then(|p| p.from.clone())
         });
 
-        let to = cli.to.clone().or_else(|| {
-            project_config
-                .and_then(|c| c.project.as_ref())
-                .and_then(|p| p.project.as_ref())
-                .and_then(|p| p.to.clone())
-        });
+        let to = cli
+            .to
+            .clone()
+            .or_else(|| selected_document.and_then(|d| d.to.clone()))
+            .or_else(|| {
+                project_config
+                    .and_then(|c| c.project.as_ref())
+                    .and_then(|p| p.project.as_ref())
+                    .and_then(|p| p.to.clone())
+            });
 
-        let target = cli.target.clone().or_else(|| {
-            project_config
+        // A document never inherits project.target: sibling documents would
+        // otherwise overwrite each other's artifacts.
+        let target = cli.target.clone().or_else(|| match selected_document {
+            Some(document) => Some(
+                document
+                    .target
+                    .clone()
+                    .unwrap_or_else(|| document.name.clone()),
+            ),
+            None => project_config
                 .and_then(|c| c.project.as_ref())
                 .and_then(|p| p.project.as_ref())
-                .and_then(|p| p.target.clone())
+                .and_then(|p| p.target.clone()),
         });
+        // Variants never share an artifact with the base build either: the
+        // default target gains a `-<variant>` suffix.
+        let target = match selected_variant {
+            Some((name, variant)) if cli.target.is_none() => variant.target.clone().or_else(|| {
+                let base = target.clone().or_else(|| {
+                    project
+                        .and_then(|p| p.path().parent())
+                        .and_then(|dir| dir.file_name())
+                        .and_then(|dir| dir.to_str())
+                        .map(str::to_string)
+                })?;
+                Some(format!("{base}-{name}"))
+            }),
+            _ => target,
+        };
 
         let outputs = if !cli.outputs.is_empty() {
             cli.outputs.clone()
+        } else if let Some(outputs) = selected_document.and_then(|d| d.outputs.clone()) {
+            outputs
         } else {
             project_config
                 .and_then(|c| c.build.as_ref())
@@ -189,10 +359,14 @@ impl ConfigManager {
         };
 
         // 合并构建配置
-        let metadata_file = project_config
-            .and_then(|c| c.build.as_ref())
-            .and_then(|b| b.build.as_ref())
-            .and_then(|b| b.metadata_file.clone());
+        let metadata_file = selected_document
+            .and_then(|d| d.metadata_file.clone())
+            .or_else(|| {
+                project_config
+                    .and_then(|c| c.build.as_ref())
+                    .and_then(|b| b.build.as_ref())
+                    .and_then(|b| b.metadata_file.clone())
+            });
 
         let verbose = cli
             .verbose
@@ -225,6 +399,16 @@ impl ConfigManager {
             })
             .unwrap_or(5);
 
+        let jobs = cli
+            .jobs
+            .or_else(|| {
+                project_config
+                    .and_then(|c| c.build.as_ref())
+                    .and_then(|b| b.build.as_ref())
+                    .and_then(|b| b.jobs)
+            })
+            .unwrap_or(1);
+
         // 合并图片配置
         let figure_paths = project_config
             .and_then(|c| c.figure.as_ref())
@@ -243,7 +427,8 @@ impl ConfigManager {
         let global_theme = global_config
             .and_then(|config| config.theme.as_ref())
             .and_then(|theme| theme.theme.as_ref());
-        let selected_theme = project_theme.or(global_theme);
+        let document_theme = selected_document.and_then(|document| document.theme.as_ref());
+        let selected_theme = document_theme.or(project_theme).or(global_theme);
         let theme_name = selected_theme.and_then(|theme| theme.name.clone());
         let theme_version = selected_theme.and_then(|theme| theme.version.clone());
         let theme_compatibility = selected_theme.and_then(|theme| theme.compatibility.clone());
@@ -268,9 +453,18 @@ impl ConfigManager {
             .and_then(|p| p.pandoc.as_ref());
 
         let pandoc_toc = pandoc_config.and_then(|p| p.toc).unwrap_or(false);
-        let pandoc_options = pandoc_config
+        let mut pandoc_options = pandoc_config
             .and_then(|p| p.options.clone())
             .unwrap_or_default();
+        if let Some(metadata) = selected_document.and_then(|d| d.metadata.as_ref()) {
+            pandoc_options.extend(metadata.iter().map(|(key, value)| {
+                let value = match value {
+                    toml::Value::String(value) => value.clone(),
+                    value => value.to_string(),
+                };
+                format!("--metadata={key}:{value}")
+            }));
+        }
         let pandoc_format_options = pandoc_config
             .and_then(|p| p.format_options.clone())
             .unwrap_or_default()
@@ -291,6 +485,10 @@ impl ConfigManager {
         let pandoc_template = pandoc_config.and_then(|p| p.template.clone());
         let pandoc_html_template = pandoc_config.and_then(|p| p.html_template.clone());
         let pandoc_latex_template = pandoc_config.and_then(|p| p.latex_template.clone());
+        let pandoc_typst_template = pandoc_config.and_then(|p| p.typst_template.clone());
+        let pandoc_site_template = pandoc_config.and_then(|p| p.site_template.clone());
+        let pandoc_site_split_level = pandoc_config.and_then(|p| p.site_split_level);
+        let pandoc_search = pandoc_config.and_then(|p| p.search);
         let pandoc_epub_template = pandoc_config.and_then(|p| p.epub_template.clone());
         let pandoc_data_dir = pandoc_config.and_then(|p| p.data_dir.clone());
         let pandoc_resource_path = pandoc_config
@@ -327,6 +525,106 @@ impl ConfigManager {
             .or_else(|| global_tectonic.and_then(|config| config.search_paths.clone()))
             .unwrap_or_default();
 
+        let pdf_config = project_config
+            .and_then(|config| config.pdf.as_ref())
+            .and_then(|config| config.pdf.as_ref());
+        let pdf_metadata = pdf_config.and_then(|pdf| pdf.metadata).unwrap_or(true);
+        let pdf_profile = pdf_config.and_then(|pdf| pdf.profile.clone());
+
+        let project_watermark = project_config
+            .and_then(|config| config.watermark.as_ref())
+            .and_then(|config| config.watermark.clone())
+            .unwrap_or_default();
+        let variant_watermark = selected_variant
+            .and_then(|(_, variant)| variant.watermark.clone())
+            .unwrap_or_default();
+        let watermark = WatermarkSection {
+            text: variant_watermark.text.or(project_watermark.text),
+            color: variant_watermark.color.or(project_watermark.color),
+            opacity: variant_watermark.opacity.or(project_watermark.opacity),
+            pages: variant_watermark.pages.or(project_watermark.pages),
+        };
+        let watermark = watermark
+            .text
+            .as_deref()
+            .is_some_and(|text| !text.trim().is_empty())
+            .then_some(watermark);
+
+        let project_cache = project_config
+            .and_then(|config| config.cache.as_ref())
+            .and_then(|config| config.cache.as_ref());
+        let global_cache = global_config
+            .and_then(|config| config.cache.as_ref())
+            .and_then(|config| config.cache.as_ref());
+        let shared_cache = project_cache
+            .and_then(|config| config.shared.clone())
+            .or_else(|| global_cache.and_then(|config| config.shared.clone()))
+            .filter(|shared| !shared.trim().is_empty());
+        let shared_cache_read_only = project_cache
+            .and_then(|config| config.read_only)
+            .or_else(|| global_cache.and_then(|config| config.read_only))
+            .unwrap_or(false);
+
+        let project_terms = project_config
+            .and_then(|config| config.terms.as_ref())
+            .and_then(|config| config.terms.as_ref());
+        let global_terms = global_config
+            .and_then(|config| config.terms.as_ref())
+            .and_then(|config| config.terms.as_ref());
+        let terms_dictionary = project_terms
+            .and_then(|config| config.dictionary.clone())
+            .filter(|dictionary| !dictionary.trim().is_empty());
+        let spellcheck_dictionaries = project_terms
+            .and_then(|config| config.spellcheck.clone())
+            .or_else(|| global_terms.and_then(|config| config.spellcheck.clone()))
+            .unwrap_or_default();
+
+        let project_glossary = project_config
+            .and_then(|config| config.glossary.as_ref())
+            .and_then(|config| config.glossary.as_ref());
+        let glossary_files = project_glossary
+            .and_then(|config| config.files.clone())
+            .or_else(|| {
+                project_root
+                    .as_deref()
+                    .map(|root| crate::glossary::default_files(Path::new(root)))
+            })
+            .unwrap_or_default();
+        let glossary_title = project_glossary
+            .and_then(|config| config.title.clone())
+            .filter(|title| !title.trim().is_empty());
+
+        let project_index = project_config
+            .and_then(|config| config.index.as_ref())
+            .and_then(|config| config.index.as_ref());
+        let index_enabled = project_index.is_some_and(|config| config.enabled.unwrap_or(true));
+        let index_title = project_index
+            .and_then(|config| config.title.clone())
+            .filter(|title| !title.trim().is_empty());
+        let index_program = project_index
+            .and_then(|config| config.program.clone())
+            .filter(|program| !program.trim().is_empty());
+
+        let project_links = project_config
+            .and_then(|config| config.links.as_ref())
+            .and_then(|config| config.links.as_ref());
+        let links_ignore = project_links
+            .and_then(|config| config.ignore.clone())
+            .unwrap_or_default();
+        let links_allow = project_links
+            .and_then(|config| config.allow.clone())
+            .unwrap_or_default();
+        let links_timeout = project_links.and_then(|config| config.timeout);
+        let links_concurrency = project_links.and_then(|config| config.concurrency);
+        let links_rate_limit = project_links.and_then(|config| config.rate_limit);
+        let links_cache_ttl = project_links.and_then(|config| config.cache_ttl);
+
+        let lint_rules = project_config
+            .and_then(|config| config.lint.as_ref())
+            .and_then(|config| config.lint.as_ref())
+            .and_then(|config| config.rules.clone())
+            .unwrap_or_default();
+
         // 合并工具路径
         let mut tool_paths = HashMap::new();
         merge_tool_paths(&mut tool_paths, global_config);
@@ -355,10 +653,19 @@ impl ConfigManager {
             to,
             outputs,
             target,
+            document,
+            documents,
+            profile: project.and_then(|p| p.profile()).map(str::to_string),
+            variant: selected_variant.map(|(name, _)| name.clone()),
+            variant_audiences: selected_variant
+                .map(|(name, variant)| variant_audiences(name, variant))
+                .unwrap_or_default(),
+            variants,
             metadata_file,
             verbose,
             latex_backend,
             max_latex_passes,
+            jobs,
             figure_paths,
             figure_output,
             theme_name,
@@ -380,6 +687,10 @@ impl ConfigManager {
             pandoc_template,
             pandoc_html_template,
             pandoc_latex_template,
+            pandoc_typst_template,
+            pandoc_site_template,
+            pandoc_site_split_level,
+            pandoc_search,
             pandoc_epub_template,
             pandoc_data_dir,
             pandoc_resource_path,
@@ -393,6 +704,25 @@ impl ConfigManager {
             tectonic_only_cached,
             tectonic_shell_escape,
             tectonic_search_paths,
+            pdf_metadata,
+            pdf_profile,
+            watermark,
+            shared_cache,
+            shared_cache_read_only,
+            terms_dictionary,
+            spellcheck_dictionaries,
+            glossary_files,
+            glossary_title,
+            index_enabled,
+            index_title,
+            index_program,
+            links_ignore,
+            links_allow,
+            links_timeout,
+            links_concurrency,
+            links_rate_limit,
+            links_cache_ttl,
+            lint_rules,
             tool_paths,
             template_dir,
             paths,
@@ -503,6 +833,99 @@ impl ConfigManager {
     }
 }
 
+/// Document and variant names become cache and lock path components, so keep
+/// them to a portable identifier alphabet and reject duplicates before
+/// anything is built.
+/// Memo key of one `ConfigManager::new` call. Tool overrides are sorted so
+/// equal requests map to the same key regardless of hash order.
+fn warm_config_key(start: &Path, cli_overrides: &CliOverrides) -> String {
+    let tools = cli_overrides.tool_paths.iter().collect::<BTreeMap<_, _>>();
+    let overrides = CliOverrides {
+        tool_paths: HashMap::new(),
+        ..cli_overrides.clone()
+    };
+    format!("{}\0{:?}\0{:?}", start.display(), overrides, tools)
+}
+
+/// Document names must be unique, and `metadata` values must be scalars:
+/// they reach Pandoc as `--metadata=key:value`, which cannot carry lists or
+/// tables.
+fn validate_documents(documents: &[DocumentSection]) -> Result<()> {
+    validate_names(
+        "[[documents]]",
+        documents.iter().map(|document| document.name.as_str()),
+    )?;
+    for document in documents {
+        for (key, value) in document.metadata.iter().flatten() {
+            if matches!(value, toml::Value::Array(_) | toml::Value::Table(_)) {
+                return Err(OmniDocError::Config(format!(
+                    "[[documents]] '{}' metadata.{key} must be a string, number, boolean, or date; put lists and tables in the document's metadata_file",
+                    document.name
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn validate_names<'a>(section: &str, names: impl Iterator<Item = &'a str>) -> Result<()> {
+    let mut seen = Vec::new();
+    for name in names {
+        let valid = name
+            .chars()
+            .next()
+            .is_some_and(|first| first.is_ascii_alphanumeric())
+            && name.chars().all(|character| {
+                character.is_ascii_alphanumeric() || matches!(character, '-' | '_')
+            });
+        if !valid {
+            return Err(OmniDocError::Config(format!(
+                "Invalid {section} name '{name}'; use letters, digits, '-' or '_'"
+            )));
+        }
+        if seen.contains(&name) {
+            return Err(OmniDocError::Config(format!(
+                "Duplicate {section} name '{name}'"
+            )));
+        }
+        seen.push(name);
+    }
+    Ok(())
+}
+
+fn variant_audiences(name: &str, variant: &VariantSection) -> Vec<String> {
+    variant
+        .audiences
+        .clone()
+        .unwrap_or_else(|| vec![name.to_string()])
+}
+
+fn unknown_variant_error(name: &str, variants: &BTreeMap<String, Vec<String>>) -> OmniDocError {
+    if variants.is_empty() {
+        OmniDocError::Config(format!(
+            "Unknown variant '{name}'; this project declares no [variants]"
+        ))
+    } else {
+        OmniDocError::Config(format!(
+            "Unknown variant '{name}'; declared variants: {}",
+            variants.keys().cloned().collect::<Vec<_>>().join(", ")
+        ))
+    }
+}
+
+fn unknown_document_error(name: &str, documents: &[String]) -> OmniDocError {
+    if documents.is_empty() {
+        OmniDocError::Config(format!(
+            "Unknown document '{name}'; this project declares no [[documents]]"
+        ))
+    } else {
+        OmniDocError::Config(format!(
+            "Unknown document '{name}'; declared documents: {}",
+            documents.join(", ")
+        ))
+    }
+}
+
 fn merge_tool_paths(target: &mut HashMap<String, Option<String>>, config: Option<&ConfigSchema>) {
     let Some(tools) = config
         .and_then(|config| config.tools.as_ref())
@@ -521,9 +944,14 @@ fn merge_tool_paths(target: &mut HashMap<String, Option<String>>, config: Option
         ("ngspice", tools.ngspice.as_ref()),
         ("latex_engine", tools.latex_engine.as_ref()),
         ("tectonic", tools.tectonic.as_ref()),
+        ("typst", tools.typst.as_ref()),
         ("pandoc-crossref", tools.pandoc_crossref.as_ref()),
         ("epubcheck", tools.epubcheck.as_ref()),
         ("kroki", tools.kroki.as_ref()),
+        ("hunspell", tools.hunspell.as_ref()),
+        ("makeindex", tools.makeindex.as_ref()),
+        ("xindy", tools.xindy.as_ref()),
+        ("zhmakeindex", tools.zhmakeindex.as_ref()),
     ] {
         if let Some(value) = value {
             target.insert(key.to_string(), Some(value.clone()));
@@ -564,9 +992,11 @@ fn resolve_global_config_path(config_file: &Path, configured: &str) -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::{merge_path_config, merge_tool_paths, resolve_global_config_path};
+    use super::{merge_path_config, merge_tool_paths, resolve_global_config_path, ConfigManager};
     use crate::config::schema::ConfigSchema;
+    use crate::config::CliOverrides;
     use std::collections::HashMap;
+    use std::fs;
     use std::path::PathBuf;
 
     #[test]
@@ -626,4 +1056,151 @@ mod tests {
             absolute_store
         );
     }
+
+    const DOCUMENTS_CONFIG: &str = r#"[project]
+entry = "main.md"
+to = "pdf"
+target = "book"
+
+[build]
+outputs = ["pdf", "html"]
+
+[[documents]]
+name = "manual"
+entry = "manual.md"
+metadata = { subtitle = "Operations", draft = true }
+
+[[documents]]
+name = "slides"
+entry = "slides.md"
+to = "pptx"
+target = "deck"
+outputs = ["pptx"]
+"#;
+
+    #[test]
+    fn document_fields_override_project_defaults_and_name_the_target() {
+        let project = tempfile::tempdir().expect("project");
+        fs::write(project.path().join(".omnidoc.toml"), DOCUMENTS_CONFIG).expect("config");
+        let merged = |document: Option<&str>| {
+            ConfigManager::new(
+                Some(project.path()),
+                CliOverrides::new().with_document(document.map(str::to_string)),
+            )
+            .expect("merged config")
+            .get_merged()
+            .clone()
+        };
+
+        let base = merged(None);
+        assert_eq!(base.documents, ["manual", "slides"]);
+        assert_eq!(base.document, None);
+        assert_eq!(base.target.as_deref(), Some("book"));
+
+        let manual = merged(Some("manual"));
+        assert_eq!(manual.entry.as_deref(), Some("manual.md"));
+        assert_eq!(manual.to.as_deref(), Some("pdf"));
+        assert_eq!(manual.target.as_deref(), Some("manual"));
+        assert_eq!(manual.outputs, ["pdf", "html"]);
+        assert!(manual.pandoc_options.ends_with(&[
+            "--metadata=draft:true".to_string(),
+            "--metadata=subtitle:Operations".to_string()
+        ]));
+
+        let slides = merged(Some("slides"));
+        assert_eq!(slides.document.as_deref(), Some("slides"));
+        assert_eq!(slides.to.as_deref(), Some("pptx"));
+        assert_eq!(slides.target.as_deref(), Some("deck"));
+        assert_eq!(slides.outputs, ["pptx"]);
+
+        let error = ConfigManager::new(
+            Some(project.path()),
+            CliOverrides::new().with_document(Some("notes".to_string())),
+        )
+        .err()
+        .expect("unknown document");
+        assert!(error.to_string().contains("manual, slides"));
+    }
+
+    #[test]
+    fn document_names_must_be_unique_path_safe_identifiers() {
+        let project = tempfile::tempdir().expect("project");
+        for (names, message) in [
+            (["manual", "manual"], "Duplicate"),
+            (["manual", "../up"], "Invalid"),
+            (["manual", "-x"], "Invalid"),
+        ] {
+            let config = names
+                .iter()
+                .map(|name| format!("[[documents]]\nname = {name:?}\n"))
+                .collect::<String>();
+            fs::write(project.path().join(".omnidoc.toml"), config).expect("config");
+            let error = ConfigManager::new(Some(project.path()), CliOverrides::new())
+                .err()
+                .expect("invalid document names");
+            assert!(error.to_string().contains(message), "{error}");
+        }
+    }
+
+    #[test]
+    fn document_metadata_rejects_lists_and_tables() {
+        let project = tempfile::tempdir().expect("project");
+        for metadata in [r#"keywords = ["a", "b"]"#, r#"author = { name = "Ada" }"#] {
+            fs::write(
+                project.path().join(".omnidoc.toml"),
+                format!("[[documents]]\nname = \"manual\"\nmetadata = {{ {metadata} }}\n"),
+            )
+            .expect("config");
+            let error = ConfigManager::new(Some(project.path()), CliOverrides::new())
+                .err()
+                .expect("non-scalar metadata");
+            assert!(error.to_string().contains("metadata_file"), "{error}");
+        }
+    }
+
+    #[test]
+    fn variants_select_audiences_and_suffix_the_target() {
+        let project = tempfile::tempdir().expect("project");
+        fs::write(
+            project.path().join(".omnidoc.toml"),
+            r#"[project]
+target = "guide"
+
+[variants.customer]
+
+[variants.partner]
+audiences = ["partner", "customer"]
+target = "partner-guide"
+
+[[documents]]
+name = "manual"
+"#,
+        )
+        .expect("config");
+        let merged = |document: Option<&str>, variant: Option<&str>| {
+            ConfigManager::new(
+                Some(project.path()),
+                CliOverrides::new()
+                    .with_document(document.map(str::to_string))
+                    .with_variant(variant.map(str::to_string)),
+            )
+            .map(|manager| manager.get_merged().clone())
+        };
+
+        let base = merged(None, None).expect("base");
+        assert_eq!(base.variant, None);
+        assert!(base.variant_audiences.is_empty());
+        assert_eq!(base.variants["partner"], ["partner", "customer"]);
+
+        let customer = merged(None, Some("customer")).expect("customer");
+        assert_eq!(customer.target.as_deref(), Some("guide-customer"));
+        assert_eq!(customer.variant_audiences, ["customer"]);
+        let manual = merged(Some("manual"), Some("customer")).expect("manual");
+        assert_eq!(manual.target.as_deref(), Some("manual-customer"));
+        let partner = merged(None, Some("partner")).expect("partner");
+        assert_eq!(partner.target.as_deref(), Some("partner-guide"));
+
+        let error = merged(None, Some("internal")).expect_err("unknown variant");
+        assert!(error.to_string().contains("customer, partner"), "{error}");
+    }
 }
@@ -1,13 +1,21 @@
 use crate::config::schema::ConfigSchema;
 use crate::error::{OmniDocError, Result};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
-const PROJECT_CONFIG_FILE: &str = ".omnidoc.toml";
+pub(crate) const PROJECT_CONFIG_FILE: &str = ".omnidoc.toml";
+const PROFILES_KEY: &str = "profiles";
 
 /// 项目配置管理器
+#[derive(Clone)]
 pub struct ProjectConfig {
     path: PathBuf,
     config: Option<ConfigSchema>,
+    /// 不含 `[profiles]` 的原始配置表，用于叠加 profile
+    table: toml::Table,
+    /// `[profiles.<name>]` overlays, each shaped like the project file itself
+    profiles: BTreeMap<String, toml::Table>,
+    profile: Option<String>,
 }
 
 impl ProjectConfig {
@@ -40,15 +48,81 @@ impl ProjectConfig {
             OmniDocError::Config(format!("Failed to parse project config: {error}"))
         })?;
 
-        let config: ConfigSchema = toml::from_str(&content)
+        let mut table: toml::Table = toml::from_str(&content)
             .map_err(|e| OmniDocError::Config(format!("Failed to parse project config: {}", e)))?;
+        let profiles = match table.remove(PROFILES_KEY) {
+            None => BTreeMap::new(),
+            Some(toml::Value::Table(profiles)) => profiles
+                .into_iter()
+                .map(|(name, profile)| match profile {
+                    toml::Value::Table(profile) if !profile.contains_key(PROFILES_KEY) => {
+                        Ok((name, profile))
+                    }
+                    _ => Err(OmniDocError::Config(format!(
+                        "Failed to parse project config: [profiles.{name}] must be a table of configuration sections"
+                    ))),
+                })
+                .collect::<Result<_>>()?,
+            Some(_) => {
+                return Err(OmniDocError::Config(
+                    "Failed to parse project config: [profiles] must be a table".to_string(),
+                ))
+            }
+        };
+        let config = parse_schema(&table)?;
 
         Ok(Self {
             path: path.to_path_buf(),
             config: Some(config),
+            table,
+            profiles,
+            profile: None,
         })
     }
 
+    /// Overlay `[profiles.<name>]` on the project configuration. Nested tables
+    /// merge key by key; any other value, including arrays, replaces the base.
+    pub fn apply_profile(&mut self, name: &str) -> Result<()> {
+        let profile = self.profiles.get(name).ok_or_else(|| {
+            OmniDocError::Config(if self.profiles.is_empty() {
+                format!(
+                    "Unknown profile '{name}'; {} declares no [profiles]",
+                    self.path.display()
+                )
+            } else {
+                format!(
+                    "Unknown profile '{name}'; declared profiles: {}",
+                    self.profile_names().join(", ")
+                )
+            })
+        })?;
+        let mut table = self.table.clone();
+        overlay_table(&mut table, profile);
+        let content = toml::to_string(&table)
+            .map_err(|e| OmniDocError::Config(format!("Failed to apply profile '{name}': {e}")))?;
+        for validate in [
+            crate::config::schema::validate_plugin_configuration,
+            crate::config::schema::validate_project_extension_scope,
+        ] {
+            validate(&content).map_err(|error| {
+                OmniDocError::Config(format!("Failed to apply profile '{name}': {error}"))
+            })?;
+        }
+        self.config = Some(parse_schema(&table)?);
+        self.profile = Some(name.to_string());
+        Ok(())
+    }
+
+    /// 已声明的 profile 名称（按字母顺序）
+    pub fn profile_names(&self) -> Vec<&str> {
+        self.profiles.keys().map(String::as_str).collect()
+    }
+
+    /// 当前生效的 profile
+    pub fn profile(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+
     /// 创建默认项目配置
     pub fn create_default(
         path: &Path,
@@ -73,6 +147,8 @@ impl ProjectConfig {
         };
         config.project = Some(ProjectConfigSchema {
             project: Some(project_section),
+            documents: None,
+            variants: None,
         });
 
         // 设置构建配置
@@ -84,6 +160,7 @@ impl ProjectConfig {
                 outputs: None,
                 latex_backend: None,
                 max_latex_passes: None,
+                jobs: None,
             }),
         });
 
@@ -102,9 +179,14 @@ impl ProjectConfig {
         use crate::utils::fs;
         fs::write(&config_path, toml_content.as_bytes())?;
 
+        let table = toml::from_str(&toml_content)
+            .map_err(|e| OmniDocError::Config(format!("Failed to parse project config: {}", e)))?;
         Ok(Self {
             path: config_path,
             config: Some(config),
+            table,
+            profiles: BTreeMap::new(),
+            profile: None,
         })
     }
 
@@ -124,6 +206,25 @@ impl ProjectConfig {
     }
 }
 
+fn parse_schema(table: &toml::Table) -> Result<ConfigSchema> {
+    toml::Value::Table(table.clone())
+        .try_into()
+        .map_err(|e| OmniDocError::Config(format!("Failed to parse project config: {}", e)))
+}
+
+fn overlay_table(base: &mut toml::Table, overlay: &toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(overlay)) => {
+                overlay_table(base, overlay)
+            }
+            _ => {
+                base.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::ProjectConfig;
@@ -163,4 +264,59 @@ mod tests {
         };
         assert!(error.to_string().contains("pre_build"));
     }
+
+    #[test]
+    fn profiles_overlay_nested_tables_and_replace_arrays() {
+        let project = tempfile::tempdir().expect("project");
+        let config_path = project.path().join(".omnidoc.toml");
+        fs::write(
+            &config_path,
+            r#"[project]
+entry = "main.md"
+target = "book"
+
+[build]
+outputs = ["pdf", "html"]
+max_latex_passes = 2
+
+[pandoc]
+toc = true
+
+[profiles.final.build]
+outputs = ["pdf"]
+max_latex_passes = 6
+
+[profiles.draft.pandoc]
+toc = false
+"#,
+        )
+        .expect("project config");
+
+        let mut config = ProjectConfig::from_file(&config_path).expect("load config");
+        assert_eq!(config.profile_names(), ["draft", "final"]);
+        assert_eq!(config.profile(), None);
+
+        config.apply_profile("final").expect("final profile");
+        let schema = config.get_config().expect("schema");
+        let build = schema
+            .build
+            .as_ref()
+            .and_then(|build| build.build.as_ref())
+            .expect("build section");
+        assert_eq!(build.outputs.as_deref(), Some(&["pdf".to_string()][..]));
+        assert_eq!(build.max_latex_passes, Some(6));
+        let project_section = schema
+            .project
+            .as_ref()
+            .and_then(|project| project.project.as_ref())
+            .expect("project section");
+        assert_eq!(project_section.target.as_deref(), Some("book"));
+        assert_eq!(config.profile(), Some("final"));
+
+        let error = match config.apply_profile("review") {
+            Ok(()) => panic!("unknown profiles must fail"),
+            Err(error) => error,
+        };
+        assert!(error.to_string().contains("draft, final"));
+    }
 }
@@ -29,6 +29,22 @@ pub struct ConfigSchema {
     #[serde(flatten)]
     pub tectonic: Option<TectonicConfig>,
     #[serde(flatten)]
+    pub pdf: Option<PdfConfig>,
+    #[serde(flatten)]
+    pub watermark: Option<WatermarkConfig>,
+    #[serde(flatten)]
+    pub cache: Option<CacheConfig>,
+    #[serde(flatten)]
+    pub terms: Option<TermsConfig>,
+    #[serde(flatten)]
+    pub glossary: Option<GlossaryConfig>,
+    #[serde(flatten)]
+    pub index: Option<IndexConfig>,
+    #[serde(flatten)]
+    pub links: Option<LinksConfig>,
+    #[serde(flatten)]
+    pub lint: Option<LintConfig>,
+    #[serde(flatten)]
     pub paths: Option<PathsConfig>,
     pub download: Option<Vec<DownloadConfig>>,
     pub template_dir: Option<String>,
@@ -167,6 +183,12 @@ pub struct EnvSection {
 pub struct ProjectConfig {
     #[serde(rename = "project")]
     pub project: Option<ProjectSection>,
+    /// 同一项目中的多个命名文档（`[[documents]]`）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub documents: Option<Vec<DocumentSection>>,
+    /// 受众变体（`[variants.<name>]`），通过 `--variant NAME` 选择
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variants: Option<BTreeMap<String, VariantSection>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -175,12 +197,41 @@ pub struct ProjectSection {
     pub entry: Option<String>,
     /// 源码类型（markdown, latex）
     pub from: Option<String>,
-    /// 生成文档类型（pdf, html, epub, docx, pptx, latex）
+    /// 生成文档类型（pdf, html, html-site, epub, docx, pptx, beamer, revealjs, gfm, plain, man, jats, docbook, latex, typst）
     pub to: Option<String>,
     /// 生成文档名称（不含扩展名）
     pub target: Option<String>,
 }
 
+/// One named document of a multi-document project. Unset fields inherit the
+/// project-level `[project]`, `[build]` and `[theme]` values; `target`
+/// defaults to the document name so documents never share an artifact.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct DocumentSection {
+    /// Name selected with `--doc NAME` (letters, digits, `-` and `_`)
+    pub name: String,
+    pub entry: Option<String>,
+    pub to: Option<String>,
+    pub target: Option<String>,
+    pub outputs: Option<Vec<String>>,
+    pub metadata_file: Option<String>,
+    /// Pandoc metadata overrides, passed as `--metadata=KEY:VALUE`
+    pub metadata: Option<BTreeMap<String, toml::Value>>,
+    pub theme: Option<ThemeSection>,
+}
+
+/// One audience variant of the project. Fenced Divs and spans marked with
+/// `.only-<audience>` or `when="..."` are kept only for the audiences listed
+/// here; `target` defaults to `<target>-<variant>`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct VariantSection {
+    /// Audiences shown by this variant (defaults to the variant name)
+    pub audiences: Option<Vec<String>>,
+    pub target: Option<String>,
+    /// Watermark fields overriding `[watermark]` for this variant
+    pub watermark: Option<WatermarkSection>,
+}
+
 /// 构建配置
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "snake_case")]
@@ -200,6 +251,8 @@ pub struct BuildSection {
     pub latex_backend: Option<String>,
     /// Maximum direct LaTeX engine passes when latex_backend = "engine"
     pub max_latex_passes: Option<usize>,
+    /// Outputs built concurrently by multi-output builds (0 = one per CPU)
+    pub jobs: Option<usize>,
 }
 
 /// 图片配置
@@ -232,7 +285,7 @@ pub struct PandocSection {
     pub toc: Option<bool>,
     /// Pandoc 额外选项列表（会被追加到默认选项之后）
     pub options: Option<Vec<String>>,
-    /// Per-output Pandoc options keyed by pdf/html/epub/docx/pptx/latex.
+    /// Per-output Pandoc options keyed by an output name such as pdf, html or revealjs.
     /// These are appended after the common options so a format can override
     /// settings whose depth semantics differ between writers.
     pub format_options: Option<BTreeMap<String, Vec<String>>>,
@@ -256,6 +309,15 @@ pub struct PandocSection {
     pub html_template: Option<String>,
     /// LaTeX/PDF output template
     pub latex_template: Option<String>,
+    /// Typst output template (also used by the Typst PDF engine)
+    pub typst_template: Option<String>,
+    /// Page template for `html-site` output (default: the library site template)
+    pub site_template: Option<String>,
+    /// Heading level at which `html-site` output starts a new page (default 1)
+    pub site_split_level: Option<usize>,
+    /// Emit an offline search index and widget for HTML outputs
+    /// (default: whatever the selected theme enables)
+    pub search: Option<bool>,
     /// EPUB output template
     pub epub_template: Option<String>,
     /// 数据目录（默认使用 omnidoc-libs/pandoc/data）
@@ -297,16 +359,24 @@ pub struct ToolsSection {
     pub kicad_cli: Option<String>,
     /// ngspice circuit simulator used by spiceplot blocks
     pub ngspice: Option<String>,
-    /// LaTeX 引擎（xelatex, pdflatex, lualatex）
+    /// LaTeX 引擎（xelatex, pdflatex, lualatex, tectonic；typst 切换到 Typst PDF 路径）
     pub latex_engine: Option<String>,
     /// Tectonic executable path
     pub tectonic: Option<String>,
+    /// Typst executable path
+    pub typst: Option<String>,
     /// pandoc-crossref 可执行文件路径
     pub pandoc_crossref: Option<String>,
     /// EPUBCheck executable path
     pub epubcheck: Option<String>,
     /// kroki 服务 URL 或本地可执行文件路径（用于 mermaid 生成）
     pub kroki: Option<String>,
+    /// Hunspell spell checker used by `[terms] spellcheck`
+    pub hunspell: Option<String>,
+    /// Index processors run between LaTeX passes for `[index]`
+    pub makeindex: Option<String>,
+    pub xindy: Option<String>,
+    pub zhmakeindex: Option<String>,
 }
 
 /// Tectonic runtime policy. Tectonic remains network-enabled by default;
@@ -331,6 +401,146 @@ pub struct TectonicSection {
     pub search_paths: Option<Vec<String>>,
 }
 
+/// PDF post-processing: metadata stamping and conformance profiles.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct PdfConfig {
+    #[serde(rename = "pdf")]
+    pub pdf: Option<PdfSection>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PdfSection {
+    /// Stamp title, author, keywords and git revision into Info and XMP (default true).
+    pub metadata: Option<bool>,
+    /// Conformance profile to complete and validate, currently `pdf-a-2b`.
+    pub profile: Option<String>,
+}
+
+/// Draft or confidentiality watermark for PDF, HTML, EPUB and DOCX outputs.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct WatermarkConfig {
+    #[serde(rename = "watermark")]
+    pub watermark: Option<WatermarkSection>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct WatermarkSection {
+    /// Stamped text such as "DRAFT"; empty text disables the watermark.
+    pub text: Option<String>,
+    /// Hex color (default `#999999`).
+    pub color: Option<String>,
+    /// Opacity between 0 and 1 (default 0.3).
+    pub opacity: Option<f64>,
+    /// `all` (default) or `first`.
+    pub pages: Option<String>,
+}
+
+/// Build cache shared between checkouts and CI jobs.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct CacheConfig {
+    #[serde(rename = "cache")]
+    pub cache: Option<CacheSection>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CacheSection {
+    /// Shared cache directory, or an `http://`/`https://` base URL.
+    pub shared: Option<String>,
+    /// Restore from the shared cache without uploading new entries.
+    pub read_only: Option<bool>,
+}
+
+/// Project terminology and spell checking enforced by `check lint`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct TermsConfig {
+    #[serde(rename = "terms")]
+    pub terms: Option<TermsSection>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TermsSection {
+    /// Terminology dictionary, relative to the project (default `terms.toml` when present).
+    pub dictionary: Option<String>,
+    /// Hunspell dictionaries such as `en_US`; unset or empty disables spell checking.
+    pub spellcheck: Option<Vec<String>>,
+}
+
+/// Glossary and acronym definitions expanded by the bundled `glossary.lua` filter.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct GlossaryConfig {
+    #[serde(rename = "glossary")]
+    pub glossary: Option<GlossarySection>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct GlossarySection {
+    /// YAML or TOML definition files, relative to the project (default `glossary.yaml`, `.yml` or `.toml` when present).
+    pub files: Option<Vec<String>>,
+    /// Title of the generated glossary chapter (default by document language).
+    pub title: Option<String>,
+}
+
+/// External link checking by `omnidoc check links`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct LinksConfig {
+    #[serde(rename = "links")]
+    pub links: Option<LinksSection>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct LinksSection {
+    /// Regular expressions for URLs that are never checked.
+    pub ignore: Option<Vec<String>>,
+    /// Regular expressions for URLs whose failures are reported but tolerated.
+    pub allow: Option<Vec<String>>,
+    /// Per-request timeout in seconds (default 10).
+    pub timeout: Option<u64>,
+    /// Requests in flight at once (default 8).
+    pub concurrency: Option<usize>,
+    /// Requests per second to any one host (default 2).
+    pub rate_limit: Option<f64>,
+    /// Hours a cached result stays valid (default 24; 0 disables the cache).
+    pub cache_ttl: Option<u64>,
+}
+
+/// Source lint rules run by `omnidoc check lint` and before builds.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct LintConfig {
+    #[serde(rename = "lint")]
+    pub lint: Option<LintSection>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct LintSection {
+    /// Severity per rule id: "error", "warning", "info", or "off".
+    pub rules: Option<BTreeMap<String, String>>,
+}
+
+/// Back-of-book index built from `[term]{.idx}` markup by the bundled `index.lua` filter.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct IndexConfig {
+    #[serde(rename = "index")]
+    pub index: Option<IndexSection>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct IndexSection {
+    /// Generate the index (default true once the section is present).
+    pub enabled: Option<bool>,
+    /// Title of the generated index chapter (default by document language).
+    pub title: Option<String>,
+    /// PDF index processor: `makeindex`, `xindy` or `zhmakeindex` (default `zhmakeindex` for Chinese documents, `makeindex` otherwise).
+    pub program: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DownloadConfig {
     pub url: String,
@@ -95,6 +95,8 @@ pub mod pandoc {
     pub const FLAG_TEMPLATE: &str = "--template";
     pub const FLAG_CSS: &str = "--css";
     pub const FLAG_INCLUDE_IN_HEADER: &str = "--include-in-header";
+    pub const FLAG_INCLUDE_AFTER_BODY: &str = "--include-after-body";
+    pub const FLAG_EXTRACT_MEDIA: &str = "--extract-media";
 
     // Defaults
     pub const DEFAULT_FROM_PDF: &str = "markdown+east_asian_line_breaks+footnotes";
@@ -104,6 +106,7 @@ pub mod pandoc {
     pub const DEFAULT_SYNTAX: &str = "idiomatic";
     pub const DEFAULT_PYTHON: &str = "python3";
     pub const DEFAULT_PLUGIN_CROSSREF: &str = "pandoc-crossref";
+    pub const DEFAULT_ENGINE_TYPST: &str = "typst";
 
     // Library paths (relative under omnidoc lib)
     pub const LIB_PANDOC_DATA: &str = "pandoc/data";
@@ -116,6 +119,12 @@ pub mod pandoc {
     pub const LIB_PANDOC_CROSSREF_YAML_HTML: &str = "pandoc/data/crossref.yaml"; // HTML fallback
     pub const LIB_PANDOC_CSS_BASE: &str = "pandoc/css/omnidoc-base.css";
     pub const LIB_PANDOC_CSS_DEFAULT: &str = "pandoc/css/advance-editor.css";
+    pub const LIB_PANDOC_TYPST_TEMPLATE: &str = "pandoc/data/templates/pantypst.typ";
+    pub const LIB_PANDOC_SITE_TEMPLATE: &str = "pandoc/data/templates/omnidoc-site.html";
+    pub const LIB_PANDOC_DOCBOOK_TEMPLATE: &str = "pandoc/data/templates/omnidoc-docbook5.xml";
+    pub const LIB_PANDOC_SEARCH_WIDGET: &str = "pandoc/data/search/omnidoc-search.html";
+    pub const LIB_PANDOC_CSS_SITE: &str = "pandoc/css/modules/site.css";
+    pub const LIB_PANDOC_CSS_REVEALJS: &str = "pandoc/css/modules/revealjs.css";
 
     // Resource path defaults
     pub const RESOURCE_PATH_COMMON_SUFFIX: &str = ":image:images:figure:figures:biblio";
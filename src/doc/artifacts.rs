@@ -90,10 +90,10 @@ pub fn expected_output_file(
     output: &str,
     target: &str,
 ) -> PathBuf {
-    let extension = PandocOutputKind::from_requested(Some(output))
-        .map(|kind| kind.extension().to_string())
-        .unwrap_or_else(|_| output.trim().to_ascii_lowercase());
-    output_directory(project_path, config).join(format!("{target}.{extension}"))
+    let artifact = PandocOutputKind::from_requested(Some(output))
+        .map(|kind| kind.artifact_name(target))
+        .unwrap_or_else(|_| format!("{target}.{}", output.trim().to_ascii_lowercase()));
+    output_directory(project_path, config).join(artifact)
 }
 
 pub fn artifact_for_format(
@@ -145,5 +145,12 @@ mod tests {
         assert_eq!(artifact.format, "html");
         assert_eq!(artifact.path_buf(), project.join("output/handbook.html"));
         assert!(!artifact.exists);
+
+        let site = artifact_for_format(project, &config, "chunkedhtml").expect("site artifact");
+        assert_eq!(site.format, "html-site");
+        assert_eq!(
+            site.path_buf(),
+            project.join("output/handbook-site/index.html")
+        );
     }
 }
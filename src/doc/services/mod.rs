@@ -6,7 +6,6 @@ pub mod format;
 
 // 预留接口（第二阶段或未来）
 // pub mod watch;
-// pub mod typst;
 // pub mod epub;
 
 pub use build::{BuildService, CleanOptions, CleanReport, CleanTarget, CleanTargetKind};
@@ -19,7 +19,8 @@ pub use plugin::{
 };
 pub(crate) use theme::resolve_theme_manifest;
 pub use theme::{
-    materialize_theme_tokens, resolve_selected_theme, resolve_theme_request, theme_catalog,
-    GeneratedThemeAssets, ResolvedTheme, ThemeCatalogEntry, ThemeMetadata, ThemeRequirements,
+    materialize_theme_tokens, materialize_watermark, resolve_selected_theme, resolve_theme_request,
+    resolve_watermark, theme_catalog, GeneratedThemeAssets, GeneratedWatermarkAssets,
+    ResolvedTheme, ResolvedWatermark, ThemeCatalogEntry, ThemeMetadata, ThemeRequirements,
     ThemeResources,
 };
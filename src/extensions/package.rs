@@ -1,6 +1,7 @@
 use crate::config::MergedConfig;
 use crate::error::{OmniDocError, Result};
 use crate::utils::directories::data_local_dir;
+use crate::warm;
 use flate2::read::GzDecoder;
 use fs2::FileExt;
 use semver::{Version, VersionReq};
@@ -106,6 +107,9 @@ pub struct ThemePackage {
     pub compatibility: Option<String>,
     #[serde(default)]
     pub extends: Option<String>,
+    /// Whether HTML outputs ship the offline search widget by default.
+    #[serde(default)]
+    pub search: Option<bool>,
     #[serde(default)]
     pub outputs: Option<Vec<String>>,
     #[serde(default)]
@@ -284,7 +288,8 @@ pub struct PluginFilter {
     pub order: i32,
     /// Optional globally unique key used by the filter to publish a depfile.
     /// OmniDoc exposes it as `omnidoc-plugin-depfile-<key>` and consumes
-    /// `.omnidoc-cache/plugin-<key>.d` only while this filter is active.
+    /// `.omnidoc-cache/outputs/<output>/plugin-<key>.d` only while this filter
+    /// is active.
     #[serde(default)]
     pub dependency_key: Option<String>,
 }
@@ -676,6 +681,22 @@ pub(crate) fn extension_store_roots(
     Ok(stores)
 }
 
+/// Stat fingerprint of every extension store consulted for `project_root`,
+/// used to keep resolved themes and plugins warm in `omnidoc daemon`.
+pub(crate) fn extension_store_fingerprint(
+    project_root: Option<&Path>,
+    config: &MergedConfig,
+) -> warm::Fingerprint {
+    match extension_store_roots(project_root, config) {
+        Ok(roots) => roots
+            .iter()
+            .fold(warm::Fingerprint::new(), |fingerprint, root| {
+                fingerprint.with_tree(root)
+            }),
+        Err(error) => warm::Fingerprint::new().with_value(error.to_string()),
+    }
+}
+
 pub fn acquire_extension_store_read_locks(
     project_root: Option<&Path>,
     config: &MergedConfig,
@@ -1492,7 +1513,7 @@ fn validate_theme_output_contract(
         );
     }
     if !theme.tokens.is_empty()
-        && !["html", "epub", "pdf", "latex"]
+        && !["html", "epub", "pdf", "latex", "typst"]
             .iter()
             .any(|output| outputs.contains(*output))
     {
@@ -1522,6 +1543,7 @@ fn validate_theme_output_contract(
                     || !resources.latex_headers.is_empty()
                     || resources.latex_template.is_some()
             }
+            "typst" => !theme.tokens.is_empty(),
             "docx" => resources.docx_reference_doc.is_some(),
             "pptx" => resources.pptx_reference_doc.is_some(),
             _ => false,
@@ -1782,13 +1804,31 @@ pub(super) fn normalized_output(value: &str) -> String {
         "html4" | "html5" => "html".to_string(),
         "epub2" | "epub3" => "epub".to_string(),
         "tex" => "latex".to_string(),
+        "typ" => "typst".to_string(),
         "powerpoint" => "pptx".to_string(),
+        "reveal" => "revealjs".to_string(),
         other => other.to_string(),
     }
 }
 
 fn supported_output(value: &str) -> bool {
-    matches!(value, "pdf" | "html" | "epub" | "docx" | "pptx" | "latex")
+    matches!(
+        value,
+        "pdf"
+            | "html"
+            | "epub"
+            | "docx"
+            | "pptx"
+            | "beamer"
+            | "revealjs"
+            | "gfm"
+            | "plain"
+            | "man"
+            | "jats"
+            | "docbook"
+            | "latex"
+            | "typst"
+    )
 }
 
 fn verify_package_checksums(root: &Path, checksum_path: &Path) -> Result<()> {
@@ -1,11 +1,12 @@
 use super::package::{
-    ensure_pandoc_compatible, normalized_output, package_records, package_spec,
-    tracked_package_files, ExtensionResource, PackageInspection, PackageKind, PackageRecord,
-    PackageScope, PackageSpec, ResolvedPackageIdentity, PACKAGE_MANIFEST_FILE,
+    ensure_pandoc_compatible, extension_store_fingerprint, normalized_output, package_records,
+    package_spec, tracked_package_files, ExtensionResource, PackageInspection, PackageKind,
+    PackageRecord, PackageScope, PackageSpec, ResolvedPackageIdentity, PACKAGE_MANIFEST_FILE,
 };
 use crate::config::MergedConfig;
 use crate::error::{OmniDocError, Result};
 use crate::utils::directories::config_local_dir;
+use crate::warm;
 use fs2::FileExt;
 use semver::Version;
 use serde::{Deserialize, Serialize};
@@ -308,6 +309,34 @@ pub(crate) fn resolve_plugin_manifest(
 }
 
 pub fn enabled_plugins(project_root: &Path, config: &MergedConfig) -> Result<Vec<ResolvedPlugin>> {
+    if config.plugins_enabled.is_empty() {
+        return Ok(Vec::new());
+    }
+    let key = format!(
+        "{}\0{:?}\0{:?}\0{:?}",
+        project_root.display(),
+        config.plugins_enabled,
+        config.extension_path,
+        config.lib_path
+    );
+    // Trust is part of the result, so revoking it must invalidate the entry.
+    warm::PLUGINS.get_or_load(
+        &key,
+        || {
+            let fingerprint = extension_store_fingerprint(Some(project_root), config);
+            match plugin_trust_path() {
+                Ok(path) => fingerprint.with_file(&path),
+                Err(_) => fingerprint,
+            }
+        },
+        || enabled_plugins_uncached(project_root, config),
+    )
+}
+
+fn enabled_plugins_uncached(
+    project_root: &Path,
+    config: &MergedConfig,
+) -> Result<Vec<ResolvedPlugin>> {
     let specs = enabled_plugin_specs(config)?;
     if specs.is_empty() {
         return Ok(Vec::new());
@@ -1,11 +1,13 @@
 use super::package::{
-    digest_files, ensure_pandoc_compatible, normalized_hex_color, normalized_output,
-    package_records, package_spec, safe_relative_path, tracked_package_files, PackageKind,
-    PackageRecord, PackageScope, ResolvedPackageIdentity, ThemePackage, ThemePackageMetadata,
-    ThemePackageRequirements, ThemePackageResources, ThemeTokens,
+    digest_files, ensure_pandoc_compatible, extension_store_fingerprint, normalized_hex_color,
+    normalized_output, package_records, package_spec, safe_relative_path, tracked_package_files,
+    PackageKind, PackageRecord, PackageScope, ResolvedPackageIdentity, ThemePackage,
+    ThemePackageMetadata, ThemePackageRequirements, ThemePackageResources, ThemeTokens,
 };
+use crate::config::schema::WatermarkSection;
 use crate::config::MergedConfig;
 use crate::error::{OmniDocError, Result};
+use crate::warm;
 use semver::{Version, VersionReq};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
@@ -14,6 +16,8 @@ use std::fs;
 use std::path::{Path, PathBuf};
 
 const MAX_THEME_INHERITANCE_DEPTH: usize = 64;
+const DEFAULT_WATERMARK_COLOR: &str = "999999";
+const DEFAULT_WATERMARK_OPACITY: f64 = 0.3;
 
 #[derive(Debug, Clone, Default)]
 pub struct ThemeResources {
@@ -52,6 +56,7 @@ pub struct ResolvedTheme {
     pub resources: ThemeResources,
     pub requirements: ThemeRequirements,
     pub metadata: ThemeMetadata,
+    pub search: Option<bool>,
     pub packages: Vec<ResolvedPackageIdentity>,
     outputs_explicit: bool,
     tokens: ThemeTokens,
@@ -68,6 +73,39 @@ impl ResolvedTheme {
 pub struct GeneratedThemeAssets {
     pub css: Option<PathBuf>,
     pub latex_header: Option<PathBuf>,
+    pub typst_header: Option<PathBuf>,
+    pub beamer_header: Option<PathBuf>,
+    pub revealjs_css: Option<PathBuf>,
+}
+
+/// Watermark settings with defaults applied and values checked.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedWatermark {
+    pub text: String,
+    /// Six-digit uppercase hex color without `#`
+    pub color: String,
+    pub opacity: f64,
+    pub first_page_only: bool,
+}
+
+impl ResolvedWatermark {
+    /// The color blended into white by the opacity, for DOCX runs, which have
+    /// no text transparency.
+    pub fn faded_color(&self) -> String {
+        let channel = |index: usize| {
+            let value = u8::from_str_radix(&self.color[index..index + 2], 16).unwrap_or(0);
+            let faded = 255.0 - (255.0 - f64::from(value)) * self.opacity;
+            format!("{:02X}", faded.round() as u8)
+        };
+        format!("{}{}{}", channel(0), channel(2), channel(4))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct GeneratedWatermarkAssets {
+    pub latex_header: Option<PathBuf>,
+    pub typst_header: Option<PathBuf>,
+    pub css: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -139,8 +177,12 @@ struct LegacyThemeManifest {
     #[serde(default)]
     compatibility: Option<String>,
     #[serde(default)]
+    search: Option<bool>,
+    #[serde(default)]
     resources: LegacyThemeResources,
     #[serde(default)]
+    tokens: ThemeTokens,
+    #[serde(default)]
     requirements: ThemePackageRequirements,
     #[serde(default)]
     metadata: ThemePackageMetadata,
@@ -242,6 +284,30 @@ pub fn theme_catalog(
 pub fn resolve_selected_theme(
     project_root: Option<&Path>,
     config: &MergedConfig,
+) -> Result<Option<ResolvedTheme>> {
+    if config.theme_name.is_none() {
+        return Ok(None);
+    }
+    let key = format!(
+        "{:?}\0{:?}\0{:?}\0{:?}\0{:?}\0{:?}\0{:?}",
+        project_root,
+        config.theme_name,
+        config.theme_version,
+        config.theme_compatibility,
+        config.extension_path,
+        config.lib_path,
+        config.tool_paths.get("pandoc")
+    );
+    warm::THEMES.get_or_load(
+        &key,
+        || extension_store_fingerprint(project_root, config),
+        || resolve_selected_theme_uncached(project_root, config),
+    )
+}
+
+fn resolve_selected_theme_uncached(
+    project_root: Option<&Path>,
+    config: &MergedConfig,
 ) -> Result<Option<ResolvedTheme>> {
     let Some(id) = config.theme_name.as_deref() else {
         return Ok(None);
@@ -447,6 +513,7 @@ fn descriptor_to_resolved(descriptor: &ThemeDescriptor) -> Result<ResolvedTheme>
         resources: resolved,
         requirements: normalized_requirements(&descriptor.theme.requirements),
         metadata: normalized_metadata(&descriptor.theme.metadata),
+        search: descriptor.theme.search,
         packages: vec![ResolvedPackageIdentity {
             kind: PackageKind::Theme,
             scope: descriptor.scope,
@@ -522,6 +589,7 @@ fn merge_themes(parent: ResolvedTheme, child: ResolvedTheme) -> ResolvedTheme {
             system_latex_packages: latex_packages,
         },
         metadata: ThemeMetadata { defaults: metadata },
+        search: child.search.or(parent.search),
         outputs_explicit: child.outputs_explicit,
         tokens: merge_tokens(parent.tokens, child.tokens),
         packages,
@@ -548,6 +616,7 @@ fn validate_resolved_theme_outputs(theme: &ResolvedTheme) -> Result<()> {
                     || !theme.resources.latex_headers.is_empty()
                     || theme.resources.latex_template.is_some()
             }
+            "typst" | "beamer" | "revealjs" => !theme.tokens.is_empty(),
             "docx" => theme.resources.docx_reference_doc.is_some(),
             "pptx" => theme.resources.pptx_reference_doc.is_some(),
             _ => false,
@@ -627,14 +696,182 @@ pub fn materialize_theme_tokens(
     fs::create_dir_all(&directory)?;
     let css = directory.join("tokens.css");
     let latex = directory.join("tokens.tex");
+    let typst = directory.join("tokens.typ");
+    let beamer = directory.join("tokens-beamer.tex");
+    let revealjs = directory.join("tokens-revealjs.css");
     write_if_changed(&css, render_css_tokens(&theme.tokens).as_bytes())?;
     write_if_changed(&latex, render_latex_tokens(&theme.tokens).as_bytes())?;
+    write_if_changed(&typst, render_typst_tokens(&theme.tokens).as_bytes())?;
+    write_if_changed(&beamer, render_beamer_tokens(&theme.tokens).as_bytes())?;
+    write_if_changed(&revealjs, render_revealjs_tokens(&theme.tokens).as_bytes())?;
     Ok(GeneratedThemeAssets {
         css: Some(css),
         latex_header: Some(latex),
+        typst_header: Some(typst),
+        beamer_header: Some(beamer),
+        revealjs_css: Some(revealjs),
     })
 }
 
+/// 校验水印配置并补全默认颜色、不透明度与页面范围
+pub fn resolve_watermark(watermark: &WatermarkSection) -> Result<ResolvedWatermark> {
+    let text = watermark
+        .text
+        .as_deref()
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .ok_or_else(|| OmniDocError::Config("watermark.text must not be empty".to_string()))?;
+    let color = match watermark.color.as_deref() {
+        Some(color) => normalized_hex_color(color).ok_or_else(|| {
+            OmniDocError::Config(format!(
+                "watermark.color '{color}' must be a hex color such as '#999999'"
+            ))
+        })?,
+        None => DEFAULT_WATERMARK_COLOR.to_string(),
+    };
+    let opacity = watermark.opacity.unwrap_or(DEFAULT_WATERMARK_OPACITY);
+    if !(0.0..=1.0).contains(&opacity) || opacity == 0.0 {
+        return Err(OmniDocError::Config(format!(
+            "watermark.opacity {opacity} must be greater than 0 and at most 1"
+        )));
+    }
+    let first_page_only = match watermark
+        .pages
+        .as_deref()
+        .map(|pages| pages.trim().to_ascii_lowercase())
+        .as_deref()
+    {
+        None | Some("all") => false,
+        Some("first") => true,
+        Some(pages) => {
+            return Err(OmniDocError::Config(format!(
+                "Unsupported watermark.pages '{pages}'; choose all or first"
+            )))
+        }
+    };
+    Ok(ResolvedWatermark {
+        text: text.to_string(),
+        color,
+        opacity,
+        first_page_only,
+    })
+}
+
+/// 生成水印的 LaTeX、Typst 头文件与 CSS 覆盖层
+///
+/// Files live under `.omnidoc-cache/watermarks/<digest>/` next to the
+/// generated theme tokens, so each distinct watermark keeps its own copy.
+pub fn materialize_watermark(
+    watermark: &ResolvedWatermark,
+    project_root: &Path,
+) -> Result<GeneratedWatermarkAssets> {
+    let latex = render_latex_watermark(watermark);
+    let typst = render_typst_watermark(watermark);
+    let css = render_css_watermark(watermark);
+    let mut hasher = Sha256::new();
+    for content in [&latex, &typst, &css] {
+        hasher.update(content.as_bytes());
+    }
+    let digest = format!("{:x}", hasher.finalize());
+    let directory = project_root
+        .join(".omnidoc-cache")
+        .join("watermarks")
+        .join(&digest[..16]);
+    fs::create_dir_all(&directory)?;
+    let latex_header = directory.join("watermark.tex");
+    let typst_header = directory.join("watermark.typ");
+    let css_file = directory.join("watermark.css");
+    write_if_changed(&latex_header, latex.as_bytes())?;
+    write_if_changed(&typst_header, typst.as_bytes())?;
+    write_if_changed(&css_file, css.as_bytes())?;
+    Ok(GeneratedWatermarkAssets {
+        latex_header: Some(latex_header),
+        typst_header: Some(typst_header),
+        css: Some(css_file),
+    })
+}
+
+/// Font size that keeps long stamps such as "CONFIDENTIAL – Customer X" on
+/// the page diagonal, in centimetres for an A4 or Letter page.
+fn watermark_size_cm(text: &str) -> f64 {
+    (30.0 / text.chars().count().max(1) as f64).clamp(1.2, 5.0)
+}
+
+fn render_latex_watermark(watermark: &ResolvedWatermark) -> String {
+    let size = watermark_size_cm(&watermark.text);
+    let mut output = String::from("% Generated by OmniDoc watermark.\n");
+    output.push_str("\\usepackage{xcolor}\n");
+    output.push_str(&format!(
+        "\\definecolor{{OmniWatermarkBase}}{{HTML}}{{{}}}\n",
+        watermark.color
+    ));
+    output.push_str(&format!(
+        "\\colorlet{{OmniWatermark}}{{OmniWatermarkBase!{:.0}!white}}\n",
+        watermark.opacity * 100.0
+    ));
+    output.push_str("\\usepackage{draftwatermark}\n");
+    output.push_str(&format!(
+        "\\DraftwatermarkOptions{{text={{{}}},color=OmniWatermark,angle=45,fontsize={size:.1}cm,scale=1{}}}\n",
+        latex_escape(&watermark.text),
+        if watermark.first_page_only {
+            ",firstpageonly=true"
+        } else {
+            ""
+        }
+    ));
+    output
+}
+
+fn render_typst_watermark(watermark: &ResolvedWatermark) -> String {
+    let mark = format!(
+        "rotate(-45deg, text(size: {:.1}cm, weight: \"bold\", fill: rgb(\"#{}\").transparentize({:.0}%), \"{}\"))",
+        watermark_size_cm(&watermark.text),
+        watermark.color,
+        (1.0 - watermark.opacity) * 100.0,
+        css_escape(&watermark.text)
+    );
+    let background = if watermark.first_page_only {
+        format!("context if here().page() == 1 {{ {mark} }}")
+    } else {
+        mark
+    };
+    format!("// Generated by OmniDoc watermark.\n#set page(background: {background})\n")
+}
+
+fn render_css_watermark(watermark: &ResolvedWatermark) -> String {
+    // Fixed positioning repeats the stamp on every printed page; absolute
+    // positioning keeps it on the first screen and first page only.
+    let position = if watermark.first_page_only {
+        "absolute"
+    } else {
+        "fixed"
+    };
+    format!(
+        "/* Generated by OmniDoc watermark. */\nbody::after {{ content: \"{}\"; position: {position}; top: 50vh; left: 50vw; transform: translate(-50%, -50%) rotate(-45deg); color: #{}; opacity: {}; font-size: {:.1}vmin; font-weight: bold; white-space: nowrap; pointer-events: none; user-select: none; z-index: 2147483647; }}\n",
+        css_escape(&watermark.text),
+        watermark.color,
+        watermark.opacity,
+        watermark_size_cm(&watermark.text) * 3.0
+    )
+}
+
+fn latex_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(character);
+            }
+            _ => escaped.push(character),
+        }
+    }
+    escaped
+}
+
 fn render_css_tokens(tokens: &ThemeTokens) -> String {
     let mut declarations = Vec::new();
     for (name, value) in [
@@ -719,21 +956,7 @@ fn render_css_page(tokens: &ThemeTokens) -> String {
 fn render_latex_tokens(tokens: &ThemeTokens) -> String {
     let mut output = String::from("% Generated by OmniDoc theme API v1.\n");
     output.push_str("\\usepackage{xcolor}\n");
-    for (name, value) in [
-        ("Text", tokens.color.text.as_deref()),
-        ("Background", tokens.color.background.as_deref()),
-        ("Accent", tokens.color.accent.as_deref()),
-        ("Muted", tokens.color.muted.as_deref()),
-        ("Link", tokens.color.link.as_deref()),
-        ("Border", tokens.color.border.as_deref()),
-        ("CodeBackground", tokens.color.code_background.as_deref()),
-    ] {
-        if let Some(value) = value.and_then(normalized_hex_color) {
-            output.push_str(&format!(
-                "\\definecolor{{OmniTheme{name}}}{{HTML}}{{{value}}}\n"
-            ));
-        }
-    }
+    output.push_str(&render_latex_colors(tokens));
     if tokens.color.text.is_some() {
         output.push_str("\\AtBeginDocument{\\color{OmniThemeText}}\n");
     }
@@ -771,6 +994,226 @@ fn render_latex_tokens(tokens: &ThemeTokens) -> String {
     output
 }
 
+/// `\definecolor` lines for every set color token, named `OmniTheme<Name>`.
+fn render_latex_colors(tokens: &ThemeTokens) -> String {
+    let mut output = String::new();
+    for (name, value) in [
+        ("Text", tokens.color.text.as_deref()),
+        ("Background", tokens.color.background.as_deref()),
+        ("Accent", tokens.color.accent.as_deref()),
+        ("Muted", tokens.color.muted.as_deref()),
+        ("Link", tokens.color.link.as_deref()),
+        ("Border", tokens.color.border.as_deref()),
+        ("CodeBackground", tokens.color.code_background.as_deref()),
+    ] {
+        if let Some(value) = value.and_then(normalized_hex_color) {
+            output.push_str(&format!(
+                "\\definecolor{{OmniTheme{name}}}{{HTML}}{{{value}}}\n"
+            ));
+        }
+    }
+    output
+}
+
+/// 将主题令牌渲染为 Beamer 配色主题
+///
+/// Beamer loads xcolor itself and sizes its own frames, so only colors and
+/// fonts apply; page geometry and line spacing tokens are ignored.
+fn render_beamer_tokens(tokens: &ThemeTokens) -> String {
+    let mut output = String::from("% Generated by OmniDoc theme API v1.\n");
+    output.push_str(&render_latex_colors(tokens));
+    let color = &tokens.color;
+    let mut beamer_colors = Vec::new();
+    match (color.text.is_some(), color.background.is_some()) {
+        (true, true) => beamer_colors.push((
+            "normal text",
+            "fg=OmniThemeText,bg=OmniThemeBackground".to_string(),
+        )),
+        (true, false) => beamer_colors.push(("normal text", "fg=OmniThemeText".to_string())),
+        (false, true) => {
+            beamer_colors.push(("background canvas", "bg=OmniThemeBackground".to_string()))
+        }
+        (false, false) => {}
+    }
+    if color.accent.is_some() {
+        for element in ["structure", "frametitle", "title", "block title"] {
+            beamer_colors.push((element, "fg=OmniThemeAccent".to_string()));
+        }
+    }
+    if color.border.is_some() {
+        beamer_colors.push(("block body", "bg=OmniThemeBorder!25".to_string()));
+    }
+    if color.muted.is_some() {
+        for element in ["subtitle", "footline", "section in toc shaded"] {
+            beamer_colors.push((element, "fg=OmniThemeMuted".to_string()));
+        }
+    }
+    for (element, value) in beamer_colors {
+        output.push_str(&format!("\\setbeamercolor{{{element}}}{{{value}}}\n"));
+    }
+    let link = if color.link.is_some() {
+        Some("Link")
+    } else {
+        color.accent.as_ref().map(|_| "Accent")
+    };
+    if let Some(link) = link {
+        output.push_str(&format!(
+            "\\AtBeginDocument{{\\hypersetup{{colorlinks=true,linkcolor=OmniTheme{link},urlcolor=OmniTheme{link}}}}}\n"
+        ));
+    }
+    if let Some(body) = tokens.typography.body.as_deref() {
+        output.push_str(&format!(
+            "\\AtBeginDocument{{\\ifdefined\\setmainfont\\setmainfont{{{body}}}\\setsansfont{{{body}}}\\fi\\ifdefined\\setCJKmainfont\\setCJKmainfont{{{body}}}\\setCJKsansfont{{{body}}}\\fi}}\n"
+        ));
+    }
+    if let Some(mono) = tokens.typography.mono.as_deref() {
+        output.push_str(&format!(
+            "\\AtBeginDocument{{\\ifdefined\\setmonofont\\setmonofont{{{mono}}}\\fi}}\n"
+        ));
+    }
+    output
+}
+
+/// 将主题令牌渲染为 reveal.js 主题变量
+fn render_revealjs_tokens(tokens: &ThemeTokens) -> String {
+    let mut declarations = Vec::new();
+    for (name, value) in [
+        ("main-color", tokens.color.text.as_deref()),
+        ("background-color", tokens.color.background.as_deref()),
+        ("heading-color", tokens.color.accent.as_deref()),
+        (
+            "link-color",
+            tokens
+                .color
+                .link
+                .as_deref()
+                .or(tokens.color.accent.as_deref()),
+        ),
+        ("selection-background-color", tokens.color.accent.as_deref()),
+    ] {
+        if let Some(value) = value {
+            declarations.push(format!("  --r-{name}: {value};"));
+        }
+    }
+    for (name, value) in [
+        ("main-font", tokens.typography.body.as_deref()),
+        ("heading-font", tokens.typography.heading.as_deref()),
+        ("code-font", tokens.typography.mono.as_deref()),
+    ] {
+        if let Some(value) = value {
+            declarations.push(format!("  --r-{name}: \"{}\";", css_escape(value)));
+        }
+    }
+    if let Some(value) = tokens.typography.base_size_pt {
+        // reveal.js sizes text for a 960×700 slide; scale the document base
+        // size the way its bundled themes scale 16px body text to 42px.
+        declarations.push(format!("  --r-main-font-size: {:.0}px;", value * 3.5));
+    }
+    let mut output = String::from("/* Generated by OmniDoc theme API v1. */\n:root {\n");
+    output.push_str(&declarations.join("\n"));
+    output.push_str("\n}\n");
+    if tokens.color.background.is_some() {
+        output.push_str(".reveal-viewport { background-color: var(--r-background-color); }\n");
+    }
+    if let Some(value) = tokens.color.code_background.as_deref() {
+        output.push_str(&format!(
+            ".reveal pre code {{ background-color: {value}; }}\n"
+        ));
+    }
+    output
+}
+
+fn render_typst_tokens(tokens: &ThemeTokens) -> String {
+    let mut output = String::from("// Generated by OmniDoc theme API v1.\n");
+    let mut colors = BTreeMap::new();
+    for (name, value) in [
+        ("text", tokens.color.text.as_deref()),
+        ("background", tokens.color.background.as_deref()),
+        ("accent", tokens.color.accent.as_deref()),
+        ("muted", tokens.color.muted.as_deref()),
+        ("link", tokens.color.link.as_deref()),
+        ("border", tokens.color.border.as_deref()),
+        ("code-background", tokens.color.code_background.as_deref()),
+    ] {
+        if let Some(value) = value.and_then(normalized_hex_color) {
+            output.push_str(&format!("#let omnidoc-color-{name} = rgb(\"#{value}\")\n"));
+            colors.insert(name, format!("omnidoc-color-{name}"));
+        }
+    }
+    let mut text = Vec::new();
+    if let Some(color) = colors.get("text") {
+        text.push(format!("fill: {color}"));
+    }
+    if let Some(body) = tokens.typography.body.as_deref() {
+        text.push(format!("font: (\"{}\",)", css_escape(body)));
+    }
+    if let Some(size) = tokens.typography.base_size_pt {
+        text.push(format!("size: {size}pt"));
+    }
+    if !text.is_empty() {
+        output.push_str(&format!("#set text({})\n", text.join(", ")));
+    }
+    if let Some(line_height) = tokens.typography.line_height {
+        // Typst's default leading is 0.65em; scale it the way \linespread
+        // scales the LaTeX baseline skip.
+        output.push_str(&format!("#set par(leading: {:.3}em)\n", 0.65 * line_height));
+    }
+    let mut heading = Vec::new();
+    if let Some(font) = tokens.typography.heading.as_deref() {
+        heading.push(format!("font: (\"{}\",)", css_escape(font)));
+    }
+    if let Some(color) = colors.get("accent") {
+        heading.push(format!("fill: {color}"));
+    }
+    if !heading.is_empty() {
+        output.push_str(&format!(
+            "#show heading: set text({})\n",
+            heading.join(", ")
+        ));
+    }
+    if let Some(color) = colors.get("link").or_else(|| colors.get("accent")) {
+        output.push_str(&format!("#show link: set text(fill: {color})\n"));
+    }
+    if let Some(mono) = tokens.typography.mono.as_deref() {
+        output.push_str(&format!(
+            "#show raw: set text(font: (\"{}\",))\n",
+            css_escape(mono)
+        ));
+    }
+    if let Some(color) = colors.get("code-background") {
+        output.push_str(&format!(
+            "#show raw.where(block: true): set block(fill: {color}, inset: 8pt, radius: 2pt, width: 100%)\n"
+        ));
+    }
+    let mut page = Vec::new();
+    if let Some(size) = tokens.page.size.as_deref() {
+        let size = match size.to_ascii_lowercase().as_str() {
+            "letter" => "us-letter".to_string(),
+            other => other.to_string(),
+        };
+        page.push(format!("paper: \"{size}\""));
+    }
+    let margins = [
+        ("top", tokens.page.margin_top_mm),
+        ("right", tokens.page.margin_right_mm),
+        ("bottom", tokens.page.margin_bottom_mm),
+        ("left", tokens.page.margin_left_mm),
+    ]
+    .into_iter()
+    .filter_map(|(name, value)| value.map(|value| format!("{name}: {value}mm")))
+    .collect::<Vec<_>>();
+    if !margins.is_empty() {
+        page.push(format!("margin: ({})", margins.join(", ")));
+    }
+    if let Some(color) = colors.get("background") {
+        page.push(format!("fill: {color}"));
+    }
+    if !page.is_empty() {
+        output.push_str(&format!("#set page({})\n", page.join(", ")));
+    }
+    output
+}
+
 fn render_geometry_options(tokens: &ThemeTokens) -> String {
     let mut options = Vec::new();
     if let Some(size) = tokens.page.size.as_deref() {
@@ -871,6 +1314,12 @@ fn theme_outputs(theme: &ThemePackage) -> Vec<String> {
         outputs.push("pdf".to_string());
         outputs.push("latex".to_string());
     }
+    // Typst and slide decks are styled from tokens alone.
+    if !theme.tokens.is_empty() {
+        outputs.push("typst".to_string());
+        outputs.push("beamer".to_string());
+        outputs.push("revealjs".to_string());
+    }
     if theme.resources.docx_reference_doc.is_some() {
         outputs.push("docx".to_string());
     }
@@ -1104,11 +1553,12 @@ fn load_legacy_descriptor(library: &Path, path: &Path) -> Result<ThemeDescriptor
             recommended_for: legacy.recommended_for,
             compatibility: legacy.compatibility,
             extends: None,
+            search: legacy.search,
             outputs: None,
             resources,
             requirements: legacy.requirements,
             metadata: legacy.metadata,
-            tokens: ThemeTokens::default(),
+            tokens: legacy.tokens,
         },
         root: library.to_path_buf(),
         scope: PackageScope::Builtin,
@@ -1130,9 +1580,11 @@ fn library_root(config: &MergedConfig) -> PathBuf {
 #[cfg(test)]
 mod tests {
     use super::{
-        materialize_theme_tokens, merge_tokens, render_css_tokens, render_geometry_options,
-        resolve_theme_manifest, resolve_theme_request,
+        materialize_theme_tokens, materialize_watermark, merge_tokens, render_beamer_tokens,
+        render_css_tokens, render_geometry_options, render_revealjs_tokens, render_typst_tokens,
+        resolve_theme_manifest, resolve_theme_request, resolve_watermark,
     };
+    use crate::config::schema::WatermarkSection;
     use crate::config::MergedConfig;
     use crate::extensions::package::{
         PackageScope, ThemeColorTokens, ThemePageTokens, ThemeTokens, ThemeTypographyTokens,
@@ -1247,6 +1699,46 @@ html_css = ["styles/theme.css"]
         assert!(css.contains("--omnidoc-color-text: #202124"));
         assert!(css.contains("@page {\n  size: A4;\n  margin-top: 20mm;\n}"));
         assert_eq!(render_geometry_options(&tokens), "a4paper,top=20mm");
+        let typst = render_typst_tokens(&tokens);
+        assert!(typst.contains("#let omnidoc-color-text = rgb(\"#202124\")"));
+        assert!(typst.contains("#set text(fill: omnidoc-color-text)"));
+        assert!(typst.contains("#set page(paper: \"a4\", margin: (top: 20mm))"));
+    }
+
+    #[test]
+    fn slide_token_renderers_emit_a_beamer_color_theme_and_reveal_variables() {
+        let tokens = ThemeTokens {
+            color: ThemeColorTokens {
+                text: Some("#202939".to_string()),
+                accent: Some("#3451b2".to_string()),
+                ..Default::default()
+            },
+            typography: ThemeTypographyTokens {
+                heading: Some("Noto Sans CJK SC".to_string()),
+                base_size_pt: Some(12.0),
+                ..Default::default()
+            },
+            page: ThemePageTokens {
+                size: Some("a4".to_string()),
+                ..Default::default()
+            },
+        };
+
+        let beamer = render_beamer_tokens(&tokens);
+        assert!(beamer.contains("\\definecolor{OmniThemeAccent}{HTML}{3451B2}"));
+        assert!(beamer.contains("\\setbeamercolor{normal text}{fg=OmniThemeText}"));
+        assert!(beamer.contains("\\setbeamercolor{frametitle}{fg=OmniThemeAccent}"));
+        assert!(beamer.contains("urlcolor=OmniThemeAccent"));
+        assert!(!beamer.contains("xcolor"));
+        assert!(!beamer.contains("geometry"));
+
+        let reveal = render_revealjs_tokens(&tokens);
+        assert!(reveal.contains("--r-main-color: #202939;"));
+        assert!(reveal.contains("--r-heading-color: #3451b2;"));
+        assert!(reveal.contains("--r-link-color: #3451b2;"));
+        assert!(reveal.contains("--r-heading-font: \"Noto Sans CJK SC\";"));
+        assert!(reveal.contains("--r-main-font-size: 42px;"));
+        assert!(!reveal.contains("@page"));
     }
 
     #[test]
@@ -1591,4 +2083,57 @@ html_css = ["pandoc/css/shared.css"]
             selected.packages.last().unwrap().digest
         );
     }
+
+    #[test]
+    fn watermarks_render_for_latex_typst_and_css() {
+        let watermark = resolve_watermark(&WatermarkSection {
+            text: Some("Draft 50% & {final}".to_string()),
+            color: Some("#c00".to_string()),
+            opacity: Some(0.25),
+            pages: Some("first".to_string()),
+        })
+        .expect("watermark");
+        assert_eq!(watermark.color, "CC0000");
+        assert!(watermark.first_page_only);
+
+        let project = tempfile::tempdir().expect("project");
+        let generated = materialize_watermark(&watermark, project.path()).expect("generated");
+        let latex = fs::read_to_string(generated.latex_header.expect("LaTeX")).expect("LaTeX");
+        assert!(latex.contains("\\definecolor{OmniWatermarkBase}{HTML}{CC0000}"));
+        assert!(latex.contains("{OmniWatermarkBase!25!white}"));
+        assert!(latex.contains("text={Draft 50\\% \\& \\{final\\}}"));
+        assert!(latex.contains("firstpageonly=true"));
+        let typst = fs::read_to_string(generated.typst_header.expect("Typst")).expect("Typst");
+        assert!(typst.contains("context if here().page() == 1"));
+        assert!(typst.contains("transparentize(75%)"));
+        let css = fs::read_to_string(generated.css.expect("CSS")).expect("CSS");
+        assert!(css.contains("content: \"Draft 50% & {final}\""));
+        assert!(css.contains("position: absolute"));
+        assert!(css.contains("opacity: 0.25"));
+    }
+
+    #[test]
+    fn watermark_settings_are_checked() {
+        let section =
+            |color: Option<&str>, opacity: Option<f64>, pages: Option<&str>| WatermarkSection {
+                text: Some("DRAFT".to_string()),
+                color: color.map(str::to_string),
+                opacity,
+                pages: pages.map(str::to_string),
+            };
+        let defaults = resolve_watermark(&section(None, None, None)).expect("defaults");
+        assert_eq!(
+            (
+                defaults.color.as_str(),
+                defaults.opacity,
+                defaults.first_page_only
+            ),
+            ("999999", 0.3, false)
+        );
+        assert_eq!(defaults.faded_color(), "E0E0E0");
+        assert!(resolve_watermark(&section(Some("red"), None, None)).is_err());
+        assert!(resolve_watermark(&section(None, Some(1.5), None)).is_err());
+        assert!(resolve_watermark(&section(None, None, Some("odd"))).is_err());
+        assert!(resolve_watermark(&WatermarkSection::default()).is_err());
+    }
 }
@@ -0,0 +1,121 @@
+//! YAML front matter of Markdown sources.
+//!
+//! Citation lint, the terms lint and PDF metadata stamping only need a few
+//! top-level keys, so this reads the flat forms documents use for them
+//! instead of pulling in a YAML parser: scalars, `[a, b]` flow lists, and
+//! block lists whose items are scalars or maps with a `name`.
+
+/// The YAML metadata block at the start of a Markdown file, without its
+/// `---` fences. A leading byte order mark is ignored.
+pub fn block(content: &str) -> Option<&str> {
+    let content = content.trim_start_matches('\u{feff}');
+    let (first, rest) = content.split_once('\n')?;
+    if first.trim_end() != "---" {
+        return None;
+    }
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if matches!(line.trim_end(), "---" | "...") {
+            return Some(&rest[..offset]);
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// Raw value of a top-level key: the rest of its line plus any indented or
+/// list continuation lines, each trimmed.
+pub fn raw(yaml: &str, key: &str) -> Option<String> {
+    let mut lines = yaml.lines();
+    let first = lines.by_ref().find_map(|line| {
+        line.strip_prefix(key)?
+            .trim_start()
+            .strip_prefix(':')
+            .map(str::trim)
+    })?;
+    let mut value = first.to_string();
+    for line in lines.take_while(|line| line.is_empty() || line.starts_with([' ', '\t', '-'])) {
+        value.push('\n');
+        value.push_str(line.trim());
+    }
+    Some(value)
+}
+
+/// Items of a top-level key, with quotes removed: one for a scalar, and one
+/// per entry of a flow or block list. A block list item that is a map
+/// contributes its `name`.
+pub fn values(yaml: &str, key: &str) -> Vec<String> {
+    let Some(value) = raw(yaml, key) else {
+        return Vec::new();
+    };
+    let mut lines = value.lines();
+    let inline = lines.next().unwrap_or_default();
+    if let Some(list) = inline
+        .strip_prefix('[')
+        .and_then(|list| list.strip_suffix(']'))
+    {
+        return list.split(',').filter_map(scalar).collect();
+    }
+    if !inline.is_empty() && !matches!(inline, "|" | ">" | "|-" | ">-") {
+        return scalar(inline).into_iter().collect();
+    }
+    let mut values = Vec::new();
+    for item in lines {
+        if let Some(item) = item.strip_prefix("- ") {
+            let item = item.strip_prefix("name:").unwrap_or(item);
+            values.extend(scalar(item));
+        } else if let Some(name) = item.strip_prefix("name:") {
+            values.extend(scalar(name));
+        }
+    }
+    values
+}
+
+/// First item of a top-level key; see [`values`].
+pub fn value(yaml: &str, key: &str) -> Option<String> {
+    values(yaml, key).into_iter().next()
+}
+
+fn scalar(value: &str) -> Option<String> {
+    let value = value.trim();
+    let value = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .or_else(|| {
+            value
+                .strip_prefix('\'')
+                .and_then(|value| value.strip_suffix('\''))
+        })
+        .unwrap_or(value)
+        .trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{block, raw, value, values};
+
+    #[test]
+    fn scalars_flow_lists_and_block_lists_share_one_reading() {
+        let yaml = block(concat!(
+            "\u{feff}---\r\n",
+            "title: \"电源设计手册\"\n",
+            "lang : zh-CN\n",
+            "author:\n  - name: Li Wei\n    affiliation: lab\n  - 'Chen Jing'\n",
+            "keywords: [GaN, 'power stage']\n",
+            "bibliography:\n- refs.bib\n- \"more refs.json\"\n",
+            "nocite: |\n  @a, @b\n",
+            "---\n# Body\n",
+        ))
+        .expect("front matter");
+
+        assert_eq!(value(yaml, "title").as_deref(), Some("电源设计手册"));
+        assert_eq!(value(yaml, "lang").as_deref(), Some("zh-CN"));
+        assert_eq!(values(yaml, "author"), ["Li Wei", "Chen Jing"]);
+        assert_eq!(values(yaml, "keywords"), ["GaN", "power stage"]);
+        assert_eq!(values(yaml, "bibliography"), ["refs.bib", "more refs.json"]);
+        assert_eq!(raw(yaml, "nocite").as_deref(), Some("|\n@a, @b"));
+        assert!(values(yaml, "csl").is_empty());
+        assert_eq!(block("# No front matter\n---\n"), None);
+    }
+}
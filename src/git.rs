@@ -160,6 +160,16 @@ where
     result
 }
 
+/// 查找包含该路径的仓库并返回 HEAD 提交的完整哈希
+pub fn git_head_revision<P>(path: P) -> Option<String>
+where
+    P: AsRef<Path>,
+{
+    let repo = Repository::discover(path).ok()?;
+    let commit = repo.head().ok()?.peel_to_commit().ok()?;
+    Some(commit.id().to_string())
+}
+
 fn index_change(status: git2::Status) -> Option<&'static str> {
     if status.contains(git2::Status::INDEX_NEW) {
         Some("added")
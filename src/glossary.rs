@@ -0,0 +1,328 @@
+//! Glossary and acronym definitions shared by every output.
+//!
+//! Entries live in YAML or TOML files keyed by the reference key used in
+//! the text (`[+ADC]` or `[ADC]{.gls}`). A plain string value is the long
+//! form; a table may set `short` (defaults to the key), `long`, and
+//! `description`:
+//!
+//! ```yaml
+//! ADC: analog-to-digital converter
+//! SoC:
+//!   long: system on chip
+//!   description: An integrated circuit holding a whole computer.
+//! ```
+//!
+//! The YAML reader covers this flat shape line by line; values are single
+//! line scalars and are parsed as Markdown by Pandoc.
+
+use crate::config::MergedConfig;
+use crate::error::{OmniDocError, Result};
+use serde_json::{json, Map, Value};
+use std::path::{Path, PathBuf};
+
+/// Glossary files picked up when `[glossary] files` is not configured.
+pub const DEFAULT_FILES: [&str; 3] = ["glossary.yaml", "glossary.yml", "glossary.toml"];
+
+/// Metadata key carrying the entries to `glossary.lua`.
+pub const METADATA_KEY: &str = "omnidoc-glossary";
+/// Metadata key carrying the generated glossary chapter title.
+pub const TITLE_METADATA_KEY: &str = "omnidoc-glossary-title";
+
+/// One glossary entry, with its 1-based line in the definition file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlossaryEntry {
+    pub key: String,
+    pub short: Option<String>,
+    pub long: Option<String>,
+    pub description: Option<String>,
+    pub line: usize,
+}
+
+/// 项目的术语表文件：配置的 `[glossary] files`，或项目根目录下存在的默认文件
+pub fn default_files(project_root: &Path) -> Vec<String> {
+    DEFAULT_FILES
+        .iter()
+        .filter(|name| project_root.join(name).is_file())
+        .map(|name| name.to_string())
+        .collect()
+}
+
+/// Absolute paths of the glossary files configured for a build.
+pub fn files(project_path: &Path, config: &MergedConfig) -> Vec<PathBuf> {
+    config
+        .glossary_files
+        .iter()
+        .map(|file| project_path.join(file))
+        .collect()
+}
+
+/// Read every glossary file of the project, keeping the file each entry came from.
+pub fn load(project_path: &Path, config: &MergedConfig) -> Result<Vec<(PathBuf, GlossaryEntry)>> {
+    let mut entries = Vec::new();
+    for path in files(project_path, config) {
+        let content = std::fs::read_to_string(&path).map_err(|error| {
+            OmniDocError::Config(format!(
+                "cannot read glossary file {}: {error}",
+                path.display()
+            ))
+        })?;
+        let parsed = parse(&path, &content)
+            .map_err(|message| OmniDocError::Config(format!("{}: {message}", path.display())))?;
+        entries.extend(parsed.into_iter().map(|entry| (path.clone(), entry)));
+    }
+    Ok(entries)
+}
+
+/// 按扩展名解析术语表文件
+pub fn parse(path: &Path, content: &str) -> std::result::Result<Vec<GlossaryEntry>, String> {
+    let extension = path
+        .extension()
+        .and_then(|value| value.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    let entries = match extension.as_str() {
+        "yaml" | "yml" => parse_yaml(content)?,
+        "toml" => parse_toml(content)?,
+        _ => return Err("glossary files must be YAML or TOML".to_string()),
+    };
+    for entry in &entries {
+        if entry.long.is_none() && entry.description.is_none() {
+            return Err(format!(
+                "glossary entry '{}' needs a `long` form or a `description`",
+                entry.key
+            ));
+        }
+    }
+    Ok(entries)
+}
+
+fn parse_yaml(content: &str) -> std::result::Result<Vec<GlossaryEntry>, String> {
+    let mut entries: Vec<GlossaryEntry> = Vec::new();
+    for (line_index, line) in content.lines().enumerate() {
+        let line_no = line_index + 1;
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed == "---" {
+            continue;
+        }
+        let Some((name, value)) = trimmed.split_once(':') else {
+            return Err(format!("line {line_no}: expected `key: value`"));
+        };
+        let name = unquote(name.trim());
+        let value = unquote(value.trim());
+        if !line.starts_with([' ', '\t']) {
+            entries.push(GlossaryEntry {
+                key: name.to_string(),
+                short: None,
+                long: (!value.is_empty()).then(|| value.to_string()),
+                description: None,
+                line: line_no,
+            });
+            continue;
+        }
+        let Some(entry) = entries.last_mut() else {
+            return Err(format!(
+                "line {line_no}: field '{name}' is outside an entry"
+            ));
+        };
+        set_field(entry, name, value.to_string())
+            .map_err(|message| format!("line {line_no}: {message}"))?;
+    }
+    Ok(entries)
+}
+
+fn parse_toml(content: &str) -> std::result::Result<Vec<GlossaryEntry>, String> {
+    let table: toml::Table = toml::from_str(content).map_err(|error| error.to_string())?;
+    let mut entries = Vec::new();
+    for (key, value) in table {
+        let line = toml_key_line(content, &key);
+        let mut entry = GlossaryEntry {
+            key: key.clone(),
+            short: None,
+            long: None,
+            description: None,
+            line,
+        };
+        match value {
+            toml::Value::String(long) => entry.long = Some(long),
+            toml::Value::Table(fields) => {
+                for (name, value) in fields {
+                    let toml::Value::String(value) = value else {
+                        return Err(format!("glossary entry '{key}': {name} must be a string"));
+                    };
+                    set_field(&mut entry, &name, value)
+                        .map_err(|message| format!("glossary entry '{key}': {message}"))?;
+                }
+            }
+            _ => {
+                return Err(format!(
+                    "glossary entry '{key}' must be a string or a table"
+                ))
+            }
+        }
+        entries.push(entry);
+    }
+    entries.sort_by_key(|entry| entry.line);
+    Ok(entries)
+}
+
+fn set_field(
+    entry: &mut GlossaryEntry,
+    name: &str,
+    value: String,
+) -> std::result::Result<(), String> {
+    let value = (!value.is_empty()).then_some(value);
+    match name {
+        "short" => entry.short = value,
+        "long" => entry.long = value,
+        "description" => entry.description = value,
+        _ => {
+            return Err(format!(
+                "unknown glossary field '{name}'; use short, long, or description"
+            ))
+        }
+    }
+    Ok(())
+}
+
+/// Line of a top-level TOML key, as `[KEY]` or `KEY = …`.
+fn toml_key_line(content: &str, key: &str) -> usize {
+    content
+        .lines()
+        .position(|line| {
+            let line = line.trim();
+            let name = line
+                .strip_prefix('[')
+                .and_then(|rest| rest.split_once(']'))
+                .map(|(name, _)| name)
+                .or_else(|| line.split_once('=').map(|(name, _)| name));
+            name.is_some_and(|name| unquote(name.trim()) == key)
+        })
+        .map_or(1, |index| index + 1)
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+        .or_else(|| {
+            value
+                .strip_prefix('\'')
+                .and_then(|rest| rest.strip_suffix('\''))
+        })
+        .unwrap_or(value)
+}
+
+/// Pandoc metadata file content handing the entries to `glossary.lua`.
+pub fn metadata(entries: &[(PathBuf, GlossaryEntry)], title: Option<&str>) -> Value {
+    let mut glossary = Map::new();
+    for (_, entry) in entries {
+        let mut fields = Map::new();
+        fields.insert(
+            "short".to_string(),
+            Value::String(entry.short.clone().unwrap_or_else(|| entry.key.clone())),
+        );
+        for (name, value) in [("long", &entry.long), ("description", &entry.description)] {
+            if let Some(value) = value {
+                fields.insert(name.to_string(), Value::String(value.clone()));
+            }
+        }
+        glossary
+            .entry(entry.key.clone())
+            .or_insert(Value::Object(fields));
+    }
+    let mut metadata = json!({ METADATA_KEY: glossary });
+    if let Some(title) = title {
+        metadata[TITLE_METADATA_KEY] = Value::String(title.to_string());
+    }
+    metadata
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{metadata, parse, GlossaryEntry};
+    use std::path::{Path, PathBuf};
+
+    fn entry(
+        key: &str,
+        long: Option<&str>,
+        description: Option<&str>,
+        line: usize,
+    ) -> GlossaryEntry {
+        GlossaryEntry {
+            key: key.to_string(),
+            short: None,
+            long: long.map(str::to_string),
+            description: description.map(str::to_string),
+            line,
+        }
+    }
+
+    #[test]
+    fn yaml_and_toml_glossaries_share_one_entry_shape() {
+        let yaml = parse(
+            Path::new("glossary.yaml"),
+            concat!(
+                "# Acronyms\n",
+                "ADC: analog-to-digital converter\n",
+                "SoC:\n",
+                "  long: \"system on chip\"\n",
+                "  description: An integrated circuit holding a whole computer.\n",
+            ),
+        )
+        .expect("YAML glossary");
+        assert_eq!(
+            yaml,
+            vec![
+                entry("ADC", Some("analog-to-digital converter"), None, 2),
+                entry(
+                    "SoC",
+                    Some("system on chip"),
+                    Some("An integrated circuit holding a whole computer."),
+                    3
+                ),
+            ]
+        );
+
+        let toml = parse(
+            Path::new("glossary.toml"),
+            concat!(
+                "ADC = \"analog-to-digital converter\"\n\n",
+                "[SoC]\n",
+                "long = \"system on chip\"\n",
+                "description = \"An integrated circuit holding a whole computer.\"\n",
+            ),
+        )
+        .expect("TOML glossary");
+        assert_eq!(
+            toml,
+            yaml.iter()
+                .cloned()
+                .map(|mut entry| {
+                    entry.line = if entry.key == "ADC" { 1 } else { 3 };
+                    entry
+                })
+                .collect::<Vec<_>>()
+        );
+
+        assert!(parse(Path::new("glossary.yaml"), "ADC:\n  longer: x\n").is_err());
+        assert!(parse(Path::new("glossary.toml"), "[ADC]\nshort = \"ADC\"\n").is_err());
+        assert!(parse(Path::new("glossary.json"), "{}").is_err());
+    }
+
+    #[test]
+    fn metadata_defaults_the_short_form_to_the_key() {
+        let entries = vec![(
+            PathBuf::from("glossary.yaml"),
+            entry("ADC", Some("analog-to-digital converter"), None, 1),
+        )];
+        assert_eq!(
+            metadata(&entries, Some("Terms")),
+            serde_json::json!({
+                "omnidoc-glossary": {
+                    "ADC": {"short": "ADC", "long": "analog-to-digital converter"}
+                },
+                "omnidoc-glossary-title": "Terms"
+            })
+        );
+    }
+}
@@ -0,0 +1,131 @@
+//! Back-of-book index for Markdown projects.
+//!
+//! `[term]{.idx}` marks running text as an index entry. `term="Parent!Child"`
+//! files the entry under another or a nested heading, and `sort="…"` sets its
+//! sort key (for example the pinyin of a Chinese term). `[]{.idx term="…"}`
+//! adds an entry without visible text.
+//!
+//! The bundled `index.lua` filter writes `\index` commands for LaTeX and a
+//! linked term list for HTML and EPUB. PDF builds sort the entries with the
+//! configured index processor, which the LaTeX recorder runs between engine
+//! passes.
+
+use std::ffi::OsString;
+use std::path::Path;
+
+/// Metadata key carrying the generated index chapter title to `index.lua`.
+pub const TITLE_METADATA_KEY: &str = "omnidoc-index-title";
+
+/// Index processor turning the engine's `.idx` file into a sorted `.ind`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexProgram {
+    Makeindex,
+    Xindy,
+    /// makeindex replacement from CTeX that orders Chinese terms by pinyin.
+    Zhmakeindex,
+}
+
+impl IndexProgram {
+    /// 解析 `[index] program`
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "makeindex" => Some(Self::Makeindex),
+            "xindy" | "texindy" => Some(Self::Xindy),
+            "zhmakeindex" => Some(Self::Zhmakeindex),
+            _ => None,
+        }
+    }
+
+    /// The configured processor, or the default for the document language:
+    /// Chinese documents need pinyin ordering, which makeindex cannot do.
+    pub fn resolve(configured: Option<&str>, lang: Option<&str>) -> Option<Self> {
+        match configured {
+            Some(program) => Self::parse(program),
+            None if lang.is_some_and(|lang| lang.to_ascii_lowercase().starts_with("zh")) => {
+                Some(Self::Zhmakeindex)
+            }
+            None => Some(Self::Makeindex),
+        }
+    }
+
+    /// `[tools]` key and executable name.
+    pub fn tool(self) -> &'static str {
+        match self {
+            Self::Makeindex => "makeindex",
+            Self::Xindy => "xindy",
+            Self::Zhmakeindex => "zhmakeindex",
+        }
+    }
+
+    /// Command-line arguments sorting `idx` into `ind`.
+    pub fn arguments(self, idx: &Path, ind: &Path) -> Vec<OsString> {
+        let mut arguments: Vec<OsString> = match self {
+            Self::Makeindex => vec!["-q".into()],
+            Self::Xindy => vec![
+                "-q".into(),
+                "-M".into(),
+                "texindy".into(),
+                "-C".into(),
+                "utf8".into(),
+            ],
+            Self::Zhmakeindex => vec!["-q".into(), "-z".into(), "pinyin".into()],
+        };
+        arguments.push("-o".into());
+        arguments.push(ind.as_os_str().to_os_string());
+        arguments.push(idx.as_os_str().to_os_string());
+        arguments
+    }
+}
+
+/// Key under which spelling variants of one index term collide: case,
+/// hyphens, underscores and spacing are ignored, so "Make file",
+/// "makefile" and "Make-File" are the same term.
+pub fn spelling_key(term: &str) -> String {
+    term.chars()
+        .filter(|character| {
+            !character.is_whitespace() && !matches!(character, '-' | '_' | '‐' | '–')
+        })
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{spelling_key, IndexProgram};
+    use std::path::Path;
+
+    #[test]
+    fn chinese_documents_default_to_pinyin_ordering() {
+        assert_eq!(
+            IndexProgram::resolve(None, Some("zh-CN")),
+            Some(IndexProgram::Zhmakeindex)
+        );
+        assert_eq!(
+            IndexProgram::resolve(None, Some("en-US")),
+            Some(IndexProgram::Makeindex)
+        );
+        assert_eq!(
+            IndexProgram::resolve(None, None),
+            Some(IndexProgram::Makeindex)
+        );
+        assert_eq!(
+            IndexProgram::resolve(Some("xindy"), Some("zh-CN")),
+            Some(IndexProgram::Xindy)
+        );
+        assert_eq!(IndexProgram::resolve(Some("mendex"), None), None);
+        assert_eq!(
+            IndexProgram::Zhmakeindex.arguments(Path::new("doc.idx"), Path::new("doc.ind")),
+            ["-q", "-z", "pinyin", "-o", "doc.ind", "doc.idx"]
+                .map(std::ffi::OsString::from)
+                .to_vec()
+        );
+    }
+
+    #[test]
+    fn spelling_variants_share_one_key() {
+        assert_eq!(spelling_key("Make file"), spelling_key("make-file"));
+        assert_eq!(spelling_key("Makefile"), "makefile");
+        assert_eq!(spelling_key("中断 向量"), spelling_key("中断向量"));
+        assert_ne!(spelling_key("interrupt"), spelling_key("interrupts"));
+    }
+}
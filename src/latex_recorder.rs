@@ -1,13 +1,17 @@
 use crate::error::{OmniDocError, Result};
+use crate::index::IndexProgram;
 use crate::terminal;
 use std::collections::BTreeSet;
 use std::ffi::{OsStr, OsString};
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, ExitStatus};
 
 const REAL_ENGINE_ENV: &str = "OMNIDOC_LATEX_RECORDER_ENGINE";
 const DEPFILE_ENV: &str = "OMNIDOC_LATEX_RECORDER_DEPFILE";
+const SYNCTEX_ENV: &str = "OMNIDOC_LATEX_RECORDER_SYNCTEX";
+const INDEXER_ENV: &str = "OMNIDOC_LATEX_RECORDER_INDEXER";
+const INDEX_PROGRAM_ENV: &str = "OMNIDOC_LATEX_RECORDER_INDEX_PROGRAM";
 
 pub struct RecorderInvocation {
     pub wrapper: PathBuf,
@@ -57,6 +61,31 @@ pub fn prepare_wrapper(
     }))
 }
 
+/// Ask the wrapper to copy the engine's SyncTeX file and its `.tex` input into
+/// `directory`. Pandoc compiles in a temporary directory that is removed
+/// afterwards, so this is the only point where both are still available.
+pub fn synctex_environment(directory: &Path) -> (OsString, OsString) {
+    (
+        OsString::from(SYNCTEX_ENV),
+        directory.as_os_str().to_os_string(),
+    )
+}
+
+/// Ask the wrapper to sort the engine's `.idx` file with `program` after
+/// each pass, rerunning the engine when the sorted index changed.
+pub fn index_environment(program: IndexProgram, executable: &Path) -> Vec<(OsString, OsString)> {
+    vec![
+        (
+            OsString::from(INDEXER_ENV),
+            executable.as_os_str().to_os_string(),
+        ),
+        (
+            OsString::from(INDEX_PROGRAM_ENV),
+            OsString::from(program.tool()),
+        ),
+    ]
+}
+
 pub fn run_wrapper_from_env() -> Option<i32> {
     let invoked_as = std::env::args_os().next()?;
     if !is_recorder_invocation(&invoked_as) {
@@ -70,7 +99,7 @@ pub fn run_wrapper_from_env() -> Option<i32> {
     let real_engine = std::env::var_os(REAL_ENGINE_ENV)?;
     let depfile = std::env::var_os(DEPFILE_ENV)?;
     let args = std::env::args_os().skip(1).collect::<Vec<_>>();
-    let status = match Command::new(&real_engine).args(&args).status() {
+    let mut status = match Command::new(&real_engine).args(&args).status() {
         Ok(status) => status,
         Err(error) => {
             terminal::print_error(&OmniDocError::CommandExecution(format!(
@@ -82,6 +111,12 @@ pub fn run_wrapper_from_env() -> Option<i32> {
         }
     };
 
+    if status.success() && invoked_name != "tectonic" {
+        if let Some(rerun) = update_index(&real_engine, &args) {
+            status = rerun;
+        }
+    }
+
     if status.success() {
         let recorded = if invoked_name == "tectonic" {
             locate_tectonic_rules(&args).map(|context| {
@@ -103,10 +138,67 @@ pub fn run_wrapper_from_env() -> Option<i32> {
                 "LaTeX recorder could not write its dependency file\n{error}"
             ));
         }
+        if let Some(directory) = std::env::var_os(SYNCTEX_ENV) {
+            if let Err(error) = preserve_synctex(&args, Path::new(&directory)) {
+                terminal::warning(format!(
+                    "LaTeX recorder could not preserve SyncTeX data\n{error}"
+                ));
+            }
+        }
     }
     Some(status.code().unwrap_or(1))
 }
 
+/// Sort the index written by the last pass and, when the sorted index
+/// changed, run the engine once more so `\printindex` includes it. Pandoc
+/// decides on reruns from the LaTeX log alone and never notices the index.
+fn update_index(real_engine: &OsStr, args: &[OsString]) -> Option<ExitStatus> {
+    let indexer = std::env::var_os(INDEXER_ENV)?;
+    let program = IndexProgram::parse(&std::env::var(INDEX_PROGRAM_ENV).ok()?)?;
+    let (fls, _) = locate_fls(args)?;
+    let idx = fls.with_extension("idx");
+    if !idx.is_file() {
+        return None;
+    }
+    let ind = idx.with_extension("ind");
+    let previous = fs::read(&ind).ok();
+    match Command::new(&indexer)
+        .args(program.arguments(&idx, &ind))
+        .output()
+    {
+        Ok(output) if output.status.success() => {}
+        Ok(output) => {
+            terminal::warning(format!(
+                "{} could not sort the index\n{}",
+                program.tool(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+            return None;
+        }
+        Err(error) => {
+            terminal::warning(format!(
+                "LaTeX recorder could not execute {}\n{error}",
+                Path::new(&indexer).display()
+            ));
+            return None;
+        }
+    }
+    if fs::read(&ind).ok() == previous {
+        return None;
+    }
+    match Command::new(real_engine).args(args).status() {
+        Ok(status) => Some(status),
+        Err(error) => {
+            terminal::print_error(&OmniDocError::CommandExecution(format!(
+                "LaTeX recorder could not execute {}\n{}",
+                Path::new(real_engine).display(),
+                error
+            )));
+            None
+        }
+    }
+}
+
 fn is_recorder_invocation(program: &OsStr) -> bool {
     Path::new(program)
         .file_name()
@@ -405,6 +497,67 @@ fn locate_fls(args: &[OsString]) -> Option<(PathBuf, PathBuf)> {
     ))
 }
 
+fn preserve_synctex(args: &[OsString], directory: &Path) -> Result<()> {
+    let Some((synctex, input)) = locate_synctex(args) else {
+        return Ok(());
+    };
+    if !synctex.is_file() {
+        return Ok(());
+    }
+    fs::create_dir_all(directory)?;
+    for source in [&synctex, &input] {
+        if let Some(name) = source.file_name() {
+            fs::copy(source, directory.join(name))?;
+        }
+    }
+    Ok(())
+}
+
+/// SyncTeX file and main input of a LaTeX or Tectonic invocation.
+fn locate_synctex(args: &[OsString]) -> Option<(PathBuf, PathBuf)> {
+    let mut output_directory = None;
+    let mut jobname = None;
+    let mut input = None;
+    let mut index = 0;
+    while index < args.len() {
+        let argument = args[index].to_string_lossy();
+        if matches!(
+            argument.as_ref(),
+            "-output-directory" | "--output-directory" | "--outdir" | "-o"
+        ) {
+            index += 1;
+            output_directory = args.get(index).map(PathBuf::from);
+        } else if let Some(value) = argument
+            .strip_prefix("-output-directory=")
+            .or_else(|| argument.strip_prefix("--output-directory="))
+            .or_else(|| argument.strip_prefix("--outdir="))
+        {
+            output_directory = Some(PathBuf::from(value));
+        } else if argument == "-jobname" || argument == "--jobname" {
+            index += 1;
+            jobname = args.get(index).cloned();
+        } else if let Some(value) = argument
+            .strip_prefix("-jobname=")
+            .or_else(|| argument.strip_prefix("--jobname="))
+        {
+            jobname = Some(OsString::from(value));
+        } else if Path::new(args[index].as_os_str())
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("tex"))
+        {
+            input = Some(PathBuf::from(&args[index]));
+        }
+        index += 1;
+    }
+
+    let input = input?;
+    let output_directory = output_directory.unwrap_or_else(|| PathBuf::from("."));
+    let stem = jobname.or_else(|| input.file_stem().map(|value| value.to_os_string()))?;
+    let mut synctex = stem;
+    synctex.push(".synctex.gz");
+    Some((output_directory.join(synctex), input))
+}
+
 fn supports_recorder_engine(engine_name: &OsStr) -> bool {
     let stem = Path::new(engine_name)
         .file_stem()
@@ -452,8 +605,8 @@ fn install_wrapper_executable(executable: &Path, wrapper: &Path) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::{
-        is_recorder_invocation, locate_fls, parse_makefile_words, write_depfile_from_fls,
-        write_depfile_from_tectonic_rules,
+        is_recorder_invocation, locate_fls, locate_synctex, parse_makefile_words,
+        write_depfile_from_fls, write_depfile_from_tectonic_rules,
     };
     use std::ffi::OsString;
     use std::fs;
@@ -602,6 +755,33 @@ mod tests {
         assert_eq!(fls, output.join("input.fls"));
     }
 
+    #[test]
+    fn locates_synctex_output_of_latex_and_tectonic_runs() {
+        let args = [
+            OsString::from("-synctex=1"),
+            OsString::from("-output-directory"),
+            OsString::from("/tmp/render"),
+            OsString::from("input.tex"),
+        ];
+        let (synctex, input) = locate_synctex(&args).expect("LaTeX SyncTeX output");
+        assert_eq!(
+            synctex,
+            std::path::Path::new("/tmp/render/input.synctex.gz")
+        );
+        assert_eq!(input, std::path::Path::new("input.tex"));
+
+        let args = [
+            OsString::from("--outdir=/tmp/render"),
+            OsString::from("--synctex"),
+            OsString::from("/tmp/render/input.tex"),
+        ];
+        let (synctex, _) = locate_synctex(&args).expect("Tectonic SyncTeX output");
+        assert_eq!(
+            synctex,
+            std::path::Path::new("/tmp/render/input.synctex.gz")
+        );
+    }
+
     #[test]
     fn normalizes_and_filters_fls_inputs() {
         let root = tempfile::tempdir().expect("recorder fixture");
@@ -1,4 +1,6 @@
+pub mod bibliography;
 pub mod build;
+pub mod cache;
 pub mod cli;
 pub mod config;
 pub mod constants;
@@ -10,13 +12,23 @@ pub mod epub;
 pub mod error;
 pub mod executor;
 pub mod extensions;
+pub mod front_matter;
 pub mod fs;
 pub mod fs_abstract;
 pub mod git;
+pub mod glossary;
+pub mod index;
 pub mod latex_recorder;
+pub mod links;
+pub mod lint;
+pub mod pdf;
+pub mod preview;
 pub mod project_tools;
 pub mod terminal;
+pub mod terms;
 pub mod utils;
+pub mod warm;
 pub mod webreq;
+pub mod xml;
 
 pub use error::{OmniDocError, Result};
@@ -0,0 +1,540 @@
+//! External link checking for `omnidoc check links`.
+//!
+//! HTTP(S) links are collected from Markdown prose, LaTeX sources and BibTeX
+//! files, then checked concurrently: a `HEAD` request first, and a `GET`
+//! when the server answers `HEAD` with anything but success or "not found".
+//! Requests to one host are spaced by `[links] rate_limit`, and outcomes are
+//! cached in `.omnidoc-cache/links.json` for `[links] cache_ttl` hours, so
+//! repeated runs only re-check stale links.
+
+use crate::config::MergedConfig;
+use crate::error::{OmniDocError, Result};
+use crate::project_tools::{
+    display_relative, markdown_prose_lines, project_cache_dir, source_files, strip_latex_comment,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const CACHE_FILE: &str = "links.json";
+const DEFAULT_TIMEOUT_SECS: u64 = 10;
+const DEFAULT_CONCURRENCY: usize = 8;
+const DEFAULT_RATE_LIMIT: f64 = 2.0;
+const DEFAULT_CACHE_TTL_HOURS: u64 = 24;
+/// Longest wait between two requests to one host, however low `rate_limit` is.
+const MAX_HOST_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// A place in the project sources where a link appears.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LinkLocation {
+    pub path: String,
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkState {
+    Ok,
+    /// The server answered with an error status.
+    Broken,
+    /// No answer: DNS, connection, TLS or timeout failures.
+    Error,
+    /// Broken or unreachable, but matched by `[links] allow`.
+    Allowed,
+    /// Matched by `[links] ignore` and never requested.
+    Ignored,
+}
+
+/// Result of one link, with every place it appears.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LinkResult {
+    pub url: String,
+    pub state: LinkState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub cached: bool,
+    pub locations: Vec<LinkLocation>,
+}
+
+impl LinkResult {
+    pub fn failed(&self) -> bool {
+        matches!(self.state, LinkState::Broken | LinkState::Error)
+    }
+
+    /// HTTP status or error text for messages.
+    pub fn detail(&self) -> String {
+        match (&self.status, &self.error) {
+            (Some(status), _) => {
+                let reason = reqwest::StatusCode::from_u16(*status)
+                    .ok()
+                    .and_then(|status| status.canonical_reason());
+                match reason {
+                    Some(reason) => format!("HTTP {status} {reason}"),
+                    None => format!("HTTP {status}"),
+                }
+            }
+            (None, Some(error)) => error.clone(),
+            (None, None) => String::new(),
+        }
+    }
+}
+
+/// Checked outcome of a URL, as stored in the cache.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Outcome {
+    state: LinkState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct LinkCache {
+    links: BTreeMap<String, CachedOutcome>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedOutcome {
+    checked_at: u64,
+    #[serde(flatten)]
+    outcome: Outcome,
+}
+
+/// Link check settings read from `[links]`.
+#[derive(Debug, Clone)]
+pub struct LinkCheckOptions {
+    pub timeout: Duration,
+    pub concurrency: usize,
+    /// Minimum spacing between two requests to the same host.
+    pub host_interval: Duration,
+    pub cache_ttl: Duration,
+}
+
+impl LinkCheckOptions {
+    pub fn from_config(config: &MergedConfig) -> Self {
+        let rate_limit = config
+            .links_rate_limit
+            .filter(|rate| *rate > 0.0)
+            .unwrap_or(DEFAULT_RATE_LIMIT);
+        Self {
+            timeout: Duration::from_secs(config.links_timeout.unwrap_or(DEFAULT_TIMEOUT_SECS)),
+            concurrency: config
+                .links_concurrency
+                .unwrap_or(DEFAULT_CONCURRENCY)
+                .max(1),
+            host_interval: Duration::try_from_secs_f64(1.0 / rate_limit)
+                .map_or(MAX_HOST_INTERVAL, |interval| {
+                    interval.min(MAX_HOST_INTERVAL)
+                }),
+            cache_ttl: Duration::from_secs(
+                config
+                    .links_cache_ttl
+                    .unwrap_or(DEFAULT_CACHE_TTL_HOURS)
+                    .saturating_mul(3600),
+            ),
+        }
+    }
+}
+
+/// HTTP(S) links in the project's Markdown, LaTeX and BibTeX sources, with
+/// every place each one appears. Markdown code spans and fenced blocks and
+/// LaTeX comments are skipped.
+pub fn collect_links(project_path: &Path) -> BTreeMap<String, Vec<LinkLocation>> {
+    let url_re = regex::Regex::new(r#"https?://[^\s<>"'`{}\[\]\\|^]+"#).expect("link URL regex");
+    let code_span_re = regex::Regex::new(r"`[^`]*`").expect("code span regex");
+    let mut links: BTreeMap<String, Vec<LinkLocation>> = BTreeMap::new();
+    let mut files = source_files(project_path);
+    files.sort();
+    for file in files {
+        let extension = file
+            .extension()
+            .and_then(|value| value.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let Ok(content) = fs::read_to_string(&file) else {
+            continue;
+        };
+        let lines: Vec<(usize, String)> = match extension.as_str() {
+            "md" | "markdown" => markdown_prose_lines(&content)
+                .map(|(line_no, line)| (line_no, code_span_re.replace_all(line, "").into_owned()))
+                .collect(),
+            "tex" | "sty" | "cls" => content
+                .lines()
+                .enumerate()
+                .map(|(index, line)| (index + 1, strip_latex_comment(line).to_string()))
+                .collect(),
+            "bib" => content
+                .lines()
+                .enumerate()
+                .map(|(index, line)| (index + 1, line.to_string()))
+                .collect(),
+            _ => continue,
+        };
+        let rel = display_relative(project_path, &file);
+        for (line_no, line) in lines {
+            for found in url_re.find_iter(&line) {
+                let url = trim_url(found.as_str());
+                if url.contains("://") && !url.ends_with("://") {
+                    links
+                        .entry(url.to_string())
+                        .or_default()
+                        .push(LinkLocation {
+                            path: rel.clone(),
+                            line: line_no,
+                        });
+                }
+            }
+        }
+    }
+    links
+}
+
+/// Drop sentence punctuation and unbalanced closing parentheses, as in
+/// `(see https://example.com/a_(b)).`
+fn trim_url(url: &str) -> &str {
+    let punctuation = ['.', ',', ';', ':', '!', '?', '*', '_', '~'];
+    let mut url = url.trim_end_matches(punctuation);
+    while url.ends_with(')') && url.matches(')').count() > url.matches('(').count() {
+        url = url[..url.len() - 1].trim_end_matches(punctuation);
+    }
+    url
+}
+
+/// Check every external link of the project. `use_cache` false re-checks
+/// links with a fresh cached result; the cache is updated either way.
+pub fn check_links(
+    project_path: &Path,
+    config: &MergedConfig,
+    use_cache: bool,
+) -> Result<Vec<LinkResult>> {
+    let patterns = |key: &str, patterns: &[String]| {
+        patterns
+            .iter()
+            .map(|pattern| {
+                regex::Regex::new(pattern).map_err(|error| {
+                    OmniDocError::Config(format!("Invalid {key} pattern '{pattern}': {error}"))
+                })
+            })
+            .collect::<Result<Vec<_>>>()
+    };
+    let ignore = patterns("links.ignore", &config.links_ignore)?;
+    let allow = patterns("links.allow", &config.links_allow)?;
+    let options = LinkCheckOptions::from_config(config);
+    let links = collect_links(project_path);
+
+    let cache_path = cache_file(project_path);
+    let now = unix_now();
+    let ttl = options.cache_ttl.as_secs();
+    let mut cache = if ttl == 0 {
+        LinkCache::default()
+    } else {
+        load_cache(&cache_path)
+    };
+    cache
+        .links
+        .retain(|_, cached| now.saturating_sub(cached.checked_at) < ttl);
+
+    let pending = links
+        .keys()
+        .filter(|url| !ignore.iter().any(|pattern| pattern.is_match(url)))
+        .filter(|url| !use_cache || !cache.links.contains_key(*url))
+        .cloned()
+        .collect::<Vec<_>>();
+    let checked = check_urls(pending, &options)?;
+
+    let mut results = Vec::new();
+    for (url, locations) in links {
+        if ignore.iter().any(|pattern| pattern.is_match(&url)) {
+            results.push(LinkResult {
+                url,
+                state: LinkState::Ignored,
+                status: None,
+                error: None,
+                cached: false,
+                locations,
+            });
+            continue;
+        }
+        let (outcome, cached) = match checked.get(&url) {
+            Some(outcome) => (outcome.clone(), false),
+            None => match cache.links.get(&url) {
+                Some(cached) => (cached.outcome.clone(), true),
+                None => continue,
+            },
+        };
+        let tolerated =
+            outcome.state != LinkState::Ok && allow.iter().any(|pattern| pattern.is_match(&url));
+        results.push(LinkResult {
+            state: if tolerated {
+                LinkState::Allowed
+            } else {
+                outcome.state
+            },
+            status: outcome.status,
+            error: outcome.error,
+            cached,
+            locations,
+            url,
+        });
+    }
+
+    if ttl > 0 && !checked.is_empty() {
+        for (url, outcome) in checked {
+            cache.links.insert(
+                url,
+                CachedOutcome {
+                    checked_at: now,
+                    outcome,
+                },
+            );
+        }
+        save_cache(&cache_path, &cache)?;
+    }
+    Ok(results)
+}
+
+fn cache_file(project_path: &Path) -> PathBuf {
+    project_cache_dir(project_path).join(CACHE_FILE)
+}
+
+fn load_cache(path: &Path) -> LinkCache {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_cache(path: &Path, cache: &LinkCache) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let content = serde_json::to_string_pretty(cache)
+        .map_err(|error| OmniDocError::Other(error.to_string()))?;
+    crate::utils::fs::atomic_write(path, content)?;
+    Ok(())
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+/// Check `urls` on `options.concurrency` worker threads sharing one client
+/// and one per-host limiter.
+fn check_urls(urls: Vec<String>, options: &LinkCheckOptions) -> Result<HashMap<String, Outcome>> {
+    if urls.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let client = reqwest::blocking::Client::builder()
+        .timeout(options.timeout)
+        .user_agent(concat!(
+            "omnidoc/",
+            env!("CARGO_PKG_VERSION"),
+            " link-check"
+        ))
+        .build()
+        .map_err(|error| OmniDocError::Other(format!("cannot create HTTP client: {error}")))?;
+    let workers = options.concurrency.min(urls.len());
+    let queue = Mutex::new(urls.into_iter());
+    let limiter = HostLimiter::new(options.host_interval);
+    let results = Mutex::new(HashMap::new());
+    std::thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let Some(url) = queue.lock().unwrap_or_else(PoisonError::into_inner).next() else {
+                    break;
+                };
+                let outcome = check_url(&client, &limiter, &url);
+                results
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .insert(url, outcome);
+            });
+        }
+    });
+    Ok(results.into_inner().unwrap_or_else(PoisonError::into_inner))
+}
+
+fn check_url(client: &reqwest::blocking::Client, limiter: &HostLimiter, url: &str) -> Outcome {
+    let host = reqwest::Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .unwrap_or_default();
+    limiter.wait(&host);
+    let status = match client.head(url).send() {
+        Ok(response) => response.status(),
+        Err(error) => return failure(error),
+    };
+    if status.is_success() || matches!(status.as_u16(), 404 | 410) {
+        return answered(status);
+    }
+    // Many servers reject or mishandle HEAD; only a GET failure counts.
+    limiter.wait(&host);
+    match client.get(url).send() {
+        Ok(response) => answered(response.status()),
+        Err(error) => failure(error),
+    }
+}
+
+fn answered(status: reqwest::StatusCode) -> Outcome {
+    Outcome {
+        state: if status.is_success() {
+            LinkState::Ok
+        } else {
+            LinkState::Broken
+        },
+        status: Some(status.as_u16()),
+        error: None,
+    }
+}
+
+fn failure(error: reqwest::Error) -> Outcome {
+    let message = if error.is_timeout() {
+        "request timed out".to_string()
+    } else {
+        let error = error.without_url();
+        let mut message = error.to_string();
+        let mut source = std::error::Error::source(&error);
+        while let Some(cause) = source {
+            message.push_str(": ");
+            message.push_str(&cause.to_string());
+            source = cause.source();
+        }
+        message
+    };
+    Outcome {
+        state: LinkState::Error,
+        status: None,
+        error: Some(message),
+    }
+}
+
+/// Spaces requests to one host at least `interval` apart across workers.
+struct HostLimiter {
+    interval: Duration,
+    next: Mutex<HashMap<String, Instant>>,
+}
+
+impl HostLimiter {
+    fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Block until the next request to `host` may start.
+    fn wait(&self, host: &str) {
+        let delay = {
+            let mut next = self.next.lock().unwrap_or_else(PoisonError::into_inner);
+            let now = Instant::now();
+            let slot = next
+                .get(host)
+                .copied()
+                .filter(|slot| *slot > now)
+                .unwrap_or(now);
+            next.insert(host.to_string(), slot + self.interval);
+            slot - now
+        };
+        if !delay.is_zero() {
+            std::thread::sleep(delay);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        collect_links, trim_url, HostLimiter, LinkCheckOptions, LinkLocation, MAX_HOST_INTERVAL,
+    };
+    use crate::config::MergedConfig;
+    use std::fs;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn links_come_from_prose_latex_and_bibliographies_only() {
+        let project = tempfile::tempdir().expect("project tempdir");
+        fs::write(
+            project.path().join("main.md"),
+            concat!(
+                "---\nurl: https://front.example/matter\n---\n",
+                "See [docs](https://docs.example/guide), <https://auto.example/>.\n",
+                "Literal `https://code.example/` and (https://en.example/wiki/A_(B)).\n",
+                "```\ncurl https://fence.example/\n```\n",
+            ),
+        )
+        .expect("Markdown");
+        fs::write(
+            project.path().join("paper.tex"),
+            "\\href{https://docs.example/guide}{Guide} % https://comment.example/\n",
+        )
+        .expect("LaTeX");
+        fs::write(
+            project.path().join("refs.bib"),
+            "@misc{spec,\n  url = {https://spec.example/v1},\n}\n",
+        )
+        .expect("BibTeX");
+
+        let links = collect_links(project.path());
+        assert_eq!(
+            links.keys().map(String::as_str).collect::<Vec<_>>(),
+            [
+                "https://auto.example/",
+                "https://docs.example/guide",
+                "https://en.example/wiki/A_(B)",
+                "https://spec.example/v1",
+            ]
+        );
+        assert_eq!(
+            links["https://docs.example/guide"],
+            [
+                LinkLocation {
+                    path: "main.md".to_string(),
+                    line: 4
+                },
+                LinkLocation {
+                    path: "paper.tex".to_string(),
+                    line: 1
+                },
+            ]
+        );
+        assert_eq!(trim_url("https://a.example/x)."), "https://a.example/x");
+    }
+
+    #[test]
+    fn host_limiter_spaces_requests_to_one_host() {
+        let limiter = HostLimiter::new(Duration::from_millis(40));
+        let started = Instant::now();
+        limiter.wait("a.example");
+        limiter.wait("b.example");
+        assert!(started.elapsed() < Duration::from_millis(40));
+        limiter.wait("a.example");
+        limiter.wait("a.example");
+        assert!(started.elapsed() >= Duration::from_millis(80));
+    }
+
+    #[test]
+    fn extreme_rate_limits_and_cache_ttls_do_not_overflow() {
+        let options = LinkCheckOptions::from_config(&MergedConfig {
+            links_rate_limit: Some(f64::MIN_POSITIVE),
+            links_cache_ttl: Some(u64::MAX),
+            ..Default::default()
+        });
+        assert_eq!(options.host_interval, MAX_HOST_INTERVAL);
+        assert_eq!(options.cache_ttl, Duration::from_secs(u64::MAX));
+
+        let options = LinkCheckOptions::from_config(&MergedConfig {
+            links_rate_limit: Some(f64::INFINITY),
+            ..Default::default()
+        });
+        assert_eq!(options.host_interval, Duration::ZERO);
+    }
+}
@@ -0,0 +1,868 @@
+//! Source lint engine behind `omnidoc check lint` and the pre-build checks.
+//!
+//! Markdown files are linted on Pandoc's JSON AST (`commonmark_x+sourcepos`),
+//! so multi-line and reference-style links, attributes on headings and
+//! `.include` blocks are seen the way Pandoc reads them, each with the line
+//! and column of its `data-pos` attribute. The extracted references are
+//! cached by file content in `.omnidoc-cache/lint.json`. Without a working
+//! Pandoc the per-line scanner takes over. LaTeX sources are always scanned
+//! line by line.
+//!
+//! Every finding belongs to a rule in [`RULES`]. `[lint.rules]` changes a
+//! rule's severity or turns it off, and comments silence rules in place:
+//!
+//! ```markdown
+//! <!-- omnidoc-lint-disable-next-line missing_image -->
+//! <!-- omnidoc-lint-disable missing_link_target missing_include -->
+//! <!-- omnidoc-lint-enable -->
+//! ```
+//!
+//! LaTeX and BibTeX sources use `% omnidoc-lint-disable-next-line …` and
+//! friends. A comment without rule ids applies to every rule.
+
+use crate::build::executor::BuildExecutor;
+use crate::config::MergedConfig;
+use crate::error::OmniDocError;
+use crate::project_tools::{markdown_prose_lines, project_cache_dir, IssueSeverity, ProjectIssue};
+use crate::terminal;
+use crate::utils;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+pub const MISSING_IMAGE: &str = "missing_image";
+pub const MISSING_LINK_TARGET: &str = "missing_link_target";
+pub const MISSING_INCLUDE: &str = "missing_include";
+pub const MISSING_LATEX_RESOURCE: &str = "missing_latex_resource";
+pub const UNRESOLVED_CROSSREF: &str = "unresolved_crossref";
+pub const DUPLICATE_CROSSREF_LABEL: &str = "duplicate_crossref_label";
+pub const UNUSED_CROSSREF_LABEL: &str = "unused_crossref_label";
+pub const UNDEFINED_CITATION: &str = "undefined_citation";
+pub const DUPLICATE_CITATION: &str = "duplicate_citation";
+pub const UNUSED_CITATION: &str = "unused_citation";
+pub const INCOMPLETE_CITATION: &str = "incomplete_citation";
+pub const FORBIDDEN_TERM: &str = "forbidden_term";
+pub const MISSPELLING: &str = "misspelling";
+pub const DUPLICATE_GLOSSARY_KEY: &str = "duplicate_glossary_key";
+pub const UNDEFINED_GLOSSARY_TERM: &str = "undefined_glossary_term";
+pub const UNUSED_GLOSSARY_TERM: &str = "unused_glossary_term";
+pub const INCONSISTENT_INDEX_TERM: &str = "inconsistent_index_term";
+pub const HIDDEN_VARIANT_LABEL: &str = "hidden_variant_label";
+
+/// One registered lint rule.
+#[derive(Debug)]
+pub struct Rule {
+    pub id: &'static str,
+    /// Severity when `[lint.rules]` does not set one.
+    pub severity: IssueSeverity,
+    pub description: &'static str,
+}
+
+/// Every rule the source lints report, in documentation order.
+pub const RULES: &[Rule] = &[
+    Rule {
+        id: MISSING_IMAGE,
+        severity: IssueSeverity::Warning,
+        description: "Markdown image whose local file does not exist",
+    },
+    Rule {
+        id: MISSING_LINK_TARGET,
+        severity: IssueSeverity::Warning,
+        description: "Markdown link to a local file that does not exist",
+    },
+    Rule {
+        id: MISSING_INCLUDE,
+        severity: IssueSeverity::Warning,
+        description:
+            "`include=`/`include-code=` attribute or `.include` block naming a missing file",
+    },
+    Rule {
+        id: MISSING_LATEX_RESOURCE,
+        severity: IssueSeverity::Warning,
+        description: "\\input, \\include, \\includegraphics or bibliography naming a missing file",
+    },
+    Rule {
+        id: UNRESOLVED_CROSSREF,
+        severity: IssueSeverity::Warning,
+        description: "`@fig:id`-style cross-reference without a matching label",
+    },
+    Rule {
+        id: DUPLICATE_CROSSREF_LABEL,
+        severity: IssueSeverity::Warning,
+        description: "Cross-reference label declared more than once",
+    },
+    Rule {
+        id: UNUSED_CROSSREF_LABEL,
+        severity: IssueSeverity::Info,
+        description: "Cross-reference label nothing references",
+    },
+    Rule {
+        id: UNDEFINED_CITATION,
+        severity: IssueSeverity::Warning,
+        description: "Citation key missing from the document's bibliographies",
+    },
+    Rule {
+        id: DUPLICATE_CITATION,
+        severity: IssueSeverity::Warning,
+        description: "Bibliography key defined more than once",
+    },
+    Rule {
+        id: UNUSED_CITATION,
+        severity: IssueSeverity::Info,
+        description: "Bibliography entry nothing cites",
+    },
+    Rule {
+        id: INCOMPLETE_CITATION,
+        severity: IssueSeverity::Warning,
+        description: "Cited entry missing fields the `csl` style prints",
+    },
+    Rule {
+        id: FORBIDDEN_TERM,
+        severity: IssueSeverity::Warning,
+        description: "Forbidden variant from the terms dictionary",
+    },
+    Rule {
+        id: MISSPELLING,
+        severity: IssueSeverity::Warning,
+        description: "Word Hunspell does not know",
+    },
+    Rule {
+        id: DUPLICATE_GLOSSARY_KEY,
+        severity: IssueSeverity::Warning,
+        description: "Glossary key defined more than once",
+    },
+    Rule {
+        id: UNDEFINED_GLOSSARY_TERM,
+        severity: IssueSeverity::Warning,
+        description: "`[+key]` or `.gls` reference to a key no glossary defines",
+    },
+    Rule {
+        id: UNUSED_GLOSSARY_TERM,
+        severity: IssueSeverity::Info,
+        description: "Glossary term nothing references",
+    },
+    Rule {
+        id: INCONSISTENT_INDEX_TERM,
+        severity: IssueSeverity::Warning,
+        description: "Index term spelled differently from its first occurrence",
+    },
+    Rule {
+        id: HIDDEN_VARIANT_LABEL,
+        severity: IssueSeverity::Warning,
+        description: "Label a variant hides while visible content references it",
+    },
+];
+
+/// Look up a registered rule by id.
+pub fn rule(id: &str) -> Option<&'static Rule> {
+    RULES.iter().find(|rule| rule.id == id)
+}
+
+/// Comma-separated rule ids, for error messages.
+pub fn rule_ids() -> String {
+    RULES
+        .iter()
+        .map(|rule| rule.id)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Level set for a rule in `[lint.rules]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleLevel {
+    Off,
+    Info,
+    Warning,
+    Error,
+}
+
+impl RuleLevel {
+    /// Parse a `[lint.rules]` level.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "off" => Some(Self::Off),
+            "info" => Some(Self::Info),
+            "warning" => Some(Self::Warning),
+            "error" => Some(Self::Error),
+            _ => None,
+        }
+    }
+
+    fn severity(self) -> Option<IssueSeverity> {
+        match self {
+            Self::Off => None,
+            Self::Info => Some(IssueSeverity::Info),
+            Self::Warning => Some(IssueSeverity::Warning),
+            Self::Error => Some(IssueSeverity::Error),
+        }
+    }
+}
+
+/// Effective severity of every rule for one project.
+#[derive(Debug, Clone, Default)]
+pub struct RuleSettings {
+    levels: HashMap<&'static str, RuleLevel>,
+}
+
+impl RuleSettings {
+    /// Unknown rules and levels are skipped here; `validate_config` reports them.
+    pub fn from_config(config: &MergedConfig) -> Self {
+        let levels = config
+            .lint_rules
+            .iter()
+            .filter_map(|(id, level)| Some((rule(id)?.id, RuleLevel::parse(level)?)))
+            .collect();
+        Self { levels }
+    }
+
+    /// Severity `rule` reports at, or `None` when it is turned off.
+    pub fn severity(&self, id: &str) -> Option<IssueSeverity> {
+        let rule = rule(id)?;
+        match self.levels.get(rule.id) {
+            Some(level) => level.severity(),
+            None => Some(rule.severity.clone()),
+        }
+    }
+}
+
+/// A rule violation at a 1-based source position, before severity and
+/// suppressions are applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub rule: &'static str,
+    pub message: String,
+    pub line: usize,
+    pub column: Option<usize>,
+}
+
+/// Turn the findings of one file into issues, dropping suppressed findings
+/// and rules that are turned off.
+pub fn file_issues(
+    rel: &str,
+    findings: Vec<Finding>,
+    suppressions: &Suppressions,
+    settings: &RuleSettings,
+) -> Vec<ProjectIssue> {
+    let mut issues = suppressions
+        .unknown
+        .iter()
+        .map(|(line, id)| ProjectIssue {
+            severity: IssueSeverity::Warning,
+            message: format!("Unknown lint rule '{id}' in suppression comment"),
+            path: Some(rel.to_string()),
+            line: Some(*line),
+            column: None,
+            rule: None,
+        })
+        .collect::<Vec<_>>();
+    issues.extend(
+        findings
+            .into_iter()
+            .filter_map(|finding| finding_issue(rel, finding, suppressions, settings)),
+    );
+    issues
+}
+
+/// Turn findings spread over several files, each paired with its path
+/// relative to `project_path`, into issues. Markdown and LaTeX files are
+/// read for suppression comments; unknown ids in them are left to
+/// `lint_project`, which reports them once.
+pub fn project_issues(
+    project_path: &Path,
+    findings: Vec<(String, Finding)>,
+    settings: &RuleSettings,
+) -> Vec<ProjectIssue> {
+    let mut suppressions: HashMap<String, Suppressions> = HashMap::new();
+    findings
+        .into_iter()
+        .filter_map(|(rel, finding)| {
+            let file = suppressions
+                .entry(rel.clone())
+                .or_insert_with(|| file_suppressions(&project_path.join(&rel)));
+            finding_issue(&rel, finding, file, settings)
+        })
+        .collect()
+}
+
+fn finding_issue(
+    rel: &str,
+    finding: Finding,
+    suppressions: &Suppressions,
+    settings: &RuleSettings,
+) -> Option<ProjectIssue> {
+    if suppressions.covers(finding.rule, finding.line) {
+        return None;
+    }
+    Some(ProjectIssue {
+        severity: settings.severity(finding.rule)?,
+        message: finding.message,
+        path: Some(rel.to_string()),
+        line: Some(finding.line),
+        column: finding.column,
+        rule: Some(finding.rule.to_string()),
+    })
+}
+
+/// Suppression comments of a Markdown, LaTeX or BibTeX file; other files
+/// have none.
+fn file_suppressions(path: &Path) -> Suppressions {
+    let Ok(content) = fs::read_to_string(path) else {
+        return Suppressions::default();
+    };
+    let extension = path
+        .extension()
+        .and_then(|value| value.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match extension.as_str() {
+        "md" | "markdown" => {
+            Suppressions::parse(markdown_prose_lines(&content), CommentSyntax::Html)
+        }
+        "tex" | "sty" | "cls" | "bib" => Suppressions::parse(
+            content
+                .lines()
+                .enumerate()
+                .map(|(index, line)| (index + 1, line)),
+            CommentSyntax::Latex,
+        ),
+        _ => Suppressions::default(),
+    }
+}
+
+/// Comment syntax carrying suppression directives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommentSyntax {
+    /// `<!-- omnidoc-lint-… -->`
+    Html,
+    /// `% omnidoc-lint-…`
+    Latex,
+}
+
+static HTML_DIRECTIVE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"<!--\s*omnidoc-lint-(disable-next-line|disable|enable)\b(.*?)-->")
+        .expect("HTML lint directive regex")
+});
+static LATEX_DIRECTIVE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:^|[^\\])%\s*omnidoc-lint-(disable-next-line|disable|enable)\b(.*)$")
+        .expect("LaTeX lint directive regex")
+});
+
+/// Line ranges of one file in which rules are silenced.
+#[derive(Debug, Clone, Default)]
+pub struct Suppressions {
+    /// Inclusive line ranges; an empty rule list silences every rule.
+    ranges: Vec<(usize, usize, Vec<String>)>,
+    /// Rule ids named by a directive that no rule has, with their line.
+    unknown: Vec<(usize, String)>,
+}
+
+impl Suppressions {
+    /// Read the directives of `lines` (1-based numbers with their text).
+    /// `disable` without a matching `enable` lasts to the end of the file.
+    pub fn parse<'a>(
+        lines: impl IntoIterator<Item = (usize, &'a str)>,
+        syntax: CommentSyntax,
+    ) -> Self {
+        let directive = match syntax {
+            CommentSyntax::Html => &*HTML_DIRECTIVE,
+            CommentSyntax::Latex => &*LATEX_DIRECTIVE,
+        };
+        let mut suppressions = Self::default();
+        let mut open: Vec<(usize, Vec<String>)> = Vec::new();
+        for (line_no, line) in lines {
+            for capture in directive.captures_iter(line) {
+                let rules = capture[2]
+                    .split([' ', '\t', ','])
+                    .filter(|id| !id.is_empty())
+                    .map(str::to_string)
+                    .collect::<Vec<_>>();
+                for id in &rules {
+                    if rule(id).is_none() {
+                        suppressions.unknown.push((line_no, id.clone()));
+                    }
+                }
+                match &capture[1] {
+                    "disable-next-line" => {
+                        suppressions.ranges.push((line_no + 1, line_no + 1, rules));
+                    }
+                    "disable" => open.push((line_no, rules)),
+                    _ => {
+                        // `enable` closes the matching rules, or everything.
+                        let (closed, kept) = open.into_iter().partition(|(_, open_rules)| {
+                            rules.is_empty() || open_rules.iter().any(|id| rules.contains(id))
+                        });
+                        open = kept;
+                        for (start, open_rules) in closed {
+                            suppressions.ranges.push((start, line_no, open_rules));
+                        }
+                    }
+                }
+            }
+        }
+        for (start, rules) in open {
+            suppressions.ranges.push((start, usize::MAX, rules));
+        }
+        suppressions
+    }
+
+    /// Whether `rule` is silenced on `line`.
+    pub fn covers(&self, rule: &str, line: usize) -> bool {
+        self.ranges.iter().any(|(start, end, rules)| {
+            (*start..=*end).contains(&line)
+                && (rules.is_empty() || rules.iter().any(|id| id == rule))
+        })
+    }
+}
+
+/// What a Markdown reference points at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReferenceKind {
+    Image,
+    Link,
+    Include,
+}
+
+impl ReferenceKind {
+    /// Rule reporting a missing target of this kind.
+    pub fn rule(self) -> &'static str {
+        match self {
+            Self::Image => MISSING_IMAGE,
+            Self::Link => MISSING_LINK_TARGET,
+            Self::Include => MISSING_INCLUDE,
+        }
+    }
+}
+
+/// A target named by Markdown source, at its 1-based line and column.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reference {
+    pub kind: ReferenceKind,
+    pub target: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Every image, link and include target in a `commonmark_x+sourcepos` AST.
+/// Positions come from the element's own `data-pos`, else from the nearest
+/// enclosing element that has one.
+pub fn ast_references(ast: &Value) -> Vec<Reference> {
+    let mut references = Vec::new();
+    if let Some(blocks) = ast.get("blocks") {
+        walk(blocks, None, &mut references);
+    }
+    references
+}
+
+fn walk(value: &Value, position: Option<(usize, usize)>, references: &mut Vec<Reference>) {
+    let map = match value {
+        Value::Array(items) => {
+            for item in items {
+                walk(item, position, references);
+            }
+            return;
+        }
+        Value::Object(map) => map,
+        _ => return,
+    };
+    let tag = map.get("t").and_then(Value::as_str).unwrap_or_default();
+    let Some(content) = map.get("c") else {
+        return;
+    };
+    let attr = element_attr(tag, content);
+    let position = attr.and_then(data_pos).or(position);
+    let (line, column) = position.unwrap_or((1, 1));
+    let mut push = |kind, target: &str, line| {
+        references.push(Reference {
+            kind,
+            target: target.to_string(),
+            line,
+            column,
+        })
+    };
+
+    if let Some(attr) = attr {
+        for (key, value) in keyvals(attr) {
+            if matches!(key, "include" | "include-code") {
+                push(ReferenceKind::Include, value, line);
+            }
+        }
+    }
+    match tag {
+        "Image" | "Link" => {
+            if let Some(target) = content.get(2).and_then(|target| target.get(0)) {
+                let kind = if tag == "Image" {
+                    ReferenceKind::Image
+                } else {
+                    ReferenceKind::Link
+                };
+                push(kind, target.as_str().unwrap_or_default(), line);
+            }
+        }
+        "CodeBlock" if attr.is_some_and(|attr| has_class(attr, "include")) => {
+            // The fence is the block's first line; each later line names a file.
+            let text = content.get(1).and_then(Value::as_str).unwrap_or_default();
+            for (offset, target) in text.lines().enumerate() {
+                let target = target.trim();
+                if !target.is_empty() && !target.starts_with("//") && !target.contains('*') {
+                    push(ReferenceKind::Include, target, line + 1 + offset);
+                }
+            }
+        }
+        _ => {}
+    }
+    walk(content, position, references);
+}
+
+/// `[id, classes, keyvals]` of an element that carries attributes.
+fn element_attr<'a>(tag: &str, content: &'a Value) -> Option<&'a [Value]> {
+    let index = match tag {
+        "Header" => 1,
+        "Link" | "Image" | "Code" | "CodeBlock" | "Div" | "Span" | "Table" | "Figure" => 0,
+        _ => return None,
+    };
+    content
+        .get(index)?
+        .as_array()
+        .filter(|attr| attr.len() == 3 && attr[0].is_string() && attr[1].is_array())
+        .map(Vec::as_slice)
+}
+
+fn keyvals(attr: &[Value]) -> impl Iterator<Item = (&str, &str)> {
+    attr[2]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|pair| Some((pair.get(0)?.as_str()?, pair.get(1)?.as_str()?)))
+}
+
+fn has_class(attr: &[Value], class: &str) -> bool {
+    attr[1]
+        .as_array()
+        .is_some_and(|classes| classes.iter().any(|value| value.as_str() == Some(class)))
+}
+
+static DATA_POS: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?:.*?@)?(\d+):(\d+)-").expect("data-pos regex"));
+
+/// Start of a `data-pos` value such as `12:3-12:20` or `main.md@12:3-12:20`.
+fn data_pos(attr: &[Value]) -> Option<(usize, usize)> {
+    let (_, value) = keyvals(attr).find(|(key, _)| *key == "data-pos")?;
+    let range = value.split(';').next().unwrap_or(value);
+    let capture = DATA_POS.captures(range)?;
+    Some((capture[1].parse().ok()?, capture[2].parse().ok()?))
+}
+
+static LINE_IMAGE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"!\[[^\]]*\]\(([^)]+)\)").expect("image regex"));
+static LINE_LINK: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?P<bang>!?)\[[^\]]+\]\((?P<target>[^)]+)\)").expect("link regex")
+});
+static LINE_INCLUDE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"include(?:-code)?="([^"]+)""#).expect("include regex"));
+
+/// References found by per-line regexes, used when Pandoc cannot parse the
+/// file. Fenced and indented code is skipped; reference-style links, links
+/// spanning lines and `.include` blocks are not seen.
+pub fn line_references(content: &str) -> Vec<Reference> {
+    let mut references = Vec::new();
+    let mut markdown_fence: Option<char> = None;
+    for (line_index, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if let Some(marker) = markdown_fence {
+            if trimmed.starts_with(&marker.to_string().repeat(3)) {
+                markdown_fence = None;
+            }
+            continue;
+        }
+        if trimmed.starts_with("```") {
+            markdown_fence = Some('`');
+            continue;
+        }
+        if trimmed.starts_with("~~~") {
+            markdown_fence = Some('~');
+            continue;
+        }
+        if line.starts_with("    ") || line.starts_with('\t') {
+            continue;
+        }
+
+        let mut push = |kind, target: &str, start: usize| {
+            references.push(Reference {
+                kind,
+                target: target.to_string(),
+                line: line_index + 1,
+                column: line[..start].chars().count() + 1,
+            })
+        };
+        for capture in LINE_IMAGE.captures_iter(line) {
+            push(
+                ReferenceKind::Image,
+                &capture[1],
+                capture.get(0).map_or(0, |m| m.start()),
+            );
+        }
+        for capture in LINE_LINK.captures_iter(line) {
+            if !capture["bang"].is_empty() {
+                continue;
+            }
+            push(
+                ReferenceKind::Link,
+                &capture["target"],
+                capture.get(0).map_or(0, |m| m.start()),
+            );
+        }
+        for capture in LINE_INCLUDE.captures_iter(line) {
+            push(
+                ReferenceKind::Include,
+                &capture[1],
+                capture.get(0).map_or(0, |m| m.start()),
+            );
+        }
+    }
+    references
+}
+
+const CACHE_FILE: &str = "lint.json";
+/// Bumped whenever [`ast_references`] extracts something new.
+const CACHE_VERSION: u32 = 1;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ReferenceCache {
+    version: u32,
+    /// References of each parsed Markdown file, keyed by a digest of its content.
+    files: BTreeMap<String, Vec<Reference>>,
+}
+
+/// Reads the references of Markdown files through Pandoc, reusing the
+/// results cached for unchanged content.
+pub struct MarkdownReferences {
+    executor: BuildExecutor,
+    pandoc: Option<bool>,
+    cache_path: PathBuf,
+    cache: ReferenceCache,
+    used: BTreeSet<String>,
+    changed: bool,
+}
+
+impl MarkdownReferences {
+    pub fn new(project_path: &Path, config: &MergedConfig) -> Self {
+        let cache_path = project_cache_dir(project_path).join(CACHE_FILE);
+        let cache = fs::read_to_string(&cache_path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<ReferenceCache>(&content).ok())
+            .filter(|cache| cache.version == CACHE_VERSION)
+            .unwrap_or(ReferenceCache {
+                version: CACHE_VERSION,
+                files: BTreeMap::new(),
+            });
+        Self {
+            executor: BuildExecutor::new(config.tool_paths.clone()),
+            pandoc: None,
+            cache_path,
+            cache,
+            used: BTreeSet::new(),
+            changed: false,
+        }
+    }
+
+    /// References of `file`, from the AST when Pandoc can parse it and from
+    /// [`line_references`] otherwise.
+    pub fn read(&mut self, file: &Path, content: &str) -> Vec<Reference> {
+        let digest = blake3::hash(content.as_bytes()).to_hex().to_string();
+        if let Some(references) = self.cache.files.get(&digest) {
+            self.used.insert(digest);
+            return references.clone();
+        }
+        let Some(references) = self.parse(file) else {
+            return line_references(content);
+        };
+        self.cache.files.insert(digest.clone(), references.clone());
+        self.used.insert(digest);
+        self.changed = true;
+        references
+    }
+
+    fn parse(&mut self, file: &Path) -> Option<Vec<Reference>> {
+        let available = *self
+            .pandoc
+            .get_or_insert_with(|| self.executor.check_tool("pandoc").is_ok());
+        if !available {
+            return None;
+        }
+        let input = file.to_string_lossy();
+        let args = ["-f", "commonmark_x+sourcepos", "-t", "json", input.as_ref()];
+        let json = self.executor.execute_with_output("pandoc", &args).ok()?;
+        let ast = serde_json::from_str::<Value>(&json).ok()?;
+        Some(ast_references(&ast))
+    }
+
+    /// Write the cache back, keeping only the files read this run. Lint
+    /// never fails because the cache cannot be written; it only warns.
+    pub fn finish(mut self) {
+        let before = self.cache.files.len();
+        self.cache
+            .files
+            .retain(|digest, _| self.used.contains(digest));
+        if !self.changed && self.cache.files.len() == before {
+            return;
+        }
+        let written = serde_json::to_string(&self.cache)
+            .map_err(|error| OmniDocError::Other(error.to_string()))
+            .and_then(|content| {
+                if let Some(parent) = self.cache_path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                utils::fs::atomic_write(&self.cache_path, content)
+            });
+        if let Err(error) = written {
+            terminal::warning(format!(
+                "Could not write the lint cache {}: {error}",
+                self.cache_path.display()
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ast_references, file_issues, line_references, CommentSyntax, Finding, Reference,
+        ReferenceKind, RuleSettings, Suppressions, MISSING_IMAGE, MISSING_INCLUDE,
+        MISSING_LINK_TARGET,
+    };
+    use crate::config::MergedConfig;
+    use crate::project_tools::IssueSeverity;
+    use serde_json::json;
+    use std::collections::BTreeMap;
+
+    fn reference(kind: ReferenceKind, target: &str, line: usize, column: usize) -> Reference {
+        Reference {
+            kind,
+            target: target.to_string(),
+            line,
+            column,
+        }
+    }
+
+    fn pos(value: &str) -> serde_json::Value {
+        json!(["", [], [["data-pos", value]]])
+    }
+
+    #[test]
+    fn ast_references_take_positions_from_data_pos() {
+        // `# Setup {include="missing.md"}`, a reference-style link resolved
+        // by Pandoc, an image inside a wrapped paragraph and an include block.
+        let ast = json!({
+            "pandoc-api-version": [1, 23, 1],
+            "meta": {},
+            "blocks": [
+                {"t": "Header", "c": [1, ["setup", [], [["include", "missing.md"], ["data-pos", "main.md@1:1-2:1"]]], []]},
+                {"t": "Div", "c": [pos("main.md@3:1-4:12"), [
+                    {"t": "Para", "c": [
+                        {"t": "Link", "c": [pos("main.md@3:5-4:12"), [], ["notes/a.md", ""]]},
+                        {"t": "Span", "c": [pos("4:14-4:30"), [
+                            {"t": "Image", "c": [["", [], []], [], ["img/x.png", ""]]}
+                        ]]}
+                    ]}
+                ]]},
+                {"t": "CodeBlock", "c": [["", ["include"], [["data-pos", "6:1-9:4"]]], "chapters/a.md\n// skipped\nchapters/b.md"]}
+            ]
+        });
+
+        assert_eq!(
+            ast_references(&ast),
+            vec![
+                reference(ReferenceKind::Include, "missing.md", 1, 1),
+                reference(ReferenceKind::Link, "notes/a.md", 3, 5),
+                reference(ReferenceKind::Image, "img/x.png", 4, 14),
+                reference(ReferenceKind::Include, "chapters/a.md", 7, 1),
+                reference(ReferenceKind::Include, "chapters/b.md", 9, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn line_references_skip_code_and_report_columns() {
+        let content =
+            "See ![a](x.png) and [b](y.md).\n\n```\n![c](z.png)\n```\n::: {include=\"w.md\"}\n";
+        assert_eq!(
+            line_references(content),
+            vec![
+                reference(ReferenceKind::Image, "x.png", 1, 5),
+                reference(ReferenceKind::Link, "y.md", 1, 21),
+                reference(ReferenceKind::Include, "w.md", 6, 6),
+            ]
+        );
+    }
+
+    #[test]
+    fn suppressions_and_rule_levels_filter_findings() {
+        let content = concat!(
+            "<!-- omnidoc-lint-disable-next-line missing_image -->\n",
+            "![a](a.png) [b](b.md)\n",
+            "<!-- omnidoc-lint-disable -->\n",
+            "![c](c.png)\n",
+            "<!-- omnidoc-lint-enable -->\n",
+            "![d](d.png) {include=\"e.md\"}\n",
+            "<!-- omnidoc-lint-disable missing_images -->\n",
+        );
+        let suppressions = Suppressions::parse(
+            content
+                .lines()
+                .enumerate()
+                .map(|(index, line)| (index + 1, line)),
+            CommentSyntax::Html,
+        );
+        let finding = |rule, line| Finding {
+            rule,
+            message: format!("{rule} on {line}"),
+            line,
+            column: Some(1),
+        };
+        let settings = RuleSettings::from_config(&MergedConfig {
+            lint_rules: BTreeMap::from([
+                (MISSING_LINK_TARGET.to_string(), "error".to_string()),
+                (MISSING_INCLUDE.to_string(), "off".to_string()),
+            ]),
+            ..Default::default()
+        });
+
+        let issues = file_issues(
+            "main.md",
+            vec![
+                finding(MISSING_IMAGE, 2),
+                finding(MISSING_LINK_TARGET, 2),
+                finding(MISSING_IMAGE, 4),
+                finding(MISSING_IMAGE, 6),
+                finding(MISSING_INCLUDE, 6),
+            ],
+            &suppressions,
+            &settings,
+        );
+        let reported = issues
+            .iter()
+            .map(|issue| {
+                (
+                    issue.severity.clone(),
+                    issue.line,
+                    issue.rule.as_deref().unwrap_or("-"),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            reported,
+            vec![
+                (IssueSeverity::Warning, Some(7), "-"),
+                (IssueSeverity::Error, Some(2), MISSING_LINK_TARGET),
+                (IssueSeverity::Warning, Some(6), MISSING_IMAGE),
+            ]
+        );
+        assert!(issues[0].message.contains("missing_images"));
+    }
+}
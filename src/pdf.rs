@@ -0,0 +1,916 @@
+//! Post-processing for PDF outputs.
+//!
+//! After the LaTeX or Typst engine finishes, OmniDoc stamps the document
+//! metadata into both the Info dictionary and an XMP packet, counts the
+//! outline (bookmark) entries, and, when a conformance profile is requested,
+//! adds what PDF/A-2b needs that an engine does not write by itself: the
+//! `pdfaid` identification and an sRGB output intent. Properties that cannot
+//! be repaired afterwards, such as unembedded fonts, are only checked.
+
+use crate::error::{OmniDocError, Result};
+use crate::front_matter;
+use lopdf::{dictionary, Dictionary, Document, Object, ObjectId, Stream, StringFormat};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::Path;
+
+const PDF_A_2B: &str = "pdf-a-2b";
+const XMP_PACKET_ID: &str = "W5M0MpCehiHzreSzNTczkc9d";
+/// Description of [`srgb_icc_profile`], also used to label the output intent:
+/// the profile approximates sRGB and is not the IEC 61966-2.1 one.
+const ICC_PROFILE_DESCRIPTION: &str = "sRGB gamma 2.2 (OmniDoc)";
+
+/// Metadata stamped into, or read back from, a PDF.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PdfMetadata {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub keywords: Vec<String>,
+    pub revision: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PdfReport {
+    pub profile: Option<String>,
+    pub valid: bool,
+    pub metadata: PdfMetadata,
+    pub outline_entries: usize,
+    pub checks: Vec<PdfCheck>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PdfCheck {
+    pub name: String,
+    pub passed: bool,
+    pub detail: String,
+}
+
+/// 规范化 PDF 一致性配置名称；不支持时返回 `None`
+pub fn normalize_pdf_profile(profile: &str) -> Option<&'static str> {
+    match profile
+        .trim()
+        .to_ascii_lowercase()
+        .replace('/', "-")
+        .as_str()
+    {
+        "pdf-a-2b" | "pdfa-2b" => Some(PDF_A_2B),
+        _ => None,
+    }
+}
+
+fn resolve_profile(profile: Option<&str>) -> Result<Option<&'static str>> {
+    profile
+        .map(|profile| {
+            normalize_pdf_profile(profile).ok_or_else(|| {
+                OmniDocError::Config(format!(
+                    "Unsupported PDF profile '{}'. Supported profiles: pdf-a-2b",
+                    profile
+                ))
+            })
+        })
+        .transpose()
+}
+
+/// 写入元数据（可选按配置补全 PDF/A-2b 要素）并校验 PDF
+///
+/// Metadata fields left empty keep the values the engine wrote, so a LaTeX
+/// `\hypersetup` title survives when the project has no front matter.
+///
+/// Without a profile, metadata is a courtesy: a PDF lopdf cannot read or
+/// rewrite is left as the engine wrote it and reported as a failed check.
+pub fn postprocess_pdf(
+    path: &Path,
+    metadata: &PdfMetadata,
+    stamp_metadata: bool,
+    profile: Option<&str>,
+) -> Result<PdfReport> {
+    let profile = resolve_profile(profile)?;
+    let mut document = match load(path) {
+        Ok(document) => document,
+        Err(error) if profile.is_none() => return Ok(unreadable_report(&error)),
+        Err(error) => return Err(error),
+    };
+    let mut stamp_error = None;
+    if (stamp_metadata || profile.is_some()) && !is_encrypted(&document) {
+        match stamp_and_save(path, &document, metadata, profile) {
+            Ok(stamped) => document = stamped,
+            Err(error) if profile.is_none() => stamp_error = Some(error),
+            Err(error) => return Err(error),
+        }
+    }
+    let mut report = inspect(&document, profile);
+    if let Some(error) = stamp_error {
+        push_check(&mut report.checks, "metadata-stamp", false, error.message());
+        report.valid = false;
+    }
+    Ok(report)
+}
+
+/// 仅校验已有的 PDF（缓存命中时使用）
+pub fn inspect_pdf(path: &Path, profile: Option<&str>) -> Result<PdfReport> {
+    let profile = resolve_profile(profile)?;
+    match load(path) {
+        Ok(document) => Ok(inspect(&document, profile)),
+        Err(error) if profile.is_none() => Ok(unreadable_report(&error)),
+        Err(error) => Err(error),
+    }
+}
+
+/// Stamps a copy of `document` and replaces `path` with it, so a failure
+/// leaves both the file and the loaded document untouched.
+fn stamp_and_save(
+    path: &Path,
+    document: &Document,
+    metadata: &PdfMetadata,
+    profile: Option<&str>,
+) -> Result<Document> {
+    let mut stamped = document.clone();
+    stamp(&mut stamped, metadata, profile)?;
+    let staging = path.with_extension("pdf.omnidoc-tmp");
+    if let Err(error) = stamped.save(&staging) {
+        let _ = std::fs::remove_file(&staging);
+        return Err(OmniDocError::Other(format!("cannot write PDF: {error}")));
+    }
+    std::fs::rename(&staging, path).map_err(OmniDocError::Io)?;
+    Ok(stamped)
+}
+
+fn unreadable_report(error: &OmniDocError) -> PdfReport {
+    let mut checks = Vec::new();
+    push_check(&mut checks, "readable", false, error.message());
+    PdfReport {
+        profile: None,
+        valid: false,
+        metadata: PdfMetadata::default(),
+        outline_entries: 0,
+        checks,
+    }
+}
+
+fn load(path: &Path) -> Result<Document> {
+    Document::load(path).map_err(|error| {
+        OmniDocError::Other(format!("cannot read PDF {}: {error}", path.display()))
+    })
+}
+
+fn is_encrypted(document: &Document) -> bool {
+    document.trailer.has(b"Encrypt") || document.was_encrypted()
+}
+
+fn stamp(document: &mut Document, metadata: &PdfMetadata, profile: Option<&str>) -> Result<()> {
+    let mut info = info_dictionary(document).cloned().unwrap_or_default();
+    if let Some(title) = &metadata.title {
+        info.set("Title", text_string(title));
+    }
+    if let Some(author) = &metadata.author {
+        info.set("Author", text_string(author));
+    }
+    if !metadata.keywords.is_empty() {
+        info.set("Keywords", text_string(&metadata.keywords.join(", ")));
+    }
+    let info_id = match document.trailer.get(b"Info").and_then(Object::as_reference) {
+        Ok(id) => {
+            document.set_object(id, info.clone());
+            id
+        }
+        Err(_) => document.add_object(info.clone()),
+    };
+    document.trailer.set("Info", info_id);
+
+    let xmp = xmp_packet(&info, metadata.revision.as_deref(), profile);
+    let mut stream = Stream::new(
+        dictionary! { "Type" => "Metadata", "Subtype" => "XML" },
+        xmp.into_bytes(),
+    );
+    // PDF/A readers must be able to read the packet without decoding it.
+    stream.allows_compression = false;
+    let metadata_id = document.add_object(stream);
+    let catalog = document
+        .catalog_mut()
+        .map_err(|error| OmniDocError::Other(format!("PDF has no catalog: {error}")))?;
+    catalog.set("Metadata", metadata_id);
+
+    if profile == Some(PDF_A_2B) {
+        if !has_pdfa_output_intent(document) {
+            let icc = Stream::new(dictionary! { "N" => 3 }, srgb_icc_profile());
+            let icc_id = document.add_object(icc);
+            let intent = document.add_object(dictionary! {
+                "Type" => "OutputIntent",
+                "S" => "GTS_PDFA1",
+                "OutputConditionIdentifier" => Object::string_literal(ICC_PROFILE_DESCRIPTION),
+                "Info" => Object::string_literal(ICC_PROFILE_DESCRIPTION),
+                "DestOutputProfile" => icc_id,
+            });
+            let catalog = document
+                .catalog_mut()
+                .map_err(|error| OmniDocError::Other(format!("PDF has no catalog: {error}")))?;
+            catalog.set("OutputIntents", vec![Object::Reference(intent)]);
+        }
+        if !document.trailer.has(b"ID") {
+            let seed = blake3::hash(xmp_seed(&info).as_bytes());
+            let id = Object::String(seed.as_bytes()[..16].to_vec(), StringFormat::Hexadecimal);
+            document.trailer.set("ID", vec![id.clone(), id]);
+        }
+    }
+    Ok(())
+}
+
+fn xmp_seed(info: &Dictionary) -> String {
+    ["Title", "Author", "CreationDate"]
+        .iter()
+        .filter_map(|key| info_text(info, key))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn info_dictionary(document: &Document) -> Option<&Dictionary> {
+    let info = document.trailer.get(b"Info").ok()?;
+    let (_, info) = document.dereference(info).ok()?;
+    info.as_dict().ok()
+}
+
+fn info_text(info: &Dictionary, key: &str) -> Option<String> {
+    let bytes = info.get(key.as_bytes()).ok()?.as_str().ok()?;
+    let text = decode_text_string(bytes);
+    (!text.trim().is_empty()).then_some(text)
+}
+
+/// PDF text strings: UTF-16BE with a byte order mark, or PDFDocEncoding,
+/// which agrees with Latin-1 for the characters engines write.
+fn decode_text_string(bytes: &[u8]) -> String {
+    if let Some(utf16) = bytes.strip_prefix(&[0xfe, 0xff]) {
+        let units = utf16
+            .chunks_exact(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+            .collect::<Vec<_>>();
+        return String::from_utf16_lossy(&units);
+    }
+    bytes.iter().map(|byte| char::from(*byte)).collect()
+}
+
+fn text_string(text: &str) -> Object {
+    if text.is_ascii() {
+        return Object::string_literal(text);
+    }
+    let mut bytes = vec![0xfe, 0xff];
+    for unit in text.encode_utf16() {
+        bytes.extend(unit.to_be_bytes());
+    }
+    Object::String(bytes, StringFormat::Hexadecimal)
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// `D:20261018093000+08'00'` → `2026-10-18T09:30:00+08:00`
+fn xmp_date(pdf_date: &str) -> Option<String> {
+    let digits = pdf_date.strip_prefix("D:").unwrap_or(pdf_date);
+    let field = |range: std::ops::Range<usize>, default: &'static str| {
+        digits
+            .get(range)
+            .filter(|part| part.bytes().all(|byte| byte.is_ascii_digit()))
+            .unwrap_or(default)
+    };
+    let year = digits
+        .get(0..4)
+        .filter(|year| year.bytes().all(|byte| byte.is_ascii_digit()))?;
+    let mut date = format!(
+        "{year}-{}-{}T{}:{}:{}",
+        field(4..6, "01"),
+        field(6..8, "01"),
+        field(8..10, "00"),
+        field(10..12, "00"),
+        field(12..14, "00"),
+    );
+    match digits.get(14..15) {
+        Some("Z") => date.push('Z'),
+        Some(sign @ ("+" | "-")) => {
+            date.push_str(sign);
+            date.push_str(field(15..17, "00"));
+            date.push(':');
+            date.push_str(field(18..20, "00"));
+        }
+        _ => {}
+    }
+    Some(date)
+}
+
+/// XMP packet mirroring the Info dictionary, as PDF/A requires.
+fn xmp_packet(info: &Dictionary, revision: Option<&str>, profile: Option<&str>) -> String {
+    let mut properties = vec!["<dc:format>application/pdf</dc:format>".to_string()];
+    if let Some(title) = info_text(info, "Title") {
+        properties.push(format!(
+            "<dc:title><rdf:Alt><rdf:li xml:lang=\"x-default\">{}</rdf:li></rdf:Alt></dc:title>",
+            xml_escape(&title)
+        ));
+    }
+    // Info has a single Author string; PDF/A matches it to one dc:creator.
+    if let Some(author) = info_text(info, "Author") {
+        properties.push(format!(
+            "<dc:creator><rdf:Seq><rdf:li>{}</rdf:li></rdf:Seq></dc:creator>",
+            xml_escape(&author)
+        ));
+    }
+    if let Some(keywords) = info_text(info, "Keywords") {
+        let items = keywords
+            .split(',')
+            .map(str::trim)
+            .filter(|keyword| !keyword.is_empty())
+            .map(|keyword| format!("<rdf:li>{}</rdf:li>", xml_escape(keyword)))
+            .collect::<String>();
+        properties.push(format!(
+            "<dc:subject><rdf:Bag>{items}</rdf:Bag></dc:subject>"
+        ));
+        properties.push(format!(
+            "<pdf:Keywords>{}</pdf:Keywords>",
+            xml_escape(&keywords)
+        ));
+    }
+    if let Some(producer) = info_text(info, "Producer") {
+        properties.push(format!(
+            "<pdf:Producer>{}</pdf:Producer>",
+            xml_escape(&producer)
+        ));
+    }
+    if let Some(creator) = info_text(info, "Creator") {
+        properties.push(format!(
+            "<xmp:CreatorTool>{}</xmp:CreatorTool>",
+            xml_escape(&creator)
+        ));
+    }
+    for (key, property) in [
+        ("CreationDate", "xmp:CreateDate"),
+        ("ModDate", "xmp:ModifyDate"),
+    ] {
+        if let Some(date) = info_text(info, key).as_deref().and_then(xmp_date) {
+            properties.push(format!("<{property}>{date}</{property}>"));
+        }
+    }
+    if let Some(revision) = revision {
+        properties.push(format!(
+            "<xmpMM:VersionID>{}</xmpMM:VersionID>",
+            xml_escape(revision)
+        ));
+    }
+    if profile == Some(PDF_A_2B) {
+        properties.push("<pdfaid:part>2</pdfaid:part>".to_string());
+        properties.push("<pdfaid:conformance>B</pdfaid:conformance>".to_string());
+    }
+    format!(
+        "<?xpacket begin=\"\u{feff}\" id=\"{XMP_PACKET_ID}\"?>\n\
+<x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\n\
+<rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n\
+<rdf:Description rdf:about=\"\" \
+xmlns:dc=\"http://purl.org/dc/elements/1.1/\" \
+xmlns:pdf=\"http://ns.adobe.com/pdf/1.3/\" \
+xmlns:xmp=\"http://ns.adobe.com/xap/1.0/\" \
+xmlns:xmpMM=\"http://ns.adobe.com/xap/1.0/mm/\" \
+xmlns:pdfaid=\"http://www.aiim.org/pdfa/ns/id/\">\n\
+{}\n\
+</rdf:Description>\n\
+</rdf:RDF>\n\
+</x:xmpmeta>\n\
+<?xpacket end=\"w\"?>",
+        properties.join("\n")
+    )
+}
+
+/// Minimal ICC v2 display profile with the sRGB primaries and a 2.2 gamma,
+/// embedded as the PDF/A output intent.
+fn srgb_icc_profile() -> Vec<u8> {
+    fn s15_fixed16(value: f64) -> [u8; 4] {
+        ((value * 65536.0).round() as i32).to_be_bytes()
+    }
+    fn xyz(x: f64, y: f64, z: f64) -> Vec<u8> {
+        let mut data = b"XYZ \0\0\0\0".to_vec();
+        for value in [x, y, z] {
+            data.extend(s15_fixed16(value));
+        }
+        data
+    }
+    let description = ICC_PROFILE_DESCRIPTION;
+    let mut desc = b"desc\0\0\0\0".to_vec();
+    desc.extend((description.len() as u32 + 1).to_be_bytes());
+    desc.extend(description.as_bytes());
+    desc.push(0);
+    // Empty Unicode and ScriptCode descriptions.
+    desc.extend([0u8; 4 + 4 + 2 + 1 + 67]);
+    let mut copyright = b"text\0\0\0\0".to_vec();
+    copyright.extend(b"No copyright, use freely\0");
+    let mut curve = b"curv\0\0\0\0".to_vec();
+    curve.extend(1u32.to_be_bytes());
+    curve.extend(0x0233u16.to_be_bytes());
+
+    let tags: Vec<(&[u8; 4], Vec<u8>)> = vec![
+        (b"desc", desc),
+        (b"cprt", copyright),
+        (b"wtpt", xyz(0.9642, 1.0, 0.8249)),
+        (b"rXYZ", xyz(0.4361, 0.2225, 0.0139)),
+        (b"gXYZ", xyz(0.3851, 0.7169, 0.0971)),
+        (b"bXYZ", xyz(0.1431, 0.0606, 0.7141)),
+        (b"rTRC", curve.clone()),
+        (b"gTRC", curve.clone()),
+        (b"bTRC", curve),
+    ];
+    let mut table = (tags.len() as u32).to_be_bytes().to_vec();
+    let mut data = Vec::new();
+    let data_start = 128 + 4 + 12 * tags.len();
+    for (signature, content) in &tags {
+        table.extend(signature.iter());
+        table.extend(((data_start + data.len()) as u32).to_be_bytes());
+        table.extend((content.len() as u32).to_be_bytes());
+        data.extend(content);
+        while data.len() % 4 != 0 {
+            data.push(0);
+        }
+    }
+
+    let size = data_start + data.len();
+    let mut header = Vec::with_capacity(128);
+    header.extend((size as u32).to_be_bytes());
+    header.extend([0u8; 4]);
+    header.extend(0x0210_0000u32.to_be_bytes());
+    header.extend(b"mntrRGB XYZ ");
+    header.extend(2000u16.to_be_bytes());
+    header.extend([0, 1, 0, 1, 0, 0, 0, 0, 0, 0]);
+    header.extend(b"acsp");
+    header.extend([0u8; 24]);
+    header.extend(0u32.to_be_bytes());
+    for value in [0.9642, 1.0, 0.8249] {
+        header.extend(s15_fixed16(value));
+    }
+    header.resize(128, 0);
+
+    let mut profile = header;
+    profile.extend(table);
+    profile.extend(data);
+    profile
+}
+
+fn has_pdfa_output_intent(document: &Document) -> bool {
+    let Ok(catalog) = document.catalog() else {
+        return false;
+    };
+    let Ok(intents) = catalog
+        .get_deref(b"OutputIntents", document)
+        .and_then(Object::as_array)
+    else {
+        return false;
+    };
+    intents.iter().any(|intent| {
+        document
+            .dereference(intent)
+            .ok()
+            .and_then(|(_, intent)| intent.as_dict().ok())
+            .is_some_and(|intent| {
+                intent.get(b"S").and_then(Object::as_name).ok() == Some(b"GTS_PDFA1".as_slice())
+                    && intent.has(b"DestOutputProfile")
+            })
+    })
+}
+
+fn xmp_text(document: &Document) -> Option<String> {
+    let catalog = document.catalog().ok()?;
+    let stream = catalog
+        .get_deref(b"Metadata", document)
+        .ok()?
+        .as_stream()
+        .ok()?;
+    let content = stream
+        .decompressed_content()
+        .unwrap_or_else(|_| stream.content.clone());
+    String::from_utf8(content).ok()
+}
+
+fn xmp_property(xmp: &str, property: &str) -> Option<String> {
+    let start = xmp.find(&format!("<{property}>"))? + property.len() + 2;
+    let end = start + xmp[start..].find(&format!("</{property}>"))?;
+    Some(xmp[start..end].to_string())
+}
+
+fn outline_entries(document: &Document) -> usize {
+    let Some(outlines) = document
+        .catalog()
+        .ok()
+        .and_then(|catalog| catalog.get_deref(b"Outlines", document).ok())
+        .and_then(|outlines| outlines.as_dict().ok())
+    else {
+        return 0;
+    };
+    let mut visited = BTreeSet::new();
+    let mut pending = outlines
+        .get(b"First")
+        .and_then(Object::as_reference)
+        .into_iter()
+        .collect::<Vec<ObjectId>>();
+    while let Some(id) = pending.pop() {
+        if !visited.insert(id) {
+            continue;
+        }
+        if let Ok(item) = document.get_dictionary(id) {
+            for key in [b"Next".as_slice(), b"First".as_slice()] {
+                if let Ok(next) = item.get(key).and_then(Object::as_reference) {
+                    pending.push(next);
+                }
+            }
+        }
+    }
+    visited.len()
+}
+
+/// Font resources that neither embed a font program nor are Type 3 or
+/// composite wrappers, by base font name.
+fn unembedded_fonts(document: &Document) -> BTreeSet<String> {
+    let mut missing = BTreeSet::new();
+    for object in document.objects.values() {
+        let Ok(font) = object.as_dict() else {
+            continue;
+        };
+        if !font.has_type(b"Font") {
+            continue;
+        }
+        let subtype = font.get(b"Subtype").and_then(Object::as_name).ok();
+        if matches!(subtype, Some(b"Type3") | Some(b"Type0")) {
+            continue;
+        }
+        let embedded = font
+            .get_deref(b"FontDescriptor", document)
+            .and_then(Object::as_dict)
+            .is_ok_and(|descriptor| {
+                ["FontFile", "FontFile2", "FontFile3"]
+                    .iter()
+                    .any(|key| descriptor.has(key.as_bytes()))
+            });
+        if !embedded {
+            let name = font
+                .get(b"BaseFont")
+                .and_then(Object::as_name)
+                .map(|name| String::from_utf8_lossy(name).to_string())
+                .unwrap_or_else(|_| "unnamed font".to_string());
+            missing.insert(name);
+        }
+    }
+    missing
+}
+
+fn has_javascript(document: &Document) -> bool {
+    document.objects.values().any(|object| {
+        let dictionary = match object {
+            Object::Dictionary(dictionary) => dictionary,
+            Object::Stream(stream) => &stream.dict,
+            _ => return false,
+        };
+        dictionary.has(b"JS")
+            || dictionary.has(b"JavaScript")
+            || dictionary.get(b"S").and_then(Object::as_name).ok() == Some(b"JavaScript".as_slice())
+    })
+}
+
+fn inspect(document: &Document, profile: Option<&str>) -> PdfReport {
+    let info = info_dictionary(document);
+    let text = |key: &str| info.and_then(|info| info_text(info, key));
+    let xmp = xmp_text(document);
+    let metadata = PdfMetadata {
+        title: text("Title"),
+        author: text("Author"),
+        keywords: text("Keywords")
+            .map(|keywords| {
+                keywords
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|keyword| !keyword.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default(),
+        revision: xmp
+            .as_deref()
+            .and_then(|xmp| xmp_property(xmp, "xmpMM:VersionID")),
+    };
+    let outline_entries = outline_entries(document);
+
+    let mut checks = Vec::new();
+    push_check(
+        &mut checks,
+        "title",
+        metadata.title.is_some(),
+        "the Info dictionary carries a document title",
+    );
+    push_check(
+        &mut checks,
+        "xmp-metadata",
+        xmp.as_deref().is_some_and(|xmp| xmp.contains("<x:xmpmeta")),
+        "the catalog references an XMP metadata packet",
+    );
+    push_check(
+        &mut checks,
+        "outline",
+        outline_entries > 0,
+        if outline_entries > 0 {
+            format!("the outline has {outline_entries} bookmark(s)")
+        } else {
+            "the PDF has no outline (bookmarks)".to_string()
+        },
+    );
+    if profile == Some(PDF_A_2B) {
+        let identified = xmp.as_deref().is_some_and(|xmp| {
+            xmp_property(xmp, "pdfaid:part").as_deref() == Some("2")
+                && xmp_property(xmp, "pdfaid:conformance").as_deref() == Some("B")
+        });
+        push_check(
+            &mut checks,
+            "pdfa-identification",
+            identified,
+            "XMP identifies the file as PDF/A-2b",
+        );
+        push_check(
+            &mut checks,
+            "output-intent",
+            has_pdfa_output_intent(document),
+            "a GTS_PDFA1 output intent with an ICC profile is present",
+        );
+        let fonts = unembedded_fonts(document);
+        push_check(
+            &mut checks,
+            "embedded-fonts",
+            fonts.is_empty(),
+            if fonts.is_empty() {
+                "all fonts are embedded".to_string()
+            } else {
+                format!(
+                    "fonts not embedded: {}",
+                    fonts.into_iter().collect::<Vec<_>>().join(", ")
+                )
+            },
+        );
+        push_check(
+            &mut checks,
+            "no-encryption",
+            !is_encrypted(document),
+            "the file is not encrypted",
+        );
+        push_check(
+            &mut checks,
+            "no-javascript",
+            !has_javascript(document),
+            "the file contains no JavaScript actions",
+        );
+        push_check(
+            &mut checks,
+            "document-id",
+            document.trailer.has(b"ID"),
+            "the trailer carries a file identifier",
+        );
+    }
+
+    PdfReport {
+        profile: profile.map(str::to_string),
+        valid: checks.iter().all(|check| check.passed),
+        metadata,
+        outline_entries,
+        checks,
+    }
+}
+
+fn push_check(checks: &mut Vec<PdfCheck>, name: &str, passed: bool, detail: impl Into<String>) {
+    checks.push(PdfCheck {
+        name: name.to_string(),
+        passed,
+        detail: detail.into(),
+    });
+}
+
+/// 从 Markdown YAML front matter 读取标题、作者和关键词
+///
+/// Keys are read with [`front_matter::values`], so authors may be a list of
+/// names or of maps with a `name`.
+pub fn front_matter_metadata(content: &str) -> PdfMetadata {
+    let Some(yaml) = front_matter::block(content) else {
+        return PdfMetadata::default();
+    };
+    let authors = front_matter::values(yaml, "author");
+    PdfMetadata {
+        title: front_matter::value(yaml, "title"),
+        author: (!authors.is_empty()).then(|| authors.join(", ")),
+        keywords: front_matter::values(yaml, "keywords")
+            .iter()
+            .flat_map(|keywords| keywords.split(','))
+            .map(str::trim)
+            .filter(|keyword| !keyword.is_empty())
+            .map(str::to_string)
+            .collect(),
+        revision: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        front_matter_metadata, inspect_pdf, postprocess_pdf, srgb_icc_profile, xmp_date,
+        PdfMetadata,
+    };
+    use lopdf::{dictionary, Document, Object, Stream};
+    use std::path::Path;
+
+    fn write_sample_pdf(path: &Path, with_outline: bool, embedded_font: bool) {
+        let mut document = Document::with_version("1.7");
+        let pages_id = document.new_object_id();
+        let descriptor = if embedded_font {
+            let program = document.add_object(Stream::new(dictionary! {}, b"font".to_vec()));
+            document.add_object(dictionary! {
+                "Type" => "FontDescriptor",
+                "FontName" => "LMRoman10-Regular",
+                "FontFile" => program,
+            })
+        } else {
+            document.add_object(dictionary! {
+                "Type" => "FontDescriptor",
+                "FontName" => "LMRoman10-Regular",
+            })
+        };
+        let font = document.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "LMRoman10-Regular",
+            "FontDescriptor" => descriptor,
+        });
+        let content = document.add_object(Stream::new(dictionary! {}, b"BT ET".to_vec()));
+        let page = document.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "Contents" => content,
+            "Resources" => dictionary! { "Font" => dictionary! { "F1" => font } },
+            "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+        });
+        document.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => vec![page.into()],
+                "Count" => 1,
+            }),
+        );
+        let mut catalog = dictionary! { "Type" => "Catalog", "Pages" => pages_id };
+        if with_outline {
+            let outlines_id = document.new_object_id();
+            let first = document.add_object(dictionary! {
+                "Title" => Object::string_literal("Intro"),
+                "Parent" => outlines_id,
+                "Dest" => vec![page.into(), "Fit".into()],
+            });
+            document.objects.insert(
+                outlines_id,
+                Object::Dictionary(dictionary! {
+                    "Type" => "Outlines",
+                    "First" => first,
+                    "Last" => first,
+                    "Count" => 1,
+                }),
+            );
+            catalog.set("Outlines", outlines_id);
+        }
+        let catalog = document.add_object(catalog);
+        let info = document.add_object(dictionary! {
+            "Producer" => Object::string_literal("xdvipdfmx"),
+            "CreationDate" => Object::string_literal("D:20261018093000+08'00'"),
+        });
+        document.trailer.set("Root", catalog);
+        document.trailer.set("Info", info);
+        document.save(path).expect("sample PDF");
+    }
+
+    #[test]
+    fn front_matter_scalars_and_lists_become_pdf_metadata() {
+        let metadata = front_matter_metadata(
+            "---\ntitle: \"电源设计手册\"\nauthor:\n  - name: Li Wei\n    affiliation: lab\n  - Chen Jing\nkeywords: [GaN, 'power stage']\n---\n# Body\n",
+        );
+        assert_eq!(metadata.title.as_deref(), Some("电源设计手册"));
+        assert_eq!(metadata.author.as_deref(), Some("Li Wei, Chen Jing"));
+        assert_eq!(metadata.keywords, ["GaN", "power stage"]);
+        assert_eq!(
+            front_matter_metadata("# No front matter\n"),
+            PdfMetadata::default()
+        );
+    }
+
+    #[test]
+    fn pdf_dates_convert_to_xmp_dates() {
+        assert_eq!(
+            xmp_date("D:20261018093000+08'00'").as_deref(),
+            Some("2026-10-18T09:30:00+08:00")
+        );
+        assert_eq!(
+            xmp_date("D:2026101809").as_deref(),
+            Some("2026-10-18T09:00:00")
+        );
+        assert_eq!(xmp_date("yesterday"), None);
+    }
+
+    #[test]
+    fn generated_icc_profile_has_a_consistent_header_and_tag_table() {
+        let profile = srgb_icc_profile();
+        let size = u32::from_be_bytes(profile[0..4].try_into().expect("size")) as usize;
+        assert_eq!(size, profile.len());
+        assert_eq!(&profile[36..40], b"acsp");
+        assert_eq!(&profile[12..20], b"mntrRGB ");
+        let tags = u32::from_be_bytes(profile[128..132].try_into().expect("count")) as usize;
+        assert_eq!(tags, 9);
+        for index in 0..tags {
+            let entry = 132 + index * 12;
+            let offset =
+                u32::from_be_bytes(profile[entry + 4..entry + 8].try_into().expect("offset"));
+            let length =
+                u32::from_be_bytes(profile[entry + 8..entry + 12].try_into().expect("length"));
+            assert_eq!(offset % 4, 0);
+            assert!((offset + length) as usize <= profile.len());
+        }
+    }
+
+    #[test]
+    fn stamping_writes_info_and_xmp_and_completes_pdf_a_2b() {
+        let directory = tempfile::tempdir().expect("temporary PDF");
+        let pdf = directory.path().join("manual.pdf");
+        write_sample_pdf(&pdf, true, true);
+        let metadata = PdfMetadata {
+            title: Some("电源设计手册".to_string()),
+            author: Some("Li Wei".to_string()),
+            keywords: vec!["GaN".to_string(), "power stage".to_string()],
+            revision: Some("4f2c1e9".to_string()),
+        };
+
+        let report = postprocess_pdf(&pdf, &metadata, true, Some("PDF/A-2b")).expect("stamp");
+
+        assert!(report.valid, "{:#?}", report.checks);
+        assert_eq!(report.profile.as_deref(), Some("pdf-a-2b"));
+        assert_eq!(report.metadata, metadata);
+        assert_eq!(report.outline_entries, 1);
+        // The stamped file validates on its own, as on a cache hit.
+        assert_eq!(
+            inspect_pdf(&pdf, Some("pdf-a-2b")).expect("inspect"),
+            report
+        );
+        let stamped = std::fs::read(&pdf).expect("stamped PDF");
+        let text = String::from_utf8_lossy(&stamped);
+        assert!(text.contains("<pdfaid:part>2</pdfaid:part>"));
+        assert!(text.contains("<xmp:CreateDate>2026-10-18T09:30:00+08:00</xmp:CreateDate>"));
+        assert!(text.contains("<pdf:Producer>xdvipdfmx</pdf:Producer>"));
+        // The output intent names the profile that is actually embedded.
+        assert_eq!(text.matches("sRGB gamma 2.2 (OmniDoc)").count(), 3);
+        assert!(!text.contains("IEC61966"));
+    }
+
+    #[test]
+    fn missing_outline_and_unembedded_fonts_are_reported() {
+        let directory = tempfile::tempdir().expect("temporary PDF");
+        let pdf = directory.path().join("draft.pdf");
+        write_sample_pdf(&pdf, false, false);
+
+        let plain = inspect_pdf(&pdf, None).expect("inspect");
+        let failed = |report: &super::PdfReport| {
+            report
+                .checks
+                .iter()
+                .filter(|check| !check.passed)
+                .map(|check| check.name.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(failed(&plain), ["title", "xmp-metadata", "outline"]);
+
+        let report =
+            postprocess_pdf(&pdf, &PdfMetadata::default(), true, Some("pdf-a-2b")).expect("stamp");
+        assert_eq!(failed(&report), ["title", "outline", "embedded-fonts"]);
+        assert!(report
+            .checks
+            .iter()
+            .any(|check| check.name == "embedded-fonts"
+                && check.detail.contains("LMRoman10-Regular")));
+        assert!(postprocess_pdf(&pdf, &PdfMetadata::default(), true, Some("pdf-x")).is_err());
+    }
+
+    #[test]
+    fn unreadable_pdfs_fail_only_when_a_profile_is_requested() {
+        let directory = tempfile::tempdir().expect("temporary PDF");
+        let pdf = directory.path().join("engine.pdf");
+        let original = b"%PDF-1.7\nnot an object graph lopdf understands\n%%EOF\n";
+        std::fs::write(&pdf, original).expect("write PDF");
+        let metadata = PdfMetadata {
+            title: Some("Manual".to_string()),
+            ..PdfMetadata::default()
+        };
+
+        let report = postprocess_pdf(&pdf, &metadata, true, None).expect("non-fatal");
+        assert!(!report.valid);
+        assert_eq!(report.checks.len(), 1);
+        assert_eq!(report.checks[0].name, "readable");
+        assert!(report.checks[0].detail.contains("cannot read PDF"));
+        assert_eq!(inspect_pdf(&pdf, None).expect("inspect"), report);
+        assert_eq!(std::fs::read(&pdf).expect("PDF"), original);
+
+        assert!(postprocess_pdf(&pdf, &metadata, true, Some("pdf-a-2b")).is_err());
+        assert!(inspect_pdf(&pdf, Some("pdf-a-2b")).is_err());
+    }
+}
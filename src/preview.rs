@@ -0,0 +1,452 @@
+//! Live-reload preview server used by `omnidoc watch --serve`.
+//!
+//! A small HTTP/1.1 server on the loopback interface serves the build
+//! directory. HTML responses get a script that listens on a Server-Sent Events
+//! stream (`/__omnidoc/events`): `reload` after a successful rebuild, and
+//! `failure` with the compact build diagnostic, which the page shows as an
+//! overlay. PDFs are previewed through `/__omnidoc/pdf/<path>`, an HTML page
+//! embedding the PDF so it reloads the same way.
+
+use crate::error::{OmniDocError, Result};
+use percent_encoding::percent_decode_str;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+use std::thread;
+use std::time::Duration;
+
+const EVENTS_PATH: &str = "/__omnidoc/events";
+const PDF_VIEWER_PREFIX: &str = "/__omnidoc/pdf/";
+const HEARTBEAT: Duration = Duration::from_secs(15);
+
+const LIVE_RELOAD_SCRIPT: &str = r#"<script data-omnidoc-live-reload>
+(() => {
+  const events = new EventSource('/__omnidoc/events');
+  let overlay = null;
+  events.addEventListener('reload', () => location.reload());
+  events.addEventListener('failure', (event) => {
+    const { message } = JSON.parse(event.data);
+    if (!overlay) {
+      overlay = document.createElement('div');
+      overlay.id = 'omnidoc-build-failure';
+      overlay.style.cssText = 'position:fixed;inset:0;z-index:2147483647;overflow:auto;' +
+        'background:rgba(24,24,27,.94);color:#fafafa;padding:2rem;font:14px/1.5 ui-monospace,monospace';
+      const title = document.createElement('strong');
+      title.textContent = 'OmniDoc rebuild failed';
+      title.style.cssText = 'display:block;color:#f87171;margin-bottom:1rem';
+      const detail = document.createElement('pre');
+      detail.style.cssText = 'white-space:pre-wrap;margin:0';
+      overlay.append(title, detail);
+      document.body.append(overlay);
+    }
+    overlay.lastChild.textContent = message;
+  });
+})();
+</script>
+"#;
+
+/// Last build outcome, shared with every open event stream.
+#[derive(Debug, Default)]
+struct BuildState {
+    generation: u64,
+    failure: Option<String>,
+}
+
+#[derive(Debug)]
+struct Shared {
+    root: PathBuf,
+    state: Mutex<BuildState>,
+    changed: Condvar,
+}
+
+/// A running preview server. The listener thread lives as long as the process.
+#[derive(Debug, Clone)]
+pub struct PreviewServer {
+    address: SocketAddr,
+    shared: Arc<Shared>,
+}
+
+impl PreviewServer {
+    /// Serve `root` on `127.0.0.1:port`; port 0 picks a free port.
+    pub fn start(root: &Path, port: u16) -> Result<Self> {
+        std::fs::create_dir_all(root)?;
+        let root = root.canonicalize()?;
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port)).map_err(|error| {
+            OmniDocError::Other(format!(
+                "Failed to start the preview server on port {port}: {error}"
+            ))
+        })?;
+        let address = listener.local_addr()?;
+        let shared = Arc::new(Shared {
+            root,
+            state: Mutex::new(BuildState::default()),
+            changed: Condvar::new(),
+        });
+        let server_shared = Arc::clone(&shared);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let shared = Arc::clone(&server_shared);
+                thread::spawn(move || {
+                    let _ = serve_connection(stream, &shared);
+                });
+            }
+        });
+        Ok(Self { address, shared })
+    }
+
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Browser URL of `artifact`, if it lives under the served directory.
+    /// PDFs point at the reloading viewer page.
+    pub fn url_for(&self, artifact: &Path) -> Option<String> {
+        let artifact = artifact
+            .canonicalize()
+            .unwrap_or_else(|_| artifact.to_path_buf());
+        let relative = artifact.strip_prefix(&self.shared.root).ok()?;
+        let relative = relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let is_pdf = artifact
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("pdf"));
+        Some(if is_pdf {
+            format!("http://{}{PDF_VIEWER_PREFIX}{relative}", self.address)
+        } else {
+            format!("http://{}/{relative}", self.address)
+        })
+    }
+
+    /// Tell open pages to reload after a successful build.
+    pub fn notify_success(&self) {
+        self.publish(None);
+    }
+
+    /// Show `diagnostic` as an overlay on open pages.
+    pub fn notify_failure(&self, diagnostic: impl Into<String>) {
+        self.publish(Some(diagnostic.into()));
+    }
+
+    fn publish(&self, failure: Option<String>) {
+        let mut state = self
+            .shared
+            .state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        state.generation += 1;
+        state.failure = failure;
+        self.shared.changed.notify_all();
+    }
+}
+
+fn serve_connection(stream: TcpStream, shared: &Shared) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Headers are not needed; drain them so the client sees a clean close.
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+    }
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let target = parts.next().unwrap_or("/");
+    let mut stream = stream;
+    if method != "GET" && method != "HEAD" {
+        return respond(
+            &mut stream,
+            "405 Method Not Allowed",
+            "text/plain",
+            b"method not allowed\n",
+            true,
+        );
+    }
+    let head_only = method == "HEAD";
+    let path = target.split(['?', '#']).next().unwrap_or("/");
+    let path = percent_decode_str(path).decode_utf8_lossy().into_owned();
+    if path == EVENTS_PATH {
+        return stream_events(stream, shared);
+    }
+    if let Some(pdf) = path.strip_prefix(PDF_VIEWER_PREFIX) {
+        let page = pdf_viewer_page(pdf);
+        return respond(
+            &mut stream,
+            "200 OK",
+            "text/html; charset=utf-8",
+            page.as_bytes(),
+            !head_only,
+        );
+    }
+    let Some(file) = resolve_request_path(&shared.root, &path) else {
+        return respond(
+            &mut stream,
+            "404 Not Found",
+            "text/plain",
+            b"not found\n",
+            !head_only,
+        );
+    };
+    if file.is_dir() {
+        let page = directory_listing(&shared.root, &file);
+        return respond(
+            &mut stream,
+            "200 OK",
+            "text/html; charset=utf-8",
+            page.as_bytes(),
+            !head_only,
+        );
+    }
+    let content_type = content_type(&file);
+    let mut body = std::fs::read(&file)?;
+    if content_type.starts_with("text/html") {
+        body = inject_live_reload(&String::from_utf8_lossy(&body)).into_bytes();
+    }
+    respond(&mut stream, "200 OK", content_type, &body, !head_only)
+}
+
+fn respond(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &[u8],
+    include_body: bool,
+) -> std::io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        body.len()
+    )?;
+    if include_body {
+        stream.write_all(body)?;
+    }
+    stream.flush()
+}
+
+/// Hold the connection open and forward build outcomes as they happen. A new
+/// stream immediately receives a pending failure, so pages opened after a
+/// broken rebuild still show the overlay.
+fn stream_events(mut stream: TcpStream, shared: &Shared) -> std::io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-store\r\nConnection: keep-alive\r\n\r\n"
+    )?;
+    let (mut seen, pending) = {
+        let state = shared.state.lock().unwrap_or_else(PoisonError::into_inner);
+        (state.generation, state.failure.clone())
+    };
+    match pending {
+        Some(failure) => write_event(&mut stream, "failure", &failure)?,
+        None => write!(stream, ": connected\n\n")?,
+    }
+    stream.flush()?;
+    loop {
+        // Never write to the socket while holding the lock: a stalled browser
+        // must not block the watch loop publishing the next build.
+        let outcome = {
+            let state = shared.state.lock().unwrap_or_else(PoisonError::into_inner);
+            let (state, wait) = shared
+                .changed
+                .wait_timeout_while(state, HEARTBEAT, |state| state.generation == seen)
+                .unwrap_or_else(PoisonError::into_inner);
+            seen = state.generation;
+            (!wait.timed_out()).then(|| state.failure.clone())
+        };
+        match outcome {
+            // Heartbeat comments also detect clients that went away.
+            None => write!(stream, ": heartbeat\n\n")?,
+            Some(Some(failure)) => write_event(&mut stream, "failure", &failure)?,
+            Some(None) => write!(stream, "event: reload\ndata: {{}}\n\n")?,
+        }
+        stream.flush()?;
+    }
+}
+
+fn write_event(stream: &mut TcpStream, event: &str, message: &str) -> std::io::Result<()> {
+    let data = serde_json::json!({ "message": message });
+    write!(stream, "event: {event}\ndata: {data}\n\n")
+}
+
+/// Map a URL path onto a file below `root`, refusing anything that escapes it.
+fn resolve_request_path(root: &Path, path: &str) -> Option<PathBuf> {
+    let relative = Path::new(path.trim_start_matches('/'));
+    if relative
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_) | Component::CurDir))
+    {
+        return None;
+    }
+    let mut candidate = root.join(relative).canonicalize().ok()?;
+    if !candidate.starts_with(root) {
+        return None;
+    }
+    if candidate.is_dir() && candidate.join("index.html").is_file() {
+        candidate = candidate.join("index.html");
+    }
+    Some(candidate)
+}
+
+/// Insert the live-reload script before `</body>`, or append it.
+fn inject_live_reload(html: &str) -> String {
+    match html.to_ascii_lowercase().rfind("</body>") {
+        Some(index) => format!("{}{LIVE_RELOAD_SCRIPT}{}", &html[..index], &html[index..]),
+        None => format!("{html}{LIVE_RELOAD_SCRIPT}"),
+    }
+}
+
+fn pdf_viewer_page(pdf: &str) -> String {
+    let source = format!("/{}", html_escape(pdf));
+    inject_live_reload(&format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{title}</title></head>\n<body style=\"margin:0\"><embed src=\"{source}\" type=\"application/pdf\" style=\"width:100vw;height:100vh;border:0\"></body></html>\n",
+        title = html_escape(pdf)
+    ))
+}
+
+fn directory_listing(root: &Path, directory: &Path) -> String {
+    let mut entries = std::fs::read_dir(directory)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    !path
+                        .file_name()
+                        .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    entries.sort();
+    let links = entries
+        .iter()
+        .filter_map(|path| {
+            let relative = path.strip_prefix(root).ok()?.to_string_lossy();
+            let relative = relative.replace('\\', "/");
+            let name = path.file_name()?.to_string_lossy();
+            let is_pdf = path
+                .extension()
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("pdf"));
+            let href = if is_pdf {
+                format!("{PDF_VIEWER_PREFIX}{relative}")
+            } else {
+                format!("/{relative}")
+            };
+            Some(format!(
+                "<li><a href=\"{}\">{}</a></li>",
+                html_escape(&href),
+                html_escape(&name)
+            ))
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    inject_live_reload(&format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>OmniDoc preview</title></head>\n<body><ul>\n{links}\n</ul></body></html>\n"
+    ))
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn content_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match extension.as_str() {
+        "html" | "htm" | "xhtml" => "text/html; charset=utf-8",
+        "css" => "text/css; charset=utf-8",
+        "js" | "mjs" => "text/javascript; charset=utf-8",
+        "json" => "application/json",
+        "txt" | "md" => "text/plain; charset=utf-8",
+        "xml" => "application/xml",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "pdf" => "application/pdf",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "epub" => "application/epub+zip",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn get(server: &PreviewServer, path: &str) -> String {
+        let mut stream = TcpStream::connect(server.address()).unwrap();
+        write!(stream, "GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn html_gets_the_live_reload_script_and_paths_cannot_escape_the_root() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().join("build");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(
+            root.join("guide.html"),
+            "<html><body><p>x</p></body></html>",
+        )
+        .unwrap();
+        std::fs::write(temp.path().join("secret.txt"), "secret").unwrap();
+        let server = PreviewServer::start(&root, 0).unwrap();
+
+        let page = get(&server, "/guide.html");
+        assert!(page.starts_with("HTTP/1.1 200 OK"));
+        assert!(page.contains("<p>x</p><script data-omnidoc-live-reload>"));
+        assert!(page.ends_with("</body></html>"));
+        assert!(get(&server, "/../secret.txt").starts_with("HTTP/1.1 404"));
+        assert!(get(&server, "/%2e%2e/secret.txt").starts_with("HTTP/1.1 404"));
+        assert!(get(&server, "/").contains("href=\"/guide.html\""));
+        assert_eq!(
+            server.url_for(&root.join("guide.pdf")),
+            Some(format!(
+                "http://{}/__omnidoc/pdf/guide.pdf",
+                server.address()
+            ))
+        );
+    }
+
+    #[test]
+    fn event_stream_forwards_failures_and_reloads() {
+        let temp = tempfile::tempdir().unwrap();
+        let server = PreviewServer::start(temp.path(), 0).unwrap();
+        server.notify_failure("error: Failed to build project\n  context: project\n");
+
+        let stream = TcpStream::connect(server.address()).unwrap();
+        let mut writer = stream.try_clone().unwrap();
+        write!(writer, "GET {EVENTS_PATH} HTTP/1.1\r\n\r\n").unwrap();
+        let mut lines = BufReader::new(stream).lines().map(|line| line.unwrap());
+        let mut next_event = || {
+            let event = lines
+                .by_ref()
+                .find(|line| line.starts_with("event: "))
+                .unwrap();
+            (event, lines.next().unwrap())
+        };
+        let (event, data) = next_event();
+        assert_eq!(event, "event: failure");
+        assert!(data.contains(r#""message":"error: Failed to build project\n"#));
+
+        server.notify_success();
+        assert_eq!(next_event().0, "event: reload");
+    }
+}
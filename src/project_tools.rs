@@ -1,4 +1,5 @@
-use crate::build::executor::{BuildExecutor, LatexEnginePreference};
+use crate::bibliography;
+use crate::build::executor::{uses_typst_pdf_engine, BuildExecutor, LatexEnginePreference};
 use crate::build::pandoc_policy::{is_supported_format_key, PandocOutputKind};
 use crate::build::pipeline::{detect_project_type, ProjectType};
 use crate::build::tectonic;
@@ -11,8 +12,16 @@ use crate::extensions::{
     enabled_plugin_resources, enabled_plugins, materialize_theme_tokens, plugin_filters_for_output,
     resolve_selected_theme, PackageKind, ResolvedTheme,
 };
+use crate::front_matter;
+use crate::glossary;
+use crate::index;
+use crate::lint;
+use crate::pdf::PdfReport;
+use crate::terminal;
+use crate::terms::{self, TermsDictionary};
 use crate::utils;
 use crate::utils::directories::data_local_dir;
+use crate::xml::XmlValidationReport;
 use blake3::Hasher;
 use fs2::FileExt;
 use percent_encoding::percent_decode_str;
@@ -28,10 +37,18 @@ const CACHE_DIR: &str = ".omnidoc-cache";
 pub(crate) const INCLUDE_DEPFILE: &str = "include-files.d";
 pub(crate) const INCLUDE_CODE_DEPFILE: &str = "include-code-files.d";
 pub(crate) const LATEX_INPUT_DEPFILE: &str = "latex-inputs.d";
+pub(crate) const INCLUDE_FRAGMENT_LOG: &str = "include-fragments.log";
+pub(crate) const DIAGRAM_RENDER_LOG: &str = "diagram-renders.log";
+pub(crate) const SYNCTEX_DIR: &str = "synctex";
 const LOCK_FILE: &str = "omnidoc.lock";
 const REPORT_FILE: &str = "omnidoc-report.json";
 const PROJECT_LOCK_FILE: &str = "project.lock";
-const CACHE_VERSION: u32 = 7;
+const OUTPUT_SCRATCH_DIR: &str = "outputs";
+const DOCUMENTS_DIR: &str = "documents";
+const VARIANTS_DIR: &str = "variants";
+const FRAGMENTS_DIR: &str = "fragments";
+const RENDERS_DIR: &str = "renders";
+const CACHE_VERSION: u32 = 10;
 const LOCK_VERSION: u32 = 5;
 
 pub struct ProjectWriteLock {
@@ -73,12 +90,18 @@ pub enum IssueSeverity {
     Info,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct ProjectIssue {
     pub severity: IssueSeverity,
     pub message: String,
     pub path: Option<String>,
     pub line: Option<usize>,
+    /// 1-based column, for issues located on the Pandoc AST.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+    /// Lint rule id (see [`crate::lint::RULES`]) for configurable source checks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -96,6 +119,10 @@ pub struct ResolvedResource {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BuildReport {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub document: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
     pub output: String,
     pub target: String,
     pub skipped: bool,
@@ -105,6 +132,10 @@ pub struct BuildReport {
     pub input_digest: String,
     pub artifact_digest: Option<String>,
     pub compatibility: Option<EpubCompatibilityReport>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xml_validation: Option<XmlValidationReport>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pdf: Option<PdfReport>,
     pub dependencies: Vec<String>,
     pub resources: Vec<LockedResource>,
     pub toolchain: BTreeMap<String, String>,
@@ -132,6 +163,8 @@ pub struct BuildReportContext<'a> {
     pub config: &'a MergedConfig,
     pub artifact: &'a Path,
     pub compatibility: Option<EpubCompatibilityReport>,
+    pub xml_validation: Option<XmlValidationReport>,
+    pub pdf: Option<PdfReport>,
     pub issues: Vec<ProjectIssue>,
 }
 
@@ -159,6 +192,9 @@ pub struct CacheProbe {
 pub struct LockFile {
     pub lock_version: u32,
     pub omnidoc_version: String,
+    /// Build profile the targets were locked with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
     pub library: Option<LockedLibrary>,
     #[serde(default)]
     pub packages: Vec<LockedPackage>,
@@ -208,6 +244,7 @@ pub struct LockedResource {
 pub struct LockStatus {
     pub exists: bool,
     pub up_to_date: bool,
+    pub profile_up_to_date: bool,
     pub library_up_to_date: bool,
     pub packages_up_to_date: bool,
     pub toolchain_up_to_date: bool,
@@ -228,7 +265,23 @@ pub struct LockTargetStatus {
 }
 
 pub fn supported_outputs() -> &'static [&'static str] {
-    &["pdf", "html", "epub", "docx", "pptx", "latex"]
+    &[
+        "pdf",
+        "html",
+        "html-site",
+        "epub",
+        "docx",
+        "pptx",
+        "beamer",
+        "revealjs",
+        "gfm",
+        "plain",
+        "man",
+        "jats",
+        "docbook",
+        "latex",
+        "typst",
+    ]
 }
 
 pub fn default_all_outputs() -> Vec<String> {
@@ -276,7 +329,7 @@ pub fn validate_config(project_path: &Path, config: &MergedConfig) -> Vec<Projec
         if !is_supported_format_key(format) {
             issues.push(error(
                 format!(
-                    "Unsupported pandoc.format_options key '{}'. Supported keys: pdf, html, epub, docx, pptx, latex",
+                    "Unsupported pandoc.format_options key '{}'. Supported keys: pdf, html, html-site, epub, docx, pptx, beamer, revealjs, gfm, plain, man, jats, docbook, latex, typst",
                     format
                 ),
                 Some(".omnidoc.toml".to_string()),
@@ -298,6 +351,17 @@ pub fn validate_config(project_path: &Path, config: &MergedConfig) -> Vec<Projec
         ));
     }
 
+    if uses_typst_pdf_engine(&config.tool_paths)
+        && detect_project_type(config, project_path) == ProjectType::Latex
+    {
+        issues.push(error(
+            "The typst PDF engine renders Markdown through Pandoc; LaTeX projects need a LaTeX engine"
+                .to_string(),
+            None,
+            None,
+        ));
+    }
+
     if config.latex_backend.eq_ignore_ascii_case("engine") && config.max_latex_passes == 0 {
         issues.push(error(
             "build.max_latex_passes must be greater than 0 when build.latex_backend is engine"
@@ -394,6 +458,37 @@ pub fn validate_config(project_path: &Path, config: &MergedConfig) -> Vec<Projec
         }
     }
 
+    if let Some(profile) = config.pdf_profile.as_deref() {
+        if crate::pdf::normalize_pdf_profile(profile).is_none() {
+            issues.push(error(
+                format!(
+                    "Unsupported PDF profile '{}'. Supported profiles: pdf-a-2b",
+                    profile
+                ),
+                Some(".omnidoc.toml".to_string()),
+                None,
+            ));
+        }
+    }
+
+    if let Some(watermark) = &config.watermark {
+        if let Err(watermark_error) = crate::extensions::resolve_watermark(watermark) {
+            issues.push(error(
+                format!("Invalid watermark configuration: {watermark_error}"),
+                Some(".omnidoc.toml".to_string()),
+                None,
+            ));
+        }
+    }
+
+    if let Err(cache_error) = crate::cache::shared_cache_store(project_path, config) {
+        issues.push(error(
+            format!("Invalid shared cache configuration: {cache_error}"),
+            Some(".omnidoc.toml".to_string()),
+            None,
+        ));
+    }
+
     if !config.plugins_enabled.is_empty() {
         if let Err(plugin_error) = enabled_plugins(project_path, config) {
             issues.push(error(
@@ -464,15 +559,103 @@ pub fn validate_config(project_path: &Path, config: &MergedConfig) -> Vec<Projec
         );
     }
 
+    for file in glossary::files(project_path, config) {
+        let rel = display_relative(project_path, &file);
+        let Ok(content) = fs::read_to_string(&file) else {
+            issues.push(error(
+                format!("Configured glossary file not found: {rel}"),
+                Some(rel),
+                None,
+            ));
+            continue;
+        };
+        if let Err(message) = glossary::parse(&file, &content) {
+            issues.push(error(
+                format!("Invalid glossary file: {message}"),
+                Some(rel),
+                None,
+            ));
+        }
+    }
+
+    if let Some(program) = config
+        .index_program
+        .as_deref()
+        .filter(|program| index::IndexProgram::parse(program).is_none())
+    {
+        issues.push(error(
+            format!(
+                "Unsupported index.program '{program}'; choose makeindex, xindy, or zhmakeindex"
+            ),
+            Some(".omnidoc.toml".to_string()),
+            None,
+        ));
+    }
+
+    for (key, patterns) in [
+        ("links.ignore", &config.links_ignore),
+        ("links.allow", &config.links_allow),
+    ] {
+        for pattern in patterns {
+            if let Err(message) = regex::Regex::new(pattern) {
+                issues.push(error(
+                    format!("Invalid {key} pattern '{pattern}': {message}"),
+                    Some(".omnidoc.toml".to_string()),
+                    None,
+                ));
+            }
+        }
+    }
+
+    for (rule, level) in &config.lint_rules {
+        if lint::rule(rule).is_none() {
+            issues.push(error(
+                format!(
+                    "Unknown lint rule '{rule}' in [lint.rules]; choose {}",
+                    lint::rule_ids()
+                ),
+                Some(".omnidoc.toml".to_string()),
+                None,
+            ));
+        } else if lint::RuleLevel::parse(level).is_none() {
+            issues.push(error(
+                format!(
+                    "Unsupported lint.rules.{rule} '{level}'; choose error, warning, info, or off"
+                ),
+                Some(".omnidoc.toml".to_string()),
+                None,
+            ));
+        }
+    }
+
+    if let Some(dictionary) = terms::dictionary_path(project_path, config) {
+        let rel = display_relative(project_path, &dictionary);
+        if !dictionary.is_file() {
+            issues.push(error(
+                format!("Configured terms.dictionary not found: {rel}"),
+                Some(rel),
+                None,
+            ));
+        } else if let Err(dictionary_error) = TermsDictionary::load(&dictionary) {
+            issues.push(error(
+                format!("Invalid terms dictionary: {dictionary_error}"),
+                Some(rel),
+                None,
+            ));
+        }
+    }
+
     issues
 }
 
-pub fn lint_project(project_path: &Path) -> Vec<ProjectIssue> {
+/// Missing local resources named by Markdown and LaTeX sources, reported
+/// under the rules of [`lint::RULES`]. Markdown is read through Pandoc's AST
+/// when Pandoc is available (see [`crate::lint`]); `[lint.rules]` and
+/// suppression comments decide what is reported and how severely.
+pub fn lint_project(project_path: &Path, config: &MergedConfig) -> Vec<ProjectIssue> {
     let mut issues = Vec::new();
-    let image_re = regex::Regex::new(r"!\[[^\]]*\]\(([^)]+)\)").expect("image regex");
-    let link_re =
-        regex::Regex::new(r"(?P<bang>!?)\[[^\]]+\]\((?P<target>[^)]+)\)").expect("link regex");
-    let include_re = regex::Regex::new(r#"include(?:-code)?="([^"]+)""#).expect("include regex");
+    let settings = lint::RuleSettings::from_config(config);
+    let mut markdown = lint::MarkdownReferences::new(project_path, config);
     let latex_resource_re = regex::Regex::new(
         r#"\\(?P<command>includegraphics|input|include|bibliography|addbibresource)(?:\s*\[[^]]*\])?\s*\{(?P<target>[^}]+)\}"#,
     )
@@ -488,215 +671,1320 @@ pub fn lint_project(project_path: &Path) -> Vec<ProjectIssue> {
             .and_then(|value| value.to_str())
             .unwrap_or("")
             .to_ascii_lowercase();
-        let is_markdown = matches!(extension.as_str(), "md" | "markdown");
-        let is_latex = matches!(extension.as_str(), "tex" | "sty" | "cls");
-        let mut markdown_fence: Option<char> = None;
-        let mut latex_verbatim: Option<String> = None;
+        let mut findings = Vec::new();
+
+        if matches!(extension.as_str(), "md" | "markdown") {
+            for reference in markdown.read(&file, &content) {
+                if let Some(missing) = missing_local_target(project_path, &file, &reference.target)
+                {
+                    findings.push(lint::Finding {
+                        rule: reference.kind.rule(),
+                        message: format!("Referenced local resource not found: {missing}"),
+                        line: reference.line,
+                        column: Some(reference.column),
+                    });
+                }
+            }
+            let suppressions = lint::Suppressions::parse(
+                markdown_prose_lines(&content),
+                lint::CommentSyntax::Html,
+            );
+            issues.extend(lint::file_issues(&rel, findings, &suppressions, &settings));
+            continue;
+        }
+        if !matches!(extension.as_str(), "tex" | "sty" | "cls") {
+            continue;
+        }
 
+        let mut latex_verbatim: Option<String> = None;
         for (line_index, line) in content.lines().enumerate() {
             let line_no = line_index + 1;
-
-            if is_markdown {
-                let trimmed = line.trim_start();
-                if let Some(marker) = markdown_fence {
-                    if trimmed.starts_with(&marker.to_string().repeat(3)) {
-                        markdown_fence = None;
-                    }
-                    continue;
-                }
-                if trimmed.starts_with("```") {
-                    markdown_fence = Some('`');
-                    continue;
-                }
-                if trimmed.starts_with("~~~") {
-                    markdown_fence = Some('~');
-                    continue;
-                }
-                if line.starts_with("    ") || line.starts_with('\t') {
-                    continue;
-                }
-
-                for capture in image_re.captures_iter(line) {
-                    check_local_target(
-                        project_path,
-                        &file,
-                        &capture[1],
-                        &rel,
-                        line_no,
-                        &mut issues,
-                    );
-                }
-                for capture in link_re.captures_iter(line) {
-                    if capture.name("bang").map(|m| m.as_str()) == Some("!") {
-                        continue;
-                    }
-                    let target = capture.name("target").map(|m| m.as_str()).unwrap_or("");
-                    if is_local_path(target) {
-                        check_local_target(project_path, &file, target, &rel, line_no, &mut issues);
-                    }
+            if let Some(environment) = latex_verbatim.as_deref() {
+                if line.contains(&format!(r"\end{{{environment}}}")) {
+                    latex_verbatim = None;
                 }
-                for capture in include_re.captures_iter(line) {
-                    check_local_target(
-                        project_path,
-                        &file,
-                        &capture[1],
-                        &rel,
-                        line_no,
-                        &mut issues,
-                    );
+                continue;
+            }
+            for environment in ["verbatim", "Verbatim", "lstlisting", "minted"] {
+                if line.contains(&format!(r"\begin{{{environment}}}")) {
+                    latex_verbatim = Some(environment.to_string());
+                    break;
                 }
+            }
+            if latex_verbatim.is_some() {
                 continue;
             }
 
-            if is_latex {
-                if let Some(environment) = latex_verbatim.as_deref() {
-                    if line.contains(&format!(r"\end{{{environment}}}")) {
-                        latex_verbatim = None;
-                    }
-                    continue;
-                }
-                for environment in ["verbatim", "Verbatim", "lstlisting", "minted"] {
-                    if line.contains(&format!(r"\begin{{{environment}}}")) {
-                        latex_verbatim = Some(environment.to_string());
-                        break;
-                    }
-                }
-                if latex_verbatim.is_some() {
+            let source = strip_latex_comment(line);
+            for capture in latex_resource_re.captures_iter(source) {
+                let command = capture
+                    .name("command")
+                    .map(|value| value.as_str())
+                    .unwrap_or("");
+                let Some(targets) = capture.name("target") else {
                     continue;
-                }
-
-                let source = strip_latex_comment(line);
-                for capture in latex_resource_re.captures_iter(source) {
-                    let command = capture
-                        .name("command")
-                        .map(|value| value.as_str())
-                        .unwrap_or("");
-                    let target = capture
-                        .name("target")
-                        .map(|value| value.as_str())
-                        .unwrap_or("");
-                    for target in target
-                        .split(',')
-                        .map(str::trim)
-                        .filter(|value| !value.is_empty())
-                    {
-                        check_latex_target(
-                            project_path,
-                            &file,
-                            target,
-                            command,
-                            &rel,
-                            line_no,
-                            &mut issues,
-                        );
+                };
+                let column = source[..capture.get(0).map_or(0, |m| m.start())]
+                    .chars()
+                    .count()
+                    + 1;
+                for target in targets
+                    .as_str()
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|value| !value.is_empty())
+                {
+                    if latex_target_missing(project_path, &file, target, command) {
+                        findings.push(lint::Finding {
+                            rule: lint::MISSING_LATEX_RESOURCE,
+                            message: format!("Referenced local resource not found: {target}"),
+                            line: line_no,
+                            column: Some(column),
+                        });
                     }
                 }
             }
         }
+        let suppressions = lint::Suppressions::parse(
+            content
+                .lines()
+                .enumerate()
+                .map(|(index, line)| (index + 1, line)),
+            lint::CommentSyntax::Latex,
+        );
+        issues.extend(lint::file_issues(&rel, findings, &suppressions, &settings));
     }
 
+    markdown.finish();
     issues
 }
 
-fn strip_latex_comment(line: &str) -> &str {
-    let mut escaped = false;
-    for (index, character) in line.char_indices() {
-        if character == '%' && !escaped {
-            return &line[..index];
+struct AudienceSyntax {
+    attributes: regex::Regex,
+    when: regex::Regex,
+    span: regex::Regex,
+    crossref: regex::Regex,
+    anchor: regex::Regex,
+}
+
+/// Warn when a `[variants]` entry hides a label that content it keeps still
+/// references. Labels are `{#id}` attributes; references are pandoc-crossref
+/// citations such as `@fig:plot` and `](#id)` links. Conditions follow the
+/// bundled `audience.lua` filter.
+pub fn lint_variants(
+    project_path: &Path,
+    config: &MergedConfig,
+    variants: &BTreeMap<String, Vec<String>>,
+) -> Vec<ProjectIssue> {
+    if variants.is_empty() {
+        return Vec::new();
+    }
+    let syntax = AudienceSyntax {
+        attributes: regex::Regex::new(r"\{([^}]*)\}").expect("attribute regex"),
+        when: regex::Regex::new(r#"when\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s}]+))"#)
+            .expect("when regex"),
+        span: regex::Regex::new(r"\[[^\]]*\]\{([^}]*)\}").expect("span regex"),
+        crossref: crossref_reference_regex(),
+        anchor: regex::Regex::new(r"\]\(#([^)\s]+)\)").expect("anchor regex"),
+    };
+    let sources = source_files(project_path)
+        .into_iter()
+        .filter(|file| {
+            file.extension()
+                .and_then(|value| value.to_str())
+                .is_some_and(|value| {
+                    matches!(value.to_ascii_lowercase().as_str(), "md" | "markdown")
+                })
+        })
+        .filter_map(|file| {
+            let content = fs::read_to_string(&file).ok()?;
+            Some((display_relative(project_path, &file), content))
+        })
+        .collect::<Vec<_>>();
+
+    let mut findings = Vec::new();
+    for (variant, audiences) in variants {
+        let mut visible_labels = BTreeSet::new();
+        let mut hidden_labels = BTreeSet::new();
+        let mut references = Vec::new();
+        for (rel, content) in &sources {
+            scan_variant_source(
+                content,
+                audiences,
+                &syntax,
+                |label, visible, line_no| match label {
+                    VariantItem::Label(label) if visible => {
+                        visible_labels.insert(label);
+                    }
+                    VariantItem::Label(label) => {
+                        hidden_labels.insert(label);
+                    }
+                    VariantItem::Reference(label) if visible => {
+                        references.push((label, rel.clone(), line_no));
+                    }
+                    VariantItem::Reference(_) => {}
+                },
+            );
         }
-        if character == '\\' {
-            escaped = !escaped;
-        } else {
-            escaped = false;
+        for (label, rel, line_no) in references {
+            if hidden_labels.contains(&label) && !visible_labels.contains(&label) {
+                findings.push(finding(
+                    lint::HIDDEN_VARIANT_LABEL,
+                    format!(
+                        "Variant '{variant}' hides '#{label}', which visible content references"
+                    ),
+                    &rel,
+                    line_no,
+                ));
+            }
         }
     }
-    line
+    lint::project_issues(
+        project_path,
+        findings,
+        &lint::RuleSettings::from_config(config),
+    )
 }
 
-fn check_latex_target(
-    project_path: &Path,
-    source_file: &Path,
-    target: &str,
-    command: &str,
-    rel: &str,
-    line: usize,
-    issues: &mut Vec<ProjectIssue>,
+enum VariantItem {
+    Label(String),
+    Reference(String),
+}
+
+fn scan_variant_source(
+    content: &str,
+    audiences: &[String],
+    syntax: &AudienceSyntax,
+    mut record: impl FnMut(VariantItem, bool, usize),
 ) {
-    if target.contains(['\\', '#']) || !is_local_path(target) {
-        return;
+    let mut markdown_fence: Option<char> = None;
+    // Visibility of every open fenced Div; content is visible only when all are.
+    let mut divs: Vec<bool> = Vec::new();
+    for (line_index, line) in content.lines().enumerate() {
+        let line_no = line_index + 1;
+        let trimmed = line.trim_start();
+        if let Some(marker) = markdown_fence {
+            if trimmed.starts_with(&marker.to_string().repeat(3)) {
+                markdown_fence = None;
+            }
+            continue;
+        }
+        if trimmed.starts_with("```") {
+            markdown_fence = Some('`');
+        } else if trimmed.starts_with("~~~") {
+            markdown_fence = Some('~');
+        }
+
+        if trimmed.starts_with(":::") {
+            let rest = trimmed.trim_matches(':').trim();
+            if rest.is_empty() {
+                divs.pop();
+                continue;
+            }
+            let attributes = match rest.strip_prefix('{') {
+                Some(rest) => rest.split('}').next().unwrap_or_default().to_string(),
+                None => format!(".{}", rest.split_whitespace().next().unwrap_or_default()),
+            };
+            divs.push(audience_visible(&attributes, audiences, syntax).unwrap_or(true));
+        }
+        let line_visible = divs.iter().all(|visible| *visible);
+
+        let hidden_spans = syntax
+            .span
+            .captures_iter(line)
+            .filter(|capture| audience_visible(&capture[1], audiences, syntax) == Some(false))
+            .filter_map(|capture| capture.get(0))
+            .map(|span| span.range())
+            .collect::<Vec<_>>();
+        let visible_at =
+            |offset: usize| line_visible && !hidden_spans.iter().any(|span| span.contains(&offset));
+
+        for capture in syntax.attributes.captures_iter(line) {
+            let offset = capture.get(0).map_or(0, |m| m.start());
+            for label in capture[1]
+                .split_whitespace()
+                .filter_map(|token| token.strip_prefix('#'))
+                .filter(|label| !label.is_empty())
+            {
+                record(
+                    VariantItem::Label(label.to_string()),
+                    visible_at(offset),
+                    line_no,
+                );
+            }
+        }
+        for capture in syntax.crossref.captures_iter(line) {
+            let offset = capture.get(1).map_or(0, |m| m.start());
+            record(
+                VariantItem::Reference(crossref_reference_label(&capture[1])),
+                visible_at(offset),
+                line_no,
+            );
+        }
+        for capture in syntax.anchor.captures_iter(line) {
+            let offset = capture.get(0).map_or(0, |m| m.start());
+            let label = capture[1].trim_end_matches(['.', ':', '-']);
+            record(
+                VariantItem::Reference(label.to_string()),
+                visible_at(offset),
+                line_no,
+            );
+        }
     }
+}
 
-    let base = source_file.parent().unwrap_or(project_path);
-    let path = Path::new(target);
-    let mut candidates = vec![path.to_path_buf()];
-    if path.extension().is_none() {
-        let extensions: &[&str] = match command {
-            "includegraphics" => &["pdf", "png", "jpg", "jpeg", "svg", "eps"],
-            "bibliography" | "addbibresource" => &["bib"],
-            "input" | "include" => &["tex"],
-            _ => &[],
-        };
-        candidates.extend(
-            extensions
-                .iter()
-                .map(|extension| path.with_extension(extension)),
-        );
+/// Whether content with these Pandoc attributes survives for `audiences`;
+/// `None` when the attributes carry no audience condition.
+fn audience_visible(
+    attributes: &str,
+    audiences: &[String],
+    syntax: &AudienceSyntax,
+) -> Option<bool> {
+    let active = |audience: &str| audiences.iter().any(|active| active == audience);
+    let only = attributes
+        .split_whitespace()
+        .filter_map(|token| token.strip_prefix(".only-"))
+        .collect::<Vec<_>>();
+    let when = syntax.when.captures(attributes).and_then(|capture| {
+        capture
+            .get(1)
+            .or_else(|| capture.get(2))
+            .or_else(|| capture.get(3))
+            .map(|value| value.as_str())
+    });
+    if only.is_empty() && when.is_none() {
+        return None;
     }
 
-    if candidates.iter().any(|candidate| {
-        base.join(candidate).exists()
-            || project_path.join(candidate).exists()
-            || project_path.join("tex").join(candidate).exists()
-    }) {
-        return;
+    let mut visible = only.is_empty() || only.iter().any(|audience| active(audience));
+    if let Some(when) = when {
+        let mut wanted = false;
+        let mut matched = false;
+        for token in when
+            .split(|character: char| character == ',' || character.is_whitespace())
+            .filter(|token| !token.is_empty())
+        {
+            match token.strip_prefix('!') {
+                Some(audience) => visible &= !active(audience),
+                None => {
+                    wanted = true;
+                    matched |= active(token);
+                }
+            }
+        }
+        visible &= matched || !wanted;
     }
+    Some(visible)
+}
 
-    issues.push(warning(
-        format!("Referenced local resource not found: {target}"),
-        Some(rel.to_string()),
-        Some(line),
-    ));
+/// pandoc-crossref label prefixes. Labels use `fig:plot` or the `fig-plot`
+/// spelling of the figure blocks in `BLOCKS.md`.
+const CROSSREF_PREFIXES: [&str; 5] = ["fig", "tbl", "sec", "eq", "lst"];
+
+struct CrossrefSyntax {
+    attributes: regex::Regex,
+    reference: regex::Regex,
+    anchor: regex::Regex,
+    code_span: regex::Regex,
 }
 
-pub fn dependency_graph(project_path: &Path, config: &MergedConfig) -> DependencyGraph {
-    let mut files = BTreeSet::new();
-    let mut pending = Vec::new();
-    let mut depfile_resources = BTreeMap::new();
+enum CrossrefItem<'a> {
+    Label(&'a str),
+    Reference(String),
+    Anchor(&'a str),
+}
 
-    track_dependency(
-        project_path,
-        project_path,
-        Path::new(".omnidoc.toml"),
-        &mut files,
-        &mut pending,
-    );
+/// Check pandoc-crossref labels across the entry and every chapter it
+/// includes: references without a label, labels declared twice, and labels
+/// nothing references. Labels come from `{#fig:id}` attributes on images,
+/// headings, tables, equations, code blocks and figure blocks such as
+/// `.bitfield` or `.circuit`; references are `@fig:id` citations, and
+/// `](#id)` links also count as uses.
+pub fn lint_crossrefs(project_path: &Path, config: &MergedConfig) -> Vec<ProjectIssue> {
+    let syntax = CrossrefSyntax {
+        attributes: regex::Regex::new(r"\{([^}]*)\}").expect("attribute regex"),
+        reference: crossref_reference_regex(),
+        anchor: regex::Regex::new(r"\]\(#([^)\s]+)\)").expect("anchor regex"),
+        code_span: regex::Regex::new(r"`[^`]*`").expect("code span regex"),
+    };
+    let mut labels: BTreeMap<String, Vec<(String, usize)>> = BTreeMap::new();
+    let mut references = Vec::new();
+    let mut anchors = BTreeSet::new();
+    for rel in dependency_graph(project_path, config).files {
+        let is_markdown = Path::new(&rel)
+            .extension()
+            .and_then(|value| value.to_str())
+            .is_some_and(|value| matches!(value.to_ascii_lowercase().as_str(), "md" | "markdown"));
+        let Some(content) = is_markdown
+            .then(|| fs::read_to_string(project_path.join(&rel)).ok())
+            .flatten()
+        else {
+            continue;
+        };
+        scan_crossrefs(&content, &syntax, |item, line_no| match item {
+            CrossrefItem::Label(label) => labels
+                .entry(label.to_string())
+                .or_default()
+                .push((rel.clone(), line_no)),
+            CrossrefItem::Reference(label) => references.push((label, rel.clone(), line_no)),
+            CrossrefItem::Anchor(label) => {
+                anchors.insert(label.to_string());
+            }
+        });
+    }
 
-    if detect_project_type(config, project_path) == ProjectType::Latex {
-        for latexmkrc in [".latexmkrc", "latexmkrc"] {
-            track_dependency(
-                project_path,
-                project_path,
-                Path::new(latexmkrc),
-                &mut files,
-                &mut pending,
-            );
+    let mut findings = Vec::new();
+    for (label, rel, line_no) in &references {
+        if !labels.contains_key(label) {
+            findings.push(finding(
+                lint::UNRESOLVED_CROSSREF,
+                format!("Unresolved cross-reference '@{label}'"),
+                rel,
+                *line_no,
+            ));
         }
     }
-
-    for configured in [
-        config.entry.as_ref(),
-        config.metadata_file.as_ref(),
-        config.pandoc_css.as_ref(),
-        config.pandoc_reference_doc.as_ref(),
-        config.pandoc_pptx_reference_doc.as_ref(),
-        config.pandoc_epub_css.as_ref(),
+    for (label, declarations) in &labels {
+        let (first_rel, first_line) = &declarations[0];
+        for (rel, line_no) in &declarations[1..] {
+            findings.push(finding(
+                lint::DUPLICATE_CROSSREF_LABEL,
+                format!(
+                    "Duplicate cross-reference label '#{label}' (first declared at {first_rel}:{first_line})"
+                ),
+                rel,
+                *line_no,
+            ));
+        }
+        let used = anchors.contains(label)
+            || references
+                .iter()
+                .any(|(reference, _, _)| reference == label);
+        if !used {
+            findings.push(finding(
+                lint::UNUSED_CROSSREF_LABEL,
+                format!("Cross-reference label '#{label}' is never referenced"),
+                first_rel,
+                *first_line,
+            ));
+        }
+    }
+    lint::project_issues(
+        project_path,
+        findings,
+        &lint::RuleSettings::from_config(config),
+    )
+}
+
+fn scan_crossrefs<'a>(
+    content: &'a str,
+    syntax: &CrossrefSyntax,
+    mut record: impl FnMut(CrossrefItem<'a>, usize),
+) {
+    let mut markdown_fence: Option<String> = None;
+    // Fence attributes may continue over several lines until the closing `}`.
+    let mut fence_attributes = false;
+    for (line_index, line) in content.lines().enumerate() {
+        let line_no = line_index + 1;
+        let trimmed = line.trim_start();
+        if fence_attributes {
+            for label in crossref_labels(line.split('}').next().unwrap_or_default()) {
+                record(CrossrefItem::Label(label), line_no);
+            }
+            fence_attributes = !line.contains('}');
+            continue;
+        }
+        if let Some(marker) = markdown_fence.as_deref() {
+            if trimmed.starts_with(marker)
+                && trimmed.trim_start_matches(['`', '~']).trim().is_empty()
+            {
+                markdown_fence = None;
+            }
+            continue;
+        }
+        let fence_length = trimmed
+            .chars()
+            .take_while(|character| *character == '`')
+            .count()
+            .max(
+                trimmed
+                    .chars()
+                    .take_while(|character| *character == '~')
+                    .count(),
+            );
+        if fence_length >= 3 {
+            markdown_fence = Some(trimmed[..fence_length].to_string());
+            if let Some((_, attributes)) = trimmed[fence_length..].split_once('{') {
+                for label in crossref_labels(attributes.split('}').next().unwrap_or_default()) {
+                    record(CrossrefItem::Label(label), line_no);
+                }
+                fence_attributes = !attributes.contains('}');
+            }
+            continue;
+        }
+
+        let prose = syntax.code_span.replace_all(line, "");
+        for capture in syntax.attributes.captures_iter(line) {
+            let attributes = capture.get(1).map_or("", |value| value.as_str());
+            for label in crossref_labels(attributes) {
+                record(CrossrefItem::Label(label), line_no);
+            }
+        }
+        for capture in syntax.reference.captures_iter(&prose) {
+            record(
+                CrossrefItem::Reference(crossref_reference_label(&capture[1])),
+                line_no,
+            );
+        }
+        for capture in syntax.anchor.captures_iter(line) {
+            if let Some(label) = capture.get(1) {
+                record(CrossrefItem::Anchor(label.as_str()), line_no);
+            }
+        }
+    }
+}
+
+/// `@fig:id` and `@fig-id` citations; the label is the first group.
+fn crossref_reference_regex() -> regex::Regex {
+    regex::Regex::new(r"(?:^|[^\w@])@((?:[Ff]ig|[Tt]bl|[Ss]ec|[Ee]q|[Ll]st)[:-][\w:.-]+)")
+        .expect("crossref regex")
+}
+
+/// The label a captured citation refers to: trailing punctuation dropped,
+/// and `@Fig:plot`, the capitalised form of `@fig:plot`, lowercased.
+fn crossref_reference_label(citation: &str) -> String {
+    let label = citation.trim_end_matches(['.', ':', '-']);
+    let mut characters = label.chars();
+    characters
+        .next()
+        .map(|first| first.to_ascii_lowercase().to_string() + characters.as_str())
+        .unwrap_or_default()
+}
+
+/// `#fig:id` style labels in the text between `{` and `}`.
+pub(crate) fn crossref_labels(attributes: &str) -> impl Iterator<Item = &str> {
+    attributes
+        .split_whitespace()
+        .filter_map(|token| token.strip_prefix('#'))
+        .filter(|label| is_crossref_label(label))
+}
+
+fn is_crossref_label(label: &str) -> bool {
+    label
+        .split_once([':', '-'])
+        .is_some_and(|(prefix, rest)| CROSSREF_PREFIXES.contains(&prefix) && !rest.is_empty())
+}
+
+/// Where citeproc reads references from for one document.
+struct CitationSources {
+    /// Declared bibliographies, each with the file that declared it.
+    bibliographies: Vec<(PathBuf, String)>,
+    /// Bibliography files under `[paths] biblio_dir` the document does not
+    /// declare; keys found only there get a more helpful message.
+    undeclared: Vec<PathBuf>,
+    /// The `csl` style, `None` for Pandoc's built-in Chicago author-date.
+    style: Option<String>,
+    nocite: Vec<String>,
+}
+
+/// Check Pandoc citations against the document's bibliographies: undefined
+/// keys, keys defined twice, cited entries missing variables their CSL style
+/// renders, and entries nothing cites. Bibliographies come from `--bibliography`
+/// options, the metadata file, and the entry's front matter; files in
+/// `[paths] biblio_dir` only explain keys the document fails to declare.
+pub fn lint_citations(project_path: &Path, config: &MergedConfig) -> Vec<ProjectIssue> {
+    let markdown = dependency_graph(project_path, config)
+        .files
+        .into_iter()
+        .filter(|rel| {
+            Path::new(rel)
+                .extension()
+                .and_then(|value| value.to_str())
+                .is_some_and(|value| {
+                    matches!(value.to_ascii_lowercase().as_str(), "md" | "markdown")
+                })
+        })
+        .filter_map(|rel| {
+            let content = fs::read_to_string(project_path.join(&rel)).ok()?;
+            Some((rel, content))
+        })
+        .collect::<Vec<_>>();
+    if markdown.is_empty() {
+        return Vec::new();
+    }
+
+    let mut issues = Vec::new();
+    let sources = citation_sources(project_path, config, &mut issues);
+    let citation_re = regex::Regex::new(r"(?:^|[^\w@])@(\{[^}]+\}|\w[\w:.#$%&+?<>~/-]*)")
+        .expect("citation regex");
+    let url_re = regex::Regex::new(r"(?:https?|ftp|mailto):\S+").expect("URL regex");
+    let code_span_re = regex::Regex::new(r"`[^`]*`").expect("code span regex");
+    let mut citations = Vec::new();
+    for (rel, content) in &markdown {
+        for (line_no, line) in markdown_prose_lines(content) {
+            let prose = code_span_re.replace_all(line, "");
+            let prose = url_re.replace_all(&prose, "");
+            for capture in citation_re.captures_iter(&prose) {
+                let key = capture[1].trim_start_matches('{').trim_end_matches('}');
+                let key = if capture[1].starts_with('{') {
+                    key
+                } else {
+                    key.trim_end_matches(|character: char| {
+                        !character.is_alphanumeric() && character != '_'
+                    })
+                };
+                if !key.is_empty() && !is_crossref_label(key) {
+                    citations.push((key.to_string(), rel.clone(), line_no));
+                }
+            }
+        }
+    }
+
+    let mut entries: BTreeMap<String, Vec<(String, bibliography::BibEntry)>> = BTreeMap::new();
+    for (path, _) in &sources.bibliographies {
+        let Some(parsed) = fs::read_to_string(path)
+            .ok()
+            .and_then(|content| bibliography::parse(path, &content))
+        else {
+            continue;
+        };
+        let rel = display_relative(project_path, path);
+        for entry in parsed {
+            entries
+                .entry(entry.key.clone())
+                .or_default()
+                .push((rel.clone(), entry));
+        }
+    }
+
+    let mut findings = Vec::new();
+    let mut undeclared_keys = BTreeMap::new();
+    for path in &sources.undeclared {
+        let parsed = fs::read_to_string(path)
+            .ok()
+            .and_then(|content| bibliography::parse(path, &content));
+        for entry in parsed.into_iter().flatten() {
+            undeclared_keys
+                .entry(entry.key)
+                .or_insert_with(|| display_relative(project_path, path));
+        }
+    }
+    for (key, rel, line_no) in &citations {
+        if entries.contains_key(key) {
+            continue;
+        }
+        let message = match undeclared_keys.get(key) {
+            Some(file) => format!(
+                "Citation key '@{key}' is only defined in {file}, which the document does not list under `bibliography`"
+            ),
+            None => format!("Undefined citation key '@{key}'"),
+        };
+        findings.push(finding(lint::UNDEFINED_CITATION, message, rel, *line_no));
+    }
+
+    let cite_all = sources.nocite.iter().any(|key| key == "*");
+    let cited = citations
+        .iter()
+        .map(|(key, _, _)| key.as_str())
+        .chain(sources.nocite.iter().map(String::as_str))
+        .collect::<BTreeSet<_>>();
+    let style_variables = sources.style.as_deref().and_then(|style| {
+        let library_root = omnidoc_library_root(config);
+        let style = if Path::new(style).extension().is_some() {
+            style.to_string()
+        } else {
+            format!("{style}.csl")
+        };
+        resolve_resource_path(
+            project_path,
+            &library_root,
+            &style,
+            Some(pandoc::LIB_PANDOC_CSL),
+        )
+        .and_then(|path| fs::read_to_string(path).ok())
+        .map(|content| bibliography::csl_style_variables(&content))
+    });
+    let style_name = sources
+        .style
+        .as_deref()
+        .unwrap_or("default Chicago author-date");
+    for (key, definitions) in &entries {
+        let (first_rel, first) = &definitions[0];
+        for (rel, entry) in &definitions[1..] {
+            findings.push(finding(
+                lint::DUPLICATE_CITATION,
+                format!(
+                    "Duplicate citation key '{key}' (first defined at {first_rel}:{})",
+                    first.line
+                ),
+                rel,
+                entry.line,
+            ));
+        }
+        if !cite_all && !cited.contains(key.as_str()) {
+            findings.push(finding(
+                lint::UNUSED_CITATION,
+                format!("Bibliography entry '{key}' is never cited"),
+                first_rel,
+                first.line,
+            ));
+            continue;
+        }
+        let missing = bibliography::required_variables(&first.csl_type)
+            .iter()
+            .filter(|alternatives| {
+                // Only variables the style actually prints are required.
+                style_variables.as_ref().is_none_or(|variables| {
+                    alternatives.iter().any(|name| variables.contains(*name))
+                }) && !alternatives
+                    .iter()
+                    .any(|name| first.variables.contains(*name))
+            })
+            .map(|alternatives| alternatives.join(" or "))
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            findings.push(finding(
+                lint::INCOMPLETE_CITATION,
+                format!(
+                    "Bibliography entry '{key}' ({}) lacks {} required by the {style_name} style",
+                    first.csl_type,
+                    missing.join(", ")
+                ),
+                first_rel,
+                first.line,
+            ));
+        }
+    }
+    issues.extend(lint::project_issues(
+        project_path,
+        findings,
+        &lint::RuleSettings::from_config(config),
+    ));
+    issues
+}
+
+fn citation_sources(
+    project_path: &Path,
+    config: &MergedConfig,
+    issues: &mut Vec<ProjectIssue>,
+) -> CitationSources {
+    let mut declared: Vec<(String, String)> = Vec::new();
+    let mut style = None;
+    let mut nocite = Vec::new();
+
+    // Later sources override earlier ones, as in Pandoc: options, then the
+    // metadata file, then the document's own front matter.
+    let mut options = config.pandoc_options.clone();
+    if let Ok(kind) = PandocOutputKind::from_config(config) {
+        if let Some(format_options) = config.pandoc_format_options.get(kind.config_key()) {
+            options.extend(format_options.clone());
+        }
+    }
+    for (flag, value) in pandoc_option_file_references(&options) {
+        match flag.as_str() {
+            "--bibliography" => declared.push((value, "pandoc options".to_string())),
+            "--csl" => style = Some(value),
+            _ => {}
+        }
+    }
+    let key_re = regex::Regex::new(r"@(\*|[^\s,;\]'\x22]+)").expect("nocite regex");
+    for source in [config.metadata_file.as_deref(), config.entry.as_deref()]
+        .into_iter()
+        .flatten()
+    {
+        let Ok(content) = fs::read_to_string(project_path.join(source)) else {
+            continue;
+        };
+        let yaml = if source.ends_with(".md") || source.ends_with(".markdown") {
+            let Some(front_matter) = front_matter::block(&content) else {
+                continue;
+            };
+            front_matter
+        } else {
+            content.as_str()
+        };
+        declared.extend(
+            front_matter::values(yaml, "bibliography")
+                .into_iter()
+                .map(|path| (path, source.to_string())),
+        );
+        style = front_matter::value(yaml, "csl").or(style);
+        if let Some(value) = front_matter::raw(yaml, "nocite") {
+            nocite.extend(
+                key_re
+                    .captures_iter(&value)
+                    .map(|capture| capture[1].to_string()),
+            );
+        }
+    }
+
+    let mut bibliographies: Vec<(PathBuf, String)> = Vec::new();
+    for (configured, declared_in) in declared {
+        let path = project_path.join(&configured);
+        if !path.is_file() {
+            issues.push(warning(
+                format!("Bibliography '{configured}' declared in {declared_in} was not found"),
+                None,
+                None,
+            ));
+            continue;
+        }
+        let canonical = path.canonicalize().unwrap_or(path);
+        if !bibliographies.iter().any(|(known, _)| known == &canonical) {
+            bibliographies.push((canonical, declared_in));
+        }
+    }
+    let biblio_dir = Some(config.paths.biblio_dir.as_str()).filter(|dir| !dir.is_empty());
+    let undeclared = biblio_dir
+        .map(|dir| WalkDir::new(project_path.join(dir)))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| {
+            entry
+                .path()
+                .canonicalize()
+                .unwrap_or_else(|_| entry.path().to_path_buf())
+        })
+        .filter(|path| bibliography::is_bibliography(path))
+        .filter(|path| !bibliographies.iter().any(|(known, _)| known == path))
+        .collect();
+    CitationSources {
+        bibliographies,
+        undeclared,
+        style,
+        nocite,
+    }
+}
+
+/// Check glossary references against the project's glossary files: `[+KEY]`
+/// and `[KEY]{.gls}` uses without an entry, keys defined twice, and entries
+/// nothing references. Missing or malformed files are left to
+/// `validate_config`.
+pub fn lint_glossary(project_path: &Path, config: &MergedConfig) -> Vec<ProjectIssue> {
+    if config.glossary_files.is_empty() {
+        return Vec::new();
+    }
+    let mut findings = Vec::new();
+    let mut definitions: BTreeMap<String, (String, usize)> = BTreeMap::new();
+    let mut defined = Vec::new();
+    for path in glossary::files(project_path, config) {
+        let Some(entries) = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| glossary::parse(&path, &content).ok())
+        else {
+            continue;
+        };
+        let rel = display_relative(project_path, &path);
+        for entry in entries {
+            if let Some((first_rel, first_line)) = definitions.get(&entry.key) {
+                findings.push(finding(
+                    lint::DUPLICATE_GLOSSARY_KEY,
+                    format!(
+                        "Duplicate glossary key '{}' (first defined at {first_rel}:{first_line})",
+                        entry.key
+                    ),
+                    &rel,
+                    entry.line,
+                ));
+                continue;
+            }
+            definitions.insert(entry.key.clone(), (rel.clone(), entry.line));
+            defined.push(entry.key);
+        }
+    }
+
+    let reference_re = regex::Regex::new(r"\[\+([^\]\s]+)\]|\[([^\]]*)\]\{([^}]*)\}")
+        .expect("glossary reference regex");
+    let class_re = regex::Regex::new(r"(?:^|\s)\.gls(?:\s|$)").expect("glossary class regex");
+    let key_re = regex::Regex::new(r#"(?:^|\s)key="?([^"\s}]+)"?"#).expect("glossary key regex");
+    let code_span_re = regex::Regex::new(r"`[^`]*`").expect("code span regex");
+    let mut used = BTreeSet::new();
+    for rel in dependency_graph(project_path, config).files {
+        let is_markdown = Path::new(&rel)
+            .extension()
+            .and_then(|value| value.to_str())
+            .is_some_and(|value| matches!(value.to_ascii_lowercase().as_str(), "md" | "markdown"));
+        let Some(content) = is_markdown
+            .then(|| fs::read_to_string(project_path.join(&rel)).ok())
+            .flatten()
+        else {
+            continue;
+        };
+        for (line_no, line) in markdown_prose_lines(&content) {
+            let prose = code_span_re.replace_all(line, "");
+            for capture in reference_re.captures_iter(&prose) {
+                let key = match capture.get(1) {
+                    Some(key) => key.as_str().to_string(),
+                    None if class_re.is_match(&capture[3]) => key_re
+                        .captures(&capture[3])
+                        .map(|key| key[1].to_string())
+                        .unwrap_or_else(|| capture[2].trim().to_string()),
+                    None => continue,
+                };
+                if definitions.contains_key(&key) {
+                    used.insert(key);
+                } else {
+                    findings.push(finding(
+                        lint::UNDEFINED_GLOSSARY_TERM,
+                        format!("Undefined glossary term '{key}'"),
+                        &rel,
+                        line_no,
+                    ));
+                }
+            }
+        }
+    }
+    for key in defined {
+        if used.contains(&key) {
+            continue;
+        }
+        let (rel, line) = &definitions[&key];
+        findings.push(finding(
+            lint::UNUSED_GLOSSARY_TERM,
+            format!("Glossary term '{key}' is never referenced"),
+            rel,
+            *line,
+        ));
+    }
+    lint::project_issues(
+        project_path,
+        findings,
+        &lint::RuleSettings::from_config(config),
+    )
+}
+
+/// Report index terms spelled more than one way: `[term]{.idx}` entries
+/// that differ only in case, hyphens or spacing would otherwise become
+/// separate index headings. Each later spelling is reported against the
+/// first one seen in build order.
+pub fn lint_index(project_path: &Path, config: &MergedConfig) -> Vec<ProjectIssue> {
+    let span_re = regex::Regex::new(r"\[([^\]]*)\]\{([^}]*)\}").expect("index span regex");
+    let class_re =
+        regex::Regex::new(r"(?:^|\s)\.(?:idx|index)(?:\s|$)").expect("index class regex");
+    let term_re =
+        regex::Regex::new(r#"(?:^|\s)term=(?:"([^"]*)"|(\S+))"#).expect("index term regex");
+    let code_span_re = regex::Regex::new(r"`[^`]*`").expect("code span regex");
+    let mut findings = Vec::new();
+    let mut spellings: BTreeMap<String, (String, String, usize)> = BTreeMap::new();
+    for rel in dependency_graph(project_path, config).files {
+        let is_markdown = Path::new(&rel)
+            .extension()
+            .and_then(|value| value.to_str())
+            .is_some_and(|value| matches!(value.to_ascii_lowercase().as_str(), "md" | "markdown"));
+        let Some(content) = is_markdown
+            .then(|| fs::read_to_string(project_path.join(&rel)).ok())
+            .flatten()
+        else {
+            continue;
+        };
+        for (line_no, line) in markdown_prose_lines(&content) {
+            let prose = code_span_re.replace_all(line, "");
+            for capture in span_re.captures_iter(&prose) {
+                if !class_re.is_match(&capture[2]) {
+                    continue;
+                }
+                let term = term_re
+                    .captures(&capture[2])
+                    .and_then(|term| term.get(1).or_else(|| term.get(2)))
+                    .map_or(&capture[1], |term| term.as_str());
+                // Subentries (`Parent!Child`) are checked level by level.
+                for level in term
+                    .split('!')
+                    .map(str::trim)
+                    .filter(|level| !level.is_empty())
+                {
+                    let key = index::spelling_key(level);
+                    match spellings.get(&key) {
+                        Some((first, first_rel, first_line)) if first != level => {
+                            findings.push(finding(
+                                lint::INCONSISTENT_INDEX_TERM,
+                                format!(
+                                    "Index term '{level}' is spelled '{first}' at {first_rel}:{first_line}"
+                                ),
+                                &rel,
+                                line_no,
+                            ));
+                        }
+                        Some(_) => {}
+                        None => {
+                            spellings.insert(key, (level.to_string(), rel.clone(), line_no));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    lint::project_issues(
+        project_path,
+        findings,
+        &lint::RuleSettings::from_config(config),
+    )
+}
+
+struct ProseSyntax {
+    markdown: Vec<regex::Regex>,
+    latex: Vec<regex::Regex>,
+    latex_skipped_environment: regex::Regex,
+}
+
+impl ProseSyntax {
+    fn new() -> Self {
+        let regex = |pattern: &str| regex::Regex::new(pattern).expect("prose regex");
+        let url = r"(?:https?|ftp|mailto|file):\S+";
+        let latex_command = r"\\[A-Za-z@]+\*?|\\.";
+        Self {
+            markdown: vec![
+                regex(r"`[^`]*`"),
+                regex(r"\$\$.*?\$\$|\$[^\s$](?:[^$]*[^\s$])?\$"),
+                regex(url),
+                regex(r"\]\([^)]*\)|^\s*\[[^\]]+\]:\s*\S+"),
+                regex(r"\{[^}]*\}"),
+                regex(r"<[^>]*>"),
+                // Citations, cross-references and e-mail addresses.
+                regex(r"\S*@\S+"),
+                regex(latex_command),
+            ],
+            latex: vec![
+                regex(r"\$\$.*?\$\$|\$[^$]*\$|\\\(.*?\\\)|\\\[.*?\\\]"),
+                regex(
+                    r"\\(?:label|[a-z]*ref|[a-z]*cite[a-z]*|input|include|includegraphics|includepdf|begin|end|url|href|bibliography[a-z]*|addbibresource|usepackage|lstinputlisting|inputminted)\*?(?:\s*\[[^\]]*\])*\s*\{[^}]*\}",
+                ),
+                regex(url),
+                regex(latex_command),
+            ],
+            latex_skipped_environment: regex(
+                r"\\begin\{((?:verbatim|Verbatim|lstlisting|minted|comment|equation|align|alignat|flalign|gather|multline|eqnarray|displaymath|math|tikzpicture)\*?)\}",
+            ),
+        }
+    }
+}
+
+/// Enforce the terminology dictionary over Markdown and LaTeX prose, and
+/// spell check the same prose with Hunspell when `[terms] spellcheck` names
+/// dictionaries. Code spans, math, URLs, attributes, fenced code blocks and
+/// verbatim or math environments are skipped, as in `lint_project`.
+pub fn lint_terms(project_path: &Path, config: &MergedConfig) -> Vec<ProjectIssue> {
+    let dictionary = match terms::dictionary_path(project_path, config) {
+        Some(path) => match TermsDictionary::load(&path) {
+            Ok(dictionary) => dictionary,
+            // validate_config explains why; say here that terms went unchecked.
+            Err(_) => {
+                let rel = display_relative(project_path, &path);
+                return vec![warning(
+                    format!("Terminology was not checked because {rel} could not be loaded"),
+                    Some(rel),
+                    None,
+                )];
+            }
+        },
+        None => TermsDictionary::default(),
+    };
+    if dictionary.terms.is_empty() && config.spellcheck_dictionaries.is_empty() {
+        return Vec::new();
+    }
+
+    let lang = document_lang(project_path, config);
+    let applicable = dictionary.terms_for(lang.as_deref());
+    let syntax = ProseSyntax::new();
+    let settings = lint::RuleSettings::from_config(config);
+    let mut findings = Vec::new();
+    let mut prose = Vec::new();
+    for file in source_files(project_path) {
+        let extension = file
+            .extension()
+            .and_then(|value| value.to_str())
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        let Ok(content) = fs::read_to_string(&file) else {
+            continue;
+        };
+        let lines = match extension.as_str() {
+            "md" | "markdown" => markdown_term_prose(&content, &syntax),
+            "tex" => latex_term_prose(&content, &syntax),
+            _ => continue,
+        };
+        let rel = display_relative(project_path, &file);
+        for (line_no, text) in lines {
+            for found in terms::forbidden_terms(&text, &applicable) {
+                findings.push(finding(
+                    lint::FORBIDDEN_TERM,
+                    format!("Use '{}' instead of '{}'", found.preferred, found.found),
+                    &rel,
+                    line_no,
+                ));
+            }
+            prose.push((rel.clone(), line_no, text));
+        }
+    }
+
+    let mut issues = Vec::new();
+    if !config.spellcheck_dictionaries.is_empty() && !prose.is_empty() {
+        issues.extend(spellcheck_prose(config, &dictionary, &prose, &mut findings));
+    }
+    issues.extend(lint::project_issues(project_path, findings, &settings));
+    issues
+}
+
+/// Spell check `prose` (path, line and text) with Hunspell, adding a
+/// finding per unknown word. Returns a warning when Hunspell is missing or
+/// fails.
+fn spellcheck_prose(
+    config: &MergedConfig,
+    dictionary: &TermsDictionary,
+    prose: &[(String, usize, String)],
+    findings: &mut Vec<(String, lint::Finding)>,
+) -> Option<ProjectIssue> {
+    let executor = BuildExecutor::new(config.tool_paths.clone());
+    let Ok(hunspell) = executor.check_tool("hunspell") else {
+        return Some(warning(
+            "Spell checking is enabled but hunspell was not found; install it or set [tools] hunspell"
+                .to_string(),
+            Some(".omnidoc.toml".to_string()),
+            None,
+        ));
+    };
+    let text: Vec<String> = prose.iter().map(|(_, _, text)| text.clone()).collect();
+    let misspellings = match terms::spellcheck(
+        &hunspell,
+        &config.spellcheck_dictionaries,
+        &dictionary.accepted_words(),
+        &text,
+    ) {
+        Ok(misspellings) => misspellings,
+        Err(spellcheck_error) => {
+            return Some(warning(
+                format!("Spell checking failed: {spellcheck_error}"),
+                Some(".omnidoc.toml".to_string()),
+                None,
+            ))
+        }
+    };
+    for misspelling in misspellings {
+        let Some((rel, line_no, _)) = prose.get(misspelling.line) else {
+            continue;
+        };
+        let message = if misspelling.suggestions.is_empty() {
+            format!("Unknown word '{}'", misspelling.word)
+        } else {
+            format!(
+                "Unknown word '{}' (suggestions: {})",
+                misspelling.word,
+                misspelling.suggestions.join(", ")
+            )
+        };
+        findings.push(finding(lint::MISSPELLING, message, rel, *line_no));
+    }
+    None
+}
+
+/// Markdown prose for the terms lint, with code, math and markup blanked.
+fn markdown_term_prose(content: &str, syntax: &ProseSyntax) -> Vec<(usize, String)> {
+    let mut display_math = false;
+    let mut lines = Vec::new();
+    for (line_no, line) in markdown_prose_lines(content) {
+        let trimmed = line.trim();
+        if display_math {
+            display_math = !trimmed.ends_with("$$");
+            continue;
+        }
+        if trimmed.starts_with("$$") {
+            display_math = trimmed.len() < 4 || !trimmed.ends_with("$$");
+            continue;
+        }
+        if line.starts_with("    ") || line.starts_with('\t') {
+            continue;
+        }
+        let mut prose = line.to_string();
+        for pattern in &syntax.markdown {
+            prose = pattern.replace_all(&prose, " ").into_owned();
+        }
+        lines.push((line_no, prose));
+    }
+    lines
+}
+
+/// LaTeX prose for the terms lint: the document body without comments,
+/// verbatim or math environments, inline math, and command names.
+fn latex_term_prose(content: &str, syntax: &ProseSyntax) -> Vec<(usize, String)> {
+    let body_start = content
+        .lines()
+        .position(|line| strip_latex_comment(line).contains(r"\begin{document}"))
+        .map_or(0, |index| index + 1);
+    let mut skipped_until: Option<String> = None;
+    let mut lines = Vec::new();
+    for (line_index, line) in content.lines().enumerate().skip(body_start) {
+        let source = strip_latex_comment(line);
+        if let Some(end) = skipped_until.as_deref() {
+            if source.contains(end) {
+                skipped_until = None;
+            }
+            continue;
+        }
+        if let Some(capture) = syntax.latex_skipped_environment.captures(source) {
+            let end = format!(r"\end{{{}}}", &capture[1]);
+            if !source.contains(&end) {
+                skipped_until = Some(end);
+            }
+            continue;
+        }
+        if source.contains(r"\[") && !source.contains(r"\]") {
+            skipped_until = Some(r"\]".to_string());
+            continue;
+        }
+        let mut prose = source.to_string();
+        for pattern in &syntax.latex {
+            prose = pattern.replace_all(&prose, " ").into_owned();
+        }
+        lines.push((line_index + 1, prose));
+    }
+    lines
+}
+
+/// Lines of Markdown prose with their 1-based numbers, skipping the YAML
+/// front matter and fenced code blocks.
+pub(crate) fn markdown_prose_lines(content: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut front_matter = content.starts_with("---");
+    let mut markdown_fence: Option<String> = None;
+    content
+        .lines()
+        .enumerate()
+        .filter_map(move |(line_index, line)| {
+            let trimmed = line.trim_start();
+            if front_matter {
+                if line_index > 0 && matches!(line.trim_end(), "---" | "...") {
+                    front_matter = false;
+                }
+                return None;
+            }
+            if let Some(marker) = markdown_fence.as_deref() {
+                if trimmed.starts_with(marker)
+                    && trimmed.trim_start_matches(['`', '~']).trim().is_empty()
+                {
+                    markdown_fence = None;
+                }
+                return None;
+            }
+            let fence_length = trimmed
+                .chars()
+                .take_while(|character| *character == '`')
+                .count()
+                .max(
+                    trimmed
+                        .chars()
+                        .take_while(|character| *character == '~')
+                        .count(),
+                );
+            if fence_length >= 3 {
+                markdown_fence = Some(trimmed[..fence_length].to_string());
+                return None;
+            }
+            Some((line_index + 1, line))
+        })
+}
+
+/// Document language: `[pandoc] lang`, else the entry file's `lang` metadata.
+pub(crate) fn document_lang(project_path: &Path, config: &MergedConfig) -> Option<String> {
+    config.pandoc_lang.clone().or_else(|| {
+        let content = fs::read_to_string(project_path.join(config.entry.as_deref()?)).ok()?;
+        front_matter::value(front_matter::block(&content)?, "lang")
+    })
+}
+
+pub(crate) fn strip_latex_comment(line: &str) -> &str {
+    let mut escaped = false;
+    for (index, character) in line.char_indices() {
+        if character == '%' && !escaped {
+            return &line[..index];
+        }
+        if character == '\\' {
+            escaped = !escaped;
+        } else {
+            escaped = false;
+        }
+    }
+    line
+}
+
+/// Whether a LaTeX resource resolves nowhere, trying the extensions the
+/// command adds by default.
+fn latex_target_missing(
+    project_path: &Path,
+    source_file: &Path,
+    target: &str,
+    command: &str,
+) -> bool {
+    if target.contains(['\\', '#']) || !is_local_path(target) {
+        return false;
+    }
+
+    let base = source_file.parent().unwrap_or(project_path);
+    let path = Path::new(target);
+    let mut candidates = vec![path.to_path_buf()];
+    if path.extension().is_none() {
+        let extensions: &[&str] = match command {
+            "includegraphics" => &["pdf", "png", "jpg", "jpeg", "svg", "eps"],
+            "bibliography" | "addbibresource" => &["bib"],
+            "input" | "include" => &["tex"],
+            _ => &[],
+        };
+        candidates.extend(
+            extensions
+                .iter()
+                .map(|extension| path.with_extension(extension)),
+        );
+    }
+
+    !candidates.iter().any(|candidate| {
+        base.join(candidate).exists()
+            || project_path.join(candidate).exists()
+            || project_path.join("tex").join(candidate).exists()
+    })
+}
+
+pub fn dependency_graph(project_path: &Path, config: &MergedConfig) -> DependencyGraph {
+    let mut files = BTreeSet::new();
+    let mut pending = Vec::new();
+    let mut depfile_resources = BTreeMap::new();
+
+    track_dependency(
+        project_path,
+        project_path,
+        Path::new(".omnidoc.toml"),
+        &mut files,
+        &mut pending,
+    );
+
+    if detect_project_type(config, project_path) == ProjectType::Latex {
+        for latexmkrc in [".latexmkrc", "latexmkrc"] {
+            track_dependency(
+                project_path,
+                project_path,
+                Path::new(latexmkrc),
+                &mut files,
+                &mut pending,
+            );
+        }
+    }
+
+    for configured in [
+        config.entry.as_ref(),
+        config.metadata_file.as_ref(),
+        config.pandoc_css.as_ref(),
+        config.pandoc_reference_doc.as_ref(),
+        config.pandoc_pptx_reference_doc.as_ref(),
+        config.pandoc_epub_css.as_ref(),
         config.pandoc_template.as_ref(),
         config.pandoc_html_template.as_ref(),
         config.pandoc_latex_template.as_ref(),
+        config.pandoc_typst_template.as_ref(),
+        config.pandoc_site_template.as_ref(),
         config.pandoc_epub_template.as_ref(),
     ]
     .into_iter()
     .flatten()
+    .chain(&config.glossary_files)
     {
         track_dependency(
             project_path,
@@ -788,7 +2076,7 @@ pub fn dependency_graph(project_path: &Path, config: &MergedConfig) -> Dependenc
         .filter_map(filter_depfile_name)
         .collect::<BTreeSet<_>>();
     if let Ok(plugin_filters) =
-        plugin_filters_for_output(project_path, config, output_kind.config_key())
+        plugin_filters_for_output(project_path, config, output_kind.family_key())
     {
         depfiles.extend(
             plugin_filters
@@ -796,22 +2084,23 @@ pub fn dependency_graph(project_path: &Path, config: &MergedConfig) -> Dependenc
                 .filter_map(|filter| filter.depfile_name()),
         );
     }
+    let scratch_dir = output_scratch_dir(project_path, config, output_kind);
     for depfile in depfiles {
         load_depfile_dependencies(
             project_path,
             &library_root,
-            &depfile,
+            &scratch_dir.join(&depfile),
             &format!("filter-depfile:{depfile}"),
             &mut files,
             &mut pending,
             &mut depfile_resources,
         );
     }
-    if output_kind == PandocOutputKind::Pdf {
+    if output_kind.uses_latex_pdf_engine() {
         load_depfile_dependencies(
             project_path,
             &library_root,
-            LATEX_INPUT_DEPFILE,
+            &scratch_dir.join(LATEX_INPUT_DEPFILE),
             "latex-fls-input",
             &mut files,
             &mut pending,
@@ -832,7 +2121,7 @@ pub fn dependency_graph(project_path: &Path, config: &MergedConfig) -> Dependenc
                 &mut files,
                 &mut pending,
             );
-            if matches!(output_kind, PandocOutputKind::Pdf | PandocOutputKind::Latex) {
+            if output_kind.uses_latex_defaults() {
                 track_svg_pdf_sibling(
                     project_path,
                     base,
@@ -857,6 +2146,158 @@ pub fn dependency_graph(project_path: &Path, config: &MergedConfig) -> Dependenc
     }
 }
 
+/// Scratch directory for one output's filter depfiles and engine side files.
+/// Outputs never share it, so `build --jobs` can run them concurrently without
+/// one writer truncating the dependency records another is about to read.
+/// Each `[[documents]]` entry and `--variant` gets its own tree for the same
+/// reason.
+pub(crate) fn output_scratch_dir(
+    project_path: &Path,
+    config: &MergedConfig,
+    output_kind: PandocOutputKind,
+) -> PathBuf {
+    target_cache_dir(
+        project_path,
+        config.document.as_deref(),
+        config.variant.as_deref(),
+    )
+    .join(OUTPUT_SCRATCH_DIR)
+    .join(output_kind.config_key())
+}
+
+/// Project-local cache root holding build records, scratch files and the
+/// fragment and diagram stores.
+pub(crate) fn project_cache_dir(project_path: &Path) -> PathBuf {
+    project_path.join(CACHE_DIR)
+}
+
+/// Parsed Pandoc AST of every transcluded chapter, named by a digest of the
+/// chapter's content and reader setup. Content addressing makes the store
+/// safe to share between outputs, documents and variants.
+pub(crate) fn fragment_cache_dir(project_path: &Path) -> PathBuf {
+    project_cache_dir(project_path).join(FRAGMENTS_DIR)
+}
+
+/// Rendered diagram images keyed by their source block and renderer setup,
+/// shared by every output of the project like [`fragment_cache_dir`].
+pub(crate) fn render_cache_dir(project_path: &Path) -> PathBuf {
+    project_cache_dir(project_path).join(RENDERS_DIR)
+}
+
+/// Cache details for the include fragments of the last build of `config`:
+/// how many chapters were reused and which ones had to be parsed again.
+pub fn fragment_cache_details(project_path: &Path, config: &MergedConfig) -> Vec<String> {
+    const MAX_REPARSED: usize = 32;
+    let events = read_cache_log(project_path, config, INCLUDE_FRAGMENT_LOG);
+    if events.is_empty() {
+        return Vec::new();
+    }
+    let canonical_project = project_path
+        .canonicalize()
+        .unwrap_or_else(|_| project_path.to_path_buf());
+    let hits = events.iter().filter(|(hit, _)| *hit).count();
+    let reparsed = events
+        .iter()
+        .filter(|(hit, _)| !hit)
+        .map(|(_, file)| {
+            let file = Path::new(file);
+            if file.starts_with(&canonical_project) {
+                display_relative(&canonical_project, file)
+            } else {
+                display_relative(project_path, file)
+            }
+        })
+        .collect::<BTreeSet<_>>();
+    let mut details = hit_ratio_details("fragment_cache", hits, events.len());
+    let omitted = reparsed.len().saturating_sub(MAX_REPARSED);
+    details.extend(
+        reparsed
+            .into_iter()
+            .take(MAX_REPARSED)
+            .map(|file| format!("fragment_reparsed:{file}")),
+    );
+    if omitted > 0 {
+        details.push(format!("additional_fragments_reparsed:{omitted}"));
+    }
+    details
+}
+
+/// Cache details for the diagrams rendered by the last build of `config`.
+pub fn render_cache_details(project_path: &Path, config: &MergedConfig) -> Vec<String> {
+    let events = read_cache_log(project_path, config, DIAGRAM_RENDER_LOG);
+    if events.is_empty() {
+        return Vec::new();
+    }
+    let hits = events.iter().filter(|(hit, _)| *hit).count();
+    hit_ratio_details("render_cache", hits, events.len())
+}
+
+/// File names in [`render_cache_dir`] that the last build of `config` used.
+pub(crate) fn rendered_diagrams(project_path: &Path, config: &MergedConfig) -> Vec<String> {
+    read_cache_log(project_path, config, DIAGRAM_RENDER_LOG)
+        .into_iter()
+        .map(|(_, name)| name)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+fn hit_ratio_details(kind: &str, hits: usize, total: usize) -> Vec<String> {
+    vec![
+        format!("{kind}_hits:{hits}/{total}"),
+        format!("{kind}_hit_ratio:{:.2}", hits as f64 / total as f64),
+    ]
+}
+
+/// `hit SUBJECT` / `miss SUBJECT` lines a Lua filter logged in the output's
+/// scratch directory during the last build.
+fn read_cache_log(project_path: &Path, config: &MergedConfig, log: &str) -> Vec<(bool, String)> {
+    let Ok(output_kind) = PandocOutputKind::from_config(config) else {
+        return Vec::new();
+    };
+    let log = output_scratch_dir(project_path, config, output_kind).join(log);
+    let Ok(content) = fs::read_to_string(log) else {
+        return Vec::new();
+    };
+    content
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.split_once(' '))
+        .map(|(event, subject)| (event == "hit", subject.to_string()))
+        .collect()
+}
+
+fn target_cache_dir(project_path: &Path, document: Option<&str>, variant: Option<&str>) -> PathBuf {
+    let mut cache_dir = project_path.join(CACHE_DIR);
+    if let Some(document) = document {
+        cache_dir = cache_dir.join(DOCUMENTS_DIR).join(document);
+    }
+    if let Some(variant) = variant {
+        cache_dir = cache_dir.join(VARIANTS_DIR).join(variant);
+    }
+    cache_dir
+}
+
+/// Name of one build target in caches, reports and `omnidoc.lock`:
+/// `OUTPUT` for single-document projects, `DOCUMENT/OUTPUT` otherwise.
+pub fn document_output_key(document: Option<&str>, output: &str) -> String {
+    let output = output.to_ascii_lowercase();
+    match document {
+        Some(document) => format!("{document}/{output}"),
+        None => output,
+    }
+}
+
+/// [`document_output_key`] of a resolved configuration, with an `@VARIANT`
+/// suffix for `--variant` builds so each variant caches and locks separately.
+pub fn target_output_key(config: &MergedConfig, output: &str) -> String {
+    let key = document_output_key(config.document.as_deref(), output);
+    match config.variant.as_deref() {
+        Some(variant) => format!("{key}@{variant}"),
+        None => key,
+    }
+}
+
 pub(crate) fn filter_depfile_name(filter: &str) -> Option<String> {
     let stem = Path::new(filter).file_stem()?.to_str()?;
     let normalized = stem
@@ -944,20 +2385,19 @@ fn track_svg_pdf_sibling(
 fn load_depfile_dependencies(
     project_path: &Path,
     library_root: &Path,
-    depfile_name: &str,
+    depfile: &Path,
     logical_name: &str,
     files: &mut BTreeSet<String>,
     pending: &mut Vec<PathBuf>,
     external: &mut BTreeMap<String, ResolvedResource>,
 ) {
-    let depfile = project_path.join(CACHE_DIR).join(depfile_name);
-    let Ok(metadata) = fs::metadata(&depfile) else {
+    let Ok(metadata) = fs::metadata(depfile) else {
         return;
     };
     if metadata.len() > 1024 * 1024 {
         return;
     }
-    let Ok(content) = fs::read_to_string(&depfile) else {
+    let Ok(content) = fs::read_to_string(depfile) else {
         return;
     };
     let mut lines = content.lines();
@@ -1030,7 +2470,7 @@ fn resolved_build_resources(project_path: &Path, config: &MergedConfig) -> Vec<R
     let theme = resolve_selected_theme(Some(project_path), config)
         .ok()
         .flatten()
-        .filter(|theme| theme.supports_output(output_kind.config_key()));
+        .filter(|theme| theme.supports_output(output_kind.family_key()));
 
     let manifest_path = library_root.join("manifest.toml");
     if let Some(path) = existing_path(manifest_path.clone()) {
@@ -1102,6 +2542,42 @@ fn resolved_build_resources(project_path: &Path, config: &MergedConfig) -> Vec<R
                     path,
                 );
             }
+            if let Some(path) = generated
+                .typst_header
+                .filter(|_| output_kind.uses_typst_defaults())
+            {
+                add_resolved_resource(
+                    &mut resources,
+                    project_path,
+                    &library_root,
+                    format!("theme-generated-typst:{}@{}", theme.id, theme.version),
+                    path,
+                );
+            }
+            if let Some(path) = generated
+                .beamer_header
+                .filter(|_| output_kind == PandocOutputKind::Beamer)
+            {
+                add_resolved_resource(
+                    &mut resources,
+                    project_path,
+                    &library_root,
+                    format!("theme-generated-beamer:{}@{}", theme.id, theme.version),
+                    path,
+                );
+            }
+            if let Some(path) = generated
+                .revealjs_css
+                .filter(|_| output_kind == PandocOutputKind::RevealJs)
+            {
+                add_resolved_resource(
+                    &mut resources,
+                    project_path,
+                    &library_root,
+                    format!("theme-generated-revealjs:{}@{}", theme.id, theme.version),
+                    path,
+                );
+            }
         }
     }
 
@@ -1131,7 +2607,9 @@ fn resolved_build_resources(project_path: &Path, config: &MergedConfig) -> Vec<R
             );
         }
     }
-    if output_kind.uses_latex_defaults() {
+    // Mirrors PandocBuilder: Beamer decks take neither the default LaTeX
+    // headers nor the theme's article headers and packages.
+    if output_kind.uses_latex_defaults() && output_kind != PandocOutputKind::Beamer {
         for (filter, relative) in [
             ("emoji.lua", pandoc::LIB_PANDOC_HEADER_EMOJI),
             ("admonition.lua", pandoc::LIB_PANDOC_HEADER_SEMANTIC_BLOCKS),
@@ -1193,7 +2671,7 @@ fn resolved_build_resources(project_path: &Path, config: &MergedConfig) -> Vec<R
         );
     }
 
-    if matches!(output_kind, PandocOutputKind::Html | PandocOutputKind::Epub) {
+    if output_kind.is_html() || output_kind == PandocOutputKind::Epub {
         if let Some(path) = existing_path(library_root.join(pandoc::LIB_PANDOC_CSS_BASE)) {
             add_resolved_resource(
                 &mut resources,
@@ -1204,9 +2682,42 @@ fn resolved_build_resources(project_path: &Path, config: &MergedConfig) -> Vec<R
             );
         }
     }
+    if output_kind == PandocOutputKind::RevealJs {
+        if let Some(path) = existing_path(library_root.join(pandoc::LIB_PANDOC_CSS_REVEALJS)) {
+            add_resolved_resource(
+                &mut resources,
+                project_path,
+                &library_root,
+                "omnidoc-revealjs-css".to_string(),
+                path,
+            );
+        }
+    }
+    if output_kind == PandocOutputKind::HtmlSite {
+        if let Some(path) = existing_path(library_root.join(pandoc::LIB_PANDOC_CSS_SITE)) {
+            add_resolved_resource(
+                &mut resources,
+                project_path,
+                &library_root,
+                "omnidoc-site-css".to_string(),
+                path,
+            );
+        }
+    }
+    if crate::build::search::search_enabled(config, theme.as_ref(), output_kind) {
+        if let Some(path) = existing_path(library_root.join(pandoc::LIB_PANDOC_SEARCH_WIDGET)) {
+            add_resolved_resource(
+                &mut resources,
+                project_path,
+                &library_root,
+                "omnidoc-search-widget".to_string(),
+                path,
+            );
+        }
+    }
 
     let (configured_css, theme_css, fallback_css, logical_prefix) = match output_kind {
-        PandocOutputKind::Html => (
+        PandocOutputKind::Html | PandocOutputKind::HtmlSite => (
             config.pandoc_css.as_deref(),
             theme
                 .as_ref()
@@ -1275,7 +2786,7 @@ fn resolved_build_resources(project_path: &Path, config: &MergedConfig) -> Vec<R
                 .or(config.pandoc_template.as_deref()),
             theme
                 .as_ref()
-                .and_then(|theme| theme.resources.latex_template.as_deref()),
+                .and_then(|theme| theme.resources.latex_template.clone()),
         )),
         PandocOutputKind::Html => Some((
             "html-template",
@@ -1285,7 +2796,14 @@ fn resolved_build_resources(project_path: &Path, config: &MergedConfig) -> Vec<R
                 .or(config.pandoc_template.as_deref()),
             theme
                 .as_ref()
-                .and_then(|theme| theme.resources.html_template.as_deref()),
+                .and_then(|theme| theme.resources.html_template.clone()),
+        )),
+        // Site pages need the navigation slots of the library site template,
+        // so the generic and theme HTML templates do not apply.
+        PandocOutputKind::HtmlSite => Some((
+            "site-template",
+            config.pandoc_site_template.as_deref(),
+            Some(library_root.join(pandoc::LIB_PANDOC_SITE_TEMPLATE)),
         )),
         PandocOutputKind::Epub => Some((
             "epub-template",
@@ -1295,9 +2813,31 @@ fn resolved_build_resources(project_path: &Path, config: &MergedConfig) -> Vec<R
                 .or(config.pandoc_template.as_deref()),
             theme
                 .as_ref()
-                .and_then(|theme| theme.resources.epub_template.as_deref()),
+                .and_then(|theme| theme.resources.epub_template.clone()),
+        )),
+        // Mirrors PandocBuilder::push_template: the shared library template is
+        // the default Typst template rather than a theme resource.
+        PandocOutputKind::Typst | PandocOutputKind::TypstPdf => Some((
+            "typst-template",
+            config
+                .pandoc_typst_template
+                .as_deref()
+                .or(config.pandoc_template.as_deref()),
+            Some(library_root.join(pandoc::LIB_PANDOC_TYPST_TEMPLATE)),
+        )),
+        PandocOutputKind::DocBook => Some((
+            "docbook-template",
+            config.pandoc_template.as_deref(),
+            Some(library_root.join(pandoc::LIB_PANDOC_DOCBOOK_TEMPLATE)),
         )),
-        PandocOutputKind::Docx | PandocOutputKind::Pptx => None,
+        PandocOutputKind::Docx
+        | PandocOutputKind::Pptx
+        | PandocOutputKind::Beamer
+        | PandocOutputKind::RevealJs
+        | PandocOutputKind::Gfm
+        | PandocOutputKind::Plain
+        | PandocOutputKind::Man
+        | PandocOutputKind::Jats => None,
     };
     if let Some((logical_name, configured, themed)) = template {
         let selected = if let Some(configured) = configured {
@@ -1308,7 +2848,7 @@ fn resolved_build_resources(project_path: &Path, config: &MergedConfig) -> Vec<R
                 Some("pandoc/data/templates"),
             )
         } else {
-            themed.and_then(|path| existing_path(path.to_path_buf()))
+            themed.and_then(existing_path)
         };
         if let Some(path) = selected {
             add_resolved_resource(
@@ -1463,7 +3003,7 @@ fn theme_resource_logical_name(theme: &ResolvedTheme, kind: &str, path: &Path) -
     )
 }
 
-fn omnidoc_library_root(config: &MergedConfig) -> PathBuf {
+pub(crate) fn omnidoc_library_root(config: &MergedConfig) -> PathBuf {
     config
         .lib_path
         .as_ref()
@@ -1673,9 +3213,19 @@ pub fn build_input_state(
         ("from", format!("{:?}", config.from)),
         ("to", format!("{:?}", config.to)),
         ("target", format!("{:?}", config.target)),
+        ("profile", format!("{:?}", config.profile)),
+        ("variant", format!("{:?}", config.variant)),
+        (
+            "variant_audiences",
+            format!("{:?}", config.variant_audiences),
+        ),
         ("outdir", format!("{:?}", config.outdir)),
         ("author", format!("{:?}", config.author)),
         ("metadata_file", format!("{:?}", config.metadata_file)),
+        ("glossary_title", format!("{:?}", config.glossary_title)),
+        ("index_enabled", format!("{:?}", config.index_enabled)),
+        ("index_title", format!("{:?}", config.index_title)),
+        ("index_program", format!("{:?}", config.index_program)),
         ("latex_backend", format!("{:?}", config.latex_backend)),
         ("max_latex_passes", format!("{:?}", config.max_latex_passes)),
         ("figure_paths", format!("{:?}", config.figure_paths)),
@@ -1686,6 +3236,9 @@ pub fn build_input_state(
             "theme_compatibility",
             format!("{:?}", config.theme_compatibility),
         ),
+        ("pdf_metadata", format!("{:?}", config.pdf_metadata)),
+        ("pdf_profile", format!("{:?}", config.pdf_profile)),
+        ("watermark", format!("{:?}", config.watermark)),
         ("extension_path", format!("{:?}", config.extension_path)),
         ("plugins_enabled", format!("{:?}", config.plugins_enabled)),
         ("pandoc_options", format!("{:?}", config.pandoc_options)),
@@ -1725,6 +3278,19 @@ pub fn build_input_state(
             "pandoc_epub_template",
             format!("{:?}", config.pandoc_epub_template),
         ),
+        (
+            "pandoc_typst_template",
+            format!("{:?}", config.pandoc_typst_template),
+        ),
+        (
+            "pandoc_site_template",
+            format!("{:?}", config.pandoc_site_template),
+        ),
+        (
+            "pandoc_site_split_level",
+            format!("{:?}", config.pandoc_site_split_level),
+        ),
+        ("pandoc_search", format!("{:?}", config.pandoc_search)),
         ("pandoc_data_dir", format!("{:?}", config.pandoc_data_dir)),
         (
             "pandoc_resource_path",
@@ -1936,7 +3502,10 @@ fn read_build_cache(project_path: &Path, output: &str) -> Option<BuildCache> {
 }
 
 fn write_build_cache(project_path: &Path, output: &str, state: &BuildInputState) -> Result<()> {
-    fs::create_dir_all(project_path.join(CACHE_DIR))?;
+    let path = cache_path(project_path, output);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
     let cache = BuildCache {
         cache_version: CACHE_VERSION,
         input_digest: state.input_digest.clone(),
@@ -1944,7 +3513,7 @@ fn write_build_cache(project_path: &Path, output: &str, state: &BuildInputState)
     };
     let content =
         serde_json::to_string_pretty(&cache).map_err(|err| OmniDocError::Other(err.to_string()))?;
-    utils::fs::atomic_write(cache_path(project_path, output), content)?;
+    utils::fs::atomic_write(path, content)?;
     Ok(())
 }
 
@@ -2052,12 +3621,13 @@ pub fn write_lock_targets(project_path: &Path, inputs: &[LockTargetInput<'_>]) -
                 resource.clone(),
             );
         }
-        targets.insert(input.output.to_ascii_lowercase(), target);
+        targets.insert(lock_target_name(input), target);
     }
     let resources = all_resources.into_values().collect::<Vec<_>>();
     let lock = LockFile {
         lock_version: LOCK_VERSION,
         omnidoc_version: env!("CARGO_PKG_VERSION").to_string(),
+        profile: first.config.profile.clone(),
         library: locked_library(first.config, &resources),
         packages: combined_locked_packages(project_path, inputs)?,
         toolchain: combined_toolchain_versions(project_path, inputs),
@@ -2093,13 +3663,27 @@ pub fn check_lock_targets(
     project_path: &Path,
     inputs: &[LockTargetInput<'_>],
 ) -> Result<LockStatus> {
+    check_document_lock_targets(project_path, inputs, None)
+}
+
+/// Check `omnidoc.lock` with target comparison limited to one document's
+/// `DOCUMENT/OUTPUT` entries. `inputs` still covers the whole project because
+/// the library, package and toolchain sections are shared by every document.
+pub fn check_document_lock_targets(
+    project_path: &Path,
+    inputs: &[LockTargetInput<'_>],
+    document: Option<&str>,
+) -> Result<LockStatus> {
+    let in_scope = |input: &&LockTargetInput<'_>| {
+        document.is_none() || input.config.document.as_deref() == document
+    };
     let lock_path = project_path.join(LOCK_FILE);
     if !lock_path.exists() {
         let mut targets = BTreeMap::new();
-        for input in inputs {
+        for input in inputs.iter().filter(in_scope) {
             let expected = locked_target(project_path, input)?;
             targets.insert(
-                input.output.to_ascii_lowercase(),
+                lock_target_name(input),
                 LockTargetStatus {
                     up_to_date: false,
                     expected_digest: expected.input_digest,
@@ -2112,6 +3696,7 @@ pub fn check_lock_targets(
         return Ok(LockStatus {
             exists: false,
             up_to_date: false,
+            profile_up_to_date: false,
             library_up_to_date: false,
             packages_up_to_date: false,
             toolchain_up_to_date: false,
@@ -2119,7 +3704,8 @@ pub fn check_lock_targets(
             extra_packages: Vec::new(),
             missing_targets: inputs
                 .iter()
-                .map(|input| input.output.to_ascii_lowercase())
+                .filter(in_scope)
+                .map(lock_target_name)
                 .collect(),
             extra_targets: Vec::new(),
             targets,
@@ -2131,9 +3717,20 @@ pub fn check_lock_targets(
         toml::from_str(&content).map_err(|err| OmniDocError::Other(err.to_string()))?;
     let expected_names = inputs
         .iter()
-        .map(|input| input.output.to_ascii_lowercase())
+        .filter(in_scope)
+        .map(lock_target_name)
+        .collect::<BTreeSet<_>>();
+    let document_prefix = document.map(|document| format!("{document}/"));
+    let actual_names = lock
+        .targets
+        .keys()
+        .filter(|name| {
+            document_prefix
+                .as_deref()
+                .is_none_or(|prefix| name.starts_with(prefix))
+        })
+        .cloned()
         .collect::<BTreeSet<_>>();
-    let actual_names = lock.targets.keys().cloned().collect::<BTreeSet<_>>();
     let missing_targets = expected_names
         .difference(&actual_names)
         .cloned()
@@ -2145,7 +3742,7 @@ pub fn check_lock_targets(
     let mut statuses = BTreeMap::new();
     let mut all_resources = BTreeMap::new();
     for input in inputs {
-        let name = input.output.to_ascii_lowercase();
+        let name = lock_target_name(input);
         let expected = locked_target(project_path, input)?;
         for resource in &expected.resources {
             all_resources.insert(
@@ -2156,6 +3753,9 @@ pub fn check_lock_targets(
                 resource.clone(),
             );
         }
+        if !in_scope(&input) {
+            continue;
+        }
         let actual = lock.targets.get(&name);
         let expected_dependencies = expected
             .dependencies
@@ -2192,6 +3792,7 @@ pub fn check_lock_targets(
     }
     let resources = all_resources.into_values().collect::<Vec<_>>();
     let first_config = inputs.first().map(|input| input.config);
+    let profile_up_to_date = lock.profile == first_config.and_then(|config| config.profile.clone());
     let library_up_to_date =
         first_config.is_some_and(|config| lock.library == locked_library(config, &resources));
     let expected_packages = combined_locked_packages(project_path, inputs)?;
@@ -2208,6 +3809,7 @@ pub fn check_lock_targets(
     let packages_up_to_date = missing_packages.is_empty() && extra_packages.is_empty();
     let toolchain_up_to_date = lock.toolchain == combined_toolchain_versions(project_path, inputs);
     let up_to_date = lock.lock_version == LOCK_VERSION
+        && profile_up_to_date
         && missing_targets.is_empty()
         && extra_targets.is_empty()
         && library_up_to_date
@@ -2218,6 +3820,7 @@ pub fn check_lock_targets(
     Ok(LockStatus {
         exists: true,
         up_to_date,
+        profile_up_to_date,
         library_up_to_date,
         packages_up_to_date,
         toolchain_up_to_date,
@@ -2229,6 +3832,10 @@ pub fn check_lock_targets(
     })
 }
 
+fn lock_target_name(input: &LockTargetInput<'_>) -> String {
+    target_output_key(input.config, input.output)
+}
+
 fn locked_target(project_path: &Path, input: &LockTargetInput<'_>) -> Result<LockedTarget> {
     Ok(LockedTarget {
         input_digest: build_input_digest(project_path, input.graph, input.config, input.output)?,
@@ -2340,7 +3947,25 @@ fn toolchain_versions(
     .map(|(name, program)| (name.to_string(), command_version(&program)))
     .collect::<BTreeMap<_, _>>();
     let output_kind = PandocOutputKind::from_requested(Some(output)).ok();
-    if output_kind == Some(PandocOutputKind::Pdf) {
+    if output_kind == Some(PandocOutputKind::Pdf) && uses_typst_pdf_engine(&config.tool_paths) {
+        let executor = BuildExecutor::new(config.tool_paths.clone());
+        versions.insert(
+            "typst".to_string(),
+            executor
+                .resolve_typst()
+                .map(|typst| command_version(&typst))
+                .unwrap_or_else(|_| "unavailable".to_string()),
+        );
+        if let Ok(Some(theme)) = resolve_selected_theme(Some(project_path), config) {
+            if theme.supports_output("pdf") {
+                for font in theme.requirements.fonts {
+                    versions.insert(format!("font:{font}"), font_identity(&font));
+                }
+            }
+        }
+        return versions;
+    }
+    if output_kind.is_some_and(PandocOutputKind::uses_latex_pdf_engine) {
         let executor = BuildExecutor::new(config.tool_paths.clone());
         let preference = latex_engine_preference(project_path, config);
         let engine = executor.resolve_latex_engine(preference).ok();
@@ -2575,14 +4200,23 @@ pub fn print_issues(issues: &[ProjectIssue]) {
             IssueSeverity::Warning => "warning",
             IssueSeverity::Info => "info",
         };
+        let message = match &issue.rule {
+            Some(rule) => format!("{} [{}]", issue.message, rule),
+            None => issue.message.clone(),
+        };
         if let Some(path) = &issue.path {
-            if let Some(line) = issue.line {
-                println!("{}:{}: {}: {}", path, line, severity, issue.message);
+            if let (Some(line), Some(column)) = (issue.line, issue.column) {
+                terminal::out(format!(
+                    "{}:{}:{}: {}: {}\n",
+                    path, line, column, severity, message
+                ));
+            } else if let Some(line) = issue.line {
+                terminal::out(format!("{}:{}: {}: {}\n", path, line, severity, message));
             } else {
-                println!("{}: {}: {}", path, severity, issue.message);
+                terminal::out(format!("{}: {}: {}\n", path, severity, message));
             }
         } else {
-            println!("{}: {}", severity, issue.message);
+            terminal::out(format!("{}: {}\n", severity, message));
         }
     }
 }
@@ -2590,6 +4224,8 @@ pub fn print_issues(issues: &[ProjectIssue]) {
 pub fn build_report(context: BuildReportContext<'_>) -> BuildReport {
     let toolchain = toolchain_versions(context.project_path, context.config, &context.output);
     BuildReport {
+        document: context.config.document.clone(),
+        profile: context.config.profile.clone(),
         output: context.output,
         target: context.target,
         skipped: context.skipped,
@@ -2603,6 +4239,8 @@ pub fn build_report(context: BuildReportContext<'_>) -> BuildReport {
             .then(|| content_digest(context.artifact).ok())
             .flatten(),
         compatibility: context.compatibility,
+        xml_validation: context.xml_validation,
+        pdf: context.pdf,
         dependencies: context.graph.files.clone(),
         resources: locked_resources(context.graph).unwrap_or_default(),
         toolchain,
@@ -2618,7 +4256,7 @@ fn current_timestamp_unix() -> u64 {
         .unwrap_or(0)
 }
 
-fn source_files(project_path: &Path) -> Vec<PathBuf> {
+pub(crate) fn source_files(project_path: &Path) -> Vec<PathBuf> {
     WalkDir::new(project_path)
         .into_iter()
         .filter_entry(|entry| should_descend(entry.path(), project_path))
@@ -2671,22 +4309,18 @@ fn is_source_file(path: &Path) -> bool {
     )
 }
 
-fn check_local_target(
-    project_path: &Path,
-    source_file: &Path,
-    target: &str,
-    rel: &str,
-    line: usize,
-    issues: &mut Vec<ProjectIssue>,
-) {
+/// The decoded path of a Markdown target that exists neither next to the
+/// source file nor under the project root; `None` for URLs, anchors and
+/// targets that resolve.
+fn missing_local_target(project_path: &Path, source_file: &Path, target: &str) -> Option<String> {
     let target = target.trim();
     let target = if let Some(angled) = target.strip_prefix('<') {
         angled.split('>').next().unwrap_or(angled)
     } else {
         target.split_whitespace().next().unwrap_or(target)
     };
-    if !is_local_path(target) {
-        return;
+    if !is_local_path(target) || target.starts_with("data:") {
+        return None;
     }
     let target = target
         .split(['#', '?'])
@@ -2694,16 +4328,11 @@ fn check_local_target(
         .map(|value| percent_decode_str(value).decode_utf8_lossy())
         .unwrap_or_default();
     if target.is_empty() {
-        return;
+        return None;
     }
     let base = source_file.parent().unwrap_or(project_path);
-    if !base.join(target.as_ref()).exists() && !project_path.join(target.as_ref()).exists() {
-        issues.push(warning(
-            format!("Referenced local resource not found: {}", target),
-            Some(rel.to_string()),
-            Some(line),
-        ));
-    }
+    (!base.join(target.as_ref()).exists() && !project_path.join(target.as_ref()).exists())
+        .then(|| target.into_owned())
 }
 
 fn check_configured_path(
@@ -2781,13 +4410,21 @@ fn looks_like_email_address(target: &str) -> bool {
         && domain.contains('.')
 }
 
+/// `output` is a [`target_output_key`]; document and variant targets live
+/// under their own cache subdirectories.
 fn cache_path(project_path: &Path, output: &str) -> PathBuf {
-    project_path
-        .join(CACHE_DIR)
-        .join(format!("build-{}.json", output))
+    let (output, variant) = match output.split_once('@') {
+        Some((output, variant)) => (output, Some(variant)),
+        None => (output, None),
+    };
+    let (document, output) = match output.split_once('/') {
+        Some((document, output)) => (Some(document), output),
+        None => (None, output),
+    };
+    target_cache_dir(project_path, document, variant).join(format!("build-{}.json", output))
 }
 
-fn display_relative(project_path: &Path, path: &Path) -> String {
+pub(crate) fn display_relative(project_path: &Path, path: &Path) -> String {
     let relative = path
         .strip_prefix(project_path)
         .unwrap_or(path)
@@ -2802,6 +4439,8 @@ fn error(message: String, path: Option<String>, line: Option<usize>) -> ProjectI
         message,
         path,
         line,
+        column: None,
+        rule: None,
     }
 }
 
@@ -2811,21 +4450,41 @@ fn warning(message: String, path: Option<String>, line: Option<usize>) -> Projec
         message,
         path,
         line,
+        column: None,
+        rule: None,
     }
 }
 
+/// A rule finding at `rel:line`, for lints that do not track columns.
+fn finding(rule: &'static str, message: String, rel: &str, line: usize) -> (String, lint::Finding) {
+    (
+        rel.to_string(),
+        lint::Finding {
+            rule,
+            message,
+            line,
+            column: None,
+        },
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::{
-        acquire_project_write_lock, build_input_digest, build_report, cache_hit,
-        changed_cache_components, check_lock, dependency_graph, filter_depfile_metadata_key,
-        filter_depfile_name, latex_engine_preference, lint_project, pandoc_option_file_references,
-        supported_outputs, validate_config, write_cache, write_lock, write_lock_targets, LockFile,
-        LockTargetInput, CACHE_DIR, INCLUDE_DEPFILE, LATEX_INPUT_DEPFILE,
+        acquire_project_write_lock, build_input_digest, build_input_state, build_report, cache_hit,
+        cache_path, changed_cache_components, check_document_lock_targets, check_lock,
+        check_lock_targets, dependency_graph, document_output_key, filter_depfile_metadata_key,
+        filter_depfile_name, fragment_cache_details, latex_engine_preference, lint_citations,
+        lint_crossrefs, lint_glossary, lint_project, lint_terms, lint_variants, output_scratch_dir,
+        pandoc_option_file_references, supported_outputs, validate_config, write_cache, write_lock,
+        write_lock_targets, IssueSeverity, LockFile, LockTargetInput, INCLUDE_DEPFILE,
+        INCLUDE_FRAGMENT_LOG, LATEX_INPUT_DEPFILE,
     };
     use crate::build::executor::LatexEnginePreference;
+    use crate::build::pandoc_policy::PandocOutputKind;
     use crate::config::MergedConfig;
-    use std::collections::BTreeMap;
+    use crate::constants::pandoc;
+    use std::collections::{BTreeMap, HashMap};
     use std::fs;
     use std::path::{Path, PathBuf};
     use std::time::{SystemTime, UNIX_EPOCH};
@@ -2942,6 +4601,7 @@ mod tests {
     fn lists_core_outputs() {
         assert!(supported_outputs().contains(&"pdf"));
         assert!(supported_outputs().contains(&"html"));
+        assert!(supported_outputs().contains(&"typst"));
     }
 
     #[test]
@@ -3104,9 +4764,14 @@ mod tests {
         fs::write(project.path().join("main.md"), "# Main\n").expect("entry");
         fs::write(&chapter, "Project LaTeX input\n").expect("project TeX input");
         fs::write(&package, "External package v1\n").expect("external package");
-        fs::create_dir_all(project.path().join(CACHE_DIR)).expect("cache dir");
+        let scratch = output_scratch_dir(
+            project.path(),
+            &MergedConfig::default(),
+            PandocOutputKind::Pdf,
+        );
+        fs::create_dir_all(&scratch).expect("scratch dir");
         fs::write(
-            project.path().join(CACHE_DIR).join(LATEX_INPUT_DEPFILE),
+            scratch.join(LATEX_INPUT_DEPFILE),
             format!(
                 "# omnidoc-depfile-v1\n# source=latex-fls\n{}\n{}\n",
                 chapter.display(),
@@ -3135,6 +4800,49 @@ mod tests {
         assert_ne!(before, after);
     }
 
+    #[test]
+    fn typst_pdf_dependency_graph_tracks_the_typst_template_not_latex_inputs() {
+        let project = tempfile::tempdir().expect("project tempdir");
+        let library = tempfile::tempdir().expect("library tempdir");
+        let chapter = project.path().join("chapter.tex");
+        let template = library.path().join(pandoc::LIB_PANDOC_TYPST_TEMPLATE);
+        fs::create_dir_all(template.parent().expect("templates dir")).expect("templates dir");
+        fs::write(&template, "$body$\n").expect("typst template");
+        fs::write(project.path().join("main.md"), "# Main\n").expect("entry");
+        fs::write(&chapter, "Stale LaTeX input\n").expect("stale TeX input");
+        let scratch = output_scratch_dir(
+            project.path(),
+            &MergedConfig::default(),
+            PandocOutputKind::Pdf,
+        );
+        fs::create_dir_all(&scratch).expect("scratch dir");
+        fs::write(
+            scratch.join(LATEX_INPUT_DEPFILE),
+            format!(
+                "# omnidoc-depfile-v1\n# source=latex-fls\n{}\n",
+                chapter.display()
+            ),
+        )
+        .expect("LaTeX depfile");
+        let config = MergedConfig {
+            entry: Some("main.md".to_string()),
+            to: Some("pdf".to_string()),
+            lib_path: Some(library.path().to_string_lossy().to_string()),
+            tool_paths: HashMap::from([("latex_engine".to_string(), Some("typst".to_string()))]),
+            ..Default::default()
+        };
+
+        let graph = dependency_graph(project.path(), &config);
+        assert!(!graph.files.contains(&"chapter.tex".to_string()));
+        assert!(graph.resources.iter().any(|resource| {
+            resource.logical_name == "typst-template" && resource.path == canonical_text(&template)
+        }));
+        let state =
+            build_input_state(project.path(), &graph, &config, "pdf").expect("Typst PDF state");
+        assert!(state.components.contains_key("toolchain:typst"));
+        assert!(!state.components.contains_key("toolchain:latex_engine"));
+    }
+
     #[test]
     fn dependency_graph_uses_filter_depfiles_for_actual_includes() {
         let project = tempfile::tempdir().expect("project tempdir");
@@ -3149,9 +4857,14 @@ mod tests {
         fs::create_dir_all(project.path().join("chapters")).expect("chapters");
         let chapter = project.path().join("chapters/actual.md");
         fs::write(&chapter, "# Actual include\n").expect("included chapter");
-        fs::create_dir_all(project.path().join(CACHE_DIR)).expect("cache dir");
+        let scratch = output_scratch_dir(
+            project.path(),
+            &MergedConfig::default(),
+            PandocOutputKind::Html,
+        );
+        fs::create_dir_all(&scratch).expect("scratch dir");
         fs::write(
-            project.path().join(CACHE_DIR).join(INCLUDE_DEPFILE),
+            scratch.join(INCLUDE_DEPFILE),
             format!(
                 "# omnidoc-depfile-v1\n{}\n{}\n",
                 chapter.display(),
@@ -3176,9 +4889,19 @@ mod tests {
                 && resource.resolved_from == "external"
                 && resource.path == canonical_text(external.path())
         }));
+        let pdf = dependency_graph(
+            project.path(),
+            &MergedConfig {
+                entry: Some("main.md".to_string()),
+                to: Some("pdf".to_string()),
+                lib_path: Some(library.path().to_string_lossy().to_string()),
+                ..Default::default()
+            },
+        );
+        assert!(!pdf.files.contains(&"chapters/actual.md".to_string()));
 
         fs::write(
-            project.path().join(CACHE_DIR).join(INCLUDE_DEPFILE),
+            scratch.join(INCLUDE_DEPFILE),
             format!("# unknown-depfile\n{}\n", chapter.display()),
         )
         .expect("invalid depfile");
@@ -3194,6 +4917,40 @@ mod tests {
         assert!(!ignored.files.contains(&"chapters/actual.md".to_string()));
     }
 
+    #[test]
+    fn fragment_cache_details_report_reuse_ratio_and_reparsed_chapters() {
+        let project = tempfile::tempdir().expect("project tempdir");
+        let config = MergedConfig {
+            to: Some("html".to_string()),
+            ..Default::default()
+        };
+        assert!(fragment_cache_details(project.path(), &config).is_empty());
+
+        let scratch = output_scratch_dir(project.path(), &config, PandocOutputKind::Html);
+        fs::create_dir_all(&scratch).expect("scratch dir");
+        let chapters = project.path().join("chapters");
+        fs::write(
+            scratch.join(INCLUDE_FRAGMENT_LOG),
+            format!(
+                "# omnidoc-fragments-v1\nhit {}\nmiss {}\nhit {}\nhit {}\n",
+                chapters.join("one.md").display(),
+                chapters.join("two.md").display(),
+                chapters.join("three.md").display(),
+                chapters.join("four.md").display()
+            ),
+        )
+        .expect("fragment log");
+
+        assert_eq!(
+            fragment_cache_details(project.path(), &config),
+            vec![
+                "fragment_cache_hits:3/4".to_string(),
+                "fragment_cache_hit_ratio:0.75".to_string(),
+                "fragment_reparsed:chapters/two.md".to_string(),
+            ]
+        );
+    }
+
     #[test]
     fn dependency_graph_consumes_depfiles_for_custom_active_filters() {
         let project = tempfile::tempdir().expect("project tempdir");
@@ -3205,9 +4962,14 @@ mod tests {
         fs::write(project.path().join("main.md"), "# Main\n").expect("entry");
         fs::create_dir_all(project.path().join("data")).expect("data dir");
         fs::write(project.path().join("data/custom.json"), "{}\n").expect("custom data");
-        fs::create_dir_all(project.path().join(CACHE_DIR)).expect("cache dir");
+        let scratch = output_scratch_dir(
+            project.path(),
+            &MergedConfig::default(),
+            PandocOutputKind::Html,
+        );
+        fs::create_dir_all(&scratch).expect("scratch dir");
         fs::write(
-            project.path().join(CACHE_DIR).join("custom-reader.d"),
+            scratch.join("custom-reader.d"),
             format!(
                 "# omnidoc-depfile-v1\n{}\n",
                 project.path().join("data/custom.json").display()
@@ -3215,7 +4977,7 @@ mod tests {
         )
         .expect("custom depfile");
         fs::write(
-            project.path().join(CACHE_DIR).join(INCLUDE_DEPFILE),
+            scratch.join(INCLUDE_DEPFILE),
             format!(
                 "# omnidoc-depfile-v1\n{}\n",
                 project.path().join("inactive.md").display()
@@ -3257,7 +5019,7 @@ mod tests {
         )
         .expect("entry");
 
-        let issues = lint_project(project.path());
+        let issues = lint_project(project.path(), &MergedConfig::default());
 
         assert!(
             issues.is_empty(),
@@ -3294,7 +5056,7 @@ user@example.com
         )
         .expect("Markdown notes");
 
-        let issues = lint_project(project.path());
+        let issues = lint_project(project.path(), &MergedConfig::default());
 
         assert!(
             issues.is_empty(),
@@ -3316,7 +5078,7 @@ user@example.com
         )
         .expect("LaTeX entry");
 
-        let issues = lint_project(project.path());
+        let issues = lint_project(project.path(), &MergedConfig::default());
         let messages = issues
             .iter()
             .map(|issue| issue.message.as_str())
@@ -3334,6 +5096,526 @@ user@example.com
             .any(|message| message.contains("figures/missing")));
     }
 
+    #[test]
+    fn crossref_lint_reports_unresolved_duplicate_and_unused_labels() {
+        let project = tempfile::tempdir().expect("project tempdir");
+        fs::create_dir_all(project.path().join("chapters")).expect("chapters dir");
+        fs::write(
+            project.path().join("main.md"),
+            concat!(
+                "# Overview {#sec:overview}\n\n",
+                "See @fig:plot, [@Tbl:pins; @eq:gain] and @fig-missing.\n",
+                "Mail me@sec-ops.example or write `@fig:code`.\n\n",
+                "```{.include}\nchapters/hardware.md\n```\n",
+            ),
+        )
+        .expect("Markdown entry");
+        fs::write(
+            project.path().join("chapters/hardware.md"),
+            concat!(
+                "## Hardware {#sec:hardware}\n\n",
+                "![Plot](plot.png){#fig:plot width=50%}\n\n",
+                "| Pin |\n|-----|\n| 1 |\n\n: Pins {#tbl:pins}\n\n",
+                "$$ g = 2 $$ {#eq:gain}\n\n",
+                "```{.circuit #fig-divider include-code=\"divider.py\"\n",
+                "caption=\"Divider\" #fig:plot}\n",
+                "```\n\n",
+                "```{#lst:setup .python}\n# {#fig:inside-code} @fig:inside-code\n```\n\n",
+                "Back to [the overview](#sec:overview).\n",
+            ),
+        )
+        .expect("chapter");
+        fs::write(
+            project.path().join("draft.md"),
+            "# Draft {#sec:draft}\n\n@fig:draft-only\n",
+        )
+        .expect("unincluded draft");
+
+        let issues = lint_crossrefs(
+            project.path(),
+            &MergedConfig {
+                entry: Some("main.md".to_string()),
+                ..Default::default()
+            },
+        );
+        let reported = issues
+            .iter()
+            .map(|issue| {
+                (
+                    issue.severity.clone(),
+                    issue.message.as_str(),
+                    issue.path.as_deref(),
+                    issue.line,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            reported,
+            [
+                (
+                    IssueSeverity::Warning,
+                    "Unresolved cross-reference '@fig-missing'",
+                    Some("main.md"),
+                    Some(3)
+                ),
+                (
+                    IssueSeverity::Info,
+                    "Cross-reference label '#fig-divider' is never referenced",
+                    Some("chapters/hardware.md"),
+                    Some(13)
+                ),
+                (
+                    IssueSeverity::Warning,
+                    "Duplicate cross-reference label '#fig:plot' (first declared at chapters/hardware.md:3)",
+                    Some("chapters/hardware.md"),
+                    Some(14)
+                ),
+                (
+                    IssueSeverity::Info,
+                    "Cross-reference label '#lst:setup' is never referenced",
+                    Some("chapters/hardware.md"),
+                    Some(17)
+                ),
+                (
+                    IssueSeverity::Info,
+                    "Cross-reference label '#sec:hardware' is never referenced",
+                    Some("chapters/hardware.md"),
+                    Some(1)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn crossref_rules_follow_lint_config_and_suppression_comments() {
+        let project = tempfile::tempdir().expect("project tempdir");
+        fs::write(
+            project.path().join("main.md"),
+            concat!(
+                "# Overview {#sec:overview}\n\n",
+                "<!-- omnidoc-lint-disable-next-line unresolved_crossref -->\n",
+                "See @fig:later.\n\n",
+                "Then @fig:missing and [#sec:overview]{#sec:overview}.\n",
+            ),
+        )
+        .expect("Markdown entry");
+
+        let issues = lint_crossrefs(
+            project.path(),
+            &MergedConfig {
+                entry: Some("main.md".to_string()),
+                lint_rules: BTreeMap::from([
+                    ("unused_crossref_label".to_string(), "off".to_string()),
+                    ("duplicate_crossref_label".to_string(), "error".to_string()),
+                ]),
+                ..Default::default()
+            },
+        );
+        let reported = issues
+            .iter()
+            .map(|issue| {
+                (
+                    issue.severity.clone(),
+                    issue.rule.as_deref(),
+                    issue.line,
+                    issue.column,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            reported,
+            [
+                (
+                    IssueSeverity::Warning,
+                    Some("unresolved_crossref"),
+                    Some(6),
+                    None
+                ),
+                (
+                    IssueSeverity::Error,
+                    Some("duplicate_crossref_label"),
+                    Some(6),
+                    None
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn citation_lint_checks_keys_against_declared_bibliographies() {
+        let project = tempfile::tempdir().expect("project tempdir");
+        fs::create_dir_all(project.path().join("biblio")).expect("biblio dir");
+        fs::write(
+            project.path().join("main.md"),
+            concat!(
+                "---\n",
+                "title: Citations\n",
+                "bibliography:\n",
+                "  - biblio/refs.bib\n",
+                "  - refs.json\n",
+                "csl: styles/short.csl\n",
+                "nocite: |\n",
+                "  @boyd2004\n",
+                "---\n\n",
+                "As shown [@knuth1984, p. 3; -@doe2020] and by @missing.\n",
+                "See @fig:plot, `@code`, me@example.org and https://medium.com/@user.\n",
+                "Also @undeclared.\n\n",
+                "```\n@incode\n```\n",
+            ),
+        )
+        .expect("Markdown entry");
+        fs::write(
+            project.path().join("biblio/refs.bib"),
+            concat!(
+                "@article{knuth1984,\n  author = {Knuth},\n  title = {Literate Programming},\n  year = 1984\n}\n\n",
+                "@book{boyd2004,\n  author = {Boyd},\n  title = {Convex Optimization},\n  year = 2004\n}\n\n",
+                "@misc{lamport94, title = {LaTeX}}\n",
+            ),
+        )
+        .expect("BibTeX");
+        fs::write(
+            project.path().join("refs.json"),
+            "[{\"id\": \"doe2020\", \"type\": \"book\", \"title\": \"Doe\", \"author\": [], \"issued\": {}},\n {\"id\": \"lamport94\", \"title\": \"Again\"}]\n",
+        )
+        .expect("CSL-JSON");
+        fs::write(
+            project.path().join("biblio/extra.bib"),
+            "@misc{undeclared, title = {Elsewhere}}\n",
+        )
+        .expect("undeclared bibliography");
+        fs::create_dir_all(project.path().join("styles")).expect("styles dir");
+        fs::write(
+            project.path().join("styles/short.csl"),
+            r#"<style><bibliography><layout><names variable="author"/><text variable="title"/><text variable="container-title"/><date variable="issued"/></layout></bibliography></style>"#,
+        )
+        .expect("CSL style");
+
+        let issues = lint_citations(
+            project.path(),
+            &MergedConfig {
+                entry: Some("main.md".to_string()),
+                paths: crate::config::PathConfig::new(),
+                ..Default::default()
+            },
+        );
+        let reported = issues
+            .iter()
+            .map(|issue| {
+                (
+                    issue.severity.clone(),
+                    issue.message.as_str(),
+                    issue.path.as_deref(),
+                    issue.line,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            reported,
+            [
+                (
+                    IssueSeverity::Warning,
+                    "Undefined citation key '@missing'",
+                    Some("main.md"),
+                    Some(11)
+                ),
+                (
+                    IssueSeverity::Warning,
+                    "Citation key '@undeclared' is only defined in biblio/extra.bib, which the document does not list under `bibliography`",
+                    Some("main.md"),
+                    Some(13)
+                ),
+                (
+                    IssueSeverity::Warning,
+                    "Bibliography entry 'knuth1984' (article-journal) lacks container-title required by the styles/short.csl style",
+                    Some("biblio/refs.bib"),
+                    Some(1)
+                ),
+                (
+                    IssueSeverity::Warning,
+                    "Duplicate citation key 'lamport94' (first defined at biblio/refs.bib:13)",
+                    Some("refs.json"),
+                    Some(2)
+                ),
+                (
+                    IssueSeverity::Info,
+                    "Bibliography entry 'lamport94' is never cited",
+                    Some("biblio/refs.bib"),
+                    Some(13)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn glossary_lint_reports_undefined_duplicate_and_unused_terms() {
+        let project = tempfile::tempdir().expect("project tempdir");
+        fs::write(
+            project.path().join("acronyms.toml"),
+            "ADC = \"analog-to-digital converter\"\n\n[PLL]\nlong = \"phase-locked loop\"\n",
+        )
+        .expect("TOML glossary");
+        fs::write(
+            project.path().join("terms.yaml"),
+            "ADC: analog-digital converter\nJitter:\n  description: Timing noise.\n",
+        )
+        .expect("YAML glossary");
+        fs::write(
+            project.path().join("main.md"),
+            concat!(
+                "# Clocks\n\n",
+                "The [+ADC] samples, the [loop]{.gls key=PLL} locks, and [+VCO] drifts.\n",
+                "Literal `[+Jitter]` and\n\n```\n[+Jitter]\n```\n",
+            ),
+        )
+        .expect("Markdown entry");
+
+        let config = MergedConfig {
+            entry: Some("main.md".to_string()),
+            glossary_files: vec!["acronyms.toml".to_string(), "terms.yaml".to_string()],
+            ..Default::default()
+        };
+        let issues = lint_glossary(project.path(), &config);
+        let reported = issues
+            .iter()
+            .map(|issue| {
+                (
+                    issue.severity.clone(),
+                    issue.message.as_str(),
+                    issue.path.as_deref(),
+                    issue.line,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            reported,
+            [
+                (
+                    IssueSeverity::Warning,
+                    "Duplicate glossary key 'ADC' (first defined at acronyms.toml:1)",
+                    Some("terms.yaml"),
+                    Some(1)
+                ),
+                (
+                    IssueSeverity::Warning,
+                    "Undefined glossary term 'VCO'",
+                    Some("main.md"),
+                    Some(3)
+                ),
+                (
+                    IssueSeverity::Info,
+                    "Glossary term 'Jitter' is never referenced",
+                    Some("terms.yaml"),
+                    Some(2)
+                ),
+            ]
+        );
+
+        let missing = MergedConfig {
+            glossary_files: vec!["missing.yaml".to_string()],
+            ..Default::default()
+        };
+        assert!(validate_config(project.path(), &missing)
+            .iter()
+            .any(|issue| issue.message == "Configured glossary file not found: missing.yaml"));
+    }
+
+    #[test]
+    fn terms_lint_skips_code_math_and_markup() {
+        let project = tempfile::tempdir().expect("project tempdir");
+        fs::write(
+            project.path().join("terms.toml"),
+            concat!(
+                "[[terms]]\npreferred = \"Wi-Fi\"\nforbidden = [\"WiFi\"]\n\n",
+                "[[terms]]\npreferred = \"USB Type-C\"\nforbidden = [\"Type-C\"]\n\n",
+                "[[terms]]\npreferred = \"服务器\"\nforbidden = [\"伺服器\"]\nlang = \"zh\"\n\n",
+                "[[terms]]\npreferred = \"colour\"\nforbidden = [\"color\"]\nlang = \"en-GB\"\n",
+            ),
+        )
+        .expect("terms dictionary");
+        fs::write(
+            project.path().join("main.md"),
+            concat!(
+                "---\ntitle: WiFi guide\nlang: zh-CN\n---\n",
+                "# Connecting over WiFi\n",
+                "Plug in a Type-C cable, or read `WiFi` and $WiFi$.\n",
+                "```\nWiFi in code\n```\n",
+                "$$\nWiFi\n$$\n",
+                "See [docs](https://example.com/WiFi) and <span class=\"WiFi\">USB Type-C</span>.\n",
+                "连接伺服器\n",
+                "Pick a color.\n",
+            ),
+        )
+        .expect("Markdown entry");
+        fs::write(
+            project.path().join("paper.tex"),
+            concat!(
+                "\\documentclass{article}\n\\usepackage{WiFi}\n\\begin{document}\n",
+                "WiFi access. % WiFi comment\n",
+                "\\begin{verbatim}\nWiFi\n\\end{verbatim}\n",
+                "\\begin{equation}\nWiFi\n\\end{equation}\n",
+                "Inline $WiFi$ and \\ref{sec:WiFi} stay quiet, \\textbf{WiFi} does not.\n",
+                "\\end{document}\n",
+            ),
+        )
+        .expect("LaTeX source");
+
+        let issues = lint_terms(
+            project.path(),
+            &MergedConfig {
+                entry: Some("main.md".to_string()),
+                ..Default::default()
+            },
+        );
+        let mut reported = issues
+            .iter()
+            .map(|issue| {
+                (
+                    issue.severity.clone(),
+                    issue.message.as_str(),
+                    issue.path.as_deref(),
+                    issue.line,
+                )
+            })
+            .collect::<Vec<_>>();
+        reported.sort_by_key(|(_, _, path, line)| (*path, *line));
+
+        assert_eq!(
+            reported,
+            [
+                (
+                    IssueSeverity::Warning,
+                    "Use 'Wi-Fi' instead of 'WiFi'",
+                    Some("main.md"),
+                    Some(5)
+                ),
+                (
+                    IssueSeverity::Warning,
+                    "Use 'USB Type-C' instead of 'Type-C'",
+                    Some("main.md"),
+                    Some(6)
+                ),
+                (
+                    IssueSeverity::Warning,
+                    "Use '服务器' instead of '伺服器'",
+                    Some("main.md"),
+                    Some(14)
+                ),
+                (
+                    IssueSeverity::Warning,
+                    "Use 'Wi-Fi' instead of 'WiFi'",
+                    Some("paper.tex"),
+                    Some(4)
+                ),
+                (
+                    IssueSeverity::Warning,
+                    "Use 'Wi-Fi' instead of 'WiFi'",
+                    Some("paper.tex"),
+                    Some(11)
+                ),
+            ]
+        );
+
+        fs::write(project.path().join("terms.toml"), "[[terms]]\nword = 1\n").expect("bad terms");
+        let issues = validate_config(project.path(), &MergedConfig::default());
+        assert!(issues
+            .iter()
+            .any(|issue| issue.severity == IssueSeverity::Error
+                && issue.message.starts_with("Invalid terms dictionary")));
+    }
+
+    #[test]
+    fn variant_lint_reports_references_to_hidden_labels() {
+        let project = tempfile::tempdir().expect("project tempdir");
+        fs::write(
+            project.path().join("main.md"),
+            concat!(
+                "# Overview {#sec:overview}\n\n",
+                "See @sec:internal, @fig:plan and [pricing](#pricing).\n\n",
+                "::: {.only-internal}\n",
+                "## Internal notes {#sec:internal}\n\n",
+                "![Plan](plan.png){#fig:plan}\n",
+                ":::\n\n",
+                "::: {when=\"!customer\"}\n",
+                "Back to @sec:overview.\n",
+                ":::\n\n",
+                "Price [list]{#pricing when=\"partner\"} and @sec:internal again.\n",
+                "[Only partners see @fig:plan]{.only-partner}\n",
+                "```markdown\n@sec:internal in code\n```\n\n",
+                "::: only-internal\n![Rack](rack.png){#fig-rack}\n:::\n\n",
+                "See @Fig-rack.\n",
+            ),
+        )
+        .expect("Markdown entry");
+        let variants = BTreeMap::from([
+            ("customer".to_string(), vec!["customer".to_string()]),
+            ("internal".to_string(), vec!["internal".to_string()]),
+            ("partner".to_string(), vec!["partner".to_string()]),
+        ]);
+
+        let issues = lint_variants(project.path(), &MergedConfig::default(), &variants);
+        let reported = issues
+            .iter()
+            .map(|issue| (issue.message.as_str(), issue.line))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            reported,
+            [
+                (
+                    "Variant 'customer' hides '#sec:internal', which visible content references",
+                    Some(3)
+                ),
+                (
+                    "Variant 'customer' hides '#fig:plan', which visible content references",
+                    Some(3)
+                ),
+                (
+                    "Variant 'customer' hides '#pricing', which visible content references",
+                    Some(3)
+                ),
+                (
+                    "Variant 'customer' hides '#sec:internal', which visible content references",
+                    Some(15)
+                ),
+                (
+                    "Variant 'customer' hides '#fig-rack', which visible content references",
+                    Some(25)
+                ),
+                (
+                    "Variant 'internal' hides '#pricing', which visible content references",
+                    Some(3)
+                ),
+                (
+                    "Variant 'partner' hides '#sec:internal', which visible content references",
+                    Some(3)
+                ),
+                (
+                    "Variant 'partner' hides '#fig:plan', which visible content references",
+                    Some(3)
+                ),
+                (
+                    "Variant 'partner' hides '#sec:internal', which visible content references",
+                    Some(15)
+                ),
+                (
+                    "Variant 'partner' hides '#fig:plan', which visible content references",
+                    Some(16)
+                ),
+                (
+                    "Variant 'partner' hides '#fig-rack', which visible content references",
+                    Some(25)
+                ),
+            ],
+            "{issues:#?}"
+        );
+        assert!(
+            lint_variants(project.path(), &MergedConfig::default(), &BTreeMap::new()).is_empty()
+        );
+    }
+
     #[test]
     fn shared_resources_invalidate_cache_and_lock_uses_portable_digests() {
         let project = tempfile::tempdir().expect("project tempdir");
@@ -3474,6 +5756,8 @@ user@example.com
             config: &config,
             artifact: &artifact,
             compatibility: None,
+            xml_validation: None,
+            pdf: None,
             issues: Vec::new(),
         });
 
@@ -3536,6 +5820,95 @@ user@example.com
         );
     }
 
+    #[test]
+    fn documents_get_namespaced_lock_targets_cache_records_and_scoped_checks() {
+        let project = tempfile::tempdir().expect("project");
+        fs::write(project.path().join("manual.md"), "# Manual\n").expect("manual");
+        fs::write(project.path().join("guide.md"), "# Guide\n").expect("guide");
+        let manual = MergedConfig {
+            entry: Some("manual.md".to_string()),
+            to: Some("html".to_string()),
+            document: Some("manual".to_string()),
+            ..Default::default()
+        };
+        let guide = MergedConfig {
+            entry: Some("guide.md".to_string()),
+            to: Some("html".to_string()),
+            document: Some("guide".to_string()),
+            ..Default::default()
+        };
+        let manual_graph = dependency_graph(project.path(), &manual);
+        let guide_graph = dependency_graph(project.path(), &guide);
+        let inputs = [
+            LockTargetInput {
+                output: "html",
+                config: &manual,
+                graph: &manual_graph,
+            },
+            LockTargetInput {
+                output: "HTML",
+                config: &guide,
+                graph: &guide_graph,
+            },
+        ];
+
+        write_lock_targets(project.path(), &inputs).expect("document lock");
+        let lock: LockFile =
+            toml::from_str(&fs::read_to_string(project.path().join("omnidoc.lock")).expect("lock"))
+                .expect("parse lock");
+        assert_eq!(
+            lock.targets.keys().cloned().collect::<Vec<_>>(),
+            ["guide/html", "manual/html"]
+        );
+
+        fs::write(project.path().join("guide.md"), "# Guide, revised\n").expect("edit guide");
+        let guide_graph = dependency_graph(project.path(), &guide);
+        let inputs = [
+            LockTargetInput {
+                output: "html",
+                config: &manual,
+                graph: &manual_graph,
+            },
+            LockTargetInput {
+                output: "html",
+                config: &guide,
+                graph: &guide_graph,
+            },
+        ];
+        let manual_status = check_document_lock_targets(project.path(), &inputs, Some("manual"))
+            .expect("manual lock status");
+        assert!(manual_status.up_to_date);
+        assert_eq!(
+            manual_status.targets.keys().cloned().collect::<Vec<_>>(),
+            ["manual/html"]
+        );
+        assert!(
+            !check_lock_targets(project.path(), &inputs)
+                .expect("project lock status")
+                .up_to_date
+        );
+
+        assert_eq!(document_output_key(None, "PDF"), "pdf");
+        assert_eq!(
+            cache_path(project.path(), &document_output_key(Some("guide"), "html")),
+            project
+                .path()
+                .join(".omnidoc-cache/documents/guide/build-html.json")
+        );
+        assert_eq!(
+            cache_path(project.path(), "guide/html@customer"),
+            project
+                .path()
+                .join(".omnidoc-cache/documents/guide/variants/customer/build-html.json")
+        );
+        assert_eq!(
+            output_scratch_dir(project.path(), &guide, PandocOutputKind::Html),
+            project
+                .path()
+                .join(".omnidoc-cache/documents/guide/outputs/html")
+        );
+    }
+
     #[test]
     fn validates_unsupported_build_outputs() {
         let config = MergedConfig {
@@ -2,8 +2,83 @@
 
 use crate::error::OmniDocError;
 use console::{colors_enabled_stderr, style};
+use std::cell::RefCell;
 use std::io::{self, Write};
 
+thread_local! {
+    static CAPTURE: RefCell<Option<CapturedOutput>> = const { RefCell::new(None) };
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Stream {
+    Stdout,
+    Stderr,
+}
+
+/// Terminal output buffered while a parallel build job runs.
+#[derive(Debug, Default)]
+pub struct CapturedOutput {
+    chunks: Vec<(Stream, String)>,
+}
+
+impl CapturedOutput {
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    /// Replay the buffered output in its original order while holding both
+    /// terminal locks, so one job's block is never interleaved with another.
+    pub fn replay(&self) {
+        let mut stdout = io::stdout().lock();
+        let mut stderr = io::stderr().lock();
+        for (stream, text) in &self.chunks {
+            let _ = match stream {
+                Stream::Stdout => stdout.write_all(text.as_bytes()).and(stdout.flush()),
+                Stream::Stderr => stderr.write_all(text.as_bytes()).and(stderr.flush()),
+            };
+        }
+    }
+}
+
+/// Run `job` on the current thread with every message printed through this
+/// module buffered instead of written to the terminal.
+pub fn capture_output<T>(job: impl FnOnce() -> T) -> (T, CapturedOutput) {
+    let previous = CAPTURE.with(|capture| capture.replace(Some(CapturedOutput::default())));
+    let result = job();
+    let captured = CAPTURE.with(|capture| capture.replace(previous));
+    (result, captured.unwrap_or_default())
+}
+
+/// Print build progress to stdout, or into the active capture buffer.
+pub fn out(text: impl AsRef<str>) {
+    emit(Stream::Stdout, text.as_ref());
+}
+
+/// Print tool diagnostics to stderr, or into the active capture buffer.
+pub fn err(text: impl AsRef<str>) {
+    emit(Stream::Stderr, text.as_ref());
+}
+
+fn emit(stream: Stream, text: &str) {
+    let captured = CAPTURE.with(|capture| {
+        let mut capture = capture.borrow_mut();
+        let Some(buffer) = capture.as_mut() else {
+            return false;
+        };
+        match buffer.chunks.last_mut() {
+            Some((last, previous)) if *last == stream => previous.push_str(text),
+            _ => buffer.chunks.push((stream, text.to_string())),
+        }
+        true
+    });
+    if !captured {
+        let _ = match stream {
+            Stream::Stdout => io::stdout().lock().write_all(text.as_bytes()),
+            Stream::Stderr => io::stderr().lock().write_all(text.as_bytes()),
+        };
+    }
+}
+
 #[derive(Clone, Copy)]
 enum Level {
     Error,
@@ -26,28 +101,35 @@ pub fn print_error(error: &OmniDocError) {
     let _ = write_error(&mut io::stderr().lock(), error, colors_enabled_stderr());
 }
 
+/// Render a fatal error like [`print_error`], without colors, for display
+/// outside the terminal such as the live-preview overlay.
+pub fn render_error(error: &OmniDocError) -> String {
+    let mut rendered = Vec::new();
+    let _ = write_error(&mut rendered, error, false);
+    String::from_utf8_lossy(&rendered).into_owned()
+}
+
 /// Print a recoverable warning to stderr.
 pub fn warning(message: impl AsRef<str>) {
-    let _ = write_diagnostic(
-        &mut io::stderr().lock(),
-        Level::Warning,
-        None,
-        message.as_ref(),
-        None,
-        colors_enabled_stderr(),
-    );
+    print_diagnostic(Level::Warning, message.as_ref());
 }
 
 /// Print a quiet informational diagnostic to stderr.
 pub fn info(message: impl AsRef<str>) {
+    print_diagnostic(Level::Info, message.as_ref());
+}
+
+fn print_diagnostic(level: Level, message: &str) {
+    let mut rendered = Vec::new();
     let _ = write_diagnostic(
-        &mut io::stderr().lock(),
-        Level::Info,
+        &mut rendered,
+        level,
         None,
-        message.as_ref(),
+        message,
         None,
         colors_enabled_stderr(),
     );
+    err(String::from_utf8_lossy(&rendered));
 }
 
 fn write_error(writer: &mut impl Write, error: &OmniDocError, color: bool) -> io::Result<()> {
@@ -152,6 +234,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn captured_output_keeps_stream_order_per_job() {
+        let ((), captured) = capture_output(|| {
+            out("Executing: pandoc\n");
+            out("✓ Built HTML\n");
+            err("[WARNING] missing alt text\n");
+            out("done\n");
+        });
+
+        assert_eq!(
+            captured.chunks,
+            vec![
+                (
+                    Stream::Stdout,
+                    "Executing: pandoc\n✓ Built HTML\n".to_string()
+                ),
+                (Stream::Stderr, "[WARNING] missing alt text\n".to_string()),
+                (Stream::Stdout, "done\n".to_string()),
+            ]
+        );
+        let ((), nested_free) = capture_output(|| {});
+        assert!(nested_free.is_empty());
+    }
+
     #[test]
     fn contextual_help_is_rendered_last() {
         let error = OmniDocError::NotOmniDocProject("No .omnidoc.toml was found".to_string());
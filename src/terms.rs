@@ -0,0 +1,367 @@
+//! Project terminology dictionary and offline spell checking.
+//!
+//! The dictionary is a TOML file (`terms.toml` by default) listing the
+//! preferred spelling of each term, the variants the style guide forbids,
+//! and optionally the language the rule belongs to:
+//!
+//! ```toml
+//! words = ["omnidoc", "Pandoc"]
+//!
+//! [[terms]]
+//! preferred = "Wi-Fi"
+//! forbidden = ["WiFi", "Wifi", "wifi"]
+//!
+//! [[terms]]
+//! preferred = "服务器"
+//! forbidden = ["伺服器"]
+//! lang = "zh"
+//! ```
+//!
+//! Forbidden variants match case-sensitively, so `forbidden` lists every
+//! spelling to reject. Preferred terms and `words` are also accepted by the
+//! Hunspell spell checker, which runs in its `-a` pipe mode.
+
+use crate::config::MergedConfig;
+use crate::error::{OmniDocError, Result};
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Dictionary file used when `[terms] dictionary` is not configured.
+pub const DEFAULT_DICTIONARY: &str = "terms.toml";
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TermsDictionary {
+    #[serde(default)]
+    pub terms: Vec<Term>,
+    /// Extra words the spell checker accepts, such as product names.
+    #[serde(default)]
+    pub words: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Term {
+    pub preferred: String,
+    #[serde(default)]
+    pub forbidden: Vec<String>,
+    /// Language the rule applies to (`zh` also covers `zh-CN`); all languages when unset.
+    pub lang: Option<String>,
+}
+
+/// A forbidden variant found in a line of prose.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TermMatch<'a> {
+    pub found: &'a str,
+    pub preferred: &'a str,
+}
+
+/// A word Hunspell does not know, with the index of the line it was found in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Misspelling {
+    pub line: usize,
+    pub word: String,
+    pub suggestions: Vec<String>,
+}
+
+/// 项目实际使用的术语词典：配置的路径，或存在时的默认 `terms.toml`
+pub fn dictionary_path(project_path: &Path, config: &MergedConfig) -> Option<PathBuf> {
+    match config.terms_dictionary.as_deref() {
+        Some(configured) => Some(project_path.join(configured)),
+        None => Some(project_path.join(DEFAULT_DICTIONARY)).filter(|path| path.is_file()),
+    }
+}
+
+impl TermsDictionary {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Self::parse(&content)
+            .map_err(|message| OmniDocError::Config(format!("{}: {message}", path.display())))
+    }
+
+    pub fn parse(content: &str) -> std::result::Result<Self, String> {
+        let dictionary: Self = toml::from_str(content).map_err(|error| error.to_string())?;
+        for term in &dictionary.terms {
+            if term.preferred.trim().is_empty() {
+                return Err("terms entries need a non-empty `preferred` spelling".to_string());
+            }
+            if term
+                .forbidden
+                .iter()
+                .any(|variant| variant.trim().is_empty())
+            {
+                return Err(format!(
+                    "term '{}' has an empty forbidden variant",
+                    term.preferred
+                ));
+            }
+        }
+        Ok(dictionary)
+    }
+
+    /// Terms that apply to a document in `lang`; every term when the language is unknown.
+    pub fn terms_for(&self, lang: Option<&str>) -> Vec<&Term> {
+        self.terms
+            .iter()
+            .filter(|term| match (term.lang.as_deref(), lang) {
+                (Some(term_lang), Some(lang)) => language_matches(term_lang, lang),
+                _ => true,
+            })
+            .collect()
+    }
+
+    /// Words the spell checker should accept: `words` plus every preferred
+    /// term, whole and split into the pieces Hunspell tokenizes.
+    pub fn accepted_words(&self) -> BTreeSet<String> {
+        let mut accepted = BTreeSet::new();
+        for word in self
+            .words
+            .iter()
+            .chain(self.terms.iter().map(|term| &term.preferred))
+        {
+            accepted.insert(word.clone());
+            accepted.extend(
+                word.split(|character: char| !character.is_alphanumeric() && character != '\'')
+                    .filter(|piece| !piece.is_empty())
+                    .map(str::to_string),
+            );
+        }
+        accepted
+    }
+}
+
+/// `zh` matches `zh`, `zh-CN` and `zh_TW`; comparison ignores case.
+fn language_matches(term_lang: &str, lang: &str) -> bool {
+    let term_lang = term_lang.trim().to_ascii_lowercase();
+    let lang = lang.trim().to_ascii_lowercase().replace('_', "-");
+    lang == term_lang
+        || lang
+            .strip_prefix(&term_lang)
+            .is_some_and(|rest| rest.starts_with('-'))
+}
+
+/// 在一行正文中查找被禁止的术语写法
+pub fn forbidden_terms<'a>(line: &str, terms: &[&'a Term]) -> Vec<TermMatch<'a>> {
+    let mut matches = Vec::new();
+    for term in terms {
+        let preferred: Vec<(usize, usize)> = line
+            .match_indices(term.preferred.as_str())
+            .map(|(start, found)| (start, start + found.len()))
+            .collect();
+        for variant in &term.forbidden {
+            for (start, found) in line.match_indices(variant.as_str()) {
+                let end = start + found.len();
+                let inside_preferred = preferred
+                    .iter()
+                    .any(|(from, to)| *from <= start && end <= *to);
+                if inside_preferred || !on_word_boundary(line, start, end) {
+                    continue;
+                }
+                matches.push((
+                    start,
+                    TermMatch {
+                        found: variant,
+                        preferred: &term.preferred,
+                    },
+                ));
+            }
+        }
+    }
+    matches.sort_by_key(|(start, _)| *start);
+    matches.into_iter().map(|(_, found)| found).collect()
+}
+
+/// ASCII words must not continue past the match (`WiFi` is not in `WiFis`);
+/// CJK text has no word separators, so it always matches.
+fn on_word_boundary(line: &str, start: usize, end: usize) -> bool {
+    let is_word = |character: char| character.is_ascii_alphanumeric() || character == '_';
+    let matched = &line[start..end];
+    let before = line[..start].chars().next_back();
+    let after = line[end..].chars().next();
+    let starts_word = matched.chars().next().is_some_and(is_word);
+    let ends_word = matched.chars().next_back().is_some_and(is_word);
+    !(starts_word && before.is_some_and(is_word) || ends_word && after.is_some_and(is_word))
+}
+
+/// Run `hunspell -a` over `lines` and collect the words it rejects. CJK text
+/// is blanked first because Hunspell dictionaries cover alphabetic scripts.
+pub fn spellcheck(
+    hunspell: &str,
+    dictionaries: &[String],
+    accepted: &BTreeSet<String>,
+    lines: &[String],
+) -> Result<Vec<Misspelling>> {
+    let mut input = String::new();
+    for line in lines {
+        // `^` keeps Hunspell from reading a line as a pipe-mode command.
+        input.push('^');
+        input.extend(
+            line.chars()
+                .map(|character| if is_cjk(character) { ' ' } else { character }),
+        );
+        input.push('\n');
+    }
+
+    let mut child = Command::new(hunspell)
+        .args(["-a", "-i", "utf-8", "-d", &dictionaries.join(",")])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|error| OmniDocError::Other(format!("Failed to run {hunspell}: {error}")))?;
+    let mut stdin = child.stdin.take().expect("hunspell stdin");
+    // Write from another thread so a large report cannot fill the stdout pipe
+    // while Hunspell is still waiting for input.
+    let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let output = child.wait_with_output()?;
+    writer
+        .join()
+        .map_err(|_| OmniDocError::Other("hunspell input writer panicked".to_string()))??;
+    if !output.status.success() {
+        return Err(OmniDocError::Other(format!(
+            "{hunspell} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(parse_pipe_output(&String::from_utf8_lossy(&output.stdout))
+        .into_iter()
+        .filter(|misspelling| !accepted.contains(&misspelling.word))
+        .collect())
+}
+
+/// Parse Ispell pipe-mode output: a version banner, then one block of
+/// results per input line, each block terminated by an empty line.
+fn parse_pipe_output(output: &str) -> Vec<Misspelling> {
+    let mut misspellings = Vec::new();
+    let mut line = 0;
+    for result in output.lines().skip(1) {
+        if result.trim().is_empty() {
+            line += 1;
+            continue;
+        }
+        let mut fields = result.splitn(3, ' ');
+        let (Some(kind), Some(word)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        let suggestions = match kind {
+            "&" | "?" => fields
+                .next()
+                .and_then(|rest| rest.split_once(": "))
+                .map(|(_, suggestions)| {
+                    suggestions
+                        .split(", ")
+                        .map(|suggestion| suggestion.trim().to_string())
+                        .filter(|suggestion| !suggestion.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            "#" => Vec::new(),
+            _ => continue,
+        };
+        misspellings.push(Misspelling {
+            line,
+            word: word.to_string(),
+            suggestions,
+        });
+    }
+    misspellings
+}
+
+fn is_cjk(character: char) -> bool {
+    matches!(
+        character,
+        '\u{2E80}'..='\u{303F}'
+            | '\u{3040}'..='\u{30FF}'
+            | '\u{3100}'..='\u{31FF}'
+            | '\u{3400}'..='\u{4DBF}'
+            | '\u{4E00}'..='\u{9FFF}'
+            | '\u{AC00}'..='\u{D7AF}'
+            | '\u{F900}'..='\u{FAFF}'
+            | '\u{FE30}'..='\u{FE4F}'
+            | '\u{FF00}'..='\u{FFEF}'
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{forbidden_terms, parse_pipe_output, Misspelling, TermsDictionary};
+
+    #[test]
+    fn forbidden_variants_respect_word_boundaries_and_languages() {
+        let dictionary = TermsDictionary::parse(concat!(
+            "words = [\"omnidoc\"]\n",
+            "[[terms]]\n",
+            "preferred = \"Wi-Fi\"\n",
+            "forbidden = [\"WiFi\", \"wifi\"]\n",
+            "[[terms]]\n",
+            "preferred = \"USB Type-C\"\n",
+            "forbidden = [\"Type-C\", \"USB-C\"]\n",
+            "[[terms]]\n",
+            "preferred = \"服务器\"\n",
+            "forbidden = [\"伺服器\"]\n",
+            "lang = \"zh\"\n",
+        ))
+        .expect("dictionary");
+
+        let all = dictionary.terms_for(None);
+        let found = |line: &str| {
+            forbidden_terms(line, &all)
+                .into_iter()
+                .map(|found| (found.found, found.preferred))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            found("Connect over WiFi or a Type-C cable, not WiFis."),
+            vec![("WiFi", "Wi-Fi"), ("Type-C", "USB Type-C")]
+        );
+        assert!(found("Use a USB Type-C cable and Wi-Fi.").is_empty());
+        assert_eq!(
+            found("连接wifi到伺服器"),
+            vec![("wifi", "Wi-Fi"), ("伺服器", "服务器")]
+        );
+
+        let english = dictionary.terms_for(Some("en-US"));
+        assert!(forbidden_terms("伺服器", &english).is_empty());
+        assert_eq!(dictionary.terms_for(Some("zh_CN")).len(), 3);
+
+        let accepted = dictionary.accepted_words();
+        for word in ["omnidoc", "Wi-Fi", "Wi", "Fi", "Type", "USB"] {
+            assert!(accepted.contains(word), "{word}");
+        }
+        assert!(TermsDictionary::parse("[[terms]]\npreferred = \"\"\n").is_err());
+        assert!(TermsDictionary::parse("[[terms]]\npreferred = \"A\"\nbanned = []\n").is_err());
+    }
+
+    #[test]
+    fn parses_hunspell_pipe_mode_output() {
+        let output = concat!(
+            "@(#) International Ispell Version 3.2.06 (but really Hunspell 1.7.2)\n",
+            "*\n",
+            "& teh 3 0: the, tech, eh\n",
+            "\n",
+            "\n",
+            "# qwzx 4\n",
+            "*\n",
+            "\n",
+        );
+        assert_eq!(
+            parse_pipe_output(output),
+            vec![
+                Misspelling {
+                    line: 0,
+                    word: "teh".to_string(),
+                    suggestions: vec!["the".to_string(), "tech".to_string(), "eh".to_string()],
+                },
+                Misspelling {
+                    line: 2,
+                    word: "qwzx".to_string(),
+                    suggestions: Vec::new(),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,222 @@
+//! 长驻进程的预热缓存
+//!
+//! One-shot commands merge configuration, resolve the selected theme and
+//! plugins (including trust checks) and look up external tools from scratch.
+//! `omnidoc daemon` enables the memo tables below so repeated requests reuse
+//! those results. Every entry records a stat fingerprint (path, length,
+//! modification time) of the files it was derived from and is reloaded as soon
+//! as one of them changes. The tables stay disabled in ordinary CLI runs.
+
+use crate::config::project::PROJECT_CONFIG_FILE;
+use crate::config::ConfigManager;
+use crate::extensions::{ResolvedPlugin, ResolvedTheme};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex, PoisonError};
+use std::time::SystemTime;
+use walkdir::WalkDir;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+pub(crate) static CONFIGS: LazyLock<MemoTable<ConfigManager>> = LazyLock::new(MemoTable::new);
+pub(crate) static THEMES: LazyLock<MemoTable<Option<ResolvedTheme>>> =
+    LazyLock::new(MemoTable::new);
+pub(crate) static PLUGINS: LazyLock<MemoTable<Vec<ResolvedPlugin>>> = LazyLock::new(MemoTable::new);
+pub(crate) static TOOLS: LazyLock<MemoTable<String>> = LazyLock::new(MemoTable::new);
+
+/// 启用预热缓存（仅长驻进程调用）
+pub fn enable() {
+    ENABLED.store(true, Ordering::SeqCst);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::SeqCst)
+}
+
+/// 丢弃所有预热条目，例如在原地升级工具之后
+pub fn clear() {
+    CONFIGS.clear();
+    THEMES.clear();
+    PLUGINS.clear();
+    TOOLS.clear();
+}
+
+/// Hit/miss counters of every memo table.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WarmStats {
+    pub configs: MemoStats,
+    pub themes: MemoStats,
+    pub plugins: MemoStats,
+    pub tools: MemoStats,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct MemoStats {
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+pub fn stats() -> WarmStats {
+    WarmStats {
+        configs: CONFIGS.stats(),
+        themes: THEMES.stats(),
+        plugins: PLUGINS.stats(),
+        tools: TOOLS.stats(),
+    }
+}
+
+/// Cheap identity of the inputs a memo entry was derived from.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Fingerprint(Vec<(String, Option<(u64, SystemTime)>)>);
+
+impl Fingerprint {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Include a value that is not backed by a file, such as `PATH`.
+    pub(crate) fn with_value(mut self, value: impl Into<String>) -> Self {
+        self.0.push((value.into(), None));
+        self
+    }
+
+    /// Include the length and modification time of `path`; a missing file is
+    /// recorded too, so creating it later invalidates the entry.
+    pub(crate) fn with_file(mut self, path: &Path) -> Self {
+        self.0
+            .push((path.to_string_lossy().to_string(), file_stamp(path)));
+        self
+    }
+
+    /// Include every file and directory below `root`, which catches packages
+    /// installed, removed or edited in an extension store.
+    pub(crate) fn with_tree(mut self, root: &Path) -> Self {
+        self = self.with_file(root);
+        for entry in WalkDir::new(root)
+            .min_depth(1)
+            .sort_by_file_name()
+            .into_iter()
+            .flatten()
+        {
+            self = self.with_file(entry.path());
+        }
+        self
+    }
+}
+
+fn file_stamp(path: &Path) -> Option<(u64, SystemTime)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.len(), metadata.modified().ok()?))
+}
+
+/// One memoized lookup, keyed by a caller-defined string.
+pub(crate) struct MemoTable<T> {
+    entries: Mutex<HashMap<String, (Fingerprint, T)>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl<T: Clone> MemoTable<T> {
+    fn new() -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Return the entry for `key` if its fingerprint still matches, otherwise
+    /// run `load` and remember a successful result. When the cache is disabled
+    /// this is exactly `load()`.
+    pub(crate) fn get_or_load<E>(
+        &self,
+        key: &str,
+        fingerprint: impl FnOnce() -> Fingerprint,
+        load: impl FnOnce() -> Result<T, E>,
+    ) -> Result<T, E> {
+        if !is_enabled() {
+            return load();
+        }
+        self.load_memoized(key, fingerprint(), load)
+    }
+
+    fn load_memoized<E>(
+        &self,
+        key: &str,
+        fingerprint: Fingerprint,
+        load: impl FnOnce() -> Result<T, E>,
+    ) -> Result<T, E> {
+        if let Some((_, value)) = self
+            .lock()
+            .get(key)
+            .filter(|(stored, _)| stored == &fingerprint)
+        {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(value.clone());
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let value = load()?;
+        self.lock()
+            .insert(key.to_string(), (fingerprint, value.clone()));
+        Ok(value)
+    }
+
+    pub(crate) fn invalidate(&self, key: &str) {
+        self.lock().remove(key);
+    }
+
+    fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn stats(&self) -> MemoStats {
+        MemoStats {
+            entries: self.lock().len(),
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, (Fingerprint, T)>> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// `.omnidoc.toml` candidates consulted when loading the project at `start`.
+pub(crate) fn project_config_candidates(start: &Path) -> Vec<PathBuf> {
+    start
+        .ancestors()
+        .map(|ancestor| ancestor.join(PROJECT_CONFIG_FILE))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_reload_when_a_fingerprinted_file_changes() {
+        let temp = tempfile::tempdir().unwrap();
+        let input = temp.path().join("input.toml");
+        std::fs::write(&input, "a").unwrap();
+        let table = MemoTable::<String>::new();
+
+        let load = |table: &MemoTable<String>| {
+            table
+                .load_memoized("key", Fingerprint::new().with_file(&input), || {
+                    std::fs::read_to_string(&input)
+                })
+                .unwrap()
+        };
+        assert_eq!(load(&table), "a");
+        assert_eq!(load(&table), "a");
+        std::fs::write(&input, "bb").unwrap();
+        assert_eq!(load(&table), "bb");
+
+        let stats = table.stats();
+        assert_eq!((stats.entries, stats.hits, stats.misses), (1, 1, 2));
+    }
+}
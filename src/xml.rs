@@ -0,0 +1,307 @@
+use crate::error::{OmniDocError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::Path;
+
+const XLINK_NAMESPACE: &str = "http://www.w3.org/1999/xlink";
+const XML_NAMESPACE: &str = "http://www.w3.org/XML/1998/namespace";
+const DOCBOOK_NAMESPACE: &str = "http://docbook.org/ns/docbook";
+
+/// Structural validation of a JATS or DocBook artifact.
+///
+/// This is not a DTD or RELAX NG validation: it checks well-formedness and
+/// the elements publishers reject a submission without, and that every
+/// referenced figure was extracted beside the XML.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct XmlValidationReport {
+    pub schema: String,
+    pub valid: bool,
+    pub checks: Vec<XmlValidationCheck>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct XmlValidationCheck {
+    pub name: String,
+    pub passed: bool,
+    pub detail: String,
+}
+
+/// 按输出格式（`jats` 或 `docbook`）校验 XML 产物结构
+pub fn validate_publisher_xml(path: &Path, format: &str) -> Result<XmlValidationReport> {
+    let schema = match format {
+        "jats" => "jats-archiving",
+        "docbook" => "docbook5",
+        _ => {
+            return Err(OmniDocError::Config(format!(
+                "Unsupported XML validation format '{}'. Supported formats: jats, docbook",
+                format
+            )))
+        }
+    };
+    let text = std::fs::read_to_string(path).map_err(OmniDocError::Io)?;
+    let base = path.parent().unwrap_or_else(|| Path::new(""));
+    let mut checks = Vec::new();
+    // Pandoc writes a DOCTYPE for JATS; it is declared, never fetched.
+    let options = roxmltree::ParsingOptions {
+        allow_dtd: true,
+        ..roxmltree::ParsingOptions::default()
+    };
+    match roxmltree::Document::parse_with_options(&text, options) {
+        Ok(document) => {
+            push_check(
+                &mut checks,
+                "well-formed",
+                true,
+                "the document parses as XML",
+            );
+            if format == "jats" {
+                check_jats(&document, base, &mut checks);
+            } else {
+                check_docbook(&document, base, &mut checks);
+            }
+        }
+        Err(error) => push_check(
+            &mut checks,
+            "well-formed",
+            false,
+            format!("the document is not well-formed: {error}"),
+        ),
+    }
+    Ok(XmlValidationReport {
+        schema: schema.to_string(),
+        valid: checks.iter().all(|check| check.passed),
+        checks,
+    })
+}
+
+fn check_jats(
+    document: &roxmltree::Document<'_>,
+    base: &Path,
+    checks: &mut Vec<XmlValidationCheck>,
+) {
+    let root = document.root_element();
+    push_check(
+        checks,
+        "root-element",
+        root.has_tag_name("article"),
+        "the root element is <article>",
+    );
+    let front = root.children().find(|node| node.has_tag_name("front"));
+    let title = front
+        .and_then(|front| {
+            front
+                .descendants()
+                .find(|node| node.has_tag_name("article-title"))
+        })
+        .map(|node| element_text(&node));
+    push_check(
+        checks,
+        "article-title",
+        title.is_some_and(|title| !title.is_empty()),
+        "<front> carries a non-empty <article-title>",
+    );
+    push_check(
+        checks,
+        "body",
+        root.children().any(|node| node.has_tag_name("body")),
+        "the article has a <body>",
+    );
+    push_unique_ids(checks, document, |node| node.attribute("id"));
+    push_figure_assets(checks, document, base, |node| {
+        (node.has_tag_name("graphic") || node.has_tag_name("inline-graphic"))
+            .then(|| node.attribute((XLINK_NAMESPACE, "href")))
+            .flatten()
+    });
+}
+
+fn check_docbook(
+    document: &roxmltree::Document<'_>,
+    base: &Path,
+    checks: &mut Vec<XmlValidationCheck>,
+) {
+    let root = document.root_element();
+    let docbook_root = root.tag_name().namespace() == Some(DOCBOOK_NAMESPACE)
+        && matches!(root.tag_name().name(), "article" | "book")
+        && root
+            .attribute("version")
+            .is_some_and(|version| version.starts_with('5'));
+    push_check(
+        checks,
+        "root-element",
+        docbook_root,
+        "the root element is a DocBook 5 <article> or <book>",
+    );
+    let title = root
+        .children()
+        .find(|node| node.has_tag_name("info"))
+        .and_then(|info| info.children().find(|node| node.has_tag_name("title")))
+        .map(|node| element_text(&node));
+    push_check(
+        checks,
+        "info-title",
+        title.is_some_and(|title| !title.is_empty()),
+        "<info> carries a non-empty <title>",
+    );
+    push_unique_ids(checks, document, |node| {
+        node.attribute((XML_NAMESPACE, "id"))
+    });
+    push_figure_assets(checks, document, base, |node| {
+        node.has_tag_name("imagedata")
+            .then(|| node.attribute("fileref"))
+            .flatten()
+    });
+}
+
+fn element_text(node: &roxmltree::Node<'_, '_>) -> String {
+    node.descendants()
+        .filter(|node| node.is_text())
+        .filter_map(|node| node.text())
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+fn push_unique_ids<'a, 'input: 'a>(
+    checks: &mut Vec<XmlValidationCheck>,
+    document: &'a roxmltree::Document<'input>,
+    id: impl Fn(&roxmltree::Node<'a, 'input>) -> Option<&'a str>,
+) {
+    let mut seen = BTreeSet::new();
+    let mut duplicates = BTreeSet::new();
+    for node in document.descendants().filter(|node| node.is_element()) {
+        if let Some(id) = id(&node) {
+            if !seen.insert(id) {
+                duplicates.insert(id);
+            }
+        }
+    }
+    push_check(
+        checks,
+        "unique-ids",
+        duplicates.is_empty(),
+        if duplicates.is_empty() {
+            "element ids are unique".to_string()
+        } else {
+            format!(
+                "duplicate ids: {}",
+                duplicates.into_iter().collect::<Vec<_>>().join(", ")
+            )
+        },
+    );
+}
+
+fn push_figure_assets<'a, 'input: 'a>(
+    checks: &mut Vec<XmlValidationCheck>,
+    document: &'a roxmltree::Document<'input>,
+    base: &Path,
+    reference: impl Fn(&roxmltree::Node<'a, 'input>) -> Option<&'a str>,
+) {
+    let mut missing = BTreeSet::new();
+    for node in document.descendants().filter(|node| node.is_element()) {
+        let Some(target) = reference(&node) else {
+            continue;
+        };
+        if target.contains("://") || target.starts_with("data:") {
+            continue;
+        }
+        if Path::new(target).is_absolute() || !base.join(target).is_file() {
+            missing.insert(target.to_string());
+        }
+    }
+    push_check(
+        checks,
+        "figure-assets",
+        missing.is_empty(),
+        if missing.is_empty() {
+            "figure references resolve to files beside the XML".to_string()
+        } else {
+            format!(
+                "missing figure assets: {}",
+                missing.into_iter().collect::<Vec<_>>().join(", ")
+            )
+        },
+    );
+}
+
+fn push_check(
+    checks: &mut Vec<XmlValidationCheck>,
+    name: &str,
+    passed: bool,
+    detail: impl Into<String>,
+) {
+    checks.push(XmlValidationCheck {
+        name: name.to_string(),
+        passed,
+        detail: detail.into(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::validate_publisher_xml;
+
+    fn failed(report: &super::XmlValidationReport) -> Vec<&str> {
+        report
+            .checks
+            .iter()
+            .filter(|check| !check.passed)
+            .map(|check| check.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn jats_articles_need_a_title_a_body_and_their_figures() {
+        let directory = tempfile::tempdir().expect("temporary XML");
+        std::fs::create_dir_all(directory.path().join("paper-jats-media")).expect("media");
+        std::fs::write(directory.path().join("paper-jats-media/flow.svg"), "<svg/>")
+            .expect("figure");
+        let xml = directory.path().join("paper-jats.xml");
+        std::fs::write(
+            &xml,
+            r#"<?xml version="1.0" encoding="utf-8" ?>
+<!DOCTYPE article PUBLIC "-//NLM//DTD JATS (Z39.96) Journal Archiving and Interchange DTD v1.2 20190208//EN" "JATS-archivearticle1.dtd">
+<article xmlns:xlink="http://www.w3.org/1999/xlink">
+<front><article-meta><title-group><article-title>Boards</article-title></title-group></article-meta></front>
+<body><fig id="fig-flow"><graphic xlink:href="paper-jats-media/flow.svg" /></fig></body>
+</article>"#,
+        )
+        .expect("JATS");
+
+        let report = validate_publisher_xml(&xml, "jats").expect("validate JATS");
+        assert!(report.valid, "{:#?}", report.checks);
+        assert_eq!(report.schema, "jats-archiving");
+
+        std::fs::write(
+            &xml,
+            r#"<article xmlns:xlink="http://www.w3.org/1999/xlink"><front/>
+<body><p id="a"/><p id="a"/><graphic xlink:href="gone.png"/></body></article>"#,
+        )
+        .expect("broken JATS");
+        let report = validate_publisher_xml(&xml, "jats").expect("validate JATS");
+        assert_eq!(
+            failed(&report),
+            ["article-title", "unique-ids", "figure-assets"]
+        );
+    }
+
+    #[test]
+    fn docbook_requires_a_namespaced_root_and_stops_at_malformed_xml() {
+        let directory = tempfile::tempdir().expect("temporary XML");
+        let xml = directory.path().join("guide-docbook.xml");
+        std::fs::write(
+            &xml,
+            r#"<article xmlns="http://docbook.org/ns/docbook" version="5.0"><info><title>Guide</title></info><para/></article>"#,
+        )
+        .expect("DocBook");
+        assert!(
+            validate_publisher_xml(&xml, "docbook")
+                .expect("validate DocBook")
+                .valid
+        );
+
+        std::fs::write(&xml, "<article><info><title>Guide</info></article>").expect("DocBook");
+        let report = validate_publisher_xml(&xml, "docbook").expect("validate DocBook");
+        assert_eq!(failed(&report), ["well-formed"]);
+        assert_eq!(report.checks.len(), 1);
+    }
+}
@@ -2483,3 +2483,2650 @@ formats = ["html"]
         before
     );
 }
+
+#[test]
+fn parallel_builds_use_per_output_scratch_dirs_and_write_one_report() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let fixture = Fixture::new("parallel-build-jobs");
+    let active = fixture.base().join("active");
+    let overlap_log = fixture.base().join("overlap.log");
+    fs::create_dir_all(&active).expect("active marker dir");
+    let fake_pandoc = fixture.base().join("pandoc");
+    fs::write(
+        &fake_pandoc,
+        format!(
+            r#"#!/bin/sh
+out=''
+prev=''
+for arg in "$@"; do
+  case "$arg" in --version) echo 'pandoc 3.1.11'; exit 0 ;; esac
+  if [ "$prev" = '-o' ]; then out="$arg"; fi
+  prev="$arg"
+done
+touch '{active}/'$$
+ls '{active}' | wc -l >> '{log}'
+sleep 1
+rm -f '{active}/'$$
+printf 'artifact\n' > "$out"
+"#,
+            active = active.display(),
+            log = overlap_log.display()
+        ),
+    )
+    .expect("fake pandoc");
+    let mut permissions = fs::metadata(&fake_pandoc)
+        .expect("fake pandoc metadata")
+        .permissions();
+    permissions.set_mode(0o755);
+    fs::set_permissions(&fake_pandoc, permissions).expect("fake pandoc permissions");
+    fs::write(
+        fixture.project.join(".omnidoc.toml"),
+        format!(
+            r#"[project]
+entry = "main.md"
+from = "markdown"
+to = "html"
+target = "smoke"
+
+[build]
+outdir = "build"
+outputs = ["html", "docx"]
+jobs = 2
+
+[tools]
+pandoc = {:?}
+pandoc-crossref = {:?}
+"#,
+            fake_pandoc.to_string_lossy(),
+            fake_pandoc.to_string_lossy()
+        ),
+    )
+    .expect("project config");
+
+    let project = fixture.project_arg();
+    assert_success(fixture.command(&["build", &project, "--all", "--force", "--report"]));
+
+    let overlap = fs::read_to_string(&overlap_log).expect("overlap log");
+    assert!(
+        overlap.lines().any(|count| count.trim() == "2"),
+        "outputs did not build concurrently: {overlap:?}"
+    );
+    assert!(fixture.project.join("build/smoke.html").is_file());
+    assert!(fixture.project.join("build/smoke.docx").is_file());
+    for output in ["html", "docx"] {
+        assert!(fixture
+            .project
+            .join(".omnidoc-cache/outputs")
+            .join(output)
+            .is_dir());
+    }
+    let report: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(fixture.project.join("build/omnidoc-report.json"))
+            .expect("build report"),
+    )
+    .expect("report JSON");
+    let outputs = report["reports"]
+        .as_array()
+        .expect("report entries")
+        .iter()
+        .map(|build| build["output"].as_str().expect("output name"))
+        .collect::<Vec<_>>();
+    assert_eq!(outputs, ["html", "docx"]);
+
+    fs::remove_file(&overlap_log).expect("reset overlap log");
+    assert_success(fixture.command(&["build", &project, "--all", "--force", "--jobs", "1"]));
+    let sequential = fs::read_to_string(&overlap_log).expect("sequential overlap log");
+    assert!(sequential.lines().all(|count| count.trim() == "1"));
+}
+
+#[test]
+fn documents_build_select_report_lock_and_status_per_document() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let fixture = Fixture::new("multi-document-project");
+    let args_log = fixture.base().join("args.log");
+    let fake_pandoc = fixture.base().join("pandoc");
+    fs::write(
+        &fake_pandoc,
+        format!(
+            r#"#!/bin/sh
+out=''
+prev=''
+for arg in "$@"; do
+  case "$arg" in --version) echo 'pandoc 3.1.11'; exit 0 ;; esac
+  if [ "$prev" = '-o' ]; then out="$arg"; fi
+  prev="$arg"
+done
+echo "$@" >> '{log}'
+printf 'artifact\n' > "$out"
+"#,
+            log = args_log.display()
+        ),
+    )
+    .expect("fake pandoc");
+    let mut permissions = fs::metadata(&fake_pandoc)
+        .expect("fake pandoc metadata")
+        .permissions();
+    permissions.set_mode(0o755);
+    fs::set_permissions(&fake_pandoc, permissions).expect("fake pandoc permissions");
+    fs::write(fixture.project.join("manual.md"), "# Manual\n").expect("manual source");
+    fs::write(fixture.project.join("slides.md"), "# Slides\n").expect("slides source");
+    fs::write(
+        fixture.project.join(".omnidoc.toml"),
+        format!(
+            r#"[project]
+entry = "main.md"
+from = "markdown"
+to = "html"
+target = "smoke"
+
+[build]
+outdir = "build"
+
+[[documents]]
+name = "manual"
+entry = "manual.md"
+outputs = ["html"]
+metadata = {{ subtitle = "Operations" }}
+
+[[documents]]
+name = "slides"
+entry = "slides.md"
+to = "docx"
+target = "deck"
+
+[tools]
+pandoc = {:?}
+pandoc-crossref = {:?}
+"#,
+            fake_pandoc.to_string_lossy(),
+            fake_pandoc.to_string_lossy()
+        ),
+    )
+    .expect("project config");
+
+    let project = fixture.project_arg();
+    assert_success(fixture.command(&["build", &project, "--report", "--write-lock"]));
+    assert!(fixture.project.join("build/manual.html").is_file());
+    assert!(fixture.project.join("build/deck.docx").is_file());
+    assert!(fixture
+        .project
+        .join(".omnidoc-cache/documents/manual/build-html.json")
+        .is_file());
+    assert!(fixture
+        .project
+        .join(".omnidoc-cache/documents/slides/outputs/docx")
+        .is_dir());
+    let args = fs::read_to_string(&args_log).expect("pandoc args");
+    assert!(args
+        .lines()
+        .any(|line| line.contains("manual.md") && line.contains("--metadata=subtitle:Operations")));
+
+    let report: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(fixture.project.join("build/omnidoc-report.json"))
+            .expect("build report"),
+    )
+    .expect("report JSON");
+    let builds = report["reports"]
+        .as_array()
+        .expect("report entries")
+        .iter()
+        .map(|build| {
+            (
+                build["document"].as_str().expect("document name"),
+                build["output"].as_str().expect("output name"),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(builds, [("manual", "html"), ("slides", "docx")]);
+
+    let lock: toml::Value = toml::from_str(
+        &fs::read_to_string(fixture.project.join("omnidoc.lock")).expect("lock file"),
+    )
+    .expect("lock TOML");
+    let targets = lock["targets"]
+        .as_table()
+        .expect("lock targets")
+        .keys()
+        .cloned()
+        .collect::<Vec<_>>();
+    assert_eq!(targets, ["manual/html", "slides/docx"]);
+    assert_success(fixture.command(&["check", "lock", &project, "--check", "--doc", "slides"]));
+
+    fs::remove_file(fixture.project.join("build/manual.html")).expect("remove manual");
+    assert_success(fixture.command(&["build", &project, "--doc", "slides"]));
+    assert!(!fixture.project.join("build/manual.html").exists());
+
+    let status: serde_json::Value = serde_json::from_str(&assert_success(
+        fixture.command(&["status", &project, "--json"]),
+    ))
+    .expect("status JSON");
+    let documents = status["documents"].as_array().expect("documents");
+    assert_eq!(documents[0]["name"], "manual");
+    assert_eq!(documents[1]["target"], "deck");
+    assert_eq!(documents[1]["default_output"], "docx");
+
+    let selected: serde_json::Value = serde_json::from_str(&assert_success(
+        fixture.command(&["status", &project, "--doc", "slides", "--json"]),
+    ))
+    .expect("selected status JSON");
+    assert_eq!(selected["document"], "slides");
+    assert_eq!(selected["target"], "deck");
+    assert!(selected.get("documents").is_none());
+
+    let open = fixture.command(&["open", &project, "--print-path"]);
+    assert!(!open.status.success());
+    assert!(String::from_utf8_lossy(&open.stderr).contains("--doc"));
+
+    let unknown = fixture.command(&["build", &project, "--doc", "missing"]);
+    assert!(!unknown.status.success());
+    assert!(String::from_utf8_lossy(&unknown.stderr).contains("manual, slides"));
+}
+
+#[test]
+fn profiles_overlay_config_and_are_recorded_in_reports_and_locks() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let fixture = Fixture::new("build-profiles");
+    let args_log = fixture.base().join("args.log");
+    let fake_pandoc = fixture.base().join("pandoc");
+    fs::write(
+        &fake_pandoc,
+        format!(
+            r#"#!/bin/sh
+out=''
+prev=''
+for arg in "$@"; do
+  case "$arg" in --version) echo 'pandoc 3.1.11'; exit 0 ;; esac
+  if [ "$prev" = '-o' ]; then out="$arg"; fi
+  prev="$arg"
+done
+echo "$@" >> '{log}'
+printf 'artifact\n' > "$out"
+"#,
+            log = args_log.display()
+        ),
+    )
+    .expect("fake pandoc");
+    let mut permissions = fs::metadata(&fake_pandoc)
+        .expect("fake pandoc metadata")
+        .permissions();
+    permissions.set_mode(0o755);
+    fs::set_permissions(&fake_pandoc, permissions).expect("fake pandoc permissions");
+    fs::write(
+        fixture.project.join(".omnidoc.toml"),
+        format!(
+            r#"[project]
+entry = "main.md"
+from = "markdown"
+to = "html"
+target = "smoke"
+
+[build]
+outdir = "build"
+
+[pandoc]
+options = ["--metadata=stage:final"]
+
+[profiles.draft.pandoc]
+options = ["--metadata=stage:draft"]
+
+[profiles.draft.project]
+target = "smoke-draft"
+
+[tools]
+pandoc = {:?}
+pandoc-crossref = {:?}
+"#,
+            fake_pandoc.to_string_lossy(),
+            fake_pandoc.to_string_lossy()
+        ),
+    )
+    .expect("project config");
+
+    let project = fixture.project_arg();
+    assert_success(fixture.command(&[
+        "build",
+        &project,
+        "--profile",
+        "draft",
+        "--report",
+        "--write-lock",
+    ]));
+    assert!(fixture.project.join("build/smoke-draft.html").is_file());
+    let args = fs::read_to_string(&args_log).expect("pandoc args");
+    assert!(args.contains("--metadata=stage:draft"));
+    assert!(!args.contains("--metadata=stage:final"));
+
+    let report: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(fixture.project.join("build/omnidoc-report.json"))
+            .expect("build report"),
+    )
+    .expect("report JSON");
+    assert_eq!(report["reports"][0]["profile"], "draft");
+    let lock: toml::Value = toml::from_str(
+        &fs::read_to_string(fixture.project.join("omnidoc.lock")).expect("lock file"),
+    )
+    .expect("lock TOML");
+    assert_eq!(lock["profile"].as_str(), Some("draft"));
+
+    assert_success(fixture.command(&["check", "lock", &project, "--check", "--profile", "draft"]));
+    let unprofiled = fixture.command(&["check", "lock", &project, "--check"]);
+    assert!(!unprofiled.status.success());
+    assert!(String::from_utf8_lossy(&unprofiled.stdout).contains("\"profile_up_to_date\": false"));
+
+    let unknown = fixture.command(&["build", &project, "--profile", "review"]);
+    assert!(!unknown.status.success());
+    assert!(String::from_utf8_lossy(&unknown.stderr).contains("declared profiles: draft"));
+}
+
+#[test]
+fn variants_filter_audiences_and_cache_separately() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let fixture = Fixture::new("build-variants");
+    let args_log = fixture.base().join("args.log");
+    let fake_pandoc = fixture.base().join("pandoc");
+    fs::write(
+        &fake_pandoc,
+        format!(
+            r#"#!/bin/sh
+out=''
+prev=''
+for arg in "$@"; do
+  case "$arg" in
+    --version) echo 'pandoc 3.1.11'; exit 0 ;;
+    commonmark_x+sourcepos) echo '{{"blocks":[]}}'; exit 0 ;;
+  esac
+  if [ "$prev" = '-o' ]; then out="$arg"; fi
+  prev="$arg"
+done
+echo "$@" >> '{log}'
+printf 'artifact\n' > "$out"
+"#,
+            log = args_log.display()
+        ),
+    )
+    .expect("fake pandoc");
+    let mut permissions = fs::metadata(&fake_pandoc)
+        .expect("fake pandoc metadata")
+        .permissions();
+    permissions.set_mode(0o755);
+    fs::set_permissions(&fake_pandoc, permissions).expect("fake pandoc permissions");
+    fs::write(
+        fixture.project.join(".omnidoc.toml"),
+        format!(
+            r#"[project]
+entry = "main.md"
+from = "markdown"
+to = "html"
+target = "smoke"
+
+[build]
+outdir = "build"
+
+[variants.customer]
+
+[variants.internal]
+audiences = ["internal", "customer"]
+
+[tools]
+pandoc = {:?}
+pandoc-crossref = {:?}
+"#,
+            fake_pandoc.to_string_lossy(),
+            fake_pandoc.to_string_lossy()
+        ),
+    )
+    .expect("project config");
+    fs::write(
+        fixture.project.join("main.md"),
+        "# Guide\n\nSee @sec:notes.\n\n::: only-internal\n## Notes {#sec:notes}\n:::\n",
+    )
+    .expect("main document");
+
+    let project = fixture.project_arg();
+    assert_success(fixture.command(&["build", &project]));
+    assert_success(fixture.command(&["build", &project, "--variant", "customer"]));
+    assert!(fixture.project.join("build/smoke-customer.html").is_file());
+    assert!(fixture
+        .project
+        .join(".omnidoc-cache/build-html.json")
+        .is_file());
+    assert!(fixture
+        .project
+        .join(".omnidoc-cache/variants/customer/build-html.json")
+        .is_file());
+    let args = fs::read_to_string(&args_log).expect("pandoc args");
+    let mut builds = args.lines();
+    let base = builds.next().expect("base build");
+    let customer = builds.next().expect("customer build");
+    assert!(!base.contains("audience.lua"));
+    assert!(!base.contains("omnidoc-variant"));
+    assert!(customer.contains("filters/audience.lua"));
+    assert!(customer.contains("omnidoc-variant=customer"));
+    assert!(customer.contains("omnidoc-audiences=customer"));
+
+    // Each variant keeps its own cache entry, so switching back is a hit.
+    assert_success(fixture.command(&["build", &project, "--variant", "customer"]));
+    assert_success(fixture.command(&["build", &project]));
+    assert_eq!(
+        fs::read_to_string(&args_log)
+            .expect("pandoc args")
+            .lines()
+            .count(),
+        2
+    );
+
+    let lint = fixture.command(&["lint", &project]);
+    let stdout = String::from_utf8_lossy(&lint.stdout);
+    assert!(
+        stdout.contains("Variant 'customer' hides '#sec:notes'"),
+        "{stdout}"
+    );
+    assert!(!stdout.contains("Variant 'internal'"), "{stdout}");
+    let strict = fixture.command(&["build", &project, "--variant", "customer", "--strict"]);
+    assert!(!strict.status.success());
+    assert!(String::from_utf8_lossy(&strict.stderr).contains("Strict mode failed"));
+
+    let unknown = fixture.command(&["build", &project, "--variant", "partner"]);
+    assert!(!unknown.status.success());
+    assert!(
+        String::from_utf8_lossy(&unknown.stderr).contains("declared variants: customer, internal")
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn html_site_builds_a_linked_page_directory_that_opens_and_publishes() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let fixture = Fixture::new("build-html-site");
+    let args_log = fixture.base().join("args.log");
+    let fake_pandoc = fixture.base().join("pandoc");
+    fs::write(
+        &fake_pandoc,
+        format!(
+            r##"#!/bin/sh
+out=''
+prev=''
+for arg in "$@"; do
+  case "$arg" in
+    --version) echo 'pandoc 3.1.11'; exit 0 ;;
+    commonmark_x+sourcepos) echo '{{"blocks":[]}}'; exit 0 ;;
+  esac
+  if [ "$prev" = '-o' ]; then out="$arg"; fi
+  prev="$arg"
+done
+echo "$@" >> '{log}'
+[ -e "$out" ] && {{ echo "$out already exists" >&2; exit 1; }}
+mkdir -p "$out"
+printf '<a href="#sec:api">API</a>\n' > "$out/index.html"
+printf '<h1 id="sec:api">API</h1>\n' > "$out/1-api.html"
+"##,
+            log = args_log.display()
+        ),
+    )
+    .expect("fake pandoc");
+    let mut permissions = fs::metadata(&fake_pandoc)
+        .expect("fake pandoc metadata")
+        .permissions();
+    permissions.set_mode(0o755);
+    fs::set_permissions(&fake_pandoc, permissions).expect("fake pandoc permissions");
+    fs::write(
+        fixture.project.join(".omnidoc.toml"),
+        format!(
+            r#"[project]
+entry = "main.md"
+from = "markdown"
+to = "html-site"
+target = "smoke"
+
+[build]
+outdir = "build"
+
+[pandoc]
+site_split_level = 2
+
+[tools]
+pandoc = {:?}
+pandoc-crossref = {:?}
+"#,
+            fake_pandoc.to_string_lossy(),
+            fake_pandoc.to_string_lossy()
+        ),
+    )
+    .expect("project config");
+
+    let project = fixture.project_arg();
+    assert_success(fixture.command(&["build", &project]));
+    let site = fixture.project.join("build/smoke-site");
+    assert_eq!(
+        fs::read_to_string(site.join("index.html")).expect("index page"),
+        "<a href=\"1-api.html#sec:api\">API</a>\n"
+    );
+    let args = fs::read_to_string(&args_log).expect("pandoc args");
+    assert!(args.contains("-t chunkedhtml"));
+    assert!(args.contains("--split-level=2"));
+    assert!(args.contains("--toc"));
+
+    // A rebuild with unchanged inputs is a cache hit on the site index.
+    assert_success(fixture.command(&["build", &project]));
+    assert_eq!(
+        fs::read_to_string(&args_log)
+            .expect("pandoc args")
+            .lines()
+            .count(),
+        1
+    );
+
+    let open =
+        assert_success(fixture.command(&["open", "--to", "html-site", "--print-path", &project]));
+    assert!(
+        open.trim().ends_with("build/smoke-site/index.html"),
+        "{open}"
+    );
+
+    assert_success(fixture.command(&[
+        "publish",
+        "--to",
+        "html-site",
+        "--no-build",
+        "--tag",
+        "site",
+        &project,
+    ]));
+    let publish_dir = fixture.project.join("dist/site");
+    assert!(publish_dir.join("smoke-site/1-api.html").is_file());
+    let manifest = fs::read_to_string(publish_dir.join("omnidoc-publish.json")).expect("manifest");
+    let manifest: serde_json::Value = serde_json::from_str(&manifest).expect("publish JSON");
+    assert!(manifest["artifacts"]
+        .as_array()
+        .expect("publish artifacts")
+        .iter()
+        .any(
+            |artifact| artifact["destination"] == "smoke-site/index.html"
+                && artifact["source"] == "build/smoke-site/index.html"
+        ));
+    let verified = assert_success(
+        fixture.command(&["publish", "--verify", "--json", "--tag", "site", &project]),
+    );
+    let verified: serde_json::Value =
+        serde_json::from_str(&verified).expect("publish verification JSON");
+    assert_eq!(verified["valid"], true, "{verified}");
+
+    fs::write(publish_dir.join("smoke-site/extra.html"), "extra\n").expect("extra page");
+    let failed = assert_failure(
+        fixture.command(&["publish", "--verify", "--json", "--tag", "site", &project]),
+    );
+    assert!(failed.contains("unexpected file in published release: smoke-site/extra.html"));
+}
+
+#[cfg(unix)]
+#[test]
+fn theme_enabled_search_writes_a_cjk_index_beside_html_output() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let fixture = Fixture::new("build-search");
+    let library = Path::new(env!("CARGO_MANIFEST_DIR")).join("bundles/libs");
+    fs::write(
+        fixture.env_root.join("config/omnidoc.toml"),
+        format!("[lib]\npath = {:?}\n", library.to_string_lossy()),
+    )
+    .expect("global config");
+    let args_log = fixture.base().join("args.log");
+    let fake_pandoc = fixture.base().join("pandoc");
+    fs::write(
+        &fake_pandoc,
+        format!(
+            r##"#!/bin/sh
+out=''
+prev=''
+for arg in "$@"; do
+  case "$arg" in --version) echo 'pandoc 3.1.11'; exit 0 ;; esac
+  if [ "$prev" = '-o' ]; then out="$arg"; fi
+  prev="$arg"
+done
+echo "$@" >> '{log}'
+printf '<html><body><h1 id="intro">简介</h1><p>构建系统</p></body></html>\n' > "$out"
+"##,
+            log = args_log.display()
+        ),
+    )
+    .expect("fake pandoc");
+    let mut permissions = fs::metadata(&fake_pandoc)
+        .expect("fake pandoc metadata")
+        .permissions();
+    permissions.set_mode(0o755);
+    fs::set_permissions(&fake_pandoc, permissions).expect("fake pandoc permissions");
+    let config = |search: &str| {
+        format!(
+            r#"[project]
+entry = "main.md"
+from = "markdown"
+to = "html"
+target = "smoke"
+
+[build]
+outdir = "build"
+
+[theme]
+name = "clean-document"
+{search}
+[tools]
+pandoc = {:?}
+pandoc-crossref = {:?}
+"#,
+            fake_pandoc.to_string_lossy(),
+            fake_pandoc.to_string_lossy()
+        )
+    };
+    fs::write(fixture.project.join(".omnidoc.toml"), config("")).expect("project config");
+
+    let project = fixture.project_arg();
+    assert_success(fixture.command(&["build", &project]));
+    let args = fs::read_to_string(&args_log).expect("pandoc args");
+    assert!(args.contains("--include-after-body"));
+    let index =
+        fs::read_to_string(fixture.project.join("build/smoke-search.json")).expect("search index");
+    let index: serde_json::Value = serde_json::from_str(&index).expect("search index JSON");
+    assert_eq!(index["documents"][0]["url"], "smoke.html#intro");
+    assert_eq!(index["documents"][0]["title"], "简介");
+    for bigram in ["构建", "建系", "系统"] {
+        assert_eq!(index["terms"][bigram], serde_json::json!([0]), "{bigram}");
+    }
+
+    assert_success(fixture.command(&[
+        "publish",
+        "--to",
+        "html",
+        "--no-build",
+        "--tag",
+        "search",
+        &project,
+    ]));
+    assert!(fixture
+        .project
+        .join("dist/search/smoke-search.json")
+        .is_file());
+
+    // The project setting overrides the theme and removes the stale index.
+    fs::write(
+        fixture.project.join(".omnidoc.toml"),
+        config("\n[pandoc]\nsearch = false\n"),
+    )
+    .expect("project config without search");
+    assert_success(fixture.command(&["build", &project]));
+    assert!(!fixture.project.join("build/smoke-search.json").exists());
+    let args = fs::read_to_string(&args_log).expect("pandoc args");
+    assert!(!args
+        .lines()
+        .last()
+        .expect("second build")
+        .contains("--include-after-body"));
+}
+
+#[test]
+#[cfg(unix)]
+fn reveal_slides_use_theme_tokens_and_keep_speaker_notes() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let fixture = Fixture::new("build-revealjs");
+    let library = Path::new(env!("CARGO_MANIFEST_DIR")).join("bundles/libs");
+    fs::write(
+        fixture.env_root.join("config/omnidoc.toml"),
+        format!("[lib]\npath = {:?}\n", library.to_string_lossy()),
+    )
+    .expect("global config");
+    let args_log = fixture.base().join("args.log");
+    let fake_pandoc = fixture.base().join("pandoc");
+    fs::write(
+        &fake_pandoc,
+        format!(
+            r##"#!/bin/sh
+out=''
+prev=''
+for arg in "$@"; do
+  case "$arg" in --version) echo 'pandoc 3.1.11'; exit 0 ;; esac
+  if [ "$prev" = '-o' ]; then out="$arg"; fi
+  prev="$arg"
+done
+echo "$@" >> '{log}'
+printf '<html><body><section><h2>Deck</h2></section></body></html>\n' > "$out"
+"##,
+            log = args_log.display()
+        ),
+    )
+    .expect("fake pandoc");
+    let mut permissions = fs::metadata(&fake_pandoc)
+        .expect("fake pandoc metadata")
+        .permissions();
+    permissions.set_mode(0o755);
+    fs::set_permissions(&fake_pandoc, permissions).expect("fake pandoc permissions");
+    fs::write(
+        fixture.project.join(".omnidoc.toml"),
+        format!(
+            r#"[project]
+entry = "main.md"
+from = "markdown"
+to = "revealjs"
+target = "talk"
+
+[build]
+outdir = "build"
+
+[theme]
+name = "modern-slides"
+
+[tools]
+pandoc = {:?}
+pandoc-crossref = {:?}
+"#,
+            fake_pandoc.to_string_lossy(),
+            fake_pandoc.to_string_lossy()
+        ),
+    )
+    .expect("project config");
+
+    let project = fixture.project_arg();
+    assert_success(fixture.command(&["build", &project]));
+    assert!(fixture.project.join("build/talk-slides.html").is_file());
+    let args = fs::read_to_string(&args_log).expect("pandoc args");
+    let deck = args.lines().last().expect("deck build");
+    assert!(deck.contains("-t revealjs"));
+    assert!(deck.contains("pandoc/css/modules/revealjs.css"));
+    assert!(deck.contains("tokens-revealjs.css"));
+    assert!(!deck.contains("modern-slides.css"));
+    assert!(!deck.contains("speaker-notes.lua"));
+    let tokens = fs::read_dir(fixture.project.join(".omnidoc-cache/themes"))
+        .expect("generated theme assets")
+        .map(|entry| {
+            entry
+                .expect("theme entry")
+                .path()
+                .join("tokens-revealjs.css")
+        })
+        .find(|path| path.is_file())
+        .expect("reveal token CSS");
+    assert!(fs::read_to_string(tokens)
+        .expect("reveal tokens")
+        .contains("--r-heading-color: #3451b2;"));
+
+    assert_success(fixture.command(&["build", "--to", "html", &project]));
+    let args = fs::read_to_string(&args_log).expect("pandoc args");
+    let page = args.lines().last().expect("page build");
+    assert!(page.contains("speaker-notes.lua"));
+    assert!(page.contains("modern-slides.css"));
+}
+
+#[cfg(unix)]
+#[test]
+fn readme_and_man_page_outputs_publish_next_to_each_other() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let fixture = Fixture::new("build-gfm-man");
+    let args_log = fixture.base().join("args.log");
+    let fake_pandoc = fixture.base().join("pandoc");
+    fs::write(
+        &fake_pandoc,
+        format!(
+            r##"#!/bin/sh
+out=''
+media=''
+prev=''
+for arg in "$@"; do
+  case "$arg" in
+    --version) echo 'pandoc 3.1.11'; exit 0 ;;
+    --extract-media=*) media="${{arg#--extract-media=}}" ;;
+  esac
+  if [ "$prev" = '-o' ]; then out="$arg"; fi
+  prev="$arg"
+done
+echo "$@" >> '{log}'
+if [ -n "$media" ]; then
+  mkdir -p "$media"
+  printf '<svg/>\n' > "$media/flow.svg"
+  printf '![Flow](%s/flow.svg)\n' "$media" > "$out"
+else
+  printf '.TH CLI 1\n' > "$out"
+fi
+"##,
+            log = args_log.display()
+        ),
+    )
+    .expect("fake pandoc");
+    let mut permissions = fs::metadata(&fake_pandoc)
+        .expect("fake pandoc metadata")
+        .permissions();
+    permissions.set_mode(0o755);
+    fs::set_permissions(&fake_pandoc, permissions).expect("fake pandoc permissions");
+    fs::write(
+        fixture.project.join(".omnidoc.toml"),
+        format!(
+            r#"[project]
+entry = "main.md"
+from = "markdown"
+to = "gfm"
+target = "cli"
+
+[build]
+outdir = "build"
+
+[tools]
+pandoc = {:?}
+pandoc-crossref = {:?}
+"#,
+            fake_pandoc.to_string_lossy(),
+            fake_pandoc.to_string_lossy()
+        ),
+    )
+    .expect("project config");
+
+    let project = fixture.project_arg();
+    assert_success(fixture.command(&["build", &project]));
+    assert_eq!(
+        fs::read_to_string(fixture.project.join("build/cli.md")).expect("README"),
+        "![Flow](cli-media/flow.svg)\n"
+    );
+    let args = fs::read_to_string(&args_log).expect("pandoc args");
+    let readme = args.lines().last().expect("README build");
+    assert!(readme.contains("-t gfm"));
+    assert!(readme.contains("include-files.lua"));
+    assert!(readme.contains("admonition.lua"));
+
+    assert_success(fixture.command(&[
+        "publish", "--output", "gfm", "--output", "man", "--tag", "docs", &project,
+    ]));
+    let args = fs::read_to_string(&args_log).expect("pandoc args");
+    let man = args.lines().last().expect("man build");
+    assert!(man.contains("-t man"));
+    assert!(!man.contains("--extract-media"));
+    let publish_dir = fixture.project.join("dist/docs");
+    assert!(publish_dir.join("cli.md").is_file());
+    assert!(publish_dir.join("cli-media/flow.svg").is_file());
+    assert!(publish_dir.join("cli.1").is_file());
+    let verified = assert_success(
+        fixture.command(&["publish", "--verify", "--json", "--tag", "docs", &project]),
+    );
+    let verified: serde_json::Value =
+        serde_json::from_str(&verified).expect("publish verification JSON");
+    assert_eq!(verified["valid"], true, "{verified}");
+}
+
+#[cfg(unix)]
+#[test]
+fn publisher_xml_outputs_extract_figures_and_report_structural_checks() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let fixture = Fixture::new("build-publisher-xml");
+    let library = Path::new(env!("CARGO_MANIFEST_DIR")).join("bundles/libs");
+    fs::write(
+        fixture.env_root.join("config/omnidoc.toml"),
+        format!("[lib]\npath = {:?}\n", library.to_string_lossy()),
+    )
+    .expect("global config");
+    let args_log = fixture.base().join("args.log");
+    let untitled = fixture.base().join("untitled");
+    let fake_pandoc = fixture.base().join("pandoc");
+    fs::write(
+        &fake_pandoc,
+        format!(
+            r##"#!/bin/sh
+out=''
+media=''
+to=''
+prev=''
+for arg in "$@"; do
+  case "$arg" in
+    --version) echo 'pandoc 3.1.11'; exit 0 ;;
+    --extract-media=*) media="${{arg#--extract-media=}}" ;;
+  esac
+  if [ "$prev" = '-o' ]; then out="$arg"; fi
+  if [ "$prev" = '-t' ]; then to="$arg"; fi
+  prev="$arg"
+done
+echo "$@" >> '{log}'
+mkdir -p "$media"
+printf '<svg/>\n' > "$media/flow.svg"
+title='Boards'
+[ -e '{untitled}' ] && title=''
+if [ "$to" = 'docbook5' ]; then
+  printf '<article xmlns="http://docbook.org/ns/docbook" version="5.0"><info><title>%s</title></info><mediaobject><imageobject><imagedata fileref="%s/flow.svg"/></imageobject></mediaobject></article>\n' "$title" "$media" > "$out"
+else
+  printf '<article xmlns:xlink="http://www.w3.org/1999/xlink"><front><article-meta><title-group><article-title>%s</article-title></title-group></article-meta></front><body><graphic xlink:href="%s/flow.svg"/></body></article>\n' "$title" "$media" > "$out"
+fi
+"##,
+            log = args_log.display(),
+            untitled = untitled.display()
+        ),
+    )
+    .expect("fake pandoc");
+    let mut permissions = fs::metadata(&fake_pandoc)
+        .expect("fake pandoc metadata")
+        .permissions();
+    permissions.set_mode(0o755);
+    fs::set_permissions(&fake_pandoc, permissions).expect("fake pandoc permissions");
+    fs::write(
+        fixture.project.join(".omnidoc.toml"),
+        format!(
+            r#"[project]
+entry = "main.md"
+from = "markdown"
+to = "jats"
+target = "paper"
+
+[build]
+outdir = "build"
+
+[tools]
+pandoc = {:?}
+pandoc-crossref = {:?}
+"#,
+            fake_pandoc.to_string_lossy(),
+            fake_pandoc.to_string_lossy()
+        ),
+    )
+    .expect("project config");
+
+    let project = fixture.project_arg();
+    assert_success(fixture.command(&["build", "--report", &project]));
+    let jats = fs::read_to_string(fixture.project.join("build/paper-jats.xml")).expect("JATS");
+    assert!(jats.contains(r#"xlink:href="paper-jats-media/flow.svg""#));
+    assert!(fixture
+        .project
+        .join("build/paper-jats-media/flow.svg")
+        .is_file());
+    let args = fs::read_to_string(&args_log).expect("pandoc args");
+    let build = args.lines().last().expect("JATS build");
+    assert!(build.contains("-t jats_archiving"));
+    assert!(build.contains("publisher-metadata.lua"));
+    assert!(!build.contains("--template"));
+    let report: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(fixture.project.join("build/omnidoc-report.json"))
+            .expect("build report"),
+    )
+    .expect("report JSON");
+    let validation = &report["reports"][0]["xml_validation"];
+    assert_eq!(validation["schema"], "jats-archiving");
+    assert_eq!(validation["valid"], true, "{validation}");
+
+    assert_success(fixture.command(&["build", "--to", "docbook", &project]));
+    let args = fs::read_to_string(&args_log).expect("pandoc args");
+    let build = args.lines().last().expect("DocBook build");
+    assert!(build.contains("-t docbook5"));
+    assert!(build.contains("omnidoc-docbook5.xml"));
+    assert!(
+        fs::read_to_string(fixture.project.join("build/paper-docbook.xml"))
+            .expect("DocBook")
+            .contains(r#"fileref="paper-docbook-media/flow.svg""#)
+    );
+
+    fs::write(&untitled, "").expect("untitled marker");
+    let failed = fixture.command(&["build", "--force", &project]);
+    assert!(!failed.status.success());
+    let stderr = String::from_utf8_lossy(&failed.stderr);
+    assert!(stderr.contains("XML validation failed for schema 'jats-archiving'"));
+    assert!(stderr.contains("article-title"));
+}
+
+/// A one-page PDF without an outline, as an engine would write for a
+/// document without headings.
+fn minimal_pdf() -> Vec<u8> {
+    let objects = [
+        "<< /Type /Catalog /Pages 2 0 R >>",
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+        "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 595 842] >>",
+        "<< /Producer (fake-typst) >>",
+    ];
+    let mut pdf = b"%PDF-1.7\n".to_vec();
+    let mut offsets = Vec::new();
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend(format!("{} 0 obj\n{}\nendobj\n", index + 1, object).as_bytes());
+    }
+    let xref = pdf.len();
+    pdf.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes());
+    for offset in offsets {
+        pdf.extend(format!("{offset:010} 00000 n \n").as_bytes());
+    }
+    pdf.extend(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R /Info 4 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+            objects.len() + 1
+        )
+        .as_bytes(),
+    );
+    pdf
+}
+
+#[test]
+fn pdf_outputs_carry_front_matter_metadata_and_enforce_pdf_a_profiles() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let fixture = Fixture::new("build-pdf-metadata");
+    let sample = fixture.base().join("sample.pdf");
+    fs::write(&sample, minimal_pdf()).expect("sample PDF");
+    let fake_pandoc = fixture.base().join("pandoc");
+    let fake_typst = fixture.base().join("typst");
+    fs::write(
+        &fake_pandoc,
+        format!(
+            r##"#!/bin/sh
+out=''
+prev=''
+for arg in "$@"; do
+  case "$arg" in
+    --version) echo 'pandoc 3.1.11'; exit 0 ;;
+  esac
+  if [ "$prev" = '-o' ]; then out="$arg"; fi
+  prev="$arg"
+done
+cp '{sample}' "$out"
+"##,
+            sample = sample.display()
+        ),
+    )
+    .expect("fake pandoc");
+    fs::write(&fake_typst, "#!/bin/sh\necho 'typst 0.12.0'\n").expect("fake typst");
+    for program in [&fake_pandoc, &fake_typst] {
+        let mut permissions = fs::metadata(program)
+            .expect("fake tool metadata")
+            .permissions();
+        permissions.set_mode(0o755);
+        fs::set_permissions(program, permissions).expect("fake tool permissions");
+    }
+    fs::write(
+        fixture.project.join("main.md"),
+        "---\ntitle: Power Stage Notes\nauthor:\n  - name: Li Wei\nkeywords: GaN, layout\n---\n\nBody text.\n",
+    )
+    .expect("front matter");
+    let config = format!(
+        r#"[project]
+entry = "main.md"
+from = "markdown"
+to = "pdf"
+target = "notes"
+
+[build]
+outdir = "build"
+
+[tools]
+pandoc = {:?}
+pandoc-crossref = {:?}
+latex_engine = {:?}
+"#,
+        fake_pandoc.to_string_lossy(),
+        fake_pandoc.to_string_lossy(),
+        fake_typst.to_string_lossy()
+    );
+    fs::write(fixture.project.join(".omnidoc.toml"), &config).expect("project config");
+
+    let project = fixture.project_arg();
+    let built = fixture.command(&["build", "--report", &project]);
+    assert!(built.status.success(), "{built:?}");
+    assert!(String::from_utf8_lossy(&built.stderr).contains("PDF check 'outline' failed"));
+    let report: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(fixture.project.join("build/omnidoc-report.json")).expect("report"),
+    )
+    .expect("report JSON");
+    let pdf = &report["reports"][0]["pdf"];
+    assert_eq!(pdf["metadata"]["title"], "Power Stage Notes");
+    assert_eq!(pdf["metadata"]["author"], "Li Wei");
+    assert_eq!(
+        pdf["metadata"]["keywords"],
+        serde_json::json!(["GaN", "layout"])
+    );
+    assert_eq!(pdf["outline_entries"], 0);
+    assert_eq!(pdf["valid"], false);
+    let stamped = fs::read(fixture.project.join("build/notes.pdf")).expect("stamped PDF");
+    let stamped = String::from_utf8_lossy(&stamped);
+    assert!(stamped.contains("<pdf:Producer>fake-typst</pdf:Producer>"));
+    assert!(!stamped.contains("pdfaid:part"));
+
+    fs::write(
+        fixture.project.join(".omnidoc.toml"),
+        format!("{config}\n[pdf]\nprofile = \"pdf-a-2b\"\n"),
+    )
+    .expect("PDF/A config");
+    let failed = fixture.command(&["build", &project]);
+    assert!(!failed.status.success());
+    let stderr = String::from_utf8_lossy(&failed.stderr);
+    assert!(stderr.contains("PDF validation failed for profile 'pdf-a-2b'"));
+    assert!(stderr.contains("- outline:"));
+    assert!(!stderr.contains("output-intent"));
+}
+
+#[test]
+fn unreadable_pdfs_only_warn_unless_a_pdf_profile_is_requested() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let fixture = Fixture::new("build-pdf-unreadable");
+    let sample = fixture.base().join("sample.pdf");
+    let original = "%PDF-1.7\nnot an object graph lopdf understands\n%%EOF\n";
+    fs::write(&sample, original).expect("sample PDF");
+    let fake_pandoc = fixture.base().join("pandoc");
+    let fake_typst = fixture.base().join("typst");
+    fs::write(
+        &fake_pandoc,
+        format!(
+            r##"#!/bin/sh
+out=''
+prev=''
+for arg in "$@"; do
+  case "$arg" in
+    --version) echo 'pandoc 3.1.11'; exit 0 ;;
+  esac
+  if [ "$prev" = '-o' ]; then out="$arg"; fi
+  prev="$arg"
+done
+cp '{sample}' "$out"
+"##,
+            sample = sample.display()
+        ),
+    )
+    .expect("fake pandoc");
+    fs::write(&fake_typst, "#!/bin/sh\necho 'typst 0.12.0'\n").expect("fake typst");
+    for program in [&fake_pandoc, &fake_typst] {
+        let mut permissions = fs::metadata(program)
+            .expect("fake tool metadata")
+            .permissions();
+        permissions.set_mode(0o755);
+        fs::set_permissions(program, permissions).expect("fake tool permissions");
+    }
+    fs::write(
+        fixture.project.join("main.md"),
+        "---\ntitle: Power Stage Notes\n---\n\nBody text.\n",
+    )
+    .expect("front matter");
+    let config = format!(
+        r#"[project]
+entry = "main.md"
+from = "markdown"
+to = "pdf"
+target = "notes"
+
+[build]
+outdir = "build"
+
+[tools]
+pandoc = {:?}
+pandoc-crossref = {:?}
+latex_engine = {:?}
+"#,
+        fake_pandoc.to_string_lossy(),
+        fake_pandoc.to_string_lossy(),
+        fake_typst.to_string_lossy()
+    );
+    fs::write(fixture.project.join(".omnidoc.toml"), &config).expect("project config");
+
+    let project = fixture.project_arg();
+    let built = fixture.command(&["build", "--report", &project]);
+    assert!(built.status.success(), "{built:?}");
+    assert!(String::from_utf8_lossy(&built.stderr).contains("PDF check 'readable' failed"));
+    assert_eq!(
+        fs::read_to_string(fixture.project.join("build/notes.pdf")).expect("PDF"),
+        original
+    );
+    let report: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(fixture.project.join("build/omnidoc-report.json")).expect("report"),
+    )
+    .expect("report JSON");
+    let pdf = &report["reports"][0]["pdf"];
+    assert_eq!(pdf["valid"], false);
+    assert_eq!(pdf["checks"][0]["name"], "readable");
+
+    fs::write(
+        fixture.project.join(".omnidoc.toml"),
+        format!("{config}\n[pdf]\nprofile = \"pdf-a-2b\"\n"),
+    )
+    .expect("PDF/A config");
+    let failed = fixture.command(&["build", &project]);
+    assert!(!failed.status.success());
+    assert!(String::from_utf8_lossy(&failed.stderr).contains("cannot read PDF"));
+}
+
+#[test]
+fn variant_watermarks_style_html_and_invalidate_the_build_cache() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let fixture = Fixture::new("build-watermark");
+    let args_log = fixture.base().join("args.log");
+    let fake_pandoc = fixture.base().join("pandoc");
+    fs::write(
+        &fake_pandoc,
+        format!(
+            r##"#!/bin/sh
+out=''
+prev=''
+for arg in "$@"; do
+  case "$arg" in
+    --version) echo 'pandoc 3.1.11'; exit 0 ;;
+  esac
+  if [ "$prev" = '-o' ]; then out="$arg"; fi
+  prev="$arg"
+done
+echo "$@" >> '{log}'
+printf '<html></html>\n' > "$out"
+"##,
+            log = args_log.display()
+        ),
+    )
+    .expect("fake pandoc");
+    let mut permissions = fs::metadata(&fake_pandoc)
+        .expect("fake pandoc metadata")
+        .permissions();
+    permissions.set_mode(0o755);
+    fs::set_permissions(&fake_pandoc, permissions).expect("fake pandoc permissions");
+    let config = |customer: &str| {
+        format!(
+            r##"[project]
+entry = "main.md"
+from = "markdown"
+to = "html"
+target = "guide"
+
+[build]
+outdir = "build"
+
+[watermark]
+text = "DRAFT"
+color = "#cc0000"
+
+[variants.customer.watermark]
+text = "CONFIDENTIAL – {customer}"
+pages = "first"
+
+[tools]
+pandoc = {:?}
+pandoc-crossref = {:?}
+"##,
+            fake_pandoc.to_string_lossy(),
+            fake_pandoc.to_string_lossy()
+        )
+    };
+    fs::write(fixture.project.join(".omnidoc.toml"), config("Customer X")).expect("config");
+    let watermark_css = || {
+        let args = fs::read_to_string(&args_log).expect("pandoc args");
+        let css = args
+            .lines()
+            .last()
+            .expect("pandoc call")
+            .split(' ')
+            .find(|arg| arg.ends_with("watermark.css"))
+            .expect("watermark stylesheet")
+            .to_string();
+        fs::read_to_string(css).expect("watermark CSS")
+    };
+    let report = || -> serde_json::Value {
+        serde_json::from_str(
+            &fs::read_to_string(fixture.project.join("build/omnidoc-report.json")).expect("report"),
+        )
+        .expect("report JSON")
+    };
+
+    let project = fixture.project_arg();
+    assert_success(fixture.command(&["build", "--report", &project]));
+    let css = watermark_css();
+    assert!(css.contains("content: \"DRAFT\""));
+    assert!(css.contains("color: #CC0000"));
+    assert!(css.contains("position: fixed"));
+
+    assert_success(fixture.command(&["build", "--variant", "customer", "--report", &project]));
+    let css = watermark_css();
+    assert!(css.contains("content: \"CONFIDENTIAL – Customer X\""));
+    assert!(css.contains("color: #CC0000"));
+    assert!(css.contains("position: absolute"));
+
+    fs::write(fixture.project.join(".omnidoc.toml"), config("Customer Y")).expect("config");
+    assert_success(fixture.command(&["build", "--variant", "customer", "--report", &project]));
+    assert_eq!(
+        report()["reports"][0]["cache_reason"],
+        "input_digest_changed"
+    );
+    assert!(watermark_css().contains("Customer Y"));
+}
+
+#[cfg(unix)]
+#[test]
+fn unchanged_included_chapters_reuse_cached_ast_fragments() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let fixture = Fixture::new("build-fragments");
+    let fake_pandoc = fixture.base().join("pandoc");
+    // Stands in for include-files.lua: every chapter is looked up in the
+    // fragment store under a key derived from the salt and its content.
+    fs::write(
+        &fake_pandoc,
+        r##"#!/bin/sh
+out=''
+prev=''
+dir=''
+salt=''
+stats=''
+for arg in "$@"; do
+  case "$arg" in
+    --version) echo 'pandoc 3.1.11'; exit 0 ;;
+    omnidoc-fragment-cache=*) dir="${arg#*=}" ;;
+    omnidoc-fragment-salt=*) salt="${arg#*=}" ;;
+    omnidoc-fragment-stats=*) stats="${arg#*=}" ;;
+  esac
+  if [ "$prev" = '-o' ]; then out="$arg"; fi
+  prev="$arg"
+done
+echo '# omnidoc-fragments-v1' > "$stats"
+for chapter in "$PWD"/chapters/*.md; do
+  key=$( (echo "$salt"; cat "$chapter") | cksum | cut -d' ' -f1)
+  if [ -f "$dir/$key.json" ]; then
+    echo "hit $chapter" >> "$stats"
+  else
+    echo '{}' > "$dir/$key.json"
+    echo "miss $chapter" >> "$stats"
+  fi
+done
+printf '<html></html>\n' > "$out"
+"##,
+    )
+    .expect("fake pandoc");
+    let mut permissions = fs::metadata(&fake_pandoc)
+        .expect("fake pandoc metadata")
+        .permissions();
+    permissions.set_mode(0o755);
+    fs::set_permissions(&fake_pandoc, permissions).expect("fake pandoc permissions");
+    fs::write(
+        fixture.project.join(".omnidoc.toml"),
+        format!(
+            r#"[project]
+entry = "main.md"
+from = "markdown"
+to = "html"
+target = "book"
+
+[build]
+outdir = "build"
+
+[tools]
+pandoc = {:?}
+pandoc-crossref = {:?}
+"#,
+            fake_pandoc.to_string_lossy(),
+            fake_pandoc.to_string_lossy()
+        ),
+    )
+    .expect("config");
+    fs::write(
+        fixture.project.join("main.md"),
+        "# Book\n\n```{.include}\nchapters/one.md\nchapters/two.md\n```\n",
+    )
+    .expect("entry");
+    fs::create_dir_all(fixture.project.join("chapters")).expect("chapters");
+    fs::write(fixture.project.join("chapters/one.md"), "# One\n").expect("chapter one");
+    fs::write(fixture.project.join("chapters/two.md"), "# Two\n").expect("chapter two");
+    let cache_details = || -> Vec<String> {
+        let report: serde_json::Value = serde_json::from_str(
+            &fs::read_to_string(fixture.project.join("build/omnidoc-report.json")).expect("report"),
+        )
+        .expect("report JSON");
+        report["reports"][0]["cache_details"]
+            .as_array()
+            .expect("cache details")
+            .iter()
+            .map(|detail| detail.as_str().expect("detail").to_string())
+            .collect()
+    };
+
+    let project = fixture.project_arg();
+    assert_success(fixture.command(&["build", "--report", &project]));
+    let details = cache_details();
+    assert!(details.contains(&"fragment_cache_hits:0/2".to_string()));
+    assert!(fixture.project.join(".omnidoc-cache/fragments").is_dir());
+
+    fs::write(fixture.project.join("chapters/two.md"), "# Two, revised\n").expect("edit");
+    assert_success(fixture.command(&["build", "--report", &project]));
+    let details = cache_details();
+    assert!(details.contains(&"dependency_changed:chapters/two.md".to_string()));
+    assert!(details.contains(&"fragment_cache_hits:1/2".to_string()));
+    assert!(details.contains(&"fragment_cache_hit_ratio:0.50".to_string()));
+    assert!(details.contains(&"fragment_reparsed:chapters/two.md".to_string()));
+    assert!(!details.contains(&"fragment_reparsed:chapters/one.md".to_string()));
+}
+
+#[cfg(unix)]
+#[test]
+fn shared_cache_restores_builds_in_another_checkout() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let producer = Fixture::new("shared-cache-producer");
+    let consumer = Fixture::new("shared-cache-consumer");
+    let shared = producer.base().join("shared-cache");
+    let pandoc_log = producer.base().join("pandoc.log");
+    let fake_pandoc = producer.base().join("pandoc");
+    fs::write(
+        &fake_pandoc,
+        format!(
+            r##"#!/bin/sh
+out=''
+prev=''
+for arg in "$@"; do
+  case "$arg" in
+    --version) echo 'pandoc 3.1.11'; exit 0 ;;
+    commonmark_x+sourcepos) echo '{{"blocks":[]}}'; exit 0 ;;
+  esac
+  if [ "$prev" = '-o' ]; then out="$arg"; fi
+  prev="$arg"
+done
+echo "$PWD" >> '{log}'
+printf '<html>shared</html>\n' > "$out"
+"##,
+            log = pandoc_log.display()
+        ),
+    )
+    .expect("fake pandoc");
+    let mut permissions = fs::metadata(&fake_pandoc)
+        .expect("fake pandoc metadata")
+        .permissions();
+    permissions.set_mode(0o755);
+    fs::set_permissions(&fake_pandoc, permissions).expect("fake pandoc permissions");
+    let config = format!(
+        r#"[project]
+entry = "main.md"
+from = "markdown"
+to = "html"
+target = "guide"
+
+[build]
+outdir = "build"
+
+[cache]
+shared = {:?}
+
+[tools]
+pandoc = {:?}
+pandoc-crossref = {:?}
+"#,
+        shared.to_string_lossy(),
+        fake_pandoc.to_string_lossy(),
+        fake_pandoc.to_string_lossy()
+    );
+    for fixture in [&producer, &consumer] {
+        fs::write(fixture.project.join(".omnidoc.toml"), &config).expect("config");
+        fs::remove_file(fixture.project.join("build/smoke.html")).expect("stale artifact");
+    }
+    let report = |fixture: &Fixture| -> serde_json::Value {
+        serde_json::from_str(
+            &fs::read_to_string(fixture.project.join("build/omnidoc-report.json")).expect("report"),
+        )
+        .expect("report JSON")
+    };
+
+    let producer_project = producer.project_arg();
+    assert_success(producer.command(&["build", "--report", &producer_project]));
+    let produced = report(&producer);
+    assert_eq!(produced["reports"][0]["cache_reason"], "artifact_missing");
+    assert!(produced["reports"][0]["cache_details"]
+        .as_array()
+        .expect("cache details")
+        .iter()
+        .any(|detail| detail == "shared_cache_miss"));
+
+    let consumer_project = consumer.project_arg();
+    assert_success(consumer.command(&["build", "--report", &consumer_project]));
+    let consumed = report(&consumer);
+    assert_eq!(consumed["reports"][0]["cache_reason"], "shared_cache_hit");
+    assert_eq!(consumed["reports"][0]["skipped"], true);
+    assert_eq!(
+        fs::read_to_string(consumer.project.join("build/guide.html")).expect("restored"),
+        "<html>shared</html>\n"
+    );
+    assert_eq!(
+        fs::read_to_string(&pandoc_log)
+            .expect("pandoc log")
+            .lines()
+            .count(),
+        1,
+        "the consumer must not run Pandoc"
+    );
+    // The restored state is recorded locally, so the next build is a plain hit.
+    assert_success(consumer.command(&["build", "--report", &consumer_project]));
+    assert_eq!(
+        report(&consumer)["reports"][0]["cache_reason"],
+        "input_digest_match"
+    );
+
+    let stats: serde_json::Value = serde_json::from_str(&assert_success(consumer.command(&[
+        "cache",
+        "stats",
+        "--json",
+        &consumer_project,
+    ])))
+    .expect("stats JSON");
+    assert_eq!(stats["shared"]["objects"]["files"], 1);
+    assert!(stats["shared"]["builds"].as_u64().expect("builds") >= 1);
+    let verified: serde_json::Value = serde_json::from_str(&assert_success(consumer.command(&[
+        "cache",
+        "verify",
+        "--json",
+        &consumer_project,
+    ])))
+    .expect("verify JSON");
+    assert_eq!(verified["valid"], true);
+    let pruned: serde_json::Value = serde_json::from_str(&assert_success(consumer.command(&[
+        "cache",
+        "prune",
+        "--older-than",
+        "0",
+        "--dry-run",
+        "--json",
+        &consumer_project,
+    ])))
+    .expect("prune JSON");
+    assert_eq!(pruned["objects"]["files"], 1);
+    assert!(shared.join("objects").is_dir());
+}
+
+#[cfg(unix)]
+#[test]
+fn daemon_serves_json_rpc_builds_with_progress_and_warm_config() {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::UnixStream;
+    use std::process::Stdio;
+
+    let fixture = Fixture::new("daemon");
+    let pandoc_log = fixture.base().join("pandoc.log");
+    let fake_pandoc = fixture.base().join("pandoc");
+    fs::write(
+        &fake_pandoc,
+        format!(
+            r##"#!/bin/sh
+out=''
+prev=''
+for arg in "$@"; do
+  case "$arg" in
+    --version) echo 'pandoc 3.1.11'; exit 0 ;;
+    commonmark_x+sourcepos) echo '{{"blocks":[]}}'; exit 0 ;;
+  esac
+  if [ "$prev" = '-o' ]; then out="$arg"; fi
+  prev="$arg"
+done
+echo run >> '{log}'
+printf '<html>daemon</html>\n' > "$out"
+"##,
+            log = pandoc_log.display()
+        ),
+    )
+    .expect("fake pandoc");
+    let mut permissions = fs::metadata(&fake_pandoc)
+        .expect("fake pandoc metadata")
+        .permissions();
+    permissions.set_mode(0o755);
+    fs::set_permissions(&fake_pandoc, permissions).expect("fake pandoc permissions");
+    fs::write(
+        fixture.project.join(".omnidoc.toml"),
+        format!(
+            r#"[project]
+entry = "main.md"
+from = "markdown"
+to = "html"
+target = "smoke"
+
+[build]
+outdir = "build"
+
+[tools]
+pandoc = {:?}
+pandoc-crossref = {:?}
+"#,
+            fake_pandoc.to_string_lossy(),
+            fake_pandoc.to_string_lossy()
+        ),
+    )
+    .expect("config");
+    fs::remove_file(fixture.project.join("build/smoke.html")).expect("stale artifact");
+
+    let socket = fixture.project.join(".omnidoc-cache/daemon.sock");
+    let mut daemon = fixture
+        .command_builder(&["daemon", &fixture.project_arg()])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("start daemon");
+    let stream = (0..200)
+        .find_map(|_| {
+            UnixStream::connect(&socket).ok().or_else(|| {
+                std::thread::sleep(std::time::Duration::from_millis(50));
+                None
+            })
+        })
+        .expect("daemon socket");
+    let mut writer = stream.try_clone().expect("socket writer");
+    let mut reader = BufReader::new(stream);
+    let mut notifications = Vec::new();
+    let mut call = |id: u64, method: &str, params: serde_json::Value| -> serde_json::Value {
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params,
+        });
+        writeln!(writer, "{request}").expect("send request");
+        loop {
+            let mut line = String::new();
+            assert!(reader.read_line(&mut line).expect("read response") > 0);
+            let message: serde_json::Value = serde_json::from_str(&line).expect("response JSON");
+            if message["id"] == id {
+                return message;
+            }
+            notifications.push(message);
+        }
+    };
+
+    let status = call(1, "status", serde_json::Value::Null);
+    assert_eq!(status["result"]["target"], "smoke");
+
+    let built = call(2, "build", serde_json::json!({ "outputs": ["html"] }));
+    assert_eq!(
+        built["result"]["reports"][0]["cache_reason"],
+        "artifact_missing"
+    );
+    assert_eq!(
+        fs::read_to_string(fixture.project.join("build/smoke.html")).expect("artifact"),
+        "<html>daemon</html>\n"
+    );
+    let rebuilt = call(3, "build", serde_json::json!({ "outputs": ["html"] }));
+    assert_eq!(rebuilt["result"]["reports"][0]["skipped"], true);
+
+    let lint = call(4, "lint", serde_json::json!({ "strict": true }));
+    assert!(lint["result"]["issues"].is_array());
+    let deps = call(5, "deps", serde_json::Value::Null);
+    assert!(deps["result"]["files"]
+        .as_array()
+        .expect("dependency files")
+        .iter()
+        .any(|file| file.as_str().is_some_and(|file| file.ends_with("main.md"))));
+    let unknown = call(6, "publish", serde_json::Value::Null);
+    assert_eq!(unknown["error"]["code"], -32601);
+
+    let stats = call(7, "stats", serde_json::Value::Null);
+    assert!(
+        stats["result"]["warm"]["configs"]["hits"]
+            .as_u64()
+            .expect("hits")
+            > 0
+    );
+    assert!(
+        stats["result"]["warm"]["tools"]["hits"]
+            .as_u64()
+            .expect("hits")
+            > 0
+    );
+
+    let shutdown = call(8, "shutdown", serde_json::Value::Null);
+    assert_eq!(shutdown["result"]["shutdown"], true);
+    assert!(daemon.wait().expect("daemon exit").success());
+    assert!(!socket.exists(), "the daemon removes its socket");
+
+    let states = notifications
+        .iter()
+        .filter(|message| {
+            message["method"] == "build/progress" && message["params"]["request"] == 2
+        })
+        .map(|message| message["params"]["state"].as_str().unwrap_or_default())
+        .collect::<Vec<_>>();
+    assert_eq!(states, ["started", "finished"]);
+    assert_eq!(
+        fs::read_to_string(&pandoc_log)
+            .expect("pandoc log")
+            .lines()
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn watch_serve_injects_live_reload_and_reports_failed_builds_as_events() {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpStream;
+    use std::process::Stdio;
+
+    let fixture = Fixture::new("watch-serve");
+    fs::write(
+        fixture.project.join(".omnidoc.toml"),
+        r#"[project]
+entry = "main.md"
+from = "markdown"
+to = "html"
+target = "smoke"
+
+[build]
+outdir = "build"
+
+[tools]
+pandoc = "/nonexistent/omnidoc-test/pandoc"
+"#,
+    )
+    .expect("config");
+
+    let mut watch = fixture
+        .command_builder(&["watch", "--serve", &fixture.project_arg()])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("start watch");
+    let stdout = BufReader::new(watch.stdout.take().expect("watch stdout"));
+    let mut lines = stdout.lines().map(|line| line.expect("watch output"));
+    let address = lines
+        .find_map(|line| {
+            line.strip_prefix("Preview server: http://")
+                .map(|address| address.trim_end_matches('/').to_string())
+        })
+        .expect("preview address");
+    assert!(lines
+        .next()
+        .is_some_and(|line| line.ends_with(&format!("html: http://{address}/smoke.html"))));
+
+    let mut page = TcpStream::connect(&address).expect("connect preview");
+    write!(page, "GET /smoke.html HTTP/1.1\r\nHost: localhost\r\n\r\n").expect("request page");
+    let mut response = String::new();
+    page.read_to_string(&mut response).expect("read page");
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    assert!(response.contains("<h1>Smoke</h1>"));
+    assert!(response.contains("new EventSource('/__omnidoc/events')"));
+
+    let events = TcpStream::connect(&address).expect("connect events");
+    write!(
+        events.try_clone().expect("events writer"),
+        "GET /__omnidoc/events HTTP/1.1\r\nHost: localhost\r\n\r\n"
+    )
+    .expect("request events");
+    let mut events = BufReader::new(events)
+        .lines()
+        .map(|line| line.expect("event"));
+    assert!(events.any(|line| line == "event: failure"));
+    let data = events.next().expect("failure data");
+    watch.kill().expect("stop watch");
+    let _ = watch.wait();
+    assert!(data.starts_with("data: {\"message\":\"error:"), "{data}");
+    assert!(data.contains("/nonexistent/omnidoc-test/pandoc"), "{data}");
+}
+
+#[cfg(unix)]
+#[test]
+fn locate_maps_markdown_lines_and_pdf_positions_through_synctex() {
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+    use std::os::unix::fs::PermissionsExt;
+
+    let fixture = Fixture::new("locate");
+    fs::write(
+        fixture.project.join("main.md"),
+        "# Smoke\n\nAlpha beta\ngamma delta\n",
+    )
+    .expect("main md");
+    let ast = fixture.base().join("main.json");
+    fs::write(
+        &ast,
+        r#"{"pandoc-api-version":[1,23,1],"meta":{},"blocks":[
+{"t":"Div","c":[["",[],[["data-pos","3:1-4:12"]]],[{"t":"Para","c":[
+{"t":"Span","c":[["",[],[["data-pos","3:1-3:6"]]],[{"t":"Str","c":"Alpha"}]]},{"t":"Space"},
+{"t":"Span","c":[["",[],[["data-pos","3:7-3:11"]]],[{"t":"Str","c":"beta"}]]},{"t":"SoftBreak"},
+{"t":"Span","c":[["",[],[["data-pos","4:1-4:6"]]],[{"t":"Str","c":"gamma"}]]},{"t":"Space"},
+{"t":"Span","c":[["",[],[["data-pos","4:7-4:12"]]],[{"t":"Str","c":"delta"}]]}]}]]}]}"#,
+    )
+    .expect("sourcepos AST");
+    let fake_pandoc = fixture.base().join("pandoc");
+    fs::write(
+        &fake_pandoc,
+        format!(
+            r#"#!/bin/sh
+for arg in "$@"; do last="$arg"; done
+case "$last" in
+  */main.md) cat '{ast}' ;;
+  *) echo '{{"pandoc-api-version":[1,23,1],"meta":{{}},"blocks":[]}}' ;;
+esac
+"#,
+            ast = ast.display()
+        ),
+    )
+    .expect("fake pandoc");
+    let mut permissions = fs::metadata(&fake_pandoc)
+        .expect("fake pandoc metadata")
+        .permissions();
+    permissions.set_mode(0o755);
+    fs::set_permissions(&fake_pandoc, permissions).expect("fake pandoc permissions");
+    fs::write(
+        fixture.project.join(".omnidoc.toml"),
+        format!(
+            r#"[project]
+entry = "main.md"
+target = "smoke"
+
+[build]
+outdir = "build"
+outputs = ["pdf"]
+
+[tools]
+pandoc = {:?}
+"#,
+            fake_pandoc.to_string_lossy()
+        ),
+    )
+    .expect("config");
+    fs::write(fixture.project.join("build/smoke.pdf"), "%PDF-1.5\n").expect("pdf");
+    let main = fixture.project.join("main.md").display().to_string();
+
+    let missing = fixture.command(&["locate", &format!("{main}:4")]);
+    assert!(!missing.status.success());
+    let stderr = String::from_utf8_lossy(&missing.stderr);
+    assert!(stderr.contains("No SyncTeX data"), "{stderr}");
+
+    let synctex_dir = fixture.project.join(".omnidoc-cache/outputs/pdf/synctex");
+    fs::create_dir_all(&synctex_dir).expect("synctex dir");
+    fs::write(
+        synctex_dir.join("input.tex"),
+        "\\begin{document}\nAlpha beta\ngamma delta\n\\end{document}\n",
+    )
+    .expect("intermediate tex");
+    let mut encoder = GzEncoder::new(
+        fs::File::create(synctex_dir.join("input.synctex.gz")).expect("synctex file"),
+        Compression::default(),
+    );
+    encoder
+        .write_all(
+            b"SyncTeX Version:1\nInput:1:/tmp/pandoc/input.tex\nOutput:pdf\nMagnification:1000\nUnit:1\nX Offset:0\nY Offset:0\nContent:\n{1\n[1,2:0,0:30000000,40000000,0\n(1,2:0,3000000:20000000,600000,200000\n)\n(1,3:0,6000000:20000000,600000,200000\n)\n]\n}1\nPostamble:\n",
+        )
+        .expect("synctex content");
+    encoder.finish().expect("synctex gzip");
+
+    let forward: serde_json::Value = serde_json::from_str(&assert_success(fixture.command(&[
+        "locate",
+        &format!("{main}:4"),
+        "--json",
+    ])))
+    .expect("forward JSON");
+    assert_eq!(forward["direction"], "forward");
+    assert_eq!(forward["source"]["file"], "main.md");
+    assert_eq!(forward["pdf"]["page"], 1);
+    assert_eq!(forward["pdf"]["x"], 72.0);
+    assert_eq!(forward["pdf"]["y"], 154.09);
+
+    let inverse = assert_success(fixture.command(&[
+        "locate",
+        "--pdf-page",
+        "1",
+        "--x",
+        "100",
+        "--y",
+        "160",
+        "--path",
+        &fixture.project_arg(),
+    ]));
+    assert_eq!(inverse.trim(), "main.md:4:1");
+}
+
+#[cfg(unix)]
+#[test]
+fn lsp_serves_diagnostics_completion_hover_and_definitions_over_stdio() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let fixture = Fixture::new("lsp");
+    fs::create_dir_all(fixture.project.join("chapters")).expect("chapters dir");
+    fs::write(
+        fixture.project.join("chapters/intro.md"),
+        "# Introduction {#sec:intro}\n\n![Plot](plot.png){#fig:plot}\n",
+    )
+    .expect("chapter");
+    fs::write(fixture.project.join("chapters/plot.png"), b"png").expect("plot");
+    fs::write(
+        fixture.project.join("refs.bib"),
+        "@book{knuth1984,\n  title = {TeX}\n}\n",
+    )
+    .expect("bibliography");
+    let main = "# Smoke\n\n![Missing](missing.png)\n\n```{.include}\nchapters/intro.md\n```\n\n::: {.admonition .warning}\nSee @\n:::\n";
+    fs::write(fixture.project.join("main.md"), main).expect("main md");
+    let main_uri = format!("file://{}", fixture.project.join("main.md").display());
+
+    let messages = [
+        serde_json::json!({"jsonrpc": "2.0", "id": 1, "method": "initialize",
+            "params": {"rootUri": format!("file://{}", fixture.project.display()), "capabilities": {}}}),
+        serde_json::json!({"jsonrpc": "2.0", "method": "initialized", "params": {}}),
+        serde_json::json!({"jsonrpc": "2.0", "method": "textDocument/didOpen",
+            "params": {"textDocument": {"uri": main_uri, "languageId": "markdown", "version": 1, "text": main}}}),
+        serde_json::json!({"jsonrpc": "2.0", "id": 2, "method": "textDocument/completion",
+            "params": {"textDocument": {"uri": main_uri}, "position": {"line": 9, "character": 5}}}),
+        serde_json::json!({"jsonrpc": "2.0", "id": 3, "method": "textDocument/hover",
+            "params": {"textDocument": {"uri": main_uri}, "position": {"line": 8, "character": 20}}}),
+        serde_json::json!({"jsonrpc": "2.0", "id": 4, "method": "textDocument/definition",
+            "params": {"textDocument": {"uri": main_uri}, "position": {"line": 5, "character": 3}}}),
+        serde_json::json!({"jsonrpc": "2.0", "id": 5, "method": "shutdown"}),
+        serde_json::json!({"jsonrpc": "2.0", "method": "exit"}),
+    ];
+    let mut input = Vec::new();
+    for message in &messages {
+        let body = message.to_string();
+        write!(input, "Content-Length: {}\r\n\r\n{body}", body.len()).expect("frame");
+    }
+
+    let mut server = fixture
+        .command_builder(&["lsp", "--stdio"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("start lsp");
+    server
+        .stdin
+        .take()
+        .expect("lsp stdin")
+        .write_all(&input)
+        .expect("write requests");
+    let output = server.wait_with_output().expect("lsp exit");
+    assert!(
+        output.status.success(),
+        "stderr:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8(output.stdout).expect("utf8 stdout");
+    let replies = stdout
+        .split("Content-Length: ")
+        .filter_map(|frame| frame.split_once("\r\n\r\n"))
+        .map(|(_, body)| serde_json::from_str::<serde_json::Value>(body).expect("json reply"))
+        .collect::<Vec<_>>();
+    let reply = |id: u64| {
+        replies
+            .iter()
+            .find(|reply| reply["id"] == id)
+            .unwrap_or_else(|| panic!("reply {id} in {replies:?}"))
+    };
+
+    assert_eq!(reply(1)["result"]["serverInfo"]["name"], "omnidoc");
+    let diagnostics = replies
+        .iter()
+        .find(|reply| {
+            reply["method"] == "textDocument/publishDiagnostics"
+                && reply["params"]["uri"] == main_uri.as_str()
+        })
+        .expect("main.md diagnostics");
+    let diagnostic = &diagnostics["params"]["diagnostics"][0];
+    assert_eq!(diagnostic["range"]["start"]["line"], 2);
+    assert!(diagnostic["message"]
+        .as_str()
+        .is_some_and(|message| message.contains("missing.png")));
+
+    let labels = reply(2)["result"]["items"]
+        .as_array()
+        .expect("completion items")
+        .iter()
+        .map(|item| item["label"].as_str().expect("label"))
+        .collect::<Vec<_>>();
+    assert_eq!(labels, ["fig:plot", "knuth1984", "sec:intro"]);
+
+    assert!(reply(3)["result"]["contents"]["value"]
+        .as_str()
+        .is_some_and(|value| value.contains(".admonition .warning")));
+    assert_eq!(
+        reply(4)["result"]["uri"],
+        format!(
+            "file://{}",
+            fixture.project.join("chapters/intro.md").display()
+        )
+    );
+    assert!(reply(5)["result"].is_null());
+}
+
+#[cfg(unix)]
+#[test]
+fn lint_enforces_project_terms_and_spell_checks_with_hunspell() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let fixture = Fixture::new("lint-terms");
+    let project = fixture.project_arg();
+    let args_log = fixture.base().join("hunspell-args.log");
+    let fake_hunspell = fixture.base().join("hunspell");
+    fs::write(
+        &fake_hunspell,
+        format!(
+            r#"#!/bin/sh
+echo "$@" > {:?}
+echo "@(#) International Ispell Version 3.2.06 (but really Hunspell 1.7.2)"
+while IFS= read -r line; do
+  case "$line" in *teh*) echo "& teh 2 8: the, ten" ;; esac
+  case "$line" in *Wi-Fi*) echo "& Wi 1 14: We" ;; esac
+  echo
+done
+"#,
+            args_log
+        ),
+    )
+    .expect("fake hunspell");
+    fs::set_permissions(&fake_hunspell, fs::Permissions::from_mode(0o755))
+        .expect("fake hunspell permissions");
+    fs::write(
+        fixture.project.join("main.md"),
+        "# Smoke\n\nWe use teh WiFi and Wi-Fi.\n\n```\nteh WiFi\n```\n",
+    )
+    .expect("main md");
+    fs::write(
+        fixture.project.join("terms.toml"),
+        "[[terms]]\npreferred = \"Wi-Fi\"\nforbidden = [\"WiFi\"]\n",
+    )
+    .expect("terms dictionary");
+    let config = fixture.project.join(".omnidoc.toml");
+    let mut content = fs::read_to_string(&config).expect("project config");
+    content.push_str(&format!(
+        "\n[terms]\nspellcheck = [\"en_US\", \"de_DE\"]\n\n[tools]\nhunspell = {:?}\n",
+        fake_hunspell
+    ));
+    fs::write(&config, &content).expect("terms config");
+
+    let lint = fixture.command(&["lint", &project]);
+    let stdout = String::from_utf8_lossy(&lint.stdout);
+    assert!(stdout.contains("Use 'Wi-Fi' instead of 'WiFi'"), "{stdout}");
+    assert!(
+        stdout.contains("Unknown word 'teh' (suggestions: the, ten)"),
+        "{stdout}"
+    );
+    assert!(!stdout.contains("Unknown word 'Wi'"), "{stdout}");
+    assert_eq!(stdout.matches("main.md:3").count(), 2, "{stdout}");
+    let args = fs::read_to_string(&args_log).expect("hunspell args");
+    assert!(args.contains("-a -i utf-8 -d en_US,de_DE"), "{args}");
+    assert_failure(fixture.command(&["lint", "--strict", &project]));
+
+    fs::write(
+        &config,
+        content.replace(
+            &format!("{:?}", fake_hunspell),
+            &format!("{:?}", fixture.base().join("missing-hunspell")),
+        ),
+    )
+    .expect("missing hunspell config");
+    let lint = fixture.command(&["lint", &project]);
+    let stdout = String::from_utf8_lossy(&lint.stdout);
+    assert!(stdout.contains("hunspell was not found"), "{stdout}");
+    assert!(stdout.contains("Use 'Wi-Fi' instead of 'WiFi'"), "{stdout}");
+
+    fs::write(
+        fixture.project.join("terms.toml"),
+        "[[terms]]\nforbidden = []\n",
+    )
+    .expect("invalid terms dictionary");
+    let failed = fixture.command(&["lint", &project]);
+    assert!(!failed.status.success());
+    let stdout = String::from_utf8_lossy(&failed.stdout);
+    assert!(stdout.contains("Invalid terms dictionary"), "{stdout}");
+    assert!(
+        stdout.contains("Terminology was not checked because terms.toml could not be loaded"),
+        "{stdout}"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn glossary_entries_reach_the_filter_and_lint_reports_terms() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let fixture = Fixture::new("build-glossary");
+    let args_log = fixture.base().join("args.log");
+    let fake_pandoc = fixture.base().join("pandoc");
+    fs::write(
+        &fake_pandoc,
+        format!(
+            r#"#!/bin/sh
+out=''
+prev=''
+for arg in "$@"; do
+  case "$arg" in
+    --version) echo 'pandoc 3.1.11'; exit 0 ;;
+    commonmark_x+sourcepos) echo '{{"blocks":[]}}'; exit 0 ;;
+  esac
+  if [ "$prev" = '-o' ]; then out="$arg"; fi
+  prev="$arg"
+done
+echo "$@" >> '{log}'
+printf 'artifact\n' > "$out"
+"#,
+            log = args_log.display()
+        ),
+    )
+    .expect("fake pandoc");
+    fs::set_permissions(&fake_pandoc, fs::Permissions::from_mode(0o755))
+        .expect("fake pandoc permissions");
+    let config = fixture.project.join(".omnidoc.toml");
+    let mut content = fs::read_to_string(&config).expect("project config");
+    content.push_str(&format!(
+        "\n[glossary]\ntitle = \"Terms\"\n\n[tools]\npandoc = {:?}\npandoc-crossref = {:?}\n",
+        fake_pandoc, fake_pandoc
+    ));
+    fs::write(&config, content).expect("glossary config");
+    fs::write(
+        fixture.project.join("glossary.yaml"),
+        "ADC: analog-to-digital converter\nSoC:\n  long: system on chip\n",
+    )
+    .expect("glossary");
+    fs::write(
+        fixture.project.join("main.md"),
+        "# Smoke\n\nSample with an [+ADC], then [the converter]{.gls key=ADC} and [+DAC].\n",
+    )
+    .expect("main md");
+
+    let project = fixture.project_arg();
+    assert_success(fixture.command(&["build", &project, "--force"]));
+    let args = fs::read_to_string(&args_log).expect("pandoc args");
+    assert!(args.contains("glossary.lua"), "{args}");
+    let metadata_file = args
+        .split_whitespace()
+        .skip_while(|arg| *arg != "--metadata-file")
+        .nth(1)
+        .expect("glossary metadata file");
+    let metadata: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(metadata_file).expect("glossary metadata"))
+            .expect("glossary metadata JSON");
+    assert_eq!(
+        metadata["omnidoc-glossary"]["ADC"]["long"],
+        "analog-to-digital converter"
+    );
+    assert_eq!(metadata["omnidoc-glossary"]["SoC"]["short"], "SoC");
+    assert_eq!(metadata["omnidoc-glossary-title"], "Terms");
+
+    // Glossary files are build inputs, so editing one rebuilds the output.
+    fs::write(
+        fixture.project.join("glossary.yaml"),
+        "ADC: analog/digital converter\nSoC:\n  long: system on chip\n",
+    )
+    .expect("edited glossary");
+    assert_success(fixture.command(&["build", &project]));
+    assert_eq!(
+        fs::read_to_string(&args_log)
+            .expect("pandoc args")
+            .lines()
+            .count(),
+        2
+    );
+
+    let lint = fixture.command(&["lint", &project]);
+    let stdout = String::from_utf8_lossy(&lint.stdout);
+    assert!(stdout.contains("Undefined glossary term 'DAC'"), "{stdout}");
+    assert!(
+        stdout.contains("Glossary term 'SoC' is never referenced"),
+        "{stdout}"
+    );
+    assert!(!stdout.contains("'ADC'"), "{stdout}");
+    assert_failure(fixture.command(&["lint", "--strict", &project]));
+
+    fs::write(
+        fixture.project.join("glossary.yaml"),
+        "ADC:\n  short: ADC\n",
+    )
+    .expect("invalid glossary");
+    let failed = fixture.command(&["build", &project]);
+    assert!(!failed.status.success());
+    assert!(
+        String::from_utf8_lossy(&failed.stdout).contains("Invalid glossary file"),
+        "{}",
+        String::from_utf8_lossy(&failed.stdout)
+    );
+}
+
+#[test]
+fn indexed_pdf_builds_sort_the_index_between_latex_passes() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let fixture = Fixture::new("build-index");
+    let sample = fixture.base().join("sample.pdf");
+    fs::write(&sample, minimal_pdf()).expect("sample PDF");
+    let args_log = fixture.base().join("args.log");
+    let engine_log = fixture.base().join("engine.log");
+    let index_log = fixture.base().join("index.log");
+    let tools = fixture.base().join("tools");
+    fs::create_dir_all(&tools).expect("tools dir");
+    let fake_pandoc = tools.join("pandoc");
+    let fake_engine = tools.join("xelatex");
+    let fake_indexer = tools.join("zhmakeindex");
+    // Pandoc compiles in a scratch directory through the configured engine,
+    // which is OmniDoc's recorder wrapper here.
+    fs::write(
+        &fake_pandoc,
+        format!(
+            r#"#!/bin/sh
+out=''
+engine=''
+prev=''
+for arg in "$@"; do
+  case "$arg" in --version) echo 'pandoc 3.1.11'; exit 0 ;; esac
+  if [ "$prev" = '-o' ]; then out="$arg"; fi
+  if [ "$prev" = '--pdf-engine' ]; then engine="$arg"; fi
+  prev="$arg"
+done
+echo "$@" >> '{args}'
+work='{base}/tex2pdf'
+mkdir -p "$work"
+printf '\\printindex\n' > "$work/input.tex"
+"$engine" -output-directory "$work" "$work/input.tex" || exit 1
+cp '{sample}' "$out"
+"#,
+            args = args_log.display(),
+            base = fixture.base().display(),
+            sample = sample.display()
+        ),
+    )
+    .expect("fake pandoc");
+    fs::write(
+        &fake_engine,
+        format!(
+            r#"#!/bin/sh
+[ "$1" = '-output-directory' ] || exit 0
+for arg in "$@"; do work="$arg"; done
+work=$(dirname "$work")
+if [ -f "$work/input.ind" ]; then echo 'run with index' >> '{log}'; else echo 'run' >> '{log}'; fi
+printf 'PWD %s\nINPUT %s/input.tex\n' "$work" "$work" > "$work/input.fls"
+printf '\\indexentry{{zhongduan@中断}}{{1}}\n' > "$work/input.idx"
+"#,
+            log = engine_log.display()
+        ),
+    )
+    .expect("fake engine");
+    fs::write(
+        &fake_indexer,
+        format!(
+            "#!/bin/sh\necho \"$@\" >> '{log}'\ncp \"$6\" \"$5\"\n",
+            log = index_log.display()
+        ),
+    )
+    .expect("fake indexer");
+    for program in [&fake_pandoc, &fake_engine, &fake_indexer] {
+        fs::set_permissions(program, fs::Permissions::from_mode(0o755))
+            .expect("fake tool permissions");
+    }
+    fs::write(
+        fixture.project.join(".omnidoc.toml"),
+        format!(
+            r#"[project]
+entry = "main.md"
+from = "markdown"
+to = "pdf"
+target = "indexed"
+
+[build]
+outdir = "build"
+
+[index]
+title = "Index of Terms"
+
+[tools]
+pandoc = {:?}
+pandoc-crossref = {:?}
+latex_engine = {:?}
+zhmakeindex = {:?}
+"#,
+            fake_pandoc, fake_pandoc, fake_engine, fake_indexer
+        ),
+    )
+    .expect("project config");
+    fs::write(
+        fixture.project.join("main.md"),
+        concat!(
+            "---\nlang: zh-CN\n---\n\n# 中断\n\n",
+            "[中断]{.idx sort=zhongduan}由 [Make file]{.idx} 配置，",
+            "[makefile]{.idx} 另见 [DMA]{.idx term=\"DMA!双缓冲\"}。\n",
+        ),
+    )
+    .expect("main md");
+
+    let project = fixture.project_arg();
+    assert_success(fixture.command(&["build", &project, "--force"]));
+    let args = fs::read_to_string(&args_log).expect("pandoc args");
+    assert!(args.contains("index.lua"), "{args}");
+    assert!(
+        args.contains("omnidoc-index-title=Index of Terms"),
+        "{args}"
+    );
+    // Chinese documents sort by pinyin, and the sorted index triggers one
+    // more engine pass so `\printindex` picks it up.
+    let index = fs::read_to_string(&index_log).expect("indexer log");
+    assert!(index.starts_with("-q -z pinyin -o "), "{index}");
+    assert_eq!(index.lines().count(), 1, "{index}");
+    assert_eq!(
+        fs::read_to_string(&engine_log).expect("engine log"),
+        "run\nrun with index\n"
+    );
+
+    let lint = fixture.command(&["lint", &project]);
+    let stdout = String::from_utf8_lossy(&lint.stdout);
+    assert!(
+        stdout.contains("Index term 'makefile' is spelled 'Make file' at main.md:7"),
+        "{stdout}"
+    );
+    assert!(!stdout.contains("'DMA'"), "{stdout}");
+
+    fs::write(
+        fixture.project.join(".omnidoc.toml"),
+        fs::read_to_string(fixture.project.join(".omnidoc.toml"))
+            .expect("project config")
+            .replace("title = \"Index of Terms\"", "program = \"mendex\""),
+    )
+    .expect("invalid index program");
+    let failed = fixture.command(&["lint", &project]);
+    assert!(
+        String::from_utf8_lossy(&failed.stdout).contains("Unsupported index.program 'mendex'"),
+        "{}",
+        String::from_utf8_lossy(&failed.stdout)
+    );
+}
+
+#[test]
+fn check_links_reports_broken_urls_and_caches_results() {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    // A local stand-in for the web: each path answers differently.
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+    let base = format!("http://{}", listener.local_addr().expect("address"));
+    let requests = Arc::new(Mutex::new(Vec::<String>::new()));
+    let log = Arc::clone(&requests);
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { break };
+            let log = Arc::clone(&log);
+            std::thread::spawn(move || {
+                let mut reader = BufReader::new(stream.try_clone().expect("clone"));
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).expect("request line");
+                loop {
+                    let mut header = String::new();
+                    if reader.read_line(&mut header).unwrap_or(0) == 0 || header.trim().is_empty() {
+                        break;
+                    }
+                }
+                let mut parts = request_line.split_whitespace();
+                let method = parts.next().unwrap_or_default().to_string();
+                let path = parts.next().unwrap_or_default().to_string();
+                log.lock()
+                    .expect("request log")
+                    .push(format!("{method} {path}"));
+                let status = match (method.as_str(), path.as_str()) {
+                    (_, "/ok") => "200 OK",
+                    ("HEAD", "/get-only") => "405 Method Not Allowed",
+                    ("GET", "/get-only") => "200 OK",
+                    (_, "/slow") => {
+                        std::thread::sleep(std::time::Duration::from_secs(3));
+                        "200 OK"
+                    }
+                    (_, "/flaky") => "503 Service Unavailable",
+                    _ => "404 Not Found",
+                };
+                let _ = stream.write_all(
+                    format!("HTTP/1.1 {status}\r\nconnection: close\r\ncontent-length: 0\r\n\r\n")
+                        .as_bytes(),
+                );
+            });
+        }
+    });
+
+    let fixture = Fixture::new("check-links");
+    let config = fixture.project.join(".omnidoc.toml");
+    let mut content = fs::read_to_string(&config).expect("project config");
+    content.push_str(
+        "\n[links]\ntimeout = 1\nrate_limit = 100\nallow = [\"/flaky$\"]\nignore = [\"^https://ignored\\\\.example/\"]\n",
+    );
+    fs::write(&config, content).expect("links config");
+    fs::write(
+        fixture.project.join("main.md"),
+        format!(
+            "# Links\n\n[Home]({base}/ok), [gone]({base}/missing) and {base}/get-only.\n\n\
+             Slow: <{base}/slow>, flaky: {base}/flaky, skipped: https://ignored.example/x.\n\n\
+             `{base}/in-code`\n"
+        ),
+    )
+    .expect("main md");
+    fs::write(
+        fixture.project.join("refs.bib"),
+        format!("@misc{{home,\n  url = {{{base}/ok}},\n}}\n"),
+    )
+    .expect("bibliography");
+
+    let project = fixture.project_arg();
+    let output = fixture.command(&["check", "links", "--json", &project]);
+    assert!(!output.status.success(), "{output:?}");
+    let results: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("link results JSON");
+    let state = |suffix: &str| {
+        results
+            .as_array()
+            .expect("results")
+            .iter()
+            .find(|result| {
+                result["url"]
+                    .as_str()
+                    .is_some_and(|url| url.ends_with(suffix))
+            })
+            .unwrap_or_else(|| panic!("no result for {suffix}: {results}"))
+            .clone()
+    };
+    assert_eq!(state("/ok")["state"], "ok");
+    assert_eq!(state("/ok")["locations"].as_array().map(Vec::len), Some(2));
+    assert_eq!(state("/missing")["state"], "broken");
+    assert_eq!(state("/missing")["status"], 404);
+    assert_eq!(state("/missing")["locations"][0]["line"], 3);
+    assert_eq!(state("/get-only")["state"], "ok");
+    assert_eq!(state("/slow")["state"], "error");
+    assert_eq!(state("/slow")["error"], "request timed out");
+    assert_eq!(state("/flaky")["state"], "allowed");
+    assert_eq!(state("ignored.example/x")["state"], "ignored");
+    assert_eq!(results.as_array().map(Vec::len), Some(6));
+    let first_run = requests.lock().expect("request log").len();
+    assert!(
+        !requests
+            .lock()
+            .expect("request log")
+            .iter()
+            .any(|request| request.ends_with("/in-code")),
+        "{requests:?}"
+    );
+
+    // Cached results answer the second run without touching the network.
+    let output = fixture.command(&["check", "links", &project]);
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains(&format!(
+            "main.md:3: error: Broken link {base}/missing (HTTP 404 Not Found)"
+        )),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("6 link(s): 2 ok, 2 broken, 1 allowed, 1 ignored"),
+        "{stdout}"
+    );
+    assert_eq!(requests.lock().expect("request log").len(), first_run);
+    assert!(fixture.project.join(".omnidoc-cache/links.json").is_file());
+
+    let output = fixture.command(&["check", "links", "--no-cache", "--json", &project]);
+    assert!(!output.status.success());
+    assert!(requests.lock().expect("request log").len() > first_run);
+}
+
+#[cfg(unix)]
+#[test]
+fn lint_reads_the_pandoc_ast_with_rule_levels_and_suppressions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let fixture = Fixture::new("lint-ast");
+    let parse_log = fixture.base().join("parse.log");
+    let ast = fixture.base().join("main.json");
+    let fake_pandoc = fixture.base().join("fake-pandoc");
+    // What `pandoc -f commonmark_x+sourcepos -t json main.md` reads: an
+    // include on a heading, a reference-style link spanning two lines and
+    // two images.
+    fs::write(
+        &ast,
+        serde_json::json!({
+            "pandoc-api-version": [1, 23, 1],
+            "meta": {},
+            "blocks": [
+                {"t": "Header", "c": [1, ["setup", [], [
+                    ["include", "parts/setup.md"], ["data-pos", "main.md@1:1-2:1"]
+                ]], []]},
+                {"t": "Div", "c": [["", [], [["wrapper", "1"], ["data-pos", "main.md@3:1-4:45"]]], [
+                    {"t": "Para", "c": [
+                        {"t": "Link", "c": [["", [], [["data-pos", "main.md@3:9-4:14"]]], [], ["notes/missing.md", ""]]},
+                        {"t": "Image", "c": [["", [], [["data-pos", "main.md@4:20-4:44"]]], [], ["img/flow.png", ""]]}
+                    ]}
+                ]]},
+                {"t": "Div", "c": [["", [], [["wrapper", "1"], ["data-pos", "main.md@7:1-7:25"]]], [
+                    {"t": "Para", "c": [
+                        {"t": "Image", "c": [["", [], [["data-pos", "main.md@7:1-7:25"]]], [], ["img/skip.png", ""]]}
+                    ]}
+                ]]}
+            ]
+        })
+        .to_string(),
+    )
+    .expect("canned AST");
+    fs::write(
+        &fake_pandoc,
+        format!(
+            r#"#!/bin/sh
+case "$*" in
+  *--version*) echo 'pandoc 3.1.11'; exit 0 ;;
+  *commonmark_x+sourcepos*) echo "$@" >> '{log}'; cat '{ast}'; exit 0 ;;
+esac
+exit 1
+"#,
+            log = parse_log.display(),
+            ast = ast.display()
+        ),
+    )
+    .expect("fake pandoc");
+    fs::set_permissions(&fake_pandoc, fs::Permissions::from_mode(0o755))
+        .expect("fake pandoc permissions");
+    fs::write(
+        fixture.project.join(".omnidoc.toml"),
+        format!(
+            r#"[project]
+entry = "main.md"
+from = "markdown"
+to = "html"
+
+[lint.rules]
+missing_image = "error"
+missing_include = "info"
+
+[tools]
+pandoc = {:?}
+"#,
+            fake_pandoc
+        ),
+    )
+    .expect("project config");
+    fs::write(
+        fixture.project.join("main.md"),
+        concat!(
+            "# Setup {include=\"parts/setup.md\"}\n\n",
+            "See the [release\nnotes][notes] and ![a flow diagram](img/flow.png).\n\n",
+            "<!-- omnidoc-lint-disable-next-line missing_image -->\n",
+            "![skipped](img/skip.png)\n\n",
+            "[notes]: notes/missing.md\n",
+        ),
+    )
+    .expect("main md");
+
+    let project = fixture.project_arg();
+    let stdout = assert_failure(fixture.command(&["check", "lint", &project]));
+    for expected in [
+        "main.md:1:1: info: Referenced local resource not found: parts/setup.md [missing_include]",
+        "main.md:3:9: warning: Referenced local resource not found: notes/missing.md [missing_link_target]",
+        "main.md:4:20: error: Referenced local resource not found: img/flow.png [missing_image]",
+    ] {
+        assert!(stdout.contains(expected), "missing {expected:?} in:\n{stdout}");
+    }
+    assert!(!stdout.contains("img/skip.png"), "{stdout}");
+
+    // Unchanged files reuse the cached references instead of parsing again.
+    assert_failure(fixture.command(&["check", "lint", &project]));
+    let parses = fs::read_to_string(&parse_log).expect("parse log");
+    assert_eq!(parses.lines().count(), 1, "{parses}");
+
+    fs::write(
+        fixture.project.join(".omnidoc.toml"),
+        fs::read_to_string(fixture.project.join(".omnidoc.toml"))
+            .expect("project config")
+            .replace("missing_image = \"error\"", "missing_images = \"off\""),
+    )
+    .expect("unknown rule");
+    let stdout = assert_failure(fixture.command(&["check", "lint", &project]));
+    assert!(
+        stdout.contains("Unknown lint rule 'missing_images' in [lint.rules]"),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn check_lint_reports_unresolved_and_duplicate_cross_references() {
+    let fixture = Fixture::new("lint-crossrefs");
+    let project = fixture.project_arg();
+    fs::write(
+        fixture.project.join("main.md"),
+        concat!(
+            "# Smoke\n\n",
+            "## Setup {#sec:setup}\n\n",
+            "## Setup again {#sec:setup}\n\n",
+            "See @sec:setup and @fig:missing.\n",
+        ),
+    )
+    .expect("main md");
+
+    let stdout = assert_success(fixture.command(&["check", "lint", &project]));
+    for expected in [
+        "main.md:7: warning: Unresolved cross-reference '@fig:missing'",
+        "main.md:5: warning: Duplicate cross-reference label '#sec:setup' (first declared at main.md:3)",
+    ] {
+        assert!(stdout.contains(expected), "missing {expected:?} in:\n{stdout}");
+    }
+    let stdout = assert_failure(fixture.command(&["check", "lint", "--strict", &project]));
+    assert!(stdout.contains("@fig:missing"), "{stdout}");
+    let stdout = assert_failure(fixture.command(&["build", "--strict", &project]));
+    assert!(stdout.contains("@fig:missing"), "{stdout}");
+}
+
+#[test]
+fn check_lint_strict_fails_on_undefined_citation_keys() {
+    let fixture = Fixture::new("lint-citations");
+    let project = fixture.project_arg();
+    fs::write(
+        fixture.project.join("main.md"),
+        concat!(
+            "---\n",
+            "bibliography: refs.bib\n",
+            "---\n\n",
+            "# Smoke\n\n",
+            "As shown by @knuth1984 and @missing2020.\n",
+        ),
+    )
+    .expect("main md");
+    fs::write(
+        fixture.project.join("refs.bib"),
+        concat!(
+            "@book{knuth1984,\n  author = {Knuth},\n  title = {Literate Programming},\n  publisher = {CSLI},\n  year = 1992\n}\n\n",
+            "@book{boyd2004,\n  author = {Boyd},\n  title = {Convex Optimization},\n  publisher = {Cambridge},\n  year = 2004\n}\n",
+        ),
+    )
+    .expect("bibliography");
+
+    let stdout = assert_success(fixture.command(&["check", "lint", &project]));
+    for expected in [
+        "main.md:7: warning: Undefined citation key '@missing2020'",
+        "info: Bibliography entry 'boyd2004' is never cited",
+    ] {
+        assert!(
+            stdout.contains(expected),
+            "missing {expected:?} in:\n{stdout}"
+        );
+    }
+    assert!(!stdout.contains("knuth1984"), "{stdout}");
+    let stdout = assert_failure(fixture.command(&["check", "lint", "--strict", &project]));
+    assert!(stdout.contains("@missing2020"), "{stdout}");
+}